@@ -0,0 +1,67 @@
+//! Times recombination executors on large populations. Run with
+//! `cargo bench --bench recombination`.
+
+use std::time::{Duration, Instant};
+
+use moga::{
+  experimental::executor::RecombinationExecutor,
+  operator::ParEach,
+  recombination::ByValue,
+};
+use rand::{rngs::SmallRng, seq::SliceRandom, SeedableRng};
+
+/// Returns the best of several timings of `f`.
+fn time<T>(mut f: impl FnMut() -> T) -> Duration {
+  (0..5)
+    .map(|_| {
+      let start = Instant::now();
+      std::hint::black_box(f());
+      start.elapsed()
+    })
+    .min()
+    .unwrap()
+}
+
+/// Recombinations of a single parent are applied to each parent directly,
+/// sequentially and in parallel.
+fn single_parent_recombination() {
+  let parents: Vec<f64> = (0..1_000_000).map(|i| i as f64).collect();
+  let parents: Vec<_> = parents.iter().collect();
+  let r = |a: &f64| a.sqrt();
+
+  let sequential = time(|| r.execute_recombination(parents.clone()));
+  let parallel = time(|| r.par_each().execute_recombination(parents.clone()));
+  println!(
+    "single parent recombination of {} parents: sequential {sequential:?}, \
+     parallel {parallel:?}",
+    parents.len()
+  );
+}
+
+/// Parents are recombined by reference and by value. The population is of
+/// Schaffer's problem, so cheap to recombine that recombination is dominated
+/// by access to parents.
+fn by_value_recombination() {
+  let solutions: Vec<f32> =
+    (0..4_000_000).map(|i| (i as f32).sin() * 1e3).collect();
+  // parents scattered all over the population, like a selector picks them
+  let mut selected: Vec<usize> = (0..solutions.len()).step_by(400).collect();
+  selected.shuffle(&mut SmallRng::seed_from_u64(0));
+  let by_reference = |a: &f32, b: &f32| (a + b) / 2.0;
+  let by_value = ByValue(|[a, b]: [f32; 2]| [(a + b) / 2.0]);
+
+  let referenced =
+    time(|| by_reference.execute_selected_recombination(&solutions, &selected));
+  let copied =
+    time(|| by_value.execute_selected_recombination(&solutions, &selected));
+  println!(
+    "recombination of {} parents: by reference {referenced:?}, by value \
+     {copied:?}",
+    selected.len()
+  );
+}
+
+fn main() {
+  single_parent_recombination();
+  by_value_recombination();
+}
@@ -0,0 +1,55 @@
+//! Times generations of `Nsga2` on a large population, which are dominated by
+//! sorting solutions into fronts. Run with `cargo bench --bench sorting`, and
+//! with `--features score-matrix` to compare storages of scores.
+
+use std::time::{Duration, Instant};
+
+use moga::{
+  optimizer::{nsga::Nsga2, Optimizer},
+  recombination::CloneParents,
+  selection::AllSelector,
+  termination::GenerationTerminator,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// Returns the best of several timings of `f`.
+fn time<T>(mut f: impl FnMut() -> T) -> Duration {
+  (0..5)
+    .map(|_| {
+      let start = Instant::now();
+      std::hint::black_box(f());
+      start.elapsed()
+    })
+    .min()
+    .unwrap()
+}
+
+fn main() {
+  let mut rng = StdRng::seed_from_u64(0);
+  // solutions are their own scores, so testing is free
+  let population: Vec<[f32; 3]> = (0..25_000)
+    .map(|_| std::array::from_fn(|_| rng.gen_range(-4.0..4.0)))
+    .collect();
+  let generations = 2;
+
+  let elapsed = time(|| {
+    Nsga2::builder()
+      .population(population.clone())
+      .tester(|x: &[f32; 3]| *x)
+      .selector(AllSelector())
+      .recombinator(CloneParents)
+      .mutator(|_: &mut [f32; 3]| {})
+      .terminator(GenerationTerminator(generations))
+      .build()
+      .optimize()
+  });
+  println!(
+    "{generations} generations of {} solutions: {elapsed:?}, score matrix {}",
+    population.len(),
+    if cfg!(feature = "score-matrix") {
+      "enabled"
+    } else {
+      "disabled"
+    }
+  );
+}
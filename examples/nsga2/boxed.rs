@@ -0,0 +1,74 @@
+//! Overhead of boxed operators on Schaffer's Problem No.1 solved with NSGA-II.
+//!
+//! Run with `--release` to get meaningful numbers.
+
+use std::time::{Duration, Instant};
+
+use moga::{
+  mutation::BoxedMutator,
+  optimizer::{nsga::Nsga2, Optimizer},
+  recombination::BoxedRecombinator,
+  selection::{BoxedSelector, RandomSelector},
+  termination::{BoxedTerminator, GenerationTerminator},
+  testing::BoxedTester,
+};
+use rand::Rng;
+
+const RUNS: u32 = 20;
+const GENERATIONS: usize = 500;
+
+fn test(x: &f32) -> [f32; 2] {
+  [x.powf(2.0), (x - 2.0).powf(2.0)]
+}
+
+fn recombination(x: &f32, y: &f32) -> f32 {
+  x + rand::thread_rng().gen_range(-1.0..2.0) * (y - x)
+}
+
+fn mutation(_: &mut f32) {}
+
+fn population() -> Vec<f32> {
+  (0..100).map(|i| i as f32).collect()
+}
+
+/// Returns the mean duration of `RUNS` runs of `optimize`.
+fn measure(mut optimize: impl FnMut()) -> Duration {
+  let start = Instant::now();
+  for _ in 0..RUNS {
+    optimize();
+  }
+  start.elapsed() / RUNS
+}
+
+fn main() {
+  let generic = measure(|| {
+    Nsga2::builder()
+      .population(population())
+      .tester(test)
+      .selector(RandomSelector(10))
+      .recombinator(recombination)
+      .mutator(mutation)
+      .terminator(GenerationTerminator(GENERATIONS))
+      .build()
+      .optimize();
+  });
+  let boxed = measure(|| {
+    Nsga2::builder()
+      .population(population())
+      .tester(BoxedTester::new(test))
+      .selector(BoxedSelector::new(RandomSelector(10)))
+      .recombinator(BoxedRecombinator::new(recombination))
+      .mutator(BoxedMutator::new(mutation))
+      .terminator(BoxedTerminator::new(GenerationTerminator(GENERATIONS)))
+      .build()
+      .optimize();
+  });
+
+  println!("{GENERATIONS} generations, mean of {RUNS} runs");
+  println!("generic operators: {generic:?}");
+  println!("boxed operators:   {boxed:?}");
+  println!(
+    "overhead:          {:+.2}%",
+    (boxed.as_secs_f64() / generic.as_secs_f64() - 1.0) * 100.0
+  );
+}
@@ -0,0 +1,58 @@
+//! DTLZ2 problem with 5 objectives solution using NSGA-II.
+
+use moga::{
+  export::write_csv,
+  init::latin_hypercube,
+  optimizer::{nsga::Nsga2, Optimizer},
+  problems::dtlz::{recommended_population_size, Dtlz2},
+  selection::RandomSelector,
+  termination::GenerationTerminator,
+  testing::Test,
+};
+use rand::Rng;
+
+fn main() {
+  // a problem with 5 objectives and the recommended number of variables
+  let problem = Dtlz2::<5>::default();
+
+  // the initial population spread evenly within problem's bounds
+  let population = latin_hypercube(
+    &problem.bounds(),
+    recommended_population_size(5).unwrap(),
+    &mut rand::thread_rng(),
+  );
+
+  // for each pair of parents create an offspring by blending their
+  // variables, keeping them within bounds
+  let recombination = |a: &Vec<f32>, b: &Vec<f32>| -> Vec<f32> {
+    a.iter()
+      .zip(b)
+      .map(|(a, b)| {
+        let r = rand::thread_rng().gen_range(-0.25..1.25);
+        (a + r * (b - a)).clamp(0.0, 1.0)
+      })
+      .collect()
+  };
+
+  // shift a random variable of each solution by a small random value
+  let mutation = |x: &mut Vec<f32>| {
+    let mut rng = rand::thread_rng();
+    let idx = rng.gen_range(0..x.len());
+    x[idx] = (x[idx] + rng.gen_range(-0.1..0.1)).clamp(0.0, 1.0);
+  };
+
+  let solutions = Nsga2::builder()
+    .population(population)
+    .tester(problem)
+    .selector(RandomSelector(30))
+    .recombinator(recombination)
+    .mutator(mutation)
+    .terminator(GenerationTerminator(250))
+    .build()
+    .optimize();
+
+  // print values of objective functions as CSV
+  let scores: Vec<_> = solutions.iter().map(|s| problem.test(s)).collect();
+  write_csv(std::io::stdout().lock(), &solutions, &scores, |_| [])
+    .expect("failed to write solutions");
+}
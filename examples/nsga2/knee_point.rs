@@ -0,0 +1,60 @@
+//! Selection of a single solution to deploy from the front of Binh and Korn
+//! problem found by NSGA-II.
+
+use moga::{
+  genomes::real::{Bounds, BoundsRepair, RepairStrategy},
+  init::latin_hypercube,
+  optimizer::{nsga::Nsga2, Optimizer},
+  score::knee_points,
+  selection::RandomSelector,
+  termination::GenerationTerminator,
+};
+use rand::Rng;
+
+fn main() {
+  type Solution = [f32; 2];
+
+  let bounds = Bounds::new(vec![0.0, 0.0], vec![5.0, 3.0]);
+  let population: Vec<Solution> =
+    latin_hypercube(&bounds, 100, &mut rand::thread_rng())
+      .into_iter()
+      .map(|v| [v[0], v[1]])
+      .collect();
+
+  let test = |s: &Solution| {
+    [
+      4.0 * s[0].powf(2.0) + 4.0 * s[1].powf(2.0),
+      (s[0] - 5.0).powf(2.0) + (s[1] - 5.0).powf(2.0),
+    ]
+  };
+  // blend crossover picks offsprings between parents
+  let recombination = |a: &Solution, b: &Solution| -> Solution {
+    let r: f32 = rand::thread_rng().gen_range(0.0..1.0);
+    [a[0] + r * (b[0] - a[0]), a[1] + r * (b[1] - a[1])]
+  };
+  let mutation = |s: &mut Solution| {
+    let mut rng = rand::thread_rng();
+    s[0] += rng.gen_range(-0.1..0.1);
+    s[1] += rng.gen_range(-0.1..0.1);
+  };
+  let repair = BoundsRepair::new(bounds, RepairStrategy::Clamp);
+
+  let solutions = Nsga2::builder()
+    .population(population)
+    .tester(test)
+    .selector(RandomSelector(20))
+    .recombinator(recombination)
+    .mutator((mutation, repair))
+    .terminator(GenerationTerminator(200))
+    .build()
+    .optimize();
+
+  // the knee point is the best compromise between both objectives: moving
+  // away from it along the front costs more than it gains
+  let scores: Vec<_> = solutions.iter().map(test).collect();
+  let knee = knee_points(&scores, 1)[0];
+  println!(
+    "deploy x = {}, y = {} with f1 = {}, f2 = {}",
+    solutions[knee][0], solutions[knee][1], scores[knee][0], scores[knee][1]
+  );
+}
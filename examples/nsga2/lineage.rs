@@ -0,0 +1,62 @@
+//! Lineage of a solution of Schaffer's Problem No.1 found by NSGA-II.
+
+use std::collections::HashMap;
+
+use moga::{
+  lineage::{Tracked, TrackedOperator},
+  optimizer::{nsga::Nsga2, Optimizer},
+  selection::RandomSelector,
+  termination::GenerationTerminator,
+};
+use rand::Rng;
+
+fn main() {
+  let population: Vec<_> = (0..20).map(|i| Tracked::new(i as f32)).collect();
+
+  // every solution that has ever been in the population, by id
+  let mut history: HashMap<u64, Tracked<f32>> =
+    population.iter().map(|s| (s.id, s.clone())).collect();
+  let (sender, receiver) = std::sync::mpsc::channel();
+  let observer = move |_: usize, solutions: &[Tracked<f32>], _: &[[f32; 2]]| {
+    sender.send(solutions.to_vec()).unwrap();
+  };
+
+  let solutions = Nsga2::builder()
+    .population(population)
+    .tester(TrackedOperator(|x: &f32| {
+      [x.powf(2.0), (x - 2.0).powf(2.0)]
+    }))
+    .selector(RandomSelector(10))
+    .recombinator(TrackedOperator(|a: &f32, b: &f32| {
+      a + rand::thread_rng().gen_range(-1.0..2.0) * (b - a)
+    }))
+    .mutator(TrackedOperator(|_: &mut f32| {}))
+    .terminator(GenerationTerminator(5))
+    .observer(observer)
+    .build()
+    .optimize();
+  for s in receiver.try_iter().flatten() {
+    history.entry(s.id).or_insert(s);
+  }
+
+  // the youngest solution has the longest lineage
+  let solution = solutions.iter().max_by_key(|s| s.generation).unwrap();
+  println!("lineage of solution #{}:", solution.id);
+  print_lineage(solution.id, &history, 0);
+}
+
+/// Prints a tree of ancestors of the solution with `id`.
+fn print_lineage(id: u64, history: &HashMap<u64, Tracked<f32>>, depth: usize) {
+  let indent = "  ".repeat(depth);
+  let Some(s) = history.get(&id) else {
+    println!("{indent}#{id}: unknown");
+    return;
+  };
+  println!(
+    "{indent}#{} = {:.3}, generation {}",
+    s.id, s.inner, s.generation
+  );
+  for &parent_id in &s.parent_ids {
+    print_lineage(parent_id, history, depth + 1);
+  }
+}
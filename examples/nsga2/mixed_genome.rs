@@ -0,0 +1,164 @@
+//! Delivery planning with a genome of a real value, a bit string and a
+//! permutation, solved using NSGA-II with operators composed field by field.
+
+use moga::{
+  compose::{FieldMutation, FieldRecombination, ZipRecombination},
+  optimizer::{nsga::Nsga2, Optimizer},
+  selection::RandomSelector,
+  termination::GenerationTerminator,
+};
+use rand::{seq::SliceRandom, Rng};
+
+/// Positions of cities to deliver to.
+const CITIES: [(f32, f32); 6] = [
+  (0.0, 0.0),
+  (4.0, 1.0),
+  (1.0, 3.0),
+  (5.0, 4.0),
+  (2.0, 6.0),
+  (6.0, 7.0),
+];
+
+/// A delivery plan.
+#[derive(Clone, Debug)]
+struct Plan {
+  /// Speed of the courier, from `0.1` to `2.0`.
+  speed: f32,
+  /// Whether each city gets an express delivery.
+  express: Vec<bool>,
+  /// Order of visiting cities, a permutation of their indices.
+  route: Vec<usize>,
+}
+
+/// Returns the length of the closed `route`.
+fn route_length(route: &[usize]) -> f32 {
+  route
+    .iter()
+    .zip(route.iter().cycle().skip(1))
+    .map(|(&a, &b)| {
+      let ((ax, ay), (bx, by)) = (CITIES[a], CITIES[b]);
+      (bx - ax).hypot(by - ay)
+    })
+    .sum()
+}
+
+fn main() {
+  let mut rng = rand::thread_rng();
+  let population = (0..100)
+    .map(|_| {
+      let mut route: Vec<_> = (0..CITIES.len()).collect();
+      route.shuffle(&mut rng);
+      Plan {
+        speed: rng.gen_range(0.1..=2.0),
+        express: (0..CITIES.len()).map(|_| rng.gen_bool(0.5)).collect(),
+        route,
+      }
+    })
+    .collect();
+
+  // delivery time goes down with speed and express deliveries, and their
+  // cost goes up
+  let test = |p: &Plan| {
+    let length = route_length(&p.route);
+    let express = p.express.iter().filter(|&&e| e).count() as f32;
+    [
+      length / p.speed * (1.0 - 0.05 * express),
+      length * p.speed + 2.0 * express,
+    ]
+  };
+
+  // order crossover: the offspring inherits a random slice of the route of
+  // `a` and visits the rest of the cities in order of `b`
+  let order_crossover = |a: &Vec<usize>, b: &Vec<usize>| -> Vec<usize> {
+    let mut rng = rand::thread_rng();
+    let start = rng.gen_range(0..a.len());
+    let end = rng.gen_range(start..=a.len());
+    let slice = &a[start..end];
+    let mut rest = b.iter().filter(|c| !slice.contains(c));
+    (0..a.len())
+      .map(|i| match i {
+        i if (start..end).contains(&i) => a[i],
+        _ => *rest.next().expect("routes must be permutations"),
+      })
+      .collect()
+  };
+
+  // each field is recombined with an operator of its own type
+  let recombination = ZipRecombination((
+    FieldRecombination::new(
+      |p: &Plan| &p.speed,
+      |p: &mut Plan| &mut p.speed,
+      |a: &f32, b: &f32| {
+        let r = rand::thread_rng().gen_range(-0.25..1.25);
+        (a + r * (b - a)).clamp(0.1, 2.0)
+      },
+    ),
+    (
+      FieldRecombination::new(
+        |p: &Plan| &p.express,
+        |p: &mut Plan| &mut p.express,
+        |a: &Vec<bool>, b: &Vec<bool>| -> Vec<bool> {
+          let mut rng = rand::thread_rng();
+          a.iter()
+            .zip(b)
+            .map(|(&a, &b)| if rng.gen_bool(0.5) { a } else { b })
+            .collect()
+        },
+      ),
+      FieldRecombination::new(
+        |p: &Plan| &p.route,
+        |p: &mut Plan| &mut p.route,
+        order_crossover,
+      ),
+    ),
+  ));
+
+  // and mutated with an operator of its own type
+  let mutation = (
+    FieldMutation::new(
+      |p: &mut Plan| &mut p.speed,
+      |s: &mut f32| {
+        *s = (*s + rand::thread_rng().gen_range(-0.1..0.1)).clamp(0.1, 2.0)
+      },
+    ),
+    (
+      FieldMutation::new(
+        |p: &mut Plan| &mut p.express,
+        |e: &mut Vec<bool>| {
+          let idx = rand::thread_rng().gen_range(0..e.len());
+          e[idx] = !e[idx];
+        },
+      ),
+      FieldMutation::new(
+        |p: &mut Plan| &mut p.route,
+        |r: &mut Vec<usize>| {
+          let mut rng = rand::thread_rng();
+          let (i, j) = (rng.gen_range(0..r.len()), rng.gen_range(0..r.len()));
+          r.swap(i, j);
+        },
+      ),
+    ),
+  );
+
+  let mut plans = Nsga2::builder()
+    .population(population)
+    .tester(test)
+    .selector(RandomSelector(30))
+    .recombinator(recombination)
+    .mutator(mutation)
+    .terminator(GenerationTerminator(200))
+    .build()
+    .optimize();
+
+  plans.sort_by(|a, b| test(a)[0].total_cmp(&test(b)[0]));
+  println!("time,cost,speed,express,route");
+  for p in &plans {
+    let [time, cost] = test(p);
+    let express: String = p
+      .express
+      .iter()
+      .map(|&e| if e { '1' } else { '0' })
+      .collect();
+    println!("{time:.3},{cost:.3},{:.3},{express},{:?}", p.speed, p.route);
+  }
+}
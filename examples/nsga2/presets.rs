@@ -0,0 +1,84 @@
+//! Comparison of distribution indices of the real-valued preset with NSGA-II
+//! on DTLZ problems of 2 objectives.
+
+use moga::{
+  bench::{compare, OptimizerFactory, Problem},
+  genomes::real::{Bounds, PolynomialMutation, SimulatedBinaryCrossover},
+  init::random_uniform,
+  mutation::IndexedRandomMutation,
+  optimizer::{nsga::Nsga2, Optimizer},
+  presets::{self, CROSSOVER_DISTRIBUTION_INDEX, MUTATION_DISTRIBUTION_INDEX},
+  problems::dtlz::{Dtlz1, Dtlz2, Dtlz7},
+  recombination::IndexedRandomRecombination,
+};
+
+fn main() {
+  // initial populations of 100 solutions within bounds of each problem
+  let (dtlz1, dtlz2, dtlz7) = (
+    Dtlz1::<2>::default(),
+    Dtlz2::<2>::default(),
+    Dtlz7::<2>::default(),
+  );
+  let (bounds1, bounds2, bounds7) =
+    (dtlz1.bounds(), dtlz2.bounds(), dtlz7.bounds());
+  let problems = vec![
+    Problem::new(
+      "DTLZ1",
+      dtlz1,
+      move |rng| random_uniform(&bounds1, 100, rng),
+      dtlz1.reference_front(100),
+    ),
+    Problem::new(
+      "DTLZ2",
+      dtlz2,
+      move |rng| random_uniform(&bounds2, 100, rng),
+      dtlz2.reference_front(100),
+    ),
+    Problem::new(
+      "DTLZ7",
+      dtlz7,
+      move |rng| random_uniform(&bounds7, 100, rng),
+      dtlz7.reference_front(100),
+    ),
+  ];
+
+  // the preset with its operators replaced by ones of other indices
+  let factory = |crossover_index: f32, mutation_index: f32| {
+    let name = format!("SBX {crossover_index}, PM {mutation_index}");
+    OptimizerFactory::new(name, move |problem, rng| {
+      // all factories start from the same population of the problem
+      let population: Vec<Vec<f32>> =
+        problem.population(&mut rng.stream("population"));
+      let bounds = Bounds::uniform(population[0].len(), 0.0..=1.0);
+      let mut ops =
+        presets::real_valued(bounds.clone(), population.len(), 250, rng);
+      ops.recombinator = IndexedRandomRecombination::from_random::<Vec<f32>>(
+        rng.split("recombination"),
+        SimulatedBinaryCrossover::new(bounds.clone(), crossover_index),
+      );
+      ops.mutator = IndexedRandomMutation::from_random::<Vec<f32>>(
+        rng.split("mutation"),
+        PolynomialMutation::new(bounds, mutation_index),
+      );
+      Nsga2::builder()
+        .population(population)
+        .tester(|x: &Vec<f32>| problem.test(x))
+        .selector(ops.selector)
+        .recombinator(ops.recombinator)
+        .mutator(ops.mutator)
+        .terminator(ops.terminator)
+        .build()
+        .optimize()
+    })
+  };
+  let factories = vec![
+    factory(CROSSOVER_DISTRIBUTION_INDEX, MUTATION_DISTRIBUTION_INDEX),
+    factory(5.0, MUTATION_DISTRIBUTION_INDEX),
+    factory(30.0, MUTATION_DISTRIBUTION_INDEX),
+    factory(CROSSOVER_DISTRIBUTION_INDEX, 5.0),
+    factory(CROSSOVER_DISTRIBUTION_INDEX, 100.0),
+  ];
+
+  // medians and interquartile ranges of indicators of 11 runs
+  println!("{}", compare(problems, factories, 11, 42));
+}
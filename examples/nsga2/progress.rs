@@ -0,0 +1,64 @@
+//! Progress reporting of NSGA-II optimizer through a channel of events.
+
+use std::{io::Write, sync::mpsc, thread};
+
+use moga::{
+  observer::OptimizerEvent,
+  optimizer::{nsga::Nsga2, Optimizer},
+  selection::RandomSelector,
+  termination::GenerationTerminator,
+};
+use rand::Rng;
+
+fn main() {
+  let generations = 1000;
+
+  // a bounded channel. if the printing thread lags, the optimizer drops events
+  // instead of waiting for it
+  let (sender, receiver) = mpsc::sync_channel(16);
+
+  // run the optimizer in another thread, so this one can print its progress
+  let optimizer = thread::spawn(move || {
+    let r = || rand::thread_rng().gen_range(-1.0..2.0);
+    Nsga2::builder()
+      .population((0..100).map(|i| i as f32).collect())
+      .tester(|x: &f32| [x.powf(2.0), (x - 2.0).powf(2.0)])
+      .selector(RandomSelector(10))
+      .recombinator(|x: &f32, y: &f32| x + r() * (y - x))
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(generations))
+      .event_sender(sender)
+      .build()
+      .optimize()
+  });
+
+  // the loop ends when the optimizer drops its sender
+  let mut stderr = std::io::stderr().lock();
+  for event in receiver {
+    match event {
+      OptimizerEvent::GenerationCompleted {
+        index, best_scores, ..
+      } => {
+        let [f1, f2] = best_scores;
+        write!(
+          stderr,
+          "\rgeneration {}/{generations}, best scores: {f1:.3} {f2:.3}",
+          index + 1
+        )
+        .unwrap();
+      }
+      OptimizerEvent::DuplicatesRemoved { .. }
+      | OptimizerEvent::TestsTimedOut { .. }
+      | OptimizerEvent::Profiled { .. } => {}
+      OptimizerEvent::Terminated { reason } => {
+        writeln!(stderr, "\nterminated: {reason:?}").unwrap()
+      }
+      OptimizerEvent::Error { message } => {
+        writeln!(stderr, "\nerror: {message}").unwrap()
+      }
+    }
+  }
+
+  let solutions = optimizer.join().expect("optimizer has panicked");
+  println!("found {} solutions", solutions.len());
+}
@@ -0,0 +1,96 @@
+//! Fonseca–Fleming problem solution using NSGA-II with a custom test
+//! executor that tests the whole population in a single vectorized call, the
+//! way a GPU kernel would.
+
+use moga::{
+  experimental::executor::TestExecutor,
+  optimizer::{nsga::Nsga2, Optimizer},
+  score::Scores,
+  selection::RandomSelector,
+  termination::GenerationTerminator,
+};
+use rand::Rng;
+
+/// Number of variables of a solution.
+const VARIABLES: usize = 3;
+/// Number of objectives of the problem.
+const OBJECTIVES: usize = 2;
+
+type Solution = [f32; VARIABLES];
+
+/// Execution strategy of [`MatrixTester`]. A custom executor needs a strategy
+/// of its own, so that it doesn't overlap with executors of the crate.
+enum MatrixStrategy {}
+
+/// Lays solutions out into a row-major matrix with a row per solution and
+/// evaluates all of them with a single call of [`kernel`].
+struct MatrixTester;
+
+impl TestExecutor<Solution, OBJECTIVES, MatrixStrategy> for MatrixTester {
+  fn execute_tests(&self, solutions: &[Solution]) -> Vec<Scores<OBJECTIVES>> {
+    let matrix = solutions.as_flattened();
+    kernel(matrix, solutions.len())
+      .chunks_exact(OBJECTIVES)
+      .map(|row| [row[0], row[1]])
+      .collect()
+  }
+}
+
+/// Stands for a kernel that evaluates objectives of each row of the `matrix`
+/// of `rows` solutions at once. Returns a row-major matrix of scores.
+fn kernel(matrix: &[f32], rows: usize) -> Vec<f32> {
+  let shift = 1.0 / (VARIABLES as f32).sqrt();
+  let mut scores = vec![0.0; rows * OBJECTIVES];
+  for (variables, scores) in matrix
+    .chunks_exact(VARIABLES)
+    .zip(scores.chunks_exact_mut(OBJECTIVES))
+  {
+    let (mut a, mut b) = (0.0, 0.0);
+    for &x in variables {
+      a += (x - shift).powi(2);
+      b += (x + shift).powi(2);
+    }
+    scores[0] = 1.0 - (-a).exp();
+    scores[1] = 1.0 - (-b).exp();
+  }
+  scores
+}
+
+fn main() {
+  let mut rng = rand::thread_rng();
+  let population: Vec<Solution> = (0..100)
+    .map(|_| std::array::from_fn(|_| rng.gen_range(-4.0..=4.0)))
+    .collect();
+
+  // for each pair of parents create an offspring by blending their
+  // variables, keeping them within bounds
+  let recombination = |a: &Solution, b: &Solution| -> Solution {
+    let mut rng = rand::thread_rng();
+    std::array::from_fn(|i| {
+      let r = rng.gen_range(-0.25..1.25);
+      (a[i] + r * (b[i] - a[i])).clamp(-4.0, 4.0)
+    })
+  };
+
+  // shift a random variable of each solution by a small random value
+  let mutation = |x: &mut Solution| {
+    let mut rng = rand::thread_rng();
+    let idx = rng.gen_range(0..VARIABLES);
+    x[idx] = (x[idx] + rng.gen_range(-0.1..0.1)).clamp(-4.0, 4.0);
+  };
+
+  let solutions = Nsga2::builder()
+    .population(population)
+    .tester(MatrixTester)
+    .selector(RandomSelector(30))
+    .recombinator(recombination)
+    .mutator(mutation)
+    .terminator(GenerationTerminator(100))
+    .build()
+    .optimize();
+
+  // print values of objective functions of found solutions
+  for scores in MatrixTester.execute_tests(&solutions) {
+    println!("{} {}", scores[0], scores[1]);
+  }
+}
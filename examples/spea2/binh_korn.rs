@@ -1,6 +1,9 @@
 //! Binh and Korn problem solution using SPEA-II.
 
 use moga::{
+  export::write_csv,
+  genomes::real::{Bounds, BoundsRepair, RepairStrategy},
+  init::latin_hypercube,
   operator::{ParBatch, ParEach},
   optimizer::{spea::Spea2, Optimizer},
   selection::TournamentSelectorWithoutReplacement,
@@ -10,27 +13,26 @@ use rand::Rng;
 use rand_distr::{Distribution, Normal};
 
 fn main() {
-  // the 'Solution' type represented by a pair of floating point values
-  struct Solution {
-    x: f32,
-    y: f32,
-  }
+  // the 'Solution' type represented by a pair of floating point values `x`
+  // and `y`
+  type Solution = [f32; 2];
 
-  // the initial population
-  let population: Vec<Solution> = (0i8..100)
-    .map(|i| Solution {
-      x: i.into(),
-      y: i.into(),
-    })
-    .collect();
+  // the initial population spread evenly over the search space of the
+  // problem: 0 <= x <= 5, 0 <= y <= 3
+  let bounds = Bounds::new(vec![0.0, 0.0], vec![5.0, 3.0]);
+  let population: Vec<Solution> =
+    latin_hypercube(&bounds, 100, &mut rand::thread_rng())
+      .into_iter()
+      .map(|v| [v[0], v[1]])
+      .collect();
 
   // the archive size of `Spea2` optimizer
   let archive_size = population.len();
 
   // the second objective function f1(x, y) = 4x^2 + 4y^2
-  let f1 = |s: &Solution| 4.0 * s.x.powf(2.0) + 4.0 * s.y.powf(2.0);
+  let f1 = |s: &Solution| 4.0 * s[0].powf(2.0) + 4.0 * s[1].powf(2.0);
   // and the second objective function f2(x, y) = (x - 5)^2 + (y - 5)^2
-  let f2 = |s: &Solution| (s.x - 5.0).powf(2.0) + (s.y - 5.0).powf(2.0);
+  let f2 = |s: &Solution| (s[0] - 5.0).powf(2.0) + (s[1] - 5.0).powf(2.0);
 
   // an array of closures forms a `Test`
   let _test = [f1, f2];
@@ -39,8 +41,8 @@ fn main() {
   // instead of using array of closures
   let test = |s: &Solution| {
     [
-      4.0 * s.x.powf(2.0) + 4.0 * s.y.powf(2.0),
-      (s.x - 5.0).powf(2.0) + (s.y - 5.0).powf(2.0),
+      4.0 * s[0].powf(2.0) + 4.0 * s[1].powf(2.0),
+      (s[0] - 5.0).powf(2.0) + (s[1] - 5.0).powf(2.0),
     ]
   };
 
@@ -65,17 +67,19 @@ fn main() {
   };
   // which is applied to both solutions' values by `Recombination` operator
   let recombination = |a: &Solution, b: &Solution| -> (Solution, Solution) {
-    let (x1, x2) = sbx_f32(a.x, b.x);
-    let (y1, y2) = sbx_f32(a.y, b.y);
-    (Solution { x: x1, y: y1 }, Solution { x: x2, y: y2 })
+    let (x1, x2) = sbx_f32(a[0], b[0]);
+    let (y1, y2) = sbx_f32(a[1], b[1]);
+    ([x1, y1], [x2, y2])
   };
 
   // a `Mutator` based on random values from normal disribution...
   let normal = Normal::new(0.0, 1.0).unwrap(); // which comes from 'rand_distr'
   let mutation = |s: &mut Solution| {
-    s.x += normal.sample(&mut rand::thread_rng());
-    s.y += normal.sample(&mut rand::thread_rng());
+    s[0] += normal.sample(&mut rand::thread_rng());
+    s[1] += normal.sample(&mut rand::thread_rng());
   };
+  // after mutation, values that left the search space are reflected back
+  let repair = BoundsRepair::new(bounds, RepairStrategy::Reflect);
 
   // a convinient builder with compile time verification from `typed-builder` crate
   let spea2 = Spea2::builder()
@@ -86,17 +90,19 @@ fn main() {
     .selector(selector)
     .recombinator(recombination)
     // solutions will be split in batches of optimal size, then the mutation
-    // operator will be applied to solutions in each batch concurrently
-    .mutator(mutation.par_batch())
+    // operator followed by the repair will be applied to solutions in each
+    // batch concurrently
+    .mutator((mutation, repair).par_batch())
     .terminator(terminator)
     .build();
 
   // consume and run the optimizer, returning the best solutions
   let solutions = spea2.optimize();
 
-  // print values of objective functions for each solution
-  for s in solutions {
-    let [x, y] = test(&s);
-    println!("{x} {y}",);
-  }
+  // print values of objective functions and solutions as CSV
+  let scores: Vec<_> = solutions.iter().map(test).collect();
+  write_csv(std::io::stdout().lock(), &solutions, &scores, |s| {
+    s.map(|v| v.to_string())
+  })
+  .expect("failed to write solutions");
 }
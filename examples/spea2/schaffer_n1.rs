@@ -1,6 +1,7 @@
 //! Schaffer's Problem No.1 solution using SPEA-II.
 
 use moga::{
+  export::write_csv,
   operator::ParBatch,
   optimizer::{spea::Spea2, Optimizer},
   selection::RouletteSelector,
@@ -48,9 +49,10 @@ fn main() {
   // upon termination optimizer returns the best solutions it has found
   let solutions = spea2.optimize();
 
-  // print values of objective functions for each solution
-  for s in solutions {
-    let [x, y] = test(&s);
-    println!("{x} {y}",);
-  }
+  // print values of objective functions and solutions as CSV
+  let scores: Vec<_> = solutions.iter().map(test).collect();
+  write_csv(std::io::stdout().lock(), &solutions, &scores, |x| {
+    [x.to_string()]
+  })
+  .expect("failed to write solutions");
 }
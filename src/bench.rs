@@ -0,0 +1,479 @@
+//! Comparison of optimizers on benchmark problems.
+//!
+//! [`compare`] runs each optimizer on each [`Problem`] several times and
+//! reports medians and interquartile ranges of quality indicators of found
+//! solutions: [`hypervolume`] and inverted generational distance ([`igd`]).
+//! It gives a principled way to show that a new optimizer, or a new
+//! configuration of one, actually helps.
+//!
+//! # Examples
+//! ```
+//! use moga::{
+//!   bench::{compare, OptimizerFactory, Problem},
+//!   init::random_uniform,
+//!   optimizer::{nsga::Nsga2, spea::Spea2, Optimizer},
+//!   problems::dtlz::Dtlz2,
+//!   selection::TournamentSelectorWithReplacement,
+//!   termination::GenerationTerminator,
+//! };
+//!
+//! let dtlz2 = Dtlz2::<2>::new(4);
+//! let bounds = dtlz2.bounds();
+//! let problem = Problem::new(
+//!   "DTLZ2",
+//!   dtlz2,
+//!   move |rng| random_uniform(&bounds, 20, rng),
+//!   dtlz2.reference_front(20),
+//! );
+//! let recombinator = |a: &Vec<f32>, b: &Vec<f32>| -> Vec<f32> {
+//!   a.iter().zip(b).map(|(a, b)| (a + b) / 2.0).collect()
+//! };
+//! let nsga2 = OptimizerFactory::new("NSGA-II", move |problem, rng| {
+//!   Nsga2::builder()
+//!     .population(problem.population(&mut rng.stream("population")))
+//!     .tester(|x: &Vec<f32>| problem.test(x))
+//!     .selector(
+//!       TournamentSelectorWithReplacement(20, 2)
+//!         .with_rng(rng.stream("selection")),
+//!     )
+//!     .recombinator(recombinator)
+//!     .mutator(|_: &mut Vec<f32>| {})
+//!     .terminator(GenerationTerminator(10))
+//!     .build()
+//!     .optimize()
+//! });
+//! let spea2 = OptimizerFactory::new("SPEA2", move |problem, rng| {
+//!   Spea2::builder()
+//!     .population(problem.population(&mut rng.stream("population")))
+//!     .archive_size(20)
+//!     .tester(|x: &Vec<f32>| problem.test(x))
+//!     .selector(
+//!       TournamentSelectorWithReplacement(20, 2)
+//!         .with_rng(rng.stream("selection")),
+//!     )
+//!     .recombinator(recombinator)
+//!     .mutator(|_: &mut Vec<f32>| {})
+//!     .terminator(GenerationTerminator(10))
+//!     .build()
+//!     .optimize()
+//! });
+//!
+//! let report = compare(vec![problem], vec![nsga2, spea2], 3, 42);
+//! assert_eq!(report.results().len(), 2);
+//! println!("{report}");
+//! ```
+//!
+//! [`hypervolume`]: crate::score::hypervolume
+//! [`igd`]: crate::score::igd
+
+use std::fmt::{self, Display};
+
+use rand::rngs::StdRng;
+
+use crate::{
+  rng::SplitRng,
+  score::{hypervolume, igd, ObjectiveLabels, Scores},
+  testing::Test,
+};
+
+/// A generator of initial populations of a [`Problem`].
+type PopulationFn<S> = Box<dyn Fn(&mut StdRng) -> Vec<S> + Send + Sync>;
+
+/// A function that runs an optimizer of an [`OptimizerFactory`].
+type RunFn<S, const N: usize> = Box<dyn Fn(&Problem<S, N>, SplitRng) -> Vec<S>>;
+
+/// A benchmark problem: a [`Test`] of solutions, a generator of initial
+/// populations and a reference front, i.e. a sample of the Pareto front of
+/// the problem, that found solutions are compared against.
+pub struct Problem<S, const N: usize> {
+  name: String,
+  test: Box<dyn Test<S, N> + Send + Sync>,
+  population: PopulationFn<S>,
+  reference_front: Vec<Scores<N>>,
+  reference_point: Scores<N>,
+  labels: Option<ObjectiveLabels<N>>,
+}
+
+impl<S, const N: usize> Problem<S, N> {
+  /// Creates a problem named `name` that tests solutions with `test`,
+  /// generates initial populations with `population` and has the
+  /// `reference_front`.
+  ///
+  /// Hypervolume of found solutions is bounded by the reference point that
+  /// is 10% worse than the worst scores of the reference front.
+  pub fn new(
+    name: impl Into<String>,
+    test: impl Test<S, N> + Send + Sync + 'static,
+    population: impl Fn(&mut StdRng) -> Vec<S> + Send + Sync + 'static,
+    reference_front: Vec<Scores<N>>,
+  ) -> Self {
+    let mut reference_point: Scores<N> = [0.0; N];
+    for sc in &reference_front {
+      for (r, s) in reference_point.iter_mut().zip(sc) {
+        *r = r.max(s.abs() * 1.1);
+      }
+    }
+    Self {
+      name: name.into(),
+      test: Box::new(test),
+      population: Box::new(population),
+      reference_front,
+      reference_point,
+      labels: None,
+    }
+  }
+
+  /// Labels objectives of the problem, so that its results in reports are
+  /// self-describing.
+  pub fn with_labels(self, labels: ObjectiveLabels<N>) -> Self {
+    Self {
+      labels: Some(labels),
+      ..self
+    }
+  }
+
+  /// Returns the name of the problem.
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  /// Tests the `solution`.
+  pub fn test(&self, solution: &S) -> Scores<N> {
+    self.test.test(solution)
+  }
+
+  /// Generates an initial population with random numbers of the `rng`.
+  pub fn population(&self, rng: &mut StdRng) -> Vec<S> {
+    (self.population)(rng)
+  }
+
+  /// Returns the reference front of the problem.
+  pub fn reference_front(&self) -> &[Scores<N>] {
+    &self.reference_front
+  }
+
+  /// Returns the reference point that bounds hypervolume of found solutions.
+  pub fn reference_point(&self) -> &Scores<N> {
+    &self.reference_point
+  }
+
+  /// Returns labels of objectives of the problem, if it has them.
+  pub fn labels(&self) -> Option<&ObjectiveLabels<N>> {
+    self.labels.as_ref()
+  }
+}
+
+impl<S, const N: usize> fmt::Debug for Problem<S, N> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Problem")
+      .field("name", &self.name)
+      .field("reference_point", &self.reference_point)
+      .field("labels", &self.labels)
+      .finish_non_exhaustive()
+  }
+}
+
+/// A named function that builds an optimizer for a [`Problem`], runs it and
+/// returns found solutions. It receives a master seed to derive all random
+/// number generators of the run from, such as the one of the initial
+/// population, so that runs are reproducible.
+pub struct OptimizerFactory<S, const N: usize> {
+  name: String,
+  run: RunFn<S, N>,
+}
+
+impl<S, const N: usize> OptimizerFactory<S, N> {
+  /// Creates a factory named `name` that runs optimizers with `run`.
+  pub fn new(
+    name: impl Into<String>,
+    run: impl Fn(&Problem<S, N>, SplitRng) -> Vec<S> + 'static,
+  ) -> Self {
+    Self {
+      name: name.into(),
+      run: Box::new(run),
+    }
+  }
+
+  /// Returns the name of the factory.
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  /// Runs an optimizer on the `problem`.
+  pub fn run(&self, problem: &Problem<S, N>, rng: SplitRng) -> Vec<S> {
+    (self.run)(problem, rng)
+  }
+}
+
+impl<S, const N: usize> fmt::Debug for OptimizerFactory<S, N> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("OptimizerFactory")
+      .field("name", &self.name)
+      .finish_non_exhaustive()
+  }
+}
+
+/// Statistics of an indicator over runs.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IndicatorStats {
+  /// Values of the indicator in each run.
+  pub values: Vec<f64>,
+  /// Median of the values.
+  pub median: f64,
+  /// Interquartile range of the values, i.e. the difference between their
+  /// 75th and 25th percentiles.
+  pub iqr: f64,
+}
+
+impl IndicatorStats {
+  /// Computes statistics of `values`. Percentiles are interpolated linearly
+  /// between values, and they are NaNs if there are no values.
+  pub fn compute(values: Vec<f64>) -> Self {
+    let mut sorted = values.clone();
+    sorted.sort_by(f64::total_cmp);
+    Self {
+      median: quantile(&sorted, 0.5),
+      iqr: quantile(&sorted, 0.75) - quantile(&sorted, 0.25),
+      values,
+    }
+  }
+}
+
+/// Returns the `q` quantile of `sorted` values.
+fn quantile(sorted: &[f64], q: f64) -> f64 {
+  let Some(last) = sorted.len().checked_sub(1) else {
+    return f64::NAN;
+  };
+  let pos = q * last as f64;
+  let (lower, upper) = (pos.floor() as usize, pos.ceil() as usize);
+  sorted[lower] + (sorted[upper] - sorted[lower]) * (pos - lower as f64)
+}
+
+/// Results of runs of an optimizer on a problem.
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ComparisonResult {
+  /// Name of the problem.
+  pub problem: String,
+  /// Name of the optimizer factory.
+  pub optimizer: String,
+  /// Labels of objectives of the problem, empty if it has none.
+  pub objectives: Vec<String>,
+  /// Hypervolume of found solutions, the bigger the better.
+  pub hypervolume: IndicatorStats,
+  /// Inverted generational distance of found solutions to the reference
+  /// front, the smaller the better.
+  pub igd: IndicatorStats,
+}
+
+/// A report of [`compare`], with a result per problem and optimizer.
+/// Displayed as a table, with labels of objectives in the last column if
+/// problems have them.
+#[derive(Clone, PartialEq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ComparisonReport {
+  results: Vec<ComparisonResult>,
+}
+
+impl ComparisonReport {
+  /// Returns results in order of problems, then optimizers.
+  pub fn results(&self) -> &[ComparisonResult] {
+    &self.results
+  }
+
+  /// Returns the result of the `optimizer` on the `problem`.
+  pub fn result(
+    &self,
+    problem: &str,
+    optimizer: &str,
+  ) -> Option<&ComparisonResult> {
+    self
+      .results
+      .iter()
+      .find(|r| r.problem == problem && r.optimizer == optimizer)
+  }
+}
+
+impl Display for ComparisonReport {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let width = |header: &str, column: fn(&ComparisonResult) -> &str| {
+      self
+        .results
+        .iter()
+        .map(|r| column(r).len())
+        .fold(header.len(), usize::max)
+    };
+    let pw = width("problem", |r| &r.problem);
+    let ow = width("optimizer", |r| &r.optimizer);
+    let labeled = self.results.iter().any(|r| !r.objectives.is_empty());
+    write!(
+      f,
+      "{:<pw$}  {:<ow$}  {:>12}  {:>12}  {:>12}  {:>12}",
+      "problem", "optimizer", "HV median", "HV IQR", "IGD median", "IGD IQR"
+    )?;
+    if labeled {
+      write!(f, "  objectives")?;
+    }
+    writeln!(f)?;
+    for r in &self.results {
+      write!(
+        f,
+        "{:<pw$}  {:<ow$}  {:>12.6}  {:>12.6}  {:>12.6}  {:>12.6}",
+        r.problem,
+        r.optimizer,
+        r.hypervolume.median,
+        r.hypervolume.iqr,
+        r.igd.median,
+        r.igd.iqr
+      )?;
+      if labeled {
+        write!(f, "  {}", r.objectives.join(", "))?;
+      }
+      writeln!(f)?;
+    }
+    Ok(())
+  }
+}
+
+/// Runs each optimizer of `factories` on each of `problems` `runs` times and
+/// reports statistics of [`hypervolume`] and [`igd`] of found solutions.
+///
+/// Random number generators of each run are derived from the `seed`, the
+/// name of the problem and the number of the run. All optimizers get the
+/// same seeds in the same run of a problem, so they start from the same
+/// initial populations.
+///
+/// [`hypervolume`]: crate::score::hypervolume
+/// [`igd`]: crate::score::igd
+pub fn compare<S, const N: usize>(
+  problems: Vec<Problem<S, N>>,
+  factories: Vec<OptimizerFactory<S, N>>,
+  runs: usize,
+  seed: u64,
+) -> ComparisonReport {
+  let master = SplitRng::new(seed);
+  let mut results = Vec::with_capacity(problems.len() * factories.len());
+  for problem in &problems {
+    let problem_rng = master.split(problem.name());
+    for factory in &factories {
+      let (hypervolumes, igds) = (0..runs)
+        .map(|run| {
+          let solutions =
+            factory.run(problem, problem_rng.split(&run.to_string()));
+          let scores: Vec<_> =
+            solutions.iter().map(|s| problem.test(s)).collect();
+          (
+            hypervolume(&scores, problem.reference_point()),
+            igd(&scores, problem.reference_front()),
+          )
+        })
+        .unzip();
+      results.push(ComparisonResult {
+        problem: problem.name().to_owned(),
+        optimizer: factory.name().to_owned(),
+        objectives: problem
+          .labels()
+          .map(|labels| labels.iter().map(str::to_owned).collect())
+          .unwrap_or_default(),
+        hypervolume: IndicatorStats::compute(hypervolumes),
+        igd: IndicatorStats::compute(igds),
+      });
+    }
+  }
+  ComparisonReport { results }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    optimizer::{nsga::Nsga2, Optimizer},
+    selection::TournamentSelectorWithReplacement,
+    termination::GenerationTerminator,
+  };
+
+  #[test]
+  fn test_indicator_stats() {
+    let stats = IndicatorStats::compute(vec![4.0, 1.0, 3.0, 2.0, 5.0]);
+    assert_eq!(stats.median, 3.0);
+    assert_eq!(stats.iqr, 2.0);
+    assert_eq!(stats.values, [4.0, 1.0, 3.0, 2.0, 5.0]);
+    let stats = IndicatorStats::compute(vec![1.0, 2.0]);
+    assert_eq!((stats.median, stats.iqr), (1.5, 0.5));
+    assert!(IndicatorStats::compute(Vec::new()).median.is_nan());
+  }
+
+  #[test]
+  fn test_compare() {
+    use rand::Rng;
+
+    // minimize `x^2` and `(x - 2)^2`, the front is `x` between 0 and 2
+    let problem = || {
+      Problem::new(
+        "Schaffer",
+        |x: &f32| [x.powf(2.0), (x - 2.0).powf(2.0)],
+        |rng| (0..10).map(|_| rng.gen_range(-10.0..10.0)).collect(),
+        (0..=20)
+          .map(|i| i as f32 / 10.0)
+          .map(|x| [x.powf(2.0), (x - 2.0).powf(2.0)])
+          .collect(),
+      )
+    };
+    let factory = |name, generations| {
+      OptimizerFactory::new(name, move |problem: &Problem<f32, 2>, rng| {
+        Nsga2::builder()
+          .population(problem.population(&mut rng.stream("population")))
+          .tester(|x: &f32| problem.test(x))
+          .selector(
+            TournamentSelectorWithReplacement(10, 2)
+              .with_rng(rng.stream("selection")),
+          )
+          .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+          .mutator(|_: &mut f32| {})
+          .terminator(GenerationTerminator(generations))
+          .build()
+          .optimize()
+      })
+    };
+    let report = || {
+      compare(
+        vec![problem()],
+        vec![factory("initial", 0), factory("evolved", 20)],
+        5,
+        7,
+      )
+    };
+
+    let first = report();
+    assert_eq!(first, report());
+    assert_eq!(first.results().len(), 2);
+    let initial = first.result("Schaffer", "initial").unwrap();
+    let evolved = first.result("Schaffer", "evolved").unwrap();
+    assert_eq!(initial.igd.values.len(), 5);
+    // evolution gets closer to the front than the initial population
+    assert!(evolved.igd.median < initial.igd.median);
+    assert!(evolved.hypervolume.median >= initial.hypervolume.median);
+
+    let table = first.to_string();
+    assert_eq!(table.lines().count(), 3);
+    assert!(table
+      .lines()
+      .nth(2)
+      .unwrap()
+      .starts_with("Schaffer  evolved"));
+    assert!(!table.contains("objectives"));
+    assert!(initial.objectives.is_empty());
+
+    let labeled = compare(
+      vec![problem().with_labels(ObjectiveLabels(["left", "right"]))],
+      vec![factory("evolved", 1)],
+      1,
+      7,
+    );
+    assert_eq!(labeled.results()[0].objectives, ["left", "right"]);
+    let table = labeled.to_string();
+    assert!(table.lines().next().unwrap().ends_with("  objectives"));
+    assert!(table.lines().nth(1).unwrap().ends_with("  left, right"));
+  }
+}
@@ -0,0 +1,338 @@
+//! Composition of operators for solutions out of operators for their fields.
+//!
+//! Solutions of many problems are structs with fields of different types:
+//! real values, bit strings, permutations etc. Instead of writing mutation and
+//! recombination of a whole struct by hand, build them out of operators for
+//! its fields with [`FieldMutation`] and [`ZipRecombination`] of
+//! [`FieldRecombination`]s. Fields are accessed with accessor closures, so
+//! structs don't have to implement anything.
+//!
+//! # Examples
+//! ```
+//! use moga::{
+//!   compose::{FieldMutation, FieldRecombination, ZipRecombination},
+//!   mutation::Mutation,
+//!   recombination::Recombination,
+//! };
+//!
+//! #[derive(Clone, Debug, PartialEq)]
+//! struct Genome {
+//!   x: f32,
+//!   flags: Vec<bool>,
+//! }
+//!
+//! let m = (
+//!   FieldMutation::new(|g: &mut Genome| &mut g.x, |x: &mut f32| *x += 1.0),
+//!   FieldMutation::new(
+//!     |g: &mut Genome| &mut g.flags,
+//!     |f: &mut Vec<bool>| f.iter_mut().for_each(|b| *b = !*b),
+//!   ),
+//! );
+//! let mut g = Genome {
+//!   x: 1.0,
+//!   flags: vec![true, false],
+//! };
+//! m.mutate(&mut g);
+//! assert_eq!(g, Genome {
+//!   x: 2.0,
+//!   flags: vec![false, true]
+//! });
+//!
+//! let r = ZipRecombination((
+//!   FieldRecombination::new(
+//!     |g: &Genome| &g.x,
+//!     |g: &mut Genome| &mut g.x,
+//!     |a: &f32, b: &f32| (a + b) / 2.0,
+//!   ),
+//!   FieldRecombination::new(
+//!     |g: &Genome| &g.flags,
+//!     |g: &mut Genome| &mut g.flags,
+//!     |a: &Vec<bool>, b: &Vec<bool>| {
+//!       a.iter().zip(b).map(|(a, b)| *a && *b).collect::<Vec<_>>()
+//!     },
+//!   ),
+//! ));
+//! let h = Genome {
+//!   x: 4.0,
+//!   flags: vec![true, true],
+//! };
+//! let [o] = r.recombine([&g, &h]);
+//! assert_eq!(o, Genome {
+//!   x: 3.0,
+//!   flags: vec![false, true]
+//! });
+//! ```
+
+use std::marker::PhantomData;
+
+use crate::{mutation::Mutation, recombination::Recombination};
+
+/// A [`Mutation`] of a struct that applies a mutation to one of its fields,
+/// accessed with an accessor closure. Combine field mutations into a mutation
+/// of the whole struct with tuples, which apply mutations one after another.
+///
+/// # Examples
+/// ```
+/// # use moga::{compose::FieldMutation, mutation::Mutation};
+/// let m =
+///   FieldMutation::new(|s: &mut (f32, u8)| &mut s.1, |x: &mut u8| *x += 1);
+/// let mut s = (0.5, 1);
+/// m.mutate(&mut s);
+/// assert_eq!(s, (0.5, 2));
+/// ```
+pub struct FieldMutation<S, F, A, M> {
+  accessor: A,
+  mutation: M,
+  _field: PhantomData<fn(&mut S) -> &mut F>,
+}
+
+impl<S, F, A, M> FieldMutation<S, F, A, M>
+where
+  A: Fn(&mut S) -> &mut F,
+  M: Mutation<F>,
+{
+  /// Creates a mutation of the field that the `accessor` returns with the
+  /// `mutation` of the field.
+  pub fn new(accessor: A, mutation: M) -> Self {
+    Self {
+      accessor,
+      mutation,
+      _field: PhantomData,
+    }
+  }
+}
+
+impl<S, F, A, M> Mutation<S> for FieldMutation<S, F, A, M>
+where
+  A: Fn(&mut S) -> &mut F,
+  M: Mutation<F>,
+{
+  fn mutate(&self, solution: &mut S) {
+    self.mutation.mutate((self.accessor)(solution))
+  }
+}
+
+impl<S, F, A: Clone, M: Clone> Clone for FieldMutation<S, F, A, M> {
+  fn clone(&self) -> Self {
+    Self {
+      accessor: self.accessor.clone(),
+      mutation: self.mutation.clone(),
+      _field: PhantomData,
+    }
+  }
+}
+
+impl<S, F, A, M> std::fmt::Debug for FieldMutation<S, F, A, M> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("FieldMutation")
+  }
+}
+
+/// A recombination of some fields of `P` parents into respective fields of
+/// `O` offsprings. Implemented by [`FieldRecombination`] and by pairs of
+/// field recombinations, which recombine their fields one after another.
+/// [`ZipRecombination`] turns it into a [`Recombination`].
+///
+/// **Note that you always can implement this trait instead of using
+/// [`FieldRecombination`].**
+pub trait FieldsRecombination<S, const P: usize, const O: usize> {
+  /// Recombines fields of `parents` and overwrites respective fields of
+  /// `offsprings` with the results.
+  fn recombine_fields(&self, parents: [&S; P], offsprings: &mut [S; O]);
+}
+
+impl<S, const P: usize, const O: usize, A, B> FieldsRecombination<S, P, O>
+  for (A, B)
+where
+  A: FieldsRecombination<S, P, O>,
+  B: FieldsRecombination<S, P, O>,
+{
+  fn recombine_fields(&self, parents: [&S; P], offsprings: &mut [S; O]) {
+    self.0.recombine_fields(parents, offsprings);
+    self.1.recombine_fields(parents, offsprings);
+  }
+}
+
+/// A recombination of one field of a struct, accessed with accessor closures,
+/// with a [`Recombination`] of the field. Combine field recombinations with
+/// [`ZipRecombination`].
+pub struct FieldRecombination<S, F, G, M, R> {
+  get: G,
+  get_mut: M,
+  recombination: R,
+  _field: PhantomData<fn(&mut S) -> &mut F>,
+}
+
+impl<S, F, G, M, R> FieldRecombination<S, F, G, M, R>
+where
+  G: Fn(&S) -> &F,
+  M: Fn(&mut S) -> &mut F,
+{
+  /// Creates a recombination of the field that `get` and `get_mut` return
+  /// with the `recombination` of the field.
+  pub fn new(get: G, get_mut: M, recombination: R) -> Self {
+    Self {
+      get,
+      get_mut,
+      recombination,
+      _field: PhantomData,
+    }
+  }
+}
+
+impl<S, F, G, M, R, const P: usize, const O: usize> FieldsRecombination<S, P, O>
+  for FieldRecombination<S, F, G, M, R>
+where
+  G: Fn(&S) -> &F,
+  M: Fn(&mut S) -> &mut F,
+  R: Recombination<F, P, O>,
+{
+  fn recombine_fields(&self, parents: [&S; P], offsprings: &mut [S; O]) {
+    let fields = self.recombination.recombine(parents.map(&self.get));
+    for (offspring, field) in offsprings.iter_mut().zip(fields) {
+      *(self.get_mut)(offspring) = field;
+    }
+  }
+}
+
+impl<S, F, G: Clone, M: Clone, R: Clone> Clone
+  for FieldRecombination<S, F, G, M, R>
+{
+  fn clone(&self) -> Self {
+    Self {
+      get: self.get.clone(),
+      get_mut: self.get_mut.clone(),
+      recombination: self.recombination.clone(),
+      _field: PhantomData,
+    }
+  }
+}
+
+impl<S, F, G, M, R> std::fmt::Debug for FieldRecombination<S, F, G, M, R> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("FieldRecombination")
+  }
+}
+
+/// A [`Recombination`] of a struct out of recombinations of its fields. Each
+/// offspring starts as a clone of a parent: the first offspring clones the
+/// first parent, the second offspring clones the second parent and so on,
+/// starting over if there are more offsprings than parents. Then field
+/// recombinations overwrite fields of offsprings. Fields that aren't
+/// recombined are inherited from those parents.
+///
+/// All field recombinations must take and create the same number of
+/// solutions as the recombination of the struct.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   compose::{FieldRecombination, ZipRecombination},
+///   recombination::Recombination,
+/// };
+///
+/// // swaps the first fields, the second fields are inherited
+/// let r = ZipRecombination(FieldRecombination::new(
+///   |s: &(u8, char)| &s.0,
+///   |s: &mut (u8, char)| &mut s.0,
+///   |a: &u8, b: &u8| (*b, *a),
+/// ));
+/// assert_eq!(r.recombine([&(1, 'a'), &(2, 'b')]), [(2, 'a'), (1, 'b')]);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct ZipRecombination<F>(pub F);
+
+impl<S, const P: usize, const O: usize, F> Recombination<S, P, O>
+  for ZipRecombination<F>
+where
+  S: Clone,
+  F: FieldsRecombination<S, P, O>,
+{
+  fn recombine(&self, parents: [&S; P]) -> [S; O] {
+    let mut offsprings = std::array::from_fn(|i| parents[i % P].clone());
+    self.0.recombine_fields(parents, &mut offsprings);
+    offsprings
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  #[cfg(feature = "parallel")]
+  use crate::operator::ParEach;
+
+  #[derive(Clone, Debug, PartialEq)]
+  struct Genome {
+    x: f32,
+    flags: Vec<bool>,
+    order: Vec<usize>,
+  }
+
+  fn genome(x: f32, flags: [bool; 3], order: [usize; 3]) -> Genome {
+    Genome {
+      x,
+      flags: flags.to_vec(),
+      order: order.to_vec(),
+    }
+  }
+
+  #[test]
+  fn test_field_mutations() {
+    let m = (
+      FieldMutation::new(|g: &mut Genome| &mut g.x, |x: &mut f32| *x *= 2.0),
+      (
+        FieldMutation::new(
+          |g: &mut Genome| &mut g.flags,
+          |f: &mut Vec<bool>| f[0] = !f[0],
+        ),
+        FieldMutation::new(
+          |g: &mut Genome| &mut g.order,
+          |o: &mut Vec<usize>| o.swap(0, 2),
+        ),
+      ),
+    );
+    let mut g = genome(1.5, [false, false, true], [0, 1, 2]);
+    m.mutate(&mut g);
+    assert_eq!(g, genome(3.0, [true, false, true], [2, 1, 0]));
+
+    #[cfg(feature = "parallel")]
+    {
+      use crate::mutation::executor::MutationExecutor;
+
+      let mut gs = vec![g.clone(), g];
+      m.par_each().execute_mutations(&mut gs);
+      assert_eq!(gs[1], genome(6.0, [false, false, true], [0, 1, 2]));
+    }
+  }
+
+  #[test]
+  fn test_zip_recombination() {
+    let r = ZipRecombination((
+      FieldRecombination::new(
+        |g: &Genome| &g.x,
+        |g: &mut Genome| &mut g.x,
+        |a: &f32, b: &f32| (a + b, a - b),
+      ),
+      FieldRecombination::new(
+        |g: &Genome| &g.flags,
+        |g: &mut Genome| &mut g.flags,
+        |a: &Vec<bool>, b: &Vec<bool>| (b.clone(), a.clone()),
+      ),
+    ));
+    let a = genome(2.0, [true; 3], [0, 1, 2]);
+    let b = genome(1.0, [false; 3], [2, 1, 0]);
+    let [m, n] = r.recombine([&a, &b]);
+    // orders are inherited from respective parents
+    assert_eq!(m, genome(3.0, [false; 3], [0, 1, 2]));
+    assert_eq!(n, genome(1.0, [true; 3], [2, 1, 0]));
+
+    // offsprings start over from the first parent
+    let r = ZipRecombination(FieldRecombination::new(
+      |g: &Genome| &g.x,
+      |g: &mut Genome| &mut g.x,
+      |a: &f32| (a + 1.0, a + 2.0, a + 3.0),
+    ));
+    let xs = r.recombine([&a]).map(|g| g.x);
+    assert_eq!(xs, [3.0, 4.0, 5.0]);
+  }
+}
@@ -0,0 +1,584 @@
+//! Constraint handling operators and utilities.
+
+use std::{fmt::Debug, marker::PhantomData};
+
+use executor::{ConstraintAdapter, ConstraintExecutor, RepairExecutor};
+
+use crate::{
+  execution::strategy::*,
+  score::{pareto_front_indices, Scores},
+};
+
+#[cfg(feature = "parallel")]
+mod parallel;
+mod penalty;
+mod ranking;
+
+pub use penalty::{AdaptivePenalty, PenalizedTest};
+pub use ranking::{StochasticRanking, StochasticRankingSelector};
+
+/// An operator that evaluates how much a single solution violates each of
+/// the constraints of a problem.
+///
+/// A constraint is satisfied if its violation is `0` or less. Positive values
+/// tell how far the solution is from satisfying the constraint. For example,
+/// constraint `x >= 1` is evaluated as `1 - x`.
+///
+/// Optimizers evaluate the constraint on the population at the end of each
+/// generation and let it [`adapt`] to the violations, which is how
+/// [`AdaptivePenalty`] tunes its weights.
+///
+/// Can be applied in parallel to each solution or to batches of solutions
+/// by converting it into a parallelized operator with `par_each()` or
+/// `par_batch()` methods. Since this requires [`Test`] to be implemented,
+/// implement both traits for your constraint, or wrap it into a closure.
+///
+/// # Examples
+/// ```
+/// let c = |x: &(f32, f32)| [1.0 - x.0, x.0 + x.1 - 4.0]; // 2 constraints
+/// // or use an array of closures that return a single value
+/// let c = [|x: &(f32, f32)| 1.0 - x.0, |x: &(f32, f32)| x.0 + x.1 - 4.0];
+/// ```
+///
+/// **Note that you always can implement this trait instead of using closures.**
+///
+/// [`adapt`]: Constraint::adapt
+/// [`Test`]: crate::testing::Test
+pub trait Constraint<S, const M: usize> {
+  /// Returns an array of constraint violations of given solution.
+  fn violations(&self, solution: &S) -> Scores<M>;
+
+  /// Adapts the operator to violations of the current population. Does
+  /// nothing by default.
+  fn adapt(&self, violations: &[Scores<M>]) {
+    let _ = violations;
+  }
+}
+
+impl<S, const M: usize, F> Constraint<S, M> for [F; M]
+where
+  F: Fn(&S) -> f32,
+{
+  fn violations(&self, solution: &S) -> Scores<M> {
+    self.each_ref().map(|f| f(solution))
+  }
+}
+
+impl<S, const M: usize, F> Constraint<S, M> for F
+where
+  F: Fn(&S) -> Scores<M>,
+{
+  fn violations(&self, solution: &S) -> Scores<M> {
+    self(solution)
+  }
+}
+
+/// An operator that evaluates constraint violations of all solutions.
+///
+/// See [`Constraint`] for the meaning of violations.
+///
+/// # Examples
+/// ```
+/// let c = |xs: &[f32]| xs.iter().map(|x| [1.0 - x]).collect();
+/// # let _: Vec<_> = c(&[]);
+/// ```
+///
+/// **Note that you always can implement this trait instead of using closures.**
+pub trait Constrainer<S, const M: usize> {
+  /// Returns a vector of arrays of constraint violations of given solutions.
+  ///
+  /// Optimization fails with [`OptimizationError::ScoreCountMismatch`] if
+  /// this function returns a different number of violations than the number
+  /// of solutions.
+  ///
+  /// [`OptimizationError::ScoreCountMismatch`]:
+  /// crate::optimizer::OptimizationError::ScoreCountMismatch
+  fn violations(&self, solutions: &[S]) -> Vec<Scores<M>>;
+
+  /// Adapts the operator to violations of the current population. Does
+  /// nothing by default.
+  fn adapt(&self, violations: &[Scores<M>]) {
+    let _ = violations;
+  }
+}
+
+impl<S, const M: usize, F> Constrainer<S, M> for F
+where
+  F: Fn(&[S]) -> Vec<Scores<M>>,
+{
+  fn violations(&self, solutions: &[S]) -> Vec<Scores<M>> {
+    self(solutions)
+  }
+}
+
+/// Returns `true` if all constraint `violations` of a solution are `0` or
+/// less, i.e. the solution satisfies all constraints. A NaN violation is
+/// never satisfied.
+///
+/// # Examples
+/// ```
+/// use moga::constraining::is_feasible;
+///
+/// assert!(is_feasible(&[0.0, -1.0]));
+/// assert!(!is_feasible(&[0.5, -1.0]));
+/// assert!(!is_feasible(&[f32::NAN]));
+/// ```
+pub fn is_feasible<const M: usize>(violations: &Scores<M>) -> bool {
+  violations.iter().all(|&v| v <= 0.0)
+}
+
+/// Solutions split by whether they satisfy constraints, as returned by
+/// [`Optimizer::optimize_feasible`]. Infeasible solutions are paired with
+/// their violations.
+///
+/// If none of the solutions is feasible, only the least violating ones are
+/// kept, so that a run that has never found a feasible solution still tells
+/// how close it has got.
+///
+/// # Examples
+/// ```
+/// use moga::constraining::Feasibility;
+///
+/// let feasibility = Feasibility::split(vec!['a', 'b'], vec![[0.0], [1.0]]);
+/// assert_eq!(feasibility, Feasibility::Feasible {
+///   feasible: vec!['a'],
+///   infeasible: vec![('b', [1.0])],
+/// });
+///
+/// let feasibility =
+///   Feasibility::split(vec!['a', 'b', 'c'], vec![[1.0, 0.0], [0.0, 1.0], [
+///     2.0, 1.0,
+///   ]]);
+/// assert_eq!(
+///   feasibility,
+///   Feasibility::NoneFeasible(vec![('a', [1.0, 0.0]), ('b', [0.0, 1.0])])
+/// );
+/// ```
+///
+/// [`Optimizer::optimize_feasible`]: crate::optimizer::Optimizer::optimize_feasible
+#[derive(Clone, PartialEq, Debug)]
+pub enum Feasibility<S, const M: usize> {
+  /// Some solutions satisfy all constraints.
+  Feasible {
+    /// Solutions that satisfy all constraints.
+    feasible: Vec<S>,
+    /// Solutions that violate a constraint, with their violations.
+    infeasible: Vec<(S, Scores<M>)>,
+  },
+  /// No solution satisfies all constraints. Holds the least violating
+  /// solutions with their violations: those whose positive violations
+  /// aren't dominated by violations of other solutions.
+  NoneFeasible(Vec<(S, Scores<M>)>),
+}
+
+impl<S, const M: usize> Feasibility<S, M> {
+  /// Splits `solutions` by their constraint `violations`, see
+  /// [`is_feasible`]. Solutions keep their order.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the number of violations differs from the number of solutions
+  /// or if a violation is NaN.
+  pub fn split(solutions: Vec<S>, violations: Vec<Scores<M>>) -> Self {
+    assert_eq!(
+      solutions.len(),
+      violations.len(),
+      "number of violations differs from the number of solutions"
+    );
+    if violations.iter().any(is_feasible) {
+      let (feasible, infeasible): (Vec<_>, Vec<_>) = solutions
+        .into_iter()
+        .zip(violations)
+        .partition(|(_, v)| is_feasible(v));
+      return Self::Feasible {
+        feasible: feasible.into_iter().map(|(s, _)| s).collect(),
+        infeasible,
+      };
+    }
+    // satisfied constraints don't make a solution less violating
+    let positive: Vec<_> =
+      violations.iter().map(|v| v.map(|v| v.max(0.0))).collect();
+    let mut front = pareto_front_indices(&positive).into_iter().peekable();
+    Self::NoneFeasible(
+      solutions
+        .into_iter()
+        .zip(violations)
+        .enumerate()
+        .filter_map(|(idx, pair)| front.next_if_eq(&idx).map(|_| pair))
+        .collect(),
+    )
+  }
+
+  /// Returns `true` if some solutions satisfy all constraints.
+  pub fn is_feasible(&self) -> bool {
+    matches!(self, Self::Feasible { .. })
+  }
+}
+
+/// An operator that repairs a single solution, i.e. turns an infeasible
+/// solution into a feasible one. For example, it can remove items from an
+/// overfilled knapsack or reconnect a broken tour.
+///
+/// Optimizers repair offsprings after they are mutated and before they are
+/// tested. Unlike a repair chained into a [`Mutation`], a repairer can be
+/// toggled and parallelized independently from mutation.
+///
+/// Can be applied in parallel to each solution or to batches of solutions
+/// by converting it into a parallelized operator with `par_each()` or
+/// `par_batch()` methods. Since this requires [`Mutation`] to be implemented,
+/// implement both traits for your repair, or wrap it into a closure.
+///
+/// # Examples
+/// ```
+/// let r = |f: &mut f32| *f = f.clamp(0.0, 1.0);
+/// ```
+///
+/// **Note that you always can implement this trait instead of using closures.**
+///
+/// [`Mutation`]: crate::mutation::Mutation
+pub trait Repair<S> {
+  /// Repairs given solution.
+  fn repair(&self, solution: &mut S);
+}
+
+impl<S, F> Repair<S> for F
+where
+  F: Fn(&mut S),
+{
+  fn repair(&self, solution: &mut S) {
+    self(solution)
+  }
+}
+
+/// An operator that repairs all solutions.
+///
+/// # Examples
+/// ```
+/// let r = |fs: &mut [f32]| fs.iter_mut().for_each(|f| *f = f.clamp(0.0, 1.0));
+/// ```
+///
+/// **Note that you always can implement this trait instead of using closures.**
+pub trait Repairer<S> {
+  /// Repairs each solution in given solutions.
+  fn repair(&self, solutions: &mut [S]);
+}
+
+impl<S, F> Repairer<S> for F
+where
+  F: Fn(&mut [S]),
+{
+  fn repair(&self, solutions: &mut [S]) {
+    self(solutions)
+  }
+}
+
+/// Executors are exposed to users only by [`crate::experimental::executor`].
+pub(crate) mod executor {
+  use crate::score::Scores;
+
+  /// An executor of constraint operators.
+  pub trait ConstraintExecutor<S, const M: usize, ExecutionStrategy> {
+    /// Evaluates constraint violations optionally parallelizing operator's
+    /// application.
+    fn execute_constraints(&self, solutions: &[S]) -> Vec<Scores<M>>;
+
+    /// Passes violations of the population to the operator.
+    fn execute_adaptation(&self, violations: &[Scores<M>]);
+  }
+
+  /// A constraint executor with its number of constraints and execution
+  /// strategy erased, which optimizers drive once per generation.
+  pub trait ConstraintAdapter<S> {
+    /// Evaluates violations of `population` and adapts the operator to them
+    /// if violations of each solution were evaluated. Returns the number of
+    /// evaluated violations.
+    fn adapt(&self, population: &[S]) -> usize;
+  }
+
+  /// An executor of repair operators.
+  pub trait RepairExecutor<S, ExecutionStrategy> {
+    /// Executes repairs optionally parallelizing operator's application.
+    fn execute_repairs(&self, solutions: &mut [S]);
+  }
+}
+
+impl<S, const M: usize, C> ConstraintExecutor<S, M, CustomExecutionStrategy>
+  for C
+where
+  C: Constrainer<S, M>,
+{
+  fn execute_constraints(&self, solutions: &[S]) -> Vec<Scores<M>> {
+    self.violations(solutions)
+  }
+
+  fn execute_adaptation(&self, violations: &[Scores<M>]) {
+    self.adapt(violations)
+  }
+}
+
+impl<S, const M: usize, C> ConstraintExecutor<S, M, SequentialExecutionStrategy>
+  for C
+where
+  C: Constraint<S, M>,
+{
+  fn execute_constraints(&self, solutions: &[S]) -> Vec<Scores<M>> {
+    solutions.iter().map(|s| self.violations(s)).collect()
+  }
+
+  fn execute_adaptation(&self, violations: &[Scores<M>]) {
+    self.adapt(violations)
+  }
+}
+
+/// Erases the number of constraints and the execution strategy of a
+/// constraint executor.
+pub(crate) struct ErasedConstraint<C, ES, const M: usize> {
+  constraint: C,
+  _strategy: PhantomData<fn() -> ES>,
+}
+
+impl<C, ES, const M: usize> ErasedConstraint<C, ES, M> {
+  pub(crate) fn new(constraint: C) -> Self {
+    Self {
+      constraint,
+      _strategy: PhantomData,
+    }
+  }
+}
+
+impl<S, C, ES, const M: usize> ConstraintAdapter<S>
+  for ErasedConstraint<C, ES, M>
+where
+  C: ConstraintExecutor<S, M, ES>,
+{
+  fn adapt(&self, population: &[S]) -> usize {
+    let violations = self.constraint.execute_constraints(population);
+    if violations.len() == population.len() {
+      self.constraint.execute_adaptation(&violations);
+    }
+    violations.len()
+  }
+}
+
+impl<S> Debug for dyn ConstraintAdapter<S> + Send {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("Constraint")
+  }
+}
+
+impl<S, R> RepairExecutor<S, CustomExecutionStrategy> for R
+where
+  R: Repairer<S>,
+{
+  fn execute_repairs(&self, solutions: &mut [S]) {
+    self.repair(solutions)
+  }
+}
+
+impl<S, R> RepairExecutor<S, SequentialExecutionStrategy> for R
+where
+  R: Repair<S>,
+{
+  fn execute_repairs(&self, solutions: &mut [S]) {
+    solutions.iter_mut().for_each(|s| self.repair(s));
+  }
+}
+
+impl<S> Debug for dyn Repairer<S> + Send {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("Repairer")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  #[cfg(feature = "parallel")]
+  use crate::operator::{ParAuto, ParBatch, ParEach};
+  use crate::{
+    genomes::real::{Bounds, BoundsRepair, RepairStrategy},
+    optimizer::{nsga::Nsga2, spea::Spea2, Optimizer},
+    selection::RandomSelector,
+    termination::GenerationTerminator,
+  };
+
+  type Solution = f32;
+
+  fn takes_repairer<ES, R: RepairExecutor<Solution, ES>>(r: &R) {
+    let mut solutions = [-1.0, 0.5, 2.0];
+    r.execute_repairs(&mut solutions);
+    assert_eq!(solutions, [0.0, 0.5, 1.0]);
+  }
+
+  #[test]
+  fn test_repair_from_closure() {
+    let repair = |s: &mut Solution| *s = s.clamp(0.0, 1.0);
+    takes_repairer(&repair);
+    #[cfg(feature = "parallel")]
+    takes_repairer(&repair.par_each());
+    #[cfg(feature = "parallel")]
+    takes_repairer(&repair.par_batch());
+    #[cfg(feature = "parallel")]
+    takes_repairer(&repair.par_auto());
+  }
+
+  #[test]
+  fn test_repairer_from_closure() {
+    let repairer = |solutions: &mut [Solution]| {
+      solutions.iter_mut().for_each(|s| *s = s.clamp(0.0, 1.0))
+    };
+    takes_repairer(&repairer);
+  }
+
+  fn assert_in_bounds(x: &[f32; 2]) -> [f32; 2] {
+    assert!(x.iter().all(|v| (0.0..=1.0).contains(v)));
+    [x[0], x[1] - 1.0]
+  }
+
+  #[test]
+  fn test_optimizers_repair_offsprings() {
+    let repair =
+      BoundsRepair::new(Bounds::uniform(2, 0.0..=1.0), RepairStrategy::Clamp);
+
+    Nsga2::builder()
+      .population(vec![[0.5; 2]; 10])
+      .tester(assert_in_bounds)
+      .selector(RandomSelector(4))
+      .recombinator(|x: &[f32; 2], y: &[f32; 2]| [x[0] + y[0], x[1] - y[1]])
+      .mutator(|x: &mut [f32; 2]| x[0] += 2.0)
+      .repairer(repair.clone())
+      .terminator(GenerationTerminator(5))
+      .build()
+      .optimize();
+
+    Spea2::builder()
+      .population(vec![[0.5; 2]; 10])
+      .archive_size(10)
+      .tester(assert_in_bounds)
+      .selector(RandomSelector(4))
+      .recombinator(|x: &[f32; 2], y: &[f32; 2]| [x[0] + y[0], x[1] - y[1]])
+      .mutator(|x: &mut [f32; 2]| x[0] += 2.0)
+      .repairer(repair)
+      .terminator(GenerationTerminator(5))
+      .build()
+      .optimize();
+  }
+
+  fn takes_constraint<ES, C: ConstraintExecutor<Solution, 2, ES>>(c: &C) {
+    let violations = c.execute_constraints(&[0.0, 1.0, 2.0]);
+    assert_eq!(violations, [[1.0, -2.0], [0.0, -1.0], [-1.0, 0.0]]);
+  }
+
+  #[test]
+  fn test_constraint_from_closure() {
+    let constraint = |x: &Solution| [1.0 - x, x - 2.0];
+    takes_constraint(&constraint);
+    #[cfg(feature = "parallel")]
+    takes_constraint(&constraint.par_each());
+    #[cfg(feature = "parallel")]
+    takes_constraint(&constraint.par_batch());
+    #[cfg(feature = "parallel")]
+    takes_constraint(&constraint.par_auto());
+  }
+
+  #[test]
+  fn test_constraint_from_closures() {
+    let constraint = [|x: &Solution| 1.0 - x, |x: &Solution| x - 2.0];
+    takes_constraint(&constraint);
+    #[cfg(feature = "parallel")]
+    takes_constraint(&constraint.par_each());
+    #[cfg(feature = "parallel")]
+    takes_constraint(&constraint.par_batch());
+  }
+
+  #[test]
+  fn test_constrainer_from_closure() {
+    let constrainer =
+      |xs: &[Solution]| xs.iter().map(|x| [1.0 - x, x - 2.0]).collect();
+    takes_constraint(&constrainer);
+  }
+
+  #[test]
+  fn test_optimize_feasible() {
+    // minimize `x` subject to `x >= 1`
+    let constraint = |x: &f32| [1.0 - x];
+    let build = |population: Vec<f32>| {
+      Nsga2::builder()
+        .population(population)
+        .tester(|x: &f32| [*x])
+        .selector(RandomSelector(4))
+        .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+        .mutator(|_: &mut f32| {})
+        .terminator(GenerationTerminator(0))
+        .build()
+    };
+    match build(vec![0.5, 1.0, 1.5, 2.0]).optimize_feasible(&constraint) {
+      Feasibility::Feasible {
+        feasible,
+        infeasible,
+      } => {
+        assert_eq!(feasible.len() + infeasible.len(), 4);
+        assert!(feasible.contains(&1.0));
+        assert_eq!(infeasible, [(0.5, [0.5])]);
+      }
+      other => panic!("unexpected {other:?}"),
+    }
+
+    // the least violating solution is returned even though it's infeasible
+    let feasibility =
+      build(vec![0.25, 0.5, 0.75]).optimize_feasible(&constraint);
+    assert!(!feasibility.is_feasible());
+    assert_eq!(feasibility, Feasibility::NoneFeasible(vec![(0.75, [0.25])]));
+  }
+
+  #[test]
+  fn test_optimizers_adapt_constraints() {
+    // minimize `x` subject to `x >= 1`, starting from infeasible solutions
+    let penalty = AdaptivePenalty::new(|x: &f32| [1.0 - x], 0.5, 1.5);
+    Nsga2::builder()
+      .population((0..10).map(|x| x as f32 / 20.0).collect())
+      .tester(penalty.penalize(|x: &f32| [*x]))
+      .selector(RandomSelector(4))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .constraint(penalty.clone())
+      .terminator(GenerationTerminator(5))
+      .build()
+      .optimize();
+    assert_eq!(penalty.weights(), [1.5f32.powi(5)]);
+
+    let penalty = AdaptivePenalty::new(|x: &f32| [1.0 - x], 0.5, 1.5);
+    Spea2::builder()
+      .population((0..10).map(|x| x as f32 + 1.0).collect())
+      .archive_size(10)
+      .tester(penalty.penalize(|x: &f32| [*x]))
+      .selector(RandomSelector(4))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .constraint(penalty.clone())
+      .terminator(GenerationTerminator(5))
+      .build()
+      .optimize();
+    assert_eq!(penalty.weights(), [1.5f32.powi(-5)]);
+  }
+
+  #[test]
+  #[cfg(feature = "parallel")]
+  fn test_par_batch_constrains_each_solution_once() {
+    let constraint = (|x: &Solution| [1.0 - x]).par_batch();
+    let repair = (|x: &mut Solution| *x += 1.0).par_batch();
+    crate::execution::for_uneven_batches(|len| {
+      let mut solutions: Vec<_> = (0..len).map(|i| i as Solution).collect();
+      let violations = constraint.execute_constraints(&solutions);
+      assert_eq!(
+        violations,
+        solutions.iter().map(|x| [1.0 - x]).collect::<Vec<_>>()
+      );
+      repair.execute_repairs(&mut solutions);
+      assert!(solutions
+        .iter()
+        .enumerate()
+        .all(|(i, &x)| x == i as f32 + 1.0));
+    });
+  }
+}
@@ -0,0 +1,161 @@
+//! Parallel execution of constraint and repair operators.
+//!
+//! Constraints and repairs share closure signatures with tests and mutations,
+//! so instead of having tags of their own, they accept parallelized operators
+//! of any tag. This way `par_each()`, `par_batch()` and `par_auto()` called on
+//! a closure stay unambiguous.
+
+use rayon::prelude::*;
+
+use super::{
+  executor::{ConstraintExecutor, RepairExecutor},
+  Constraint,
+  Repair,
+};
+use crate::{
+  execution::{batch_size, strategy::*},
+  operator::{
+    AutoDecision,
+    ParAutoOperator,
+    ParBatchOperator,
+    ParEachOperator,
+  },
+  score::Scores,
+};
+
+impl<T, S, const M: usize, C>
+  ConstraintExecutor<S, M, ParallelEachExecutionStrategy>
+  for ParEachOperator<T, S, C>
+where
+  T: Sync,
+  S: Sync,
+  C: Constraint<S, M> + Sync,
+{
+  fn execute_constraints(&self, solutions: &[S]) -> Vec<Scores<M>> {
+    solutions
+      .par_iter()
+      .map(|s| self.operator().violations(s))
+      .collect()
+  }
+
+  fn execute_adaptation(&self, violations: &[Scores<M>]) {
+    self.operator().adapt(violations)
+  }
+}
+
+impl<T, S, const M: usize, C>
+  ConstraintExecutor<S, M, ParallelBatchExecutionStrategy>
+  for ParBatchOperator<T, S, C>
+where
+  T: Sync,
+  S: Sync,
+  C: Constraint<S, M> + Sync,
+{
+  fn execute_constraints(&self, solutions: &[S]) -> Vec<Scores<M>> {
+    let chunk_size = batch_size(solutions.len());
+    solutions
+      .par_chunks(chunk_size)
+      .flat_map_iter(|chunk| {
+        chunk.iter().map(|s| self.operator().violations(s))
+      })
+      .collect()
+  }
+
+  fn execute_adaptation(&self, violations: &[Scores<M>]) {
+    self.operator().adapt(violations)
+  }
+}
+
+impl<T, S, R> RepairExecutor<S, ParallelEachExecutionStrategy>
+  for ParEachOperator<T, S, R>
+where
+  S: Sync + Send,
+  T: Sync,
+  R: Repair<S> + Sync,
+{
+  fn execute_repairs(&self, solutions: &mut [S]) {
+    solutions
+      .par_iter_mut()
+      .for_each(|s| self.operator().repair(s));
+  }
+}
+
+impl<T, S, R> RepairExecutor<S, ParallelBatchExecutionStrategy>
+  for ParBatchOperator<T, S, R>
+where
+  S: Sync + Send,
+  T: Sync,
+  R: Repair<S> + Sync,
+{
+  fn execute_repairs(&self, solutions: &mut [S]) {
+    let chunk_size = batch_size(solutions.len());
+    solutions.par_chunks_mut(chunk_size).for_each(|chunk| {
+      chunk.iter_mut().for_each(|s| self.operator().repair(s))
+    });
+  }
+}
+
+impl<T, S, const M: usize, C> ConstraintExecutor<S, M, AutoExecutionStrategy>
+  for ParAutoOperator<T, S, C>
+where
+  T: Sync,
+  S: Sync,
+  C: Constraint<S, M> + Sync,
+{
+  fn execute_constraints(&self, solutions: &[S]) -> Vec<Scores<M>> {
+    let mut violations = Vec::with_capacity(solutions.len());
+    let (evaluated, decision) = self.plan(solutions.len(), |sample| {
+      violations.extend(
+        solutions[..sample]
+          .iter()
+          .map(|s| self.operator().violations(s)),
+      )
+    });
+    let rest = &solutions[evaluated..];
+    match decision {
+      AutoDecision::Sequential => {
+        violations.extend(rest.iter().map(|s| self.operator().violations(s)))
+      }
+      AutoDecision::Batch => violations.par_extend(
+        rest
+          .par_chunks(batch_size(rest.len()))
+          .flat_map_iter(|chunk| {
+            chunk.iter().map(|s| self.operator().violations(s))
+          }),
+      ),
+    }
+    violations
+  }
+
+  fn execute_adaptation(&self, violations: &[Scores<M>]) {
+    self.operator().adapt(violations)
+  }
+}
+
+impl<T, S, R> RepairExecutor<S, AutoExecutionStrategy>
+  for ParAutoOperator<T, S, R>
+where
+  S: Sync + Send,
+  T: Sync,
+  R: Repair<S> + Sync,
+{
+  fn execute_repairs(&self, solutions: &mut [S]) {
+    let (repaired, decision) = self.plan(solutions.len(), |sample| {
+      solutions[..sample]
+        .iter_mut()
+        .for_each(|s| self.operator().repair(s))
+    });
+    let rest = &mut solutions[repaired..];
+    match decision {
+      AutoDecision::Sequential => {
+        rest.iter_mut().for_each(|s| self.operator().repair(s))
+      }
+      AutoDecision::Batch => {
+        let chunk_size = batch_size(rest.len());
+        rest.par_chunks_mut(chunk_size).for_each(|chunk| {
+          chunk.iter_mut().for_each(|s| self.operator().repair(s))
+        })
+      }
+    }
+  }
+}
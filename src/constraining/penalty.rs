@@ -0,0 +1,244 @@
+//! Adaptive penalty constraint handling.
+
+use std::sync::{Arc, RwLock};
+
+use super::Constraint;
+use crate::{score::Scores, testing::Test};
+
+/// Bounds of penalty weights, which keep them from vanishing or overflowing
+/// after long runs.
+const MIN_WEIGHT: f32 = 1e-6;
+const MAX_WEIGHT: f32 = 1e6;
+
+/// A [`Constraint`] that penalizes violations with per-constraint weights,
+/// adapted to the population once per generation.
+///
+/// The penalty of a solution is a weighted sum of its positive violations.
+/// Each generation, if a share of the population that satisfies a constraint
+/// is below the `target`, the weight of that constraint is multiplied by
+/// `factor`, otherwise it is divided by it. Hence, the optimizer is pushed
+/// towards the feasible region only as hard as it takes to keep the `target`
+/// share of the population there. All weights start at `1.0`.
+///
+/// Penalties are added to scores of a tester wrapped with [`penalize`], and
+/// the weights are adapted only if the optimizer is given the penalty as its
+/// `constraint`. Clones of the penalty share the weights, so clone it to use
+/// it in both places.
+///
+/// Since the weights change over time, scores of solutions that survive
+/// several generations are penalized with the weights they were tested with.
+///
+/// # Examples
+/// ```no_run
+/// # use moga::{
+/// #   constraining::AdaptivePenalty,
+/// #   optimizer::{nsga::Nsga2, Optimizer},
+/// #   selection::RandomSelector,
+/// #   termination::GenerationTerminator,
+/// # };
+/// // minimize `x` subject to `x >= 1`
+/// let penalty = AdaptivePenalty::new(|x: &f32| [1.0 - x], 0.5, 1.5);
+/// let solutions = Nsga2::builder()
+///   .population((0..100).map(|x| x as f32).collect())
+///   .tester(penalty.penalize(|x: &f32| [*x]))
+///   .selector(RandomSelector(20))
+///   .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+///   .mutator(|x: &mut f32| *x -= 0.1)
+///   .constraint(penalty.clone())
+///   .terminator(GenerationTerminator(100))
+///   .build()
+///   .optimize();
+/// ```
+///
+/// [`penalize`]: AdaptivePenalty::penalize
+#[derive(Debug)]
+pub struct AdaptivePenalty<C, const M: usize> {
+  shared: Arc<Shared<C, M>>,
+}
+
+#[derive(Debug)]
+struct Shared<C, const M: usize> {
+  constraint: C,
+  weights: RwLock<Scores<M>>,
+  target: f32,
+  factor: f32,
+}
+
+impl<C, const M: usize> AdaptivePenalty<C, M> {
+  /// Creates a penalty of `constraint` that keeps the `target` share of the
+  /// population feasible, changing the weights by `factor` each generation.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `target` is not within `0.0..=1.0` or `factor` is not greater
+  /// than `1.0`.
+  pub fn new<S>(constraint: C, target: f32, factor: f32) -> Self
+  where
+    C: Constraint<S, M>,
+  {
+    assert!(
+      (0.0..=1.0).contains(&target),
+      "target share of feasible solutions must be within 0.0..=1.0"
+    );
+    assert!(factor > 1.0, "weights factor must be greater than 1.0");
+    Self {
+      shared: Arc::new(Shared {
+        constraint,
+        weights: RwLock::new([1.0; M]),
+        target,
+        factor,
+      }),
+    }
+  }
+
+  /// Returns the current weights of constraints.
+  pub fn weights(&self) -> Scores<M> {
+    *self.shared.weights.read().unwrap()
+  }
+
+  /// Returns the target share of feasible solutions.
+  pub fn target(&self) -> f32 {
+    self.shared.target
+  }
+
+  /// Returns the factor weights are changed by.
+  pub fn factor(&self) -> f32 {
+    self.shared.factor
+  }
+
+  /// Returns the penalty of given constraint violations with the current
+  /// weights.
+  pub fn penalty(&self, violations: &Scores<M>) -> f32 {
+    let weights = self.shared.weights.read().unwrap();
+    violations
+      .iter()
+      .zip(weights.iter())
+      .map(|(v, w)| v.max(0.0) * w)
+      .sum()
+  }
+
+  /// Wraps `tester` into a [`Test`] that moves each score of a solution away
+  /// from `0` by the solution's penalty.
+  pub fn penalize<T>(&self, tester: T) -> PenalizedTest<T, C, M> {
+    PenalizedTest {
+      tester,
+      penalty: self.clone(),
+    }
+  }
+}
+
+impl<C, const M: usize> Clone for AdaptivePenalty<C, M> {
+  fn clone(&self) -> Self {
+    Self {
+      shared: Arc::clone(&self.shared),
+    }
+  }
+}
+
+impl<S, C, const M: usize> Constraint<S, M> for AdaptivePenalty<C, M>
+where
+  C: Constraint<S, M>,
+{
+  fn violations(&self, solution: &S) -> Scores<M> {
+    self.shared.constraint.violations(solution)
+  }
+
+  fn adapt(&self, violations: &[Scores<M>]) {
+    if violations.is_empty() {
+      return;
+    }
+    let Shared { target, factor, .. } = *self.shared;
+    let mut weights = self.shared.weights.write().unwrap();
+    for (i, w) in weights.iter_mut().enumerate() {
+      let feasible = violations.iter().filter(|v| v[i] <= 0.0).count();
+      let share = feasible as f32 / violations.len() as f32;
+      *w = if share < target {
+        *w * factor
+      } else {
+        *w / factor
+      }
+      .clamp(MIN_WEIGHT, MAX_WEIGHT);
+    }
+  }
+}
+
+/// A [`Test`] with scores penalized by [`AdaptivePenalty`].
+///
+/// Created with [`AdaptivePenalty::penalize`].
+#[derive(Clone, Debug)]
+pub struct PenalizedTest<T, C, const M: usize> {
+  tester: T,
+  penalty: AdaptivePenalty<C, M>,
+}
+
+impl<S, T, C, const N: usize, const M: usize> Test<S, N>
+  for PenalizedTest<T, C, M>
+where
+  T: Test<S, N>,
+  C: Constraint<S, M>,
+{
+  fn test(&self, solution: &S) -> Scores<N> {
+    let violations = self.penalty.shared.constraint.violations(solution);
+    let penalty = self.penalty.penalty(&violations);
+    self
+      .tester
+      .test(solution)
+      .map(|score| score + penalty.copysign(score))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_penalty() {
+    let penalty = AdaptivePenalty::new([|_: &f32| 0.0; 2], 0.5, 2.0);
+    *penalty.shared.weights.write().unwrap() = [1.0, 3.0];
+    assert_eq!(penalty.penalty(&[-1.0, -1.0]), 0.0);
+    assert_eq!(penalty.penalty(&[2.0, -1.0]), 2.0);
+    assert_eq!(penalty.penalty(&[2.0, 0.5]), 3.5);
+  }
+
+  #[test]
+  fn test_adapt_weights() {
+    let penalty = AdaptivePenalty::new([|_: &f32| 0.0; 2], 0.5, 2.0);
+    // first constraint is satisfied by a quarter of solutions,
+    // second - by all of them
+    let violations = [[1.0, 0.0], [1.0, -1.0], [0.0, -1.0], [3.0, -2.0]];
+    penalty.adapt(&violations);
+    assert_eq!(penalty.weights(), [2.0, 0.5]);
+    penalty.adapt(&violations);
+    assert_eq!(penalty.weights(), [4.0, 0.25]);
+    penalty.adapt(&[]);
+    assert_eq!(penalty.weights(), [4.0, 0.25]);
+
+    for _ in 0..100 {
+      penalty.adapt(&violations);
+    }
+    assert_eq!(penalty.weights(), [MAX_WEIGHT, MIN_WEIGHT]);
+  }
+
+  #[test]
+  fn test_clones_share_weights() {
+    let penalty = AdaptivePenalty::new(|_: &f32| [1.0], 0.5, 2.0);
+    penalty.clone().adapt(&[[1.0]]);
+    assert_eq!(penalty.weights(), [2.0]);
+  }
+
+  #[test]
+  fn test_penalized_test() {
+    let penalty = AdaptivePenalty::new(|x: &f32| [1.0 - x], 0.5, 2.0);
+    let tester = penalty.penalize(|x: &f32| [*x, -x, 0.0]);
+    assert_eq!(tester.test(&2.0), [2.0, -2.0, 0.0]);
+    assert_eq!(tester.test(&0.5), [1.0, -1.0, 0.5]);
+    penalty.adapt(&[[0.5]]);
+    assert_eq!(tester.test(&0.5), [1.5, -1.5, 1.0]);
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_invalid_factor() {
+    AdaptivePenalty::new(|_: &f32| [0.0], 0.5, 1.0);
+  }
+}
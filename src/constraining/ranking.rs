@@ -0,0 +1,306 @@
+//! Stochastic ranking of constrained solutions.
+
+use rand::prelude::*;
+
+use super::Constraint;
+use crate::{
+  optimizer::{apply_permutation, to_index},
+  score::{ParetoDominance, Scores},
+  selection::{SeedableSelector, SeededSelector, Selector},
+  truncation::Truncation,
+};
+
+/// Stochastic ranking of solutions of a constrained problem, introduced by
+/// [Runarsson and Yao].
+///
+/// Solutions are ranked with a bubble-sort-like procedure, in which adjacent
+/// solutions are compared by their scores if both of them are feasible or
+/// with probability `pf`, and by their total constraint violation otherwise.
+/// Unlike static penalties, stochastic ranking balances objectives and
+/// constraints without tuning any weights, and infeasible solutions with good
+/// scores still get a chance to outrank feasible ones. The authors recommend
+/// `pf` slightly below `0.5`, such as `0.45`.
+///
+/// Scores are compared by Pareto dominance, and total violation of a solution
+/// is a sum of its positive constraint violations. See [`Constraint`].
+///
+/// Stochastic ranking can replace the default truncation of [`Nsga2`], keeping
+/// the best ranked solutions. To select solutions with stochastic ranking, use
+/// [`StochasticRankingSelector`].
+///
+/// [Runarsson and Yao]: https://doi.org/10.1109/4235.873238
+/// [`Nsga2`]: crate::optimizer::nsga::Nsga2
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct StochasticRanking<C, const M: usize> {
+  constraint: C,
+  pf: f32,
+}
+
+impl<C, const M: usize> StochasticRanking<C, M> {
+  /// Creates a stochastic ranking of solutions violating `constraint`, which
+  /// compares solutions by their scores with probability `pf`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `pf` is not within `0.0..=1.0`.
+  pub fn new<S>(constraint: C, pf: f32) -> Self
+  where
+    C: Constraint<S, M>,
+  {
+    assert!(
+      (0.0..=1.0).contains(&pf),
+      "probability of comparing by scores must be within 0.0..=1.0"
+    );
+    Self { constraint, pf }
+  }
+
+  /// Returns the ranked constraint.
+  pub fn constraint(&self) -> &C {
+    &self.constraint
+  }
+
+  /// Returns the probability of comparing infeasible solutions by scores.
+  pub fn pf(&self) -> f32 {
+    self.pf
+  }
+
+  /// Ranks `solutions` with their `scores` using `rng`. Returns indices of
+  /// solutions from the best to the worst.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the number of scores doesn't match the number of solutions.
+  pub fn rank<S, const N: usize>(
+    &self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+    rng: &mut impl Rng,
+  ) -> Vec<usize>
+  where
+    C: Constraint<S, M>,
+  {
+    assert_eq!(
+      solutions.len(),
+      scores.len(),
+      "number of scores doesn't match the number of solutions"
+    );
+    let violations = solutions
+      .iter()
+      .map(|s| total_violation(&self.constraint.violations(s)))
+      .collect::<Vec<_>>();
+    rank(scores, &violations, self.pf, rng)
+  }
+}
+
+impl<S, C, const N: usize, const M: usize> Truncation<S, N>
+  for StochasticRanking<C, M>
+where
+  C: Constraint<S, M>,
+{
+  fn truncate(
+    &self,
+    mut solutions: Vec<S>,
+    mut scores: Vec<Scores<N>>,
+    target: usize,
+  ) -> (Vec<S>, Vec<Scores<N>>) {
+    let mut permutation = self
+      .rank(&solutions, &scores, &mut rand::thread_rng())
+      .into_iter()
+      .map(to_index)
+      .collect::<Vec<_>>();
+    apply_permutation(&mut permutation, &mut solutions, &mut scores);
+    solutions.truncate(target);
+    scores.truncate(target);
+    (solutions, scores)
+  }
+}
+
+/// Selects at most `n` best solutions ranked by [`StochasticRanking`].
+///
+/// If `n` is bigger than the number of solutions, this selector selects all
+/// solutions.
+///
+/// # Examples
+/// ```
+/// # use moga::constraining::{StochasticRanking, StochasticRankingSelector};
+/// // `x >= 1` and `y >= 1` constraints
+/// let constraint = |x: &(f32, f32)| [1.0 - x.0, 1.0 - x.1];
+/// let ranking = StochasticRanking::new(constraint, 0.45);
+/// let selector = StochasticRankingSelector(10, ranking);
+/// ```
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct StochasticRankingSelector<C, const M: usize>(
+  pub usize,
+  pub StochasticRanking<C, M>,
+);
+
+impl<C, const M: usize> StochasticRankingSelector<C, M> {
+  /// Wraps the selector into a [`Selector`] that draws random numbers from
+  /// `rng`. See [`SeededSelector`].
+  pub fn with_rng<R: RngCore>(self, rng: R) -> SeededSelector<Self, R> {
+    SeededSelector::new(self, rng)
+  }
+}
+
+impl<S, C, const N: usize, const M: usize> Selector<S, N>
+  for StochasticRankingSelector<C, M>
+where
+  C: Constraint<S, M>,
+{
+  fn select<'a>(&self, solutions: &'a [S], scores: &[Scores<N>]) -> Vec<&'a S> {
+    self
+      .select_indices(solutions, scores)
+      .into_iter()
+      .map(|i| &solutions[i])
+      .collect()
+  }
+
+  fn select_indices(
+    &self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) -> Vec<usize> {
+    self.select_indices_with(solutions, scores, &mut rand::thread_rng())
+  }
+}
+
+impl<S, C, const N: usize, const M: usize> SeedableSelector<S, N>
+  for StochasticRankingSelector<C, M>
+where
+  C: Constraint<S, M>,
+{
+  fn select_indices_with<R: Rng + ?Sized>(
+    &self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+    mut rng: &mut R,
+  ) -> Vec<usize> {
+    let mut ranks = self.1.rank(solutions, scores, &mut rng);
+    ranks.truncate(self.0);
+    ranks
+  }
+}
+
+/// Returns a sum of positive constraint violations.
+fn total_violation<const M: usize>(violations: &Scores<M>) -> f32 {
+  violations.iter().map(|v| v.max(0.0)).sum()
+}
+
+/// Ranks solutions by their scores and total violations with stochastic
+/// bubble sort. Returns indices of solutions from the best to the worst.
+fn rank<const N: usize>(
+  scores: &[Scores<N>],
+  violations: &[f32],
+  pf: f32,
+  rng: &mut impl Rng,
+) -> Vec<usize> {
+  let mut ranks = (0..scores.len()).collect::<Vec<_>>();
+  for _ in 0..ranks.len() {
+    let mut swapped = false;
+    for j in 1..ranks.len() {
+      let (p, q) = (ranks[j - 1], ranks[j]);
+      let feasible = violations[p] <= 0.0 && violations[q] <= 0.0;
+      let by_scores = feasible || rng.gen_bool(pf as f64);
+      let swap = if by_scores {
+        scores[q].dominance(&scores[p]).is_lt()
+      } else {
+        violations[q] < violations[p]
+      };
+      if swap {
+        ranks.swap(j - 1, j);
+        swapped = true;
+      }
+    }
+    if !swapped {
+      break;
+    }
+  }
+  ranks
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Solutions of the example are scored with their indices. Solutions `0`
+  /// and `1` are infeasible, others are feasible.
+  fn example() -> (Vec<Scores<1>>, Vec<f32>) {
+    let scores = (0..5).map(|i| [i as f32]).collect();
+    let violations = vec![2.0, 1.0, 0.0, 0.0, 0.0];
+    (scores, violations)
+  }
+
+  #[test]
+  fn test_rank_by_violations() {
+    let (scores, violations) = example();
+    let mut rng = StdRng::seed_from_u64(0);
+    // feasible solutions first, ordered by their scores,
+    // then infeasible ones, ordered by their violations
+    assert_eq!(rank(&scores, &violations, 0.0, &mut rng), [2, 3, 4, 1, 0]);
+  }
+
+  #[test]
+  fn test_rank_by_scores() {
+    let (scores, violations) = example();
+    let mut rng = StdRng::seed_from_u64(0);
+    assert_eq!(rank(&scores, &violations, 1.0, &mut rng), [0, 1, 2, 3, 4]);
+  }
+
+  #[test]
+  fn test_rank_stochastically() {
+    let (scores, violations) = example();
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut infeasible_first = 0;
+    for _ in 0..1000 {
+      let ranks = rank(&scores, &violations, 0.45, &mut rng);
+      // feasible solutions always keep their relative order
+      let feasible = ranks
+        .iter()
+        .filter(|&&i| i >= 2)
+        .copied()
+        .collect::<Vec<_>>();
+      assert_eq!(feasible, [2, 3, 4]);
+      if ranks[0] < 2 {
+        infeasible_first += 1;
+      }
+    }
+    // an infeasible solution with good scores outranks feasible ones only
+    // sometimes
+    assert!((100..900).contains(&infeasible_first), "{infeasible_first}");
+  }
+
+  #[test]
+  fn test_rank_is_reproducible() {
+    let (scores, violations) = example();
+    let ranks =
+      |seed| rank(&scores, &violations, 0.45, &mut StdRng::seed_from_u64(seed));
+    assert_eq!(ranks(42), ranks(42));
+  }
+
+  #[test]
+  fn test_stochastic_ranking_selector() {
+    let ranking = StochasticRanking::new(|x: &f32| [1.0 - x], 0.0);
+    let solutions = [0.0, 0.5, 1.0, 2.0, 3.0];
+    let scores = solutions.map(|x| [x]);
+    let selector = StochasticRankingSelector(3, ranking);
+    assert_eq!(selector.select(&solutions, &scores), [&1.0, &2.0, &3.0]);
+    let selector = StochasticRankingSelector(10, ranking);
+    assert_eq!(selector.select(&solutions, &scores).len(), 5);
+  }
+
+  #[test]
+  fn test_stochastic_ranking_truncation() {
+    let ranking = StochasticRanking::new(|x: &f32| [1.0 - x], 0.0);
+    let solutions = vec![3.0, 0.0, 2.0, 0.5, 1.0];
+    let scores = solutions.iter().map(|&x| [x]).collect();
+    let (solutions, scores) = ranking.truncate(solutions, scores, 4);
+    assert_eq!(solutions, [1.0, 2.0, 3.0, 0.5]);
+    assert_eq!(scores, [[1.0], [2.0], [3.0], [0.5]]);
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_invalid_pf() {
+    StochasticRanking::new(|_: &f32| [0.0], 1.5);
+  }
+}
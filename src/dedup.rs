@@ -0,0 +1,85 @@
+//! Deduplication of offsprings.
+
+use std::{collections::HashSet, fmt::Debug, hash::Hash};
+
+/// A policy of removing duplicate offsprings before they are tested.
+///
+/// Duplicates waste test evaluations, inflate strength values of [`Spea2`]
+/// and crowd fronts of [`Nsga2`] with copies of the same solution. Optimizers
+/// given a policy apply it to offsprings after mutation and repair, so removed
+/// offsprings are never tested and scores stay paired with their solutions.
+///
+/// # Examples
+/// ```
+/// use moga::dedup::{Dedup, Deduplicate};
+///
+/// // solutions are duplicates if their rounded values are equal
+/// let dedup = Dedup::By(|x: &f32| x.round() as i32);
+/// let population = [1.0, 2.0];
+/// let mut offsprings = vec![0.9, 3.0, 3.2, 4.0];
+/// assert_eq!(dedup.dedup(&population, &mut offsprings), 2);
+/// assert_eq!(offsprings, [3.0, 4.0]);
+/// ```
+///
+/// [`Spea2`]: crate::optimizer::spea::Spea2
+/// [`Nsga2`]: crate::optimizer::nsga::Nsga2
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum Dedup<F> {
+  /// Solutions are duplicates if `F` maps them to equal keys, so solutions
+  /// themselves don't have to implement [`Hash`]. The first occurrence is
+  /// kept: offsprings that duplicate a solution of the population are
+  /// removed, and of duplicate offsprings only the first one is kept.
+  By(F),
+}
+
+/// An operator that removes duplicate offsprings. Implemented by [`Dedup`].
+///
+/// **Note that you always can implement this trait instead of using
+/// [`Dedup`].**
+pub trait Deduplicate<S> {
+  /// Removes `offsprings` that duplicate solutions of the `population` or
+  /// other offsprings, keeping the order of the rest. Returns the number of
+  /// removed offsprings.
+  fn dedup(&self, population: &[S], offsprings: &mut Vec<S>) -> usize;
+}
+
+impl<S, F, K> Deduplicate<S> for Dedup<F>
+where
+  F: Fn(&S) -> K,
+  K: Hash + Eq,
+{
+  fn dedup(&self, population: &[S], offsprings: &mut Vec<S>) -> usize {
+    let Dedup::By(key) = self;
+    let mut keys = population.iter().map(key).collect::<HashSet<_>>();
+    let len = offsprings.len();
+    offsprings.retain(|s| keys.insert(key(s)));
+    len - offsprings.len()
+  }
+}
+
+impl<S> Debug for dyn Deduplicate<S> + Send {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("Deduplicate")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_dedup_keeps_first_occurrence() {
+    let dedup = Dedup::By(|x: &(u8, char)| x.0);
+    let population = [(1, 'a'), (2, 'b'), (1, 'c')];
+    let mut offsprings = vec![(3, 'd'), (2, 'e'), (3, 'f'), (4, 'g'), (4, 'h')];
+    assert_eq!(dedup.dedup(&population, &mut offsprings), 3);
+    assert_eq!(offsprings, [(3, 'd'), (4, 'g')]);
+
+    let mut offsprings = vec![];
+    assert_eq!(dedup.dedup(&population, &mut offsprings), 0);
+    let mut offsprings = vec![(5, 'i')];
+    assert_eq!(dedup.dedup(&[], &mut offsprings), 0);
+    assert_eq!(offsprings, [(5, 'i')]);
+  }
+}
@@ -1,16 +1,107 @@
+/// Number of solutions starting from which internal routines of optimizers,
+/// such as dominance and fitness computations, are parallelized with
+/// `parallel` feature enabled.
+pub(crate) const PARALLEL_THRESHOLD: usize = 1024;
+
+/// Estimated sequential cost of the work left after calibration starting
+/// from which operators parallelized with `par_auto()` are executed in
+/// parallel. It outweighs the overhead of spreading work across threads.
+#[cfg(feature = "parallel")]
+pub(crate) const PARALLEL_WORK_THRESHOLD: std::time::Duration =
+  std::time::Duration::from_micros(100);
+
 pub(crate) mod strategy {
   /// Sequential execution strategy marker, i.e. no parallelization involved.
   pub enum SequentialExecutionStrategy {}
 
   /// Parallel execution strategy marker, parallelizes objective testing for
   /// **each** solution.
+  #[cfg(feature = "parallel")]
   pub enum ParallelEachExecutionStrategy {}
 
   /// Parallel execution strategy marker, parallelizes objective testing for a
   /// **batch** of solutions. The crate tries to split the work equally for each
-  /// available thread.
+  /// available thread: solutions are split into at most as many batches as
+  /// there are threads, and each solution is processed exactly once.
+  #[cfg(feature = "parallel")]
   pub enum ParallelBatchExecutionStrategy {}
 
+  /// Automatic execution strategy marker, executes operators either
+  /// sequentially or in parallel for a **batch** of solutions, depending on
+  /// their measured cost.
+  #[cfg(feature = "parallel")]
+  pub enum AutoExecutionStrategy {}
+
   /// Custom execution strategy marker.
   pub enum CustomExecutionStrategy {}
+
+  /// By-value execution strategy marker, sequentially recombines copies of
+  /// parents rather than references to them.
+  pub enum ByValueExecutionStrategy {}
+
+  /// Cloning execution strategy marker, sequentially clones each parent into
+  /// an offspring.
+  pub enum CloningExecutionStrategy {}
+
+  /// Indexed execution strategy marker, recombines each combination of
+  /// parents with a generator derived from its index.
+  pub enum IndexedExecutionStrategy {}
+
+  /// Scored execution strategy marker, recombines parents given their scores
+  /// of `N` objectives.
+  pub enum ScoredExecutionStrategy<const N: usize> {}
+}
+
+/// Returns the size of batches that executors of operators parallelized with
+/// `par_batch()` split `len` solutions into. There are at most as many
+/// batches as there are threads in the current rayon pool. All batches but
+/// the last one have this size, and the last one gets the rest.
+#[cfg(feature = "parallel")]
+pub(crate) fn batch_size(len: usize) -> usize {
+  batch_size_for(len, rayon::current_num_threads())
+}
+
+#[cfg(feature = "parallel")]
+fn batch_size_for(len: usize, threads: usize) -> usize {
+  len.div_ceil(threads.max(1)).max(1)
+}
+
+/// Calls `f` with numbers of solutions that don't split into batches evenly,
+/// in rayon pools of different sizes.
+#[cfg(all(test, feature = "parallel"))]
+pub(crate) fn for_uneven_batches(f: impl Fn(usize) + Sync) {
+  for threads in [1, 2, 3, 16] {
+    let pool = rayon::ThreadPoolBuilder::new()
+      .num_threads(threads)
+      .build()
+      .unwrap();
+    for len in [0, 1, 2, 3, 7, 15, 17, 33, 101] {
+      pool.install(|| f(len));
+    }
+  }
+}
+
+#[cfg(all(test, feature = "parallel"))]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_batch_size() {
+    for threads in 0..20 {
+      for len in 0..200 {
+        let size = batch_size_for(len, threads);
+        let batches = len.div_ceil(size);
+        assert!(
+          batches <= threads.max(1),
+          "{len} solutions, {threads} threads"
+        );
+        // the last batch isn't empty, so no thread is left without work
+        // that could be given to it
+        assert!(len == 0 || (batches - 1) * size < len);
+      }
+    }
+    assert_eq!(batch_size_for(17, 16), 2);
+    assert_eq!(batch_size_for(10, 16), 1);
+    assert_eq!(batch_size_for(0, 4), 1);
+  }
 }
@@ -0,0 +1,75 @@
+//! Unstable APIs that may change between minor versions.
+
+/// Executors of operators, which optimizers drive.
+///
+/// An optimizer never calls operators directly. Each operator is applied by
+/// its executor, which decides how: [`Test`]s are applied to each solution,
+/// [`Tester`]s to all of them at once, and operators parallelized with
+/// `par_each()` or `par_batch()` are applied in parallel. Executors are
+/// distinguished by a type of their execution strategy, so that executors of
+/// different kinds of operators don't overlap.
+///
+/// Implement an executor for your own type when operators don't give enough
+/// control over execution, e.g. to evaluate a whole population with a single
+/// call into a GPU kernel. Since strategies of the crate's executors aren't
+/// exported, a custom executor needs a strategy type of its own, usually an
+/// empty enum. Don't implement operator traits for the same type, or its
+/// strategy can't be inferred when it's passed to an optimizer.
+///
+/// Executor traits aren't sealed, but they aren't stable either: they may
+/// change between minor versions, though provided methods are preferred to
+/// changes of required ones. Operator traits stay the stable way to plug
+/// into optimizers.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   experimental::executor::TestExecutor,
+///   optimizer::{nsga::Nsga2, Optimizer},
+///   score::Scores,
+///   selection::RandomSelector,
+///   termination::GenerationTerminator,
+/// };
+///
+/// /// Execution strategy of `SquaresTester`.
+/// enum Vectorized {}
+///
+/// /// Tests all solutions in a single vectorized call.
+/// struct SquaresTester;
+///
+/// impl TestExecutor<f32, 2, Vectorized> for SquaresTester {
+///   fn execute_tests(&self, solutions: &[f32]) -> Vec<Scores<2>> {
+///     let squares: Vec<f32> = solutions.iter().map(|x| x * x).collect();
+///     let shifted: Vec<f32> = solutions.iter().map(|x| x - 2.0).collect();
+///     squares
+///       .into_iter()
+///       .zip(shifted.iter().map(|x| x * x))
+///       .map(|(a, b)| [a, b])
+///       .collect()
+///   }
+/// }
+///
+/// let solutions = Nsga2::builder()
+///   .population(vec![-1.0, 0.5, 3.0])
+///   .tester(SquaresTester)
+///   .selector(RandomSelector(3))
+///   .recombinator(|a: &f32, b: &f32| (a + b) / 2.0)
+///   .mutator(|_: &mut f32| {})
+///   .terminator(GenerationTerminator(10))
+///   .build()
+///   .optimize();
+/// assert!(solutions.iter().all(|x| (-1.0..=3.0).contains(x)));
+/// ```
+///
+/// [`Test`]: crate::testing::Test
+/// [`Tester`]: crate::testing::Tester
+pub mod executor {
+  pub use crate::{
+    constraining::executor::{ConstraintExecutor, RepairExecutor},
+    mutation::executor::MutationExecutor,
+    recombination::executor::RecombinationExecutor,
+    selection::executor::SelectionExecutor,
+    termination::executor::TerminationExecutor,
+    testing::executor::TestExecutor,
+  };
+}
@@ -0,0 +1,342 @@
+//! Export of solutions and their scores.
+
+use std::io::{self, Write};
+
+#[cfg(feature = "serde_json")]
+use crate::score::ScoredSolution;
+use crate::score::{ObjectiveLabels, Scores};
+
+/// Writes solutions and their scores as CSV with a header. Each row contains
+/// scores of a solution, named `objective_0`, `objective_1`, etc., followed by
+/// `C` columns returned by `solution_formatter`, named `solution_0`,
+/// `solution_1`, etc. Fields that contain commas, quotes or line breaks are
+/// quoted.
+///
+/// Returns an error of [`io::ErrorKind::InvalidInput`] kind if the number of
+/// solutions doesn't match the number of scores.
+///
+/// # Examples
+/// ```
+/// use moga::export::write_csv;
+///
+/// let solutions = [(1.0, "a"), (2.0, "b, c")];
+/// let scores = [[1.0, 4.0], [4.0, 1.0]];
+/// let mut csv = Vec::new();
+/// write_csv(&mut csv, &solutions, &scores, |(x, name)| {
+///   [x.to_string(), name.to_string()]
+/// })
+/// .unwrap();
+/// assert_eq!(
+///   String::from_utf8(csv).unwrap(),
+///   "objective_0,objective_1,solution_0,solution_1\n\
+///    1,4,1,a\n\
+///    4,1,2,\"b, c\"\n"
+/// );
+/// ```
+pub fn write_csv<W: Write, S, const N: usize, const C: usize>(
+  writer: W,
+  solutions: &[S],
+  scores: &[Scores<N>],
+  solution_formatter: impl Fn(&S) -> [String; C],
+) -> io::Result<()> {
+  let objective_columns = (0..N).map(|o_idx| format!("objective_{o_idx}"));
+  write_csv_with_columns(
+    writer,
+    solutions,
+    scores,
+    objective_columns,
+    solution_formatter,
+  )
+}
+
+/// Writes solutions and their scores as CSV with a header, like
+/// [`write_csv`] does, but names columns of scores by their `labels`.
+///
+/// # Examples
+/// ```
+/// use moga::{export::write_labeled_csv, score::ObjectiveLabels};
+///
+/// let mut csv = Vec::new();
+/// let labels = ObjectiveLabels(["cost", "weight"]);
+/// write_labeled_csv(&mut csv, &["a"], &[[1.0, 4.0]], &labels, |name| {
+///   [name.to_string()]
+/// })
+/// .unwrap();
+/// assert_eq!(
+///   String::from_utf8(csv).unwrap(),
+///   "cost,weight,solution_0\n1,4,a\n"
+/// );
+/// ```
+pub fn write_labeled_csv<W: Write, S, const N: usize, const C: usize>(
+  writer: W,
+  solutions: &[S],
+  scores: &[Scores<N>],
+  labels: &ObjectiveLabels<N>,
+  solution_formatter: impl Fn(&S) -> [String; C],
+) -> io::Result<()> {
+  write_csv_with_columns(
+    writer,
+    solutions,
+    scores,
+    labels.iter().map(str::to_owned),
+    solution_formatter,
+  )
+}
+
+fn write_csv_with_columns<W: Write, S, const N: usize, const C: usize>(
+  mut writer: W,
+  solutions: &[S],
+  scores: &[Scores<N>],
+  objective_columns: impl Iterator<Item = String>,
+  solution_formatter: impl Fn(&S) -> [String; C],
+) -> io::Result<()> {
+  check_lengths(solutions, scores)?;
+
+  let solution_columns = (0..C).map(|c_idx| format!("solution_{c_idx}"));
+  write_row(&mut writer, objective_columns.chain(solution_columns))?;
+  for (solution, scores) in solutions.iter().zip(scores) {
+    let score_fields = scores.iter().map(|s| s.to_string());
+    write_row(
+      &mut writer,
+      score_fields.chain(solution_formatter(solution)),
+    )?;
+  }
+  writer.flush()
+}
+
+/// Writes solutions and their scores as a JSON array of objects with
+/// `solution` and `scores` fields, i.e. an array of [`ScoredSolution`]s.
+///
+/// Returns an error of [`io::ErrorKind::InvalidInput`] kind if the number of
+/// solutions doesn't match the number of scores.
+///
+/// # Examples
+/// ```
+/// use moga::export::write_json;
+///
+/// let mut json = Vec::new();
+/// write_json(&mut json, &["a", "b"], &[[1.0], [2.0]]).unwrap();
+/// assert_eq!(
+///   String::from_utf8(json).unwrap(),
+///   r#"[{"solution":"a","scores":[1.0]},{"solution":"b","scores":[2.0]}]"#
+/// );
+/// ```
+#[cfg(feature = "serde_json")]
+pub fn write_json<W: Write, S: serde::Serialize, const N: usize>(
+  mut writer: W,
+  solutions: &[S],
+  scores: &[Scores<N>],
+) -> io::Result<()> {
+  check_lengths(solutions, scores)?;
+  let entries: Vec<_> = solutions
+    .iter()
+    .zip(scores)
+    .map(|(solution, &scores)| ScoredSolution { solution, scores })
+    .collect();
+  serde_json::to_writer(&mut writer, &entries)?;
+  writer.flush()
+}
+
+/// Writes solutions and their scores as a JSON array of objects with
+/// `solution` and `scores` fields, like [`write_json`] does, but writes
+/// scores as objects with a field per objective named by its label.
+///
+/// # Examples
+/// ```
+/// use moga::{export::write_labeled_json, score::ObjectiveLabels};
+///
+/// let mut json = Vec::new();
+/// let labels = ObjectiveLabels(["cost", "weight"]);
+/// write_labeled_json(&mut json, &["a"], &[[1.0, 2.0]], &labels).unwrap();
+/// assert_eq!(
+///   String::from_utf8(json).unwrap(),
+///   r#"[{"solution":"a","scores":{"cost":1.0,"weight":2.0}}]"#
+/// );
+/// ```
+#[cfg(feature = "serde_json")]
+pub fn write_labeled_json<W: Write, S: serde::Serialize, const N: usize>(
+  mut writer: W,
+  solutions: &[S],
+  scores: &[Scores<N>],
+  labels: &ObjectiveLabels<N>,
+) -> io::Result<()> {
+  /// Scores serialized as a map from labels of objectives to scores.
+  struct LabeledScores<'a, const N: usize> {
+    labels: &'a ObjectiveLabels<N>,
+    scores: &'a Scores<N>,
+  }
+
+  impl<const N: usize> serde::Serialize for LabeledScores<'_, N> {
+    fn serialize<Ser: serde::Serializer>(
+      &self,
+      serializer: Ser,
+    ) -> Result<Ser::Ok, Ser::Error> {
+      serializer.collect_map(self.labels.iter().zip(self.scores))
+    }
+  }
+
+  /// A solution paired with its labeled scores.
+  #[derive(serde::Serialize)]
+  struct LabeledEntry<'a, S, const N: usize> {
+    solution: &'a S,
+    scores: LabeledScores<'a, N>,
+  }
+
+  check_lengths(solutions, scores)?;
+  let entries: Vec<_> = solutions
+    .iter()
+    .zip(scores)
+    .map(|(solution, scores)| LabeledEntry {
+      solution,
+      scores: LabeledScores { labels, scores },
+    })
+    .collect();
+  serde_json::to_writer(&mut writer, &entries)?;
+  writer.flush()
+}
+
+fn check_lengths<S, const N: usize>(
+  solutions: &[S],
+  scores: &[Scores<N>],
+) -> io::Result<()> {
+  if solutions.len() == scores.len() {
+    Ok(())
+  } else {
+    Err(io::Error::new(
+      io::ErrorKind::InvalidInput,
+      format!(
+        "the number of solutions ({}) doesn't match the number of scores ({})",
+        solutions.len(),
+        scores.len()
+      ),
+    ))
+  }
+}
+
+/// Writes a row of CSV fields, quoting them if necessary.
+pub(crate) fn write_row(
+  writer: &mut impl Write,
+  fields: impl IntoIterator<Item = String>,
+) -> io::Result<()> {
+  for (i, field) in fields.into_iter().enumerate() {
+    if i > 0 {
+      writer.write_all(b",")?;
+    }
+    if field.contains([',', '"', '\n', '\r']) {
+      write!(writer, "\"{}\"", field.replace('"', "\"\""))?;
+    } else {
+      writer.write_all(field.as_bytes())?;
+    }
+  }
+  writer.write_all(b"\n")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn csv<S, const N: usize, const C: usize>(
+    solutions: &[S],
+    scores: &[Scores<N>],
+    solution_formatter: impl Fn(&S) -> [String; C],
+  ) -> String {
+    let mut csv = Vec::new();
+    write_csv(&mut csv, solutions, scores, solution_formatter).unwrap();
+    String::from_utf8(csv).unwrap()
+  }
+
+  #[test]
+  fn test_write_csv_escaping() {
+    let solutions = ["plain", "with \"quotes\"", "multi\nline", "a,b"];
+    let scores = [[0.5]; 4];
+    assert_eq!(
+      csv(&solutions, &scores, |s| [s.to_string()]),
+      "objective_0,solution_0\n\
+       0.5,plain\n\
+       0.5,\"with \"\"quotes\"\"\"\n\
+       0.5,\"multi\nline\"\n\
+       0.5,\"a,b\"\n"
+    );
+  }
+
+  #[test]
+  fn test_write_csv_without_solution_columns() {
+    assert_eq!(
+      csv(&[(), ()], &[[1.0, -2.0], [3.0, 4.5]], |_| []),
+      "objective_0,objective_1\n1,-2\n3,4.5\n"
+    );
+    assert_eq!(
+      csv::<(), 2, 0>(&[], &[], |_| []),
+      "objective_0,objective_1\n"
+    );
+  }
+
+  #[test]
+  fn test_write_labeled_csv() {
+    let mut csv = Vec::new();
+    let labels = ObjectiveLabels(["cost, total", "weight"]);
+    write_labeled_csv(
+      &mut csv,
+      &[(), ()],
+      &[[1.0, 2.0], [3.0, 4.0]],
+      &labels,
+      |_| [],
+    )
+    .unwrap();
+    assert_eq!(
+      String::from_utf8(csv).unwrap(),
+      "\"cost, total\",weight\n1,2\n3,4\n"
+    );
+    let err =
+      write_labeled_csv(Vec::new(), &[1], &[], &labels, |_| []).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+  }
+
+  #[test]
+  fn test_write_csv_length_mismatch() {
+    let err = write_csv(Vec::new(), &[1, 2], &[[0.0]], |s| [s.to_string()])
+      .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+  }
+
+  #[cfg(feature = "serde_json")]
+  #[test]
+  fn test_write_json() {
+    #[derive(serde::Serialize)]
+    struct Point {
+      x: f32,
+      y: f32,
+    }
+
+    let mut json = Vec::new();
+    let solutions = [Point { x: 1.0, y: 2.0 }];
+    write_json(&mut json, &solutions, &[[0.5, 0.25]]).unwrap();
+    assert_eq!(
+      String::from_utf8(json).unwrap(),
+      r#"[{"solution":{"x":1.0,"y":2.0},"scores":[0.5,0.25]}]"#
+    );
+    assert_eq!(
+      write_json::<_, _, 2>(Vec::new(), &solutions, &[])
+        .unwrap_err()
+        .kind(),
+      io::ErrorKind::InvalidInput
+    );
+  }
+
+  #[cfg(feature = "serde_json")]
+  #[test]
+  fn test_write_labeled_json() {
+    let mut json = Vec::new();
+    let labels = ObjectiveLabels(["cost", "weight"]);
+    write_labeled_json(&mut json, &[1, 2], &[[0.5, 0.25], [1.0, 2.0]], &labels)
+      .unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&json).unwrap();
+    assert_eq!(
+      json,
+      serde_json::json!([
+        { "solution": 1, "scores": { "cost": 0.5, "weight": 0.25 } },
+        { "solution": 2, "scores": { "cost": 1.0, "weight": 2.0 } },
+      ])
+    );
+  }
+}
@@ -0,0 +1,5 @@
+//! Utilities for common representations of solutions.
+
+pub mod binary;
+pub mod permutation;
+pub mod real;
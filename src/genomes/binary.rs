@@ -0,0 +1,125 @@
+//! Solutions represented as vectors of bits.
+
+use rand::{rngs::SmallRng, Rng};
+
+use crate::{mutation::RandomMutation, recombination::RandomRecombination};
+
+/// Uniform crossover of two bit vectors into two offsprings: each pair of
+/// bits is swapped between offsprings with probability `0.5`.
+///
+/// Solutions are bit vectors, i.e. any
+/// `S: AsRef<[bool]> + AsMut<[bool]> + Clone`. Apply it with an
+/// [`IndexedRandomRecombination`].
+///
+/// # Panics
+///
+/// Panics if parents have different lengths.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   genomes::binary::UniformCrossover,
+///   recombination::RandomRecombination,
+/// };
+/// use rand::{rngs::SmallRng, SeedableRng};
+///
+/// let (a, b) = (vec![true; 8], vec![false; 8]);
+/// let [c, d] =
+///   UniformCrossover.recombine_with([&a, &b], &mut SmallRng::seed_from_u64(0));
+/// assert!(c.iter().zip(&d).all(|(c, d)| c != d));
+/// ```
+///
+/// [`IndexedRandomRecombination`]: crate::recombination::IndexedRandomRecombination
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UniformCrossover;
+
+impl<S> RandomRecombination<S, 2, 2> for UniformCrossover
+where
+  S: AsRef<[bool]> + AsMut<[bool]> + Clone,
+{
+  fn recombine_with(&self, [a, b]: [&S; 2], rng: &mut SmallRng) -> [S; 2] {
+    assert_eq!(
+      a.as_ref().len(),
+      b.as_ref().len(),
+      "parents must have the same length"
+    );
+    let (mut c, mut d) = (a.clone(), b.clone());
+    for (x, y) in c.as_mut().iter_mut().zip(d.as_mut()) {
+      if rng.gen_bool(0.5) {
+        std::mem::swap(x, y);
+      }
+    }
+    [c, d]
+  }
+}
+
+/// Bit-flip mutation of a bit vector: each bit is flipped with the given
+/// probability. A probability of `1 / length` flips one bit per solution on
+/// average.
+///
+/// Solutions are bit vectors, i.e. any `S: AsMut<[bool]>`. Apply it with an
+/// [`IndexedRandomMutation`].
+///
+/// # Panics
+///
+/// Panics if the probability isn't between `0` and `1`.
+///
+/// # Examples
+/// ```
+/// use moga::{genomes::binary::BitFlipMutation, mutation::RandomMutation};
+/// use rand::{rngs::SmallRng, SeedableRng};
+///
+/// let mut x = vec![false; 4];
+/// BitFlipMutation(1.0).mutate_with(&mut x, &mut SmallRng::seed_from_u64(0));
+/// assert_eq!(x, [true; 4]);
+/// ```
+///
+/// [`IndexedRandomMutation`]: crate::mutation::IndexedRandomMutation
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BitFlipMutation(pub f64);
+
+impl<S: AsMut<[bool]>> RandomMutation<S> for BitFlipMutation {
+  fn mutate_with(&self, solution: &mut S, rng: &mut SmallRng) {
+    for bit in solution.as_mut() {
+      if rng.gen_bool(self.0) {
+        *bit = !*bit;
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::SeedableRng;
+
+  use super::*;
+
+  #[test]
+  fn test_uniform_crossover() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let (a, b) = (vec![true, false, true, false], vec![false; 4]);
+    let mut swapped = 0;
+    for _ in 0..250 {
+      let [c, d] = UniformCrossover.recombine_with([&a, &b], &mut rng);
+      // offsprings share bits of their parents
+      for i in 0..4 {
+        assert_eq!(c[i] as u8 + d[i] as u8, a[i] as u8 + b[i] as u8);
+      }
+      swapped += (c[0] != a[0]) as usize;
+    }
+    assert!((100..150).contains(&swapped), "{swapped}");
+  }
+
+  #[test]
+  fn test_bit_flip_mutation() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut x = vec![false; 1000];
+    BitFlipMutation(0.0).mutate_with(&mut x, &mut rng);
+    assert!(x.iter().all(|bit| !bit));
+    BitFlipMutation(0.1).mutate_with(&mut x, &mut rng);
+    let flipped = x.iter().filter(|&&bit| bit).count();
+    assert!((50..150).contains(&flipped), "{flipped}");
+  }
+}
@@ -0,0 +1,170 @@
+//! Solutions represented as permutations of indices `0..n`, such as orders of
+//! cities of a tour.
+
+use rand::{rngs::SmallRng, Rng};
+
+use crate::{mutation::RandomMutation, recombination::RandomRecombination};
+
+/// Order crossover (OX) of two permutations into two offsprings. Each
+/// offspring keeps a random segment of one parent in place and fills the
+/// rest with the remaining indices in the order they follow the segment in
+/// the other parent, so offsprings inherit both positions and relative
+/// orders of indices.
+///
+/// Solutions are permutations of indices `0..n`, i.e. any
+/// `S: AsRef<[usize]> + AsMut<[usize]> + Clone`. Apply it with an
+/// [`IndexedRandomRecombination`].
+///
+/// # Panics
+///
+/// Panics if parents have different lengths or if an index of a parent is
+/// out of `0..n`.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   genomes::permutation::OrderCrossover,
+///   recombination::RandomRecombination,
+/// };
+/// use rand::{rngs::SmallRng, SeedableRng};
+///
+/// let (a, b) = (vec![0, 1, 2, 3, 4], vec![4, 3, 2, 1, 0]);
+/// let [mut c, _] =
+///   OrderCrossover.recombine_with([&a, &b], &mut SmallRng::seed_from_u64(0));
+/// c.sort();
+/// assert_eq!(c, [0, 1, 2, 3, 4]);
+/// ```
+///
+/// [`IndexedRandomRecombination`]: crate::recombination::IndexedRandomRecombination
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrderCrossover;
+
+/// Returns the offspring of order crossover that keeps the `segment` of the
+/// `kept` parent and takes the rest from the `other` one.
+fn order_offspring<S>(kept: &S, other: &S, segment: std::ops::Range<usize>) -> S
+where
+  S: AsRef<[usize]> + AsMut<[usize]> + Clone,
+{
+  let (k, o) = (kept.as_ref(), other.as_ref());
+  let n = k.len();
+  let mut is_kept = vec![false; n];
+  k[segment.clone()]
+    .iter()
+    .for_each(|&idx| is_kept[idx] = true);
+  let mut offspring = kept.clone();
+  let mut rest = (segment.end..n)
+    .chain(0..segment.end)
+    .map(|pos| o[pos])
+    .filter(|&idx| !is_kept[idx]);
+  for pos in (segment.end..n).chain(0..segment.start) {
+    offspring.as_mut()[pos] =
+      rest.next().expect("parents must be permutations");
+  }
+  offspring
+}
+
+impl<S> RandomRecombination<S, 2, 2> for OrderCrossover
+where
+  S: AsRef<[usize]> + AsMut<[usize]> + Clone,
+{
+  fn recombine_with(&self, [a, b]: [&S; 2], rng: &mut SmallRng) -> [S; 2] {
+    let n = a.as_ref().len();
+    assert_eq!(n, b.as_ref().len(), "parents must have the same length");
+    if n < 2 {
+      return [a.clone(), b.clone()];
+    }
+    let (start, end) = (rng.gen_range(0..n), rng.gen_range(0..n));
+    let segment = start.min(end)..start.max(end) + 1;
+    [
+      order_offspring(a, b, segment.clone()),
+      order_offspring(b, a, segment),
+    ]
+  }
+}
+
+/// Swap mutation of a permutation: each position is swapped with a random
+/// position with the given probability. A probability of `1 / n` makes one
+/// swap per solution on average.
+///
+/// Solutions are permutations, i.e. any `S: AsMut<[usize]>`. Apply it with
+/// an [`IndexedRandomMutation`].
+///
+/// # Panics
+///
+/// Panics if the probability isn't between `0` and `1`.
+///
+/// # Examples
+/// ```
+/// use moga::{genomes::permutation::SwapMutation, mutation::RandomMutation};
+/// use rand::{rngs::SmallRng, SeedableRng};
+///
+/// let mut x = vec![0, 1, 2, 3];
+/// SwapMutation(0.5).mutate_with(&mut x, &mut SmallRng::seed_from_u64(0));
+/// x.sort();
+/// assert_eq!(x, [0, 1, 2, 3]);
+/// ```
+///
+/// [`IndexedRandomMutation`]: crate::mutation::IndexedRandomMutation
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SwapMutation(pub f64);
+
+impl<S: AsMut<[usize]>> RandomMutation<S> for SwapMutation {
+  fn mutate_with(&self, solution: &mut S, rng: &mut SmallRng) {
+    let x = solution.as_mut();
+    for pos in 0..x.len() {
+      if rng.gen_bool(self.0) {
+        let other = rng.gen_range(0..x.len());
+        x.swap(pos, other);
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::SeedableRng;
+
+  use super::*;
+
+  fn is_permutation(x: &[usize]) -> bool {
+    let mut sorted = x.to_vec();
+    sorted.sort();
+    sorted.into_iter().eq(0..x.len())
+  }
+
+  #[test]
+  fn test_order_offspring() {
+    let (a, b) = (vec![0, 1, 2, 3, 4, 5], vec![5, 3, 1, 0, 4, 2]);
+    // `1, 2` are kept, the rest follows `0, 4, 2, 5, 3, 1` of `b`
+    assert_eq!(order_offspring(&a, &b, 1..3), [3, 1, 2, 0, 4, 5]);
+    assert_eq!(order_offspring(&a, &b, 0..6), a);
+  }
+
+  #[test]
+  fn test_order_crossover() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let (a, b) = (vec![0, 1, 2, 3, 4, 5, 6], vec![6, 2, 4, 0, 5, 1, 3]);
+    for _ in 0..100 {
+      let [c, d] = OrderCrossover.recombine_with([&a, &b], &mut rng);
+      assert!(is_permutation(&c) && is_permutation(&d), "{c:?} {d:?}");
+    }
+    let single = vec![0];
+    assert_eq!(
+      OrderCrossover.recombine_with([&single, &single], &mut rng),
+      [[0], [0]]
+    );
+  }
+
+  #[test]
+  fn test_swap_mutation() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut x: Vec<_> = (0..100).collect();
+    SwapMutation(0.0).mutate_with(&mut x, &mut rng);
+    assert!(x.iter().copied().eq(0..100));
+    SwapMutation(0.1).mutate_with(&mut x, &mut rng);
+    assert!(is_permutation(&x));
+    assert!(!x.iter().copied().eq(0..100));
+  }
+}
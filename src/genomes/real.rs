@@ -0,0 +1,605 @@
+//! Solutions represented as vectors of real numbers.
+
+use std::ops::RangeInclusive;
+
+use rand::{rngs::SmallRng, Rng};
+
+use crate::{
+  constraining::Repair,
+  mutation::{Mutation, RandomMutation},
+  recombination::RandomRecombination,
+};
+
+/// Lower and upper bounds of each dimension of real vectors.
+///
+/// # Examples
+/// ```
+/// use moga::genomes::real::Bounds;
+///
+/// let bounds = Bounds::new(vec![0.0, -1.0], vec![5.0, 1.0]);
+/// assert_eq!(bounds.dimension(), 2);
+/// assert!(bounds.contains(&[2.5, 0.0]));
+/// assert!(!bounds.contains(&[2.5, 2.0]));
+/// assert_eq!(Bounds::uniform(2, 0.0..=1.0).upper(), [1.0, 1.0]);
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct Bounds {
+  lower: Vec<f32>,
+  upper: Vec<f32>,
+}
+
+impl Bounds {
+  /// Creates bounds from `lower` and `upper` bounds of each dimension.
+  ///
+  /// # Panics
+  ///
+  /// Panics if lengths of `lower` and `upper` differ, or if any lower bound
+  /// is greater than its upper bound or is `NaN`.
+  pub fn new(lower: Vec<f32>, upper: Vec<f32>) -> Self {
+    assert_eq!(
+      lower.len(),
+      upper.len(),
+      "the number of lower bounds doesn't match the number of upper bounds"
+    );
+    assert!(
+      lower.iter().zip(&upper).all(|(l, u)| l <= u),
+      "lower bounds must not be greater than upper bounds"
+    );
+    Self { lower, upper }
+  }
+
+  /// Creates bounds with the same `range` for each of `dimension` dimensions.
+  ///
+  /// # Panics
+  ///
+  /// Panics if start of the `range` is greater than its end or is `NaN`.
+  pub fn uniform(dimension: usize, range: RangeInclusive<f32>) -> Self {
+    let (lower, upper) = range.into_inner();
+    Self::new(vec![lower; dimension], vec![upper; dimension])
+  }
+
+  /// Returns the number of dimensions.
+  pub fn dimension(&self) -> usize {
+    self.lower.len()
+  }
+
+  /// Returns lower bounds of each dimension.
+  pub fn lower(&self) -> &[f32] {
+    &self.lower
+  }
+
+  /// Returns upper bounds of each dimension.
+  pub fn upper(&self) -> &[f32] {
+    &self.upper
+  }
+
+  /// Returns an iterator over pairs of lower and upper bounds of each
+  /// dimension.
+  pub fn iter(&self) -> impl ExactSizeIterator<Item = (f32, f32)> + '_ {
+    self.lower.iter().copied().zip(self.upper.iter().copied())
+  }
+
+  /// Checks whether the vector `x` has the same number of dimensions and each
+  /// of its values lies within bounds.
+  pub fn contains(&self, x: &[f32]) -> bool {
+    x.len() == self.dimension()
+      && self.iter().zip(x).all(|((l, u), v)| (l..=u).contains(v))
+  }
+}
+
+/// A strategy of moving values that lie outside of their [`Bounds`] back
+/// within them.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub enum RepairStrategy {
+  /// Replaces a value with its nearest bound.
+  #[default]
+  Clamp,
+  /// Reflects a value from the bound it has crossed, as many times as needed
+  /// to get it within bounds. E.g., `1.5` is reflected to `0.5` within
+  /// bounds `0` and `1`.
+  Reflect,
+  /// Wraps a value around bounds, as if they were connected. E.g., `1.25` is
+  /// wrapped to `0.25` within bounds `0` and `1`.
+  Wrap,
+}
+
+impl Bounds {
+  /// Moves values of the vector `x` that lie outside of bounds within them
+  /// with the given `strategy`. Infinite values are clamped regardless of
+  /// the strategy, `NaN`s are left as is.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the number of values doesn't match the number of dimensions.
+  ///
+  /// # Examples
+  /// ```
+  /// use moga::genomes::real::{Bounds, RepairStrategy};
+  ///
+  /// let bounds = Bounds::uniform(3, 0.0..=1.0);
+  /// let mut x = [-0.25, 0.5, 1.25];
+  /// bounds.repair(&mut x, RepairStrategy::Reflect);
+  /// assert_eq!(x, [0.25, 0.5, 0.75]);
+  /// ```
+  pub fn repair<V: AsMut<[f32]> + ?Sized>(
+    &self,
+    x: &mut V,
+    strategy: RepairStrategy,
+  ) {
+    let x = x.as_mut();
+    assert_eq!(
+      x.len(),
+      self.dimension(),
+      "the number of values doesn't match the number of dimensions"
+    );
+    for ((lower, upper), v) in self.iter().zip(x) {
+      if (lower..=upper).contains(v) || v.is_nan() {
+        continue;
+      }
+      let width = upper - lower;
+      *v = match strategy {
+        _ if v.is_infinite() || width == 0.0 => v.clamp(lower, upper),
+        RepairStrategy::Clamp => v.clamp(lower, upper),
+        RepairStrategy::Reflect => {
+          let t = (*v - lower).rem_euclid(2.0 * width);
+          lower + if t > width { 2.0 * width - t } else { t }
+        }
+        RepairStrategy::Wrap => lower + (*v - lower).rem_euclid(width),
+      }
+      // rounding must not push the value outside of bounds
+      .clamp(lower, upper);
+    }
+  }
+
+  /// Replaces values of the vector `x` that lie outside of bounds with their
+  /// nearest bounds. See [`RepairStrategy::Clamp`].
+  ///
+  /// # Panics
+  ///
+  /// Panics if the number of values doesn't match the number of dimensions.
+  pub fn clamp<V: AsMut<[f32]> + ?Sized>(&self, x: &mut V) {
+    self.repair(x, RepairStrategy::Clamp)
+  }
+
+  /// Reflects values of the vector `x` that lie outside of bounds from the
+  /// bounds they've crossed. See [`RepairStrategy::Reflect`].
+  ///
+  /// # Panics
+  ///
+  /// Panics if the number of values doesn't match the number of dimensions.
+  pub fn reflect<V: AsMut<[f32]> + ?Sized>(&self, x: &mut V) {
+    self.repair(x, RepairStrategy::Reflect)
+  }
+
+  /// Wraps values of the vector `x` that lie outside of bounds around them.
+  /// See [`RepairStrategy::Wrap`].
+  ///
+  /// # Panics
+  ///
+  /// Panics if the number of values doesn't match the number of dimensions.
+  pub fn wrap<V: AsMut<[f32]> + ?Sized>(&self, x: &mut V) {
+    self.repair(x, RepairStrategy::Wrap)
+  }
+}
+
+/// A [`Mutation`] that moves values of solutions outside of its [`Bounds`]
+/// back within them with its [`RepairStrategy`]. Solutions are real vectors,
+/// i.e. any `S: AsMut<[f32]>`.
+///
+/// Chain it after your own mutation in a tuple, so that solutions are
+/// repaired right after they are mutated.
+///
+/// # Panics
+///
+/// Panics if the number of values of a solution doesn't match the number of
+/// dimensions of bounds.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   genomes::real::{Bounds, BoundsRepair, RepairStrategy},
+///   mutation::Mutation,
+/// };
+///
+/// let repair =
+///   BoundsRepair::new(Bounds::uniform(2, 0.0..=1.0), RepairStrategy::Wrap);
+/// let mutation = (|x: &mut Vec<f32>| x[0] += 0.5, repair);
+/// let mut x = vec![0.75, 0.5];
+/// mutation.mutate(&mut x);
+/// assert_eq!(x, [0.25, 0.5]);
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct BoundsRepair {
+  bounds: Bounds,
+  strategy: RepairStrategy,
+}
+
+impl BoundsRepair {
+  /// Creates a repair that moves values within `bounds` with `strategy`.
+  pub fn new(bounds: Bounds, strategy: RepairStrategy) -> Self {
+    Self { bounds, strategy }
+  }
+
+  /// Returns bounds of the repair.
+  pub fn bounds(&self) -> &Bounds {
+    &self.bounds
+  }
+
+  /// Returns the strategy of the repair.
+  pub fn strategy(&self) -> RepairStrategy {
+    self.strategy
+  }
+}
+
+impl<S: AsMut<[f32]>> Mutation<S> for BoundsRepair {
+  fn mutate(&self, solution: &mut S) {
+    self.bounds.repair(solution, self.strategy)
+  }
+}
+
+impl<S: AsMut<[f32]>> Repair<S> for BoundsRepair {
+  fn repair(&self, solution: &mut S) {
+    self.bounds.repair(solution, self.strategy)
+  }
+}
+
+/// Panics if the distribution index `eta` is negative or NaN.
+fn check_distribution_index(eta: f32) {
+  if eta.is_nan() || eta < 0.0 {
+    panic!("distribution index must not be negative")
+  }
+}
+
+/// Simulated binary crossover (SBX) of two real vectors within [`Bounds`]
+/// into two offsprings. Each pair of values is recombined with probability
+/// `0.5` into a pair spread around their mean the way one-point crossover
+/// spreads binary strings. The larger the distribution index, the closer
+/// offsprings stay to their parents. Offsprings are kept within bounds.
+///
+/// Solutions are real vectors, i.e. any `S: AsRef<[f32]> + AsMut<[f32]>`.
+/// Apply it with an [`IndexedRandomRecombination`].
+///
+/// # Panics
+///
+/// Panics if the number of values of a parent doesn't match the number of
+/// dimensions of bounds.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   genomes::real::{Bounds, SimulatedBinaryCrossover},
+///   recombination::RandomRecombination,
+/// };
+/// use rand::{rngs::SmallRng, SeedableRng};
+///
+/// let bounds = Bounds::uniform(2, 0.0..=1.0);
+/// let sbx = SimulatedBinaryCrossover::new(bounds.clone(), 15.0);
+/// let mut rng = SmallRng::seed_from_u64(0);
+/// let [a, b] = sbx.recombine_with([&vec![0.2, 0.4], &vec![0.6, 0.8]], &mut rng);
+/// assert!(bounds.contains(&a) && bounds.contains(&b));
+/// ```
+///
+/// [`IndexedRandomRecombination`]: crate::recombination::IndexedRandomRecombination
+#[derive(Clone, PartialEq, Debug)]
+pub struct SimulatedBinaryCrossover {
+  bounds: Bounds,
+  distribution_index: f32,
+}
+
+impl SimulatedBinaryCrossover {
+  /// Creates a crossover of vectors within `bounds` with the
+  /// `distribution_index`, usually between `2` and `30`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the `distribution_index` is negative or NaN.
+  pub fn new(bounds: Bounds, distribution_index: f32) -> Self {
+    check_distribution_index(distribution_index);
+    Self {
+      bounds,
+      distribution_index,
+    }
+  }
+
+  /// Returns bounds of the crossover.
+  pub fn bounds(&self) -> &Bounds {
+    &self.bounds
+  }
+
+  /// Returns the distribution index of the crossover.
+  pub fn distribution_index(&self) -> f32 {
+    self.distribution_index
+  }
+
+  /// Returns the spread factor of the offspring on the side of the parent
+  /// `gap` away from its bound, where `distance` is between the parents and
+  /// `u` is uniform in `[0, 1)`.
+  fn spread(&self, gap: f32, distance: f32, u: f32) -> f32 {
+    let exponent = self.distribution_index + 1.0;
+    let beta = 1.0 + 2.0 * gap / distance;
+    let alpha = 2.0 - beta.powf(-exponent);
+    if u <= 1.0 / alpha {
+      (u * alpha).powf(1.0 / exponent)
+    } else {
+      (1.0 / (2.0 - u * alpha)).powf(1.0 / exponent)
+    }
+  }
+}
+
+impl<S> RandomRecombination<S, 2, 2> for SimulatedBinaryCrossover
+where
+  S: AsRef<[f32]> + AsMut<[f32]> + Clone,
+{
+  fn recombine_with(&self, [a, b]: [&S; 2], rng: &mut SmallRng) -> [S; 2] {
+    assert!(
+      a.as_ref().len() == self.bounds.dimension()
+        && b.as_ref().len() == self.bounds.dimension(),
+      "the number of values doesn't match the number of dimensions"
+    );
+    let (mut c, mut d) = (a.clone(), b.clone());
+    for (dimension, (lower, upper)) in self.bounds.iter().enumerate() {
+      let (x, y) = (a.as_ref()[dimension], b.as_ref()[dimension]);
+      let distance = (x - y).abs();
+      if !rng.gen_bool(0.5) || distance <= f32::EPSILON * x.abs().max(1.0) {
+        continue;
+      }
+      let (low, high) = (x.min(y), x.max(y));
+      let mean = (low + high) / 2.0;
+      let u = rng.gen::<f32>();
+      let below = mean - self.spread(low - lower, distance, u) * distance / 2.0;
+      let above =
+        mean + self.spread(upper - high, distance, u) * distance / 2.0;
+      let (mut below, mut above) =
+        (below.clamp(lower, upper), above.clamp(lower, upper));
+      if rng.gen_bool(0.5) {
+        std::mem::swap(&mut below, &mut above);
+      }
+      c.as_mut()[dimension] = below;
+      d.as_mut()[dimension] = above;
+    }
+    [c, d]
+  }
+}
+
+/// Polynomial mutation of a real vector within [`Bounds`]. Each value is
+/// mutated with a probability, `1 / dimension` by default, by a random
+/// shift that the polynomial distribution keeps small and the bounds keep
+/// within them. The larger the distribution index, the smaller shifts are.
+///
+/// Solutions are real vectors, i.e. any `S: AsMut<[f32]>`. Apply it with an
+/// [`IndexedRandomMutation`].
+///
+/// # Panics
+///
+/// Panics if the number of values of a solution doesn't match the number of
+/// dimensions of bounds.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   genomes::real::{Bounds, PolynomialMutation},
+///   mutation::RandomMutation,
+/// };
+/// use rand::{rngs::SmallRng, SeedableRng};
+///
+/// let bounds = Bounds::uniform(2, 0.0..=1.0);
+/// let m = PolynomialMutation::new(bounds.clone(), 20.0).with_probability(1.0);
+/// let mut x = vec![0.5, 1.0];
+/// m.mutate_with(&mut x, &mut SmallRng::seed_from_u64(0));
+/// assert!(bounds.contains(&x));
+/// assert_ne!(x, [0.5, 1.0]);
+/// ```
+///
+/// [`IndexedRandomMutation`]: crate::mutation::IndexedRandomMutation
+#[derive(Clone, PartialEq, Debug)]
+pub struct PolynomialMutation {
+  bounds: Bounds,
+  distribution_index: f32,
+  probability: f64,
+}
+
+impl PolynomialMutation {
+  /// Creates a mutation of vectors within `bounds` with the
+  /// `distribution_index`, usually between `5` and `100`, that mutates each
+  /// value with probability `1 / dimension`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the `distribution_index` is negative or NaN.
+  pub fn new(bounds: Bounds, distribution_index: f32) -> Self {
+    check_distribution_index(distribution_index);
+    let probability = 1.0 / bounds.dimension().max(1) as f64;
+    Self {
+      bounds,
+      distribution_index,
+      probability,
+    }
+  }
+
+  /// Sets the `probability` of each value to be mutated.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the `probability` isn't between `0` and `1`.
+  pub fn with_probability(self, probability: f64) -> Self {
+    assert!(
+      (0.0..=1.0).contains(&probability),
+      "probability must be between 0 and 1"
+    );
+    Self {
+      probability,
+      ..self
+    }
+  }
+
+  /// Returns bounds of the mutation.
+  pub fn bounds(&self) -> &Bounds {
+    &self.bounds
+  }
+
+  /// Returns the distribution index of the mutation.
+  pub fn distribution_index(&self) -> f32 {
+    self.distribution_index
+  }
+
+  /// Returns the probability of each value to be mutated.
+  pub fn probability(&self) -> f64 {
+    self.probability
+  }
+}
+
+impl<S: AsMut<[f32]>> RandomMutation<S> for PolynomialMutation {
+  fn mutate_with(&self, solution: &mut S, rng: &mut SmallRng) {
+    let x = solution.as_mut();
+    assert_eq!(
+      x.len(),
+      self.bounds.dimension(),
+      "the number of values doesn't match the number of dimensions"
+    );
+    let exponent = self.distribution_index + 1.0;
+    for ((lower, upper), v) in self.bounds.iter().zip(x) {
+      let width = upper - lower;
+      if !rng.gen_bool(self.probability) || width == 0.0 {
+        continue;
+      }
+      let u = rng.gen::<f32>();
+      let shift = if u < 0.5 {
+        let rest = 1.0 - (*v - lower) / width;
+        let value = 2.0 * u + (1.0 - 2.0 * u) * rest.powf(exponent);
+        value.powf(1.0 / exponent) - 1.0
+      } else {
+        let rest = 1.0 - (upper - *v) / width;
+        let value = 2.0 * (1.0 - u) + 2.0 * (u - 0.5) * rest.powf(exponent);
+        1.0 - value.powf(1.0 / exponent)
+      };
+      *v = (*v + shift * width).clamp(lower, upper);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  #[should_panic]
+  fn test_bounds_length_mismatch() {
+    Bounds::new(vec![0.0], vec![1.0, 2.0]);
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_bounds_inverted() {
+    Bounds::new(vec![0.0, 1.0], vec![1.0, 0.0]);
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_bounds_nan() {
+    Bounds::uniform(1, f32::NAN..=1.0);
+  }
+
+  fn repaired(x: [f32; 5], strategy: RepairStrategy) -> [f32; 5] {
+    let bounds = Bounds::new(vec![0.0; 5], vec![1.0, 1.0, 1.0, 1.0, 0.0]);
+    let mut x = x;
+    bounds.repair(&mut x, strategy);
+    x
+  }
+
+  #[test]
+  fn test_clamp() {
+    assert_eq!(
+      repaired([-0.5, 0.5, 1.5, f32::INFINITY, 3.0], RepairStrategy::Clamp),
+      [0.0, 0.5, 1.0, 1.0, 0.0]
+    );
+  }
+
+  #[test]
+  fn test_reflect() {
+    assert_eq!(
+      repaired([-0.25, 1.5, 2.25, -3.75, 3.0], RepairStrategy::Reflect),
+      [0.25, 0.5, 0.25, 0.25, 0.0]
+    );
+    assert_eq!(
+      repaired(
+        [2.0, -1.0, 1.0, f32::NEG_INFINITY, 0.0],
+        RepairStrategy::Reflect
+      ),
+      [0.0, 1.0, 1.0, 0.0, 0.0]
+    );
+  }
+
+  #[test]
+  fn test_wrap() {
+    assert_eq!(
+      repaired([-0.25, 1.5, 2.25, -3.75, 3.0], RepairStrategy::Wrap),
+      [0.75, 0.5, 0.25, 0.25, 0.0]
+    );
+    let x = repaired([f32::NAN, 1.0, 0.0, 1e30, 0.0], RepairStrategy::Wrap);
+    assert!(x[0].is_nan());
+    assert_eq!(x[1..], [1.0, 0.0, 0.0, 0.0]);
+  }
+
+  #[test]
+  fn test_repair_keeps_values_within_bounds() {
+    let bounds = Bounds::new(vec![-1.1, 0.3], vec![0.7, 0.30001]);
+    for strategy in [
+      RepairStrategy::Clamp,
+      RepairStrategy::Reflect,
+      RepairStrategy::Wrap,
+    ] {
+      for i in -1000..1000 {
+        let mut x = [i as f32 * 0.0137, i as f32 * -7.1];
+        bounds.repair(&mut x, strategy);
+        assert!(bounds.contains(&x), "{x:?} with {strategy:?}");
+      }
+    }
+  }
+
+  #[test]
+  fn test_simulated_binary_crossover() {
+    use rand::SeedableRng;
+
+    let bounds = Bounds::new(vec![0.0, -1.0, 0.0], vec![1.0, 1.0, 0.0]);
+    let sbx = SimulatedBinaryCrossover::new(bounds.clone(), 2.0);
+    let mut rng = SmallRng::seed_from_u64(0);
+    let (mut changed, mut sum) = (0, 0.0);
+    for _ in 0..1000 {
+      let (a, b) = (vec![0.1, -0.5, 0.0], vec![0.9, 0.5, 0.0]);
+      let [c, d] = sbx.recombine_with([&a, &b], &mut rng);
+      assert!(bounds.contains(&c) && bounds.contains(&d), "{c:?} {d:?}");
+      changed += (c[0] != a[0] && c[0] != b[0]) as usize;
+      // offsprings are spread around the mean of their parents
+      sum += c[0] + d[0];
+    }
+    assert!((400..600).contains(&changed), "{changed}");
+    assert!((sum / 1000.0 - 1.0).abs() < 0.05, "{sum}");
+    // equal parents are left as they are
+    let [c, d] = sbx.recombine_with([&[0.5; 3], &[0.5; 3]], &mut rng);
+    assert_eq!((c, d), ([0.5; 3], [0.5; 3]));
+  }
+
+  #[test]
+  fn test_polynomial_mutation() {
+    use rand::SeedableRng;
+
+    let bounds = Bounds::new(vec![0.0, 0.0], vec![1.0, 0.0]);
+    let m = PolynomialMutation::new(bounds.clone(), 20.0);
+    assert_eq!(m.probability(), 0.5);
+    let m = m.with_probability(1.0);
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut shifts = 0.0;
+    for _ in 0..1000 {
+      let mut x = [0.5, 0.0];
+      m.mutate_with(&mut x, &mut rng);
+      assert!(bounds.contains(&x), "{x:?}");
+      shifts += (x[0] - 0.5).abs();
+    }
+    // shifts are small with a large distribution index
+    assert!(shifts / 1000.0 < 0.05, "{shifts}");
+    let never = PolynomialMutation::new(bounds, 20.0).with_probability(0.0);
+    let mut x = [0.5, 0.0];
+    never.mutate_with(&mut x, &mut rng);
+    assert_eq!(x, [0.5, 0.0]);
+  }
+}
@@ -0,0 +1,170 @@
+//! Initialization of populations.
+//!
+//! A genetic algorithm explores the search space starting from its initial
+//! population, so solutions of the initial population should be spread over
+//! the whole space. Otherwise, the algorithm may never reach its unexplored
+//! regions.
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::genomes::real::Bounds;
+
+/// Creates a population of `n` solutions generated by `generator` from their
+/// positions in the population.
+///
+/// # Examples
+/// ```
+/// use moga::init::from_generator;
+///
+/// let population = from_generator(4, |i| i as f32 * 0.5);
+/// assert_eq!(population, [0.0, 0.5, 1.0, 1.5]);
+/// ```
+pub fn from_generator<S>(
+  n: usize,
+  generator: impl FnMut(usize) -> S,
+) -> Vec<S> {
+  (0..n).map(generator).collect()
+}
+
+/// Creates a population of `n` real vectors, each value of which is sampled
+/// uniformly within its `bounds`.
+///
+/// # Examples
+/// ```
+/// use moga::{genomes::real::Bounds, init::random_uniform};
+///
+/// let bounds = Bounds::new(vec![0.0, -1.0], vec![5.0, 1.0]);
+/// let population = random_uniform(&bounds, 10, &mut rand::thread_rng());
+/// assert_eq!(population.len(), 10);
+/// assert!(population.iter().all(|x| bounds.contains(x)));
+/// ```
+pub fn random_uniform<R: Rng + ?Sized>(
+  bounds: &Bounds,
+  n: usize,
+  rng: &mut R,
+) -> Vec<Vec<f32>> {
+  from_generator(n, |_| {
+    bounds
+      .iter()
+      .map(|(lower, upper)| interpolate(lower, upper, rng.gen()))
+      .collect()
+  })
+}
+
+/// Creates a population of `n` real vectors with Latin hypercube sampling
+/// within `bounds`. Range of each dimension is divided into `n` equal
+/// intervals, and each interval contains exactly one value of that dimension,
+/// sampled uniformly within the interval. Combinations of intervals are
+/// random.
+///
+/// Unlike [`random_uniform`] sampling, there are no clusters or gaps in
+/// projections of the population onto each dimension.
+///
+/// # Examples
+/// ```
+/// use moga::{genomes::real::Bounds, init::latin_hypercube};
+///
+/// let bounds = Bounds::uniform(3, 0.0..=1.0);
+/// let population = latin_hypercube(&bounds, 4, &mut rand::thread_rng());
+/// // each quarter of each dimension contains a value
+/// for d_idx in 0..3 {
+///   let mut quarters: Vec<_> = population
+///     .iter()
+///     .map(|x| (x[d_idx] * 4.0) as usize)
+///     .collect();
+///   quarters.sort();
+///   assert_eq!(quarters, [0, 1, 2, 3]);
+/// }
+/// ```
+pub fn latin_hypercube<R: Rng + ?Sized>(
+  bounds: &Bounds,
+  n: usize,
+  rng: &mut R,
+) -> Vec<Vec<f32>> {
+  let mut population = vec![Vec::with_capacity(bounds.dimension()); n];
+  let mut intervals: Vec<usize> = (0..n).collect();
+  for (lower, upper) in bounds.iter() {
+    intervals.shuffle(rng);
+    for (x, &i_idx) in population.iter_mut().zip(&intervals) {
+      let t = (i_idx as f64 + rng.gen::<f64>()) / n as f64;
+      x.push(interpolate(lower, upper, t));
+    }
+  }
+  population
+}
+
+/// Returns a value between `lower` and `upper` at `t` fraction of the way.
+fn interpolate(lower: f32, upper: f32, t: f64) -> f32 {
+  let value = f64::from(lower) + t * (f64::from(upper) - f64::from(lower));
+  // rounding must not push the value outside of bounds
+  (value as f32).clamp(lower, upper)
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::prelude::*;
+
+  use super::*;
+
+  #[test]
+  fn test_random_uniform() {
+    let bounds = Bounds::new(vec![-10.0, 0.0, 3.0], vec![10.0, 1.0, 3.0]);
+    let mut rng = StdRng::seed_from_u64(0);
+    let population = random_uniform(&bounds, 10_000, &mut rng);
+    assert_eq!(population.len(), 10_000);
+    assert!(population.iter().all(|x| bounds.contains(x)));
+    let mean = |d_idx: usize| {
+      population.iter().map(|x| x[d_idx]).sum::<f32>() / 10_000.0
+    };
+    assert!(mean(0).abs() < 0.5);
+    assert!((mean(1) - 0.5).abs() < 0.05);
+    assert_eq!(mean(2), 3.0);
+  }
+
+  #[test]
+  fn test_latin_hypercube_marginal_coverage() {
+    let bounds = Bounds::new(vec![-1.0, 0.0, 100.0], vec![1.0, 0.001, 200.0]);
+    let mut rng = StdRng::seed_from_u64(0);
+    for n in [1, 2, 7, 100, 1000] {
+      let population = latin_hypercube(&bounds, n, &mut rng);
+      assert_eq!(population.len(), n);
+      assert!(population.iter().all(|x| bounds.contains(x)));
+      // each of `n` intervals of each dimension contains exactly one value
+      for (d_idx, (lower, upper)) in bounds.iter().enumerate() {
+        let mut counts = vec![0; n];
+        for x in &population {
+          let t = (f64::from(x[d_idx]) - f64::from(lower))
+            / (f64::from(upper) - f64::from(lower));
+          counts[((t * n as f64) as usize).min(n - 1)] += 1;
+        }
+        assert!(counts.iter().all(|&c| c == 1), "dimension {d_idx}");
+      }
+    }
+  }
+
+  #[test]
+  fn test_latin_hypercube_random_combinations() {
+    // values of different dimensions should not be correlated
+    let bounds = Bounds::uniform(2, 0.0..=1.0);
+    let mut rng = StdRng::seed_from_u64(0);
+    let population = latin_hypercube(&bounds, 1000, &mut rng);
+    let mean = |f: &dyn Fn(&Vec<f32>) -> f32| {
+      population.iter().map(f).sum::<f32>() / 1000.0
+    };
+    let covariance = mean(&|x| x[0] * x[1]) - mean(&|x| x[0]) * mean(&|x| x[1]);
+    assert!(covariance.abs() < 0.01, "covariance is {covariance}");
+  }
+
+  #[test]
+  fn test_empty_populations() {
+    let bounds = Bounds::uniform(2, 0.0..=1.0);
+    let mut rng = StdRng::seed_from_u64(0);
+    assert!(random_uniform(&bounds, 0, &mut rng).is_empty());
+    assert!(latin_hypercube(&bounds, 0, &mut rng).is_empty());
+    let bounds = Bounds::uniform(0, 0.0..=1.0);
+    assert_eq!(latin_hypercube(&bounds, 2, &mut rng), [
+      Vec::<f32>::new(),
+      vec![]
+    ]);
+  }
+}
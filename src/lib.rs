@@ -69,6 +69,11 @@
 //! solution. Only [`Recombination`]s are a bit different: they are applied to
 //! each [combination] of solutions.
 //!
+//! **Executors** themselves are exposed as unstable traits in
+//! [`experimental::executor`], for operators that need full control over
+//! their execution, such as evaluation of a whole population in a single call
+//! into a GPU kernel.
+//!
 //! This crate does not provide the common crossover or mutation functions you'd
 //! expect to see in a usual GA focused crate. The reason for this is that
 //! **genetic algorithms must be tailored to each problem to solve**. Fail to do
@@ -80,7 +85,13 @@
 //!
 //! However, this crate does implement for you a few
 //! [selectors](crate::selection#structs) and one commonly used
-//! [`GenerationTerminator`] for good measure.
+//! [`GenerationTerminator`] for good measure. And to test your algorithms,
+//! there are a few benchmark [problems] with known Pareto
+//! fronts.
+//!
+//! If you need a baseline before tailoring operators, [`presets`] bundle
+//! common operators of real, binary and permutation [genomes] into ready
+//! operator sets for any optimizer.
 //!
 //! # Closures
 //!
@@ -108,9 +119,11 @@
 //! will apply such **operator** in parallel to each solution/score or to their
 //! batches of equal size. And you can call these methods on closures too:
 //! ```
+//! # #[cfg(feature = "parallel")] {
 //! # use moga::operator::*;
 //! let test = |f: &f32| [f + 1.0, f * 2.0];
 //! let par_test = test.par_batch();
+//! # }
 //! ```
 //!
 //! Note that to be parallelized, both **operators** and solutions must
@@ -127,7 +140,57 @@
 //!
 //! For simple operators, the overhead introduced by parallelization usually
 //! only decreases performance, but when you need it, *you need it*. Benchmark,
-//! if in doubt.
+//! if in doubt, or call [`par_auto()`] instead, so that the **executor** times
+//! the **operator** on a few solutions and decides for itself whether to run
+//! it sequentially or in parallel batches.
+//!
+//! Parallelization requires `parallel` feature, which is enabled by default.
+//! Without it, the crate doesn't depend on [rayon], [`par_each()`],
+//! [`par_batch()`] and [`par_auto()`] methods aren't available, and optimizers
+//! run sequentially. This allows the crate to be built for targets without
+//! threads, such as `wasm32-unknown-unknown`. Note that random number
+//! generation on this target additionally requires `js` feature of `getrandom`
+//! crate.
+//!
+//! # Tracing
+//!
+//! With `tracing` feature enabled, optimizers instrument each generation with
+//! spans of [tracing] crate, which makes it easy to see where the time goes.
+//! Without the feature, instrumentation compiles to nothing. Names and fields
+//! of spans and events listed below are considered a part of the public API
+//! and won't change between minor versions. All of them have `moga` target.
+//!
+//! | Span         | Level   | Fields                                                |
+//! |:-------------|:-------:|:------------------------------------------------------|
+//! | `generation` | `DEBUG` | `generation`                                          |
+//! | `terminate`  | `DEBUG` | `population_size`, `terminated`, `elapsed_us`         |
+//! | `select`     | `DEBUG` | `population_size`, `selected`, `elapsed_us`           |
+//! | `recombine`  | `DEBUG` | `parents`, `offspring`, `elapsed_us`                  |
+//! | `mutate`     | `DEBUG` | `offspring`, `elapsed_us`                             |
+//! | `repair`     | `DEBUG` | `offspring`, `elapsed_us`                             |
+//! | `dedup`      | `DEBUG` | `offspring`, `removed`, `elapsed_us`                  |
+//! | `test`       | `DEBUG` | `population_size`, `elapsed_us`                       |
+//! | `truncate`   | `DEBUG` | `population_size`, `survived`, `elapsed_us`           |
+//! | `constrain`  | `DEBUG` | `population_size`, `elapsed_us`                       |
+//!
+//! Phase spans are children of the `generation` span. `elapsed_us` is the
+//! duration of the phase in microseconds. The initial population is tested
+//! in a `test` span outside of any `generation`. `repair`, `dedup` and
+//! `constrain` spans are entered only if the optimizer has a repairer, a
+//! deduplication operator or a constraint respectively.
+//!
+//! At the end of each generation, an `INFO` event with `generation completed`
+//! message is emitted. Its fields are `generation`, `population_size` of the
+//! survived population and `front_sizes` - sizes of fronts that solutions
+//! were sorted into before truncation. [SPEA-II] optimizer doesn't sort
+//! solutions into fronts, so it reports only the number of nondominated
+//! solutions. [NSGA-II] optimizer with a custom truncation reports no fronts.
+//!
+//! If parents and offsprings of a generation of [NSGA-II] are fewer than the
+//! initial population, or than the population of its schedule, nothing is
+//! truncated and a `WARN` event with `population is smaller than the target
+//! population` message is emitted before truncation. Its fields are
+//! `generation`, `population_size` and `target_population_size`.
 //!
 //! # Example
 //!
@@ -137,6 +200,7 @@
 //! optimal solutions for that problem.
 //! ```no_run
 //! # fn main() {
+//! # #[cfg(feature = "parallel")] {
 //! use moga::{
 //!   operator::ParBatch,
 //!   optimizer::{spea::Spea2, Optimizer},
@@ -175,6 +239,7 @@
 //! // upon termination optimizer returns the best solutions it has found
 //! let solutions = spea2.optimize();
 //! # }
+//! # }
 //! ```
 //!
 //! You can find more examples in the *examples* folder in the root of the
@@ -189,11 +254,14 @@
 //!   can paint your code red and the error will appear far away from where you
 //!   actually made a mistake. Since Rust does not allow you to annotate your
 //!   variables with traits, always keep an eye on your closures or just
-//!   implement traits for your own types instead.
+//!   implement traits for your own types instead. Wrapping a closure into
+//!   `assert_test`, `assert_mutation` or another function of the [prelude]
+//!   named after the operator, or naming the type of an optimizer with
+//!   [`Nsga2For`], moves the error closer to the mistake.
 //! - [`Selection`] and [`Termination`] traits are implemented for the same
 //!   closure of type `Fn(&S, &[f32; N]) -> bool` which may confuse the compiler
 //!   (and you) from time to time. Move closures into an optimizer as soon as
-//!    possible, or, again, implement those traits for your own type.
+//!   possible, or, again, implement those traits for your own type.
 //! - More often than not, parallelization only decreases performance of the
 //!   algorithm. Currently, Rust does not provide any benchmarking utilities
 //!   "out-of-the-box", but you can use the tools that your OS has, like
@@ -203,6 +271,8 @@
 //!
 //! [`Optimizer`]: crate::optimizer::Optimizer
 //! [NSGA-II]: https://sci2s.ugr.es/sites/default/files/files/Teaching/OtherPostGraduateCourses/Metaheuristicas/Deb_NSGAII.pdf
+//! [tracing]: https://docs.rs/tracing
+//! [rayon]: https://docs.rs/rayon
 //! [SPEA-II]: https://www.research-collection.ethz.ch/bitstream/handle/20.500.11850/145755/eth-24689-01.pdf
 //! [`Tester`]: crate::testing::Tester
 //! [`Test`]: crate::testing::Test
@@ -217,7 +287,10 @@
 //! [`GenerationTerminator`]: crate::termination::GenerationTerminator
 //! [`par_each()`]: crate::operator::ParEach::par_each
 //! [`par_batch()`]: crate::operator::ParBatch::par_batch
+//! [`par_auto()`]: crate::operator::ParAuto::par_auto
 //! [`score`]: crate::score
+//! [prelude]: crate::prelude
+//! [`Nsga2For`]: crate::optimizer::nsga::Nsga2For
 //! [combination]: https://en.wikipedia.org/wiki/Combination
 //! [time]: https://www.man7.org/linux/man-pages/man1/time.1.html
 //! [Measure-Command]: https://learn.microsoft.com/en-us/powershell/module/microsoft.powershell.utility/measure-command
@@ -226,12 +299,31 @@
 
 #![warn(missing_docs)]
 
+pub mod bench;
+pub mod compose;
+pub mod constraining;
+pub mod dedup;
 mod execution;
+pub mod experimental;
+pub mod export;
+pub mod genomes;
+pub mod init;
+pub mod lineage;
 pub mod mutation;
+pub mod observer;
 pub mod operator;
 pub mod optimizer;
+pub mod pause;
+pub mod prelude;
+pub mod presets;
+pub mod problems;
 pub mod recombination;
+pub mod rng;
+pub mod runner;
 pub mod score;
 pub mod selection;
+pub mod speciation;
 pub mod termination;
 pub mod testing;
+mod trace;
+pub mod truncation;
@@ -0,0 +1,170 @@
+//! Identity tracking and lineage of solutions.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::{
+  mutation::Mutation,
+  recombination::Recombination,
+  score::Scores,
+  testing::Test,
+};
+
+/// The next free solution id.
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Returns a new solution id, unique within the process.
+fn next_id() -> u64 {
+  NEXT_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// A solution with an identity and lineage metadata: ids of its parents and
+/// the generation it was created in.
+///
+/// Optimize `Tracked` solutions with operators on inner solutions wrapped
+/// into [`TrackedOperator`]. Offsprings created by a wrapped recombination
+/// get new ids, ids of their parents and a generation that follows the latest
+/// generation of the parents. Mutations don't change identity of solutions.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   lineage::{Tracked, TrackedOperator},
+///   recombination::Recombination,
+/// };
+///
+/// let a = Tracked::new(1.0);
+/// let b = Tracked::new(3.0);
+/// let r = TrackedOperator(|x: &f32, y: &f32| (x + y) / 2.0);
+/// let [o] = r.recombine([&a, &b]);
+/// assert_eq!(o.inner, 2.0);
+/// assert_eq!(o.parent_ids, [a.id, b.id]);
+/// assert_eq!(o.generation, 1);
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Tracked<S> {
+  /// Id of the solution, unique within the process.
+  pub id: u64,
+  /// Ids of the parents the solution was created from. Empty for solutions
+  /// of the initial population.
+  pub parent_ids: Vec<u64>,
+  /// Generation of the solution: `0` for solutions of the initial population,
+  /// otherwise one more than the latest generation of its parents.
+  pub generation: u32,
+  /// The tracked solution.
+  pub inner: S,
+}
+
+impl<S> Tracked<S> {
+  /// Creates a tracked solution without parents with a new id.
+  pub fn new(inner: S) -> Self {
+    Self {
+      id: next_id(),
+      parent_ids: Vec::new(),
+      generation: 0,
+      inner,
+    }
+  }
+
+  /// Returns the tracked solution.
+  pub fn into_inner(self) -> S {
+    self.inner
+  }
+}
+
+/// A wrapper that lifts [`Test`], [`Mutation`] and [`Recombination`]
+/// operators on solutions onto [`Tracked`] solutions. Wrapped operators can
+/// be parallelized as usual.
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "parallel")] {
+/// use moga::{lineage::TrackedOperator, operator::*};
+///
+/// let t = TrackedOperator(|x: &f32| [x + 1.0, x - 1.0]).par_batch();
+/// let m = TrackedOperator(|x: &mut f32| *x += 1.0).par_each();
+/// # }
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct TrackedOperator<O>(pub O);
+
+impl<S, const N: usize, T> Test<Tracked<S>, N> for TrackedOperator<T>
+where
+  T: Test<S, N>,
+{
+  fn test(&self, solution: &Tracked<S>) -> Scores<N> {
+    self.0.test(&solution.inner)
+  }
+}
+
+impl<S, M> Mutation<Tracked<S>> for TrackedOperator<M>
+where
+  M: Mutation<S>,
+{
+  fn mutate(&self, solution: &mut Tracked<S>) {
+    self.0.mutate(&mut solution.inner)
+  }
+}
+
+impl<S, const P: usize, const O: usize, R> Recombination<Tracked<S>, P, O>
+  for TrackedOperator<R>
+where
+  R: Recombination<S, P, O>,
+{
+  fn recombine(&self, parents: [&Tracked<S>; P]) -> [Tracked<S>; O] {
+    let generation = parents
+      .iter()
+      .map(|p| p.generation)
+      .max()
+      .map_or(0, |g| g.saturating_add(1));
+    let parent_ids = parents.iter().map(|p| p.id).collect::<Vec<_>>();
+    self
+      .0
+      .recombine(parents.map(|p| &p.inner))
+      .map(|inner| Tracked {
+        id: next_id(),
+        parent_ids: parent_ids.clone(),
+        generation,
+        inner,
+      })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_tracked_ids_are_unique() {
+    let a = Tracked::new(0);
+    let b = Tracked::new(0);
+    assert_ne!(a.id, b.id);
+    assert!(a.parent_ids.is_empty());
+    assert_eq!(a.generation, 0);
+  }
+
+  #[test]
+  fn test_tracked_operators() {
+    let t = TrackedOperator(|x: &f32| [*x, -x]);
+    let mut s = Tracked::new(1.0);
+    assert_eq!(t.test(&s), [1.0, -1.0]);
+
+    let id = s.id;
+    TrackedOperator(|x: &mut f32| *x += 1.0).mutate(&mut s);
+    assert_eq!(s.inner, 2.0);
+    assert_eq!(s.id, id);
+
+    let r = TrackedOperator(|x: &f32| (x + 1.0, x - 1.0));
+    let [a, b] = r.recombine([&s]);
+    assert_eq!((a.inner, b.inner), (3.0, 1.0));
+    assert_eq!(a.parent_ids, [id]);
+    assert_eq!(b.parent_ids, [id]);
+    assert_ne!(a.id, b.id);
+    assert_eq!(a.generation, 1);
+
+    let r = TrackedOperator(|x: &f32, y: &f32| x + y);
+    let [c] = r.recombine([&a, &s]);
+    assert_eq!(c.parent_ids, [a.id, id]);
+    assert_eq!(c.generation, 2);
+  }
+}
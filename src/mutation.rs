@@ -1,19 +1,18 @@
 //! Mutation operators and utilities.
 
 use executor::MutationExecutor;
-use rayon::prelude::*;
+use rand::rngs::SmallRng;
 
 use crate::{
   execution::strategy::*,
-  operator::{
-    tag::MutationOperatorTag,
-    ParBatch,
-    ParBatchOperator,
-    ParEach,
-    ParEachOperator,
-  },
+  pause::{Directive, PauseHandler},
+  rng::{IndexedStreams, SplitRng},
+  score::Scores,
 };
 
+#[cfg(feature = "parallel")]
+mod parallel;
+
 /// An operator that mutates a single solution.
 ///
 /// Can be applied in parallel to each solution or to batches of solutions
@@ -22,9 +21,11 @@ use crate::{
 ///
 /// # Examples
 /// ```
+/// # #[cfg(feature = "parallel")] {
 /// # use moga::operator::*;
 /// let m = |f: &mut f32| *f *= 2.0;
 /// let m = m.par_batch();
+/// # }
 /// ```
 ///
 /// **Note that you always can implement this trait instead of using closures.**
@@ -42,18 +43,16 @@ where
   }
 }
 
-impl<S, M> ParEach<MutationOperatorTag, S, 0, 0> for M
-where
-  S: Sync + Send,
-  M: Mutation<S> + Sync,
-{
-}
-
-impl<S, M> ParBatch<MutationOperatorTag, S, 0> for M
+/// Applies mutations one after another.
+impl<S, A, B> Mutation<S> for (A, B)
 where
-  S: Sync + Send,
-  M: Mutation<S> + Sync,
+  A: Mutation<S>,
+  B: Mutation<S>,
 {
+  fn mutate(&self, solution: &mut S) {
+    self.0.mutate(solution);
+    self.1.mutate(solution);
+  }
 }
 
 /// An operator that mutates all solutions.
@@ -78,9 +77,9 @@ where
   }
 }
 
-/// This private module prevents exposing the `Executor` to a user.
+/// Executors are exposed to users only by [`crate::experimental::executor`].
 pub(crate) mod executor {
-  /// An internal mutation executor.
+  /// An executor of mutation operators.
   pub trait MutationExecutor<S, ExecutionStrategy> {
     /// Executes mutations optionally parallelizing operator's application.
     fn execute_mutations(&self, solutions: &mut [S]);
@@ -105,36 +104,332 @@ where
   }
 }
 
-impl<S, M> MutationExecutor<S, ParallelEachExecutionStrategy>
-  for ParEachOperator<MutationOperatorTag, S, M>
+/// A boxed [`Mutator`] that any mutation operator, parallelized or not, can
+/// be converted into. Unlike operators, boxed mutators have the same type, so
+/// the type of an optimizer with a mutator chosen at runtime can be named.
+///
+/// # Examples
+/// ```
+/// # use moga::mutation::BoxedMutator;
+/// let mutate = false;
+/// let m: BoxedMutator<f32> = if mutate {
+///   BoxedMutator::new(|x: &mut f32| *x += 0.1)
+/// } else {
+///   BoxedMutator::new(|_: &mut f32| {})
+/// };
+/// ```
+pub struct BoxedMutator<S>(Box<dyn Mutator<S> + Send>);
+
+impl<S: 'static> BoxedMutator<S> {
+  /// Boxes the `mutator`.
+  pub fn new<ExecStrat: 'static>(
+    mutator: impl MutationExecutor<S, ExecStrat> + Send + 'static,
+  ) -> Self {
+    Self(Box::new(move |solutions: &mut [S]| {
+      mutator.execute_mutations(solutions)
+    }))
+  }
+}
+
+impl<S> Mutator<S> for BoxedMutator<S> {
+  fn mutate(&self, solutions: &mut [S]) {
+    self.0.mutate(solutions)
+  }
+}
+
+impl<S> std::fmt::Debug for BoxedMutator<S> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("BoxedMutator")
+  }
+}
+
+/// A mutation of a single solution that draws random numbers from a given
+/// generator. Apply it with an [`IndexedRandomMutation`], which gives each
+/// solution a generator of its own.
+///
+/// # Examples
+/// Any closure that takes a mutable reference to a solution and a generator
+/// is a `RandomMutation`.
+/// ```
+/// use moga::mutation::RandomMutation;
+/// use rand::{rngs::SmallRng, Rng, SeedableRng};
+///
+/// let m = |x: &mut f32, rng: &mut SmallRng| *x += rng.gen_range(-1.0..1.0);
+/// let mut x = 0.0;
+/// m.mutate_with(&mut x, &mut SmallRng::seed_from_u64(0));
+/// assert!((-1.0..1.0).contains(&x));
+/// ```
+///
+/// **Note that you always can implement this trait instead of using closures.**
+pub trait RandomMutation<S> {
+  /// Mutates the `solution`, drawing random numbers from `rng`.
+  fn mutate_with(&self, solution: &mut S, rng: &mut SmallRng);
+}
+
+impl<S, F> RandomMutation<S> for F
 where
-  S: Sync + Send,
-  M: Mutation<S> + Sync,
+  F: Fn(&mut S, &mut SmallRng),
 {
-  fn execute_mutations(&self, solutions: &mut [S]) {
-    solutions
-      .par_iter_mut()
-      .for_each(|s| self.operator().mutate(s));
+  fn mutate_with(&self, solution: &mut S, rng: &mut SmallRng) {
+    self(solution, rng)
   }
 }
 
-impl<S, M> MutationExecutor<S, ParallelBatchExecutionStrategy>
-  for ParBatchOperator<MutationOperatorTag, S, M>
+/// A [`Mutator`] that mutates each solution with a random number generator
+/// of its own, derived from the master seed, the generation and the index of
+/// the solution with [`SplitRng::indexed`]. Unlike a random [`Mutation`] that
+/// shares a generator between solutions, it mutates each solution the same
+/// way regardless of the order solutions are mutated in, so it stays
+/// reproducible when mutating solutions in parallel with `par_each()`.
+///
+/// Generations are counted by the number of times the mutator was applied,
+/// which optimizers do once per generation.
+///
+/// # Examples
+/// ```
+/// use moga::{mutation::IndexedRandomMutation, rng::SplitRng};
+/// use rand::Rng;
+///
+/// let m = IndexedRandomMutation::new(SplitRng::new(42), |x: &mut f32, rng| {
+///   *x += rng.gen_range(-1.0..1.0)
+/// });
+/// # #[cfg(feature = "parallel")]
+/// let m = m.par_each();
+/// ```
+pub struct IndexedRandomMutation<F> {
+  streams: IndexedStreams,
+  mutation: F,
+  parallel: bool,
+}
+
+impl<F> IndexedRandomMutation<F> {
+  /// Creates a mutator that applies the `mutation` to each solution with
+  /// a generator derived from the master seed `rng`.
+  pub fn new<S>(rng: SplitRng, mutation: F) -> Self
+  where
+    F: Fn(&mut S, &mut SmallRng),
+  {
+    Self::from_random(rng, mutation)
+  }
+
+  /// Creates a mutator that applies the [`RandomMutation`] to each solution
+  /// with a generator derived from the master seed `rng`. Unlike
+  /// [`new`](Self::new), it accepts mutations that aren't closures, such as
+  /// [`PolynomialMutation`].
+  ///
+  /// [`PolynomialMutation`]: crate::genomes::real::PolynomialMutation
+  pub fn from_random<S>(rng: SplitRng, mutation: F) -> Self
+  where
+    F: RandomMutation<S>,
+  {
+    Self {
+      streams: IndexedStreams::new(rng),
+      mutation,
+      parallel: false,
+    }
+  }
+
+  /// Mutates solutions in parallel. Solutions are mutated the same way as
+  /// they would be sequentially.
+  #[cfg(feature = "parallel")]
+  pub fn par_each(self) -> Self {
+    Self {
+      parallel: true,
+      ..self
+    }
+  }
+}
+
+#[cfg(not(feature = "parallel"))]
+impl<S, F> Mutator<S> for IndexedRandomMutation<F>
 where
-  S: Sync + Send,
-  M: Mutation<S> + Sync,
+  F: RandomMutation<S>,
 {
-  fn execute_mutations(&self, solutions: &mut [S]) {
-    let chunk_size = (solutions.len() / rayon::current_num_threads()).max(1);
-    solutions.par_chunks_mut(chunk_size).for_each(|chunk| {
-      chunk.iter_mut().for_each(|s| self.operator().mutate(s))
+  fn mutate(&self, solutions: &mut [S]) {
+    let generation = self.streams.next_generation();
+    solutions.iter_mut().enumerate().for_each(|(idx, s)| {
+      self
+        .mutation
+        .mutate_with(s, &mut self.streams.stream(generation, idx))
     });
   }
 }
 
+#[cfg(feature = "parallel")]
+impl<S, F> Mutator<S> for IndexedRandomMutation<F>
+where
+  S: Send,
+  F: RandomMutation<S> + Sync,
+{
+  fn mutate(&self, solutions: &mut [S]) {
+    use rayon::prelude::*;
+
+    let generation = self.streams.next_generation();
+    let mutate = |(idx, s): (usize, &mut S)| {
+      self
+        .mutation
+        .mutate_with(s, &mut self.streams.stream(generation, idx))
+    };
+    if self.parallel {
+      solutions.par_iter_mut().enumerate().for_each(mutate);
+    } else {
+      solutions.iter_mut().enumerate().for_each(mutate);
+    }
+  }
+}
+
+impl<F> std::fmt::Debug for IndexedRandomMutation<F> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("IndexedRandomMutation")
+      .field("streams", &self.streams)
+      .field("parallel", &self.parallel)
+      .finish_non_exhaustive()
+  }
+}
+
+/// A [`PauseHandler`] that restarts the population without restarting the
+/// optimizer: when triggered, it keeps the Pareto front of the population and
+/// replaces the rest of it with fresh solutions from a generator, such as
+/// [`latin_hypercube`] sampling. This brings a stagnated population's
+/// diversity back, while the best found solutions survive.
+///
+/// The restart is triggered either every `k` pauses or when a stagnation
+/// predicate, given scores of the Pareto front, returns `true`. It counts
+/// pauses itself, so it's meant to be given to `pause_every(1, restart)` of
+/// a single optimizer, which pauses once per generation. The generator
+/// receives the number of solutions to generate: the difference between the
+/// size of the population and the size of its Pareto front.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   genomes::real::Bounds,
+///   init::latin_hypercube,
+///   mutation::CataclysmicRestart,
+///   optimizer::{nsga::Nsga2, Optimizer},
+///   selection::RandomSelector,
+///   termination::GenerationTerminator,
+/// };
+/// use rand::{rngs::SmallRng, SeedableRng};
+///
+/// let bounds = Bounds::uniform(2, -10.0..=10.0);
+/// let mut rng = SmallRng::seed_from_u64(0);
+/// let population = latin_hypercube(&bounds, 20, &mut rng);
+/// let solutions = Nsga2::builder()
+///   .population(population)
+///   .tester(|x: &Vec<f32>| [x[0].powi(2), (x[1] - 2.0).powi(2)])
+///   .selector(RandomSelector(10))
+///   .recombinator(|x: &Vec<f32>, y: &Vec<f32>| vec![x[0], y[1]])
+///   .mutator(|_: &mut Vec<f32>| {})
+///   .terminator(GenerationTerminator(50))
+///   .pause_every(
+///     1,
+///     CataclysmicRestart::every(10, 20, move |n| {
+///       latin_hypercube(&bounds, n, &mut rng)
+///     }),
+///   )
+///   .build()
+///   .optimize();
+/// ```
+///
+/// [`latin_hypercube`]: crate::init::latin_hypercube
+pub struct CataclysmicRestart<G, P> {
+  population_size: usize,
+  generator: G,
+  trigger: RestartTrigger<P>,
+  /// Number of pauses since the last restart.
+  pauses: usize,
+}
+
+/// What triggers a [`CataclysmicRestart`].
+#[derive(Clone, Copy, Debug)]
+enum RestartTrigger<P> {
+  Every(usize),
+  Stagnation(P),
+}
+
+impl<G, const N: usize> CataclysmicRestart<G, fn(&[Scores<N>]) -> bool> {
+  /// Creates a restart that's triggered every `k` pauses, replacing
+  /// solutions of a population of `population_size` solutions outside of its
+  /// Pareto front with solutions from the `generator`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `k` is `0`.
+  pub fn every(k: usize, population_size: usize, generator: G) -> Self {
+    assert!(k > 0, "number of pauses between restarts is zero");
+    Self {
+      population_size,
+      generator,
+      trigger: RestartTrigger::Every(k),
+      pauses: 0,
+    }
+  }
+}
+
+impl<G, P> CataclysmicRestart<G, P> {
+  /// Creates a restart that's triggered when the `stagnation` predicate
+  /// returns `true` for scores of the Pareto front, replacing solutions of a
+  /// population of `population_size` solutions outside of its Pareto front
+  /// with solutions from the `generator`.
+  pub fn on_stagnation(
+    stagnation: P,
+    population_size: usize,
+    generator: G,
+  ) -> Self {
+    Self {
+      population_size,
+      generator,
+      trigger: RestartTrigger::Stagnation(stagnation),
+      pauses: 0,
+    }
+  }
+}
+
+impl<S, G, P, const N: usize> PauseHandler<S, N> for CataclysmicRestart<G, P>
+where
+  G: FnMut(usize) -> Vec<S>,
+  P: FnMut(&[Scores<N>]) -> bool,
+{
+  fn pause(
+    &mut self,
+    _: usize,
+    solutions: &[&S],
+    scores: &[Scores<N>],
+  ) -> Directive<S, N> {
+    self.pauses += 1;
+    let triggered = match &mut self.trigger {
+      RestartTrigger::Every(k) => self.pauses >= *k,
+      RestartTrigger::Stagnation(stagnation) => stagnation(scores),
+    };
+    if !triggered {
+      return Directive::Continue;
+    }
+    self.pauses = 0;
+    let n = self.population_size.saturating_sub(solutions.len());
+    Directive::Restart((self.generator)(n))
+  }
+}
+
+impl<G, P> std::fmt::Debug for CataclysmicRestart<G, P> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let trigger = match self.trigger {
+      RestartTrigger::Every(k) => format!("every {k} pauses"),
+      RestartTrigger::Stagnation(_) => "stagnation".to_string(),
+    };
+    f.debug_struct("CataclysmicRestart")
+      .field("population_size", &self.population_size)
+      .field("trigger", &trigger)
+      .field("pauses", &self.pauses)
+      .finish_non_exhaustive()
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
+  #[cfg(feature = "parallel")]
+  use crate::operator::{ParAuto, ParBatch, ParEach};
 
   type Solution = f32;
 
@@ -146,7 +441,22 @@ mod tests {
   fn test_mutation_from_closure() {
     let mutation = |solution: &mut Solution| *solution *= 2.0;
     takes_mutator(&mutation);
+    #[cfg(feature = "parallel")]
     takes_mutator(&mutation.par_each());
+    #[cfg(feature = "parallel")]
+    takes_mutator(&mutation.par_batch());
+    #[cfg(feature = "parallel")]
+    takes_mutator(&mutation.par_auto());
+  }
+
+  #[test]
+  fn test_chained_mutations() {
+    let mutation = (|s: &mut Solution| *s += 1.0, |s: &mut Solution| *s *= 2.0);
+    let mut solution = 1.0;
+    mutation.mutate(&mut solution);
+    assert_eq!(solution, 4.0);
+    takes_mutator(&mutation);
+    #[cfg(feature = "parallel")]
     takes_mutator(&mutation.par_batch());
   }
 
@@ -179,4 +489,100 @@ mod tests {
     let mutator = CustomMutator {};
     takes_mutator(&mutator);
   }
+
+  #[test]
+  fn test_boxed_mutator() {
+    let mutator = BoxedMutator::new(|v: &mut Solution| *v *= 2.0);
+    takes_mutator(&mutator);
+    let mut solutions = [1.0, 2.0];
+    mutator.execute_mutations(&mut solutions);
+    assert_eq!(solutions, [2.0, 4.0]);
+  }
+
+  #[test]
+  fn test_indexed_random_mutation() {
+    use rand::Rng;
+
+    let mutate = |rng| {
+      let m = IndexedRandomMutation::new(rng, |v: &mut Solution, rng| {
+        *v += rng.gen_range(0.0..1.0)
+      });
+      let mut solutions = vec![0.0; 100];
+      m.mutate(&mut solutions);
+      m.mutate(&mut solutions);
+      takes_mutator(&m);
+      solutions
+    };
+    let solutions = mutate(SplitRng::new(0));
+    assert_eq!(solutions, mutate(SplitRng::new(0)));
+    assert_ne!(solutions, mutate(SplitRng::new(1)));
+    assert_ne!(solutions[0], solutions[1]);
+    // the second generation draws other numbers than the first one
+    let m =
+      IndexedRandomMutation::new(SplitRng::new(0), |v: &mut Solution, rng| {
+        *v = rng.gen()
+      });
+    let (mut first, mut second) = ([0.0], [0.0]);
+    m.mutate(&mut first);
+    m.mutate(&mut second);
+    assert_ne!(first, second);
+  }
+
+  #[test]
+  #[cfg(feature = "parallel")]
+  fn test_par_each_indexed_random_mutation() {
+    use rand::Rng;
+
+    let mutation = |v: &mut Solution, rng: &mut SmallRng| *v = rng.gen();
+    let sequential = IndexedRandomMutation::new(SplitRng::new(3), mutation);
+    let parallel =
+      IndexedRandomMutation::new(SplitRng::new(3), mutation).par_each();
+    crate::execution::for_uneven_batches(|len| {
+      let mut expected = vec![0.0; len];
+      sequential.mutate(&mut expected);
+      let mut solutions = vec![0.0; len];
+      parallel.mutate(&mut solutions);
+      assert_eq!(solutions, expected, "{len} solutions");
+    });
+  }
+
+  #[test]
+  fn test_cataclysmic_restart() {
+    let front = [&1.0, &2.0];
+    let scores = [[1.0, 2.0], [2.0, 1.0]];
+    let mut restart =
+      CataclysmicRestart::every(2, 5, |n| vec![10.0 as Solution; n]);
+    let mut directives: Vec<_> =
+      (0..4).map(|g| restart.pause(g, &front, &scores)).collect();
+    assert_eq!(directives, [
+      Directive::Continue,
+      Directive::Restart(vec![10.0; 3]),
+      Directive::Continue,
+      Directive::Restart(vec![10.0; 3]),
+    ]);
+
+    let mut stagnated = false;
+    let mut restart = CataclysmicRestart::on_stagnation(
+      |_: &[Scores<2>]| std::mem::replace(&mut stagnated, true),
+      1,
+      |n| vec![10.0 as Solution; n],
+    );
+    directives = (0..2).map(|g| restart.pause(g, &front, &scores)).collect();
+    // the front is larger than the population, so nothing is generated
+    assert_eq!(directives, [
+      Directive::Continue,
+      Directive::Restart(vec![])
+    ]);
+  }
+
+  #[test]
+  #[cfg(feature = "parallel")]
+  fn test_par_batch_mutates_each_solution_once() {
+    let mutation = (|v: &mut Solution| *v += 1.0).par_batch();
+    crate::execution::for_uneven_batches(|len| {
+      let mut solutions = vec![0.0; len];
+      mutation.execute_mutations(&mut solutions);
+      assert_eq!(solutions, vec![1.0; len], "{len} solutions");
+    });
+  }
 }
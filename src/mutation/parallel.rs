@@ -0,0 +1,93 @@
+//! Parallel execution of mutation operators.
+
+use rayon::prelude::*;
+
+use super::{executor::MutationExecutor, Mutation};
+use crate::{
+  execution::{batch_size, strategy::*},
+  operator::{
+    tag::MutationOperatorTag,
+    AutoDecision,
+    ParAuto,
+    ParAutoOperator,
+    ParBatch,
+    ParBatchOperator,
+    ParEach,
+    ParEachOperator,
+  },
+};
+
+impl<S, M> ParEach<MutationOperatorTag, S, 0, 0> for M
+where
+  S: Sync + Send,
+  M: Mutation<S> + Sync,
+{
+}
+
+impl<S, M> ParBatch<MutationOperatorTag, S, 0> for M
+where
+  S: Sync + Send,
+  M: Mutation<S> + Sync,
+{
+}
+
+impl<S, M> ParAuto<MutationOperatorTag, S, 0> for M
+where
+  S: Sync + Send,
+  M: Mutation<S> + Sync,
+{
+}
+
+impl<S, M> MutationExecutor<S, ParallelEachExecutionStrategy>
+  for ParEachOperator<MutationOperatorTag, S, M>
+where
+  S: Sync + Send,
+  M: Mutation<S> + Sync,
+{
+  fn execute_mutations(&self, solutions: &mut [S]) {
+    solutions
+      .par_iter_mut()
+      .for_each(|s| self.operator().mutate(s));
+  }
+}
+
+impl<S, M> MutationExecutor<S, ParallelBatchExecutionStrategy>
+  for ParBatchOperator<MutationOperatorTag, S, M>
+where
+  S: Sync + Send,
+  M: Mutation<S> + Sync,
+{
+  fn execute_mutations(&self, solutions: &mut [S]) {
+    let chunk_size = batch_size(solutions.len());
+    solutions.par_chunks_mut(chunk_size).for_each(|chunk| {
+      chunk.iter_mut().for_each(|s| self.operator().mutate(s))
+    });
+  }
+}
+
+impl<S, M> MutationExecutor<S, AutoExecutionStrategy>
+  for ParAutoOperator<MutationOperatorTag, S, M>
+where
+  S: Sync + Send,
+  M: Mutation<S> + Sync,
+{
+  fn execute_mutations(&self, solutions: &mut [S]) {
+    let (mutated, decision) = self.plan(solutions.len(), |sample| {
+      solutions[..sample]
+        .iter_mut()
+        .for_each(|s| self.operator().mutate(s))
+    });
+    let rest = &mut solutions[mutated..];
+    match decision {
+      AutoDecision::Sequential => {
+        rest.iter_mut().for_each(|s| self.operator().mutate(s))
+      }
+      AutoDecision::Batch => {
+        let chunk_size = batch_size(rest.len());
+        rest.par_chunks_mut(chunk_size).for_each(|chunk| {
+          chunk.iter_mut().for_each(|s| self.operator().mutate(s))
+        })
+      }
+    }
+  }
+}
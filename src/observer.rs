@@ -0,0 +1,1298 @@
+//! Observers of optimization process and utilities.
+
+use std::{
+  fmt::{Debug, Display},
+  fs::File,
+  io::{self, Write},
+  path::Path,
+  sync::{mpsc, Arc, Mutex, MutexGuard},
+  time::Duration,
+};
+
+use crate::{
+  export::write_row,
+  optimizer::from_index,
+  score::{
+    hypervolume,
+    pareto_front_indices,
+    sorting::{nondominated_sort_with, SortingBuffers},
+    ObjectiveLabels,
+    ScoreStats,
+    Scores,
+  },
+};
+
+/// An observer that is notified by an optimizer after each generation. It
+/// receives solutions that survived environmental selection of the generation
+/// and their scores. Generations are numbered from `0`.
+///
+/// Observers can be combined by putting them into a tuple.
+///
+/// # Examples
+/// Any closure that takes the generation number, solutions and their scores
+/// is an `Observer`.
+/// ```
+/// let o = |generation: usize, solutions: &[f32], _: &[[f32; 2]]| {
+///   println!("generation {generation}: {} solutions", solutions.len());
+/// };
+/// ```
+///
+/// **Note that you always can implement this trait instead of using closures.**
+pub trait Observer<S, const N: usize> {
+  /// Observes solutions and scores of the `generation`.
+  fn observe(
+    &mut self,
+    generation: usize,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  );
+
+  /// Receives labels of objectives before the first generation, if the
+  /// optimizer was built with them. Does nothing by default.
+  fn label_objectives(&mut self, labels: &ObjectiveLabels<N>) {
+    let _ = labels;
+  }
+
+  /// Receives the number of offsprings of the `generation` that the
+  /// optimizer's deduplication has removed before testing, because they
+  /// duplicated members of the population or other offsprings. Called only
+  /// if the optimizer has a deduplication operator. Does nothing by default.
+  fn duplicates_removed(&mut self, generation: usize, count: usize) {
+    let _ = (generation, count);
+  }
+
+  /// Receives the number of offsprings of the `generation` that the
+  /// optimizer has dropped because their tests didn't finish within the
+  /// generation's time budget. Called only if some offsprings were dropped.
+  /// Does nothing by default.
+  fn tests_timed_out(&mut self, generation: usize, count: usize) {
+    let _ = (generation, count);
+  }
+}
+
+impl<S, const N: usize, F> Observer<S, N> for F
+where
+  F: FnMut(usize, &[S], &[Scores<N>]),
+{
+  fn observe(
+    &mut self,
+    generation: usize,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) {
+    self(generation, solutions, scores)
+  }
+}
+
+impl<S, const N: usize, A, B> Observer<S, N> for (A, B)
+where
+  A: Observer<S, N>,
+  B: Observer<S, N>,
+{
+  fn observe(
+    &mut self,
+    generation: usize,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) {
+    self.0.observe(generation, solutions, scores);
+    self.1.observe(generation, solutions, scores);
+  }
+
+  fn label_objectives(&mut self, labels: &ObjectiveLabels<N>) {
+    self.0.label_objectives(labels);
+    self.1.label_objectives(labels);
+  }
+
+  fn duplicates_removed(&mut self, generation: usize, count: usize) {
+    self.0.duplicates_removed(generation, count);
+    self.1.duplicates_removed(generation, count);
+  }
+
+  fn tests_timed_out(&mut self, generation: usize, count: usize) {
+    self.0.tests_timed_out(generation, count);
+    self.1.tests_timed_out(generation, count);
+  }
+}
+
+impl<S, const N: usize> Observer<S, N> for Box<dyn Observer<S, N> + Send> {
+  fn observe(
+    &mut self,
+    generation: usize,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) {
+    (**self).observe(generation, solutions, scores);
+  }
+
+  fn label_objectives(&mut self, labels: &ObjectiveLabels<N>) {
+    (**self).label_objectives(labels);
+  }
+
+  fn duplicates_removed(&mut self, generation: usize, count: usize) {
+    (**self).duplicates_removed(generation, count);
+  }
+
+  fn tests_timed_out(&mut self, generation: usize, count: usize) {
+    (**self).tests_timed_out(generation, count);
+  }
+}
+
+impl<S, const N: usize> Debug for dyn Observer<S, N> + Send {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("Observer")
+  }
+}
+
+/// A summary of a solution, such as its id or key metrics, that observers
+/// record instead of the solution itself. Summaries make observation of
+/// heavy solutions, e.g. states of simulations, cheap, and let solutions that
+/// aren't [`Clone`] be recorded at all.
+///
+/// [`Cloned`] summarizes solutions with their clones.
+///
+/// # Examples
+/// Any closure that takes a reference to a solution is a `Summarize`.
+/// ```
+/// use moga::observer::Summarize;
+///
+/// struct Simulation {
+///   id: u32,
+///   state: Vec<u8>,
+/// }
+///
+/// let s = |sim: &Simulation| sim.id;
+/// let sim = Simulation {
+///   id: 7,
+///   state: vec![0; 1024],
+/// };
+/// assert_eq!(s.summarize(&sim), 7);
+/// ```
+///
+/// **Note that you always can implement this trait instead of using closures.**
+pub trait Summarize<S> {
+  /// Type of summaries.
+  type Summary;
+
+  /// Summarizes the `solution`.
+  fn summarize(&self, solution: &S) -> Self::Summary;
+}
+
+impl<S, T, F> Summarize<S> for F
+where
+  F: Fn(&S) -> T,
+{
+  type Summary = T;
+
+  fn summarize(&self, solution: &S) -> T {
+    self(solution)
+  }
+}
+
+/// A [`Summarize`] that summarizes solutions with their clones.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Cloned;
+
+impl<S: Clone> Summarize<S> for Cloned {
+  type Summary = S;
+
+  fn summarize(&self, solution: &S) -> S {
+    solution.clone()
+  }
+}
+
+/// Summary of a generation recorded by [`HistoryRecorder`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GenerationRecord<const N: usize> {
+  /// Number of the generation, starting from `0`.
+  pub generation: usize,
+  /// Number of solutions that survived the generation.
+  pub population_size: usize,
+  /// Minimum of each objective's scores.
+  #[cfg_attr(feature = "serde", serde(with = "crate::score::serde_scores"))]
+  pub min: Scores<N>,
+  /// Mean of each objective's scores.
+  #[cfg_attr(feature = "serde", serde(with = "crate::score::serde_scores"))]
+  pub mean: Scores<N>,
+  /// Maximum of each objective's scores.
+  #[cfg_attr(feature = "serde", serde(with = "crate::score::serde_scores"))]
+  pub max: Scores<N>,
+  /// Hypervolume of scores, if [`HistoryRecorder`] was given a reference
+  /// point.
+  pub hypervolume: Option<f64>,
+}
+
+impl<const N: usize> GenerationRecord<N> {
+  /// Summarizes scores of the `generation`. Statistics of an empty slice of
+  /// scores are `NaN`s.
+  pub(crate) fn new(
+    generation: usize,
+    scores: &[Scores<N>],
+    reference: Option<&Scores<N>>,
+  ) -> Self {
+    let stats = ScoreStats::compute(scores);
+    Self {
+      generation,
+      population_size: scores.len(),
+      min: stats.min(),
+      mean: stats.mean(),
+      max: stats.max(),
+      hypervolume: reference.map(|r| hypervolume(scores, r)),
+    }
+  }
+}
+
+/// An [`Observer`] that records history of optimization: a
+/// [`GenerationRecord`] for each generation and, optionally, the best front of
+/// each generation. Solutions of fronts are recorded as their clones or as
+/// their summaries of type `T`, see [`Summarize`].
+///
+/// `HistoryRecorder` is a handle to shared records, so it's cheap to clone.
+/// Give a clone to an optimizer and retrieve records from the original handle
+/// after optimization.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   observer::{write_records_csv, HistoryRecorder},
+///   optimizer::{nsga::Nsga2, Optimizer},
+///   selection::RandomSelector,
+///   termination::GenerationTerminator,
+/// };
+///
+/// let recorder = HistoryRecorder::new()
+///   .with_hypervolume([10.0, 10.0])
+///   .with_fronts();
+/// let solutions = Nsga2::builder()
+///   .population(vec![1.0, 2.0, 3.0])
+///   .tester(|x: &f32| [*x, 3.0 - x])
+///   .selector(RandomSelector(2))
+///   .recombinator(|a: &f32, b: &f32| (a + b) / 2.0)
+///   .mutator(|_: &mut f32| {})
+///   .terminator(GenerationTerminator(5))
+///   .observer(recorder.clone())
+///   .build()
+///   .optimize();
+///
+/// let records = recorder.records();
+/// assert_eq!(records.len(), 5);
+/// assert_eq!(recorder.take_fronts().len(), 5);
+///
+/// let mut csv = Vec::new();
+/// write_records_csv(&mut csv, &records).unwrap();
+/// ```
+#[derive(Debug)]
+pub struct HistoryRecorder<S, const N: usize, T = S> {
+  history: Arc<Mutex<History<S, N, T>>>,
+}
+
+struct History<S, const N: usize, T> {
+  records: Vec<GenerationRecord<N>>,
+  fronts: Vec<Vec<T>>,
+  reference: Option<Scores<N>>,
+  labels: Option<ObjectiveLabels<N>>,
+  /// Summarizes solutions of the best front, if they're to be recorded.
+  summarizer: Option<Box<dyn Summarize<S, Summary = T> + Send>>,
+  sorting: SortingBuffers,
+}
+
+impl<S, const N: usize, T> Debug for History<S, N, T> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("History")
+      .field("records", &self.records)
+      .field("fronts", &self.fronts.len())
+      .field("reference", &self.reference)
+      .field("labels", &self.labels)
+      .finish_non_exhaustive()
+  }
+}
+
+impl<S, const N: usize> HistoryRecorder<S, N> {
+  /// Creates a new `HistoryRecorder` that records only summaries of
+  /// generations.
+  pub fn new() -> Self {
+    Self {
+      history: Arc::new(Mutex::new(History {
+        records: Vec::new(),
+        fronts: Vec::new(),
+        reference: None,
+        labels: None,
+        summarizer: None,
+        sorting: SortingBuffers::default(),
+      })),
+    }
+  }
+
+  /// Makes the recorder store clones of nondominated solutions of each
+  /// generation.
+  pub fn with_fronts(self) -> Self
+  where
+    S: Clone,
+  {
+    self.lock().summarizer = Some(Box::new(Cloned));
+    self
+  }
+}
+
+impl<S, const N: usize, T> HistoryRecorder<S, N, T> {
+  /// Makes the recorder calculate hypervolume of each generation relative to
+  /// the `reference` point. See [`hypervolume`].
+  pub fn with_hypervolume(self, reference: Scores<N>) -> Self {
+    self.lock().reference = Some(reference);
+    self
+  }
+
+  /// Makes the recorder store summaries of nondominated solutions of each
+  /// generation made by the `summarizer`, instead of clones of solutions.
+  ///
+  /// Returns a new recorder with records of this one, so clone the recorder
+  /// after calling this method.
+  pub fn with_front_summaries<Z>(
+    self,
+    summarizer: Z,
+  ) -> HistoryRecorder<S, N, Z::Summary>
+  where
+    Z: Summarize<S> + Send + 'static,
+  {
+    let mut history = self.lock();
+    HistoryRecorder {
+      history: Arc::new(Mutex::new(History {
+        records: std::mem::take(&mut history.records),
+        fronts: Vec::new(),
+        reference: history.reference,
+        labels: history.labels,
+        summarizer: Some(Box::new(summarizer)),
+        sorting: SortingBuffers::default(),
+      })),
+    }
+  }
+
+  /// Returns recorded summaries of generations.
+  pub fn records(&self) -> Vec<GenerationRecord<N>> {
+    self.lock().records.clone()
+  }
+
+  /// Takes recorded fronts out of the recorder. Fronts are recorded only if
+  /// the recorder was created [`with_fronts`](HistoryRecorder::with_fronts)
+  /// or [`with_front_summaries`](Self::with_front_summaries).
+  pub fn take_fronts(&self) -> Vec<Vec<T>> {
+    std::mem::take(&mut self.lock().fronts)
+  }
+
+  /// Returns labels of objectives the recorder has received from the
+  /// optimizer, see [`Observer::label_objectives`].
+  pub fn labels(&self) -> Option<ObjectiveLabels<N>> {
+    self.lock().labels
+  }
+
+  /// Writes recorded summaries of generations as CSV. See
+  /// [`write_records_csv`]. If the recorder has received labels of
+  /// objectives, columns are named by them, as [`write_labeled_records_csv`]
+  /// does.
+  pub fn write_csv(&self, writer: impl Write) -> io::Result<()> {
+    let history = self.lock();
+    match &history.labels {
+      Some(labels) => {
+        write_labeled_records_csv(writer, &history.records, labels)
+      }
+      None => write_records_csv(writer, &history.records),
+    }
+  }
+
+  fn lock(&self) -> MutexGuard<'_, History<S, N, T>> {
+    // a poisoned lock means an observer has panicked mid-record, the history
+    // is still usable
+    self.history.lock().unwrap_or_else(|e| e.into_inner())
+  }
+}
+
+impl<S, const N: usize> Default for HistoryRecorder<S, N> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<S, const N: usize, T> Clone for HistoryRecorder<S, N, T> {
+  fn clone(&self) -> Self {
+    Self {
+      history: Arc::clone(&self.history),
+    }
+  }
+}
+
+impl<S, const N: usize, T> Observer<S, N> for HistoryRecorder<S, N, T> {
+  fn observe(
+    &mut self,
+    generation: usize,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) {
+    let mut history = self.lock();
+    let History {
+      records,
+      fronts,
+      reference,
+      summarizer,
+      sorting,
+      ..
+    } = &mut *history;
+    records.push(GenerationRecord::new(
+      generation,
+      scores,
+      reference.as_ref(),
+    ));
+    if let Some(summarizer) = summarizer {
+      nondominated_sort_with(scores, sorting);
+      let front = sorting.fronts().next().unwrap_or_default();
+      fronts.push(
+        front
+          .iter()
+          .map(|&idx| summarizer.summarize(&solutions[from_index(idx)]))
+          .collect(),
+      );
+    }
+  }
+
+  fn label_objectives(&mut self, labels: &ObjectiveLabels<N>) {
+    self.lock().labels = Some(*labels);
+  }
+}
+
+/// Writes `records` as CSV with a header. Each row contains the generation
+/// number, the population size, minimums, means and maximums of objectives'
+/// scores and the hypervolume, which is left empty if it wasn't calculated.
+///
+/// # Examples
+/// ```
+/// use moga::observer::{write_records_csv, GenerationRecord};
+///
+/// let record = GenerationRecord {
+///   generation: 0,
+///   population_size: 2,
+///   min: [1.0],
+///   mean: [1.5],
+///   max: [2.0],
+///   hypervolume: None,
+/// };
+/// let mut csv = Vec::new();
+/// write_records_csv(&mut csv, &[record]).unwrap();
+/// assert_eq!(
+///   String::from_utf8(csv).unwrap(),
+///   "generation,population_size,min_0,mean_0,max_0,hypervolume\n0,2,1,1.5,2,\n"
+/// );
+/// ```
+pub fn write_records_csv<const N: usize>(
+  writer: impl Write,
+  records: &[GenerationRecord<N>],
+) -> io::Result<()> {
+  let suffixes: [String; N] = std::array::from_fn(|o_idx| o_idx.to_string());
+  write_records_csv_with_suffixes(writer, records, &suffixes)
+}
+
+/// Writes `records` as CSV with a header, like [`write_records_csv`] does,
+/// but names columns of objectives' statistics by their `labels`, such as
+/// `min_cost`.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   observer::{write_labeled_records_csv, GenerationRecord},
+///   score::ObjectiveLabels,
+/// };
+///
+/// let record = GenerationRecord {
+///   generation: 0,
+///   population_size: 2,
+///   min: [1.0],
+///   mean: [1.5],
+///   max: [2.0],
+///   hypervolume: None,
+/// };
+/// let mut csv = Vec::new();
+/// write_labeled_records_csv(&mut csv, &[record], &ObjectiveLabels(["cost"]))
+///   .unwrap();
+/// assert_eq!(
+///   String::from_utf8(csv).unwrap(),
+///   "generation,population_size,min_cost,mean_cost,max_cost,hypervolume\n\
+///    0,2,1,1.5,2,\n"
+/// );
+/// ```
+pub fn write_labeled_records_csv<const N: usize>(
+  writer: impl Write,
+  records: &[GenerationRecord<N>],
+  labels: &ObjectiveLabels<N>,
+) -> io::Result<()> {
+  write_records_csv_with_suffixes(writer, records, &labels.0)
+}
+
+fn write_records_csv_with_suffixes<const N: usize>(
+  mut writer: impl Write,
+  records: &[GenerationRecord<N>],
+  suffixes: &[impl Display; N],
+) -> io::Result<()> {
+  write!(writer, "generation,population_size")?;
+  for stat in ["min", "mean", "max"] {
+    for suffix in suffixes {
+      write!(writer, ",{stat}_{suffix}")?;
+    }
+  }
+  writeln!(writer, ",hypervolume")?;
+
+  for record in records {
+    write!(writer, "{},{}", record.generation, record.population_size)?;
+    for stat in [&record.min, &record.mean, &record.max] {
+      for score in stat {
+        write!(writer, ",{score}")?;
+      }
+    }
+    match record.hypervolume {
+      Some(hv) => writeln!(writer, ",{hv}")?,
+      None => writeln!(writer, ",")?,
+    }
+  }
+  writer.flush()
+}
+
+/// An [`Observer`] that appends the first front of each generation to a CSV
+/// file as soon as the generation completes, so that a crashed run loses
+/// nothing. Each row contains the generation number, scores of a
+/// nondominated solution, named `objective_0`, `objective_1`, etc. or by
+/// labels of objectives, and `C` columns returned by `solution_formatter`,
+/// named `solution_0`, `solution_1`, etc. Fields are quoted like
+/// [`write_csv`] does. The writer is flushed after each generation.
+///
+/// The observer can't stop optimization, so a write error doesn't panic.
+/// Instead, the observer stops writing and keeps the error until it's taken
+/// with [`take_error`]. `StreamingFrontWriter` is a handle to a shared
+/// writer, so give a clone to an optimizer and check the original handle for
+/// errors after or during optimization.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   observer::StreamingFrontWriter,
+///   optimizer::{nsga::Nsga2, Optimizer},
+///   selection::RandomSelector,
+///   termination::GenerationTerminator,
+/// };
+///
+/// # let dir = std::env::temp_dir().join("moga_streaming_front_doctest");
+/// # std::fs::create_dir_all(&dir).unwrap();
+/// # let path = dir.join("fronts.csv");
+/// # let _ = std::fs::remove_file(&path);
+/// let writer =
+///   StreamingFrontWriter::create(&path, |x: &f32| [x.to_string()]).unwrap();
+/// Nsga2::builder()
+///   .population(vec![1.0, 2.0, 3.0])
+///   .tester(|x: &f32| [*x, 3.0 - x])
+///   .selector(RandomSelector(2))
+///   .recombinator(|a: &f32, b: &f32| (a + b) / 2.0)
+///   .mutator(|_: &mut f32| {})
+///   .terminator(GenerationTerminator(5))
+///   .observer(writer.clone())
+///   .build()
+///   .optimize();
+/// assert!(writer.take_error().is_none());
+///
+/// let csv = std::fs::read_to_string(&path).unwrap();
+/// assert!(csv.starts_with("generation,objective_0,objective_1,solution_0\n"));
+/// assert!(csv.lines().skip(1).all(|row| row.split(',').count() == 4));
+/// ```
+///
+/// [`write_csv`]: crate::export::write_csv
+/// [`take_error`]: StreamingFrontWriter::take_error
+pub struct StreamingFrontWriter<W, F, const C: usize> {
+  stream: Arc<Mutex<FrontStream<W, F>>>,
+}
+
+struct FrontStream<W, F> {
+  writer: W,
+  solution_formatter: F,
+  labels: Option<Vec<String>>,
+  header_written: bool,
+  /// Whether writing has failed, after which nothing is written anymore.
+  failed: bool,
+  error: Option<io::Error>,
+}
+
+impl<W: Write, F, const C: usize> StreamingFrontWriter<W, F, C> {
+  /// Creates an observer that writes fronts into the `writer`, formatting
+  /// solutions with the `solution_formatter`. The header is written before
+  /// the first front.
+  pub fn new<S>(writer: W, solution_formatter: F) -> Self
+  where
+    F: Fn(&S) -> [String; C],
+  {
+    Self::with_header(writer, solution_formatter, false)
+  }
+
+  fn with_header(
+    writer: W,
+    solution_formatter: F,
+    header_written: bool,
+  ) -> Self {
+    Self {
+      stream: Arc::new(Mutex::new(FrontStream {
+        writer,
+        solution_formatter,
+        labels: None,
+        header_written,
+        failed: false,
+        error: None,
+      })),
+    }
+  }
+
+  /// Takes the error writing has failed with, if it has. Nothing is written
+  /// after an error, even if it has been taken.
+  pub fn take_error(&self) -> Option<io::Error> {
+    self.lock().error.take()
+  }
+
+  /// Returns `true` if writing has failed, after which nothing is written.
+  pub fn has_failed(&self) -> bool {
+    self.lock().failed
+  }
+
+  fn lock(&self) -> MutexGuard<'_, FrontStream<W, F>> {
+    // a poisoned lock means a formatter has panicked mid-row, the error is
+    // still worth reading
+    self.stream.lock().unwrap_or_else(|e| e.into_inner())
+  }
+}
+
+impl<F, const C: usize> StreamingFrontWriter<io::BufWriter<File>, F, C> {
+  /// Opens the file at `path` for appending, creating it if it doesn't
+  /// exist, and creates an observer that writes fronts into it, formatting
+  /// solutions with the `solution_formatter`. The header is written only
+  /// into an empty file, so that a resumed run continues the same table.
+  pub fn create<S>(
+    path: impl AsRef<Path>,
+    solution_formatter: F,
+  ) -> io::Result<Self>
+  where
+    F: Fn(&S) -> [String; C],
+  {
+    let file = File::options().create(true).append(true).open(path)?;
+    let header_written = file.metadata()?.len() > 0;
+    Ok(Self::with_header(
+      io::BufWriter::new(file),
+      solution_formatter,
+      header_written,
+    ))
+  }
+}
+
+impl<W, F, const C: usize> Clone for StreamingFrontWriter<W, F, C> {
+  fn clone(&self) -> Self {
+    Self {
+      stream: Arc::clone(&self.stream),
+    }
+  }
+}
+
+impl<W, F, const C: usize> Debug for StreamingFrontWriter<W, F, C> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("StreamingFrontWriter")
+  }
+}
+
+impl<S, const N: usize, W, F, const C: usize> Observer<S, N>
+  for StreamingFrontWriter<W, F, C>
+where
+  W: Write,
+  F: Fn(&S) -> [String; C],
+{
+  fn observe(
+    &mut self,
+    generation: usize,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) {
+    let mut stream = self.lock();
+    if stream.failed {
+      return;
+    }
+    if let Err(e) = stream.write_front(generation, solutions, scores) {
+      stream.failed = true;
+      stream.error = Some(e);
+    }
+  }
+
+  fn label_objectives(&mut self, labels: &ObjectiveLabels<N>) {
+    self.lock().labels = Some(labels.iter().map(str::to_owned).collect());
+  }
+}
+
+impl<W: Write, F> FrontStream<W, F> {
+  /// Writes rows of nondominated solutions of the `generation`, preceded by
+  /// the header if it hasn't been written yet, and flushes the writer.
+  fn write_front<S, const N: usize, const C: usize>(
+    &mut self,
+    generation: usize,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) -> io::Result<()>
+  where
+    F: Fn(&S) -> [String; C],
+  {
+    if !self.header_written {
+      let objective_columns: Vec<_> = match &self.labels {
+        Some(labels) => labels.clone(),
+        None => (0..N).map(|o_idx| format!("objective_{o_idx}")).collect(),
+      };
+      let solution_columns = (0..C).map(|c_idx| format!("solution_{c_idx}"));
+      write_row(
+        &mut self.writer,
+        std::iter::once("generation".to_owned())
+          .chain(objective_columns)
+          .chain(solution_columns),
+      )?;
+      self.header_written = true;
+    }
+    for idx in pareto_front_indices(scores) {
+      let score_fields = scores[idx].iter().map(|s| s.to_string());
+      write_row(
+        &mut self.writer,
+        std::iter::once(generation.to_string())
+          .chain(score_fields)
+          .chain((self.solution_formatter)(&solutions[idx])),
+      )?;
+    }
+    self.writer.flush()
+  }
+}
+
+/// An event of optimization process sent by [`EventSender`].
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OptimizerEvent<const N: usize> {
+  /// A generation has completed.
+  GenerationCompleted {
+    /// Number of the generation, starting from `0`.
+    index: usize,
+    /// The best score of each objective in the population, i.e. the score
+    /// closest to `0`.
+    #[cfg_attr(feature = "serde", serde(with = "crate::score::serde_scores"))]
+    best_scores: Scores<N>,
+    /// Number of solutions that survived the generation.
+    population_len: usize,
+    /// Labels of objectives, if the optimizer was built with them. Labels
+    /// aren't deserialized, since they are static strings.
+    #[cfg_attr(feature = "serde", serde(skip_deserializing))]
+    objective_labels: Option<ObjectiveLabels<N>>,
+  },
+  /// Duplicate offsprings have been removed by the optimizer's
+  /// deduplication before testing. Sent each generation only if the optimizer
+  /// has a deduplication operator.
+  DuplicatesRemoved {
+    /// Number of the generation the offsprings were created in.
+    index: usize,
+    /// Number of removed offsprings.
+    count: usize,
+  },
+  /// Offsprings have been dropped untested, because their tests didn't
+  /// finish within the generation's time budget. Sent only if some
+  /// offsprings were dropped.
+  TestsTimedOut {
+    /// Number of the generation the offsprings were created in.
+    index: usize,
+    /// Number of dropped offsprings.
+    count: usize,
+  },
+  /// Time spent in each phase of optimization. Sent once before
+  /// [`OptimizerEvent::Terminated`], only if the optimizer was built with
+  /// `profile(true)`.
+  Profiled {
+    /// Timings of phases over all generations.
+    timings: OperatorTimings,
+  },
+  /// Optimization has terminated.
+  Terminated {
+    /// Why optimization has terminated.
+    reason: TerminationReason,
+  },
+  /// Optimization has failed. It is the last event sent by an optimizer
+  /// before it panics or returns an error.
+  Error {
+    /// Description of the error.
+    message: String,
+  },
+}
+
+/// Total duration and number of calls of a phase of optimization.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PhaseTiming {
+  /// Total duration of all calls.
+  pub total: Duration,
+  /// Number of calls.
+  pub calls: usize,
+}
+
+/// Time spent in each phase of optimization, i.e. in execution of each
+/// operator and in truncation. Collected by optimizers built with
+/// `profile(true)` and sent as [`OptimizerEvent::Profiled`]. Optimizers built
+/// without it take no timers at all.
+///
+/// # Examples
+/// ```
+/// use std::sync::mpsc;
+///
+/// use moga::{
+///   observer::OptimizerEvent,
+///   optimizer::{nsga::Nsga2, Optimizer},
+///   selection::RandomSelector,
+///   termination::GenerationTerminator,
+/// };
+///
+/// let (sender, receiver) = mpsc::channel();
+/// Nsga2::builder()
+///   .population((0..10).map(|i| i as f32).collect())
+///   .tester(|x: &f32| [*x, x - 2.0])
+///   .selector(RandomSelector(4))
+///   .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+///   .mutator(|_: &mut f32| {})
+///   .terminator(GenerationTerminator(5))
+///   .event_sender(sender)
+///   .profile(true)
+///   .build()
+///   .optimize();
+/// let timings = receiver
+///   .try_iter()
+///   .find_map(|event| match event {
+///     OptimizerEvent::Profiled { timings } => Some(timings),
+///     _ => None,
+///   })
+///   .unwrap();
+/// // the initial population is tested, too
+/// assert_eq!(timings.test.calls, 6);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct OperatorTimings {
+  /// Testing of solutions.
+  pub test: PhaseTiming,
+  /// Selection of parents.
+  pub select: PhaseTiming,
+  /// Recombination of parents.
+  pub recombine: PhaseTiming,
+  /// Mutation of offsprings.
+  pub mutate: PhaseTiming,
+  /// Repair of offsprings.
+  pub repair: PhaseTiming,
+  /// Deduplication of offsprings.
+  pub dedup: PhaseTiming,
+  /// Truncation of the population or environmental selection of the
+  /// archive.
+  pub truncate: PhaseTiming,
+  /// Adaptation of constraints.
+  pub constrain: PhaseTiming,
+  /// Termination checks.
+  pub terminate: PhaseTiming,
+}
+
+impl OperatorTimings {
+  /// Returns the total duration of all phases.
+  pub fn total(&self) -> Duration {
+    [
+      self.test,
+      self.select,
+      self.recombine,
+      self.mutate,
+      self.repair,
+      self.dedup,
+      self.truncate,
+      self.constrain,
+      self.terminate,
+    ]
+    .iter()
+    .map(|timing| timing.total)
+    .sum()
+  }
+}
+
+/// A reason of optimization termination.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum TerminationReason {
+  /// The termination operator has decided to terminate optimization.
+  Terminator,
+  /// A pause handler has stopped optimization with [`Directive::Stop`].
+  ///
+  /// [`Directive::Stop`]: crate::pause::Directive::Stop
+  Paused,
+  /// The generation limit of [`Nsga2::optimize_n`] or [`Spea2::optimize_n`]
+  /// has been reached before the termination operator terminated
+  /// optimization.
+  ///
+  /// [`Nsga2::optimize_n`]: crate::optimizer::nsga::Nsga2::optimize_n
+  /// [`Spea2::optimize_n`]: crate::optimizer::spea::Spea2::optimize_n
+  GenerationLimit,
+  /// The flag of the optimizer's cancel [`FlagTerminator`] has been raised.
+  ///
+  /// [`FlagTerminator`]: crate::termination::FlagTerminator
+  Cancelled,
+}
+
+/// A sending half of a channel that optimizers send [`OptimizerEvent`]s
+/// through. Created from sending halves of [`mpsc`] channels.
+///
+/// Sending never blocks optimization. If the channel is bounded, i.e. it was
+/// created with [`mpsc::sync_channel`], events that don't fit into the channel
+/// because the receiver lags are dropped. Unbounded channels created with
+/// [`mpsc::channel`] accumulate events instead. Disconnection of the receiver
+/// is ignored.
+///
+/// With `crossbeam-channel` feature enabled, it can be created from
+/// `crossbeam_channel::Sender` as well.
+///
+/// # Examples
+/// ```
+/// use std::sync::mpsc;
+///
+/// use moga::{
+///   observer::OptimizerEvent,
+///   optimizer::{nsga::Nsga2, Optimizer},
+///   selection::RandomSelector,
+///   termination::GenerationTerminator,
+/// };
+///
+/// let (sender, receiver) = mpsc::sync_channel(16);
+/// Nsga2::builder()
+///   .population(vec![1.0, 2.0, 3.0])
+///   .tester(|x: &f32| [*x, 3.0 - x])
+///   .selector(RandomSelector(2))
+///   .recombinator(|a: &f32, b: &f32| (a + b) / 2.0)
+///   .mutator(|_: &mut f32| {})
+///   .terminator(GenerationTerminator(5))
+///   .event_sender(sender)
+///   .build()
+///   .optimize();
+///
+/// let events: Vec<_> = receiver.try_iter().collect();
+/// assert_eq!(events.len(), 6);
+/// assert!(matches!(events[5], OptimizerEvent::Terminated { .. }));
+/// ```
+#[derive(Clone, Debug)]
+pub struct EventSender<const N: usize>(Channel<N>);
+
+#[derive(Clone, Debug)]
+enum Channel<const N: usize> {
+  Unbounded(mpsc::Sender<OptimizerEvent<N>>),
+  Bounded(mpsc::SyncSender<OptimizerEvent<N>>),
+  #[cfg(feature = "crossbeam-channel")]
+  Crossbeam(crossbeam_channel::Sender<OptimizerEvent<N>>),
+}
+
+impl<const N: usize> EventSender<N> {
+  /// Sends an `event` without blocking.
+  pub(crate) fn send(&self, event: OptimizerEvent<N>) {
+    // errors mean that the receiver is either gone or lags behind, neither of
+    // which should stop optimization
+    match &self.0 {
+      Channel::Unbounded(sender) => sender.send(event).ok(),
+      Channel::Bounded(sender) => sender.try_send(event).ok(),
+      #[cfg(feature = "crossbeam-channel")]
+      Channel::Crossbeam(sender) => sender.try_send(event).ok(),
+    };
+  }
+
+  /// Sends [`OptimizerEvent::GenerationCompleted`] event.
+  pub(crate) fn send_generation<S>(
+    &self,
+    index: usize,
+    solutions: &[S],
+    scores: &[Scores<N>],
+    objective_labels: Option<ObjectiveLabels<N>>,
+  ) {
+    self.send(OptimizerEvent::GenerationCompleted {
+      index,
+      best_scores: ScoreStats::compute(scores).best(),
+      population_len: solutions.len(),
+      objective_labels,
+    });
+  }
+}
+
+impl<const N: usize> From<mpsc::Sender<OptimizerEvent<N>>> for EventSender<N> {
+  fn from(sender: mpsc::Sender<OptimizerEvent<N>>) -> Self {
+    Self(Channel::Unbounded(sender))
+  }
+}
+
+impl<const N: usize> From<mpsc::SyncSender<OptimizerEvent<N>>>
+  for EventSender<N>
+{
+  fn from(sender: mpsc::SyncSender<OptimizerEvent<N>>) -> Self {
+    Self(Channel::Bounded(sender))
+  }
+}
+
+#[cfg(feature = "crossbeam-channel")]
+impl<const N: usize> From<crossbeam_channel::Sender<OptimizerEvent<N>>>
+  for EventSender<N>
+{
+  fn from(sender: crossbeam_channel::Sender<OptimizerEvent<N>>) -> Self {
+    Self(Channel::Crossbeam(sender))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_generation_record() {
+    let scores = [[1.0, -4.0], [3.0, 2.0]];
+    let record = GenerationRecord::new(3, &scores, Some(&[4.0, 5.0]));
+    assert_eq!(record.generation, 3);
+    assert_eq!(record.population_size, 2);
+    assert_eq!(record.min, [1.0, -4.0]);
+    assert_eq!(record.mean, [2.0, -1.0]);
+    assert_eq!(record.max, [3.0, 2.0]);
+    assert_eq!(record.hypervolume, Some(5.0));
+  }
+
+  #[test]
+  fn test_generation_record_empty() {
+    let record = GenerationRecord::<2>::new(0, &[], None);
+    assert_eq!(record.population_size, 0);
+    assert!(record.min.iter().chain(&record.max).all(|s| s.is_nan()));
+    assert_eq!(record.hypervolume, None);
+  }
+
+  #[test]
+  fn test_history_recorder_fronts() {
+    let recorder = HistoryRecorder::new().with_fronts();
+    let mut observer = recorder.clone();
+    observer
+      .observe(0, &['a', 'b', 'c'], &[[1.0, 2.0], [2.0, 1.0], [2.0, 2.0]]);
+    observer.observe(1, &['d'], &[[0.0, 0.0]]);
+    assert_eq!(recorder.records().len(), 2);
+    let mut fronts = recorder.take_fronts();
+    fronts[0].sort();
+    assert_eq!(fronts, [vec!['a', 'b'], vec!['d']]);
+  }
+
+  #[test]
+  fn test_history_recorder_front_summaries() {
+    // a solution that can't be cloned
+    struct Heavy {
+      id: u32,
+    }
+
+    let recorder = HistoryRecorder::new()
+      .with_hypervolume([4.0, 4.0])
+      .with_front_summaries(|h: &Heavy| h.id);
+    let mut observer = recorder.clone();
+    let solutions = [Heavy { id: 1 }, Heavy { id: 2 }];
+    observer.observe(0, &solutions, &[[1.0, 1.0], [2.0, 2.0]]);
+    assert_eq!(recorder.take_fronts(), [vec![1]]);
+    assert_eq!(recorder.records()[0].hypervolume, Some(9.0));
+    assert_eq!(Cloned.summarize(&'a'), 'a');
+  }
+
+  #[test]
+  fn test_history_recorder_labels() {
+    let recorder = HistoryRecorder::<char, 2>::new();
+    let mut observer = (recorder.clone(), HistoryRecorder::new());
+    observer.label_objectives(&ObjectiveLabels(["cost", "weight"]));
+    observer.observe(0, &['a'], &[[1.0, 2.0]]);
+    assert_eq!(recorder.labels(), Some(ObjectiveLabels(["cost", "weight"])));
+    assert_eq!(recorder.labels(), observer.1.labels());
+    let mut csv = Vec::new();
+    recorder.write_csv(&mut csv).unwrap();
+    assert_eq!(
+      String::from_utf8(csv).unwrap(),
+      "generation,population_size,min_cost,min_weight,mean_cost,mean_weight,\
+       max_cost,max_weight,hypervolume\n\
+       0,1,1,2,1,2,1,2,\n"
+    );
+  }
+
+  #[test]
+  fn test_history_recorder_without_fronts() {
+    let mut recorder = HistoryRecorder::<char, 1>::new();
+    recorder.observe(0, &['a'], &[[1.0]]);
+    assert_eq!(recorder.records().len(), 1);
+    assert!(recorder.take_fronts().is_empty());
+  }
+
+  #[test]
+  fn test_combined_observers() {
+    let mut generations = Vec::new();
+    let recorder = HistoryRecorder::new();
+    let mut observer = (
+      |generation: usize, _: &[f32], _: &[Scores<1>]| {
+        generations.push(generation)
+      },
+      recorder.clone(),
+    );
+    observer.observe(0, &[1.0], &[[1.0]]);
+    observer.observe(1, &[1.0], &[[1.0]]);
+    drop(observer);
+    assert_eq!(generations, [0, 1]);
+    assert_eq!(recorder.records().len(), 2);
+  }
+
+  #[test]
+  fn test_streaming_front_writer() {
+    let mut csv = Vec::new();
+    let mut writer = StreamingFrontWriter::new(&mut csv, |x: &f32| {
+      [x.to_string(), format!("x = {x}, y = {x}")]
+    });
+    writer.label_objectives(&ObjectiveLabels(["cost", "weight"]));
+    writer.observe(0, &[1.0, 2.0, 3.0], &[[1.0, 4.0], [2.0, 5.0], [3.0, 1.0]]);
+    writer.observe(1, &[4.0], &[[0.0, 0.0]]);
+    assert!(!writer.has_failed());
+    drop(writer);
+    assert_eq!(
+      String::from_utf8(csv).unwrap(),
+      "generation,cost,weight,solution_0,solution_1\n\
+       0,1,4,1,\"x = 1, y = 1\"\n\
+       0,3,1,3,\"x = 3, y = 3\"\n\
+       1,0,0,4,\"x = 4, y = 4\"\n"
+    );
+  }
+
+  #[test]
+  fn test_streaming_front_writer_error() {
+    struct FullDisk(usize);
+
+    impl Write for FullDisk {
+      fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.0 < buf.len() {
+          return Err(io::Error::new(io::ErrorKind::WriteZero, "disk full"));
+        }
+        self.0 -= buf.len();
+        Ok(buf.len())
+      }
+
+      fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+      }
+    }
+
+    let writer =
+      StreamingFrontWriter::new(FullDisk(40), |x: &f32| [x.to_string()]);
+    let mut observer = writer.clone();
+    // the header and the first front fit
+    observer.observe(0, &[1.0], &[[1.0]]);
+    assert!(!writer.has_failed());
+    for generation in 1..3 {
+      observer.observe(generation, &[1.0], &[[1.0]]);
+    }
+    assert!(writer.has_failed());
+    let error = writer.take_error().unwrap();
+    assert_eq!(error.kind(), io::ErrorKind::WriteZero);
+    assert!(writer.take_error().is_none());
+    assert!(writer.has_failed());
+  }
+
+  #[test]
+  fn test_streaming_front_writer_appends() {
+    let dir = std::env::temp_dir().join("moga_streaming_front_writer_test");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(format!("fronts_{}.csv", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    for generation in 0..2 {
+      let mut writer =
+        StreamingFrontWriter::create(&path, |x: &f32| [x.to_string()]).unwrap();
+      writer.observe(generation, &[1.0], &[[2.0]]);
+      assert!(writer.take_error().is_none());
+    }
+    let csv = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    // the header is written only once
+    assert_eq!(csv, "generation,objective_0,solution_0\n0,2,1\n1,2,1\n");
+  }
+
+  #[test]
+  fn test_event_sender_drops_events_when_receiver_lags() {
+    let (sender, receiver) = mpsc::sync_channel(1);
+    let events = EventSender::from(sender);
+    let labels = Some(ObjectiveLabels(["cost", "weight"]));
+    events.send_generation(0, &[(), ()], &[[1.0, -2.0], [-0.5, 3.0]], labels);
+    events.send_generation(1, &[()], &[[1.0, 1.0]], labels);
+    assert_eq!(receiver.try_iter().collect::<Vec<_>>(), [
+      OptimizerEvent::GenerationCompleted {
+        index: 0,
+        best_scores: [-0.5, -2.0],
+        population_len: 2,
+        objective_labels: labels,
+      }
+    ]);
+  }
+
+  #[test]
+  fn test_event_sender_ignores_disconnected_receiver() {
+    let (sender, receiver) = mpsc::channel();
+    drop(receiver);
+    EventSender::<1>::from(sender).send(OptimizerEvent::Terminated {
+      reason: TerminationReason::Terminator,
+    });
+  }
+
+  #[cfg(feature = "crossbeam-channel")]
+  #[test]
+  fn test_event_sender_crossbeam() {
+    let (sender, receiver) = crossbeam_channel::bounded(1);
+    let events = EventSender::<1>::from(sender);
+    events.send_generation(0, &[()], &[[1.0]], None);
+    events.send_generation(1, &[()], &[[1.0]], None);
+    assert_eq!(receiver.try_iter().count(), 1);
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn test_records_and_events_serde_round_trip() {
+    let record = GenerationRecord::new(3, &[[1.0, -2.0], [3.0, 4.0]], None);
+    let json = serde_json::to_string(&record).unwrap();
+    assert_eq!(
+      serde_json::from_str::<GenerationRecord<2>>(&json).unwrap(),
+      record
+    );
+
+    for event in [
+      OptimizerEvent::GenerationCompleted {
+        index: 1,
+        best_scores: [0.5, 1.5],
+        population_len: 10,
+        objective_labels: None,
+      },
+      OptimizerEvent::Terminated {
+        reason: TerminationReason::Terminator,
+      },
+      OptimizerEvent::Error {
+        message: "error".to_string(),
+      },
+    ] {
+      let json = serde_json::to_string(&event).unwrap();
+      assert_eq!(
+        serde_json::from_str::<OptimizerEvent<2>>(&json).unwrap(),
+        event
+      );
+    }
+
+    // labels are serialized, but not deserialized
+    let event = OptimizerEvent::GenerationCompleted {
+      index: 1,
+      best_scores: [0.5, 1.5],
+      population_len: 10,
+      objective_labels: Some(ObjectiveLabels(["cost", "weight"])),
+    };
+    let json = serde_json::to_string(&event).unwrap();
+    assert!(json.contains(r#""objective_labels":["cost","weight"]"#));
+    assert!(matches!(
+      serde_json::from_str::<OptimizerEvent<2>>(&json).unwrap(),
+      OptimizerEvent::GenerationCompleted {
+        objective_labels: None,
+        ..
+      }
+    ));
+  }
+}
@@ -1,11 +1,35 @@
 //! Common GA operators' utilities.
 
-use std::marker::PhantomData;
+#[cfg(feature = "parallel")]
+mod parallel;
 
+#[cfg(feature = "parallel")]
+pub use parallel::{
+  AutoDecision,
+  ParAuto,
+  ParAutoOperator,
+  ParBatch,
+  ParBatchOperator,
+  ParEach,
+  ParEachOperator,
+};
+
+use crate::{
+  mutation::Mutation,
+  recombination::Recombination,
+  score::Scores,
+  selection::Selection,
+  termination::Termination,
+  testing::Test,
+};
+
+#[cfg(feature = "parallel")]
 pub(crate) mod tag {
   #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
   pub enum TestOperatorTag {}
   #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+  pub enum BatchTestOperatorTag {}
+  #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
   pub enum SelectionOperatorTag {}
   #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
   pub enum RecombinationOperatorTag {}
@@ -15,81 +39,267 @@ pub(crate) mod tag {
   pub enum TerminationOperatorTag {}
 }
 
-/// A wrapper around an operator that marks it to
-/// be executed in parallel for **each** solution by the executor.
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
-pub struct ParEachOperator<OperatorTag, S, O> {
-  operator: O,
-  _solution: PhantomData<S>,
-  _operator_tag: PhantomData<OperatorTag>,
+/// An operator that borrows a read-only `context`, such as a distance matrix
+/// or a dataset, and passes it to the function `f` along with the arguments
+/// of the operator. It turns such function into a [`Test`], [`Selection`],
+/// [`Recombination`], [`Mutation`] or [`Termination`], depending on its
+/// signature.
+///
+/// The context is only borrowed, so it doesn't have to be wrapped into an
+/// [`Arc`] or live for `'static`, as long as it outlives the optimizer. The
+/// operator is [`Sync`] if the context and the function are, so it can be
+/// parallelized with `par_each()` or `par_batch()` methods too.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   operator::WithContext,
+///   optimizer::{nsga::Nsga2, Optimizer},
+///   selection::RandomSelector,
+///   termination::GenerationTerminator,
+/// };
+///
+/// // a problem instance that isn't `'static`
+/// let targets = vec![1.0, 2.0];
+/// let solutions = Nsga2::builder()
+///   .population(vec![0.0, 3.0])
+///   .tester(WithContext::new(&targets, |targets: &Vec<f32>, x: &f32| {
+///     [x - targets[0], x - targets[1]]
+///   }))
+///   .selector(RandomSelector(2))
+///   .recombinator(WithContext::new(
+///     &targets,
+///     |_: &Vec<f32>, [a, b]: [&f32; 2]| [(a + b) / 2.0],
+///   ))
+///   .mutator(WithContext::new(
+///     &targets,
+///     |targets: &Vec<f32>, x: &mut f32| *x = x.clamp(targets[0], targets[1]),
+///   ))
+///   .terminator(GenerationTerminator(10))
+///   .build()
+///   .optimize();
+/// assert!(solutions.iter().all(|x| (1.0..=2.0).contains(x)));
+/// ```
+///
+/// [`Test`]: crate::testing::Test
+/// [`Selection`]: crate::selection::Selection
+/// [`Recombination`]: crate::recombination::Recombination
+/// [`Mutation`]: crate::mutation::Mutation
+/// [`Termination`]: crate::termination::Termination
+/// [`Arc`]: std::sync::Arc
+pub struct WithContext<'a, C: ?Sized, F> {
+  context: &'a C,
+  f: F,
 }
 
-impl<OperatorTag, S, O> ParEachOperator<OperatorTag, S, O> {
-  pub(crate) fn operator(&self) -> &O {
-    &self.operator
+impl<'a, C: ?Sized, F> WithContext<'a, C, F> {
+  /// Creates an operator that calls `f` with the borrowed `context`.
+  pub fn new(context: &'a C, f: F) -> Self {
+    Self { context, f }
+  }
+
+  /// Returns the borrowed context.
+  pub fn context(&self) -> &'a C {
+    self.context
   }
 }
 
-/// Used to tag an operator as parallelized for **each** solution.
-///
-/// This conversion is cheap and doesn't change operator's behavior. However,
-/// executors treat tagged operators differently.
-pub trait ParEach<OperatorTag, S, const N: usize, const M: usize> {
-  /// Creates a wrapper around the given operator that marks it to
-  /// be executed in parallel for **each** solution.
-  ///
-  /// **Parallelization is implemented with [rayon]. As a result, for simple
-  /// functions, parallelization may only decrease performance because of
-  /// additional overhead introduced. Benchmark if in doubt.**
-  fn par_each(self) -> ParEachOperator<OperatorTag, S, Self>
-  where
-    Self: Sized,
-  {
-    ParEachOperator {
-      operator: self,
-      _solution: PhantomData,
-      _operator_tag: PhantomData,
+impl<C: ?Sized, F: Clone> Clone for WithContext<'_, C, F> {
+  fn clone(&self) -> Self {
+    Self {
+      context: self.context,
+      f: self.f.clone(),
     }
   }
 }
 
-/// A wrapper around an operator that marks it to
-/// be executed in parallel for each **batch** of solutions by the executor.
-#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
-pub struct ParBatchOperator<OperatorTag, S, O> {
-  operator: O,
-  _solution: PhantomData<S>,
-  _operator_tag: PhantomData<OperatorTag>,
+impl<C: ?Sized, F: Copy> Copy for WithContext<'_, C, F> {}
+
+impl<C: ?Sized, F> std::fmt::Debug for WithContext<'_, C, F> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("WithContext").finish_non_exhaustive()
+  }
+}
+
+impl<S, const N: usize, C: ?Sized, F> Test<S, N> for WithContext<'_, C, F>
+where
+  F: Fn(&C, &S) -> Scores<N>,
+{
+  fn test(&self, solution: &S) -> Scores<N> {
+    (self.f)(self.context, solution)
+  }
+}
+
+impl<S, const N: usize, C: ?Sized, F> Selection<S, N> for WithContext<'_, C, F>
+where
+  F: Fn(&C, &S, &Scores<N>) -> bool,
+{
+  fn select(&self, solution: &S, scores: &Scores<N>) -> bool {
+    (self.f)(self.context, solution, scores)
+  }
+}
+
+impl<S, const P: usize, const O: usize, C: ?Sized, F> Recombination<S, P, O>
+  for WithContext<'_, C, F>
+where
+  F: Fn(&C, [&S; P]) -> [S; O],
+{
+  fn recombine(&self, parents: [&S; P]) -> [S; O] {
+    (self.f)(self.context, parents)
+  }
+}
+
+impl<S, C: ?Sized, F> Mutation<S> for WithContext<'_, C, F>
+where
+  F: Fn(&C, &mut S),
+{
+  fn mutate(&self, solution: &mut S) {
+    (self.f)(self.context, solution)
+  }
 }
 
-impl<OperatorTag, S, O> ParBatchOperator<OperatorTag, S, O> {
-  pub(crate) fn operator(&self) -> &O {
-    &self.operator
+impl<S, const N: usize, C: ?Sized, F> Termination<S, N>
+  for WithContext<'_, C, F>
+where
+  F: Fn(&C, &S, &Scores<N>) -> bool,
+{
+  fn terminate(&self, solution: &S, scores: &Scores<N>) -> bool {
+    (self.f)(self.context, solution, scores)
   }
 }
 
-/// Used to tag an operator as parallelized for each **batch** of solutions.
+/// An operator that learns from the outcome of its generations. Optimizers
+/// built with a `feedback` option tell it, after each generation's
+/// truncation, how many offsprings were created in the generation and how
+/// many of them survived, so that an operator like
+/// [`AdaptiveRecombinator`] can reward the variants that created them.
+///
+/// Offsprings are recognized among survivors by their scores, so an offspring
+/// that scores the same as one of its parents counts as survived if either of
+/// them survives.
+///
+/// # Examples
+/// Any closure that takes the generation number, the number of offsprings and
+/// the number of survived offsprings is an `OperatorFeedback`.
+/// ```
+/// let f = |generation: usize, offsprings: usize, survived: usize| {
+///   println!("generation {generation}: {survived} of {offsprings} survived");
+/// };
+/// ```
+///
+/// **Note that you always can implement this trait instead of using closures.**
 ///
-/// This conversion is cheap and doesn't change operator's behavior. However,
-/// executors treat tagged operators differently.
-pub trait ParBatch<OperatorTag, S, const N: usize> {
-  /// Creates a wrapper around the given operator that marks it to
-  /// be executed in parallel for each **batch** of solutions.
-  /// The crate calculates the size of the batch in such a way as to evenly
-  /// distribute the calculations across all available threads. This is usually
-  /// faster than parallelization for each individual solution.
-  ///
-  /// **Parallelization is implemented with [rayon]. As a result, for simple
-  /// functions, parallelization may only decrease performance because of
-  /// additional overhead introduced. Benchmark if in doubt.**
-  fn par_batch(self) -> ParBatchOperator<OperatorTag, S, Self>
-  where
-    Self: Sized,
-  {
-    ParBatchOperator {
-      operator: self,
-      _solution: PhantomData,
-      _operator_tag: PhantomData,
+/// [`AdaptiveRecombinator`]: crate::recombination::AdaptiveRecombinator
+pub trait OperatorFeedback {
+  /// Receives the number of `offsprings` created in the `generation` and the
+  /// number of them that `survived` its truncation.
+  fn feedback(&mut self, generation: usize, offsprings: usize, survived: usize);
+}
+
+impl<F> OperatorFeedback for F
+where
+  F: FnMut(usize, usize, usize),
+{
+  fn feedback(
+    &mut self,
+    generation: usize,
+    offsprings: usize,
+    survived: usize,
+  ) {
+    self(generation, offsprings, survived)
+  }
+}
+
+impl std::fmt::Debug for dyn OperatorFeedback + Send {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("OperatorFeedback")
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::optimizer::nsga::Nsga2;
+
+  /// A problem instance that optimizers only borrow.
+  struct Problem {
+    targets: Vec<f32>,
+    bounds: (f32, f32),
+  }
+
+  fn problem() -> Problem {
+    Problem {
+      targets: vec![1.0, 2.0],
+      bounds: (0.0, 3.0),
     }
   }
+
+  #[test]
+  fn test_operators_with_borrowed_context() {
+    let problem = problem();
+    let solutions = Nsga2::builder()
+      .population(vec![0.0, 1.5, 3.0])
+      .tester(WithContext::new(&problem, |p: &Problem, x: &f32| {
+        [x - p.targets[0], x - p.targets[1]]
+      }))
+      .selector(WithContext::new(&problem, |p: &Problem, x: &f32, _: &_| {
+        (p.bounds.0..=p.bounds.1).contains(x)
+      }))
+      .recombinator(WithContext::new(
+        &problem,
+        |p: &Problem, [a, b]: [&f32; 2]| [((a + b) / 2.0).min(p.bounds.1)],
+      ))
+      .mutator(WithContext::new(&problem, |p: &Problem, x: &mut f32| {
+        *x = x.clamp(p.targets[0], p.targets[1])
+      }))
+      .terminator(WithContext::new(
+        &problem,
+        |p: &Problem, x: &f32, _: &Scores<2>| *x > p.bounds.1,
+      ))
+      .build()
+      .optimize_n(10);
+    assert_eq!(solutions.len(), 3);
+    assert!(solutions.iter().any(|x| (1.0..=2.0).contains(x)));
+  }
+
+  #[cfg(feature = "parallel")]
+  #[test]
+  fn test_parallel_operators_with_borrowed_context() {
+    let problem = problem();
+    let solutions = Nsga2::builder()
+      .population(vec![0.0, 1.5, 3.0])
+      .tester(
+        WithContext::new(&problem, |p: &Problem, x: &f32| {
+          [x - p.targets[0], x - p.targets[1]]
+        })
+        .par_each(),
+      )
+      .selector(
+        WithContext::new(&problem, |p: &Problem, x: &f32, _: &_| {
+          (p.bounds.0..=p.bounds.1).contains(x)
+        })
+        .par_batch(),
+      )
+      .recombinator(
+        WithContext::new(&problem, |p: &Problem, [a, b]: [&f32; 2]| {
+          [((a + b) / 2.0).min(p.bounds.1)]
+        })
+        .par_each(),
+      )
+      .mutator(
+        WithContext::new(&problem, |p: &Problem, x: &mut f32| {
+          *x = x.clamp(p.targets[0], p.targets[1])
+        })
+        .par_batch(),
+      )
+      .terminator(
+        WithContext::new(&problem, |p: &Problem, x: &f32, _: &Scores<2>| {
+          *x > p.bounds.1
+        })
+        .par_each(),
+      )
+      .build()
+      .optimize_n(10);
+    assert_eq!(solutions.len(), 3);
+  }
 }
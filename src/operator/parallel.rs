@@ -0,0 +1,386 @@
+//! Wrappers that mark operators to be executed in parallel.
+
+use std::{
+  marker::PhantomData,
+  sync::atomic::{AtomicU8, AtomicUsize, Ordering},
+  time::Instant,
+};
+
+use crate::execution::PARALLEL_WORK_THRESHOLD;
+
+/// A wrapper around an operator that marks it to
+/// be executed in parallel for **each** solution by the executor.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ParEachOperator<OperatorTag, S, O> {
+  operator: O,
+  _solution: PhantomData<S>,
+  _operator_tag: PhantomData<OperatorTag>,
+}
+
+impl<OperatorTag, S, O> ParEachOperator<OperatorTag, S, O> {
+  pub(crate) fn operator(&self) -> &O {
+    &self.operator
+  }
+}
+
+/// Used to tag an operator as parallelized for **each** solution.
+///
+/// This conversion is cheap and doesn't change operator's behavior. However,
+/// executors treat tagged operators differently.
+pub trait ParEach<OperatorTag, S, const N: usize, const M: usize> {
+  /// Creates a wrapper around the given operator that marks it to
+  /// be executed in parallel for **each** solution.
+  ///
+  /// **Parallelization is implemented with [rayon]. As a result, for simple
+  /// functions, parallelization may only decrease performance because of
+  /// additional overhead introduced. Benchmark if in doubt.**
+  fn par_each(self) -> ParEachOperator<OperatorTag, S, Self>
+  where
+    Self: Sized,
+  {
+    ParEachOperator {
+      operator: self,
+      _solution: PhantomData,
+      _operator_tag: PhantomData,
+    }
+  }
+}
+
+/// A wrapper around an operator that marks it to
+/// be executed in parallel for each **batch** of solutions by the executor.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ParBatchOperator<OperatorTag, S, O> {
+  operator: O,
+  _solution: PhantomData<S>,
+  _operator_tag: PhantomData<OperatorTag>,
+}
+
+impl<OperatorTag, S, O> ParBatchOperator<OperatorTag, S, O> {
+  pub(crate) fn operator(&self) -> &O {
+    &self.operator
+  }
+}
+
+/// Used to tag an operator as parallelized for each **batch** of solutions.
+///
+/// This conversion is cheap and doesn't change operator's behavior. However,
+/// executors treat tagged operators differently.
+pub trait ParBatch<OperatorTag, S, const N: usize> {
+  /// Creates a wrapper around the given operator that marks it to
+  /// be executed in parallel for each **batch** of solutions.
+  /// The crate calculates the size of the batch in such a way as to evenly
+  /// distribute the calculations across all available threads. This is usually
+  /// faster than parallelization for each individual solution.
+  ///
+  /// Solutions are split into at most as many batches as there are threads in
+  /// the current [rayon] pool, and all batches but the last one are of the
+  /// same size. Regardless of the number of threads, the operator is applied
+  /// to each solution exactly once, and results, such as scores or indices of
+  /// selected solutions, keep the order of solutions.
+  ///
+  /// **Parallelization is implemented with [rayon]. As a result, for simple
+  /// functions, parallelization may only decrease performance because of
+  /// additional overhead introduced. Benchmark if in doubt.**
+  fn par_batch(self) -> ParBatchOperator<OperatorTag, S, Self>
+  where
+    Self: Sized,
+  {
+    ParBatchOperator {
+      operator: self,
+      _solution: PhantomData,
+      _operator_tag: PhantomData,
+    }
+  }
+}
+
+/// How a [`ParAutoOperator`] executes its operator until its next
+/// calibration.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum AutoDecision {
+  /// The operator is applied to each solution sequentially.
+  Sequential,
+  /// The operator is applied in parallel to each **batch** of solutions, like
+  /// [`ParBatchOperator`] does.
+  Batch,
+}
+
+/// The number of solutions that a [`ParAutoOperator`] times its operator on
+/// to calibrate.
+const CALIBRATION_SAMPLE: usize = 8;
+
+/// The number of executions after which a [`ParAutoOperator`] calibrates
+/// again by default.
+const DEFAULT_RECALIBRATION: usize = 10;
+
+/// A wrapper around an operator that marks it to be executed either
+/// sequentially or in parallel for each **batch** of solutions, whichever is
+/// expected to be faster.
+///
+/// On its first execution, the executor times the operator on a small sample
+/// of solutions, sequentially, then processes the rest of them in parallel
+/// only if their estimated sequential cost outweighs the overhead of
+/// parallelization. The decision holds for the following executions, until
+/// the executor calibrates again, which it does every
+/// [`recalibrate_every`](Self::recalibrate_every) executions. Solutions of
+/// the sample are processed once, and results keep the order of solutions.
+#[derive(Debug)]
+pub struct ParAutoOperator<OperatorTag, S, O> {
+  operator: O,
+  recalibration: usize,
+  executions: AtomicUsize,
+  decision: AtomicU8,
+  _solution: PhantomData<S>,
+  _operator_tag: PhantomData<OperatorTag>,
+}
+
+impl<OperatorTag, S, O> ParAutoOperator<OperatorTag, S, O> {
+  /// Sets the number of executions after which the executor calibrates again,
+  /// `10` by default.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `executions` is `0`.
+  pub fn recalibrate_every(mut self, executions: usize) -> Self {
+    assert!(executions > 0, "the number of executions must be positive");
+    self.recalibration = executions;
+    self
+  }
+
+  /// Returns the decision of the last calibration, or `None` if the operator
+  /// hasn't been calibrated yet.
+  pub fn decision(&self) -> Option<AutoDecision> {
+    match self.decision.load(Ordering::Relaxed) {
+      SEQUENTIAL => Some(AutoDecision::Sequential),
+      BATCH => Some(AutoDecision::Batch),
+      _ => None,
+    }
+  }
+
+  pub(crate) fn operator(&self) -> &O {
+    &self.operator
+  }
+
+  /// Decides how to execute the operator on `len` solutions. On calibration,
+  /// `calibrate` is called with the size of the sample and must apply the
+  /// operator to that many first solutions sequentially. Returns the number
+  /// of solutions that `calibrate` has processed and how to process the rest.
+  pub(crate) fn plan(
+    &self,
+    len: usize,
+    calibrate: impl FnOnce(usize),
+  ) -> (usize, AutoDecision) {
+    let current = self.decision().unwrap_or(AutoDecision::Sequential);
+    if len == 0 {
+      return (0, current);
+    }
+    let execution = self.executions.fetch_add(1, Ordering::Relaxed);
+    if !execution.is_multiple_of(self.recalibration) {
+      return (0, current);
+    }
+    let sample = len.min(CALIBRATION_SAMPLE);
+    let start = Instant::now();
+    calibrate(sample);
+    let cost = start.elapsed() / sample as u32;
+    let rest = (len - sample) as u32;
+    let decision = if rayon::current_num_threads() > 1
+      && cost.saturating_mul(rest) >= PARALLEL_WORK_THRESHOLD
+    {
+      AutoDecision::Batch
+    } else {
+      AutoDecision::Sequential
+    };
+    self.decision.store(
+      match decision {
+        AutoDecision::Sequential => SEQUENTIAL,
+        AutoDecision::Batch => BATCH,
+      },
+      Ordering::Relaxed,
+    );
+    (sample, decision)
+  }
+}
+
+const UNCALIBRATED: u8 = 0;
+const SEQUENTIAL: u8 = 1;
+const BATCH: u8 = 2;
+
+impl<OperatorTag, S, O: Clone> Clone for ParAutoOperator<OperatorTag, S, O> {
+  /// Clones the operator, which is calibrated anew.
+  fn clone(&self) -> Self {
+    Self {
+      operator: self.operator.clone(),
+      recalibration: self.recalibration,
+      executions: AtomicUsize::new(0),
+      decision: AtomicU8::new(UNCALIBRATED),
+      _solution: PhantomData,
+      _operator_tag: PhantomData,
+    }
+  }
+}
+
+/// Used to tag an operator as parallelized for each **batch** of solutions
+/// only if that's expected to be faster than sequential execution.
+///
+/// This conversion is cheap and doesn't change operator's behavior. However,
+/// executors treat tagged operators differently.
+pub trait ParAuto<OperatorTag, S, const N: usize> {
+  /// Creates a wrapper around the given operator that marks it to be executed
+  /// either sequentially or in parallel for each **batch** of solutions,
+  /// depending on its measured cost, see [`ParAutoOperator`].
+  ///
+  /// Use it when choosing between sequential execution and `par_batch()`
+  /// comes down to trial and error, e.g. when the cost of the operator
+  /// depends on the problem instance.
+  fn par_auto(self) -> ParAutoOperator<OperatorTag, S, Self>
+  where
+    Self: Sized,
+  {
+    ParAutoOperator {
+      operator: self,
+      recalibration: DEFAULT_RECALIBRATION,
+      executions: AtomicUsize::new(0),
+      decision: AtomicU8::new(UNCALIBRATED),
+      _solution: PhantomData,
+      _operator_tag: PhantomData,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{sync::atomic::AtomicBool, thread, time::Duration};
+
+  use super::*;
+  use crate::{
+    constraining::executor::ConstraintExecutor,
+    mutation::executor::MutationExecutor,
+    score::Scores,
+    selection::executor::SelectionExecutor,
+    termination::executor::TerminationExecutor,
+    testing::executor::TestExecutor,
+  };
+
+  /// Runs `f` in a rayon pool of `threads` threads.
+  fn in_pool<R: Send>(threads: usize, f: impl FnOnce() -> R + Send) -> R {
+    rayon::ThreadPoolBuilder::new()
+      .num_threads(threads)
+      .build()
+      .unwrap()
+      .install(f)
+  }
+
+  fn slow() {
+    thread::sleep(Duration::from_micros(200));
+  }
+
+  #[test]
+  fn test_par_auto_decisions() {
+    let solutions: Vec<_> = (0..32).map(|i| i as f32).collect();
+    let expected: Vec<_> = solutions.iter().map(|x| [x * 2.0]).collect();
+    let tested = AtomicUsize::new(0);
+    let slow_test = |x: &f32| {
+      slow();
+      tested.fetch_add(1, Ordering::Relaxed);
+      [x * 2.0]
+    };
+    let fast_test = |x: &f32| [x * 2.0];
+
+    let tester = slow_test.par_auto();
+    assert_eq!(tester.decision(), None);
+    assert_eq!(in_pool(4, || tester.execute_tests(&solutions)), expected);
+    assert_eq!(tester.decision(), Some(AutoDecision::Batch));
+    // solutions of the calibration sample are tested once
+    assert_eq!(tested.load(Ordering::Relaxed), solutions.len());
+
+    // the rest of solutions is too cheap to parallelize
+    let tester = fast_test.par_auto();
+    assert_eq!(
+      in_pool(4, || tester.execute_tests(&solutions[..9])),
+      &expected[..9]
+    );
+    assert_eq!(tester.decision(), Some(AutoDecision::Sequential));
+
+    // there is nothing to gain from parallelization with a single thread
+    let tester = slow_test.par_auto();
+    assert_eq!(in_pool(1, || tester.execute_tests(&solutions)), expected);
+    assert_eq!(tester.decision(), Some(AutoDecision::Sequential));
+
+    // nothing to calibrate on
+    assert!(tester.clone().execute_tests(&[]).is_empty());
+    assert_eq!(tester.clone().decision(), None);
+  }
+
+  #[test]
+  fn test_par_auto_recalibration() {
+    let is_slow = AtomicBool::new(true);
+    let test = |x: &f32| {
+      if is_slow.load(Ordering::Relaxed) {
+        slow();
+      }
+      [*x]
+    };
+    let tester = test.par_auto().recalibrate_every(2);
+    let solutions = vec![1.0; 32];
+    in_pool(4, || {
+      tester.execute_tests(&solutions);
+      assert_eq!(tester.decision(), Some(AutoDecision::Batch));
+      is_slow.store(false, Ordering::Relaxed);
+      tester.execute_tests(&solutions[..9]);
+      assert_eq!(tester.decision(), Some(AutoDecision::Batch));
+      tester.execute_tests(&solutions[..9]);
+      assert_eq!(tester.decision(), Some(AutoDecision::Sequential));
+    });
+  }
+
+  #[test]
+  fn test_par_auto_executors() {
+    let solutions: Vec<_> = (0..32).map(|i| i as f32).collect();
+    let scores: Vec<_> = solutions.iter().map(|x| [*x]).collect();
+    in_pool(4, || {
+      let mutation = (|x: &mut f32| {
+        slow();
+        *x += 1.0
+      })
+      .par_auto();
+      let mut mutated = solutions.clone();
+      mutation.execute_mutations(&mut mutated);
+      assert_eq!(mutation.decision(), Some(AutoDecision::Batch));
+      assert!(mutated.iter().zip(&solutions).all(|(m, s)| *m == s + 1.0));
+
+      let selection = (|x: &f32, _: &Scores<1>| {
+        slow();
+        (*x as usize).is_multiple_of(3)
+      })
+      .par_auto();
+      let selected = selection.execute_selection(&solutions, &scores);
+      assert_eq!(selection.decision(), Some(AutoDecision::Batch));
+      assert_eq!(selected, (0..32).step_by(3).collect::<Vec<_>>());
+
+      // the last solution is outside of the calibration sample
+      let mut termination = (|x: &f32, _: &Scores<1>| {
+        slow();
+        *x == 31.0
+      })
+      .par_auto();
+      let decision = termination.execute_termination(&solutions, &scores);
+      assert_eq!(termination.decision(), Some(AutoDecision::Batch));
+      assert!(decision.is_stop());
+
+      let constraint = (|x: &f32| {
+        slow();
+        [1.0 - x]
+      })
+      .par_auto();
+      let violations = constraint.execute_constraints(&solutions);
+      assert_eq!(constraint.decision(), Some(AutoDecision::Batch));
+      assert_eq!(violations[31], [-30.0]);
+      assert_eq!(violations.len(), 32);
+    });
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_par_auto_zero_recalibration() {
+    let _ = (|x: &f32| [*x]).par_auto().recalibrate_every(0);
+  }
+}
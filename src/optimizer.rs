@@ -1,11 +1,1494 @@
 //! Abstract optimizer.
 
+use std::{
+  any::Any,
+  borrow::Cow,
+  collections::HashMap,
+  fmt::{self, Display},
+  hash::{DefaultHasher, Hash, Hasher},
+  marker::PhantomData,
+  panic::{self, AssertUnwindSafe},
+  time::Instant,
+};
+
+use crate::{
+  constraining::{
+    executor::{ConstraintAdapter, ConstraintExecutor, RepairExecutor},
+    ErasedConstraint,
+    Feasibility,
+    Repairer,
+  },
+  mutation::executor::MutationExecutor,
+  observer::{EventSender, OperatorTimings, OptimizerEvent, TerminationReason},
+  recombination::executor::RecombinationExecutor,
+  rng::SplitRng,
+  score::{ParetoDominance, Score, Scores},
+  selection::{executor::SelectionExecutor, SelectionContract},
+  termination::executor::TerminationExecutor,
+  testing::{executor::TestExecutor, TestFailure},
+  truncation::Acceptance,
+};
+
 pub mod nsga;
+pub mod phased;
 pub mod spea;
 
 /// Represents an abstract optimizer.
+///
+/// Implementors implement [`Optimizer::optimize`]. Optimizers that can fail
+/// should implement [`Optimizer::try_optimize`], too, which never fails by
+/// default.
 pub trait Optimizer<Solution, const OBJECTIVE_NUM: usize>: Sized {
   /// Runs `Optimizer` until the termination condition is met, then returns
   /// the last found population.
+  ///
+  /// # Panics
+  ///
+  /// Panics if optimization fails with an [`OptimizationError`].
   fn optimize(self) -> Vec<Solution>;
+
+  /// Runs `Optimizer` until the termination condition is met, then returns
+  /// the last found population, or an [`OptimizationError`] if optimization
+  /// fails.
+  ///
+  /// By default, runs [`Optimizer::optimize`], which never fails.
+  fn try_optimize(self) -> Result<Vec<Solution>, OptimizationError> {
+    Ok(self.optimize())
+  }
+
+  /// Runs `Optimizer` until the termination condition is met, then returns
+  /// the last found population. If optimization fails, returns an
+  /// [`OptimizationFailure`] with the population the optimizer had by then,
+  /// so that the work done before the failure isn't lost.
+  ///
+  /// By default, the failure has no population.
+  fn try_optimize_best_effort(
+    self,
+  ) -> Result<Vec<Solution>, OptimizationFailure<Solution>> {
+    self.try_optimize().map_err(|error| OptimizationFailure {
+      error,
+      population: Vec::new(),
+    })
+  }
+
+  /// Runs `Optimizer` until the termination condition is met, then splits
+  /// the last found population into feasible and infeasible solutions by
+  /// their violations of the `constraint`, usually the one the optimizer
+  /// was built with. See [`Feasibility`].
+  ///
+  /// # Panics
+  ///
+  /// Panics if optimization fails with an [`OptimizationError`], or if the
+  /// constraint evaluates a different number of violations than the number
+  /// of solutions or a NaN violation.
+  fn optimize_feasible<ConExecStrat, const CONSTRAINT_NUM: usize>(
+    self,
+    constraint: &impl ConstraintExecutor<Solution, CONSTRAINT_NUM, ConExecStrat>,
+  ) -> Feasibility<Solution, CONSTRAINT_NUM> {
+    self
+      .try_optimize_feasible(constraint)
+      .unwrap_or_else(|e| panic!("{e}"))
+  }
+
+  /// Runs `Optimizer` like [`Optimizer::try_optimize`] does, then splits the
+  /// last found population by violations of the `constraint`, see
+  /// [`Optimizer::optimize_feasible`].
+  ///
+  /// # Panics
+  ///
+  /// Panics if the constraint evaluates a different number of violations
+  /// than the number of solutions or a NaN violation.
+  fn try_optimize_feasible<ConExecStrat, const CONSTRAINT_NUM: usize>(
+    self,
+    constraint: &impl ConstraintExecutor<Solution, CONSTRAINT_NUM, ConExecStrat>,
+  ) -> Result<Feasibility<Solution, CONSTRAINT_NUM>, OptimizationError> {
+    let solutions = self.try_optimize()?;
+    let violations = constraint.execute_constraints(&solutions);
+    Ok(Feasibility::split(solutions, violations))
+  }
+}
+
+/// An [`OptimizationError`] together with the population an optimizer had
+/// when optimization failed, as returned by
+/// [`Optimizer::try_optimize_best_effort`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct OptimizationFailure<S> {
+  /// The error that stopped optimization.
+  pub error: OptimizationError,
+  /// The population an optimizer had when optimization failed. Usually, it's
+  /// the population of the last completed generation, see optimizers' docs
+  /// for details.
+  pub population: Vec<S>,
+}
+
+impl<S> Display for OptimizationFailure<S> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(
+      f,
+      "{} ({} solutions were salvaged)",
+      self.error,
+      self.population.len()
+    )
+  }
+}
+
+impl<S: fmt::Debug> std::error::Error for OptimizationFailure<S> {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    Some(&self.error)
+  }
+}
+
+/// A report of a dry run of an optimizer, such as [`Nsga2::dry_run`], that
+/// applies its operators once to a sample of the initial population.
+///
+/// [`Nsga2::dry_run`]: nsga::Nsga2::dry_run
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DryRunReport {
+  /// Number of sampled solutions of the initial population.
+  pub sampled: usize,
+  /// Number of parents selected out of the sample.
+  pub selected: usize,
+  /// Number of offsprings created out of the parents.
+  pub offsprings: usize,
+  /// Suspicious results of operators, which don't stop optimization but
+  /// usually are mistakes.
+  pub anomalies: Vec<DryRunAnomaly>,
+}
+
+impl DryRunReport {
+  /// Returns `true` if no anomalies were detected.
+  pub fn is_clean(&self) -> bool {
+    self.anomalies.is_empty()
+  }
+}
+
+/// A suspicious result of an operator detected by a dry run, see
+/// [`DryRunReport`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum DryRunAnomaly {
+  /// The tester has evaluated NaN scores of sampled solutions.
+  NanScores {
+    /// Number of solutions with NaN scores.
+    solutions: usize,
+  },
+  /// The tester has evaluated NaN scores of mutated offsprings.
+  OffspringNanScores {
+    /// Number of offsprings with NaN scores.
+    offsprings: usize,
+  },
+  /// The selector has selected fewer parents than the recombinator takes,
+  /// so there are no combinations of parents to recombine.
+  TooFewParents {
+    /// Number of selected parents.
+    selected: usize,
+    /// Number of parents the recombinator takes.
+    required: usize,
+  },
+  /// The recombinator has created no offsprings.
+  NoOffsprings,
+}
+
+impl Display for DryRunAnomaly {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      DryRunAnomaly::NanScores { solutions } => {
+        write!(f, "{solutions} sampled solutions have NaN scores")
+      }
+      DryRunAnomaly::OffspringNanScores { offsprings } => {
+        write!(f, "{offsprings} offsprings have NaN scores")
+      }
+      DryRunAnomaly::TooFewParents { selected, required } => write!(
+        f,
+        "{selected} parents were selected, but recombination takes {required}"
+      ),
+      DryRunAnomaly::NoOffsprings => f.write_str("no offsprings were created"),
+    }
+  }
+}
+
+/// Operators of an optimizer, which [`Nsga2::from_operators`] and
+/// [`Spea2::from_operators`] build optimizers from. The same operators can be
+/// given to either algorithm, so switching between them is a one-line change.
+///
+/// The set holds the five operators every optimizer has and, optionally, a
+/// repair operator and a constraint. Other options of an optimizer keep their
+/// defaults.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   optimizer::{nsga::Nsga2, spea::Spea2, OperatorSet, Optimizer},
+///   selection::RandomSelector,
+///   termination::GenerationTerminator,
+/// };
+///
+/// let operators = || {
+///   OperatorSet::new(
+///     |x: &f32| [x.powf(2.0), (x - 2.0).powf(2.0)],
+///     RandomSelector(3),
+///     |x: &f32, y: &f32| (x + y) / 2.0,
+///     |_: &mut f32| {},
+///     GenerationTerminator(10),
+///   )
+///   .with_repairer(|x: &mut f32| *x = x.clamp(0.0, 2.0))
+/// };
+/// let population = vec![1.0, 2.0, 3.0];
+/// let nsga = Nsga2::from_operators(population.clone(), operators());
+/// let spea = Spea2::from_operators(population, 3, operators());
+/// assert!(nsga.optimize().iter().all(|x| (0.0..=2.0).contains(x)));
+/// assert!(!spea.optimize().is_empty());
+/// ```
+///
+/// [`Nsga2::from_operators`]: nsga::Nsga2::from_operators
+/// [`Spea2::from_operators`]: spea::Spea2::from_operators
+#[derive(Debug)]
+pub struct OperatorSet<
+  Solution,
+  Tst: TestExecutor<Solution, OBJECTIVE_NUM, TstExecStrat>,
+  Sel: SelectionExecutor<Solution, OBJECTIVE_NUM, SelExecStrat>,
+  Rec: RecombinationExecutor<Solution, PARENT_NUM, OFFSPRING_NUM, RecExecStrat>,
+  Mut: MutationExecutor<Solution, MutExecStrat>,
+  Ter: TerminationExecutor<Solution, OBJECTIVE_NUM, TerExecStrat>,
+  TstExecStrat,
+  TerExecStrat,
+  SelExecStrat,
+  MutExecStrat,
+  RecExecStrat,
+  const OBJECTIVE_NUM: usize,
+  const PARENT_NUM: usize,
+  const OFFSPRING_NUM: usize,
+> {
+  pub(crate) tester: Tst,
+  pub(crate) selector: Sel,
+  pub(crate) recombinator: Rec,
+  pub(crate) mutator: Mut,
+  pub(crate) terminator: Ter,
+  pub(crate) repairer: Option<Box<dyn Repairer<Solution> + Send>>,
+  pub(crate) constraint: Option<Box<dyn ConstraintAdapter<Solution> + Send>>,
+  _eva_es: PhantomData<TstExecStrat>,
+  _ter_es: PhantomData<TerExecStrat>,
+  _sel_es: PhantomData<SelExecStrat>,
+  _mut_es: PhantomData<MutExecStrat>,
+  _rec_es: PhantomData<RecExecStrat>,
+}
+
+impl<
+    Solution,
+    Tst: TestExecutor<Solution, OBJECTIVE_NUM, TstExecStrat>,
+    Sel: SelectionExecutor<Solution, OBJECTIVE_NUM, SelExecStrat>,
+    Rec: RecombinationExecutor<Solution, PARENT_NUM, OFFSPRING_NUM, RecExecStrat>,
+    Mut: MutationExecutor<Solution, MutExecStrat>,
+    Ter: TerminationExecutor<Solution, OBJECTIVE_NUM, TerExecStrat>,
+    TstExecStrat,
+    TerExecStrat,
+    SelExecStrat,
+    MutExecStrat,
+    RecExecStrat,
+    const OBJECTIVE_NUM: usize,
+    const PARENT_NUM: usize,
+    const OFFSPRING_NUM: usize,
+  >
+  OperatorSet<
+    Solution,
+    Tst,
+    Sel,
+    Rec,
+    Mut,
+    Ter,
+    TstExecStrat,
+    TerExecStrat,
+    SelExecStrat,
+    MutExecStrat,
+    RecExecStrat,
+    OBJECTIVE_NUM,
+    PARENT_NUM,
+    OFFSPRING_NUM,
+  >
+{
+  /// Creates a set of operators without a repair operator and a constraint.
+  pub fn new(
+    tester: Tst,
+    selector: Sel,
+    recombinator: Rec,
+    mutator: Mut,
+    terminator: Ter,
+  ) -> Self {
+    Self {
+      tester,
+      selector,
+      recombinator,
+      mutator,
+      terminator,
+      repairer: None,
+      constraint: None,
+      _eva_es: PhantomData,
+      _ter_es: PhantomData,
+      _sel_es: PhantomData,
+      _mut_es: PhantomData,
+      _rec_es: PhantomData,
+    }
+  }
+
+  /// Sets a repair operator that is applied to offsprings after mutation
+  /// and before testing.
+  pub fn with_repairer<RepExecStrat: 'static>(
+    mut self,
+    repairer: impl RepairExecutor<Solution, RepExecStrat> + Send + 'static,
+  ) -> Self
+  where
+    Solution: 'static,
+  {
+    self.repairer = Some(Box::new(move |solutions: &mut [Solution]| {
+      repairer.execute_repairs(solutions)
+    }));
+    self
+  }
+
+  /// Sets a [`Constraint`] that is evaluated on the population at the end
+  /// of each generation to let it adapt to violations, like
+  /// [`AdaptivePenalty`] does.
+  ///
+  /// [`Constraint`]: crate::constraining::Constraint
+  /// [`AdaptivePenalty`]: crate::constraining::AdaptivePenalty
+  pub fn with_constraint<ConExecStrat: 'static, const CONSTRAINT_NUM: usize>(
+    mut self,
+    constraint: impl ConstraintExecutor<Solution, CONSTRAINT_NUM, ConExecStrat>
+      + Send
+      + 'static,
+  ) -> Self
+  where
+    Solution: 'static,
+  {
+    self.constraint = Some(Box::new(ErasedConstraint::new(constraint)));
+    self
+  }
+}
+
+/// Returns the number of `scores` that have a NaN score.
+pub(crate) fn count_nan_scores<const N: usize>(scores: &[Scores<N>]) -> usize {
+  scores
+    .iter()
+    .filter(|sc| sc.iter().any(|s| s.is_nan()))
+    .count()
+}
+
+/// An error that stops optimization.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum OptimizationError {
+  /// The selector selected no solutions, so no offsprings could be created.
+  /// See [`EmptySelection`].
+  SelectionEmpty {
+    /// Number of the generation, starting from `0`.
+    generation: usize,
+  },
+  /// An operator has evaluated a different number of scores than the number
+  /// of solutions it was given.
+  ScoreCountMismatch {
+    /// The operator that has evaluated the scores.
+    operator: OperatorKind,
+    /// Number of the generation, starting from `0`. The initial population
+    /// is tested in generation `0`.
+    generation: usize,
+    /// Number of evaluated scores.
+    actual: usize,
+    /// Number of solutions.
+    expected: usize,
+  },
+  /// Offsprings can't replace the population under
+  /// [`Replacement::MuCommaLambda`], because there are fewer of them than
+  /// the population size.
+  TooFewOffsprings {
+    /// Number of the generation, starting from `0`.
+    generation: usize,
+    /// Number of created offsprings.
+    offsprings: usize,
+    /// Size of the population to replace.
+    required: usize,
+  },
+  /// Recombination of the selected parents would create more offsprings than
+  /// the optimizer's `max_offspring_per_generation` limit, and the optimizer
+  /// was built with [`OffspringBudget::Fail`].
+  OffspringBudgetExceeded {
+    /// Number of the generation, starting from `0`.
+    generation: usize,
+    /// Number of offsprings recombination would create.
+    projected: usize,
+    /// Maximum number of offsprings per generation.
+    limit: usize,
+  },
+  /// The selector has selected a number of solutions that breaks its
+  /// [`SelectionContract`], given to it with [`Expect`].
+  ///
+  /// [`Expect`]: crate::selection::Expect
+  SelectionContractViolated {
+    /// Number of the generation, starting from `0`.
+    generation: usize,
+    /// The number of solutions the selector has promised to select.
+    expected: SelectionContract,
+    /// Number of selected solutions.
+    actual: usize,
+  },
+  /// An operator has panicked, and the optimizer was built to catch panics of
+  /// operators with `catch_operator_panics(true)`.
+  OperatorPanicked {
+    /// The operator that has panicked.
+    operator: OperatorKind,
+    /// Number of the generation, starting from `0`. The initial population
+    /// is tested in generation `0`.
+    generation: usize,
+    /// The message the operator has panicked with, if it was a string.
+    payload_message: String,
+  },
+  /// An operator has raised a [`TestFailure`], e.g. a
+  /// [`SubprocessTester`](crate::testing::SubprocessTester) whose worker has
+  /// timed out. Unlike other panics of operators, test failures are caught
+  /// regardless of `catch_operator_panics`.
+  ///
+  /// [`TestFailure`]: crate::testing::TestFailure
+  TestFailed {
+    /// Number of the generation, starting from `0`. The initial population
+    /// is tested in generation `0`.
+    generation: usize,
+    /// The message of the failure.
+    message: String,
+  },
+}
+
+/// A kind of operator that has caused an [`OptimizationError`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum OperatorKind {
+  /// A test operator.
+  Tester,
+  /// A constraint operator.
+  Constrainer,
+  /// A truncation operator.
+  Truncation,
+  /// A termination operator.
+  Terminator,
+  /// A selection operator.
+  Selector,
+  /// A recombination operator.
+  Recombinator,
+  /// A mutation operator.
+  Mutator,
+  /// A repair operator.
+  Repairer,
+  /// A deduplication operator.
+  Deduplicator,
+  /// An observer of generations.
+  Observer,
+  /// A handler of pause points.
+  PauseHandler,
+  /// An acceptance rule of dominated offsprings.
+  Acceptance,
+  /// An operator that receives feedback on survival of offsprings.
+  Feedback,
+}
+
+impl Display for OperatorKind {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(match self {
+      Self::Tester => "tester",
+      Self::Constrainer => "constrainer",
+      Self::Truncation => "truncation",
+      Self::Terminator => "terminator",
+      Self::Selector => "selector",
+      Self::Recombinator => "recombinator",
+      Self::Mutator => "mutator",
+      Self::Repairer => "repairer",
+      Self::Deduplicator => "deduplicator",
+      Self::Observer => "observer",
+      Self::PauseHandler => "pause handler",
+      Self::Acceptance => "acceptance",
+      Self::Feedback => "feedback",
+    })
+  }
+}
+
+impl Display for OptimizationError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::SelectionEmpty { generation } => {
+        write!(f, "no solutions were selected in generation {generation}")
+      }
+      Self::ScoreCountMismatch {
+        operator,
+        generation,
+        actual,
+        expected,
+      } => write!(
+        f,
+        "{operator} has evaluated {actual} scores for {expected} solutions \
+         in generation {generation}"
+      ),
+      Self::TooFewOffsprings {
+        generation,
+        offsprings,
+        required,
+      } => write!(
+        f,
+        "{offsprings} offsprings were created in generation {generation}, \
+         but replacement requires at least {required}"
+      ),
+      Self::OffspringBudgetExceeded {
+        generation,
+        projected,
+        limit,
+      } => write!(
+        f,
+        "recombination would create {projected} offsprings in generation \
+         {generation}, but at most {limit} are allowed"
+      ),
+      Self::SelectionContractViolated {
+        generation,
+        expected,
+        actual,
+      } => write!(
+        f,
+        "{actual} solutions were selected in generation {generation}, but \
+         {expected} were expected"
+      ),
+      Self::OperatorPanicked {
+        operator,
+        generation,
+        payload_message,
+      } => write!(
+        f,
+        "{operator} has panicked in generation {generation}: \
+         {payload_message}"
+      ),
+      Self::TestFailed {
+        generation,
+        message,
+      } => write!(f, "test has failed in generation {generation}: {message}"),
+    }
+  }
+}
+
+impl std::error::Error for OptimizationError {}
+
+/// What an optimizer does if its selector selects no solutions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EmptySelection {
+  /// Optimization fails with [`OptimizationError::SelectionEmpty`].
+  #[default]
+  Fail,
+  /// All solutions are selected for the generation, as [`AllSelector`] does.
+  ///
+  /// [`AllSelector`]: crate::selection::AllSelector
+  SelectAll,
+}
+
+/// What an optimizer does if recombination of the selected parents would
+/// create more offsprings than its `max_offspring_per_generation` limit.
+///
+/// The number of offsprings is projected from the number of parents for
+/// operators that recombine each combination of parents, like
+/// [`Recombination`]. A [`Recombinator`], including a boxed one, creates an
+/// unknown number of offsprings and isn't limited.
+///
+/// [`Recombination`]: crate::recombination::Recombination
+/// [`Recombinator`]: crate::recombination::Recombinator
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum OffspringBudget {
+  /// Optimization fails with [`OptimizationError::OffspringBudgetExceeded`].
+  #[default]
+  Fail,
+  /// Only a uniform sample of combinations of parents that fits the limit is
+  /// recombined. The sample of each generation is drawn from a stream derived
+  /// from the `seed` and the generation with [`SplitRng::indexed`], so it's
+  /// reproducible.
+  Subsample {
+    /// The master seed of samples.
+    seed: u64,
+  },
+}
+
+/// Recombines `solutions` with `scores` at `selected` indices with the
+/// `recombinator`, unless it would create more offsprings than the `limit`, in
+/// which case the `budget` decides what happens.
+pub(crate) fn recombine_within_limit<
+  S,
+  const N: usize,
+  const P: usize,
+  const O: usize,
+  ExecStrat,
+>(
+  recombinator: &impl RecombinationExecutor<S, P, O, ExecStrat>,
+  solutions: &[S],
+  scores: &[Scores<N>],
+  selected: &[usize],
+  limit: Option<usize>,
+  budget: OffspringBudget,
+  generation: usize,
+) -> Result<Vec<S>, OptimizationError> {
+  recombinator.start_generation(generation);
+  let projected = recombinator.projected_offsprings(selected.len());
+  match (limit, projected) {
+    (Some(limit), Some(projected)) if projected > limit => match budget {
+      OffspringBudget::Fail => {
+        Err(OptimizationError::OffspringBudgetExceeded {
+          generation,
+          projected,
+          limit,
+        })
+      }
+      OffspringBudget::Subsample { seed } => {
+        let mut rng = SplitRng::new(seed).indexed(generation as u64, 0);
+        Ok(recombinator.execute_sampled_selected_recombination(
+          solutions, selected, limit, &mut rng,
+        ))
+      }
+    },
+    _ => Ok(
+      recombinator
+        .execute_scored_selected_recombination(solutions, scores, selected),
+    ),
+  }
+}
+
+/// How an optimizer forms the next population out of the current population
+/// of size `mu` and its `lambda` offsprings.
+///
+/// The number of offsprings depends on the selector and the recombinator. To
+/// create exactly `lambda` offsprings, select `lambda` parents with
+/// replacement, with [`TournamentSelectorWithReplacement`] for example, and
+/// recombine each of them into one offspring with a `Recombination<S, 1, 1>`,
+/// a mutating clone for example.
+///
+/// [`TournamentSelectorWithReplacement`]:
+///   crate::selection::TournamentSelectorWithReplacement
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Replacement {
+  /// `(mu + lambda)`: parents and offsprings are merged, then truncated down
+  /// to the population size, so the best solutions are never lost.
+  #[default]
+  MuPlusLambda,
+  /// `(mu, lambda)`: offsprings replace parents and are truncated down to
+  /// the population size. Without elitism, the population can escape local
+  /// optima of rugged landscapes. Optimization fails with
+  /// [`OptimizationError::TooFewOffsprings`] if fewer offsprings than the
+  /// population size are created.
+  MuCommaLambda,
+}
+
+/// Sizes of a population over generations, like the archive of [`Spea2`] as
+/// [`ArchiveSchedule`] or the population of [`Nsga2`]. Optimizers evaluate the
+/// size at each generation before truncating the population, so it may start
+/// large to explore and shrink to exploit, or start with a handful of known
+/// solutions and grow. Sizes below `1` are treated as `1`.
+///
+/// A size converts into a constant schedule.
+///
+/// # Examples
+/// ```
+/// use moga::optimizer::SizeSchedule;
+///
+/// let schedule = SizeSchedule::Linear {
+///   from: 100,
+///   to: 20,
+///   over_generations: 40,
+/// };
+/// assert_eq!(schedule.size(0), 100);
+/// assert_eq!(schedule.size(20), 60);
+/// assert_eq!(schedule.size(1000), 20);
+///
+/// // halves the population every 10 generations
+/// let schedule = SizeSchedule::Custom(Box::new(|g| 128 >> (g / 10)));
+/// assert_eq!(schedule.size(25), 32);
+/// ```
+///
+/// [`Spea2`]: spea::Spea2
+/// [`ArchiveSchedule`]: spea::ArchiveSchedule
+/// [`Nsga2`]: nsga::Nsga2
+pub enum SizeSchedule {
+  /// The same size at each generation.
+  Constant(usize),
+  /// A size that changes linearly from `from` at the first generation to
+  /// `to` at `over_generations` generation, and stays `to` after that.
+  Linear {
+    /// The size at the first generation.
+    from: usize,
+    /// The size from `over_generations` generation on.
+    to: usize,
+    /// The number of generations the size changes over.
+    over_generations: usize,
+  },
+  /// A size returned by a function of the generation number.
+  Custom(Box<dyn Fn(usize) -> usize + Send>),
+}
+
+impl SizeSchedule {
+  /// Returns the population or archive size at the `generation`, starting
+  /// from `0`.
+  pub fn size(&self, generation: usize) -> usize {
+    let size = match self {
+      SizeSchedule::Constant(size) => *size,
+      SizeSchedule::Linear {
+        from,
+        to,
+        over_generations,
+      } => {
+        if generation >= *over_generations {
+          *to
+        } else {
+          let progress = generation as f64 / *over_generations as f64;
+          let size = *from as f64 + (*to as f64 - *from as f64) * progress;
+          size.round() as usize
+        }
+      }
+      SizeSchedule::Custom(schedule) => schedule(generation),
+    };
+    size.max(1)
+  }
+}
+
+impl From<usize> for SizeSchedule {
+  fn from(size: usize) -> Self {
+    SizeSchedule::Constant(size)
+  }
+}
+
+impl std::fmt::Debug for SizeSchedule {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      SizeSchedule::Constant(size) => {
+        f.debug_tuple("Constant").field(size).finish()
+      }
+      SizeSchedule::Linear {
+        from,
+        to,
+        over_generations,
+      } => f
+        .debug_struct("Linear")
+        .field("from", from)
+        .field("to", to)
+        .field("over_generations", over_generations)
+        .finish(),
+      SizeSchedule::Custom(_) => f.write_str("Custom"),
+    }
+  }
+}
+
+/// Sends the `error` as [`OptimizerEvent::Error`] and returns it.
+pub(crate) fn report_error<const N: usize>(
+  error: OptimizationError,
+  event_sender: Option<&EventSender<N>>,
+) -> OptimizationError {
+  if let Some(event_sender) = event_sender {
+    event_sender.send(OptimizerEvent::Error {
+      message: error.to_string(),
+    });
+  }
+  error
+}
+
+/// The operator an optimizer is calling and the generation it's called in,
+/// which a panic is attributed to.
+#[derive(Debug, Default)]
+pub(crate) struct CurrentOperator(Option<(OperatorKind, usize)>);
+
+impl CurrentOperator {
+  /// Calls `f` as the `operator` in the `generation`.
+  pub(crate) fn call<T>(
+    &mut self,
+    operator: OperatorKind,
+    generation: usize,
+    f: impl FnOnce() -> T,
+  ) -> T {
+    self.0 = Some((operator, generation));
+    let result = f();
+    self.0 = None;
+    result
+  }
+}
+
+/// Runs `optimization`, which calls operators through the [`CurrentOperator`]
+/// it's given. If `catch` is set, a panic of an operator is caught and
+/// returned as [`OptimizationError::OperatorPanicked`], sending it as
+/// [`OptimizerEvent::Error`] beforehand. A [`TestFailure`] raised by an
+/// operator is always caught and returned as
+/// [`OptimizationError::TestFailed`]. Panics of the optimizer itself, outside
+/// of operators, are never caught.
+///
+/// Operators aren't required to be `UnwindSafe`: after a panic, the optimizer
+/// returns without calling them again, so a broken invariant of an operator
+/// can't be observed.
+pub(crate) fn catch_operator_panics<T, const N: usize>(
+  catch: bool,
+  event_sender: Option<&EventSender<N>>,
+  optimization: impl FnOnce(&mut CurrentOperator) -> Result<T, OptimizationError>,
+) -> Result<T, OptimizationError> {
+  let mut current = CurrentOperator::default();
+  match panic::catch_unwind(AssertUnwindSafe(|| optimization(&mut current))) {
+    Ok(result) => result,
+    Err(payload) => match current.0 {
+      Some((_, generation)) if payload.is::<TestFailure>() => {
+        let failure = payload.downcast::<TestFailure>().unwrap();
+        Err(report_error(
+          OptimizationError::TestFailed {
+            generation,
+            message: failure.message,
+          },
+          event_sender,
+        ))
+      }
+      Some((operator, generation)) if catch => Err(report_error(
+        OptimizationError::OperatorPanicked {
+          operator,
+          generation,
+          payload_message: panic_message(payload.as_ref()),
+        },
+        event_sender,
+      )),
+      _ => panic::resume_unwind(payload),
+    },
+  }
+}
+
+/// Returns the message of a panic `payload`, which is a string for panics
+/// with a message.
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+  if let Some(message) = payload.downcast_ref::<&str>() {
+    message.to_string()
+  } else if let Some(message) = payload.downcast_ref::<String>() {
+    message.clone()
+  } else {
+    "non-string panic payload".to_string()
+  }
+}
+
+/// Number of objectives `N` as a type. Optimizers' builders are pinned to it
+/// with `objectives::<N>()`, e.g. [`Nsga2Builder::objectives`], so that an
+/// operator that disagrees with it is reported as the culprit.
+///
+/// [`Nsga2Builder::objectives`]: nsga::Nsga2Builder::objectives
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Objectives<const N: usize>;
+
+/// Implemented by [`Objectives<N>`] only for `M` equal to `N`, so that the
+/// compiler infers the number of objectives `N` bounded by it as `M`.
+pub trait EqualTo<const M: usize> {}
+
+impl<const N: usize> EqualTo<N> for Objectives<N> {}
+
+/// The result of optimization along with why and when it has terminated.
+/// Returned by `optimize_with_outcome` methods of optimizers, e.g.
+/// [`Nsga2::optimize_with_outcome`](nsga::Nsga2::optimize_with_outcome).
+#[derive(Clone, Debug, PartialEq)]
+pub struct OptimizationOutcome<S> {
+  /// The last found population.
+  pub solutions: Vec<S>,
+  /// Why optimization has terminated, e.g. the reason given by the
+  /// terminator, see [`Terminator::decide`].
+  ///
+  /// [`Terminator::decide`]: crate::termination::Terminator::decide
+  pub reason: Cow<'static, str>,
+  /// What has terminated optimization.
+  pub termination: TerminationReason,
+  /// The number of generations run, i.e. observed by the observer.
+  pub generations_run: usize,
+}
+
+/// How a run of an optimizer has terminated.
+pub(crate) struct Terminated {
+  /// What has terminated the run.
+  pub kind: TerminationReason,
+  /// Why the run has terminated.
+  pub reason: Cow<'static, str>,
+  /// The number of generations run.
+  pub generations: usize,
+}
+
+impl Terminated {
+  /// Creates a termination of a run after `generations` generations.
+  pub fn new(
+    kind: TerminationReason,
+    reason: impl Into<Cow<'static, str>>,
+    generations: usize,
+  ) -> Self {
+    Self {
+      kind,
+      reason: reason.into(),
+      generations,
+    }
+  }
+
+  /// Creates a termination of a run by the limit of `generations`.
+  pub fn generation_limit(generations: usize) -> Self {
+    Self::new(
+      TerminationReason::GenerationLimit,
+      format!("the limit of {generations} generations has been reached"),
+      generations,
+    )
+  }
+
+  /// Creates a termination of a run by the cancel flag after `generations`
+  /// generations.
+  pub fn cancelled(generations: usize) -> Self {
+    Self::new(
+      TerminationReason::Cancelled,
+      "the cancel flag has been raised",
+      generations,
+    )
+  }
+
+  /// Creates a termination of a run by the pause handler after `generations`
+  /// generations.
+  pub fn paused(generations: usize) -> Self {
+    Self::new(
+      TerminationReason::Paused,
+      "the pause handler has stopped optimization",
+      generations,
+    )
+  }
+
+  /// Pairs the termination with the `solutions` found by the run.
+  pub fn into_outcome<S>(self, solutions: Vec<S>) -> OptimizationOutcome<S> {
+    OptimizationOutcome {
+      solutions,
+      reason: self.reason,
+      termination: self.kind,
+      generations_run: self.generations,
+    }
+  }
+}
+
+/// Sends `timings` as [`OptimizerEvent::Profiled`], if they were measured,
+/// and the termination `reason` as [`OptimizerEvent::Terminated`].
+pub(crate) fn report_termination<const N: usize>(
+  timings: Option<OperatorTimings>,
+  reason: TerminationReason,
+  event_sender: Option<&EventSender<N>>,
+) {
+  if let Some(event_sender) = event_sender {
+    if let Some(timings) = timings {
+      event_sender.send(OptimizerEvent::Profiled { timings });
+    }
+    event_sender.send(OptimizerEvent::Terminated { reason });
+  }
+}
+
+/// Index of a solution in a population, as stored in internal buffers of
+/// optimizers. With `u32-indices` feature enabled it's `u32`, which halves the
+/// memory of these buffers on 64-bit targets for very large populations.
+#[cfg(not(feature = "u32-indices"))]
+pub(crate) type SolutionIndex = usize;
+#[cfg(feature = "u32-indices")]
+pub(crate) type SolutionIndex = u32;
+
+/// Converts position of a solution into a [`SolutionIndex`].
+///
+/// # Panics
+///
+/// Panics if the position doesn't fit into `u32` with `u32-indices` feature
+/// enabled.
+#[inline]
+pub(crate) fn to_index(idx: usize) -> SolutionIndex {
+  #[cfg(not(feature = "u32-indices"))]
+  return idx;
+  #[cfg(feature = "u32-indices")]
+  return u32::try_from(idx).expect("population is too large for u32 indices");
+}
+
+/// Converts a [`SolutionIndex`] back into position of a solution.
+#[inline]
+pub(crate) fn from_index(idx: SolutionIndex) -> usize {
+  #[cfg(not(feature = "u32-indices"))]
+  return idx;
+  #[cfg(feature = "u32-indices")]
+  return idx as usize;
+}
+
+/// Checks that the population isn't empty.
+///
+/// # Panics
+///
+/// Panics if the population is empty, sending [`OptimizerEvent::Error`]
+/// beforehand.
+pub(crate) fn check_population<const N: usize>(
+  population_len: usize,
+  event_sender: Option<&EventSender<N>>,
+) {
+  if population_len > 0 {
+    return;
+  }
+  let message = "the population is empty";
+  if let Some(event_sender) = event_sender {
+    event_sender.send(OptimizerEvent::Error {
+      message: message.to_string(),
+    });
+  }
+  panic!("{message}");
+}
+
+/// Checks that the `operator` has evaluated `actual` scores for `expected`
+/// solutions. Otherwise, returns [`OptimizationError::ScoreCountMismatch`],
+/// sending it as [`OptimizerEvent::Error`] beforehand.
+pub(crate) fn check_scores<const N: usize>(
+  operator: OperatorKind,
+  generation: usize,
+  actual: usize,
+  expected: usize,
+  event_sender: Option<&EventSender<N>>,
+) -> Result<(), OptimizationError> {
+  if actual == expected {
+    return Ok(());
+  }
+  let error = OptimizationError::ScoreCountMismatch {
+    operator,
+    generation,
+    actual,
+    expected,
+  };
+  Err(report_error(error, event_sender))
+}
+
+/// Tests `solutions` with the `tester`, until the `deadline` if there is one.
+/// Solutions left untested by the deadline are dropped, and their number is
+/// returned along with scores of the rest.
+pub(crate) fn test_until<S, const N: usize, ExecStrat>(
+  tester: &impl TestExecutor<S, N, ExecStrat>,
+  solutions: &mut Vec<S>,
+  deadline: Option<Instant>,
+) -> (Vec<Scores<N>>, usize) {
+  let Some(deadline) = deadline else {
+    return (tester.execute_tests(solutions), 0);
+  };
+  let scores = tester.execute_tests_until(solutions, deadline);
+  if scores.len() != solutions.len() {
+    // scores can't be paired with solutions, so the mismatch is left for
+    // `check_scores` to report
+    let scores = scores.into_iter().map(|sc| sc.unwrap_or([Score::NAN; N]));
+    return (scores.collect(), 0);
+  }
+  let mut tested = scores.iter().map(Option::is_some);
+  solutions.retain(|_| tested.next() == Some(true));
+  let untested = scores.len() - solutions.len();
+  (scores.into_iter().flatten().collect(), untested)
+}
+
+/// Checks that `actual` selected solutions fulfill the selector's `contract`,
+/// if it has one. Otherwise, returns
+/// [`OptimizationError::SelectionContractViolated`], sending it as
+/// [`OptimizerEvent::Error`] beforehand.
+pub(crate) fn check_selection<const N: usize>(
+  contract: Option<SelectionContract>,
+  generation: usize,
+  actual: usize,
+  event_sender: Option<&EventSender<N>>,
+) -> Result<(), OptimizationError> {
+  match contract {
+    Some(expected) if !expected.is_fulfilled_by(actual) => {
+      let error = OptimizationError::SelectionContractViolated {
+        generation,
+        expected,
+        actual,
+      };
+      Err(report_error(error, event_sender))
+    }
+    _ => Ok(()),
+  }
+}
+
+/// Reorders `solutions` and `scores` in place, so that `i`-th element of each
+/// slice becomes the element that was at `permutation[i]` before. Applies the
+/// same swaps to both slices, keeping solutions and their scores paired.
+///
+/// `permutation` is left as the identity permutation afterwards.
+pub(crate) fn apply_permutation<A, B>(
+  permutation: &mut [SolutionIndex],
+  solutions: &mut [A],
+  scores: &mut [B],
+) {
+  debug_assert_eq!(permutation.len(), solutions.len());
+  debug_assert_eq!(permutation.len(), scores.len());
+  for start in 0..permutation.len() {
+    // follow each cycle of the permutation, swapping elements into place
+    let mut cur = start;
+    loop {
+      let next = from_index(permutation[cur]);
+      permutation[cur] = to_index(cur);
+      if next == start {
+        break;
+      }
+      solutions.swap(cur, next);
+      scores.swap(cur, next);
+      cur = next;
+    }
+  }
+}
+
+/// Lets `candidates` replace `incumbents` that dominate them, if the
+/// `acceptance` accepts them in the `generation`, until `limit` candidates
+/// are accepted. The incumbent of a candidate is the first incumbent that
+/// dominates it and isn't replaced yet. Replaced incumbents are removed, and
+/// accepted candidates are moved out of `candidates` and returned with their
+/// scores. Remaining solutions keep their order and stay paired with their
+/// scores.
+pub(crate) fn accept_dominated<S, const N: usize>(
+  acceptance: &(dyn Acceptance<N> + Send),
+  incumbents: &mut Vec<S>,
+  incumbent_scores: &mut Vec<Scores<N>>,
+  candidates: &mut Vec<S>,
+  candidate_scores: &mut Vec<Scores<N>>,
+  generation: usize,
+  limit: usize,
+) -> (Vec<S>, Vec<Scores<N>>) {
+  let mut is_replaced = vec![false; incumbents.len()];
+  let mut is_accepted = vec![false; candidates.len()];
+  let mut accepted_count = 0;
+  for (c_idx, c_sc) in candidate_scores.iter().enumerate() {
+    if accepted_count >= limit {
+      break;
+    }
+    let incumbent = (0..incumbents.len()).find(|&i_idx| {
+      !is_replaced[i_idx] && incumbent_scores[i_idx].dominance(c_sc).is_lt()
+    });
+    if let Some(i_idx) = incumbent {
+      if acceptance.accept(c_sc, &incumbent_scores[i_idx], generation) {
+        is_replaced[i_idx] = true;
+        is_accepted[c_idx] = true;
+        accepted_count += 1;
+      }
+    }
+  }
+  if accepted_count == 0 {
+    return (Vec::new(), Vec::new());
+  }
+  let mut flags = is_replaced.iter();
+  incumbents.retain(|_| !flags.next().unwrap());
+  let mut flags = is_replaced.iter();
+  incumbent_scores.retain(|_| !flags.next().unwrap());
+
+  let mut accepted = (Vec::new(), Vec::new());
+  let mut rejected = (Vec::new(), Vec::new());
+  let pairs = candidates.drain(..).zip(candidate_scores.drain(..));
+  for ((solution, scores), is_accepted) in pairs.zip(is_accepted) {
+    let (solutions, all_scores) = if is_accepted {
+      &mut accepted
+    } else {
+      &mut rejected
+    };
+    solutions.push(solution);
+    all_scores.push(scores);
+  }
+  (*candidates, *candidate_scores) = rejected;
+  accepted
+}
+
+/// Applies `select`, which reorders and truncates solutions, to `solutions`
+/// paired with whether they are offsprings, which are the solutions from
+/// `offsprings_start` on. Returns the number of surviving offsprings.
+pub(crate) fn select_tracking_offsprings<S>(
+  solutions: &mut Vec<S>,
+  offsprings_start: usize,
+  select: impl FnOnce(&mut Vec<(S, bool)>),
+) -> usize {
+  let mut tagged: Vec<_> = solutions
+    .drain(..)
+    .enumerate()
+    .map(|(i, solution)| (solution, i >= offsprings_start))
+    .collect();
+  select(&mut tagged);
+  let survived = tagged
+    .iter()
+    .filter(|(_, is_offspring)| *is_offspring)
+    .count();
+  solutions.extend(tagged.into_iter().map(|(solution, _)| solution));
+  survived
+}
+
+/// Returns the number of `survivor_scores` that are scores of offsprings, for
+/// truncations that don't tell which solutions survived. Survivors are matched
+/// with `parent_scores` first, so an offspring that equals a parent in scores
+/// survives only if both of them do. Each of `parent_scores` and
+/// `offspring_scores` is matched with at most one survivor.
+pub(crate) fn count_surviving_offsprings<const N: usize>(
+  parent_scores: &[Scores<N>],
+  offspring_scores: &[Scores<N>],
+  survivor_scores: &[Scores<N>],
+) -> usize {
+  let count = |scores: &[Scores<N>]| {
+    let mut counts = HashMap::<_, usize>::new();
+    for scores in scores {
+      *counts.entry(scores.map(f32::to_bits)).or_default() += 1;
+    }
+    counts
+  };
+  let (mut parents, mut offsprings) =
+    (count(parent_scores), count(offspring_scores));
+  survivor_scores
+    .iter()
+    .filter(|scores| {
+      let bits = scores.map(f32::to_bits);
+      let take = |counts: &mut HashMap<_, usize>| {
+        counts.get_mut(&bits).is_some_and(|count| {
+          let is_matched = *count > 0;
+          *count = count.saturating_sub(1);
+          is_matched
+        })
+      };
+      !take(&mut parents) && take(&mut offsprings)
+    })
+    .count()
+}
+
+/// Fingerprints of solutions that tell whether a solution has changed, set
+/// with `mutate_parents` setters of optimizers. Keys of solutions are hashed,
+/// so a change is missed only if hashes of the key before and after it collide.
+pub struct Fingerprint<S>(Box<dyn Fn(&S) -> u64 + Send>);
+
+impl<S> Fingerprint<S> {
+  /// Creates fingerprints of hashes of solutions' keys.
+  pub(crate) fn new<K: Hash>(key: impl Fn(&S) -> K + Send + 'static) -> Self {
+    Self(Box::new(move |solution| {
+      let mut hasher = DefaultHasher::new();
+      key(solution).hash(&mut hasher);
+      hasher.finish()
+    }))
+  }
+}
+
+impl<S> fmt::Debug for Fingerprint<S> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str("Fingerprint")
+  }
+}
+
+/// Mutates solutions of the `population` in place and tests again those
+/// whose fingerprints have changed, updating their `scores`. Returns the
+/// number of changed solutions. The population keeps its order.
+///
+/// Optimizers use it to refine survivors of a generation, so the population
+/// and its scores are left paired even if the tester fails.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn mutate_survivors<
+  S,
+  const N: usize,
+  MutExecStrat,
+  TstExecStrat,
+>(
+  mutator: &impl MutationExecutor<S, MutExecStrat>,
+  tester: &impl TestExecutor<S, N, TstExecStrat>,
+  fingerprint: &Fingerprint<S>,
+  population: &mut Vec<S>,
+  scores: &mut [Scores<N>],
+  generation: usize,
+  current: &mut CurrentOperator,
+  event_sender: Option<&EventSender<N>>,
+) -> Result<usize, OptimizationError> {
+  let before: Vec<_> = population.iter().map(&fingerprint.0).collect();
+  current.call(OperatorKind::Mutator, generation, || {
+    mutator.execute_mutations(population)
+  });
+  let is_changed: Vec<_> = population
+    .iter()
+    .zip(before)
+    .map(|(s, before)| (fingerprint.0)(s) != before)
+    .collect();
+  // changed solutions are moved out to be tested in one go
+  let mut changed = Vec::new();
+  let slots: Vec<_> = std::mem::take(population)
+    .into_iter()
+    .zip(&is_changed)
+    .map(|(s, &is_changed)| {
+      if is_changed {
+        changed.push(s);
+        None
+      } else {
+        Some(s)
+      }
+    })
+    .collect();
+  let changed_count = changed.len();
+  let new_scores = if changed.is_empty() {
+    Vec::new()
+  } else {
+    current.call(OperatorKind::Tester, generation, || {
+      tester.execute_tests(&changed)
+    })
+  };
+  let actual = new_scores.len();
+  let is_matched = actual == changed_count;
+  let mut changed = changed.into_iter().zip(
+    new_scores
+      .into_iter()
+      .map(Some)
+      .chain(std::iter::repeat(None)),
+  );
+  *population = slots
+    .into_iter()
+    .zip(scores.iter_mut())
+    .map(|(slot, sc)| {
+      slot.unwrap_or_else(|| {
+        let (s, new_sc) = changed.next().expect("a changed solution");
+        if is_matched {
+          *sc = new_sc.expect("scores of a changed solution");
+        }
+        s
+      })
+    })
+    .collect();
+  check_scores(
+    OperatorKind::Tester,
+    generation,
+    actual,
+    changed_count,
+    event_sender,
+  )?;
+  Ok(changed_count)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_catch_operator_panics() {
+    let result = catch_operator_panics::<_, 1>(true, None, |current| {
+      current.call(OperatorKind::Selector, 3, || panic::panic_any(7));
+      Ok(())
+    });
+    assert_eq!(
+      result,
+      Err(OptimizationError::OperatorPanicked {
+        operator: OperatorKind::Selector,
+        generation: 3,
+        payload_message: "non-string panic payload".to_string(),
+      })
+    );
+    // panics outside of operators aren't caught
+    let result = panic::catch_unwind(|| {
+      catch_operator_panics::<(), 1>(true, None, |current| {
+        current.call(OperatorKind::Tester, 0, || {});
+        panic!("the population is empty")
+      })
+    });
+    assert!(result.is_err());
+  }
+
+  #[test]
+  fn test_accept_dominated() {
+    let mut incumbents = vec!['a', 'b', 'c'];
+    let mut incumbent_scores = vec![[0.0, 2.0], [1.0, 1.0], [2.0, 0.0]];
+    let mut candidates = vec!['d', 'e', 'f', 'g'];
+    let mut candidate_scores =
+      vec![[3.0, 3.0], [0.5, 0.5], [2.0, 2.0], [3.0, 3.0]];
+    let accept_all = |_: &Scores<2>, _: &Scores<2>, _| true;
+    let accepted = accept_dominated(
+      &accept_all,
+      &mut incumbents,
+      &mut incumbent_scores,
+      &mut candidates,
+      &mut candidate_scores,
+      0,
+      2,
+    );
+    // `e` isn't dominated, and `g` exceeds the limit
+    assert_eq!(accepted, (vec!['d', 'f'], vec![[3.0, 3.0], [2.0, 2.0]]));
+    assert_eq!(incumbents, ['c']);
+    assert_eq!(incumbent_scores, [[2.0, 0.0]]);
+    assert_eq!(candidates, ['e', 'g']);
+    assert_eq!(candidate_scores, [[0.5, 0.5], [3.0, 3.0]]);
+
+    let accepted = accept_dominated(
+      &crate::truncation::ParetoAcceptance,
+      &mut incumbents,
+      &mut incumbent_scores,
+      &mut candidates,
+      &mut candidate_scores,
+      0,
+      usize::MAX,
+    );
+    assert!(accepted.0.is_empty());
+    assert_eq!(candidates, ['e', 'g']);
+  }
+
+  #[test]
+  fn test_select_tracking_offsprings() {
+    let mut solutions = vec!['a', 'b', 'c', 'd', 'e'];
+    let survived = select_tracking_offsprings(&mut solutions, 3, |tagged| {
+      tagged.retain(|&(solution, _)| solution != 'd');
+      tagged.reverse();
+    });
+    assert_eq!(survived, 1);
+    assert_eq!(solutions, ['e', 'c', 'b', 'a']);
+  }
+
+  #[test]
+  fn test_count_surviving_offsprings() {
+    let parent_scores = [[1.0, 2.0], [4.0, 4.0]];
+    let offspring_scores = [[1.0, 2.0], [1.0, 2.0], [3.0, 0.0]];
+    let survivor_scores = [[0.0, 5.0], [1.0, 2.0], [3.0, 0.0], [3.0, 0.0]];
+    assert_eq!(
+      count_surviving_offsprings(&[], &offspring_scores, &survivor_scores),
+      2
+    );
+    // the surviving copy is the parent
+    assert_eq!(
+      count_surviving_offsprings(
+        &parent_scores,
+        &offspring_scores,
+        &survivor_scores
+      ),
+      1
+    );
+    assert_eq!(count_surviving_offsprings(&[], &[], &survivor_scores), 0);
+  }
+
+  #[test]
+  fn test_apply_permutation() {
+    let mut permutation = [2, 0, 4, 3, 1];
+    let mut solutions = ['a', 'b', 'c', 'd', 'e'];
+    let mut scores = [0, 1, 2, 3, 4];
+    apply_permutation(&mut permutation, &mut solutions, &mut scores);
+    assert_eq!(solutions, ['c', 'a', 'e', 'd', 'b']);
+    assert_eq!(scores, [2, 0, 4, 3, 1]);
+    assert_eq!(permutation, [0, 1, 2, 3, 4]);
+  }
+
+  #[test]
+  fn test_apply_permutation_empty() {
+    apply_permutation::<i32, i32>(&mut [], &mut [], &mut []);
+  }
+
+  #[test]
+  fn test_infallible_optimizer() {
+    // optimizers written before `try_optimize` implement only `optimize`
+    struct Constant;
+    impl Optimizer<i32, 1> for Constant {
+      fn optimize(self) -> Vec<i32> {
+        vec![7]
+      }
+    }
+    assert_eq!(Constant.try_optimize(), Ok(vec![7]));
+    assert_eq!(Constant.try_optimize_best_effort(), Ok(vec![7]));
+
+    // fallible optimizers implement both, like built-in ones do
+    struct Failing;
+    impl Optimizer<i32, 1> for Failing {
+      fn optimize(self) -> Vec<i32> {
+        self.try_optimize().unwrap_or_else(|e| panic!("{e}"))
+      }
+
+      fn try_optimize(self) -> Result<Vec<i32>, OptimizationError> {
+        Err(OptimizationError::SelectionEmpty { generation: 3 })
+      }
+    }
+    assert_eq!(
+      Failing.try_optimize(),
+      Err(OptimizationError::SelectionEmpty { generation: 3 })
+    );
+    assert!(panic::catch_unwind(|| Failing.optimize()).is_err());
+  }
 }
@@ -1,17 +1,104 @@
 //! Implementations of genetic algorithms of NSGA family.
 
-use std::{cmp::Ordering, collections::HashSet, marker::PhantomData, ops::Not};
+use std::{
+  cmp::Ordering,
+  collections::HashSet,
+  hash::Hash,
+  marker::PhantomData,
+  sync::Mutex,
+  time::{Duration, Instant},
+};
 
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use typed_builder::TypedBuilder;
 
-use super::Optimizer;
+use super::{
+  accept_dominated,
+  apply_permutation,
+  catch_operator_panics,
+  check_population,
+  check_scores,
+  check_selection,
+  count_nan_scores,
+  count_surviving_offsprings,
+  from_index,
+  mutate_survivors,
+  recombine_within_limit,
+  report_error,
+  report_termination,
+  select_tracking_offsprings,
+  test_until,
+  to_index,
+  CurrentOperator,
+  DryRunAnomaly,
+  DryRunReport,
+  EmptySelection,
+  EqualTo,
+  Fingerprint,
+  Objectives,
+  OffspringBudget,
+  OperatorKind,
+  OperatorSet,
+  OptimizationError,
+  OptimizationFailure,
+  OptimizationOutcome,
+  Optimizer,
+  Replacement,
+  SizeSchedule,
+  Terminated,
+};
+#[cfg(feature = "parallel")]
+use crate::execution::PARALLEL_THRESHOLD;
+#[cfg(feature = "score-matrix")]
+use crate::score::matrix::ScoreMatrix;
 use crate::{
-  mutation::executor::MutationExecutor,
-  recombination::executor::RecombinationExecutor,
-  score::{ParetoDominance, Scores},
-  selection::executor::SelectionExecutor,
-  termination::executor::TerminationExecutor,
-  testing::executor::TestExecutor,
+  constraining::{
+    executor::{ConstraintAdapter, ConstraintExecutor, RepairExecutor},
+    ErasedConstraint,
+    Repairer,
+  },
+  dedup::Deduplicate,
+  execution::strategy::{CustomExecutionStrategy, SequentialExecutionStrategy},
+  mutation::{executor::MutationExecutor, BoxedMutator},
+  observer::{
+    EventSender,
+    Observer,
+    OperatorTimings,
+    OptimizerEvent,
+    TerminationReason,
+  },
+  operator::OperatorFeedback,
+  optimizer::phased::Seedable,
+  pause::{retain_front, Directive, PauseHandler, PausePoints},
+  recombination::{executor::RecombinationExecutor, BoxedRecombinator},
+  runner::Spawnable,
+  score::{
+    select_by,
+    shadow_view,
+    sorting::{
+      nondominated_sort_with,
+      Front,
+      IncrementalSorting,
+      SolutionIndex,
+      SortingBuffers,
+    },
+    ObjectiveLabels,
+    ScoreQuantizer,
+    ScoreTransform,
+    Scores,
+  },
+  selection::{executor::SelectionExecutor, BoxedSelector},
+  speciation::{quota_budget, speciate, SpeciesAssigner},
+  termination::{
+    executor::TerminationExecutor,
+    BoxedTerminator,
+    FlagTerminator,
+    TerminationDecision,
+  },
+  testing::{executor::TestExecutor, BoxedTester},
+  trace::{phase, Timer},
+  truncation::{Acceptance, Truncation},
 };
 
 /// An implementation of a fast and elitist multiobjective genetic algorithm -
@@ -24,6 +111,7 @@ use crate::{
 /// *Schaffer's Problem No.1* solution.
 /// ```no_run
 /// # fn main() {
+/// # #[cfg(feature = "parallel")] {
 /// use rand::Rng;
 /// use moga::{
 ///   operator::ParBatch,
@@ -58,6 +146,7 @@ use crate::{
 /// // upon termination the optimizer returns the best solutions it has found
 /// let solutions = optimizer.optimize();
 /// # }
+/// # }
 /// ```
 #[derive(TypedBuilder, Debug)]
 pub struct Nsga2<
@@ -76,29 +165,429 @@ pub struct Nsga2<
   const PARENT_NUM: usize,
   const OFFSPRING_NUM: usize,
 > {
-  #[builder(setter(
-    transform = |v: Vec<Solution>| {
-      v.is_empty()
-        .not()
-        .then_some(v)
-        .unwrap_or_else(|| panic!("initial population is empty"))
-    },
-    doc = "
-      The initial population setter.
-
-      # Panics
-
-      Panics if population is empty.",    
-  ))]
+  #[builder(
+    via_mutators(init = Vec::new()),
+    mutators(
+      /// The initial population setter. Either this or
+      /// [`population_with_scores`](Self::population_with_scores) must be
+      /// called, or both.
+      ///
+      /// # Panics
+      ///
+      /// Panics if population is empty.
+      pub fn population(&mut self, population: Vec<Solution>) {
+        if population.is_empty() {
+          panic!("initial population is empty")
+        }
+        self.population = population;
+      }
+    )
+  )]
   population: Vec<Solution>,
   tester: Tst,
   selector: Sel,
   recombinator: Rec,
   mutator: Mut,
   terminator: Ter,
-  #[builder(setter(skip), default = population.len())]
+  #[builder(
+    via_mutators(init = None),
+    mutators(
+      /// Sets an [`Observer`] that is notified after each generation.
+      pub fn observer(
+        &mut self,
+        observer: impl Observer<Solution, OBJECTIVE_NUM> + Send + 'static
+      ) {
+        self.observer = Some(Box::new(observer));
+      }
+    )
+  )]
+  observer: Option<Box<dyn Observer<Solution, OBJECTIVE_NUM> + Send>>,
+  #[builder(
+    via_mutators(init = None),
+    mutators(
+      /// Sets a repair operator that is applied to offsprings after mutation
+      /// and before testing.
+      pub fn repairer<RepExecStrat: 'static>(
+        &mut self,
+        repairer: impl RepairExecutor<Solution, RepExecStrat> + Send + 'static
+      ) where Solution: 'static {
+        self.repairer = Some(Box::new(move |solutions: &mut [Solution]| {
+          repairer.execute_repairs(solutions)
+        }));
+      }
+    )
+  )]
+  repairer: Option<Box<dyn Repairer<Solution> + Send>>,
+  #[builder(
+    via_mutators(init = None),
+    mutators(
+      /// Sets a [`Deduplicate`] operator, like [`Dedup`], that removes
+      /// duplicate offsprings after repair and before testing. Offsprings that
+      /// duplicate members of the population are removed too, so they are
+      /// neither tested nor cloned into the population again. Numbers of
+      /// removed offsprings are reported to the observer with
+      /// [`Observer::duplicates_removed`].
+      ///
+      /// [`Dedup`]: crate::dedup::Dedup
+      pub fn dedup(
+        &mut self,
+        dedup: impl Deduplicate<Solution> + Send + 'static
+      ) {
+        self.dedup = Some(Box::new(dedup));
+      }
+    )
+  )]
+  dedup: Option<Box<dyn Deduplicate<Solution> + Send>>,
+  #[builder(
+    via_mutators(init = None),
+    mutators(
+      /// Mutates the population with the optimizer's mutator after each
+      /// truncation, too, so that survivors themselves are refined instead of
+      /// only their offsprings (Lamarckian learning). Survivors that the
+      /// mutation has changed are tested again.
+      ///
+      /// A survivor has changed if its `key` has changed. Keys are hashed,
+      /// so they should be cheap to compute, e.g. bits of a number, but not
+      /// necessarily cheap to compare.
+      pub fn mutate_parents<K: Hash>(
+        &mut self,
+        key: impl Fn(&Solution) -> K + Send + 'static
+      ) {
+        self.parent_fingerprint = Some(Fingerprint::new(key));
+      }
+    )
+  )]
+  parent_fingerprint: Option<Fingerprint<Solution>>,
+  #[builder(
+    via_mutators(init = None),
+    mutators(
+      /// Sets a [`Constraint`] that is evaluated on the population at the end
+      /// of each generation to let it adapt to violations, like
+      /// [`AdaptivePenalty`] does.
+      ///
+      /// [`Constraint`]: crate::constraining::Constraint
+      /// [`AdaptivePenalty`]: crate::constraining::AdaptivePenalty
+      pub fn constraint<ConExecStrat: 'static, const CONSTRAINT_NUM: usize>(
+        &mut self,
+        constraint: impl ConstraintExecutor<
+          Solution,
+          CONSTRAINT_NUM,
+          ConExecStrat
+        > + Send + 'static
+      ) where Solution: 'static {
+        self.constraint = Some(Box::new(ErasedConstraint::new(constraint)));
+      }
+    )
+  )]
+  constraint: Option<Box<dyn ConstraintAdapter<Solution> + Send>>,
+  #[builder(
+    via_mutators(init = None),
+    mutators(
+      /// Sets a [`Truncation`] that replaces [`CrowdingTruncation`] of the
+      /// population after each generation.
+      pub fn truncation(
+        &mut self,
+        truncation: impl Truncation<Solution, OBJECTIVE_NUM> + Send + 'static
+      ) {
+        self.truncation = Some(Box::new(truncation));
+      }
+    )
+  )]
+  truncation: Option<Box<dyn Truncation<Solution, OBJECTIVE_NUM> + Send>>,
+  #[builder(
+    via_mutators(init = None),
+    mutators(
+      /// Sets an [`Acceptance`] rule that lets dominated offsprings replace
+      /// solutions of the population that dominate them and survive the
+      /// truncation, such as [`AnnealedAcceptance`]. It's consulted only with
+      /// [`Replacement::MuPlusLambda`].
+      ///
+      /// [`AnnealedAcceptance`]: crate::truncation::AnnealedAcceptance
+      pub fn acceptance(
+        &mut self,
+        acceptance: impl Acceptance<OBJECTIVE_NUM> + Send + 'static
+      ) {
+        self.acceptance = Some(Box::new(acceptance));
+      }
+    )
+  )]
+  acceptance: Option<Box<dyn Acceptance<OBJECTIVE_NUM> + Send>>,
+  #[builder(
+    via_mutators(init = None),
+    mutators(
+      /// Sets a [`SpeciesAssigner`], like [`ThresholdSpeciation`], that
+      /// partitions the population into species each generation, see
+      /// [`speciation`](crate::speciation). Parents are selected and recombined
+      /// within each species, and each species creates at most its quota of
+      /// `max_offspring_per_generation` offsprings, or of the population size
+      /// if it isn't set. Quotas are met by sampling combinations of parents,
+      /// as with [`OffspringBudget::Subsample`].
+      ///
+      /// [`ThresholdSpeciation`]: crate::speciation::ThresholdSpeciation
+      pub fn speciation(
+        &mut self,
+        assigner: impl SpeciesAssigner<Solution> + Send + 'static
+      ) {
+        self.speciation = Some(Box::new(assigner));
+      }
+    )
+  )]
+  speciation: Option<Box<dyn SpeciesAssigner<Solution> + Send>>,
+  #[builder(
+    via_mutators(init = None),
+    mutators(
+      /// Sorts the population into fronts incrementally: dominance relations
+      /// of survivors are kept between generations, and only relations of
+      /// offsprings and of survivors whose scores have changed are computed,
+      /// which saves comparisons in late generations, when most survivors
+      /// survive again. All relations are computed anew every
+      /// `full_sort_interval` generations.
+      ///
+      /// The fronts are the same as those of a full sort, but unlike the full
+      /// sort of large populations, the incremental sort keeps relations of
+      /// all pairs of solutions in memory. It's used only by the default
+      /// crowding truncation.
+      ///
+      /// # Panics
+      ///
+      /// Panics if `full_sort_interval` is `0`.
+      pub fn incremental_sorting(&mut self, full_sort_interval: usize) {
+        assert!(full_sort_interval > 0, "full sort interval must not be 0");
+        self.full_sort_interval = Some(full_sort_interval);
+      }
+    )
+  )]
+  full_sort_interval: Option<usize>,
+  #[builder(
+    via_mutators(init = None),
+    mutators(
+      /// Multiplies each objective's contribution to crowding distances of
+      /// the default crowding truncation by its weight from `weights`, so
+      /// that survivors of the last front that fits are spread mostly along
+      /// objectives of greater weights. An objective of zero weight is
+      /// ignored for diversity, but still counts for dominance. Crowding
+      /// distances of [`Nsga2::optimize_detailed`] are weighted as well.
+      ///
+      /// # Panics
+      ///
+      /// Panics if any of the `weights` is negative, infinite or NaN.
+      pub fn crowding_weights(&mut self, weights: [f64; OBJECTIVE_NUM]) {
+        assert_crowding_weights(&weights);
+        self.crowding_weights = Some(weights);
+      }
+    )
+  )]
+  crowding_weights: Option<[f64; OBJECTIVE_NUM]>,
+  #[builder(
+    via_mutators(init = None),
+    mutators(
+      /// Sets an [`OperatorFeedback`] that the optimizer tells after each
+      /// generation how many of the generation's offsprings survived its
+      /// truncation, e.g. an
+      /// [`AdaptiveRecombinator`](crate::recombination::AdaptiveRecombinator)
+      /// that is the optimizer's recombinator as well.
+      pub fn feedback(
+        &mut self,
+        feedback: impl OperatorFeedback + Send + 'static
+      ) {
+        self.feedback = Some(Box::new(feedback));
+      }
+    )
+  )]
+  feedback: Option<Box<dyn OperatorFeedback + Send>>,
+  #[builder(
+    default,
+    setter(
+      strip_option,
+      into,
+      doc = "
+      Sets an [`EventSender`] that the optimizer sends [`OptimizerEvent`]s
+      through.",
+    )
+  )]
+  event_sender: Option<EventSender<OBJECTIVE_NUM>>,
+  #[builder(
+    default,
+    setter(
+      strip_option,
+      doc = "
+      Sets labels of objectives. Labels are metadata only: the optimizer
+      gives them to its observer with [`Observer::label_objectives`] and
+      sends them with [`OptimizerEvent::GenerationCompleted`] events.",
+    )
+  )]
+  objective_labels: Option<ObjectiveLabels<OBJECTIVE_NUM>>,
+  #[builder(
+    default,
+    setter(
+      strip_option,
+      doc = "
+      Snaps scores to a grid before the default crowding truncation sorts the
+      population into fronts, so that scores differing only by noise don't
+      inflate the nondominated front. The population keeps its original
+      scores. A custom [`Truncation`] gets the original scores as well.",
+    )
+  )]
+  quantizer: Option<ScoreQuantizer<OBJECTIVE_NUM>>,
+  #[builder(
+    default,
+    setter(
+      strip_option,
+      doc = "
+      Transforms scores of each objective before the default crowding
+      truncation sorts the population into fronts and computes crowding
+      distances, after they are snapped to the grid of the quantizer, if
+      any. The population keeps its original scores, as do selectors,
+      terminators, observers and a custom [`Truncation`]. A terminator that
+      should see transformed scores can apply [`ScoreTransform::apply_each`]
+      itself.",
+    )
+  )]
+  score_transform: Option<[ScoreTransform; OBJECTIVE_NUM]>,
+  #[builder(
+    default,
+    setter(doc = "
+      Sets what the optimizer does if its selector selects no solutions.
+      Defaults to [`EmptySelection::Fail`].")
+  )]
+  empty_selection: EmptySelection,
+  #[builder(
+    default,
+    setter(
+      strip_option,
+      into,
+      doc = "
+      Sets a [`SizeSchedule`] of population sizes over generations. At each
+      generation, parents and offsprings are truncated down to the size of
+      the schedule instead of the size of the initial population.",
+    )
+  )]
+  population_schedule: Option<SizeSchedule>,
+  #[builder(
+    default,
+    setter(doc = "
+      Sets how offsprings replace the population. Defaults to
+      [`Replacement::MuPlusLambda`].")
+  )]
+  replacement: Replacement,
+  #[builder(
+    default,
+    setter(
+      strip_option,
+      doc = "
+      Sets the maximum number of offsprings recombination may create in a
+      generation. If recombination of the selected parents would create more
+      of them, the optimizer does what its [`OffspringBudget`] tells.",
+    )
+  )]
+  max_offspring_per_generation: Option<usize>,
+  #[builder(
+    default,
+    setter(doc = "
+      Sets what the optimizer does if recombination would exceed
+      `max_offspring_per_generation`. Defaults to [`OffspringBudget::Fail`].")
+  )]
+  offspring_budget: OffspringBudget,
+  #[builder(
+    default,
+    setter(
+      strip_option,
+      doc = "
+      Sets the time budget of each generation, counted from its start.
+      Offsprings whose tests haven't started by the time it runs out are
+      dropped untested instead of waited for, so that a few pathological
+      solutions can't stall a generation. Numbers of dropped offsprings are
+      reported to the observer with [`Observer::tests_timed_out`]. Only
+      testers parallelized with `par_batch()` check the budget, others test
+      all offsprings regardless of it.",
+    )
+  )]
+  generation_time_budget: Option<Duration>,
+  #[builder(
+    default,
+    setter(doc = "
+      Sets whether the optimizer measures time spent in each phase of
+      optimization and sends it as [`OptimizerEvent::Profiled`] before
+      termination. See [`OperatorTimings`].")
+  )]
+  profile: bool,
+  #[builder(
+    default,
+    setter(doc = "
+      Sets whether the optimizer catches panics of its operators, such as a
+      test that panics on a pathological solution. A caught panic stops
+      optimization with [`OptimizationError::OperatorPanicked`], and
+      [`Optimizer::try_optimize_best_effort`] returns the population
+      along with it. Operators don't have to be `UnwindSafe`, since they
+      aren't called after a panic. The panic is still reported by the panic
+      hook, which prints it to stderr by default.")
+  )]
+  catch_operator_panics: bool,
+  #[builder(
+    default,
+    setter(
+      strip_option,
+      doc = "
+      Sets a [`FlagTerminator`] that cancels optimization once its flag is
+      raised, e.g. by another thread. The flag is checked at the start of
+      each generation, along with the terminator, and the optimizer reports
+      [`TerminationReason::Cancelled`].",
+    )
+  )]
+  cancel_flag: Option<FlagTerminator>,
+  #[builder(
+    via_mutators(init = None),
+    mutators(
+      /// Sets a [`PauseHandler`] that the optimizer pauses for after every
+      /// `generations` generations, see [`pause`](crate::pause).
+      ///
+      /// # Panics
+      ///
+      /// Panics if `generations` is `0`.
+      pub fn pause_every(
+        &mut self,
+        generations: usize,
+        handler: impl PauseHandler<Solution, OBJECTIVE_NUM> + Send + 'static
+      ) {
+        self.pause = Some(PausePoints::new(generations, handler));
+      }
+    )
+  )]
+  pause: Option<PausePoints<Solution, OBJECTIVE_NUM>>,
+  #[builder(setter(skip), default)]
+  generation_limit: Option<usize>,
+  #[builder(
+    via_mutators(init = Vec::new()),
+    mutators(
+      /// Adds solutions along with their scores to the initial population,
+      /// such as solutions of a previous run returned by
+      /// [`Nsga2::optimize_with_scores`]. They aren't
+      /// tested again, only the solutions set with
+      /// [`population`](Self::population) are.
+      ///
+      /// Scores must be the ones the tester would evaluate, otherwise the
+      /// solutions compete unfairly.
+      pub fn population_with_scores(
+        &mut self,
+        population: Vec<(Solution, Scores<OBJECTIVE_NUM>)>
+      ) {
+        for (solution, scores) in population {
+          self.injected.push(solution);
+          self.injected_scores.push(scores);
+        }
+      }
+    )
+  )]
+  injected: Vec<Solution>,
+  #[builder(via_mutators(init = Vec::new()))]
+  injected_scores: Vec<Scores<OBJECTIVE_NUM>>,
+  #[builder(setter(skip), default = population.len() + injected.len())]
   initial_population_size: usize,
   #[builder(setter(skip), default)]
+  scratch: ScratchBuffers,
+  #[builder(setter(skip), default)]
+  incremental_sorting: Option<IncrementalSorting<OBJECTIVE_NUM>>,
+  #[builder(setter(skip), default)]
   _solution: PhantomData<Solution>,
   #[builder(setter(skip), default)]
   _eva_es: PhantomData<TstExecStrat>,
@@ -112,19 +601,6 @@ pub struct Nsga2<
   _rec_es: PhantomData<RecExecStrat>,
 }
 
-/// Index of solution in `solutions` vector.
-type SolutionIndex = usize;
-/// Number of solution's dominators.
-type DominanceCounter = u32;
-/// Crowding distance of a solution.
-type CrowdingDistance = f64;
-/// Front number. the lower - the better.
-type FrontNumber = u32;
-/// Dominated by each solution solutions' indices.
-type DominanceList = Vec<SolutionIndex>;
-/// Indices of solutions of a front.
-type Front = Vec<SolutionIndex>;
-
 impl<
     Solution,
     Tst: TestExecutor<Solution, OBJECTIVE_NUM, TstExecStrat>,
@@ -140,8 +616,9 @@ impl<
     const OBJECTIVE_NUM: usize,
     const PARENT_NUM: usize,
     const OFFSPRING_NUM: usize,
+    Fields,
   >
-  Nsga2<
+  Nsga2Builder<
     Solution,
     Tst,
     Sel,
@@ -156,167 +633,593 @@ impl<
     OBJECTIVE_NUM,
     PARENT_NUM,
     OFFSPRING_NUM,
+    Fields,
   >
 {
-  fn crowding_distance_selection(
-    &self,
-    solutions: Vec<Solution>,
-    scores: Vec<Scores<OBJECTIVE_NUM>>,
-  ) -> (Vec<Solution>, Vec<Scores<OBJECTIVE_NUM>>) {
-    let mut dominance_lists: Vec<DominanceList> =
-      vec![Vec::new(); solutions.len()];
-    let mut dominance_counters: Vec<DominanceCounter> =
-      vec![0; solutions.len()];
-    let mut first_front: Front = Vec::new();
-
-    // fill dominance sets and counters
-    for p_idx in 0..solutions.len() {
-      // for each unique pair of solutions `p`...
-      let (p_sc, rest_scs) =
-        scores[p_idx..].split_first().expect("no scores remain");
-      // and `q`...
-      for (i, q_sc) in rest_scs.iter().enumerate() {
-        let q_idx = p_idx + i + 1;
-        match p_sc.dominance(q_sc) {
-          // if solution `p` dominates solution `q`...
-          Ordering::Less => {
-            // put solution `q` into list of solutions dominated by `p`
-            dominance_lists[p_idx].push(q_idx);
-            // and increment counter of solutions dominating `q`
-            dominance_counters[q_idx] += 1;
-          }
-          // if solution `q` dominates solution `p`...
-          Ordering::Greater => {
-            // put solution `p` into list of solutions dominated by `q`
-            dominance_lists[q_idx].push(p_idx);
-            // and increment counter of solutions dominating `p`
-            dominance_counters[p_idx] += 1;
-          }
-          Ordering::Equal => {}
-        }
-      }
-      // if solution `p` isn't dominated by any other solution...
-      if dominance_counters[p_idx] == 0 {
-        first_front.push(p_idx); // put its index into the first front
-      }
+  /// Pins the number of objectives of the optimizer to `N`. Otherwise, it's
+  /// inferred from all operators at once, and if one of them disagrees, the
+  /// compiler can't tell which one and reports the mismatch at `build()`.
+  /// Once pinned, the operator that disagrees fails to be set instead.
+  ///
+  /// # Examples
+  /// ```
+  /// use moga::{
+  ///   optimizer::nsga::Nsga2,
+  ///   selection::AllSelector,
+  ///   termination::GenerationTerminator,
+  /// };
+  ///
+  /// let optimizer = Nsga2::builder()
+  ///   .objectives::<2>()
+  ///   .population(vec![1.0, 2.0])
+  ///   .tester(|x: &f32| [*x, 1.0 / x])
+  ///   .selector(AllSelector())
+  ///   .recombinator(|x: &f32| *x)
+  ///   .mutator(|x: &mut f32| *x *= 2.0)
+  ///   .terminator(GenerationTerminator(10))
+  ///   .build();
+  /// ```
+  ///
+  /// With 3 objectives pinned, the tester of 2 objectives is the culprit:
+  /// ```compile_fail,E0271
+  /// # use moga::{
+  /// #   optimizer::nsga::Nsga2,
+  /// #   selection::AllSelector,
+  /// #   termination::GenerationTerminator,
+  /// # };
+  /// let optimizer = Nsga2::builder()
+  ///   .objectives::<3>()
+  ///   .population(vec![1.0, 2.0])
+  ///   .tester(|x: &f32| [*x, 1.0 / x])
+  /// #   .selector(AllSelector())
+  /// #   .recombinator(|x: &f32| *x)
+  /// #   .mutator(|x: &mut f32| *x *= 2.0)
+  /// #   .terminator(GenerationTerminator(10))
+  /// #   .build();
+  /// ```
+  pub fn objectives<const N: usize>(self) -> Self
+  where
+    Objectives<OBJECTIVE_NUM>: EqualTo<N>,
+  {
+    self
+  }
+}
+
+/// [`Nsga2`] with execution strategies of operators that aren't parallelized:
+/// a [`Test`], a [`Recombination`] and a [`Mutation`] applied to each
+/// solution, and a [`Selector`] and a [`Terminator`] applied to all solutions,
+/// like the built-in ones. Parents and offsprings numbers default to those of
+/// `Fn(&S, &S) -> S` recombination.
+///
+/// Naming the type of an optimizer fixes the execution strategies, so the
+/// compiler doesn't have to infer them and reports a mismatched operator in
+/// terms of its trait.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   optimizer::nsga::Nsga2For,
+///   prelude::*,
+///   selection::RandomSelector,
+///   termination::GenerationTerminator,
+/// };
+///
+/// let optimizer: Nsga2For<f32, 2, _, _, _, _, _> = Nsga2::builder()
+///   .population(vec![1.0, 2.0, 3.0])
+///   .tester(|x: &f32| [*x, x - 2.0])
+///   .selector(RandomSelector(2))
+///   .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+///   .mutator(|_: &mut f32| {})
+///   .terminator(GenerationTerminator(5))
+///   .build();
+/// ```
+///
+/// [`Test`]: crate::testing::Test
+/// [`Recombination`]: crate::recombination::Recombination
+/// [`Mutation`]: crate::mutation::Mutation
+/// [`Selector`]: crate::selection::Selector
+/// [`Terminator`]: crate::termination::Terminator
+pub type Nsga2For<
+  S,
+  const N: usize,
+  Tst,
+  Sel,
+  Rec,
+  Mut,
+  Ter,
+  const P: usize = 2,
+  const O: usize = 1,
+> = Nsga2<
+  S,
+  Tst,
+  Sel,
+  Rec,
+  Mut,
+  Ter,
+  SequentialExecutionStrategy,
+  CustomExecutionStrategy,
+  CustomExecutionStrategy,
+  SequentialExecutionStrategy,
+  SequentialExecutionStrategy,
+  N,
+  P,
+  O,
+>;
+
+/// [`Nsga2`] with boxed operators, which is a nameable type of an optimizer
+/// with operators chosen at runtime.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   mutation::BoxedMutator,
+///   optimizer::{nsga::BoxedNsga2, Optimizer},
+///   prelude::*,
+///   recombination::BoxedRecombinator,
+///   selection::{BoxedSelector, RandomSelector},
+///   termination::{BoxedTerminator, GenerationTerminator},
+///   testing::BoxedTester,
+/// };
+///
+/// struct Experiment {
+///   optimizer: BoxedNsga2<f32, 2>,
+/// }
+///
+/// let experiment = Experiment {
+///   optimizer: Nsga2::builder()
+///     .population(vec![1.0, 2.0, 3.0])
+///     .tester(BoxedTester::new(|x: &f32| [*x, x - 2.0]))
+///     .selector(BoxedSelector::new(RandomSelector(2)))
+///     .recombinator(BoxedRecombinator::new(|x: &f32, y: &f32| (x + y) / 2.0))
+///     .mutator(BoxedMutator::new(|_: &mut f32| {}))
+///     .terminator(BoxedTerminator::new(GenerationTerminator(5)))
+///     .build(),
+/// };
+/// let solutions = experiment.optimizer.optimize();
+/// ```
+pub type BoxedNsga2<S, const N: usize> = Nsga2<
+  S,
+  BoxedTester<S, N>,
+  BoxedSelector<S, N>,
+  BoxedRecombinator<S>,
+  BoxedMutator<S>,
+  BoxedTerminator<S, N>,
+  CustomExecutionStrategy,
+  CustomExecutionStrategy,
+  CustomExecutionStrategy,
+  CustomExecutionStrategy,
+  CustomExecutionStrategy,
+  N,
+  { usize::MAX },
+  { usize::MAX },
+>;
+
+/// Crowding distance of a solution.
+type CrowdingDistance = f64;
+/// Front number. the lower - the better.
+type FrontNumber = u32;
+
+/// Front number and crowding distance of a solution returned by
+/// [`Nsga2::optimize_detailed`], computed over the last found population.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct SolutionMeta {
+  /// Number of the front the solution belongs to, starting from `0` for
+  /// nondominated solutions.
+  pub front: u32,
+  /// Crowding distance of the solution within its front. Boundary solutions
+  /// of each objective get distance of `f64::MAX`.
+  pub crowding_distance: f64,
+}
+
+/// Sorts solutions into fronts by their `scores` and returns the front number
+/// and crowding distance of each solution, weighted by `weights`, if any.
+fn solution_metas<const N: usize>(
+  scores: &[Scores<N>],
+  weights: Option<&[f64; N]>,
+) -> Vec<SolutionMeta> {
+  let mut sorting = SortingBuffers::default();
+  nondominated_sort_with(scores, &mut sorting);
+  let mut metas = vec![
+    SolutionMeta {
+      front: 0,
+      crowding_distance: 0.0,
+    };
+    scores.len()
+  ];
+  let mut front = Front::new();
+  let mut cds = Vec::new();
+  for (front_idx, sorted_front) in sorting.fronts().enumerate() {
+    front.clear();
+    front.extend_from_slice(sorted_front);
+    crowding_distances(&mut front, scores, weights, &mut cds);
+    for &idx in front.iter() {
+      metas[from_index(idx)] = SolutionMeta {
+        front: front_idx as FrontNumber,
+        crowding_distance: cds[from_index(idx)],
+      };
     }
+  }
+  metas
+}
+
+/// Buffers that `Nsga2` reuses between generations to avoid allocations.
+#[derive(Clone, Debug, Default)]
+struct ScratchBuffers {
+  sorting: SortingBuffers,
+  front_numbers: Vec<FrontNumber>,
+  crowding_distances: Vec<CrowdingDistance>,
+  last_front: Front,
+  permutation: Vec<SolutionIndex>,
+  is_survivor: Vec<bool>,
+}
+
+/// The default [`Truncation`] of `Nsga2`. Keeps the solutions of the best
+/// fronts and the least crowded solutions of the last front that fits.
+///
+/// Reuses its buffers between truncations, so its `clone` doesn't clone them.
+#[derive(Debug, Default)]
+pub struct CrowdingTruncation {
+  scratch: Mutex<ScratchBuffers>,
+}
+
+impl Clone for CrowdingTruncation {
+  fn clone(&self) -> Self {
+    Self::default()
+  }
+}
 
-    debug_assert!(
-      !first_front.is_empty(),
-      "first front must have at least 1 solution"
+impl<S, const N: usize> Truncation<S, N> for CrowdingTruncation {
+  fn truncate(
+    &self,
+    mut solutions: Vec<S>,
+    mut scores: Vec<Scores<N>>,
+    target: usize,
+  ) -> (Vec<S>, Vec<Scores<N>>) {
+    let mut scratch = self.scratch.lock().unwrap();
+    crowding_distance_selection(
+      &mut solutions,
+      &mut scores,
+      target,
+      &mut scratch,
+      None,
+      None,
     );
+    (solutions, scores)
+  }
+}
 
-    let mut front_numbers: Vec<FrontNumber> =
-      vec![FrontNumber::MAX; solutions.len()];
-    let mut last_front = first_front;
-    let mut new_solutions_indices: Vec<SolutionIndex> = Vec::new();
-    let mut front_idx = 0;
-    // until we select enough solutions...
-    while new_solutions_indices.len() + last_front.len()
-      < self.initial_population_size
-    {
-      let mut next_front = Vec::new();
-      // for each solution `p` in last front...
-      for p_idx in last_front.iter() {
-        // for each solution `q` dominated by `p`...
-        for q_idx in dominance_lists[*p_idx].iter_mut() {
-          // decrement counter of solutions dominating `q`
-          dominance_counters[*q_idx] -= 1;
-          // if no more solutions dominate `q`...
-          if dominance_counters[*q_idx] == 0 {
-            front_numbers[*q_idx] = front_idx; // set front number of solution
-            next_front.push(*q_idx); // and push its index into next front
-          }
-        }
-      }
-      // save current last front indices
-      new_solutions_indices.append(&mut last_front);
-      // and replace last front with next front
-      last_front = next_front;
-      front_idx += 1;
+/// Truncates solutions and their scores in place down to the `target`
+/// population size, keeping the solutions of the best fronts and the least
+/// crowded solutions of the last front that fits. If there are no more
+/// solutions than the `target`, all of them are kept in their order.
+///
+/// Solutions are sorted into fronts with the `incremental` sorting, if any,
+/// which then keeps relations of the survivors. Crowding distances are
+/// weighted by `weights`, if any.
+fn crowding_distance_selection<S, const N: usize>(
+  solutions: &mut Vec<S>,
+  scores: &mut Vec<Scores<N>>,
+  target: usize,
+  scratch: &mut ScratchBuffers,
+  mut incremental: Option<&mut IncrementalSorting<N>>,
+  weights: Option<&[f64; N]>,
+) {
+  let ScratchBuffers {
+    sorting,
+    front_numbers,
+    crowding_distances: cds,
+    last_front,
+    permutation,
+    is_survivor,
+  } = scratch;
+
+  match incremental.as_deref_mut() {
+    Some(incremental) => incremental.sort(scores, sorting),
+    None => nondominated_sort_with(scores, sorting),
+  }
+  // fronts are still sorted to be reported, but there is nothing to truncate
+  if solutions.len() <= target {
+    if let Some(incremental) = incremental {
+      permutation.clear();
+      permutation.extend((0..solutions.len()).map(to_index));
+      incremental.retain(scores, permutation, sorting);
+    }
+    return;
+  }
+  front_numbers.clear();
+  front_numbers.resize(solutions.len(), FrontNumber::MAX);
+  // indices of selected solutions come first in the permutation
+  permutation.clear();
+  last_front.clear();
+  // until we select enough solutions...
+  for (front_idx, front) in sorting.fronts().enumerate() {
+    // set front number of each solution in the front
+    for &idx in front.iter() {
+      front_numbers[from_index(idx)] = front_idx as FrontNumber;
     }
+    // save front indices if the whole front fits, otherwise the front
+    // becomes the last front and is truncated by crowding distance
+    if permutation.len() + front.len() < target {
+      permutation.extend_from_slice(front);
+    } else {
+      last_front.extend_from_slice(front);
+      break;
+    }
+  }
 
-    // calculate crowding distance for each solution in the last found front
-    let mut crowding_distances: Vec<CrowdingDistance> =
-      vec![0.0; solutions.len()];
-    // if last front has more than 2 values...
-    if last_front.len() > 2 {
-      // for each objective `o`...
-      for o_idx in 0..OBJECTIVE_NUM {
-        // sort solutions by their scores of objective `o`
-        last_front.sort_by(|&a_idx, &b_idx| {
-          scores[a_idx][o_idx]
-            .partial_cmp(&scores[b_idx][o_idx])
-            .unwrap_or(Ordering::Greater) // sort NaNs away
-        });
+  // calculate crowding distance for each solution in the last found front
+  crowding_distances(last_front, scores, weights, cds);
+  // sort solutions in the last front by their crowding distances
+  last_front.sort_by(|&a_idx, &b_idx| {
+    cds[from_index(b_idx)].total_cmp(&cds[from_index(a_idx)])
+  });
 
-        // get the first and the last front members
-        let first_idx = last_front[0];
-        let last_idx = last_front[last_front.len() - 1];
-        // set crowding distances of first and last front member to `f64::MAX`
-        crowding_distances[first_idx] = f64::MAX;
-        crowding_distances[last_idx] = f64::MAX;
-        // calculate difference between max and min score of current objective
-        let min_score = scores[first_idx][o_idx];
-        let max_score = scores[last_idx][o_idx];
-        let score_diff = if max_score != min_score {
-          f64::from(max_score - min_score)
-        } else {
-          1.0
-        };
-        // for each solution except the first and the last in the last front...
-        for idx in 2..last_front.len() - 2 {
-          if crowding_distances[idx] != f64::MAX {
-            let prev_cd = crowding_distances[idx - 1];
-            let next_cd = crowding_distances[idx + 1];
-            crowding_distances[idx] += (next_cd - prev_cd).abs() / score_diff;
-          }
-        }
-      }
-      // sort solutions in the last front by their crowding distances
-      last_front.sort_by(|&a_idx, &b_idx| {
-        crowding_distances[b_idx].total_cmp(&crowding_distances[a_idx])
-      });
-    }
+  permutation.extend_from_slice(last_front);
+  permutation.truncate(target);
+  permutation.sort_by(|&a_idx, &b_idx| {
+    let (a_idx, b_idx) = (from_index(a_idx), from_index(b_idx));
+    front_numbers[a_idx]
+      .cmp(&front_numbers[b_idx])
+      .then(cds[b_idx].total_cmp(&cds[a_idx]))
+  });
 
-    new_solutions_indices.append(&mut last_front);
-    new_solutions_indices.truncate(self.initial_population_size);
-    new_solutions_indices.sort_by(|&a_idx, &b_idx| {
-      front_numbers[a_idx]
-        .cmp(&front_numbers[b_idx])
-        .then(crowding_distances[b_idx].total_cmp(&crowding_distances[a_idx]))
-    });
+  debug_assert_eq!(
+    permutation.len(),
+    HashSet::<SolutionIndex>::from_iter(permutation.iter().cloned()).len(),
+    "new_solutions_indices must have only unique indices"
+  );
 
-    debug_assert_eq!(
-      new_solutions_indices.len(),
-      HashSet::<usize>::from_iter(new_solutions_indices.iter().cloned()).len(),
-      "new_solutions_indices must have only unique indices"
-    );
+  // indices of discarded solutions complete the permutation
+  let new_population_size = permutation.len();
+  if let Some(incremental) = incremental {
+    incremental.retain(scores, permutation, sorting);
+  }
+  is_survivor.clear();
+  is_survivor.resize(solutions.len(), false);
+  permutation
+    .iter()
+    .for_each(|&idx| is_survivor[from_index(idx)] = true);
+  permutation.extend(
+    (0..solutions.len())
+      .filter(|&idx| !is_survivor[idx])
+      .map(to_index),
+  );
+  apply_permutation(permutation, solutions, scores);
+  solutions.truncate(new_population_size);
+  scores.truncate(new_population_size);
 
-    let mut some_sols: Vec<_> = solutions.into_iter().map(Some).collect();
-    let mut some_scs: Vec<_> = scores.into_iter().map(Some).collect();
-    let (new_sols, new_scs): (Vec<_>, Vec<_>) = new_solutions_indices
-      .into_iter()
-      .map(|idx| {
-        (
-          some_sols[idx].take().expect("must be something here"),
-          some_scs[idx].take().expect("must be something here"),
-        )
+  debug_assert_eq!(
+    solutions.len(),
+    target.min(permutation.len()),
+    "new population size must match target population size"
+  );
+  debug_assert_eq!(
+    solutions.len(),
+    scores.len(),
+    "number of solutions must match number of scores"
+  );
+}
+
+/// Calculates crowding distance of each of the solutions with `scores`,
+/// treating all of them as a single front. Both boundary solutions of each
+/// objective get distance of `f64::MAX`.
+///
+/// Contribution of each objective, its gap between the neighbours of a
+/// solution normalized by the objective's range, is multiplied by the
+/// objective's weight from `weights`, if any. An objective of zero weight is
+/// ignored entirely, its boundary solutions included, so diversity is kept
+/// only along the other objectives.
+///
+/// # Panics
+///
+/// Panics if any of the `weights` is negative, infinite or NaN.
+///
+/// # Examples
+///
+/// ```
+/// use moga::optimizer::nsga::crowding_distance;
+///
+/// let scores = [[0.0, 3.0], [1.0, 2.0], [3.0, 1.0], [4.0, 0.0]];
+/// let distances = crowding_distance(&scores, Some(&[1.0, 0.0]));
+/// assert_eq!(distances, [f64::MAX, 0.75, 0.75, f64::MAX]);
+/// ```
+pub fn crowding_distance<const N: usize>(
+  scores: &[Scores<N>],
+  weights: Option<&[f64; N]>,
+) -> Vec<f64> {
+  if let Some(weights) = weights {
+    assert_crowding_weights(weights);
+  }
+  let mut front: Vec<_> = (0..scores.len()).map(to_index).collect();
+  let mut distances = Vec::with_capacity(scores.len());
+  crowding_distances(&mut front, scores, weights, &mut distances);
+  distances
+}
+
+/// Panics if any of crowding distance `weights` is negative, infinite or NaN.
+fn assert_crowding_weights(weights: &[f64]) {
+  assert!(
+    weights.iter().all(|w| w.is_finite() && *w >= 0.0),
+    "crowding distance weights must be finite and non-negative"
+  );
+}
+
+/// Calculates crowding distance for each solution in the `front` and stores
+/// it in `crowding_distances` by solutions' indices. Solutions outside of the
+/// front get distance of `0.0`, both boundary solutions of each objective get
+/// distance of `f64::MAX`. Contribution of each objective is multiplied by its
+/// weight from `weights`, if any, and objectives of zero weight are skipped.
+/// Order of indices in the `front` is not preserved.
+///
+/// With `parallel` feature enabled, fronts of [`PARALLEL_THRESHOLD`] or more
+/// solutions are processed in parallel for each objective. With `score-matrix`
+/// feature enabled, smaller fronts are processed over columns of a
+/// [`ScoreMatrix`].
+pub(crate) fn crowding_distances<const N: usize>(
+  front: &mut [SolutionIndex],
+  scores: &[Scores<N>],
+  weights: Option<&[f64; N]>,
+  crowding_distances: &mut Vec<CrowdingDistance>,
+) {
+  crowding_distances.clear();
+  crowding_distances.resize(scores.len(), 0.0);
+  let weight = |o_idx: usize| weights.map_or(1.0, |weights| weights[o_idx]);
+  // objectives of zero weight don't crowd solutions at all
+  if (0..N).all(|o_idx| weight(o_idx) == 0.0) {
+    return;
+  }
+  // if front has 2 values or less, all of them are boundary solutions
+  if front.len() <= 2 {
+    front
+      .iter()
+      .for_each(|&idx| crowding_distances[from_index(idx)] = f64::MAX);
+    return;
+  }
+
+  #[cfg(feature = "parallel")]
+  if front.len() >= PARALLEL_THRESHOLD {
+    *crowding_distances = (0..N)
+      .into_par_iter()
+      .filter(|&o_idx| weight(o_idx) != 0.0)
+      .map(|o_idx| {
+        let mut front = front.to_vec();
+        let mut distances = vec![0.0; scores.len()];
+        add_objective_distances(
+          &mut front,
+          scores,
+          o_idx,
+          weight(o_idx),
+          &mut distances,
+        );
+        distances
       })
-      .unzip();
+      .reduce(
+        || vec![0.0; scores.len()],
+        |mut a, b| {
+          // keep boundary solutions at maximum distance
+          a.iter_mut().zip(b).for_each(|(a, b)| {
+            *a = if *a == f64::MAX || b == f64::MAX {
+              f64::MAX
+            } else {
+              *a + b
+            }
+          });
+          a
+        },
+      );
+    return;
+  }
 
-    debug_assert_eq!(
-      new_sols.len(),
-      self.initial_population_size,
-      "new population size must match initial population size"
-    );
-    debug_assert_eq!(
-      new_sols.len(),
-      new_scs.len(),
-      "number of solutions must match number of scores"
+  #[cfg(feature = "score-matrix")]
+  {
+    add_column_distances(front, scores, weight, crowding_distances);
+  }
+  #[cfg(not(feature = "score-matrix"))]
+  for o_idx in (0..N).filter(|&o_idx| weight(o_idx) != 0.0) {
+    add_objective_distances(
+      front,
+      scores,
+      o_idx,
+      weight(o_idx),
+      crowding_distances,
     );
+  }
+}
+
+/// Adds crowding distance contributions of objectives of nonzero `weight` to
+/// each solution in the `front` like [`add_objective_distances`] does for each
+/// of them, but sorts and scans columns of scores of the front, copied from a
+/// [`ScoreMatrix`] of them.
+#[cfg(feature = "score-matrix")]
+fn add_column_distances<const N: usize>(
+  front: &[SolutionIndex],
+  scores: &[Scores<N>],
+  weight: impl Fn(usize) -> f64,
+  crowding_distances: &mut [CrowdingDistance],
+) {
+  let mut matrix = ScoreMatrix::<N>::default();
+  front
+    .iter()
+    .for_each(|&idx| matrix.push(&scores[from_index(idx)]));
+  // positions of solutions in the front, sorted by the last objective, so that
+  // ties are broken like in the front sorted objective after objective
+  let mut order: Vec<_> = (0..front.len()).collect();
+  let mut column = Vec::with_capacity(front.len());
+  let mut sorted = Vec::with_capacity(front.len());
+  for o_idx in (0..N).filter(|&o_idx| weight(o_idx) != 0.0) {
+    matrix.copy_column(o_idx, &mut column);
+    order.sort_by(|&a, &b| {
+      column[a]
+        .partial_cmp(&column[b])
+        .unwrap_or(Ordering::Greater) // sort NaNs away
+    });
+    sorted.clear();
+    sorted.extend(order.iter().map(|&pos| f64::from(column[pos])));
+
+    let (first, last) = (order[0], order[order.len() - 1]);
+    crowding_distances[from_index(front[first])] = f64::MAX;
+    crowding_distances[from_index(front[last])] = f64::MAX;
+    let score_diff = if column[last] != column[first] {
+      f64::from(column[last] - column[first])
+    } else {
+      1.0
+    };
+    let weight = weight(o_idx);
+    for (window, &pos) in sorted.windows(3).zip(&order[1..]) {
+      let idx = from_index(front[pos]);
+      if crowding_distances[idx] != f64::MAX {
+        crowding_distances[idx] +=
+          weight * (window[2] - window[0]).abs() / score_diff;
+      }
+    }
+  }
+}
+
+/// Adds crowding distance contribution of objective `o`, multiplied by its
+/// `weight`, to each solution in the `front`, sorting it by scores of that
+/// objective.
+#[cfg(any(feature = "parallel", not(feature = "score-matrix")))]
+fn add_objective_distances<const N: usize>(
+  front: &mut [SolutionIndex],
+  scores: &[Scores<N>],
+  o_idx: usize,
+  weight: f64,
+  crowding_distances: &mut [CrowdingDistance],
+) {
+  // sort solutions by their scores of objective `o`
+  front.sort_by(|&a_idx, &b_idx| {
+    scores[from_index(a_idx)][o_idx]
+      .partial_cmp(&scores[from_index(b_idx)][o_idx])
+      .unwrap_or(Ordering::Greater) // sort NaNs away
+  });
 
-    (new_sols, new_scs)
+  // get the first and the last front members, which are the same one in a
+  // front of a single solution
+  let (Some(&first), Some(&last)) = (front.first(), front.last()) else {
+    return;
+  };
+  let [first_idx, last_idx] = [first, last].map(from_index);
+  // set crowding distances of first and last front member to `f64::MAX`
+  crowding_distances[first_idx] = f64::MAX;
+  crowding_distances[last_idx] = f64::MAX;
+  // calculate difference between max and min score of current objective
+  let min_score = scores[first_idx][o_idx];
+  let max_score = scores[last_idx][o_idx];
+  let score_diff = if max_score != min_score {
+    f64::from(max_score - min_score)
+  } else {
+    1.0
+  };
+  // for each solution except the first and the last in the front...
+  for window in front.windows(3) {
+    let [prev_idx, idx, next_idx] =
+      [window[0], window[1], window[2]].map(from_index);
+    if crowding_distances[idx] != f64::MAX {
+      let prev_sc = f64::from(scores[prev_idx][o_idx]);
+      let next_sc = f64::from(scores[next_idx][o_idx]);
+      crowding_distances[idx] +=
+        weight * (next_sc - prev_sc).abs() / score_diff;
+    }
   }
 }
 
@@ -335,8 +1238,8 @@ impl<
     const OBJECTIVE_NUM: usize,
     const PARENT_NUM: usize,
     const OFFSPRING_NUM: usize,
-  > Optimizer<Solution, OBJECTIVE_NUM>
-  for Nsga2<
+  >
+  Nsga2<
     Solution,
     Tst,
     Sel,
@@ -353,38 +1256,2562 @@ impl<
     OFFSPRING_NUM,
   >
 {
-  /// Runs NSGA-II `Optimizer` until the termination condition is met, then
-  /// returns the last found population.
+  /// Creates NSGA-II `Optimizer` with the initial `population` and
+  /// `operators`. Other options keep their defaults.
+  ///
+  /// # Panics
+  ///
+  /// Panics if population is empty.
+  pub fn from_operators(
+    population: Vec<Solution>,
+    operators: OperatorSet<
+      Solution,
+      Tst,
+      Sel,
+      Rec,
+      Mut,
+      Ter,
+      TstExecStrat,
+      TerExecStrat,
+      SelExecStrat,
+      MutExecStrat,
+      RecExecStrat,
+      OBJECTIVE_NUM,
+      PARENT_NUM,
+      OFFSPRING_NUM,
+    >,
+  ) -> Self {
+    let mut optimizer = Self::builder()
+      .population(population)
+      .tester(operators.tester)
+      .selector(operators.selector)
+      .recombinator(operators.recombinator)
+      .mutator(operators.mutator)
+      .terminator(operators.terminator)
+      .build();
+    optimizer.repairer = operators.repairer;
+    optimizer.constraint = operators.constraint;
+    optimizer
+  }
+
+  /// Tests `solutions` with the optimizer's tester right away and merges
+  /// them into the initial population when optimization starts. Solutions
+  /// can be injected into running optimization with a pause handler as well,
+  /// see [`Directive::InjectSolutions`].
+  ///
+  /// Injected solutions don't change the size of the population: if there
+  /// are more of them than the initial population size, the population is
+  /// truncated down to it at the end of the first generation.
+  /// Duplicates aren't removed, just like duplicates in the initial
+  /// population.
+  ///
+  /// Nothing is injected if the tester returns a different number of scores
+  /// than the number of `solutions`, and
+  /// [`OptimizationError::ScoreCountMismatch`] is returned instead.
+  pub fn inject(
+    &mut self,
+    mut solutions: Vec<Solution>,
+  ) -> Result<(), OptimizationError> {
+    let mut scores = self.tester.execute_tests(&solutions);
+    check_scores(
+      OperatorKind::Tester,
+      0,
+      scores.len(),
+      solutions.len(),
+      self.event_sender.as_ref(),
+    )?;
+    self.injected.append(&mut solutions);
+    self.injected_scores.append(&mut scores);
+    Ok(())
+  }
+
+  /// Applies the operators once to a sample of at most `sample_size`
+  /// solutions of the initial population, without running optimization, and
+  /// reports how many solutions each phase has produced and any anomalies,
+  /// such as NaN scores. It catches mistakes in operators before an
+  /// expensive run.
+  ///
+  /// The sample is tested, parents are selected out of it and recombined
+  /// within the offspring limit and budget of the optimizer, and their
+  /// offsprings are mutated, repaired and tested. Fails with the same errors
+  /// that optimization would fail with in the first generation, but sends no
+  /// events. Operators with internal state, such as random number
+  /// generators, are affected by the dry run just as by a generation.
+  pub fn dry_run(
+    &self,
+    sample_size: usize,
+  ) -> Result<DryRunReport, OptimizationError> {
+    // a population set only with scores is sampled instead
+    let population = if self.population.is_empty() {
+      &self.injected
+    } else {
+      &self.population
+    };
+    let sample = &population[..sample_size.min(population.len())];
+    let mut report = DryRunReport {
+      sampled: sample.len(),
+      ..Default::default()
+    };
+    catch_operator_panics::<_, OBJECTIVE_NUM>(
+      self.catch_operator_panics,
+      None,
+      |current| {
+        let scores = current.call(OperatorKind::Tester, 0, || {
+          self.tester.execute_tests(sample)
+        });
+        check_scores::<OBJECTIVE_NUM>(
+          OperatorKind::Tester,
+          0,
+          scores.len(),
+          sample.len(),
+          None,
+        )?;
+        let nan_scores = count_nan_scores(&scores);
+        if nan_scores > 0 {
+          report.anomalies.push(DryRunAnomaly::NanScores {
+            solutions: nan_scores,
+          });
+        }
+
+        let mut selected = current.call(OperatorKind::Selector, 0, || {
+          self.selector.execute_selection(sample, &scores)
+        });
+        check_selection::<OBJECTIVE_NUM>(
+          self.selector.selection_contract(),
+          0,
+          selected.len(),
+          None,
+        )?;
+        if selected.is_empty() {
+          match self.empty_selection {
+            EmptySelection::Fail => {
+              return Err(OptimizationError::SelectionEmpty { generation: 0 })
+            }
+            EmptySelection::SelectAll => selected = (0..sample.len()).collect(),
+          }
+        }
+        report.selected = selected.len();
+        if selected.len() < PARENT_NUM {
+          report.anomalies.push(DryRunAnomaly::TooFewParents {
+            selected: selected.len(),
+            required: PARENT_NUM,
+          });
+        }
+        let mut offsprings =
+          current.call(OperatorKind::Recombinator, 0, || {
+            recombine_within_limit(
+              &self.recombinator,
+              sample,
+              &scores,
+              &selected,
+              self.max_offspring_per_generation,
+              self.offspring_budget,
+              0,
+            )
+          })?;
+        report.offsprings = offsprings.len();
+        if offsprings.is_empty() {
+          report.anomalies.push(DryRunAnomaly::NoOffsprings);
+        }
+        current.call(OperatorKind::Mutator, 0, || {
+          self.mutator.execute_mutations(&mut offsprings)
+        });
+        if let Some(repairer) = &self.repairer {
+          current.call(OperatorKind::Repairer, 0, || {
+            repairer.repair(&mut offsprings)
+          });
+        }
+        let offspring_scores = current.call(OperatorKind::Tester, 0, || {
+          self.tester.execute_tests(&offsprings)
+        });
+        check_scores::<OBJECTIVE_NUM>(
+          OperatorKind::Tester,
+          0,
+          offspring_scores.len(),
+          offsprings.len(),
+          None,
+        )?;
+        let nan_scores = count_nan_scores(&offspring_scores);
+        if nan_scores > 0 {
+          report.anomalies.push(DryRunAnomaly::OffspringNanScores {
+            offsprings: nan_scores,
+          });
+        }
+        Ok(())
+      },
+    )?;
+    Ok(report)
+  }
+
+  /// Runs NSGA-II `Optimizer` like [`Optimizer::optimize`] does, but for at
+  /// most `generations` generations. The terminator is still called at the
+  /// start of each generation, so a stateful terminator sees every generation
+  /// and may terminate optimization earlier. If the limit is reached first,
+  /// the optimizer reports [`TerminationReason::GenerationLimit`].
+  ///
+  /// # Panics
+  ///
+  /// Panics if optimization fails with an [`OptimizationError`].
+  pub fn optimize_n(self, generations: usize) -> Vec<Solution> {
+    self
+      .try_optimize_n(generations)
+      .unwrap_or_else(|e| panic!("{e}"))
+  }
+
+  /// Runs NSGA-II `Optimizer` like [`Optimizer::try_optimize`] does, but for
+  /// at most `generations` generations, see [`Self::optimize_n`].
+  pub fn try_optimize_n(
+    mut self,
+    generations: usize,
+  ) -> Result<Vec<Solution>, OptimizationError> {
+    self.generation_limit = Some(generations);
+    self.try_optimize()
+  }
+
+  /// Runs NSGA-II `Optimizer` like [`Optimizer::optimize`] does, but pairs
+  /// each solution of the last found population with its [`SolutionMeta`].
+  ///
+  /// # Panics
+  ///
+  /// Panics if optimization fails with an [`OptimizationError`].
+  pub fn optimize_detailed(self) -> Vec<(Solution, SolutionMeta)> {
+    self
+      .try_optimize_detailed()
+      .unwrap_or_else(|e| panic!("{e}"))
+  }
+
+  /// Runs NSGA-II `Optimizer` like [`Optimizer::try_optimize`] does, but
+  /// pairs each solution of the last found population with its
+  /// [`SolutionMeta`].
+  pub fn try_optimize_detailed(
+    mut self,
+  ) -> Result<Vec<(Solution, SolutionMeta)>, OptimizationError> {
+    let mut population = std::mem::take(&mut self.population);
+    let mut scores = Vec::new();
+    let event_sender = self.event_sender.clone();
+    catch_operator_panics(
+      self.catch_operator_panics,
+      event_sender.as_ref(),
+      |current| self.run(&mut population, &mut scores, current),
+    )?;
+    Ok(
+      population
+        .into_iter()
+        .zip(solution_metas(&scores, self.crowding_weights.as_ref()))
+        .collect(),
+    )
+  }
+
+  /// Runs NSGA-II `Optimizer` like [`Optimizer::optimize`] does, but returns
+  /// the last found population along with why optimization has terminated
+  /// and how many generations it has run, see [`OptimizationOutcome`].
+  ///
+  /// # Panics
+  ///
+  /// Panics if optimization fails with an [`OptimizationError`].
+  pub fn optimize_with_outcome(self) -> OptimizationOutcome<Solution> {
+    self
+      .try_optimize_with_outcome()
+      .unwrap_or_else(|e| panic!("{e}"))
+  }
+
+  /// Runs NSGA-II `Optimizer` like [`Optimizer::try_optimize`] does, but
+  /// returns the last found population along with why optimization has
+  /// terminated and how many generations it has run, see
+  /// [`OptimizationOutcome`].
+  pub fn try_optimize_with_outcome(
+    mut self,
+  ) -> Result<OptimizationOutcome<Solution>, OptimizationError> {
+    let mut population = std::mem::take(&mut self.population);
+    let mut scores = Vec::new();
+    let event_sender = self.event_sender.clone();
+    let terminated = catch_operator_panics(
+      self.catch_operator_panics,
+      event_sender.as_ref(),
+      |current| self.run(&mut population, &mut scores, current),
+    )?;
+    Ok(terminated.into_outcome(population))
+  }
+
+  /// Runs NSGA-II `Optimizer` like [`Optimizer::optimize`] does, but pairs
+  /// each solution of the last found population with its scores, so that
+  /// they can seed another optimizer with [`population_with_scores`]
+  /// without being tested again.
   ///
   /// # Panics
   ///
-  /// Panic if at some point the population becomes empty, or the number of
-  /// scores doesn't match the population size.
-  fn optimize(mut self) -> Vec<Solution> {
+  /// Panics if optimization fails with an [`OptimizationError`].
+  ///
+  /// [`population_with_scores`]: Nsga2Builder::population_with_scores
+  pub fn optimize_with_scores(self) -> Vec<(Solution, Scores<OBJECTIVE_NUM>)> {
+    self
+      .try_optimize_with_scores()
+      .unwrap_or_else(|e| panic!("{e}"))
+  }
+
+  /// Runs NSGA-II `Optimizer` like [`Optimizer::try_optimize`] does, but
+  /// pairs each solution of the last found population with its scores, see
+  /// [`Self::optimize_with_scores`].
+  pub fn try_optimize_with_scores(
+    mut self,
+  ) -> Result<Vec<(Solution, Scores<OBJECTIVE_NUM>)>, OptimizationError> {
     let mut population = std::mem::take(&mut self.population);
-    let mut scores = self.tester.execute_tests(&population);
+    let mut scores = Vec::new();
+    let event_sender = self.event_sender.clone();
+    catch_operator_panics(
+      self.catch_operator_panics,
+      event_sender.as_ref(),
+      |current| self.run(&mut population, &mut scores, current),
+    )?;
+    Ok(population.into_iter().zip(scores).collect())
+  }
 
-    while !self.terminator.execute_termination(&population, &scores) {
-      assert!(!population.is_empty(), "the population is empty");
-      assert_eq!(
+  /// Tests the initial `population` and evolves it until the termination
+  /// condition is met. The `population` and its `scores` are kept up to date,
+  /// so the caller gets them even if optimization fails.
+  fn run(
+    &mut self,
+    population: &mut Vec<Solution>,
+    scores: &mut Vec<Scores<OBJECTIVE_NUM>>,
+    current: &mut CurrentOperator,
+  ) -> Result<Terminated, OptimizationError> {
+    let mut timings = self.profile.then(OperatorTimings::default);
+    if let (Some(observer), Some(labels)) =
+      (self.observer.as_mut(), &self.objective_labels)
+    {
+      current.call(OperatorKind::Observer, 0, || {
+        observer.label_objectives(labels)
+      });
+    }
+    check_population(
+      population.len() + self.injected.len(),
+      self.event_sender.as_ref(),
+    );
+    // solutions set with scores are merged without testing
+    if !population.is_empty() {
+      *scores = {
+        let _timer = Timer::start(&mut timings, |t| &mut t.test);
+        let _phase = phase!("test", population_size = population.len());
+        current.call(OperatorKind::Tester, 0, || {
+          self.tester.execute_tests(population)
+        })
+      };
+      check_scores(
+        OperatorKind::Tester,
+        0,
         scores.len(),
         population.len(),
-        "the number of calculated fitness scores doesn't match size of the population"
-      );
-      let selected_population =
-        self.selector.execute_selection(&population, &scores);
-      let mut created_population =
-        self.recombinator.execute_recombination(selected_population);
-      self.mutator.execute_mutations(&mut created_population);
-      let mut created_scores = self.tester.execute_tests(&created_population);
-
-      population.append(&mut created_population);
-      scores.append(&mut created_scores);
-
-      (population, scores) =
-        self.crowding_distance_selection(population, scores);
+        self.event_sender.as_ref(),
+      )?;
     }
+    population.append(&mut self.injected);
+    scores.append(&mut self.injected_scores);
+    self.incremental_sorting =
+      self.full_sort_interval.map(IncrementalSorting::new);
+
+    let mut generation = 0;
+    let terminated = loop {
+      let _generation = phase!("generation", generation = generation);
+      let deadline = self
+        .generation_time_budget
+        .map(|budget| Instant::now() + budget);
+      let terminated = {
+        let _timer = Timer::start(&mut timings, |t| &mut t.terminate);
+        let phase =
+          phase!("terminate", population_size = population.len(), terminated);
+        let terminated =
+          current.call(OperatorKind::Terminator, generation, || {
+            self.terminator.execute_termination(population, scores)
+          });
+        phase.record("terminated", terminated.is_stop());
+        terminated
+      };
+      if let TerminationDecision::Stop(reason) = terminated {
+        break Terminated::new(
+          TerminationReason::Terminator,
+          reason,
+          generation,
+        );
+      }
+      if self
+        .generation_limit
+        .is_some_and(|limit| generation >= limit)
+      {
+        break Terminated::generation_limit(generation);
+      }
+      if self
+        .cancel_flag
+        .as_ref()
+        .is_some_and(FlagTerminator::is_raised)
+      {
+        break Terminated::cancelled(generation);
+      }
+      check_population(population.len(), self.event_sender.as_ref());
+      let species = self
+        .speciation
+        .as_ref()
+        .map(|assigner| {
+          let offsprings = self
+            .max_offspring_per_generation
+            .unwrap_or(population.len());
+          speciate(assigner.as_ref(), population, scores, offsprings)
+        })
+        .unwrap_or_default();
+      // without species, the whole population is selected from at once
+      let whole = species.is_empty().then_some((0..population.len(), None));
+      let groups = whole.into_iter().chain(
+        species
+          .into_iter()
+          .map(|species| (species.range, Some(species.quota))),
+      );
+      let mut created_population = Vec::new();
+      for (range, quota) in groups {
+        let mut selected_population = {
+          let _timer = Timer::start(&mut timings, |t| &mut t.select);
+          let phase = phase!("select", population_size = range.len(), selected);
+          let selected =
+            current.call(OperatorKind::Selector, generation, || {
+              self.selector.execute_selection(
+                &population[range.clone()],
+                &scores[range.clone()],
+              )
+            });
+          phase.record("selected", selected.len());
+          selected
+        };
+        check_selection(
+          self.selector.selection_contract(),
+          generation,
+          selected_population.len(),
+          self.event_sender.as_ref(),
+        )?;
+        selected_population
+          .iter_mut()
+          .for_each(|idx| *idx += range.start);
+        if selected_population.is_empty() {
+          match self.empty_selection {
+            EmptySelection::Fail => {
+              return Err(report_error(
+                OptimizationError::SelectionEmpty { generation },
+                self.event_sender.as_ref(),
+              ))
+            }
+            EmptySelection::SelectAll => selected_population = range.collect(),
+          }
+        }
+        let (limit, budget) = match quota {
+          Some(quota) => (Some(quota), quota_budget(self.offspring_budget)),
+          None => (self.max_offspring_per_generation, self.offspring_budget),
+        };
+        let mut created = {
+          let _timer = Timer::start(&mut timings, |t| &mut t.recombine);
+          let phase =
+            phase!("recombine", parents = selected_population.len(), offspring);
+          let created = current
+            .call(OperatorKind::Recombinator, generation, || {
+              recombine_within_limit(
+                &self.recombinator,
+                population,
+                scores,
+                &selected_population,
+                limit,
+                budget,
+                generation,
+              )
+            })
+            .map_err(|e| report_error(e, self.event_sender.as_ref()))?;
+          phase.record("offspring", created.len());
+          created
+        };
+        // recombinators that can't tell their number of offsprings in advance
+        // recombine all parents of a species
+        if let Some(quota) = quota {
+          created.truncate(quota);
+        }
+        if created_population.is_empty() {
+          created_population = created;
+        } else {
+          created_population.append(&mut created);
+        }
+      }
+      {
+        let _timer = Timer::start(&mut timings, |t| &mut t.mutate);
+        let _phase = phase!("mutate", offspring = created_population.len());
+        current.call(OperatorKind::Mutator, generation, || {
+          self.mutator.execute_mutations(&mut created_population)
+        });
+      }
+      if let Some(repairer) = &self.repairer {
+        let _timer = Timer::start(&mut timings, |t| &mut t.repair);
+        let _phase = phase!("repair", offspring = created_population.len());
+        current.call(OperatorKind::Repairer, generation, || {
+          repairer.repair(&mut created_population)
+        });
+      }
+      if let Some(dedup) = &self.dedup {
+        let _timer = Timer::start(&mut timings, |t| &mut t.dedup);
+        let phase =
+          phase!("dedup", offspring = created_population.len(), removed);
+        let removed =
+          current.call(OperatorKind::Deduplicator, generation, || {
+            dedup.dedup(population, &mut created_population)
+          });
+        phase.record("removed", removed);
+        if let Some(observer) = self.observer.as_mut() {
+          current.call(OperatorKind::Observer, generation, || {
+            observer.duplicates_removed(generation, removed)
+          });
+        }
+        if let Some(event_sender) = &self.event_sender {
+          event_sender.send(OptimizerEvent::DuplicatesRemoved {
+            index: generation,
+            count: removed,
+          });
+        }
+      }
+      let (mut created_scores, untested) = {
+        let _timer = Timer::start(&mut timings, |t| &mut t.test);
+        let _phase = phase!("test", population_size = created_population.len());
+        current.call(OperatorKind::Tester, generation, || {
+          test_until(&self.tester, &mut created_population, deadline)
+        })
+      };
+      check_scores(
+        OperatorKind::Tester,
+        generation,
+        created_scores.len(),
+        created_population.len(),
+        self.event_sender.as_ref(),
+      )?;
+      if untested > 0 {
+        if let Some(observer) = self.observer.as_mut() {
+          current.call(OperatorKind::Observer, generation, || {
+            observer.tests_timed_out(generation, untested)
+          });
+        }
+        if let Some(event_sender) = &self.event_sender {
+          event_sender.send(OptimizerEvent::TestsTimedOut {
+            index: generation,
+            count: untested,
+          });
+        }
+      }
+      let offsprings_num = created_population.len();
+
+      let target_population_size = match &self.population_schedule {
+        Some(schedule) => schedule.size(generation),
+        None => self.initial_population_size,
+      };
+      let (mut accepted, mut accepted_scores) = match &self.acceptance {
+        Some(acceptance) if self.replacement == Replacement::MuPlusLambda => {
+          current.call(OperatorKind::Acceptance, generation, || {
+            accept_dominated(
+              acceptance.as_ref(),
+              population,
+              scores,
+              &mut created_population,
+              &mut created_scores,
+              generation,
+              target_population_size,
+            )
+          })
+        }
+        _ => Default::default(),
+      };
+      let target_population_size = target_population_size - accepted.len();
+      // offsprings follow parents in the merged population
+      let offsprings_start = match self.replacement {
+        Replacement::MuPlusLambda => population.len(),
+        Replacement::MuCommaLambda => 0,
+      };
+      match self.replacement {
+        Replacement::MuPlusLambda => {
+          population.append(&mut created_population);
+          scores.append(&mut created_scores);
+        }
+        Replacement::MuCommaLambda => {
+          if created_population.len() < target_population_size {
+            return Err(report_error(
+              OptimizationError::TooFewOffsprings {
+                generation,
+                offsprings: created_population.len(),
+                required: target_population_size,
+              },
+              self.event_sender.as_ref(),
+            ));
+          }
+          *population = created_population;
+          *scores = created_scores;
+        }
+      }
+      #[cfg(feature = "tracing")]
+      if population.len() < target_population_size {
+        tracing::warn!(
+          target: "moga",
+          generation,
+          population_size = population.len(),
+          target_population_size,
+          "population is smaller than the target population"
+        );
+      }
+      // accepted offsprings survive regardless of their fronts
+      let mut surviving_offsprings = accepted.len();
+      {
+        let _timer = Timer::start(&mut timings, |t| &mut t.truncate);
+        let phase =
+          phase!("truncate", population_size = population.len(), survived);
+        match &self.truncation {
+          Some(truncation) => {
+            // custom truncations don't tell which solutions survive, so
+            // offsprings are recognized among survivors by their scores
+            let merged_scores = self.feedback.as_ref().map(|_| scores.clone());
+            (*population, *scores) =
+              current.call(OperatorKind::Truncation, generation, || {
+                truncation.truncate(
+                  std::mem::take(population),
+                  std::mem::take(scores),
+                  target_population_size,
+                )
+              });
+            check_scores(
+              OperatorKind::Truncation,
+              generation,
+              scores.len(),
+              population.len(),
+              self.event_sender.as_ref(),
+            )?;
+            if let Some(merged_scores) = merged_scores {
+              let (parent_scores, offspring_scores) =
+                merged_scores.split_at(offsprings_start);
+              surviving_offsprings += count_surviving_offsprings(
+                parent_scores,
+                offspring_scores,
+                scores,
+              );
+            }
+          }
+          None if self.feedback.is_some() => {
+            surviving_offsprings += select_tracking_offsprings(
+              population,
+              offsprings_start,
+              |tagged| {
+                self.truncate_by_crowding(
+                  tagged,
+                  scores,
+                  target_population_size,
+                )
+              },
+            );
+          }
+          None => self.truncate_by_crowding(
+            population,
+            scores,
+            target_population_size,
+          ),
+        }
+        population.append(&mut accepted);
+        scores.append(&mut accepted_scores);
+        phase.record("survived", population.len());
+      }
+      if let Some(feedback) = self.feedback.as_mut() {
+        current.call(OperatorKind::Feedback, generation, || {
+          feedback.feedback(generation, offsprings_num, surviving_offsprings)
+        });
+      }
+      if let Some(fingerprint) = &self.parent_fingerprint {
+        let _timer = Timer::start(&mut timings, |t| &mut t.mutate);
+        let phase = phase!(
+          "mutate_parents",
+          population_size = population.len(),
+          changed
+        );
+        let changed = mutate_survivors(
+          &self.mutator,
+          &self.tester,
+          fingerprint,
+          population,
+          scores,
+          generation,
+          current,
+          self.event_sender.as_ref(),
+        )?;
+        phase.record("changed", changed);
+      }
+      if let Some(constraint) = &self.constraint {
+        let _timer = Timer::start(&mut timings, |t| &mut t.constrain);
+        let _phase = phase!("constrain", population_size = population.len());
+        check_scores(
+          OperatorKind::Constrainer,
+          generation,
+          current.call(OperatorKind::Constrainer, generation, || {
+            constraint.adapt(population)
+          }),
+          population.len(),
+          self.event_sender.as_ref(),
+        )?;
+      }
+      #[cfg(feature = "tracing")]
+      tracing::info!(
+        target: "moga",
+        generation,
+        population_size = population.len(),
+        front_sizes = ?self.scratch.sorting.fronts().map(<[_]>::len).collect::<Vec<_>>(),
+        "generation completed"
+      );
+      if let Some(observer) = self.observer.as_mut() {
+        current.call(OperatorKind::Observer, generation, || {
+          observer.observe(generation, population, scores)
+        });
+      }
+      if let Some(event_sender) = &self.event_sender {
+        event_sender.send_generation(
+          generation,
+          population,
+          scores,
+          self.objective_labels,
+        );
+      }
+      if let Some(directive) = self.pause.as_mut().and_then(|pause| {
+        current.call(OperatorKind::PauseHandler, generation, || {
+          pause.directive(generation, population, scores)
+        })
+      }) {
+        let injected = match directive {
+          Directive::Continue => None,
+          Directive::Stop => break Terminated::paused(generation + 1),
+          Directive::AdjustReference(reference) => {
+            if let Some(truncation) = self.truncation.as_mut() {
+              truncation.adjust_reference(reference);
+            }
+            None
+          }
+          Directive::InjectSolutions(injected) => Some(injected),
+          Directive::Restart(fresh) => {
+            retain_front(population, scores);
+            Some(fresh)
+          }
+        };
+        if let Some(mut injected) = injected {
+          let mut injected_scores = {
+            let _timer = Timer::start(&mut timings, |t| &mut t.test);
+            let _phase = phase!("test", population_size = injected.len());
+            current.call(OperatorKind::Tester, generation, || {
+              self.tester.execute_tests(&injected)
+            })
+          };
+          check_scores(
+            OperatorKind::Tester,
+            generation,
+            injected_scores.len(),
+            injected.len(),
+            self.event_sender.as_ref(),
+          )?;
+          population.append(&mut injected);
+          scores.append(&mut injected_scores);
+        }
+      }
+      generation += 1;
+    };
 
-    population
+    report_termination(timings, terminated.kind, self.event_sender.as_ref());
+    Ok(terminated)
+  }
+
+  /// Truncates `solutions` to the `target` size by fronts and crowding
+  /// distances of their `scores`, as seen through the quantizer and the score
+  /// transforms.
+  fn truncate_by_crowding<T>(
+    &mut self,
+    solutions: &mut Vec<T>,
+    scores: &mut Vec<Scores<OBJECTIVE_NUM>>,
+    target: usize,
+  ) {
+    match shadow_view(self.quantizer.as_ref(), self.score_transform.as_ref()) {
+      Some(view) => select_by(solutions, scores, view, |paired, viewed| {
+        crowding_distance_selection(
+          paired,
+          viewed,
+          target,
+          &mut self.scratch,
+          self.incremental_sorting.as_mut(),
+          self.crowding_weights.as_ref(),
+        )
+      }),
+      None => crowding_distance_selection(
+        solutions,
+        scores,
+        target,
+        &mut self.scratch,
+        self.incremental_sorting.as_mut(),
+        self.crowding_weights.as_ref(),
+      ),
+    }
+  }
+}
+
+impl<
+    Solution,
+    Tst: TestExecutor<Solution, OBJECTIVE_NUM, TstExecStrat>,
+    Sel: SelectionExecutor<Solution, OBJECTIVE_NUM, SelExecStrat>,
+    Rec: RecombinationExecutor<Solution, PARENT_NUM, OFFSPRING_NUM, RecExecStrat>,
+    Mut: MutationExecutor<Solution, MutExecStrat>,
+    Ter: TerminationExecutor<Solution, OBJECTIVE_NUM, TerExecStrat>,
+    TstExecStrat,
+    TerExecStrat,
+    SelExecStrat,
+    MutExecStrat,
+    RecExecStrat,
+    const OBJECTIVE_NUM: usize,
+    const PARENT_NUM: usize,
+    const OFFSPRING_NUM: usize,
+  > Optimizer<Solution, OBJECTIVE_NUM>
+  for Nsga2<
+    Solution,
+    Tst,
+    Sel,
+    Rec,
+    Mut,
+    Ter,
+    TstExecStrat,
+    TerExecStrat,
+    SelExecStrat,
+    MutExecStrat,
+    RecExecStrat,
+    OBJECTIVE_NUM,
+    PARENT_NUM,
+    OFFSPRING_NUM,
+  >
+{
+  /// Runs NSGA-II `Optimizer` until the termination condition is met, then
+  /// returns the last found population.
+  ///
+  /// # Panics
+  ///
+  /// Panics if optimization fails with an [`OptimizationError`], or if at
+  /// some point the population becomes empty.
+  fn optimize(self) -> Vec<Solution> {
+    self.try_optimize().unwrap_or_else(|e| panic!("{e}"))
+  }
+
+  /// Runs NSGA-II `Optimizer` until the termination condition is met, then
+  /// returns the last found population, or
+  /// [`OptimizationError::SelectionEmpty`] if the selector selects no
+  /// solutions and the optimizer was built with [`EmptySelection::Fail`].
+  ///
+  /// # Panics
+  ///
+  /// Panic if at some point the population becomes empty.
+  fn try_optimize(self) -> Result<Vec<Solution>, OptimizationError> {
+    self
+      .try_optimize_best_effort()
+      .map_err(|failure| failure.error)
+  }
+
+  /// Runs NSGA-II `Optimizer` like [`Optimizer::try_optimize`] does, but
+  /// returns the population along with an error if optimization fails.
+  ///
+  /// The population is the population of the last completed generation,
+  /// or, if a generation fails before its offsprings are truncated, the
+  /// population merged with them. If a custom truncation panics, the
+  /// population it was given is lost, and the returned population is empty.
+  fn try_optimize_best_effort(
+    mut self,
+  ) -> Result<Vec<Solution>, OptimizationFailure<Solution>> {
+    let mut population = std::mem::take(&mut self.population);
+    let mut scores = Vec::new();
+    let event_sender = self.event_sender.clone();
+    match catch_operator_panics(
+      self.catch_operator_panics,
+      event_sender.as_ref(),
+      |current| self.run(&mut population, &mut scores, current),
+    ) {
+      Ok(_) => Ok(population),
+      Err(error) => Err(OptimizationFailure { error, population }),
+    }
+  }
+}
+
+impl<
+    Solution: 'static,
+    Tst: TestExecutor<Solution, OBJECTIVE_NUM, TstExecStrat>,
+    Sel: SelectionExecutor<Solution, OBJECTIVE_NUM, SelExecStrat>,
+    Rec: RecombinationExecutor<Solution, PARENT_NUM, OFFSPRING_NUM, RecExecStrat>,
+    Mut: MutationExecutor<Solution, MutExecStrat>,
+    Ter: TerminationExecutor<Solution, OBJECTIVE_NUM, TerExecStrat>,
+    TstExecStrat,
+    TerExecStrat,
+    SelExecStrat,
+    MutExecStrat,
+    RecExecStrat,
+    const OBJECTIVE_NUM: usize,
+    const PARENT_NUM: usize,
+    const OFFSPRING_NUM: usize,
+  > Spawnable<Solution, OBJECTIVE_NUM>
+  for Nsga2<
+    Solution,
+    Tst,
+    Sel,
+    Rec,
+    Mut,
+    Ter,
+    TstExecStrat,
+    TerExecStrat,
+    SelExecStrat,
+    MutExecStrat,
+    RecExecStrat,
+    OBJECTIVE_NUM,
+    PARENT_NUM,
+    OFFSPRING_NUM,
+  >
+{
+  fn cancel_flag(&mut self) -> FlagTerminator {
+    self
+      .cancel_flag
+      .get_or_insert_with(FlagTerminator::new)
+      .clone()
+  }
+
+  fn add_observer(
+    &mut self,
+    observer: Box<dyn Observer<Solution, OBJECTIVE_NUM> + Send>,
+  ) {
+    self.observer = Some(match self.observer.take() {
+      Some(own) => Box::new((own, observer)),
+      None => observer,
+    });
+  }
+}
+
+impl<
+    Solution,
+    Tst: TestExecutor<Solution, OBJECTIVE_NUM, TstExecStrat>,
+    Sel: SelectionExecutor<Solution, OBJECTIVE_NUM, SelExecStrat>,
+    Rec: RecombinationExecutor<Solution, PARENT_NUM, OFFSPRING_NUM, RecExecStrat>,
+    Mut: MutationExecutor<Solution, MutExecStrat>,
+    Ter: TerminationExecutor<Solution, OBJECTIVE_NUM, TerExecStrat>,
+    TstExecStrat,
+    TerExecStrat,
+    SelExecStrat,
+    MutExecStrat,
+    RecExecStrat,
+    const OBJECTIVE_NUM: usize,
+    const PARENT_NUM: usize,
+    const OFFSPRING_NUM: usize,
+  > Seedable<Solution, OBJECTIVE_NUM>
+  for Nsga2<
+    Solution,
+    Tst,
+    Sel,
+    Rec,
+    Mut,
+    Ter,
+    TstExecStrat,
+    TerExecStrat,
+    SelExecStrat,
+    MutExecStrat,
+    RecExecStrat,
+    OBJECTIVE_NUM,
+    PARENT_NUM,
+    OFFSPRING_NUM,
+  >
+{
+  fn seed(&mut self, population: Vec<Solution>) {
+    if !population.is_empty() {
+      self.population = population;
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::{
+    atomic::{self, AtomicUsize},
+    mpsc,
+    Arc,
+    Mutex,
+  };
+
+  use rand::prelude::*;
+
+  use super::*;
+  use crate::{
+    dedup::Dedup,
+    mutation::CataclysmicRestart,
+    selection::{AllSelector, FirstSelector},
+    termination::GenerationTerminator,
+    truncation::HypervolumeTruncation,
+  };
+
+  /// Returns an evenly spaced 2-objective front of `len` solutions.
+  fn line_front(len: usize) -> Vec<Scores<2>> {
+    (0..len).map(|i| [i as f32, (len - 1 - i) as f32]).collect()
+  }
+
+  fn assert_line_crowding_distances(len: usize) {
+    let scores = line_front(len);
+    let mut front: Vec<_> = (0..len).rev().map(to_index).collect();
+    let mut distances = Vec::new();
+    crowding_distances(&mut front, &scores, None, &mut distances);
+    assert_eq!(distances[0], f64::MAX);
+    assert_eq!(distances[len - 1], f64::MAX);
+    let expected = 2.0 * 2.0 / (len - 1) as f64;
+    for d in &distances[1..len - 1] {
+      assert!((d - expected).abs() < 1e-9, "{d} != {expected}");
+    }
+  }
+
+  #[test]
+  fn test_crowding_distances() {
+    assert_line_crowding_distances(4);
+    assert_line_crowding_distances(crate::execution::PARALLEL_THRESHOLD + 1);
+  }
+
+  #[test]
+  fn test_crowding_distances_small_front() {
+    let scores = line_front(3);
+    let mut distances = Vec::new();
+    crowding_distances(&mut [to_index(2)], &scores, None, &mut distances);
+    assert_eq!(distances, [0.0, 0.0, f64::MAX]);
+    crowding_distances(&mut [], &scores, None, &mut distances);
+    assert!(distances.iter().all(|&d| d == 0.0));
+  }
+
+  #[test]
+  fn test_weighted_crowding_distance() {
+    let scores = line_front(5);
+    assert_eq!(
+      crowding_distance(&scores, None),
+      crowding_distance(&scores, Some(&[1.0, 1.0]))
+    );
+    let distances = crowding_distance(&scores, Some(&[3.0, 1.0]));
+    assert_eq!(distances[0], f64::MAX);
+    assert_eq!(distances[4], f64::MAX);
+    assert!(distances[1..4].iter().all(|&d| (d - 2.0).abs() < 1e-9));
+    assert!(crowding_distance(&scores, Some(&[0.0, 0.0]))
+      .iter()
+      .all(|&d| d == 0.0));
+    // boundary solutions of an ignored objective are not kept at maximum
+    let scores = [[0.0, 1.0], [1.0, 0.0], [2.0, 2.0], [3.0, 3.0]];
+    assert_eq!(crowding_distance(&scores, Some(&[0.0, 1.0])), [
+      2.0 / 3.0,
+      f64::MAX,
+      2.0 / 3.0,
+      f64::MAX
+    ]);
+  }
+
+  #[test]
+  #[should_panic(expected = "must be finite and non-negative")]
+  fn test_negative_crowding_weights() {
+    crowding_distance(&line_front(3), Some(&[1.0, -1.0]));
+  }
+
+  #[test]
+  fn test_weighted_crowding_truncation() {
+    // the first solutions are spread along the first objective, the last ones
+    // are spread along the second one
+    let scores = vec![
+      [0.0, 10.0],
+      [2.0, 9.9],
+      [4.0, 9.8],
+      [6.0, 9.7],
+      [8.0, 9.6],
+      [8.1, 7.6],
+      [8.2, 5.6],
+      [8.3, 3.6],
+      [8.4, 1.6],
+    ];
+    let mut scratch = ScratchBuffers::default();
+    let mut truncate = |weights: [f64; 2]| {
+      let (mut solutions, mut scores) =
+        ((0..scores.len()).collect::<Vec<_>>(), scores.clone());
+      crowding_distance_selection(
+        &mut solutions,
+        &mut scores,
+        5,
+        &mut scratch,
+        None,
+        Some(&weights),
+      );
+      solutions.sort();
+      solutions
+    };
+    assert_eq!(truncate([1.0, 0.0]), [0, 1, 2, 3, 8]);
+    assert_eq!(truncate([0.0, 1.0]), [0, 5, 6, 7, 8]);
+    assert_eq!(truncate([1.0, 0.1]), [0, 1, 2, 3, 8]);
+  }
+
+  #[test]
+  fn test_crowding_truncation() {
+    let scores = vec![
+      [0.0, 4.0],
+      [1.0, 3.0],
+      [3.0, 1.0],
+      [4.0, 0.0],
+      [2.5, 1.5],
+      [5.0, 5.0],
+    ];
+    let solutions = (0..scores.len()).collect::<Vec<_>>();
+    let (mut solutions, scores) =
+      CrowdingTruncation::default().truncate(solutions, scores, 4);
+    assert_eq!(scores.len(), 4);
+    // the dominated solution and the most crowded one are discarded
+    solutions.sort();
+    assert_eq!(solutions, [0, 1, 3, 4]);
+  }
+
+  #[test]
+  fn test_crowding_truncation_keeps_pairing() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut scratch = ScratchBuffers::default();
+    for target in [0, 7, 40, 100] {
+      let mut scores: Vec<Scores<2>> = (0..60)
+        .map(|_| [rng.gen_range(0..10) as f32, rng.gen_range(0..10) as f32])
+        .collect();
+      // solutions of heap-allocated copies of their scores
+      let mut solutions: Vec<Vec<f32>> =
+        scores.iter().map(|sc| sc.to_vec()).collect();
+      crowding_distance_selection(
+        &mut solutions,
+        &mut scores,
+        target,
+        &mut scratch,
+        None,
+        None,
+      );
+      assert_eq!(solutions.len(), target.min(60));
+      assert_eq!(solutions.len(), scores.len());
+      assert!(solutions.iter().zip(&scores).all(|(sol, sc)| sol == sc));
+    }
+  }
+
+  #[test]
+  fn test_crowding_truncation_of_undersized_population() {
+    let scores = vec![[0.0, 4.0], [5.0, 5.0], [4.0, 0.0]];
+    let solutions = (0..scores.len()).collect::<Vec<_>>();
+    let (solutions, truncated_scores) =
+      CrowdingTruncation::default().truncate(solutions, scores.clone(), 5);
+    assert_eq!(solutions, [0, 1, 2]);
+    assert_eq!(truncated_scores, scores);
+  }
+
+  #[test]
+  fn test_log_scaled_crowding_truncation() {
+    // scores of the first objective span orders of magnitude, so the last
+    // interval dwarfs the others unless they are log-scaled
+    let scores = vec![[1.0, 10.0], [10.0, 8.0], [100.0, 4.0], [1000.0, 1.0], [
+      10000.0, 0.0,
+    ]];
+    let mut scratch = ScratchBuffers::default();
+    let (mut solutions, mut truncated_scores) =
+      (vec![0, 1, 2, 3, 4], scores.clone());
+    crowding_distance_selection(
+      &mut solutions,
+      &mut truncated_scores,
+      4,
+      &mut scratch,
+      None,
+      None,
+    );
+    solutions.sort();
+    assert_eq!(solutions, [0, 2, 3, 4]);
+
+    let (mut solutions, mut truncated_scores) =
+      (vec![0, 1, 2, 3, 4], scores.clone());
+    let transforms = [ScoreTransform::Log1p, ScoreTransform::Identity];
+    let view = shadow_view(None, Some(&transforms)).unwrap();
+    select_by(
+      &mut solutions,
+      &mut truncated_scores,
+      view,
+      |paired, transformed| {
+        crowding_distance_selection(
+          paired,
+          transformed,
+          4,
+          &mut scratch,
+          None,
+          None,
+        )
+      },
+    );
+    assert_eq!(solutions.len(), 4);
+    assert!(!solutions.contains(&3));
+    // solutions keep their original scores
+    assert!(solutions
+      .iter()
+      .zip(&truncated_scores)
+      .all(|(&i, sc)| *sc == scores[i]));
+  }
+
+  #[test]
+  fn test_quantized_crowding_truncation() {
+    // the third solution is better than the second one by an objective only
+    // because of noise, and it's less crowded
+    let scores = vec![[0.0, 1.5], [1.0002, 1.0], [3.0, 0.9999999], [4.0, 0.0]];
+    let mut scratch = ScratchBuffers::default();
+    let (mut solutions, mut truncated_scores) =
+      (vec![0, 1, 2, 3], scores.clone());
+    crowding_distance_selection(
+      &mut solutions,
+      &mut truncated_scores,
+      3,
+      &mut scratch,
+      None,
+      None,
+    );
+    solutions.sort();
+    assert_eq!(solutions, [0, 2, 3]);
+
+    let (mut solutions, mut truncated_scores) =
+      (vec![0, 1, 2, 3], scores.clone());
+    let quantizer = ScoreQuantizer::new([1e-3, 1e-3]);
+    let view = shadow_view(Some(&quantizer), None).unwrap();
+    select_by(
+      &mut solutions,
+      &mut truncated_scores,
+      view,
+      |paired, quantized| {
+        crowding_distance_selection(
+          paired,
+          quantized,
+          3,
+          &mut scratch,
+          None,
+          None,
+        )
+      },
+    );
+    assert_eq!(solutions.len(), 3);
+    assert!(!solutions.contains(&2));
+    // solutions keep their original scores
+    assert!(solutions
+      .iter()
+      .zip(&truncated_scores)
+      .all(|(&i, sc)| *sc == scores[i]));
+  }
+
+  #[test]
+  fn test_few_offsprings() {
+    let solutions = Nsga2::builder()
+      .population((0..100).map(|i| i as f32).collect())
+      .tester(|x: &f32| [*x, x - 2.0])
+      .selector(FirstSelector(2))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(10))
+      .build()
+      .optimize();
+    assert_eq!(solutions.len(), 100);
+  }
+
+  #[test]
+  fn test_custom_truncation() {
+    let truncations = Arc::new(AtomicUsize::new(0));
+    let counter = truncations.clone();
+    let solutions = Nsga2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .tester(|x: &f32| [*x, x - 2.0])
+      .selector(AllSelector())
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .truncation(move |solutions, scores, target| {
+        counter.fetch_add(1, atomic::Ordering::Relaxed);
+        CrowdingTruncation::default().truncate(solutions, scores, target)
+      })
+      .terminator(GenerationTerminator(3))
+      .build()
+      .optimize();
+    assert_eq!(solutions.len(), 10);
+    assert_eq!(truncations.load(atomic::Ordering::Relaxed), 3);
+  }
+
+  #[test]
+  fn test_hypervolume_truncation() {
+    let solutions = Nsga2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .tester(|x: &f32| [*x, x - 2.0])
+      .selector(AllSelector())
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .truncation(HypervolumeTruncation {
+        reference: [20.0; 2],
+      })
+      .terminator(GenerationTerminator(3))
+      .build()
+      .optimize();
+    assert_eq!(solutions.len(), 10);
+    // the front lies between 0 and 2, and both of its ends survive
+    assert!(solutions.contains(&0.0));
+    assert!(solutions.contains(&2.0));
+  }
+
+  /// Returns population lengths of each generation of `Nsga2` with the
+  /// `schedule` that optimizes the `population` on a front of nondominated
+  /// solutions.
+  fn population_lengths(
+    population: Vec<f32>,
+    schedule: SizeSchedule,
+    generations: usize,
+  ) -> Vec<usize> {
+    let lengths = Arc::new(Mutex::new(Vec::new()));
+    let observed = lengths.clone();
+    Nsga2::builder()
+      .population(population)
+      .tester(|x: &f32| [*x, x - 30.0])
+      .selector(AllSelector())
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .population_schedule(schedule)
+      .observer(move |_: usize, solutions: &[f32], _: &[Scores<2>]| {
+        observed.lock().unwrap().push(solutions.len())
+      })
+      .terminator(GenerationTerminator(generations))
+      .build()
+      .optimize();
+    let lengths = lengths.lock().unwrap().clone();
+    lengths
+  }
+
+  #[test]
+  fn test_population_schedule() {
+    // all solutions are in the first front, which is truncated by crowding
+    let population = (0..=30).map(|i| i as f32).collect::<Vec<_>>();
+    let shrinking = SizeSchedule::Linear {
+      from: 20,
+      to: 5,
+      over_generations: 3,
+    };
+    assert_eq!(population_lengths(population.clone(), shrinking, 5), [
+      20, 15, 10, 5, 5
+    ]);
+    assert_eq!(population_lengths(population, 50.into(), 2), [50, 50]);
+
+    // a grown population keeps all parents and offsprings until there are
+    // enough of them
+    let growing = SizeSchedule::Linear {
+      from: 3,
+      to: 30,
+      over_generations: 3,
+    };
+    assert_eq!(population_lengths(vec![0.0, 15.0, 30.0], growing, 4), [
+      3, 6, 21, 30
+    ]);
+  }
+
+  #[test]
+  fn test_dedup() {
+    let distinct = |solutions: &[f32]| {
+      solutions
+        .iter()
+        .map(|x| x.to_bits())
+        .collect::<HashSet<_>>()
+        .len()
+    };
+    // offsprings that don't dominate each other are copies of a solution
+    // of the first front, which crowd the population without deduplication
+    let solutions = Nsga2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .tester(|x: &f32| [*x, x - 2.0])
+      .selector(AllSelector())
+      .recombinator(|_: &f32, _: &f32| 1.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(5))
+      .build()
+      .optimize();
+    assert!(distinct(&solutions) < 10);
+
+    struct DuplicateCounter(Arc<Mutex<Vec<(usize, usize)>>>);
+    impl Observer<f32, 2> for DuplicateCounter {
+      fn observe(&mut self, _: usize, _: &[f32], _: &[Scores<2>]) {}
+
+      fn duplicates_removed(&mut self, generation: usize, count: usize) {
+        self.0.lock().unwrap().push((generation, count));
+      }
+    }
+
+    let (sender, receiver) = mpsc::channel();
+    let counts = Arc::new(Mutex::new(Vec::new()));
+    let solutions = Nsga2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .tester(|x: &f32| [*x, x - 2.0])
+      .selector(AllSelector())
+      .recombinator(|_: &f32, _: &f32| 1.0)
+      .mutator(|_: &mut f32| {})
+      .dedup(Dedup::By(|x: &f32| x.to_bits()))
+      .terminator(GenerationTerminator(5))
+      .observer(DuplicateCounter(counts.clone()))
+      .event_sender(sender)
+      .build()
+      .optimize();
+    assert_eq!(solutions.len(), 10);
+    assert_eq!(distinct(&solutions), 10);
+    let removed = receiver
+      .try_iter()
+      .filter_map(|event| match event {
+        OptimizerEvent::DuplicatesRemoved { count, .. } => Some(count),
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(removed, [45; 5]);
+    assert_eq!(
+      *counts.lock().unwrap(),
+      (0..5).map(|g| (g, 45)).collect::<Vec<_>>()
+    );
+  }
+
+  #[test]
+  #[cfg(feature = "parallel")]
+  fn test_generation_time_budget() {
+    use crate::operator::ParBatch;
+
+    struct TimeoutCounter(Arc<Mutex<Vec<(usize, usize)>>>);
+    impl Observer<f32, 2> for TimeoutCounter {
+      fn observe(&mut self, _: usize, _: &[f32], _: &[Scores<2>]) {}
+
+      fn tests_timed_out(&mut self, generation: usize, count: usize) {
+        self.0.lock().unwrap().push((generation, count));
+      }
+    }
+
+    // the second offspring of each generation is pathological, so the
+    // budget runs out while it is tested and the other 8 are dropped
+    let test = |x: &f32| {
+      if *x == 101.0 {
+        std::thread::sleep(Duration::from_millis(400));
+      }
+      [*x, x - 2.0]
+    };
+    let (sender, receiver) = mpsc::channel();
+    let counts = Arc::new(Mutex::new(Vec::new()));
+    let pool = rayon::ThreadPoolBuilder::new()
+      .num_threads(1)
+      .build()
+      .unwrap();
+    let solutions = pool.install(|| {
+      Nsga2::builder()
+        .population((0..10).map(|i| i as f32).collect())
+        .tester(test.par_batch())
+        .selector(AllSelector())
+        .recombinator(|x: &f32| x + 100.0)
+        .mutator(|_: &mut f32| {})
+        .generation_time_budget(Duration::from_millis(200))
+        .terminator(GenerationTerminator(2))
+        .observer(TimeoutCounter(counts.clone()))
+        .event_sender(sender)
+        .build()
+        .optimize()
+    });
+    assert_eq!(solutions.len(), 10);
+    assert!(solutions.iter().all(|&x| x < 100.0));
+    let dropped = receiver
+      .try_iter()
+      .filter_map(|event| match event {
+        OptimizerEvent::TestsTimedOut { count, .. } => Some(count),
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+    assert_eq!(dropped, [8, 8]);
+    assert_eq!(*counts.lock().unwrap(), [(0, 8), (1, 8)]);
+  }
+
+  #[test]
+  fn test_empty_selection() {
+    let (sender, receiver) = mpsc::channel();
+    let result = Nsga2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .tester(|x: &f32| [*x, x - 2.0])
+      .selector(FirstSelector(0))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(3))
+      .event_sender(sender)
+      .build()
+      .try_optimize();
+    assert_eq!(
+      result,
+      Err(OptimizationError::SelectionEmpty { generation: 0 })
+    );
+    assert!(matches!(
+      receiver.try_iter().last(),
+      Some(OptimizerEvent::Error { .. })
+    ));
+
+    let solutions = Nsga2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .tester(|x: &f32| [*x, x - 2.0])
+      .selector(FirstSelector(0))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(3))
+      .empty_selection(EmptySelection::SelectAll)
+      .build()
+      .optimize();
+    assert_eq!(solutions.len(), 10);
+    // offsprings of all solutions have survived
+    assert!(solutions.iter().any(|x| x.fract() != 0.0));
+  }
+
+  #[test]
+  fn test_selection_contract() {
+    use crate::selection::{Expect, SelectionContract};
+
+    let result = Nsga2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .tester(|x: &f32| [*x, x - 2.0])
+      .selector(Expect::exactly(FirstSelector(4), 5))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(3))
+      .build()
+      .try_optimize();
+    assert_eq!(
+      result,
+      Err(OptimizationError::SelectionContractViolated {
+        generation: 0,
+        expected: SelectionContract::Exactly(5),
+        actual: 4,
+      })
+    );
+
+    // the contract is checked before an empty selection is handled
+    let result = Nsga2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .tester(|x: &f32| [*x, x - 2.0])
+      .selector(Expect::at_least(FirstSelector(0), 1))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(3))
+      .empty_selection(EmptySelection::SelectAll)
+      .build()
+      .try_optimize();
+    assert!(matches!(
+      result,
+      Err(OptimizationError::SelectionContractViolated { actual: 0, .. })
+    ));
+
+    let solutions = Nsga2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .tester(|x: &f32| [*x, x - 2.0])
+      .selector(Expect::at_most(FirstSelector(4), 4))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(3))
+      .build()
+      .optimize();
+    assert_eq!(solutions.len(), 10);
+  }
+
+  #[test]
+  fn test_replacement() {
+    let optimize = |selector: FirstSelector, replacement| {
+      Nsga2::builder()
+        .population((0..10).map(|i| i as f32).collect())
+        .tester(|x: &f32| [*x, x - 2.0])
+        .selector(selector)
+        .recombinator(|x: &f32| x + 100.0)
+        .mutator(|_: &mut f32| {})
+        .terminator(GenerationTerminator(2))
+        .replacement(replacement)
+        .build()
+        .try_optimize()
+    };
+
+    // worse offsprings never replace parents
+    let solutions = optimize(FirstSelector(10), Replacement::MuPlusLambda);
+    assert_eq!(solutions, Ok((0..10).map(|i| i as f32).collect()));
+
+    // but do when parents are discarded
+    let solutions =
+      optimize(FirstSelector(10), Replacement::MuCommaLambda).unwrap();
+    assert_eq!(solutions.len(), 10);
+    assert!(solutions.iter().all(|&x| x >= 200.0));
+
+    assert_eq!(
+      optimize(FirstSelector(5), Replacement::MuCommaLambda),
+      Err(OptimizationError::TooFewOffsprings {
+        generation: 0,
+        offsprings: 5,
+        required: 10,
+      })
+    );
+  }
+
+  #[test]
+  fn test_optimize_n() {
+    let optimize = |generations, terminate_at| {
+      let calls = Arc::new(AtomicUsize::new(0));
+      let terminator = {
+        let calls = calls.clone();
+        move |_: &[f32], _: &[Scores<2>]| {
+          calls.fetch_add(1, atomic::Ordering::Relaxed) == terminate_at
+        }
+      };
+      let (sender, receiver) = mpsc::channel();
+      Nsga2::builder()
+        .population(vec![1.0, 2.0, 3.0])
+        .tester(|x: &f32| [*x, 3.0 - x])
+        .selector(AllSelector())
+        .recombinator(|x: &f32| *x)
+        .mutator(|_: &mut f32| {})
+        .terminator(terminator)
+        .event_sender(sender)
+        .build()
+        .optimize_n(generations);
+      let reason = receiver.try_iter().find_map(|event| match event {
+        OptimizerEvent::Terminated { reason } => Some(reason),
+        _ => None,
+      });
+      (calls.load(atomic::Ordering::Relaxed), reason)
+    };
+
+    // the terminator is called in each generation, and once more before the
+    // limit stops optimization
+    assert_eq!(
+      optimize(3, usize::MAX),
+      (4, Some(TerminationReason::GenerationLimit))
+    );
+    assert_eq!(
+      optimize(0, usize::MAX),
+      (1, Some(TerminationReason::GenerationLimit))
+    );
+    // the terminator may terminate optimization earlier
+    assert_eq!(optimize(3, 1), (2, Some(TerminationReason::Terminator)));
+    // and takes precedence over the limit in the same generation
+    assert_eq!(optimize(3, 3), (4, Some(TerminationReason::Terminator)));
+  }
+
+  #[test]
+  fn test_mutate_parents() {
+    let tests = AtomicUsize::new(0);
+    let solutions = Nsga2::builder()
+      .population(vec![1.0, 2.0, 3.0])
+      .tester(|x: &f32| {
+        tests.fetch_add(1, atomic::Ordering::Relaxed);
+        [*x, 10.0 - x]
+      })
+      .selector(AllSelector())
+      .recombinator(|x: &f32| *x)
+      .mutator(|x: &mut f32| {
+        if *x > 2.0 {
+          *x -= 1.0
+        }
+      })
+      .mutate_parents(|x: &f32| x.to_bits())
+      .terminator(GenerationTerminator(1))
+      .build()
+      .optimize_with_scores();
+    // 3 initial solutions, 3 offspring and the only changed survivor
+    assert_eq!(tests.load(atomic::Ordering::Relaxed), 7);
+    assert!(solutions
+      .iter()
+      .all(|(x, sc)| *x <= 2.0 && *sc == [*x, 10.0 - x]));
+  }
+
+  #[test]
+  fn test_population_with_scores() {
+    let seeds = Nsga2::builder()
+      .population(vec![1.0, 2.0, 3.0])
+      .tester(|x: &f32| [*x, 3.0 - x])
+      .selector(AllSelector())
+      .recombinator(|x: &f32| *x)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(2))
+      .build()
+      .optimize_with_scores();
+    assert_eq!(seeds.len(), 3);
+    assert!(seeds.iter().all(|(x, sc)| *sc == [*x, 3.0 - x]));
+
+    let tests = AtomicUsize::new(0);
+    let build = |population: Vec<f32>| {
+      let builder = Nsga2::builder()
+        .population_with_scores(seeds.clone())
+        .tester(|x: &f32| {
+          tests.fetch_add(1, atomic::Ordering::Relaxed);
+          [*x, 3.0 - x]
+        })
+        .selector(AllSelector())
+        .recombinator(|x: &f32| *x)
+        .mutator(|_: &mut f32| {})
+        .terminator(GenerationTerminator(0));
+      if population.is_empty() {
+        builder.build()
+      } else {
+        builder.population(population).build()
+      }
+    };
+
+    let solutions = build(vec![]).optimize_with_scores();
+    assert_eq!(tests.load(atomic::Ordering::Relaxed), 0);
+    assert_eq!(solutions, seeds);
+
+    // only the solution without scores is tested
+    let solutions = build(vec![0.5]).optimize_with_scores();
+    assert_eq!(tests.load(atomic::Ordering::Relaxed), 1);
+    assert_eq!(solutions.len(), 4);
+    assert!(solutions.contains(&(0.5, [0.5, 2.5])));
+  }
+
+  #[test]
+  fn test_optimize_with_outcome() {
+    let build = |terminator| {
+      Nsga2::builder()
+        .population(vec![1.0, 2.0, 3.0])
+        .tester(|x: &f32| [*x, 3.0 - x])
+        .selector(AllSelector())
+        .recombinator(|x: &f32| *x)
+        .mutator(|_: &mut f32| {})
+        .terminator(terminator)
+        .build()
+    };
+
+    let outcome = build(BoxedTerminator::new(GenerationTerminator(3)))
+      .optimize_with_outcome();
+    assert_eq!(outcome.solutions.len(), 3);
+    assert_eq!(outcome.termination, TerminationReason::Terminator);
+    assert_eq!(outcome.reason, "all generations have passed");
+    assert_eq!(outcome.generations_run, 3);
+
+    let mut generations = 0;
+    let outcome =
+      build(BoxedTerminator::new(move |_: &[f32], _: &[Scores<2>]| {
+        generations += 1;
+        generations > 2
+      }))
+      .optimize_with_outcome();
+    assert_eq!(outcome.reason, "the terminator has met its condition");
+    assert_eq!(outcome.generations_run, 2);
+
+    let mut optimizer = build(BoxedTerminator::new(GenerationTerminator(10)));
+    optimizer.generation_limit = Some(4);
+    let outcome = optimizer.optimize_with_outcome();
+    assert_eq!(outcome.termination, TerminationReason::GenerationLimit);
+    assert_eq!(
+      outcome.reason,
+      "the limit of 4 generations has been reached"
+    );
+    assert_eq!(outcome.generations_run, 4);
+
+    let mut optimizer = build(BoxedTerminator::new(GenerationTerminator(10)));
+    optimizer.cancel_flag().raise();
+    let outcome = optimizer.optimize_with_outcome();
+    assert_eq!(outcome.termination, TerminationReason::Cancelled);
+    assert_eq!(outcome.generations_run, 0);
+  }
+
+  #[test]
+  fn test_from_operators() {
+    let population: Vec<f32> = vec![-1.0, 1.0, 2.0, 3.0];
+    let tester = |x: &f32| [*x, 3.0 - x];
+    let recombinator = |x: &f32, y: &f32| x + y;
+    let repairer = |x: &mut f32| *x = x.clamp(0.0, 3.0);
+    let operators = OperatorSet::new(
+      tester,
+      FirstSelector(2),
+      recombinator,
+      |_: &mut f32| {},
+      GenerationTerminator(3),
+    )
+    .with_repairer(repairer);
+    let built = Nsga2::builder()
+      .population(population.clone())
+      .tester(tester)
+      .selector(FirstSelector(2))
+      .recombinator(recombinator)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(3))
+      .repairer(repairer)
+      .build();
+    assert_eq!(
+      Nsga2::from_operators(population, operators).optimize(),
+      built.optimize()
+    );
+  }
+
+  #[test]
+  fn test_offspring_budget() {
+    let optimizer = |budget| {
+      let offsprings = Arc::new(Mutex::new(Vec::new()));
+      let counted = Arc::clone(&offsprings);
+      let result = Nsga2::builder()
+        .population((0..30).map(|i| i as f32).collect())
+        .tester(|x: &f32| [*x, -x])
+        .selector(AllSelector())
+        .recombinator(|a: &f32, b: &f32, c: &f32| (a + b + c) / 3.0)
+        .mutator(|_: &mut f32| {})
+        .terminator(GenerationTerminator(2))
+        .max_offspring_per_generation(100)
+        .offspring_budget(budget)
+        .feedback(move |_, created, _| counted.lock().unwrap().push(created))
+        .build()
+        .try_optimize();
+      (
+        result,
+        Arc::into_inner(offsprings).unwrap().into_inner().unwrap(),
+      )
+    };
+    assert_eq!(
+      optimizer(OffspringBudget::Fail),
+      (
+        Err(OptimizationError::OffspringBudgetExceeded {
+          generation: 0,
+          projected: 4060,
+          limit: 100,
+        }),
+        vec![]
+      )
+    );
+    let (solutions, offsprings) =
+      optimizer(OffspringBudget::Subsample { seed: 7 });
+    assert_eq!(offsprings, [100, 100]);
+    assert_eq!(
+      solutions,
+      optimizer(OffspringBudget::Subsample { seed: 7 }).0
+    );
+  }
+
+  #[test]
+  fn test_tiny_populations() {
+    use crate::selection::{
+      BoxedSelector,
+      RandomSelector,
+      ReferencePointSelector,
+      RouletteSelector,
+      TournamentSelectorWithReplacement,
+      TournamentSelectorWithoutReplacement,
+    };
+
+    let selectors = || -> Vec<BoxedSelector<f32, 2>> {
+      vec![
+        BoxedSelector::new(AllSelector()),
+        BoxedSelector::new(FirstSelector(0)),
+        BoxedSelector::new(FirstSelector(1)),
+        BoxedSelector::new(RandomSelector(2)),
+        BoxedSelector::new(RouletteSelector(2)),
+        BoxedSelector::new(TournamentSelectorWithoutReplacement(2, 2)),
+        BoxedSelector::new(TournamentSelectorWithReplacement(2, 2)),
+        BoxedSelector::new(ReferencePointSelector(2, [0.0; 2], [1.0; 2])),
+      ]
+    };
+    for size in 1..=3 {
+      for single_parent in [false, true] {
+        for (selector_idx, selector) in selectors().into_iter().enumerate() {
+          let population = (0..size).map(|i| i as f32).collect();
+          let recombinator = if single_parent {
+            BoxedRecombinator::new(|x: &f32| x + 0.5)
+          } else {
+            BoxedRecombinator::new(|x: &f32, y: &f32| (x + y) / 2.0)
+          };
+          let solutions = Nsga2::builder()
+            .population(population)
+            .tester(|x: &f32| [x.powf(2.0), (x - 2.0).powf(2.0)])
+            .selector(selector)
+            .recombinator(recombinator)
+            .mutator(|x: &mut f32| *x += 0.25)
+            .terminator(GenerationTerminator(10))
+            .empty_selection(EmptySelection::SelectAll)
+            .build()
+            .optimize();
+          assert!(
+            (1..=size).contains(&solutions.len()),
+            "population of {size}, selector #{selector_idx}, \
+             single parent: {single_parent}"
+          );
+        }
+      }
+    }
+  }
+
+  #[test]
+  fn test_feedback() {
+    let feedback = |replacement| {
+      let records = Arc::new(Mutex::new(Vec::new()));
+      let recorded = Arc::clone(&records);
+      Nsga2::builder()
+        .population(vec![0.0, 1.0])
+        .tester(|x: &f32| [*x, *x])
+        .selector(AllSelector())
+        .recombinator(|x: &f32| x + 10.0)
+        .mutator(|_: &mut f32| {})
+        .terminator(GenerationTerminator(3))
+        .feedback(move |generation, offsprings, survived| {
+          recorded
+            .lock()
+            .unwrap()
+            .push((generation, offsprings, survived))
+        })
+        .replacement(replacement)
+        .build()
+        .optimize();
+      Arc::into_inner(records).unwrap().into_inner().unwrap()
+    };
+    assert_eq!(feedback(Replacement::MuPlusLambda), [
+      (0, 2, 0),
+      (1, 2, 0),
+      (2, 2, 0)
+    ]);
+    assert_eq!(feedback(Replacement::MuCommaLambda), [
+      (0, 2, 2),
+      (1, 2, 2),
+      (2, 2, 2)
+    ]);
+  }
+
+  #[test]
+  fn test_feedback_on_copied_parents() {
+    let records = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&records);
+    // offsprings are copies of their parents flagged as offsprings, which the
+    // tester ignores
+    let solutions = Nsga2::builder()
+      .population(vec![(0.0, false)])
+      .tester(|&(x, _): &(f32, bool)| [x, x])
+      .selector(AllSelector())
+      .recombinator(|&(x, _): &(f32, bool)| (x, true))
+      .mutator(|_: &mut (f32, bool)| {})
+      .terminator(GenerationTerminator(1))
+      .feedback(move |generation, offsprings, survived| {
+        recorded
+          .lock()
+          .unwrap()
+          .push((generation, offsprings, survived))
+      })
+      .build()
+      .optimize();
+    assert_eq!(solutions, [(0.0, false)]);
+    assert_eq!(*records.lock().unwrap(), [(0, 1, 0)]);
+  }
+
+  #[test]
+  fn test_incremental_sorting() {
+    let optimize = |full_sort_interval: Option<usize>| {
+      let mut builder = Nsga2::builder()
+        .population((0..40).map(|i| i as f32 * 0.37 - 5.0).collect())
+        // rounded scores make plenty of ties and equal scores
+        .tester(|x: &f32| [x.powi(2).round(), (x - 2.0).powi(2).round()])
+        .selector(FirstSelector(8))
+        .recombinator(|a: &f32, b: &f32| (a * 0.7 + b * 0.3).sin() * 4.0)
+        .mutator(|x: &mut f32| *x += 0.01)
+        .terminator(GenerationTerminator(30));
+      if let Some(interval) = full_sort_interval {
+        builder = builder.incremental_sorting(interval);
+      }
+      builder.build().optimize_with_scores()
+    };
+    let full = optimize(None);
+    assert_eq!(full.len(), 40);
+    assert_eq!(optimize(Some(1)), full);
+    assert_eq!(optimize(Some(4)), full);
+    assert_eq!(optimize(Some(usize::MAX)), full);
+  }
+
+  #[test]
+  fn test_scored_recombinator() {
+    use crate::recombination::ComplementaryPairing;
+
+    let tester = |x: &f32| [x.powi(2), (x - 2.0).powi(2)];
+    Nsga2::builder()
+      .population(vec![-1.0, 0.0, 1.0, 2.0, 3.0])
+      .tester(tester)
+      .selector(AllSelector())
+      .recombinator(move |parents: Vec<(&f32, &Scores<2>)>| {
+        assert!(parents.iter().all(|(p, scores)| tester(p) == **scores));
+        parents.into_iter().map(|(p, _)| p + 0.5).collect()
+      })
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(3))
+      .build()
+      .optimize();
+
+    // the opposite specialists are paired first
+    let pairs = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&pairs);
+    Nsga2::builder()
+      .population(vec![0.0, 1.0, 2.0])
+      .tester(tester)
+      .selector(AllSelector())
+      .recombinator(ComplementaryPairing::new(
+        [1.0, 1.0],
+        move |a: &f32, b: &f32| {
+          recorded.lock().unwrap().push((*a, *b));
+          a + b
+        },
+      ))
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(1))
+      .build()
+      .optimize();
+    assert_eq!(*pairs.lock().unwrap(), [(0.0, 2.0)]);
+  }
+
+  #[test]
+  fn test_speciation() {
+    let crossed = Arc::new(AtomicUsize::new(0));
+    let counted = Arc::clone(&crossed);
+    let created = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&created);
+    // both signs are equally good, and parents of different signs recombine
+    // into a poor offspring close to `0`
+    let population: Vec<f32> =
+      (1..=10).flat_map(|x| [x as f32, -x as f32]).collect();
+    let solutions = Nsga2::builder()
+      .population(population)
+      .tester(|x: &f32| [(x.abs() - 1.0).powi(2), (x.abs() - 3.0).powi(2)])
+      .selector(AllSelector())
+      .recombinator(move |a: &f32, b: &f32| {
+        if a.signum() != b.signum() {
+          counted.fetch_add(1, atomic::Ordering::Relaxed);
+        }
+        (a + b) / 2.0
+      })
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(5))
+      .speciation(|solutions: &[f32]| {
+        let (negative, rest) =
+          (0..solutions.len()).partition(|&i| solutions[i] < 0.0);
+        vec![negative, rest]
+      })
+      .feedback(move |_, offsprings, _| {
+        recorded.lock().unwrap().push(offsprings)
+      })
+      .build()
+      .optimize();
+    assert_eq!(crossed.load(atomic::Ordering::Relaxed), 0);
+    // species share the population size of offsprings
+    assert_eq!(*created.lock().unwrap(), [20; 5]);
+    assert!(solutions.iter().any(|x| *x < 0.0));
+    assert!(solutions.iter().any(|x| *x > 0.0));
+  }
+
+  #[test]
+  fn test_speciation_adaptive_recombinator() {
+    use crate::recombination::{
+      AdaptivePolicy,
+      AdaptiveRecombinator,
+      BoxedRecombinator,
+    };
+
+    // offsprings of either recombinator are better than their parents, so
+    // nearly all of them survive
+    let recombinator = AdaptiveRecombinator::new(
+      AdaptivePolicy::Ucb { exploration: 0.5 },
+      vec![
+        BoxedRecombinator::new(|a: &f32| a * 1e-3),
+        BoxedRecombinator::new(|a: &f32| a * 2e-3),
+      ],
+    );
+    let population: Vec<f32> =
+      (1..=10).flat_map(|x| [x as f32, -x as f32]).collect();
+    Nsga2::builder()
+      .population(population)
+      .tester(|x: &f32| [x.abs(), x.abs()])
+      .selector(AllSelector())
+      .recombinator(recombinator.clone())
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(5))
+      .speciation(|solutions: &[f32]| {
+        let (negative, rest) =
+          (0..solutions.len()).partition(|&i| solutions[i] < 0.0);
+        vec![negative, rest]
+      })
+      .feedback(recombinator.clone())
+      .build()
+      .optimize();
+    // a recombinator is picked for each of two species each generation
+    assert_eq!(recombinator.picks().iter().sum::<usize>(), 10);
+    // each pick is rewarded, not just the last one of a generation
+    let rewards = recombinator.mean_rewards();
+    assert!(rewards.iter().all(|&reward| reward > 0.9), "{rewards:?}");
+  }
+
+  #[test]
+  fn test_acceptance() {
+    use crate::truncation::{AnnealedAcceptance, ParetoAcceptance};
+
+    /// Returns the number of 20 runs of a deceptive problem that have escaped
+    /// its local optimum at `0` and found the global one at `5`. Offsprings
+    /// get worse on the way there, and much worse after it. Each run gets the
+    /// acceptance of its index.
+    fn escapes<A: Acceptance<2> + Send + 'static>(
+      acceptance: impl Fn(u64) -> A,
+    ) -> usize {
+      let test = |x: &f32| match *x as u32 {
+        0..=4 => [x + 1.0, x + 1.0],
+        5 => [0.0, 0.0],
+        _ => [1e6, 1e6],
+      };
+      let optimize = |run| {
+        Nsga2::builder()
+          .population(vec![0.0])
+          .tester(test)
+          .selector(AllSelector())
+          .recombinator(|x: &f32| x + 1.0)
+          .mutator(|_: &mut f32| {})
+          .terminator(GenerationTerminator(10))
+          .acceptance(acceptance(run))
+          .build()
+          .optimize()
+      };
+      (0..20).filter(|&run| optimize(run) == [5.0]).count()
+    }
+
+    assert_eq!(escapes(|_| ParetoAcceptance), 0);
+    // dominated offsprings are accepted almost surely in first generations,
+    // and almost never after the global optimum is found
+    let annealed = |seed| AnnealedAcceptance {
+      t0: 1000.0,
+      cooling: 0.5,
+      seed,
+    };
+    let escaped = escapes(annealed);
+    assert!(escaped >= 10);
+    assert_eq!(escapes(annealed), escaped);
+  }
+
+  #[test]
+  fn test_objective_labels() {
+    use crate::observer::HistoryRecorder;
+
+    let labels = ObjectiveLabels(["cost", "weight"]);
+    let recorder = HistoryRecorder::new();
+    let (sender, receiver) = mpsc::channel();
+    Nsga2::builder()
+      .population(vec![1.0, 2.0, 3.0])
+      .tester(|x: &f32| [*x, 3.0 - x])
+      .selector(AllSelector())
+      .recombinator(|x: &f32| *x)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(2))
+      .objective_labels(labels)
+      .observer(recorder.clone())
+      .event_sender(sender)
+      .build()
+      .optimize();
+    assert_eq!(recorder.labels(), Some(labels));
+    let mut csv = Vec::new();
+    recorder.write_csv(&mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    assert!(csv.starts_with("generation,population_size,min_cost,min_weight"));
+    let labeled_events = receiver
+      .try_iter()
+      .filter(|event| {
+        matches!(
+          event,
+          OptimizerEvent::GenerationCompleted {
+            objective_labels: Some(l),
+            ..
+          } if *l == labels
+        )
+      })
+      .count();
+    assert_eq!(labeled_events, 2);
+  }
+
+  #[test]
+  fn test_profile() {
+    use std::time::{Duration, Instant};
+
+    let (sender, receiver) = mpsc::channel();
+    let start = Instant::now();
+    Nsga2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .tester(|xs: &[f32]| {
+        std::thread::sleep(Duration::from_millis(20));
+        xs.iter().map(|x| [*x, x - 2.0]).collect()
+      })
+      .selector(FirstSelector(4))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(4))
+      .event_sender(sender)
+      .profile(true)
+      .build()
+      .optimize();
+    let elapsed = start.elapsed();
+
+    let events: Vec<_> = receiver.try_iter().collect();
+    let Some(OptimizerEvent::Profiled { timings }) = events.iter().rev().nth(1)
+    else {
+      panic!("no timings before termination");
+    };
+    assert_eq!(timings.test.calls, 5);
+    assert_eq!(timings.terminate.calls, 5);
+    assert_eq!(timings.select.calls, 4);
+    assert_eq!(timings.truncate.calls, 4);
+    assert_eq!(timings.repair.calls, 0);
+    // the slow tester takes almost all the time
+    assert!(timings.test.total >= Duration::from_millis(100));
+    assert!(timings.total() <= elapsed);
+    assert!(timings.total() >= elapsed.mul_f32(0.8));
+  }
+
+  #[test]
+  fn test_score_count_mismatch() {
+    // offsprings are given only one score
+    let tester = |xs: &[f32]| match xs.len() {
+      10 => xs.iter().map(|x| [*x, x - 2.0]).collect(),
+      _ => vec![[0.0, 0.0]],
+    };
+    let result = Nsga2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .tester(tester)
+      .selector(FirstSelector(3))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(3))
+      .build()
+      .try_optimize();
+    assert_eq!(
+      result,
+      Err(OptimizationError::ScoreCountMismatch {
+        operator: OperatorKind::Tester,
+        generation: 0,
+        actual: 1,
+        expected: 3,
+      })
+    );
+
+    let result = Nsga2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .tester(|x: &f32| [*x, x - 2.0])
+      .selector(FirstSelector(3))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .constraint(|_: &[f32]| vec![[0.0]])
+      .terminator(GenerationTerminator(3))
+      .build()
+      .try_optimize();
+    assert_eq!(
+      result,
+      Err(OptimizationError::ScoreCountMismatch {
+        operator: OperatorKind::Constrainer,
+        generation: 0,
+        actual: 1,
+        expected: 10,
+      })
+    );
+  }
+
+  #[test]
+  fn test_pause_every() {
+    struct RecordingTruncation(Arc<Mutex<Vec<Scores<2>>>>);
+    impl Truncation<f32, 2> for RecordingTruncation {
+      fn truncate(
+        &self,
+        solutions: Vec<f32>,
+        scores: Vec<Scores<2>>,
+        target: usize,
+      ) -> (Vec<f32>, Vec<Scores<2>>) {
+        CrowdingTruncation::default().truncate(solutions, scores, target)
+      }
+
+      fn adjust_reference(&mut self, reference: Scores<2>) {
+        self.0.lock().unwrap().push(reference);
+      }
+    }
+
+    let references = Arc::new(Mutex::new(Vec::new()));
+    let mut pauses = Vec::new();
+    let (sender, receiver) = mpsc::channel();
+    let solutions = Nsga2::builder()
+      .population((5..15).map(|i| i as f32).collect())
+      .tester(|x: &f32| [*x, x - 2.0])
+      .selector(FirstSelector(4))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(10))
+      .truncation(RecordingTruncation(references.clone()))
+      .event_sender(sender)
+      .pause_every(2, move |generation, _: &[&f32], _: &[Scores<2>]| {
+        pauses.push(generation);
+        match generation {
+          1 => Directive::InjectSolutions(vec![1.0, 20.0]),
+          3 => Directive::AdjustReference([1.0, 1.0]),
+          5 => Directive::Stop,
+          _ => panic!("optimizer didn't stop at generation {generation}"),
+        }
+      })
+      .build()
+      .optimize();
+
+    // the injected solution dominates the population and survives
+    assert!(solutions.contains(&1.0));
+    assert!(!solutions.contains(&20.0));
+    assert_eq!(*references.lock().unwrap(), [[1.0, 1.0]]);
+    let events: Vec<_> = receiver.try_iter().collect();
+    let generations = events
+      .iter()
+      .filter(|e| matches!(e, OptimizerEvent::GenerationCompleted { .. }))
+      .count();
+    assert_eq!(generations, 6);
+    assert_eq!(
+      events.last(),
+      Some(&OptimizerEvent::Terminated {
+        reason: TerminationReason::Paused
+      })
+    );
+  }
+
+  #[test]
+  fn test_cataclysmic_restart() {
+    let generated = Arc::new(Mutex::new(Vec::new()));
+    let mut solutions = Nsga2::builder()
+      .population(vec![10.0, 0.0, 11.0, 1.0, 2.0, 12.0, 3.0, 4.0])
+      .tester(|x: &f32| [*x, 4.0 - x])
+      .selector(FirstSelector(4))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      // offsprings are dominated by the whole population
+      .mutator(|x: &mut f32| *x += 50.0)
+      .terminator(GenerationTerminator(1))
+      .pause_every(
+        1,
+        CataclysmicRestart::every(1, 8, {
+          let generated = generated.clone();
+          move |n| {
+            generated.lock().unwrap().push(n);
+            (0..n).map(|i| 100.0 + i as f32).collect()
+          }
+        }),
+      )
+      .build()
+      .optimize();
+
+    assert_eq!(*generated.lock().unwrap(), [3]);
+    // the front is preserved, and the rest of the population is replaced
+    solutions.sort_by(f32::total_cmp);
+    assert_eq!(solutions, [0.0, 1.0, 2.0, 3.0, 4.0, 100.0, 101.0, 102.0]);
+  }
+
+  #[test]
+  fn test_inject() {
+    let mut optimizer = Nsga2::builder()
+      .population((5..15).map(|i| i as f32).collect())
+      .tester(|xs: &[f32]| match xs.len() {
+        // pretends to fail on batches of two
+        2 => Vec::new(),
+        _ => xs.iter().map(|x| [*x, x - 2.0]).collect(),
+      })
+      .selector(FirstSelector(4))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(1))
+      .build();
+    assert_eq!(
+      optimizer.inject(vec![0.0, 0.5]),
+      Err(OptimizationError::ScoreCountMismatch {
+        operator: OperatorKind::Tester,
+        generation: 0,
+        actual: 0,
+        expected: 2,
+      })
+    );
+    // more solutions than the population size, with duplicates
+    optimizer.inject(vec![1.0; 3]).unwrap();
+    optimizer
+      .inject((20..50).map(|i| i as f32).collect())
+      .unwrap();
+    let solutions = optimizer.optimize();
+    assert_eq!(solutions.len(), 10);
+    assert!(solutions.contains(&1.0));
+    assert!(!solutions.contains(&0.5));
+    assert!(solutions.iter().all(|&x| x < 20.0));
+  }
+
+  #[test]
+  fn test_solution_metas() {
+    let scores = [[0.0, 2.0], [3.0, 3.0], [1.0, 1.0], [2.0, 0.0], [4.0, 4.0]];
+    let metas = solution_metas(&scores, None);
+    assert_eq!(metas.iter().map(|meta| meta.front).collect::<Vec<_>>(), [
+      0, 1, 0, 0, 2
+    ]);
+    assert_eq!(metas[0].crowding_distance, f64::MAX);
+    assert_eq!(metas[2].crowding_distance, 2.0);
+    assert_eq!(metas[4].crowding_distance, f64::MAX);
+  }
+
+  #[test]
+  fn test_optimize_detailed() {
+    let detailed = Nsga2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .tester(|x: &f32| [x.abs(), (x - 2.0).abs()])
+      .selector(FirstSelector(4))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|x: &mut f32| *x += 0.5)
+      .terminator(GenerationTerminator(3))
+      .build()
+      .optimize_detailed();
+    assert_eq!(detailed.len(), 10);
+    let max_front = detailed.iter().map(|(_, meta)| meta.front).max().unwrap();
+    for front in 0..=max_front {
+      assert!(detailed.iter().any(|(_, meta)| meta.front == front));
+    }
+    // solutions between 0 and 2 are exactly the nondominated ones
+    for (x, meta) in &detailed {
+      assert_eq!(meta.front == 0, (0.0..=2.0).contains(x), "{x}: {meta:?}");
+    }
+  }
+
+  #[test]
+  fn test_dry_run_offspring_budget() {
+    let dry_run = |budget| {
+      Nsga2::builder()
+        .population((0..10).map(|i| i as f32).collect())
+        .tester(|x: &f32| [*x, x - 2.0])
+        .selector(FirstSelector(8))
+        .recombinator(|x: &f32, y: &f32, z: &f32| (x + y + z) / 3.0)
+        .mutator(|_: &mut f32| {})
+        .terminator(GenerationTerminator(1))
+        .max_offspring_per_generation(10)
+        .offspring_budget(budget)
+        .build()
+        .dry_run(8)
+    };
+    assert_eq!(
+      dry_run(OffspringBudget::Fail),
+      Err(OptimizationError::OffspringBudgetExceeded {
+        generation: 0,
+        projected: 56,
+        limit: 10,
+      })
+    );
+    let report = dry_run(OffspringBudget::Subsample { seed: 0 }).unwrap();
+    assert_eq!(report.offsprings, 10);
+  }
+
+  #[test]
+  fn test_dry_run() {
+    let optimizer = Nsga2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .tester(|x: &f32| [*x, x - 2.0])
+      .selector(FirstSelector(4))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|x: &mut f32| *x = x.sqrt() - 2.0)
+      .terminator(GenerationTerminator(1))
+      .build();
+    let report = optimizer.dry_run(3).unwrap();
+    assert_eq!(
+      (report.sampled, report.selected, report.offsprings),
+      (3, 3, 3)
+    );
+    assert!(report.is_clean());
+    // the sample is never larger than the population
+    let report = optimizer.dry_run(100).unwrap();
+    assert_eq!(
+      (report.sampled, report.selected, report.offsprings),
+      (10, 4, 6)
+    );
+    assert!(report.is_clean());
+    // the optimizer isn't consumed
+    assert_eq!(optimizer.optimize().len(), 10);
+
+    let report = Nsga2::builder()
+      .population(vec![-1.0, 1.0])
+      .tester(|x: &f32| [x.sqrt()])
+      .selector(FirstSelector(1))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|x: &mut f32| *x -= 2.0)
+      .terminator(GenerationTerminator(1))
+      .build()
+      .dry_run(2)
+      .unwrap();
+    assert_eq!(report.anomalies, [
+      DryRunAnomaly::NanScores { solutions: 1 },
+      DryRunAnomaly::TooFewParents {
+        selected: 1,
+        required: 2
+      },
+      DryRunAnomaly::NoOffsprings,
+    ]);
+
+    let optimizer = Nsga2::builder()
+      .population(vec![1.0, 2.0])
+      .tester(|x: &f32| [x.sqrt()])
+      .selector(AllSelector())
+      .recombinator(|x: &f32| -x)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(1))
+      .build();
+    assert_eq!(optimizer.dry_run(2).unwrap().anomalies, [
+      DryRunAnomaly::OffspringNanScores { offsprings: 2 }
+    ]);
+    assert_eq!(
+      Nsga2::builder()
+        .population(vec![1.0, 2.0])
+        .tester(|xs: &[f32]| vec![[0.0]; xs.len() + 1])
+        .selector(AllSelector())
+        .recombinator(|x: &f32| *x)
+        .mutator(|_: &mut f32| {})
+        .terminator(GenerationTerminator(1))
+        .build()
+        .dry_run(2),
+      Err(OptimizationError::ScoreCountMismatch {
+        operator: OperatorKind::Tester,
+        generation: 0,
+        actual: 3,
+        expected: 2,
+      })
+    );
+  }
+
+  /// Tests solutions, panicking on solutions greater than `8`.
+  fn panicking_test(x: &f32) -> [f32; 1] {
+    assert!(*x <= 8.0, "pathological solution {x}");
+    [1.0 / x]
+  }
+
+  #[test]
+  fn test_catch_operator_panics() {
+    let failure = Nsga2::builder()
+      .population(vec![1.0, 2.0, 3.0])
+      .tester(panicking_test)
+      .selector(AllSelector())
+      .recombinator(|x: &f32| x + 1.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(100))
+      .catch_operator_panics(true)
+      .build()
+      .try_optimize_best_effort()
+      .unwrap_err();
+    assert_eq!(failure.error, OptimizationError::OperatorPanicked {
+      operator: OperatorKind::Tester,
+      generation: 5,
+      payload_message: "pathological solution 9".to_string(),
+    });
+    let mut population = failure.population;
+    population.sort_by(f32::total_cmp);
+    assert_eq!(population, [7.0, 7.0, 8.0]);
+  }
+
+  #[test]
+  #[should_panic(expected = "pathological solution 9")]
+  fn test_operator_panics_are_not_caught_by_default() {
+    Nsga2::builder()
+      .population(vec![1.0, 2.0, 3.0])
+      .tester(panicking_test)
+      .selector(AllSelector())
+      .recombinator(|x: &f32| x + 1.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(100))
+      .build()
+      .try_optimize_best_effort()
+      .ok();
+  }
+
+  #[test]
+  fn test_best_effort_population_on_error() {
+    let failure = Nsga2::builder()
+      .population(vec![1.0, 2.0, 3.0])
+      .tester(|x: &f32| [*x])
+      .selector(FirstSelector(0))
+      .recombinator(|x: &f32| x + 1.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(100))
+      .build()
+      .try_optimize_best_effort()
+      .unwrap_err();
+    assert_eq!(failure.error, OptimizationError::SelectionEmpty {
+      generation: 0
+    });
+    assert_eq!(failure.population, [1.0, 2.0, 3.0]);
   }
 }
@@ -0,0 +1,317 @@
+//! Optimization in two consecutive phases.
+//!
+//! A common recipe is a broad global search followed by a focused local one
+//! around the discovered front, e.g. [`Nsga2`] with wide mutations followed
+//! by [`Spea2`] with tight ones. [`Phased`] runs two optimizers one after the
+//! other, seeding the second one with the result of the first one.
+//!
+//! [`Nsga2`]: super::nsga::Nsga2
+//! [`Spea2`]: super::spea::Spea2
+
+use super::{OptimizationError, OptimizationFailure, Optimizer};
+
+/// An [`Optimizer`] whose initial population can be replaced after it has
+/// been built. Implemented by [`Nsga2`], [`Spea2`] and [`Phased`].
+///
+/// [`Nsga2`]: super::nsga::Nsga2
+/// [`Spea2`]: super::spea::Spea2
+pub trait Seedable<S, const N: usize>: Optimizer<S, N> {
+  /// Replaces the initial population with the `population`. An empty
+  /// `population` is ignored, so the optimizer starts from its own initial
+  /// population instead.
+  ///
+  /// The size of the population the optimizer maintains doesn't change, the
+  /// `population` is truncated down to it, or grows up to it, in the first
+  /// generation. Solutions injected before are kept.
+  fn seed(&mut self, population: Vec<S>);
+}
+
+/// Two optimizers that run one after the other. The `first` one runs until
+/// its terminator stops it, then its result, passed through the transition
+/// hook, replaces the initial population of the `second` one, see
+/// [`Seedable::seed`]. The result of the `second` optimizer is the result of
+/// both phases. With [`Spea2`] as the second phase, the seeded population
+/// competes for its initial archive.
+///
+/// The transition hook, set with [`with_transition`], can densify or filter
+/// the result of the first phase, e.g. add perturbed copies of solutions of
+/// the front. By default, the result is passed as it is.
+///
+/// Optimizers of phases may have different operators and even different
+/// types of operators, only solutions and the number of objectives must be
+/// the same. Phases can be nested to run more than two of them.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   optimizer::{nsga::Nsga2, phased::Phased, spea::Spea2, Optimizer},
+///   selection::{RandomSelector, TournamentSelectorWithReplacement},
+///   termination::GenerationTerminator,
+/// };
+/// use rand::Rng;
+///
+/// let test = |x: &f32| [x.powf(2.0), (x - 2.0).powf(2.0)];
+/// // a broad search with wide mutations
+/// let global = Nsga2::builder()
+///   .population((0..100).map(|i| i as f32 - 50.0).collect())
+///   .tester(test)
+///   .selector(RandomSelector(50))
+///   .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+///   .mutator(|x: &mut f32| *x += rand::thread_rng().gen_range(-5.0..5.0))
+///   .terminator(GenerationTerminator(20))
+///   .build();
+/// // a focused search with tight mutations, its own initial population is
+/// // replaced by the result of the first phase
+/// let local = Spea2::builder()
+///   .population(vec![0.0])
+///   .archive_size(50)
+///   .tester(test)
+///   .selector(TournamentSelectorWithReplacement(50, 2))
+///   .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+///   .mutator(|x: &mut f32| *x += rand::thread_rng().gen_range(-0.1..0.1))
+///   .terminator(GenerationTerminator(20))
+///   .build();
+/// let solutions = Phased::new(global, local)
+///   // keep only solutions near the interesting region
+///   .with_transition(|front: Vec<f32>| {
+///     front
+///       .into_iter()
+///       .filter(|x| (-10.0..=10.0).contains(x))
+///       .collect()
+///   })
+///   .optimize();
+/// assert!(solutions.iter().all(|x| (-0.5..=2.5).contains(x)));
+/// ```
+///
+/// [`Spea2`]: super::spea::Spea2
+/// [`with_transition`]: Phased::with_transition
+#[derive(Clone, Debug)]
+pub struct Phased<A, B, T = ()> {
+  first: A,
+  second: B,
+  transition: T,
+}
+
+impl<A, B> Phased<A, B> {
+  /// Creates optimization that runs the `first` optimizer, then the `second`
+  /// one seeded with the result of the `first` one.
+  pub fn new(first: A, second: B) -> Self {
+    Self {
+      first,
+      second,
+      transition: (),
+    }
+  }
+
+  /// Sets the `transition` hook that turns the result of the first phase
+  /// into the initial population of the second one.
+  pub fn with_transition<S, T>(self, transition: T) -> Phased<A, B, T>
+  where
+    T: FnOnce(Vec<S>) -> Vec<S>,
+  {
+    Phased {
+      first: self.first,
+      second: self.second,
+      transition,
+    }
+  }
+}
+
+impl<A, B, T> Phased<A, B, T> {
+  /// Returns the optimizer of the first phase.
+  pub fn first(&self) -> &A {
+    &self.first
+  }
+
+  /// Returns the optimizer of the second phase.
+  pub fn second(&self) -> &B {
+    &self.second
+  }
+
+  /// Runs the first phase, then the second one seeded with the result of the
+  /// first one passed through the `transition`. If the first phase fails,
+  /// the second one doesn't run.
+  fn run<S, const N: usize>(
+    self,
+    transition: impl FnOnce(T, Vec<S>) -> Vec<S>,
+  ) -> Result<Vec<S>, OptimizationFailure<S>>
+  where
+    A: Optimizer<S, N>,
+    B: Seedable<S, N>,
+  {
+    let Self {
+      first,
+      mut second,
+      transition: hook,
+    } = self;
+    let population = first.try_optimize_best_effort()?;
+    second.seed(transition(hook, population));
+    second.try_optimize_best_effort()
+  }
+}
+
+impl<S, const N: usize, A, B> Optimizer<S, N> for Phased<A, B>
+where
+  A: Optimizer<S, N>,
+  B: Seedable<S, N>,
+{
+  fn optimize(self) -> Vec<S> {
+    self.try_optimize().unwrap_or_else(|e| panic!("{e}"))
+  }
+
+  fn try_optimize(self) -> Result<Vec<S>, OptimizationError> {
+    self
+      .try_optimize_best_effort()
+      .map_err(|failure| failure.error)
+  }
+
+  /// Runs both phases. If the first phase fails, its salvaged population is
+  /// returned with the error, and the second phase doesn't run.
+  fn try_optimize_best_effort(self) -> Result<Vec<S>, OptimizationFailure<S>> {
+    self.run(|(), population| population)
+  }
+}
+
+impl<S, const N: usize, A, B, T> Optimizer<S, N> for Phased<A, B, T>
+where
+  A: Optimizer<S, N>,
+  B: Seedable<S, N>,
+  T: FnOnce(Vec<S>) -> Vec<S>,
+{
+  fn optimize(self) -> Vec<S> {
+    self.try_optimize().unwrap_or_else(|e| panic!("{e}"))
+  }
+
+  fn try_optimize(self) -> Result<Vec<S>, OptimizationError> {
+    self
+      .try_optimize_best_effort()
+      .map_err(|failure| failure.error)
+  }
+
+  /// Runs both phases. If the first phase fails, its salvaged population is
+  /// returned with the error, and the second phase doesn't run.
+  fn try_optimize_best_effort(self) -> Result<Vec<S>, OptimizationFailure<S>> {
+    self.run(|transition, population| transition(population))
+  }
+}
+
+impl<S, const N: usize, A, B> Seedable<S, N> for Phased<A, B>
+where
+  A: Seedable<S, N>,
+  B: Seedable<S, N>,
+{
+  /// Seeds the first phase.
+  fn seed(&mut self, population: Vec<S>) {
+    self.first.seed(population);
+  }
+}
+
+impl<S, const N: usize, A, B, T> Seedable<S, N> for Phased<A, B, T>
+where
+  A: Seedable<S, N>,
+  B: Seedable<S, N>,
+  T: FnOnce(Vec<S>) -> Vec<S>,
+{
+  /// Seeds the first phase.
+  fn seed(&mut self, population: Vec<S>) {
+    self.first.seed(population);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::sync::{Arc, Mutex};
+
+  use super::*;
+  use crate::{
+    optimizer::{nsga::Nsga2, spea::Spea2},
+    score::Scores,
+    selection::{AllSelector, FirstSelector, RandomSelector},
+    termination::GenerationTerminator,
+  };
+
+  #[test]
+  fn test_phased() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let observed = Arc::clone(&seen);
+    let first = Nsga2::builder()
+      .population(vec![1.0, 2.0, 3.0, 4.0])
+      .tester(|x: &f32| [x.powf(2.0), (x - 2.0).powf(2.0)])
+      .selector(RandomSelector(4))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(3))
+      .build();
+    let second = Spea2::builder()
+      .population(vec![100.0])
+      .archive_size(4)
+      .tester(|x: &f32| [x.powf(2.0), (x - 2.0).powf(2.0)])
+      .selector(AllSelector())
+      .recombinator(|x: &f32| *x)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(2))
+      .observer(move |generation: usize, s: &[f32], _: &[Scores<2>]| {
+        if generation == 0 {
+          observed.lock().unwrap().extend_from_slice(s);
+        }
+      })
+      .build();
+    let solutions = Phased::new(first, second)
+      .with_transition(|mut front: Vec<f32>| {
+        front.push(-1.0);
+        front
+      })
+      .optimize();
+    // the second phase has started from the front of the first one instead of
+    // its own initial population
+    let seen = seen.lock().unwrap();
+    assert!(!seen.contains(&100.0));
+    assert!(solutions.iter().all(|x| (1.0..=2.0).contains(x)));
+  }
+
+  #[test]
+  fn test_phased_first_phase_fails() {
+    let first = Nsga2::builder()
+      .population(vec![1.0, 2.0, 3.0])
+      .tester(|x: &f32| [x.powf(2.0), (x - 2.0).powf(2.0)])
+      .selector(FirstSelector(0))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(3))
+      .build();
+    let second = Nsga2::builder()
+      .population(vec![1.0])
+      .tester(|x: &f32| -> [f32; 2] { panic!("ran with {x}") })
+      .selector(AllSelector())
+      .recombinator(|x: &f32| *x)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(3))
+      .build();
+    let failure = Phased::new(first, second)
+      .try_optimize_best_effort()
+      .unwrap_err();
+    assert_eq!(failure.error, OptimizationError::SelectionEmpty {
+      generation: 0
+    });
+    assert_eq!(failure.population, [1.0, 2.0, 3.0]);
+  }
+
+  #[test]
+  fn test_nested_phases() {
+    let phase = |generations| {
+      Nsga2::builder()
+        .population(vec![0.0])
+        .tester(|x: &f32| [x.powf(2.0), (x - 2.0).powf(2.0)])
+        .selector(AllSelector())
+        .recombinator(|x: &f32| x + 1.0)
+        .mutator(|_: &mut f32| {})
+        .terminator(GenerationTerminator(generations))
+        .build()
+    };
+    let mut phases = Phased::new(Phased::new(phase(2), phase(2)), phase(2));
+    phases.seed(vec![-10.0, -20.0]);
+    // each phase has moved the population by two steps towards the front
+    let solutions = phases.optimize();
+    assert_eq!(solutions, [-4.0]);
+  }
+}
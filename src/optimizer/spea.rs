@@ -1,17 +1,99 @@
 //! Implementations of genetic algorithms of SPEA family.
 
-use std::{cmp::Ordering, marker::PhantomData, ops::Not};
+use std::{
+  cmp::Ordering,
+  collections::HashSet,
+  hash::Hash,
+  marker::PhantomData,
+  time::{Duration, Instant},
+};
 
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use typed_builder::TypedBuilder;
 
-use super::Optimizer;
+use super::{
+  accept_dominated,
+  apply_permutation,
+  catch_operator_panics,
+  check_population,
+  check_scores,
+  check_selection,
+  count_nan_scores,
+  from_index,
+  mutate_survivors,
+  recombine_within_limit,
+  report_error,
+  report_termination,
+  select_tracking_offsprings,
+  test_until,
+  to_index,
+  CurrentOperator,
+  DryRunAnomaly,
+  DryRunReport,
+  EmptySelection,
+  EqualTo,
+  Fingerprint,
+  Objectives,
+  OffspringBudget,
+  OperatorKind,
+  OperatorSet,
+  OptimizationError,
+  OptimizationFailure,
+  OptimizationOutcome,
+  Optimizer,
+  Replacement,
+  SizeSchedule,
+  SolutionIndex,
+  Terminated,
+};
 use crate::{
-  mutation::executor::MutationExecutor,
-  recombination::executor::RecombinationExecutor,
-  score::{ParetoDominance, Scores},
-  selection::executor::SelectionExecutor,
-  termination::executor::TerminationExecutor,
-  testing::executor::TestExecutor,
+  constraining::{
+    executor::{ConstraintAdapter, ConstraintExecutor, RepairExecutor},
+    ErasedConstraint,
+    Repairer,
+  },
+  dedup::Deduplicate,
+  execution::{
+    strategy::{CustomExecutionStrategy, SequentialExecutionStrategy},
+    PARALLEL_THRESHOLD,
+  },
+  mutation::{executor::MutationExecutor, BoxedMutator},
+  observer::{
+    EventSender,
+    Observer,
+    OperatorTimings,
+    OptimizerEvent,
+    TerminationReason,
+  },
+  operator::OperatorFeedback,
+  optimizer::phased::Seedable,
+  pause::{retain_front, Directive, PauseHandler, PausePoints},
+  recombination::{executor::RecombinationExecutor, BoxedRecombinator},
+  runner::Spawnable,
+  score::{
+    select_by,
+    shadow_view,
+    squared_distance,
+    KdTree,
+    ObjectiveLabels,
+    ParetoArchive,
+    ParetoDominance,
+    ScoreQuantizer,
+    ScoreTransform,
+    Scores,
+  },
+  selection::{executor::SelectionExecutor, BoxedSelector},
+  speciation::{quota_budget, speciate, SpeciesAssigner},
+  termination::{
+    executor::TerminationExecutor,
+    BoxedTerminator,
+    FlagTerminator,
+    TerminationDecision,
+  },
+  testing::{executor::TestExecutor, BoxedTester},
+  trace::{phase, Timer},
+  truncation::Acceptance,
 };
 
 /// An implementation of an improved version of the Strength Pareto Evolutionary
@@ -24,6 +106,7 @@ use crate::{
 /// *Schaffer's Problem No.1* solution.
 /// ```no_run
 /// # fn main() {
+/// # #[cfg(feature = "parallel")] {
 /// use rand::Rng;
 /// use moga::{
 ///   operator::ParBatch,
@@ -62,6 +145,7 @@ use crate::{
 /// // upon termination optimizer returns the best solutions it has found
 /// let solutions = spea2.optimize();
 /// # }
+/// # }
 /// ```
 #[derive(TypedBuilder, Debug)]
 pub struct Spea2<
@@ -80,41 +164,377 @@ pub struct Spea2<
   const PARENT_NUM: usize,
   const OFFSPRING_NUM: usize,
 > {
-  #[builder(setter(
-    transform = |v: Vec<Solution>| {
-      v.is_empty()
-        .not()
-        .then_some(v)
-        .unwrap_or_else(|| panic!("initial population is empty"))
-    },
-    doc = "
-      The initial population setter.
-
-      # Panics
-
-      Panics if population is empty.",    
-  ))]
+  #[builder(
+    via_mutators(init = Vec::new()),
+    mutators(
+      /// The initial population setter. Either this or
+      /// [`population_with_scores`](Self::population_with_scores) must be
+      /// called, or both.
+      ///
+      /// # Panics
+      ///
+      /// Panics if population is empty.
+      pub fn population(&mut self, population: Vec<Solution>) {
+        if population.is_empty() {
+          panic!("initial population is empty")
+        }
+        self.population = population;
+      }
+    )
+  )]
   population: Vec<Solution>,
   #[builder(setter(
-    transform = |v: usize| {
-      if v == 0 {
+    transform = |v: impl Into<SizeSchedule>| {
+      let v = v.into();
+      if let SizeSchedule::Constant(0) = v {
         panic!("archive size cannot be 0")
       }
       v
     },
     doc = "
-      The archive size setter.
+      The archive size setter. Takes an archive size or an [`ArchiveSchedule`]
+      of archive sizes over generations.
 
       # Panics
 
       Panics if archive size is 0.",    
   ))]
-  archive_size: usize,
+  archive_size: SizeSchedule,
   tester: Tst,
   selector: Sel,
   recombinator: Rec,
   mutator: Mut,
   terminator: Ter,
+  #[builder(
+    via_mutators(init = None),
+    mutators(
+      /// Sets an [`Observer`] that is notified after each generation.
+      pub fn observer(
+        &mut self,
+        observer: impl Observer<Solution, OBJECTIVE_NUM> + Send + 'static
+      ) {
+        self.observer = Some(Box::new(observer));
+      }
+    )
+  )]
+  observer: Option<Box<dyn Observer<Solution, OBJECTIVE_NUM> + Send>>,
+  #[builder(
+    via_mutators(init = None),
+    mutators(
+      /// Sets a repair operator that is applied to offsprings after mutation
+      /// and before testing.
+      pub fn repairer<RepExecStrat: 'static>(
+        &mut self,
+        repairer: impl RepairExecutor<Solution, RepExecStrat> + Send + 'static
+      ) where Solution: 'static {
+        self.repairer = Some(Box::new(move |solutions: &mut [Solution]| {
+          repairer.execute_repairs(solutions)
+        }));
+      }
+    )
+  )]
+  repairer: Option<Box<dyn Repairer<Solution> + Send>>,
+  #[builder(
+    via_mutators(init = None),
+    mutators(
+      /// Sets a [`Deduplicate`] operator, like [`Dedup`], that removes
+      /// duplicate offsprings after repair and before testing. Offsprings
+      /// that duplicate members of the archive are removed too, so they are
+      /// neither tested nor cloned into the archive again. Numbers of removed
+      /// offsprings are reported to the observer with
+      /// [`Observer::duplicates_removed`].
+      ///
+      /// [`Dedup`]: crate::dedup::Dedup
+      pub fn dedup(
+        &mut self,
+        dedup: impl Deduplicate<Solution> + Send + 'static
+      ) {
+        self.dedup = Some(Box::new(dedup));
+      }
+    )
+  )]
+  dedup: Option<Box<dyn Deduplicate<Solution> + Send>>,
+  #[builder(
+    via_mutators(init = None),
+    mutators(
+      /// Mutates the archive with the optimizer's mutator after each
+      /// environmental selection, too, so that survivors themselves are refined
+      /// instead of only their offsprings (Lamarckian learning). Survivors that
+      /// the mutation has changed are tested again.
+      ///
+      /// A survivor has changed if its `key` has changed. Keys are hashed,
+      /// so they should be cheap to compute, e.g. bits of a number, but not
+      /// necessarily cheap to compare.
+      pub fn mutate_parents<K: Hash>(
+        &mut self,
+        key: impl Fn(&Solution) -> K + Send + 'static
+      ) {
+        self.parent_fingerprint = Some(Fingerprint::new(key));
+      }
+    )
+  )]
+  parent_fingerprint: Option<Fingerprint<Solution>>,
+  #[builder(
+    via_mutators(init = None),
+    mutators(
+      /// Sets a [`Constraint`] that is evaluated on the population at the end
+      /// of each generation to let it adapt to violations, like
+      /// [`AdaptivePenalty`] does.
+      ///
+      /// [`Constraint`]: crate::constraining::Constraint
+      /// [`AdaptivePenalty`]: crate::constraining::AdaptivePenalty
+      pub fn constraint<ConExecStrat: 'static, const CONSTRAINT_NUM: usize>(
+        &mut self,
+        constraint: impl ConstraintExecutor<
+          Solution,
+          CONSTRAINT_NUM,
+          ConExecStrat
+        > + Send + 'static
+      ) where Solution: 'static {
+        self.constraint = Some(Box::new(ErasedConstraint::new(constraint)));
+      }
+    )
+  )]
+  constraint: Option<Box<dyn ConstraintAdapter<Solution> + Send>>,
+  #[builder(
+    via_mutators(init = None),
+    mutators(
+      /// Sets an [`Acceptance`] rule that lets dominated offsprings replace
+      /// solutions of the archive that dominate them and survive the
+      /// truncation, such as [`AnnealedAcceptance`]. It's consulted only with
+      /// [`Replacement::MuPlusLambda`].
+      ///
+      /// [`AnnealedAcceptance`]: crate::truncation::AnnealedAcceptance
+      pub fn acceptance(
+        &mut self,
+        acceptance: impl Acceptance<OBJECTIVE_NUM> + Send + 'static
+      ) {
+        self.acceptance = Some(Box::new(acceptance));
+      }
+    )
+  )]
+  acceptance: Option<Box<dyn Acceptance<OBJECTIVE_NUM> + Send>>,
+  #[builder(
+    via_mutators(init = None),
+    mutators(
+      /// Sets a [`SpeciesAssigner`], like [`ThresholdSpeciation`], that
+      /// partitions the archive into species each generation, see
+      /// [`speciation`](crate::speciation). Parents are selected and recombined
+      /// within each species, and each species creates at most its quota of
+      /// `max_offspring_per_generation` offsprings, or of the archive size if
+      /// it isn't set. Quotas are met by sampling combinations of parents, as
+      /// with [`OffspringBudget::Subsample`].
+      ///
+      /// [`ThresholdSpeciation`]: crate::speciation::ThresholdSpeciation
+      pub fn speciation(
+        &mut self,
+        assigner: impl SpeciesAssigner<Solution> + Send + 'static
+      ) {
+        self.speciation = Some(Box::new(assigner));
+      }
+    )
+  )]
+  speciation: Option<Box<dyn SpeciesAssigner<Solution> + Send>>,
+  #[builder(
+    via_mutators(init = None),
+    mutators(
+      /// Sets an [`OperatorFeedback`] that the optimizer tells after each
+      /// generation how many of the generation's offsprings survived its
+      /// truncation, e.g. an
+      /// [`AdaptiveRecombinator`](crate::recombination::AdaptiveRecombinator)
+      /// that is the optimizer's recombinator as well.
+      pub fn feedback(
+        &mut self,
+        feedback: impl OperatorFeedback + Send + 'static
+      ) {
+        self.feedback = Some(Box::new(feedback));
+      }
+    )
+  )]
+  feedback: Option<Box<dyn OperatorFeedback + Send>>,
+  #[builder(
+    default,
+    setter(
+      strip_option,
+      into,
+      doc = "
+      Sets an [`EventSender`] that the optimizer sends [`OptimizerEvent`]s
+      through.",
+    )
+  )]
+  event_sender: Option<EventSender<OBJECTIVE_NUM>>,
+  #[builder(
+    default,
+    setter(
+      strip_option,
+      doc = "
+      Sets labels of objectives. Labels are metadata only: the optimizer
+      gives them to its observer with [`Observer::label_objectives`] and
+      sends them with [`OptimizerEvent::GenerationCompleted`] events.",
+    )
+  )]
+  objective_labels: Option<ObjectiveLabels<OBJECTIVE_NUM>>,
+  #[builder(
+    default,
+    setter(
+      strip_option,
+      doc = "
+      Snaps scores to a grid before the environmental selection computes
+      dominance and distances, so that scores differing only by noise don't
+      inflate the nondominated set. The archive keeps its original scores.",
+    )
+  )]
+  quantizer: Option<ScoreQuantizer<OBJECTIVE_NUM>>,
+  #[builder(
+    default,
+    setter(
+      strip_option,
+      doc = "
+      Transforms scores of each objective before the environmental selection
+      computes dominance and distances, after they are snapped to the grid
+      of the quantizer, if any. The archive keeps its original scores, as do
+      selectors, terminators and observers. A terminator that should see
+      transformed scores can apply [`ScoreTransform::apply_each`] itself.",
+    )
+  )]
+  score_transform: Option<[ScoreTransform; OBJECTIVE_NUM]>,
+  #[builder(
+    default,
+    setter(doc = "
+      Sets what the optimizer does if its selector selects no solutions.
+      Defaults to [`EmptySelection::Fail`].")
+  )]
+  empty_selection: EmptySelection,
+  #[builder(
+    default,
+    setter(doc = "
+      Sets how offsprings replace the population. Defaults to
+      [`Replacement::MuPlusLambda`].")
+  )]
+  replacement: Replacement,
+  #[builder(
+    default,
+    setter(
+      strip_option,
+      doc = "
+      Sets the maximum number of offsprings recombination may create in a
+      generation. If recombination of the selected parents would create more
+      of them, the optimizer does what its [`OffspringBudget`] tells.",
+    )
+  )]
+  max_offspring_per_generation: Option<usize>,
+  #[builder(
+    default,
+    setter(doc = "
+      Sets what the optimizer does if recombination would exceed
+      `max_offspring_per_generation`. Defaults to [`OffspringBudget::Fail`].")
+  )]
+  offspring_budget: OffspringBudget,
+  #[builder(
+    default,
+    setter(
+      strip_option,
+      doc = "
+      Sets the time budget of each generation, counted from its start.
+      Offsprings whose tests haven't started by the time it runs out are
+      dropped untested instead of waited for, so that a few pathological
+      solutions can't stall a generation. Numbers of dropped offsprings are
+      reported to the observer with [`Observer::tests_timed_out`]. Only
+      testers parallelized with `par_batch()` check the budget, others test
+      all offsprings regardless of it.",
+    )
+  )]
+  generation_time_budget: Option<Duration>,
+  #[builder(
+    default,
+    setter(doc = "
+      Sets whether the optimizer measures time spent in each phase of
+      optimization and sends it as [`OptimizerEvent::Profiled`] before
+      termination. See [`OperatorTimings`].")
+  )]
+  profile: bool,
+  #[builder(
+    default,
+    setter(doc = "
+      Sets whether the optimizer catches panics of its operators, such as a
+      test that panics on a pathological solution. A caught panic stops
+      optimization with [`OptimizationError::OperatorPanicked`], and
+      [`Optimizer::try_optimize_best_effort`] returns the archive along
+      with it. Operators don't have to be `UnwindSafe`, since they aren't
+      called after a panic. The panic is still reported by the panic hook,
+      which prints it to stderr by default.")
+  )]
+  catch_operator_panics: bool,
+  #[builder(
+    default,
+    setter(
+      strip_option,
+      doc = "
+      Sets a [`FlagTerminator`] that cancels optimization once its flag is
+      raised, e.g. by another thread. The flag is checked at the start of
+      each generation, along with the terminator, and the optimizer reports
+      [`TerminationReason::Cancelled`].",
+    )
+  )]
+  cancel_flag: Option<FlagTerminator>,
+  #[builder(
+    default,
+    setter(doc = "
+      Sets the metric of distances between scores that density of solutions
+      and the archive truncation are based on. Defaults to
+      [`DistanceMetric::Euclidean`] for compatibility, but
+      [`DistanceMetric::NormalizedEuclidean`] is recommended for objectives
+      of different scales.")
+  )]
+  distance: DistanceMetric<OBJECTIVE_NUM>,
+  #[builder(
+    via_mutators(init = None),
+    mutators(
+      /// Sets a [`PauseHandler`] that the optimizer pauses for after every
+      /// `generations` generations, see [`pause`](crate::pause).
+      ///
+      /// # Panics
+      ///
+      /// Panics if `generations` is `0`.
+      pub fn pause_every(
+        &mut self,
+        generations: usize,
+        handler: impl PauseHandler<Solution, OBJECTIVE_NUM> + Send + 'static
+      ) {
+        self.pause = Some(PausePoints::new(generations, handler));
+      }
+    )
+  )]
+  pause: Option<PausePoints<Solution, OBJECTIVE_NUM>>,
+  #[builder(setter(skip), default)]
+  generation_limit: Option<usize>,
+  #[builder(
+    via_mutators(init = Vec::new()),
+    mutators(
+      /// Adds solutions along with their scores to the initial population,
+      /// such as solutions of a previous run returned by
+      /// [`Spea2::optimize_with_scores`]. They aren't
+      /// tested again, only the solutions set with
+      /// [`population`](Self::population) are.
+      ///
+      /// Scores must be the ones the tester would evaluate, otherwise the
+      /// solutions compete unfairly.
+      pub fn population_with_scores(
+        &mut self,
+        population: Vec<(Solution, Scores<OBJECTIVE_NUM>)>
+      ) {
+        for (solution, scores) in population {
+          self.injected.push(solution);
+          self.injected_scores.push(scores);
+        }
+      }
+    )
+  )]
+  injected: Vec<Solution>,
+  #[builder(via_mutators(init = Vec::new()))]
+  injected_scores: Vec<Scores<OBJECTIVE_NUM>>,
+  #[builder(setter(skip), default)]
+  scratch: ScratchBuffers<OBJECTIVE_NUM>,
   #[builder(setter(skip), default)]
   _solution: PhantomData<Solution>,
   #[builder(setter(skip), default)]
@@ -129,8 +549,306 @@ pub struct Spea2<
   _rec_es: PhantomData<RecExecStrat>,
 }
 
-/// Index of solution in `solutions` vector.
-type SolutionIndex = usize;
+impl<
+    Solution,
+    Tst: TestExecutor<Solution, OBJECTIVE_NUM, TstExecStrat>,
+    Sel: SelectionExecutor<Solution, OBJECTIVE_NUM, SelExecStrat>,
+    Rec: RecombinationExecutor<Solution, PARENT_NUM, OFFSPRING_NUM, RecExecStrat>,
+    Mut: MutationExecutor<Solution, MutExecStrat>,
+    Ter: TerminationExecutor<Solution, OBJECTIVE_NUM, TerExecStrat>,
+    TstExecStrat,
+    TerExecStrat,
+    SelExecStrat,
+    MutExecStrat,
+    RecExecStrat,
+    const OBJECTIVE_NUM: usize,
+    const PARENT_NUM: usize,
+    const OFFSPRING_NUM: usize,
+    Fields,
+  >
+  Spea2Builder<
+    Solution,
+    Tst,
+    Sel,
+    Rec,
+    Mut,
+    Ter,
+    TstExecStrat,
+    TerExecStrat,
+    SelExecStrat,
+    MutExecStrat,
+    RecExecStrat,
+    OBJECTIVE_NUM,
+    PARENT_NUM,
+    OFFSPRING_NUM,
+    Fields,
+  >
+{
+  /// Pins the number of objectives of the optimizer to `N`. Otherwise, it's
+  /// inferred from all operators at once, and if one of them disagrees, the
+  /// compiler can't tell which one and reports the mismatch at `build()`.
+  /// Once pinned, the operator that disagrees fails to be set instead.
+  ///
+  /// # Examples
+  /// ```
+  /// use moga::{
+  ///   optimizer::spea::Spea2,
+  ///   selection::AllSelector,
+  ///   termination::GenerationTerminator,
+  /// };
+  ///
+  /// let optimizer = Spea2::builder()
+  ///   .objectives::<2>()
+  ///   .population(vec![1.0, 2.0])
+  ///   .archive_size(2)
+  ///   .tester(|x: &f32| [*x, 1.0 / x])
+  ///   .selector(AllSelector())
+  ///   .recombinator(|x: &f32| *x)
+  ///   .mutator(|x: &mut f32| *x *= 2.0)
+  ///   .terminator(GenerationTerminator(10))
+  ///   .build();
+  /// ```
+  ///
+  /// With 3 objectives pinned, the tester of 2 objectives is the culprit:
+  /// ```compile_fail,E0271
+  /// # use moga::{
+  /// #   optimizer::spea::Spea2,
+  /// #   selection::AllSelector,
+  /// #   termination::GenerationTerminator,
+  /// # };
+  /// let optimizer = Spea2::builder()
+  ///   .objectives::<3>()
+  ///   .population(vec![1.0, 2.0])
+  ///   .tester(|x: &f32| [*x, 1.0 / x])
+  /// #   .selector(AllSelector())
+  /// #   .recombinator(|x: &f32| *x)
+  /// #   .mutator(|x: &mut f32| *x *= 2.0)
+  /// #   .terminator(GenerationTerminator(10))
+  /// #   .archive_size(2)
+  /// #   .build();
+  /// ```
+  pub fn objectives<const N: usize>(self) -> Self
+  where
+    Objectives<OBJECTIVE_NUM>: EqualTo<N>,
+  {
+    self
+  }
+}
+
+/// [`Spea2`] with execution strategies of operators that aren't parallelized:
+/// a [`Test`], a [`Recombination`] and a [`Mutation`] applied to each
+/// solution, and a [`Selector`] and a [`Terminator`] applied to all solutions,
+/// like the built-in ones. Parents and offsprings numbers default to those of
+/// `Fn(&S, &S) -> S` recombination.
+///
+/// Naming the type of an optimizer fixes the execution strategies, so the
+/// compiler doesn't have to infer them and reports a mismatched operator in
+/// terms of its trait.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   optimizer::spea::Spea2For,
+///   prelude::*,
+///   selection::RandomSelector,
+///   termination::GenerationTerminator,
+/// };
+///
+/// let optimizer: Spea2For<f32, 2, _, _, _, _, _> = Spea2::builder()
+///   .population(vec![1.0, 2.0, 3.0])
+///   .archive_size(10)
+///   .tester(|x: &f32| [*x, x - 2.0])
+///   .selector(RandomSelector(2))
+///   .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+///   .mutator(|_: &mut f32| {})
+///   .terminator(GenerationTerminator(5))
+///   .build();
+/// ```
+///
+/// [`Test`]: crate::testing::Test
+/// [`Recombination`]: crate::recombination::Recombination
+/// [`Mutation`]: crate::mutation::Mutation
+/// [`Selector`]: crate::selection::Selector
+/// [`Terminator`]: crate::termination::Terminator
+pub type Spea2For<
+  S,
+  const N: usize,
+  Tst,
+  Sel,
+  Rec,
+  Mut,
+  Ter,
+  const P: usize = 2,
+  const O: usize = 1,
+> = Spea2<
+  S,
+  Tst,
+  Sel,
+  Rec,
+  Mut,
+  Ter,
+  SequentialExecutionStrategy,
+  CustomExecutionStrategy,
+  CustomExecutionStrategy,
+  SequentialExecutionStrategy,
+  SequentialExecutionStrategy,
+  N,
+  P,
+  O,
+>;
+
+/// [`Spea2`] with boxed operators, which is a nameable type of an optimizer
+/// with operators chosen at runtime.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   mutation::BoxedMutator,
+///   optimizer::{spea::BoxedSpea2, Optimizer},
+///   prelude::*,
+///   recombination::BoxedRecombinator,
+///   selection::{BoxedSelector, RandomSelector},
+///   termination::{BoxedTerminator, GenerationTerminator},
+///   testing::BoxedTester,
+/// };
+///
+/// struct Experiment {
+///   optimizer: BoxedSpea2<f32, 2>,
+/// }
+///
+/// let experiment = Experiment {
+///   optimizer: Spea2::builder()
+///     .population(vec![1.0, 2.0, 3.0])
+///     .archive_size(10)
+///     .tester(BoxedTester::new(|x: &f32| [*x, x - 2.0]))
+///     .selector(BoxedSelector::new(RandomSelector(2)))
+///     .recombinator(BoxedRecombinator::new(|x: &f32, y: &f32| (x + y) / 2.0))
+///     .mutator(BoxedMutator::new(|_: &mut f32| {}))
+///     .terminator(BoxedTerminator::new(GenerationTerminator(5)))
+///     .build(),
+/// };
+/// let solutions = experiment.optimizer.optimize();
+/// ```
+pub type BoxedSpea2<S, const N: usize> = Spea2<
+  S,
+  BoxedTester<S, N>,
+  BoxedSelector<S, N>,
+  BoxedRecombinator<S>,
+  BoxedMutator<S>,
+  BoxedTerminator<S, N>,
+  CustomExecutionStrategy,
+  CustomExecutionStrategy,
+  CustomExecutionStrategy,
+  CustomExecutionStrategy,
+  CustomExecutionStrategy,
+  N,
+  { usize::MAX },
+  { usize::MAX },
+>;
+
+/// Archive sizes of [`Spea2`] over generations. The size of the archive is
+/// evaluated at each generation before environmental selection, so the archive
+/// may start large to explore and shrink to exploit. Shrinking the archive
+/// below the number of nondominated solutions truncates them by density.
+///
+/// # Examples
+/// ```
+/// use moga::optimizer::spea::ArchiveSchedule;
+///
+/// let schedule = ArchiveSchedule::Linear {
+///   from: 100,
+///   to: 20,
+///   over_generations: 40,
+/// };
+/// assert_eq!(schedule.size(20), 60);
+/// ```
+pub type ArchiveSchedule = SizeSchedule;
+
+/// A metric of distances between scores that [`Spea2`] estimates density of
+/// solutions with, for both the distance to the k-th nearest neighbor in
+/// fitness of solutions and the truncation of the archive.
+///
+/// Distances between raw scores are dominated by objectives with the largest
+/// scales, so [`DistanceMetric::NormalizedEuclidean`] is recommended for
+/// objectives of different scales. [`DistanceMetric::Euclidean`] is the
+/// default for compatibility.
+///
+/// # Examples
+/// ```
+/// use moga::optimizer::spea::DistanceMetric;
+///
+/// // manhattan distance
+/// let metric =
+///   DistanceMetric::Custom(Box::new(|a: &[f32; 2], b: &[f32; 2]| {
+///     a.iter().zip(b).map(|(a, b)| f64::from((a - b).abs())).sum()
+///   }));
+/// ```
+#[derive(Default)]
+pub enum DistanceMetric<const N: usize> {
+  /// Squared euclidean distance between raw scores.
+  #[default]
+  Euclidean,
+  /// Squared euclidean distance between scores normalized to `0.0..=1.0`
+  /// by minimum and maximum scores of each objective among solutions of the
+  /// generation.
+  NormalizedEuclidean,
+  /// A custom distance between raw scores. It must be symmetric and
+  /// non-negative, and must not be NaN.
+  Custom(Box<DistanceFn<N>>),
+}
+
+/// A custom distance function of [`DistanceMetric::Custom`].
+type DistanceFn<const N: usize> =
+  dyn Fn(&Scores<N>, &Scores<N>) -> f64 + Send + Sync;
+
+impl<const N: usize> DistanceMetric<N> {
+  /// Returns the distance between scores `a` and `b`, which are normalized
+  /// already if the metric is normalized.
+  fn between(&self, a: &Scores<N>, b: &Scores<N>) -> Distance {
+    match self {
+      DistanceMetric::Custom(distance) => distance(a, b),
+      _ => squared_distance(a, b),
+    }
+  }
+}
+
+impl<const N: usize> std::fmt::Debug for DistanceMetric<N> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      DistanceMetric::Euclidean => f.write_str("Euclidean"),
+      DistanceMetric::NormalizedEuclidean => f.write_str("NormalizedEuclidean"),
+      DistanceMetric::Custom(_) => f.write_str("Custom"),
+    }
+  }
+}
+
+/// Normalizes `scores` to `0.0..=1.0` by minimum and maximum scores of each
+/// objective and stores them in `normalized`. Objectives with the same
+/// scores are normalized to `0.0`.
+fn normalize<const N: usize>(
+  scores: &[Scores<N>],
+  normalized: &mut Vec<Scores<N>>,
+) {
+  let (mut min, mut max) = ([f32::INFINITY; N], [f32::NEG_INFINITY; N]);
+  for sc in scores {
+    for (o_idx, &s) in sc.iter().enumerate() {
+      min[o_idx] = min[o_idx].min(s);
+      max[o_idx] = max[o_idx].max(s);
+    }
+  }
+  normalized.clear();
+  normalized.extend(scores.iter().map(|sc| {
+    std::array::from_fn(|o_idx| {
+      let range = max[o_idx] - min[o_idx];
+      if range > 0.0 {
+        (sc[o_idx] - min[o_idx]) / range
+      } else {
+        0.0
+      }
+    })
+  }));
+}
+
 /// Number of dominated solutions.
 type StrengthValue = u32;
 /// Sum of strength values of dominating solutions and density.
@@ -171,169 +889,1126 @@ impl<
     OFFSPRING_NUM,
   >
 {
-  /// Environmental selection procedure of SPEA-II algorithm.
-  fn environmental_selection(
+  /// Creates SPEA-II `Optimizer` with the initial `population` and
+  /// `archive_size` and `operators`. Other options keep their defaults.
+  ///
+  /// # Panics
+  ///
+  /// Panics if population is empty or archive size is 0.
+  pub fn from_operators(
+    population: Vec<Solution>,
+    archive_size: impl Into<SizeSchedule>,
+    operators: OperatorSet<
+      Solution,
+      Tst,
+      Sel,
+      Rec,
+      Mut,
+      Ter,
+      TstExecStrat,
+      TerExecStrat,
+      SelExecStrat,
+      MutExecStrat,
+      RecExecStrat,
+      OBJECTIVE_NUM,
+      PARENT_NUM,
+      OFFSPRING_NUM,
+    >,
+  ) -> Self {
+    let mut optimizer = Self::builder()
+      .population(population)
+      .archive_size(archive_size)
+      .tester(operators.tester)
+      .selector(operators.selector)
+      .recombinator(operators.recombinator)
+      .mutator(operators.mutator)
+      .terminator(operators.terminator)
+      .build();
+    optimizer.repairer = operators.repairer;
+    optimizer.constraint = operators.constraint;
+    optimizer
+  }
+
+  /// Tests `solutions` with the optimizer's tester right away and merges
+  /// them into the initial population when optimization starts. Solutions
+  /// can be injected into running optimization with a pause handler as well,
+  /// see [`Directive::InjectSolutions`].
+  ///
+  /// Injected solutions compete for the archive with the initial population,
+  /// which is truncated down to the archive size in the first generation.
+  /// Like solutions of the initial population, only one of solutions with
+  /// exactly equal scores gets into the archive.
+  ///
+  /// Nothing is injected if the tester returns a different number of scores
+  /// than the number of `solutions`, and
+  /// [`OptimizationError::ScoreCountMismatch`] is returned instead.
+  pub fn inject(
+    &mut self,
+    mut solutions: Vec<Solution>,
+  ) -> Result<(), OptimizationError> {
+    let mut scores = self.tester.execute_tests(&solutions);
+    check_scores(
+      OperatorKind::Tester,
+      0,
+      scores.len(),
+      solutions.len(),
+      self.event_sender.as_ref(),
+    )?;
+    self.injected.append(&mut solutions);
+    self.injected_scores.append(&mut scores);
+    Ok(())
+  }
+
+  /// Applies the operators once to a sample of at most `sample_size`
+  /// solutions of the initial population, without running optimization, and
+  /// reports how many solutions each phase has produced and any anomalies,
+  /// such as NaN scores. It catches mistakes in operators before an
+  /// expensive run.
+  ///
+  /// The sample is tested, parents are selected out of it rather than out of an
+  /// archive and recombined within the offspring limit and budget of the
+  /// optimizer, and their offsprings are mutated, repaired and tested. Fails
+  /// with the same errors that optimization would fail with in the first
+  /// generation, but sends no events. Operators with internal state, such as
+  /// random number generators, are affected by the dry run just as by a
+  /// generation.
+  pub fn dry_run(
     &self,
-    solutions: Vec<Solution>,
-    scores: Vec<Scores<OBJECTIVE_NUM>>,
-  ) -> (Vec<Solution>, Vec<Scores<OBJECTIVE_NUM>>) {
-    // each i-th value is a number of solutions that i-th solution dominates
-    let mut strength_values: Vec<StrengthValue> = vec![0; solutions.len()];
-    // count strength values for each solution
-    for p_idx in 0..solutions.len() - 1 {
-      let (p_sc, rest_scs) =
-        scores[p_idx..].split_first().expect("no scores remain");
-      for (i, q_sc) in rest_scs.iter().enumerate() {
-        let q_idx = p_idx + i + 1;
-        match p_sc.dominance(q_sc) {
-          Ordering::Less => strength_values[p_idx] += 1,
-          Ordering::Greater => strength_values[q_idx] += 1,
-          Ordering::Equal => {}
+    sample_size: usize,
+  ) -> Result<DryRunReport, OptimizationError> {
+    // a population set only with scores is sampled instead
+    let population = if self.population.is_empty() {
+      &self.injected
+    } else {
+      &self.population
+    };
+    let sample = &population[..sample_size.min(population.len())];
+    let mut report = DryRunReport {
+      sampled: sample.len(),
+      ..Default::default()
+    };
+    catch_operator_panics::<_, OBJECTIVE_NUM>(
+      self.catch_operator_panics,
+      None,
+      |current| {
+        let scores = current.call(OperatorKind::Tester, 0, || {
+          self.tester.execute_tests(sample)
+        });
+        check_scores::<OBJECTIVE_NUM>(
+          OperatorKind::Tester,
+          0,
+          scores.len(),
+          sample.len(),
+          None,
+        )?;
+        let nan_scores = count_nan_scores(&scores);
+        if nan_scores > 0 {
+          report.anomalies.push(DryRunAnomaly::NanScores {
+            solutions: nan_scores,
+          });
         }
-      }
-    }
 
-    // each i-th value is a sum of strength values of solutions that dominate
-    // i-th solution paired with index of a solution in `solutions`
-    let mut sol_idx_fit: Vec<(SolutionIndex, Fitness)> =
-      (0..solutions.len()).map(|i| (i, 0.0)).collect();
-    // compute raw fitness for each solution
-    for p_idx in 0..solutions.len() - 1 {
-      let (p_sc, rest_scs) =
-        scores[p_idx..].split_first().expect("no scores remain");
-      for (i, q_sc) in rest_scs.iter().enumerate() {
-        let q_idx = p_idx + i + 1;
-        match p_sc.dominance(q_sc) {
-          Ordering::Less => {
-            sol_idx_fit[q_idx].1 += f64::from(strength_values[p_idx])
-          }
-          Ordering::Greater => {
-            sol_idx_fit[p_idx].1 += f64::from(strength_values[q_idx])
+        let mut selected = current.call(OperatorKind::Selector, 0, || {
+          self.selector.execute_selection(sample, &scores)
+        });
+        check_selection::<OBJECTIVE_NUM>(
+          self.selector.selection_contract(),
+          0,
+          selected.len(),
+          None,
+        )?;
+        if selected.is_empty() {
+          match self.empty_selection {
+            EmptySelection::Fail => {
+              return Err(OptimizationError::SelectionEmpty { generation: 0 })
+            }
+            EmptySelection::SelectAll => selected = (0..sample.len()).collect(),
           }
-          Ordering::Equal => {}
         }
+        report.selected = selected.len();
+        if selected.len() < PARENT_NUM {
+          report.anomalies.push(DryRunAnomaly::TooFewParents {
+            selected: selected.len(),
+            required: PARENT_NUM,
+          });
+        }
+        let mut offsprings =
+          current.call(OperatorKind::Recombinator, 0, || {
+            recombine_within_limit(
+              &self.recombinator,
+              sample,
+              &scores,
+              &selected,
+              self.max_offspring_per_generation,
+              self.offspring_budget,
+              0,
+            )
+          })?;
+        report.offsprings = offsprings.len();
+        if offsprings.is_empty() {
+          report.anomalies.push(DryRunAnomaly::NoOffsprings);
+        }
+        current.call(OperatorKind::Mutator, 0, || {
+          self.mutator.execute_mutations(&mut offsprings)
+        });
+        if let Some(repairer) = &self.repairer {
+          current.call(OperatorKind::Repairer, 0, || {
+            repairer.repair(&mut offsprings)
+          });
+        }
+        let offspring_scores = current.call(OperatorKind::Tester, 0, || {
+          self.tester.execute_tests(&offsprings)
+        });
+        check_scores::<OBJECTIVE_NUM>(
+          OperatorKind::Tester,
+          0,
+          offspring_scores.len(),
+          offsprings.len(),
+          None,
+        )?;
+        let nan_scores = count_nan_scores(&offspring_scores);
+        if nan_scores > 0 {
+          report.anomalies.push(DryRunAnomaly::OffspringNanScores {
+            offsprings: nan_scores,
+          });
+        }
+        Ok(())
+      },
+    )?;
+    Ok(report)
+  }
+
+  /// Runs SPEA-II `Optimizer` like [`Optimizer::optimize`] does, but for at
+  /// most `generations` generations. The terminator is still called at the
+  /// start of each generation, so a stateful terminator sees every generation
+  /// and may terminate optimization earlier. If the limit is reached first,
+  /// the optimizer reports [`TerminationReason::GenerationLimit`].
+  ///
+  /// # Panics
+  ///
+  /// Panics if optimization fails with an [`OptimizationError`].
+  pub fn optimize_n(self, generations: usize) -> Vec<Solution> {
+    self
+      .try_optimize_n(generations)
+      .unwrap_or_else(|e| panic!("{e}"))
+  }
+
+  /// Runs SPEA-II `Optimizer` like [`Optimizer::try_optimize`] does, but for
+  /// at most `generations` generations, see [`Self::optimize_n`].
+  pub fn try_optimize_n(
+    mut self,
+    generations: usize,
+  ) -> Result<Vec<Solution>, OptimizationError> {
+    self.generation_limit = Some(generations);
+    self.try_optimize()
+  }
+
+  /// Runs SPEA-II `Optimizer` like [`Optimizer::optimize`] does, but returns
+  /// the nondominated solutions of the last archive along with why
+  /// optimization has terminated and how many generations it has run, see
+  /// [`OptimizationOutcome`].
+  ///
+  /// # Panics
+  ///
+  /// Panics if optimization fails with an [`OptimizationError`].
+  pub fn optimize_with_outcome(self) -> OptimizationOutcome<Solution> {
+    self
+      .try_optimize_with_outcome()
+      .unwrap_or_else(|e| panic!("{e}"))
+  }
+
+  /// Runs SPEA-II `Optimizer` like [`Optimizer::try_optimize`] does, but
+  /// returns the nondominated solutions of the last archive along with why
+  /// optimization has terminated and how many generations it has run, see
+  /// [`OptimizationOutcome`].
+  pub fn try_optimize_with_outcome(
+    self,
+  ) -> Result<OptimizationOutcome<Solution>, OptimizationError> {
+    self
+      .run_best_effort()
+      .map(|(solutions, _, terminated)| terminated.into_outcome(solutions))
+      .map_err(|failure| failure.error)
+  }
+
+  /// Runs SPEA-II `Optimizer` like [`Optimizer::optimize`] does, but pairs
+  /// the nondominated solutions of the last archive with their scores, so
+  /// that they can seed another optimizer with [`population_with_scores`]
+  /// without being tested again.
+  ///
+  /// # Panics
+  ///
+  /// Panics if optimization fails with an [`OptimizationError`].
+  ///
+  /// [`population_with_scores`]: Spea2Builder::population_with_scores
+  pub fn optimize_with_scores(self) -> Vec<(Solution, Scores<OBJECTIVE_NUM>)> {
+    self
+      .try_optimize_with_scores()
+      .unwrap_or_else(|e| panic!("{e}"))
+  }
+
+  /// Runs SPEA-II `Optimizer` like [`Optimizer::try_optimize`] does, but
+  /// pairs the nondominated solutions of the last archive with their scores,
+  /// see [`Self::optimize_with_scores`].
+  pub fn try_optimize_with_scores(
+    self,
+  ) -> Result<Vec<(Solution, Scores<OBJECTIVE_NUM>)>, OptimizationError> {
+    self
+      .run_best_effort()
+      .map(|(solutions, scores, _)| solutions.into_iter().zip(scores).collect())
+      .map_err(|failure| failure.error)
+  }
+
+  /// Returns the nondominated solutions of the last archive with their scores
+  /// and how optimization has terminated, or the archive of the last completed
+  /// generation along with an error if optimization fails, see
+  /// [`Optimizer::try_optimize_best_effort`].
+  fn run_best_effort(
+    mut self,
+  ) -> Result<
+    (Vec<Solution>, Vec<Scores<OBJECTIVE_NUM>>, Terminated),
+    OptimizationFailure<Solution>,
+  > {
+    let mut population = std::mem::take(&mut self.population);
+    let mut population_scores = Vec::new();
+    let mut archive = Vec::new();
+    let mut archive_scores = Vec::new();
+    let event_sender = self.event_sender.clone();
+    let result = catch_operator_panics(
+      self.catch_operator_panics,
+      event_sender.as_ref(),
+      |current| {
+        self.run(
+          &mut population,
+          &mut population_scores,
+          &mut archive,
+          &mut archive_scores,
+          current,
+        )
+      },
+    );
+    let terminated = match result {
+      Ok(terminated) => terminated,
+      Err(error) => {
+        let population = if archive.is_empty() {
+          population
+        } else {
+          archive
+        };
+        return Err(OptimizationFailure { error, population });
       }
+    };
+
+    let (nondominated, scores) =
+      ParetoArchive::from((archive, archive_scores)).into_parts();
+    Ok((nondominated, scores, terminated))
+  }
+
+  /// Tests the initial `population` and evolves the `archive` until the
+  /// termination condition is met. The archive of the last completed
+  /// generation and the offsprings of the current one are kept in `archive`
+  /// and `population` with their scores, so the caller gets them even if
+  /// optimization fails.
+  fn run(
+    &mut self,
+    population: &mut Vec<Solution>,
+    population_scores: &mut Vec<Scores<OBJECTIVE_NUM>>,
+    archive: &mut Vec<Solution>,
+    archive_scores: &mut Vec<Scores<OBJECTIVE_NUM>>,
+    current: &mut CurrentOperator,
+  ) -> Result<Terminated, OptimizationError> {
+    let mut timings = self.profile.then(OperatorTimings::default);
+    if let (Some(observer), Some(labels)) =
+      (self.observer.as_mut(), &self.objective_labels)
+    {
+      current.call(OperatorKind::Observer, 0, || {
+        observer.label_objectives(labels)
+      });
     }
+    check_population(
+      population.len() + self.injected.len(),
+      self.event_sender.as_ref(),
+    );
+    // solutions set with scores are merged without testing
+    if !population.is_empty() {
+      *population_scores = {
+        let _timer = Timer::start(&mut timings, |t| &mut t.test);
+        let _phase = phase!("test", population_size = population.len());
+        current.call(OperatorKind::Tester, 0, || {
+          self.tester.execute_tests(population)
+        })
+      };
+      check_scores(
+        OperatorKind::Tester,
+        0,
+        population_scores.len(),
+        population.len(),
+        self.event_sender.as_ref(),
+      )?;
+    }
+    population.append(&mut self.injected);
+    population_scores.append(&mut self.injected_scores);
 
-    // count nondominated solutions
-    let nondommed_cnt = sol_idx_fit.iter().filter(|(_, f)| *f < 1.0).count();
-    let new_sol_idx_fit: Vec<(SolutionIndex, Distance)> =
-      if nondommed_cnt > self.archive_size {
-        // if there are more nondommed solutions than the archive can fit, truncate
-        // solutions iteratively by their distance to k-th neighbor
-        let mut nondom_idx_fit: Vec<_> =
-          sol_idx_fit.into_iter().filter(|(_, f)| *f < 1.0).collect();
-
-        // get vector of distances for each solution
-        let mut sol_distances = sorted_sol_distances(&nondom_idx_fit, &scores);
-        // while there are more solutions than the archive size...
-        while nondom_idx_fit.len() > self.archive_size {
-          // find index of a solution with smallest distance to another solution
-          let removed_sol_idx = sol_distances
-            .iter()
-            .enumerate()
-            .min_by(|(_, a), (_, b)| {
-              for (i, j) in a.1.iter().zip(b.1.iter()) {
-                match i.1.partial_cmp(&j.1).expect("NaN encountered") {
-                  Ordering::Less => return Ordering::Less,
-                  Ordering::Greater => return Ordering::Greater,
-                  Ordering::Equal => {}
-                }
-              }
-              Ordering::Equal
-            })
-            .expect("no solutions remain")
-            .0;
-          // remove distances of removed solutions from distance matrix
-          let removed_dist_idx = sol_distances.remove(removed_sol_idx).0;
-          // for each other row in the distance matrix...
-          for distances in sol_distances.iter_mut() {
-            // find and remove distances to the removed solution
-            let removed_idx = distances
-              .1
-              .iter()
-              .enumerate()
-              .find(|(_, (i, _))| *i == removed_dist_idx)
-              .expect("distance matrix has no entry for removed solution")
-              .0;
-            distances.1.remove(removed_idx);
+    let mut generation = 0;
+    let terminated = loop {
+      let _generation = phase!("generation", generation = generation);
+      let deadline = self
+        .generation_time_budget
+        .map(|budget| Instant::now() + budget);
+      let terminated = {
+        let _timer = Timer::start(&mut timings, |t| &mut t.terminate);
+        let phase =
+          phase!("terminate", population_size = archive.len(), terminated);
+        let terminated =
+          current.call(OperatorKind::Terminator, generation, || {
+            self.terminator.execute_termination(archive, archive_scores)
+          });
+        phase.record("terminated", terminated.is_stop());
+        terminated
+      };
+      if let TerminationDecision::Stop(reason) = terminated {
+        break Terminated::new(
+          TerminationReason::Terminator,
+          reason,
+          generation,
+        );
+      }
+      if self
+        .generation_limit
+        .is_some_and(|limit| generation >= limit)
+      {
+        break Terminated::generation_limit(generation);
+      }
+      if self
+        .cancel_flag
+        .as_ref()
+        .is_some_and(FlagTerminator::is_raised)
+      {
+        break Terminated::cancelled(generation);
+      }
+      if self.replacement == Replacement::MuCommaLambda {
+        archive.clear();
+        archive_scores.clear();
+      }
+      let archive_size = self.archive_size.size(generation);
+      // the population holds offsprings of this generation, except for the
+      // initial population
+      let offsprings_num = population.len();
+      let (mut accepted, mut accepted_scores) = match &self.acceptance {
+        Some(acceptance) if self.replacement == Replacement::MuPlusLambda => {
+          current.call(OperatorKind::Acceptance, generation, || {
+            accept_dominated(
+              acceptance.as_ref(),
+              archive,
+              archive_scores,
+              population,
+              population_scores,
+              generation,
+              archive_size,
+            )
+          })
+        }
+        _ => Default::default(),
+      };
+      // offsprings follow the archive in the merged population
+      let offsprings_start = archive.len();
+      archive.append(population);
+      archive_scores.append(population_scores);
+      assert_paired("merge", archive.len(), archive_scores.len());
+
+      check_population(
+        archive.len() + accepted.len(),
+        self.event_sender.as_ref(),
+      );
+
+      // accepted offsprings survive regardless of their fitness
+      let mut surviving_offsprings = accepted.len();
+      {
+        let _timer = Timer::start(&mut timings, |t| &mut t.truncate);
+        let phase =
+          phase!("truncate", population_size = archive.len(), survived);
+        let target = archive_size - accepted.len();
+        if self.feedback.is_some() && generation > 0 {
+          surviving_offsprings +=
+            select_tracking_offsprings(archive, offsprings_start, |tagged| {
+              self.truncate_archive(tagged, archive_scores, target)
+            });
+        } else {
+          self.truncate_archive(archive, archive_scores, target);
+        }
+        archive.append(&mut accepted);
+        archive_scores.append(&mut accepted_scores);
+        phase.record("survived", archive.len());
+      }
+      if let Some(feedback) = self.feedback.as_mut().filter(|_| generation > 0)
+      {
+        current.call(OperatorKind::Feedback, generation, || {
+          feedback.feedback(generation, offsprings_num, surviving_offsprings)
+        });
+      }
+      if let Some(fingerprint) = &self.parent_fingerprint {
+        let _timer = Timer::start(&mut timings, |t| &mut t.mutate);
+        let phase =
+          phase!("mutate_parents", population_size = archive.len(), changed);
+        let changed = mutate_survivors(
+          &self.mutator,
+          &self.tester,
+          fingerprint,
+          archive,
+          archive_scores,
+          generation,
+          current,
+          self.event_sender.as_ref(),
+        )?;
+        phase.record("changed", changed);
+      }
+      if let Some(constraint) = &self.constraint {
+        let _timer = Timer::start(&mut timings, |t| &mut t.constrain);
+        let _phase = phase!("constrain", population_size = archive.len());
+        check_scores(
+          OperatorKind::Constrainer,
+          generation,
+          current.call(OperatorKind::Constrainer, generation, || {
+            constraint.adapt(archive)
+          }),
+          archive.len(),
+          self.event_sender.as_ref(),
+        )?;
+      }
+      #[cfg(feature = "tracing")]
+      tracing::info!(
+        target: "moga",
+        generation,
+        population_size = archive.len(),
+        front_sizes = ?[self.scratch.nondominated_count],
+        "generation completed"
+      );
+      if let Some(observer) = self.observer.as_mut() {
+        current.call(OperatorKind::Observer, generation, || {
+          observer.observe(generation, archive, archive_scores)
+        });
+      }
+      if let Some(event_sender) = &self.event_sender {
+        event_sender.send_generation(
+          generation,
+          archive,
+          archive_scores,
+          self.objective_labels,
+        );
+      }
+      if let Some(directive) = self.pause.as_mut().and_then(|pause| {
+        current.call(OperatorKind::PauseHandler, generation, || {
+          pause.directive(generation, archive, archive_scores)
+        })
+      }) {
+        let injected = match directive {
+          Directive::Continue => None,
+          Directive::Stop => break Terminated::paused(generation + 1),
+          // the archive truncation doesn't prefer any point
+          Directive::AdjustReference(_) => None,
+          Directive::InjectSolutions(injected) => Some(injected),
+          Directive::Restart(fresh) => {
+            retain_front(archive, archive_scores);
+            Some(fresh)
           }
-          // remove solution by this index
-          nondom_idx_fit.remove(removed_sol_idx);
+        };
+        if let Some(mut injected) = injected {
+          let mut injected_scores = {
+            let _timer = Timer::start(&mut timings, |t| &mut t.test);
+            let _phase = phase!("test", population_size = injected.len());
+            current.call(OperatorKind::Tester, generation, || {
+              self.tester.execute_tests(&injected)
+            })
+          };
+          check_scores(
+            OperatorKind::Tester,
+            generation,
+            injected_scores.len(),
+            injected.len(),
+            self.event_sender.as_ref(),
+          )?;
+          archive.append(&mut injected);
+          archive_scores.append(&mut injected_scores);
         }
+      }
+      generation += 1;
 
-        debug_assert_eq!(nondom_idx_fit.len(), self.archive_size);
-        nondom_idx_fit
-      } else {
-        // calculate and add distance to the k-th neighbor to solutions' fitness
-        // values
-        let sol_distances = sorted_sol_distances(&sol_idx_fit, &scores);
-        let k = (sol_idx_fit.len() as f64).sqrt() as usize;
-        sol_distances.into_iter().for_each(|(idx, distances)| {
-          sol_idx_fit[idx].1 += 1.0 / (distances[k - 1].1 + 2.0);
+      let species = self
+        .speciation
+        .as_ref()
+        .map(|assigner| {
+          let offsprings =
+            self.max_offspring_per_generation.unwrap_or(archive.len());
+          speciate(assigner.as_ref(), archive, archive_scores, offsprings)
+        })
+        .unwrap_or_default();
+      // without species, the whole population is selected from at once
+      let whole = species.is_empty().then_some((0..archive.len(), None));
+      let groups = whole.into_iter().chain(
+        species
+          .into_iter()
+          .map(|species| (species.range, Some(species.quota))),
+      );
+      let mut created_solutions = Vec::new();
+      for (range, quota) in groups {
+        let mut selected_solutions = {
+          let _timer = Timer::start(&mut timings, |t| &mut t.select);
+          let phase = phase!("select", population_size = range.len(), selected);
+          let selected =
+            current.call(OperatorKind::Selector, generation, || {
+              self.selector.execute_selection(
+                &archive[range.clone()],
+                &archive_scores[range.clone()],
+              )
+            });
+          phase.record("selected", selected.len());
+          selected
+        };
+        check_selection(
+          self.selector.selection_contract(),
+          generation,
+          selected_solutions.len(),
+          self.event_sender.as_ref(),
+        )?;
+        selected_solutions
+          .iter_mut()
+          .for_each(|idx| *idx += range.start);
+        if selected_solutions.is_empty() {
+          match self.empty_selection {
+            EmptySelection::Fail => {
+              return Err(report_error(
+                OptimizationError::SelectionEmpty { generation },
+                self.event_sender.as_ref(),
+              ))
+            }
+            EmptySelection::SelectAll => selected_solutions = range.collect(),
+          }
+        }
+        let (limit, budget) = match quota {
+          Some(quota) => (Some(quota), quota_budget(self.offspring_budget)),
+          None => (self.max_offspring_per_generation, self.offspring_budget),
+        };
+        let mut created = {
+          let _timer = Timer::start(&mut timings, |t| &mut t.recombine);
+          let phase =
+            phase!("recombine", parents = selected_solutions.len(), offspring);
+          let created = current
+            .call(OperatorKind::Recombinator, generation, || {
+              recombine_within_limit(
+                &self.recombinator,
+                archive,
+                archive_scores,
+                &selected_solutions,
+                limit,
+                budget,
+                generation,
+              )
+            })
+            .map_err(|e| report_error(e, self.event_sender.as_ref()))?;
+          phase.record("offspring", created.len());
+          created
+        };
+        // recombinators that can't tell their number of offsprings in advance
+        // recombine all parents of a species
+        if let Some(quota) = quota {
+          created.truncate(quota);
+        }
+        if created_solutions.is_empty() {
+          created_solutions = created;
+        } else {
+          created_solutions.append(&mut created);
+        }
+      }
+      {
+        let _timer = Timer::start(&mut timings, |t| &mut t.mutate);
+        let _phase = phase!("mutate", offspring = created_solutions.len());
+        current.call(OperatorKind::Mutator, generation, || {
+          self.mutator.execute_mutations(&mut created_solutions)
         });
-        // sort and truncate solutions. if there are less solutions than the
-        // archive size, the archive will be partially filled
-        sol_idx_fit.sort_unstable_by(|a, b| {
-          a.1.partial_cmp(&b.1).expect("NaN encountered")
+      }
+      if let Some(repairer) = &self.repairer {
+        let _timer = Timer::start(&mut timings, |t| &mut t.repair);
+        let _phase = phase!("repair", offspring = created_solutions.len());
+        current.call(OperatorKind::Repairer, generation, || {
+          repairer.repair(&mut created_solutions)
         });
-        sol_idx_fit.truncate(self.archive_size);
-        sol_idx_fit
+      }
+      if let Some(dedup) = &self.dedup {
+        let _timer = Timer::start(&mut timings, |t| &mut t.dedup);
+        let phase =
+          phase!("dedup", offspring = created_solutions.len(), removed);
+        let removed =
+          current.call(OperatorKind::Deduplicator, generation, || {
+            dedup.dedup(archive, &mut created_solutions)
+          });
+        phase.record("removed", removed);
+        if let Some(observer) = self.observer.as_mut() {
+          current.call(OperatorKind::Observer, generation, || {
+            observer.duplicates_removed(generation, removed)
+          });
+        }
+        if let Some(event_sender) = &self.event_sender {
+          event_sender.send(OptimizerEvent::DuplicatesRemoved {
+            index: generation,
+            count: removed,
+          });
+        }
+      }
+      let (created_scores, untested) = {
+        let _timer = Timer::start(&mut timings, |t| &mut t.test);
+        let _phase = phase!("test", population_size = created_solutions.len());
+        current.call(OperatorKind::Tester, generation, || {
+          test_until(&self.tester, &mut created_solutions, deadline)
+        })
       };
+      check_scores(
+        OperatorKind::Tester,
+        generation,
+        created_scores.len(),
+        created_solutions.len(),
+        self.event_sender.as_ref(),
+      )?;
+      if untested > 0 {
+        if let Some(observer) = self.observer.as_mut() {
+          current.call(OperatorKind::Observer, generation, || {
+            observer.tests_timed_out(generation, untested)
+          });
+        }
+        if let Some(event_sender) = &self.event_sender {
+          event_sender.send(OptimizerEvent::TestsTimedOut {
+            index: generation,
+            count: untested,
+          });
+        }
+      }
+      if self.replacement == Replacement::MuCommaLambda {
+        let required = self.archive_size.size(generation);
+        if created_solutions.len() < required {
+          return Err(report_error(
+            OptimizationError::TooFewOffsprings {
+              generation,
+              offsprings: created_solutions.len(),
+              required,
+            },
+            self.event_sender.as_ref(),
+          ));
+        }
+      }
+      *population = created_solutions;
+      *population_scores = created_scores;
+    };
+
+    report_termination(timings, terminated.kind, self.event_sender.as_ref());
+    Ok(terminated)
+  }
+
+  /// Truncates `solutions` to the `archive_size` with the environmental
+  /// selection on their `scores`, as seen through the quantizer and the score
+  /// transforms.
+  fn truncate_archive<T>(
+    &mut self,
+    solutions: &mut Vec<T>,
+    scores: &mut Vec<Scores<OBJECTIVE_NUM>>,
+    archive_size: usize,
+  ) {
+    let (quantizer, transform) = (self.quantizer, self.score_transform);
+    match shadow_view(quantizer.as_ref(), transform.as_ref()) {
+      Some(view) => select_by(solutions, scores, view, |paired, viewed| {
+        self.environmental_selection(paired, viewed, archive_size)
+      }),
+      None => self.environmental_selection(solutions, scores, archive_size),
+    };
+  }
+
+  /// Environmental selection procedure of SPEA-II algorithm. Truncates
+  /// solutions and their scores in place down to the `archive_size`.
+  ///
+  /// Of solutions with exactly equal scores only the first one is kept.
+  /// Such duplicates neither dominate each other nor are apart, so they
+  /// would all be nondominated or equally fit, flood the archive and get
+  /// truncated at random at distance `0`, crowding out distinct solutions.
+  fn environmental_selection<T>(
+    &mut self,
+    solutions: &mut Vec<T>,
+    scores: &mut Vec<Scores<OBJECTIVE_NUM>>,
+    archive_size: usize,
+  ) {
+    let ScratchBuffers {
+      strength_values: strengths,
+      fitnesses,
+      candidates,
+      distances,
+      tree,
+      neighbors,
+      is_removed,
+      permutation,
+      is_survivor,
+      nondominated_count,
+      normalized,
+      seen_scores,
+    } = &mut self.scratch;
+    remove_exact_duplicates(solutions, scores, seen_scores, is_survivor);
+    let metric = &self.distance;
+    // scores that distances are measured between
+    let metric_scores = match metric {
+      DistanceMetric::NormalizedEuclidean => {
+        normalize(scores, normalized);
+        &normalized[..]
+      }
+      _ => &scores[..],
+    };
+
+    // each i-th value is a number of solutions that i-th solution dominates
+    let parallel = scores.len() >= PARALLEL_THRESHOLD;
+    strength_values(scores, parallel, strengths);
+    // each i-th value is a sum of strength values of solutions that dominate
+    // i-th solution
+    raw_fitnesses(scores, strengths, parallel, fitnesses);
+
+    // indices of nondominated solutions
+    candidates.clear();
+    candidates.extend(
+      (0..scores.len())
+        .filter(|&idx| fitnesses[idx] < 1.0)
+        .map(to_index),
+    );
+    *nondominated_count = candidates.len();
+    if candidates.len() > archive_size {
+      // if there are more nondommed solutions than the archive can fit,
+      // truncate solutions iteratively by their distance to k-th neighbor
+      distances.fill(candidates, metric_scores, |a, b| metric.between(a, b));
+      is_removed.clear();
+      is_removed.resize(candidates.len(), false);
+      // while there are more solutions than the archive size...
+      for _ in archive_size..candidates.len() {
+        // find a solution with smallest distance to another solution
+        let removed_idx = (0..candidates.len())
+          .filter(|&i| !is_removed[i])
+          .min_by(|&a, &b| {
+            let is_kept =
+              |(i, _): &&(SolutionIndex, _)| !is_removed[from_index(*i)];
+            let a_row = distances.row(a).iter().filter(is_kept);
+            let b_row = distances.row(b).iter().filter(is_kept);
+            a_row
+              .zip(b_row)
+              .map(|((_, a), (_, b))| {
+                a.partial_cmp(b).expect("NaN encountered")
+              })
+              .find(|ord| ord.is_ne())
+              .unwrap_or(Ordering::Equal)
+          })
+          .expect("no solutions remain");
+        // and mark it as removed instead of removing it from the matrix
+        is_removed[removed_idx] = true;
+      }
+      let mut is_removed = is_removed.iter();
+      candidates.retain(|_| !is_removed.next().expect("no flag for candidate"));
+      debug_assert_eq!(candidates.len(), archive_size);
+    } else {
+      // calculate and add distance to the k-th neighbor to solutions' fitness
+      // values
+      candidates.clear();
+      candidates.extend((0..scores.len()).map(to_index));
+      let k = (candidates.len() as f64).sqrt() as usize;
+      if let DistanceMetric::Custom(distance) = metric {
+        for (idx, p_sc) in scores.iter().enumerate() {
+          neighbors.clear();
+          neighbors.extend(
+            scores
+              .iter()
+              .enumerate()
+              .filter(|&(i, _)| i != idx)
+              .map(|(i, q_sc)| (i, distance(p_sc, q_sc))),
+          );
+          // a lone solution has no neighbors and gets no density
+          let Some(kth_idx) = k.checked_sub(1).filter(|&i| i < neighbors.len())
+          else {
+            continue;
+          };
+          let (_, kth, _) = neighbors
+            .select_nth_unstable_by(kth_idx, |a, b| {
+              a.1.partial_cmp(&b.1).expect("NaN encountered")
+            });
+          fitnesses[idx] += 1.0 / (kth.1 + 2.0);
+        }
+      } else {
+        tree.build(metric_scores);
+        for (idx, sc) in metric_scores.iter().enumerate() {
+          // the solution itself is among its nearest neighbors, unless it's
+          // tied with enough duplicates, then the farthest one is extra
+          tree.nearest(metric_scores, sc, k + 1, neighbors);
+          match neighbors.iter().position(|&(i, _)| i == idx) {
+            Some(own_pos) => {
+              neighbors.remove(own_pos);
+            }
+            None => {
+              neighbors.pop();
+            }
+          }
+          // a lone solution has no neighbors and gets no density
+          if let Some(&(_, kth)) =
+            k.checked_sub(1).and_then(|kth_idx| neighbors.get(kth_idx))
+          {
+            fitnesses[idx] += 1.0 / (kth + 2.0);
+          }
+        }
+      }
+      // sort and truncate solutions. if there are less solutions than the
+      // archive size, the archive will be partially filled
+      candidates.sort_unstable_by(|&a, &b| {
+        fitnesses[from_index(a)]
+          .partial_cmp(&fitnesses[from_index(b)])
+          .expect("NaN encountered")
+      });
+      candidates.truncate(archive_size);
+    }
 
     debug_assert!(
-      new_sol_idx_fit.len() <= self.archive_size,
+      candidates.len() <= archive_size,
       "new archive population size cannot be bigger than the archive size"
     );
 
-    let mut solutions = solutions.into_iter().map(Some).collect::<Vec<_>>();
-    new_sol_idx_fit
-      .into_iter()
-      .map(|(idx, _)| {
-        (
-          std::mem::take(&mut solutions[idx]).expect("must be something here"),
-          scores[idx],
-        )
+    let expected_order = CHECK_PAIRING.then(|| {
+      order_fingerprint(candidates.iter().map(|&i| &scores[from_index(i)]))
+    });
+
+    // move survivors to the front, discarded solutions to the back
+    let new_archive_size = candidates.len();
+    is_survivor.clear();
+    is_survivor.resize(solutions.len(), false);
+    candidates
+      .iter()
+      .for_each(|&idx| is_survivor[from_index(idx)] = true);
+    permutation.clear();
+    permutation.extend_from_slice(candidates);
+    permutation.extend(
+      (0..solutions.len())
+        .filter(|&idx| !is_survivor[idx])
+        .map(to_index),
+    );
+    apply_permutation(permutation, solutions, scores);
+    solutions.truncate(new_archive_size);
+    scores.truncate(new_archive_size);
+    assert_paired("environmental selection", solutions.len(), scores.len());
+    if let Some(expected_order) = expected_order {
+      assert_eq!(
+        order_fingerprint(scores.iter()),
+        expected_order,
+        "scores were reordered apart from their solutions"
+      );
+    }
+  }
+}
+
+/// Whether pairing of solutions and their scores is checked, which is in
+/// debug builds or with `paranoid` feature enabled.
+const CHECK_PAIRING: bool = cfg!(any(debug_assertions, feature = "paranoid"));
+
+/// Asserts that there is one array of scores per solution after the `phase`,
+/// if [`CHECK_PAIRING`] is set.
+fn assert_paired(phase: &str, solutions: usize, scores: usize) {
+  if CHECK_PAIRING {
+    assert_eq!(
+      solutions, scores,
+      "number of scores differs from the number of solutions after {phase}"
+    );
+  }
+}
+
+/// Returns a fingerprint of `scores` in their order, which changes if the
+/// order of scores changes.
+fn order_fingerprint<'a, const N: usize>(
+  scores: impl Iterator<Item = &'a Scores<N>>,
+) -> u64 {
+  // a polynomial hash of FNV-1a hashes of scores
+  scores.fold(0, |fingerprint: u64, sc| {
+    let hash = sc.iter().fold(0xcbf2_9ce4_8422_2325, |hash, s| {
+      (hash ^ s.to_bits() as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    fingerprint
+      .wrapping_mul(0x9e37_79b9_7f4a_7c15)
+      .wrapping_add(hash)
+  })
+}
+
+/// Buffers that `Spea2` reuses between generations to avoid allocations.
+#[derive(Clone, Debug, Default)]
+struct ScratchBuffers<const N: usize> {
+  strength_values: Vec<StrengthValue>,
+  fitnesses: Vec<Fitness>,
+  /// Indices of solutions that may get into the archive.
+  candidates: Vec<SolutionIndex>,
+  /// Sorted distances between candidates.
+  distances: DistanceMatrix,
+  /// A tree over scores of solutions to find their nearest neighbors.
+  tree: KdTree,
+  /// Nearest neighbors of a solution.
+  neighbors: Vec<(usize, Distance)>,
+  /// Flags of candidates removed by the archive truncation.
+  is_removed: Vec<bool>,
+  permutation: Vec<SolutionIndex>,
+  is_survivor: Vec<bool>,
+  /// Number of nondominated solutions found by the last selection.
+  nondominated_count: usize,
+  /// Normalized scores of solutions for
+  /// [`DistanceMetric::NormalizedEuclidean`].
+  normalized: Vec<Scores<N>>,
+  /// Bits of distinct scores met by the removal of exact duplicates.
+  seen_scores: HashSet<[u32; N]>,
+}
+
+/// Removes solutions whose scores exactly equal scores of an earlier
+/// solution, keeping the order of the rest. `seen` and `is_duplicate` are
+/// buffers.
+fn remove_exact_duplicates<T, const N: usize>(
+  solutions: &mut Vec<T>,
+  scores: &mut Vec<Scores<N>>,
+  seen: &mut HashSet<[u32; N]>,
+  is_duplicate: &mut Vec<bool>,
+) {
+  seen.clear();
+  is_duplicate.clear();
+  // adding zero turns `-0.0` into `0.0`, which are equal scores
+  is_duplicate.extend(
+    scores
+      .iter()
+      .map(|sc| !seen.insert(sc.map(|s| (s + 0.0).to_bits()))),
+  );
+  if !is_duplicate.contains(&true) {
+    return;
+  }
+  let mut flags = is_duplicate.iter();
+  solutions.retain(|_| !flags.next().expect("no flag for solution"));
+  let mut flags = is_duplicate.iter();
+  scores.retain(|_| !flags.next().expect("no flag for scores"));
+}
+
+/// Counts strength value of each solution, i.e. the number of solutions it
+/// dominates, and stores them in `strength_values`. If `parallel` is set and
+/// `parallel` feature is enabled, counting is parallelized per solution.
+#[cfg_attr(not(feature = "parallel"), allow(unused_variables))]
+fn strength_values<const N: usize>(
+  scores: &[Scores<N>],
+  parallel: bool,
+  strength_values: &mut Vec<StrengthValue>,
+) {
+  #[cfg(feature = "parallel")]
+  if parallel {
+    return scores
+      .par_iter()
+      .map(|p_sc| {
+        scores
+          .iter()
+          .filter(|&q_sc| p_sc.dominance(q_sc) == Ordering::Less)
+          .count() as StrengthValue
       })
-      .unzip()
+      .collect_into_vec(strength_values);
+  }
+
+  strength_values.clear();
+  strength_values.resize(scores.len(), 0);
+  for (p_idx, p_sc) in scores.iter().enumerate() {
+    for (q_idx, q_sc) in scores.iter().enumerate().skip(p_idx + 1) {
+      match p_sc.dominance(q_sc) {
+        Ordering::Less => strength_values[p_idx] += 1,
+        Ordering::Greater => strength_values[q_idx] += 1,
+        Ordering::Equal => {}
+      }
+    }
   }
 }
 
-/// Calculates and returns sorted distances between solutions.
-/// Each element of returned vector contains index of a solution from
-/// `sol_indices` and sorted vector of distances to other solutions paired with
-/// index of those solutions.
-#[inline]
-fn sorted_sol_distances<const N: usize>(
-  sol_idx_fit: &[(SolutionIndex, Fitness)],
-  scores: &[[f32; N]],
-) -> Vec<(SolutionIndex, Vec<(SolutionIndex, Distance)>)> {
-  let mut sol_distances: Vec<_> = (0..sol_idx_fit.len())
-    .map(|i| (i, Vec::with_capacity(sol_idx_fit.len() - 1)))
-    .collect();
-
-  for i in 0..sol_idx_fit.len() {
-    let (p, rest) =
-      sol_idx_fit[i..].split_first().expect("no solutions remain");
-
-    for (j, q) in rest.iter().enumerate() {
-      let j = i + j + 1;
-      let p_sc = scores[p.0];
-      let q_sc = scores[q.0];
-      let d: Distance = p_sc
-        .iter()
-        .zip(q_sc)
-        .map(|(a, b)| (a - b).powf(2.0) as f64)
-        .sum();
-      sol_distances[i].1.push((j, d));
-      sol_distances[j].1.push((i, d));
+/// Computes raw fitness of each solution, i.e. the sum of strength values of
+/// solutions that dominate it, and stores them in `raw_fitnesses`. If
+/// `parallel` is set and `parallel` feature is enabled, computation is
+/// parallelized per solution.
+#[cfg_attr(not(feature = "parallel"), allow(unused_variables))]
+fn raw_fitnesses<const N: usize>(
+  scores: &[Scores<N>],
+  strength_values: &[StrengthValue],
+  parallel: bool,
+  raw_fitnesses: &mut Vec<Fitness>,
+) {
+  #[cfg(feature = "parallel")]
+  if parallel {
+    return scores
+      .par_iter()
+      .map(|p_sc| {
+        scores
+          .iter()
+          .zip(strength_values)
+          .filter(|(q_sc, _)| q_sc.dominance(p_sc) == Ordering::Less)
+          .map(|(_, strength)| f64::from(*strength))
+          .sum()
+      })
+      .collect_into_vec(raw_fitnesses);
+  }
+
+  raw_fitnesses.clear();
+  raw_fitnesses.resize(scores.len(), 0.0);
+  for (p_idx, p_sc) in scores.iter().enumerate() {
+    for (q_idx, q_sc) in scores.iter().enumerate().skip(p_idx + 1) {
+      match p_sc.dominance(q_sc) {
+        Ordering::Less => {
+          raw_fitnesses[q_idx] += f64::from(strength_values[p_idx])
+        }
+        Ordering::Greater => {
+          raw_fitnesses[p_idx] += f64::from(strength_values[q_idx])
+        }
+        Ordering::Equal => {}
+      }
+    }
+  }
+}
+
+/// Sorted distances between solutions, stored row after row in one
+/// contiguous buffer.
+#[derive(Clone, Debug, Default)]
+struct DistanceMatrix {
+  /// Distances to other solutions paired with their positions.
+  entries: Vec<(SolutionIndex, Distance)>,
+  /// Number of entries in each row.
+  row_len: usize,
+}
+
+impl DistanceMatrix {
+  /// Calculates sorted `distance`s between solutions with indices from
+  /// `sol_indices`. Each i-th row contains distances from i-th solution of
+  /// `sol_indices` to other solutions, paired with positions of those
+  /// solutions in `sol_indices`. The buffer is reused between calls.
+  fn fill<const N: usize>(
+    &mut self,
+    sol_indices: &[SolutionIndex],
+    scores: &[Scores<N>],
+    distance: impl Fn(&Scores<N>, &Scores<N>) -> Distance,
+  ) {
+    let len = sol_indices.len();
+    self.row_len = len.saturating_sub(1);
+    self.entries.clear();
+    self.entries.resize(len * self.row_len, (to_index(0), 0.0));
+
+    for (i, &p_idx) in sol_indices.iter().enumerate() {
+      for (j, &q_idx) in sol_indices.iter().enumerate().skip(i + 1) {
+        let d =
+          distance(&scores[from_index(p_idx)], &scores[from_index(q_idx)]);
+        // row `i` skips its own entry, so `j` goes to the previous column
+        self.entries[i * self.row_len + j - 1] = (to_index(j), d);
+        self.entries[j * self.row_len + i] = (to_index(i), d);
+      }
     }
 
-    sol_distances[i]
-      .1
-      .sort_unstable_by(|a, b| a.1.partial_cmp(&b.1).expect("NaN encountered"));
+    if self.row_len > 0 {
+      self.entries.chunks_exact_mut(self.row_len).for_each(|row| {
+        row.sort_unstable_by(|a, b| {
+          a.1.partial_cmp(&b.1).expect("NaN encountered")
+        })
+      });
+    }
+  }
+
+  /// Returns sorted distances from i-th solution to other solutions.
+  fn row(&self, i: usize) -> &[(SolutionIndex, Distance)] {
+    &self.entries[i * self.row_len..(i + 1) * self.row_len]
   }
-  sol_distances
 }
 
 impl<
@@ -370,69 +2045,1151 @@ impl<
   >
 {
   /// Runs SPEA-II `Optimizer` until the termination condition is met, then
-  /// returns nondominated solutions.
+  /// returns the last found population.
   ///
   /// # Panics
   ///
-  /// Panic if at some point the population becomes empty, or the number of
-  /// scores doesn't match the population size.
-  fn optimize(mut self) -> Vec<Solution> {
-    let mut population = std::mem::take(&mut self.population);
-    let mut population_scores = self.tester.execute_tests(&population);
+  /// Panics if optimization fails with an [`OptimizationError`], or if at
+  /// some point the population becomes empty.
+  fn optimize(self) -> Vec<Solution> {
+    self.try_optimize().unwrap_or_else(|e| panic!("{e}"))
+  }
 
-    let mut archive: Vec<Solution> = Vec::new();
-    let mut archive_scores: Vec<Scores<OBJECTIVE_NUM>> = Vec::new();
+  /// Runs SPEA-II `Optimizer` until the termination condition is met, then
+  /// returns nondominated solutions of the archive in their archive order,
+  /// or [`OptimizationError::SelectionEmpty`] if the selector selects no
+  /// solutions and the optimizer was built with [`EmptySelection::Fail`].
+  ///
+  /// # Panics
+  ///
+  /// Panic if at some point the population becomes empty.
+  fn try_optimize(self) -> Result<Vec<Solution>, OptimizationError> {
+    self
+      .try_optimize_best_effort()
+      .map_err(|failure| failure.error)
+  }
 
-    while !self
-      .terminator
-      .execute_termination(&archive, &archive_scores)
-    {
-      archive.append(&mut population);
-      archive_scores.append(&mut population_scores);
+  /// Runs SPEA-II `Optimizer` like [`Optimizer::try_optimize`] does, but
+  /// returns the archive along with an error if optimization fails.
+  ///
+  /// The archive is the whole archive of the last completed generation,
+  /// dominated solutions included, or the initial population if
+  /// optimization fails before the first archive is filled.
+  fn try_optimize_best_effort(
+    self,
+  ) -> Result<Vec<Solution>, OptimizationFailure<Solution>> {
+    self.run_best_effort().map(|(solutions, ..)| solutions)
+  }
+}
 
-      assert!(!archive.is_empty(), "the population is empty");
-      assert_eq!(
-        archive_scores.len(),
-        archive.len(),
-        "the number of calculated fitness scores doesn't match size of the population"
-      );
+impl<
+    Solution: 'static,
+    Tst: TestExecutor<Solution, OBJECTIVE_NUM, TstExecStrat>,
+    Sel: SelectionExecutor<Solution, OBJECTIVE_NUM, SelExecStrat>,
+    Rec: RecombinationExecutor<Solution, PARENT_NUM, OFFSPRING_NUM, RecExecStrat>,
+    Mut: MutationExecutor<Solution, MutExecStrat>,
+    Ter: TerminationExecutor<Solution, OBJECTIVE_NUM, TerExecStrat>,
+    TstExecStrat,
+    TerExecStrat,
+    SelExecStrat,
+    MutExecStrat,
+    RecExecStrat,
+    const OBJECTIVE_NUM: usize,
+    const PARENT_NUM: usize,
+    const OFFSPRING_NUM: usize,
+  > Spawnable<Solution, OBJECTIVE_NUM>
+  for Spea2<
+    Solution,
+    Tst,
+    Sel,
+    Rec,
+    Mut,
+    Ter,
+    TstExecStrat,
+    TerExecStrat,
+    SelExecStrat,
+    MutExecStrat,
+    RecExecStrat,
+    OBJECTIVE_NUM,
+    PARENT_NUM,
+    OFFSPRING_NUM,
+  >
+{
+  fn cancel_flag(&mut self) -> FlagTerminator {
+    self
+      .cancel_flag
+      .get_or_insert_with(FlagTerminator::new)
+      .clone()
+  }
+
+  fn add_observer(
+    &mut self,
+    observer: Box<dyn Observer<Solution, OBJECTIVE_NUM> + Send>,
+  ) {
+    self.observer = Some(match self.observer.take() {
+      Some(own) => Box::new((own, observer)),
+      None => observer,
+    });
+  }
+}
 
-      let (survived_solutions, survived_scores) =
-        self.environmental_selection(archive, archive_scores);
-      let selected_solutions = self
-        .selector
-        .execute_selection(&survived_solutions, &survived_scores);
-      let mut created_solutions =
-        self.recombinator.execute_recombination(selected_solutions);
-      self.mutator.execute_mutations(&mut created_solutions);
-      let created_scores = self.tester.execute_tests(&created_solutions);
-
-      archive = survived_solutions;
-      archive_scores = survived_scores;
-      population = created_solutions;
-      population_scores = created_scores;
+impl<
+    Solution,
+    Tst: TestExecutor<Solution, OBJECTIVE_NUM, TstExecStrat>,
+    Sel: SelectionExecutor<Solution, OBJECTIVE_NUM, SelExecStrat>,
+    Rec: RecombinationExecutor<Solution, PARENT_NUM, OFFSPRING_NUM, RecExecStrat>,
+    Mut: MutationExecutor<Solution, MutExecStrat>,
+    Ter: TerminationExecutor<Solution, OBJECTIVE_NUM, TerExecStrat>,
+    TstExecStrat,
+    TerExecStrat,
+    SelExecStrat,
+    MutExecStrat,
+    RecExecStrat,
+    const OBJECTIVE_NUM: usize,
+    const PARENT_NUM: usize,
+    const OFFSPRING_NUM: usize,
+  > Seedable<Solution, OBJECTIVE_NUM>
+  for Spea2<
+    Solution,
+    Tst,
+    Sel,
+    Rec,
+    Mut,
+    Ter,
+    TstExecStrat,
+    TerExecStrat,
+    SelExecStrat,
+    MutExecStrat,
+    RecExecStrat,
+    OBJECTIVE_NUM,
+    PARENT_NUM,
+    OFFSPRING_NUM,
+  >
+{
+  fn seed(&mut self, population: Vec<Solution>) {
+    if !population.is_empty() {
+      self.population = population;
     }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{
+    collections::HashSet,
+    sync::{
+      atomic::{AtomicUsize, Ordering},
+      mpsc,
+      Arc,
+      Mutex,
+    },
+  };
+
+  use rand::prelude::*;
+
+  use super::*;
+  use crate::{
+    dedup::Dedup,
+    selection::{AllSelector, FirstSelector},
+    termination::GenerationTerminator,
+  };
+
+  #[test]
+  fn test_parallel_fitness_matches_sequential() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let scores: Vec<Scores<3>> = (0..500)
+      .map(|_| [(); 3].map(|_| rng.gen_range(-10..=10) as f32))
+      .collect();
+
+    let (mut sequential, mut parallel) = (Vec::new(), Vec::new());
+    strength_values(&scores, false, &mut sequential);
+    strength_values(&scores, true, &mut parallel);
+    assert_eq!(sequential, parallel);
 
-    // if a flag is not set, the corresponding solution in the `archive` is
-    // dominated and is to be discarded
-    let mut selected_sols = vec![true; archive.len()];
-    for i in 0..selected_sols.len() - 1 {
-      if !selected_sols[i] {
-        continue;
-      }
-      for j in (i + 1)..selected_sols.len() {
-        match archive_scores[i].dominance(&archive_scores[j]) {
-          Ordering::Less => selected_sols[j] = false,
-          Ordering::Greater => selected_sols[i] = false,
-          Ordering::Equal => {}
+    let strength_values = sequential;
+    let (mut sequential, mut parallel) = (Vec::new(), Vec::new());
+    raw_fitnesses(&scores, &strength_values, false, &mut sequential);
+    raw_fitnesses(&scores, &strength_values, true, &mut parallel);
+    assert_eq!(sequential, parallel);
+  }
+
+  #[test]
+  fn test_distance_matrix() {
+    let scores = [[0.0, 0.0], [3.0, 0.0], [0.0, 1.0]];
+    let mut distances = DistanceMatrix::default();
+    distances.fill(&[0, 1, 2].map(to_index), &scores, |a, b| {
+      DistanceMetric::Euclidean.between(a, b)
+    });
+    assert_eq!(distances.row(0), [(to_index(2), 1.0), (to_index(1), 9.0)]);
+    assert_eq!(distances.row(1), [(to_index(0), 9.0), (to_index(2), 10.0)]);
+    assert_eq!(distances.row(2), [(to_index(0), 1.0), (to_index(1), 10.0)]);
+
+    distances.fill(&[to_index(2)], &scores, |a, b| {
+      DistanceMetric::Euclidean.between(a, b)
+    });
+    assert!(distances.row(0).is_empty());
+  }
+
+  /// Returns archive lengths of each generation of `Spea2` with the
+  /// `schedule` that optimizes a front of nondominated solutions.
+  fn archive_lengths(
+    schedule: ArchiveSchedule,
+    generations: usize,
+  ) -> Vec<usize> {
+    let lengths = Arc::new(Mutex::new(Vec::new()));
+    let observed = lengths.clone();
+    Spea2::builder()
+      .population((0..=30).map(|i| i as f32).collect())
+      .archive_size(schedule)
+      .tester(|x: &f32| [*x, x - 30.0])
+      .selector(AllSelector())
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .observer(move |_: usize, solutions: &[f32], _: &[Scores<2>]| {
+        observed.lock().unwrap().push(solutions.len())
+      })
+      .terminator(GenerationTerminator(generations))
+      .build()
+      .optimize();
+    let lengths = lengths.lock().unwrap().clone();
+    lengths
+  }
+
+  #[test]
+  fn test_archive_schedule() {
+    let linear = ArchiveSchedule::Linear {
+      from: 20,
+      to: 5,
+      over_generations: 3,
+    };
+    assert_eq!(archive_lengths(linear, 5), [20, 15, 10, 5, 5]);
+    assert_eq!(archive_lengths(8.into(), 3), [8, 8, 8]);
+    // a grown archive is filled with as many solutions as there are
+    let custom = ArchiveSchedule::Custom(Box::new(|g| [20, 5, 40, 10][g]));
+    assert_eq!(archive_lengths(custom, 4), [20, 5, 15, 10]);
+
+    let linear = ArchiveSchedule::Linear {
+      from: 1,
+      to: 4,
+      over_generations: 0,
+    };
+    assert_eq!(linear.size(0), 4);
+    assert_eq!(ArchiveSchedule::Custom(Box::new(|_| 0)).size(0), 1);
+  }
+
+  #[test]
+  fn test_dedup() {
+    let distinct = |solutions: &[f32]| {
+      solutions
+        .iter()
+        .map(|x| x.to_bits())
+        .collect::<HashSet<_>>()
+        .len()
+    };
+    // copies of a nondominated solution don't get into the archive even
+    // without deduplication, but they are tested
+    let (sender, receiver) = mpsc::channel();
+    let solutions = Spea2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .archive_size(10)
+      .tester(|x: &f32| [*x, x - 2.0])
+      .selector(AllSelector())
+      .recombinator(|_: &f32, _: &f32| 1.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(5))
+      .event_sender(sender)
+      .build()
+      .optimize();
+    assert_eq!(distinct(&solutions), solutions.len());
+    assert!(!receiver
+      .try_iter()
+      .any(|event| matches!(event, OptimizerEvent::DuplicatesRemoved { .. })));
+
+    let solutions = Spea2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .archive_size(10)
+      .tester(|x: &f32| [*x, x - 2.0])
+      .selector(AllSelector())
+      .recombinator(|_: &f32, _: &f32| 1.0)
+      .mutator(|_: &mut f32| {})
+      .dedup(Dedup::By(|x: &f32| x.to_bits()))
+      .terminator(GenerationTerminator(5))
+      .build()
+      .optimize();
+    assert_eq!(solutions, [0.0, 1.0, 2.0]);
+  }
+
+  #[test]
+  fn test_empty_selection() {
+    let result = Spea2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .archive_size(10)
+      .tester(|x: &f32| [*x, x - 2.0])
+      .selector(FirstSelector(0))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(3))
+      .build()
+      .try_optimize();
+    assert_eq!(
+      result,
+      Err(OptimizationError::SelectionEmpty { generation: 1 })
+    );
+
+    let solutions = Spea2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .archive_size(10)
+      .tester(|x: &f32| [*x, x - 2.0])
+      .selector(FirstSelector(0))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(3))
+      .empty_selection(EmptySelection::SelectAll)
+      .build()
+      .optimize();
+    // offsprings between nondominated `0.0..=2.0` have survived
+    assert!(solutions.iter().any(|x| x.fract() != 0.0));
+  }
+
+  #[test]
+  fn test_selection_contract() {
+    use crate::selection::{Expect, SelectionContract};
+
+    let result = Spea2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .archive_size(10)
+      .tester(|x: &f32| [*x, x - 2.0])
+      .selector(Expect::exactly(FirstSelector(4), 5))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(3))
+      .build()
+      .try_optimize();
+    assert_eq!(
+      result,
+      Err(OptimizationError::SelectionContractViolated {
+        generation: 1,
+        expected: SelectionContract::Exactly(5),
+        actual: 4,
+      })
+    );
+
+    // the contract is checked before an empty selection is handled
+    let result = Spea2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .archive_size(10)
+      .tester(|x: &f32| [*x, x - 2.0])
+      .selector(Expect::at_least(FirstSelector(0), 1))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(3))
+      .empty_selection(EmptySelection::SelectAll)
+      .build()
+      .try_optimize();
+    assert!(matches!(
+      result,
+      Err(OptimizationError::SelectionContractViolated { actual: 0, .. })
+    ));
+
+    let solutions = Spea2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .archive_size(10)
+      .tester(|x: &f32| [*x, x - 2.0])
+      .selector(Expect::at_most(FirstSelector(4), 4))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(3))
+      .build()
+      .optimize();
+    assert!(!solutions.is_empty() && solutions.len() <= 10);
+  }
+
+  #[test]
+  fn test_replacement() {
+    let optimize = |selector: FirstSelector, replacement| {
+      Spea2::builder()
+        .population((0..10).map(|i| i as f32).collect())
+        .archive_size(10)
+        .tester(|x: &f32| [*x, x - 2.0])
+        .selector(selector)
+        .recombinator(|x: &f32| x + 100.0)
+        .mutator(|_: &mut f32| {})
+        .terminator(GenerationTerminator(3))
+        .replacement(replacement)
+        .build()
+        .try_optimize()
+    };
+
+    // nondominated solutions are never replaced
+    let solutions = optimize(FirstSelector(10), Replacement::MuPlusLambda);
+    assert_eq!(solutions, Ok(vec![0.0, 1.0, 2.0]));
+
+    // but are when the archive is discarded
+    let solutions = optimize(FirstSelector(10), Replacement::MuCommaLambda);
+    assert_eq!(solutions, Ok(vec![200.0]));
+
+    assert_eq!(
+      optimize(FirstSelector(5), Replacement::MuCommaLambda),
+      Err(OptimizationError::TooFewOffsprings {
+        generation: 1,
+        offsprings: 5,
+        required: 10,
+      })
+    );
+  }
+
+  #[test]
+  fn test_empty_archive() {
+    // the archive is empty if optimization terminates before it's filled
+    let solutions = Spea2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .archive_size(10)
+      .tester(|x: &f32| [*x, x - 2.0])
+      .selector(AllSelector())
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(0))
+      .build()
+      .optimize();
+    assert!(solutions.is_empty());
+  }
+
+  #[test]
+  fn test_archive_is_not_retested() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    // solutions are unique ids paired with values
+    let next_id = AtomicUsize::new(10);
+    let tested = Mutex::new(Vec::new());
+    let solutions = Spea2::builder()
+      .population((0..10).map(|i| (i, i as f32)).collect())
+      .archive_size(6)
+      .tester(|xs: &[(usize, f32)]| {
+        tested.lock().unwrap().extend(xs.iter().map(|(id, _)| *id));
+        xs.iter().map(|(_, x)| [*x, x - 2.0]).collect()
+      })
+      .selector(AllSelector())
+      .recombinator(|a: &(usize, f32), b: &(usize, f32)| {
+        (next_id.fetch_add(1, Ordering::Relaxed), (a.1 + b.1) / 2.0)
+      })
+      .mutator(|_: &mut (usize, f32)| {})
+      .terminator(GenerationTerminator(10))
+      .build()
+      .optimize();
+    assert!(!solutions.is_empty());
+
+    let tested = tested.into_inner().unwrap();
+    // each solution is tested exactly once, right after it's created
+    assert_eq!(tested.len(), next_id.into_inner());
+    assert_eq!(tested.len(), tested.iter().collect::<HashSet<_>>().len());
+  }
+
+  #[test]
+  fn test_normalize() {
+    let mut normalized = Vec::new();
+    normalize(&[[0.0, 5.0], [2.0, 5.0], [1.0, 5.0]], &mut normalized);
+    assert_eq!(normalized, [[0.0, 0.0], [1.0, 0.0], [0.5, 0.0]]);
+  }
+
+  #[test]
+  fn test_distance_metric() {
+    // a front with objectives of different scales
+    const FRONT: [Scores<2>; 4] =
+      [[0.0, 1000.0], [0.1, 500.0], [0.5, 400.0], [1.0, 0.0]];
+    let optimize = |archive_size: usize, distance| {
+      Spea2::builder()
+        .population(vec![0, 1, 2, 3])
+        .archive_size(archive_size)
+        .tester(|&i: &usize| FRONT.get(i).copied().unwrap_or([1e4, 1e4]))
+        .selector(AllSelector())
+        // offsprings are dominated by the whole front
+        .recombinator(|&i: &usize| i + 10)
+        .mutator(|_: &mut usize| {})
+        .terminator(GenerationTerminator(1))
+        .distance(distance)
+        .build()
+        .optimize()
+    };
+    // raw distances are dominated by the second objective
+    assert_eq!(optimize(3, DistanceMetric::Euclidean), [0, 1, 3]);
+    assert_eq!(optimize(3, DistanceMetric::NormalizedEuclidean), [0, 2, 3]);
+    let squared_euclidean = |a: &Scores<2>, b: &Scores<2>| {
+      a.iter().zip(b).map(|(a, b)| f64::from(a - b).powi(2)).sum()
+    };
+    assert_eq!(
+      optimize(3, DistanceMetric::Custom(Box::new(squared_euclidean))),
+      [0, 1, 3]
+    );
+    // density of solutions is measured with the custom distance, too, and
+    // orders the archive
+    assert_eq!(
+      optimize(10, DistanceMetric::Custom(Box::new(squared_euclidean))),
+      optimize(10, DistanceMetric::Euclidean)
+    );
+  }
+
+  #[test]
+  fn test_environmental_selection_keeps_pairing() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for (archive_size, distance) in [
+      (5, DistanceMetric::Euclidean),
+      (5, DistanceMetric::NormalizedEuclidean),
+      (40, DistanceMetric::Euclidean),
+      (40, DistanceMetric::NormalizedEuclidean),
+    ] {
+      let mut optimizer = Spea2::builder()
+        .population(vec![vec![0.0, 0.0]])
+        .archive_size(archive_size)
+        .tester(|sol: &Vec<f32>| [sol[0], sol[1]])
+        .selector(AllSelector())
+        .recombinator(|sol: &Vec<f32>| sol.clone())
+        .mutator(|_: &mut Vec<f32>| {})
+        .terminator(GenerationTerminator(0))
+        .distance(distance)
+        .build();
+      let mut scores: Vec<Scores<2>> = (0..60)
+        .map(|_| [rng.gen_range(0..10) as f32, rng.gen_range(0..10) as f32])
+        .collect();
+      // solutions of heap-allocated copies of their scores
+      let mut solutions: Vec<Vec<f32>> =
+        scores.iter().map(|sc| sc.to_vec()).collect();
+      optimizer.environmental_selection(
+        &mut solutions,
+        &mut scores,
+        archive_size,
+      );
+      assert_eq!(solutions.len(), archive_size);
+      assert_eq!(solutions.len(), scores.len());
+      assert!(solutions.iter().zip(&scores).all(|(sol, sc)| sol == sc));
+    }
+  }
+
+  #[test]
+  fn test_environmental_selection_collapses_duplicates() {
+    // 90 copies of a boundary solution and 10 distinct solutions of a front
+    let scores: Vec<_> = (0..10)
+      .map(|i| [i as f32, 9.0 - i as f32])
+      .chain(std::iter::repeat_n([0.0, 9.0], 90))
+      .collect();
+    let mut optimizer = Spea2::builder()
+      .population(vec![0])
+      .archive_size(10)
+      .tester(|i: &usize| scores[*i])
+      .selector(AllSelector())
+      .recombinator(|i: &usize| *i)
+      .mutator(|_: &mut usize| {})
+      .terminator(GenerationTerminator(0))
+      .build();
+    let (mut solutions, mut archive_scores) =
+      ((0..100).collect(), scores.clone());
+    optimizer.environmental_selection(&mut solutions, &mut archive_scores, 10);
+    solutions.sort();
+    assert_eq!(solutions, (0..10).collect::<Vec<_>>());
+
+    // the first of duplicates is kept, even if the archive isn't filled
+    let (mut solutions, mut archive_scores) =
+      ((0..100).collect(), scores.clone());
+    optimizer.environmental_selection(&mut solutions, &mut archive_scores, 50);
+    assert_eq!(solutions.len(), 10);
+    assert!(!solutions.iter().any(|&i| i >= 10));
+    let mut solutions = vec!['a', 'b', 'c'];
+    let mut archive_scores = vec![[-0.0, 1.0], [1.0, 0.0], [0.0, 1.0]];
+    optimizer.environmental_selection(&mut solutions, &mut archive_scores, 3);
+    assert_eq!(solutions, ['a', 'b']);
+  }
+
+  #[test]
+  fn test_quantized_environmental_selection() {
+    // the third solution is better than the second one by an objective only
+    // because of noise, and it's farther from the others
+    let scores = vec![[0.0, 1.5], [1.0002, 1.0], [3.0, 0.9999999], [4.0, 0.0]];
+    let mut optimizer = Spea2::builder()
+      .population(vec![0])
+      .archive_size(3)
+      .tester(|i: &usize| scores[*i])
+      .selector(AllSelector())
+      .recombinator(|i: &usize| *i)
+      .mutator(|_: &mut usize| {})
+      .terminator(GenerationTerminator(0))
+      .quantizer(ScoreQuantizer::new([1e-3, 1e-3]))
+      .build();
+    let (mut solutions, mut archive_scores) =
+      (vec![0, 1, 2, 3], scores.clone());
+    optimizer.environmental_selection(&mut solutions, &mut archive_scores, 3);
+    solutions.sort();
+    assert_eq!(solutions, [0, 2, 3]);
+
+    let (mut solutions, mut archive_scores) =
+      (vec![0, 1, 2, 3], scores.clone());
+    let quantizer = optimizer.quantizer.unwrap();
+    let view = shadow_view(Some(&quantizer), None).unwrap();
+    select_by(
+      &mut solutions,
+      &mut archive_scores,
+      view,
+      |paired, quantized| {
+        optimizer.environmental_selection(paired, quantized, 3)
+      },
+    );
+    assert_eq!(solutions.len(), 3);
+    assert!(!solutions.contains(&2));
+    // solutions keep their original scores
+    assert!(solutions
+      .iter()
+      .zip(&archive_scores)
+      .all(|(&i, sc)| *sc == scores[i]));
+  }
+
+  #[test]
+  fn test_order_fingerprint() {
+    let scores = [[1.0, 2.0], [3.0, 4.0], [5.0, 6.0]];
+    let fingerprint = order_fingerprint(scores.iter());
+    assert_eq!(fingerprint, order_fingerprint(scores.iter()));
+    assert_ne!(fingerprint, order_fingerprint(scores.iter().rev()));
+    assert_ne!(fingerprint, order_fingerprint(scores[..2].iter()));
+  }
+
+  #[test]
+  fn test_offspring_score_count_mismatch() {
+    // offsprings are given only one score
+    let tester = |xs: &[f32]| match xs.len() {
+      10 => xs.iter().map(|x| [*x, x - 2.0]).collect(),
+      _ => vec![[0.0, 0.0]],
+    };
+    let result = Spea2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .archive_size(10)
+      .tester(tester)
+      .selector(FirstSelector(3))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(3))
+      .build()
+      .try_optimize();
+    assert_eq!(
+      result,
+      Err(OptimizationError::ScoreCountMismatch {
+        operator: OperatorKind::Tester,
+        generation: 1,
+        actual: 1,
+        expected: 3,
+      })
+    );
+  }
+
+  #[test]
+  fn test_pause_every() {
+    let solutions = Spea2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .archive_size(10)
+      .tester(|x: &f32| [*x, x - 2.0])
+      .selector(FirstSelector(4))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(10))
+      .pause_every(3, |generation, _: &[&f32], scores: &[Scores<2>]| {
+        match generation {
+          2 => Directive::InjectSolutions(vec![-0.5, 0.5]),
+          5 => {
+            // injected solutions made it into the archive
+            assert!(scores.contains(&[0.5, -1.5]));
+            Directive::Stop
+          }
+          _ => panic!("optimizer didn't stop at generation {generation}"),
+        }
+      })
+      .build()
+      .optimize();
+    assert!(solutions.contains(&0.5));
+    assert!(!solutions.contains(&-0.5));
+  }
+
+  #[test]
+  fn test_optimize_n() {
+    let optimize = |generations, terminate_at| {
+      let calls = Arc::new(Mutex::new(0));
+      let terminator = {
+        let calls = calls.clone();
+        move |_: &[f32], _: &[Scores<2>]| {
+          let mut calls = calls.lock().unwrap();
+          *calls += 1;
+          *calls > terminate_at
+        }
+      };
+      Spea2::builder()
+        .population(vec![1.0, 2.0, 3.0])
+        .archive_size(3)
+        .tester(|x: &f32| [*x, 3.0 - x])
+        .selector(AllSelector())
+        .recombinator(|x: &f32| *x)
+        .mutator(|_: &mut f32| {})
+        .terminator(terminator)
+        .build()
+        .optimize_n(generations);
+      let calls = *calls.lock().unwrap();
+      calls
+    };
+
+    assert_eq!(optimize(3, usize::MAX), 4);
+    assert_eq!(optimize(3, 1), 2);
+  }
+
+  #[test]
+  fn test_mutate_parents() {
+    let tests = AtomicUsize::new(0);
+    let solutions = Spea2::builder()
+      .population(vec![1.0, 2.0, 3.0])
+      .archive_size(3)
+      .tester(|x: &f32| {
+        tests.fetch_add(1, Ordering::Relaxed);
+        [*x, 10.0 - x]
+      })
+      .selector(AllSelector())
+      .recombinator(|x: &f32| *x)
+      .mutator(|x: &mut f32| {
+        if *x > 2.0 {
+          *x -= 1.0
+        }
+      })
+      .mutate_parents(|x: &f32| x.to_bits())
+      .terminator(GenerationTerminator(1))
+      .build()
+      .optimize_with_scores();
+    // 3 initial solutions, 3 offspring and the only changed survivor
+    assert_eq!(tests.load(Ordering::Relaxed), 7);
+    assert!(solutions
+      .iter()
+      .all(|(x, sc)| *x <= 2.0 && *sc == [*x, 10.0 - x]));
+  }
+
+  #[test]
+  fn test_population_with_scores() {
+    let seeds = Spea2::builder()
+      .population(vec![1.0, 2.0, 3.0])
+      .archive_size(3)
+      .tester(|x: &f32| [*x, 3.0 - x])
+      .selector(AllSelector())
+      .recombinator(|x: &f32| *x)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(2))
+      .build()
+      .optimize_with_scores();
+    assert_eq!(seeds.len(), 3);
+    assert!(seeds.iter().all(|(x, sc)| *sc == [*x, 3.0 - x]));
+
+    let tests = AtomicUsize::new(0);
+    let tester = |x: &f32| {
+      tests.fetch_add(1, Ordering::Relaxed);
+      [*x, 3.0 - x]
+    };
+    let solutions = Spea2::builder()
+      .population_with_scores(seeds.clone())
+      .archive_size(3)
+      .tester(tester)
+      .selector(AllSelector())
+      .recombinator(|x: &f32| *x)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(1))
+      .build()
+      .optimize_with_scores();
+    // the offsprings of the first generation are tested, but not the seeds
+    assert_eq!(tests.load(Ordering::Relaxed), 3);
+    assert_eq!(solutions.len(), 3);
+
+    tests.store(0, Ordering::Relaxed);
+    Spea2::builder()
+      .population(vec![0.5])
+      .population_with_scores(seeds)
+      .archive_size(4)
+      .tester(tester)
+      .selector(AllSelector())
+      .recombinator(|x: &f32| *x)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(0))
+      .build()
+      .optimize();
+    assert_eq!(tests.load(Ordering::Relaxed), 1);
+  }
+
+  #[test]
+  fn test_optimize_with_outcome() {
+    let terminator = FlagTerminator::new();
+    let flag = terminator.clone();
+    let outcome = Spea2::builder()
+      .population(vec![1.0, 2.0, 3.0])
+      .archive_size(3)
+      .tester(|x: &f32| [*x, 3.0 - x])
+      .selector(AllSelector())
+      .recombinator(|x: &f32| *x)
+      .mutator(|_: &mut f32| {})
+      .terminator(terminator)
+      .observer(move |generation: usize, _: &[f32], _: &[Scores<2>]| {
+        if generation == 2 {
+          flag.raise();
+        }
+      })
+      .build()
+      .optimize_with_outcome();
+    assert_eq!(outcome.solutions.len(), 3);
+    assert_eq!(outcome.termination, TerminationReason::Terminator);
+    assert_eq!(outcome.reason, "the flag has been raised");
+    assert_eq!(outcome.generations_run, 3);
+
+    let outcome = Spea2::builder()
+      .population(vec![1.0, 2.0, 3.0])
+      .archive_size(3)
+      .tester(|x: &f32| [*x, 3.0 - x])
+      .selector(AllSelector())
+      .recombinator(|x: &f32| *x)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(10))
+      .pause_every(2, |_, _: &[&f32], _: &[Scores<2>]| Directive::Stop)
+      .build()
+      .optimize_with_outcome();
+    assert_eq!(outcome.termination, TerminationReason::Paused);
+    assert_eq!(outcome.generations_run, 2);
+  }
+
+  #[test]
+  fn test_from_operators() {
+    let population: Vec<f32> = vec![-1.0, 1.0, 2.0, 3.0];
+    let tester = |x: &f32| [*x, 3.0 - x];
+    let recombinator = |x: &f32, y: &f32| x + y;
+    let repairer = |x: &mut f32| *x = x.clamp(0.0, 3.0);
+    let operators = OperatorSet::new(
+      tester,
+      FirstSelector(2),
+      recombinator,
+      |_: &mut f32| {},
+      GenerationTerminator(3),
+    )
+    .with_repairer(repairer);
+    let built = Spea2::builder()
+      .population(population.clone())
+      .archive_size(4)
+      .tester(tester)
+      .selector(FirstSelector(2))
+      .recombinator(recombinator)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(3))
+      .repairer(repairer)
+      .build();
+    assert_eq!(
+      Spea2::from_operators(population, 4, operators).optimize(),
+      built.optimize()
+    );
+  }
+
+  #[test]
+  fn test_offspring_budget() {
+    let optimizer = |limit| {
+      Spea2::builder()
+        .population((0..30).map(|i| i as f32).collect())
+        .archive_size(30)
+        .tester(|x: &f32| [*x, -x])
+        .selector(AllSelector())
+        .recombinator(|a: &f32, b: &f32, c: &f32| (a + b + c) / 3.0)
+        .mutator(|_: &mut f32| {})
+        .terminator(GenerationTerminator(2))
+        .max_offspring_per_generation(limit)
+        .build()
+        .try_optimize()
+    };
+    assert_eq!(
+      optimizer(4059),
+      Err(OptimizationError::OffspringBudgetExceeded {
+        generation: 1,
+        projected: 4060,
+        limit: 4059,
+      })
+    );
+    assert!(optimizer(4060).is_ok());
+  }
+
+  #[test]
+  fn test_tiny_populations() {
+    use crate::selection::{
+      BoxedSelector,
+      RandomSelector,
+      ReferencePointSelector,
+      RouletteSelector,
+      TournamentSelectorWithReplacement,
+      TournamentSelectorWithoutReplacement,
+    };
+
+    let selectors = || -> Vec<BoxedSelector<f32, 2>> {
+      vec![
+        BoxedSelector::new(AllSelector()),
+        BoxedSelector::new(FirstSelector(0)),
+        BoxedSelector::new(FirstSelector(1)),
+        BoxedSelector::new(RandomSelector(2)),
+        BoxedSelector::new(RouletteSelector(2)),
+        BoxedSelector::new(TournamentSelectorWithoutReplacement(2, 2)),
+        BoxedSelector::new(TournamentSelectorWithReplacement(2, 2)),
+        BoxedSelector::new(ReferencePointSelector(2, [0.0; 2], [1.0; 2])),
+      ]
+    };
+    for size in 1..=3 {
+      for single_parent in [false, true] {
+        for (selector_idx, selector) in selectors().into_iter().enumerate() {
+          let population = (0..size).map(|i| i as f32).collect();
+          let recombinator = if single_parent {
+            BoxedRecombinator::new(|x: &f32| x + 0.5)
+          } else {
+            BoxedRecombinator::new(|x: &f32, y: &f32| (x + y) / 2.0)
+          };
+          // a lone solution has no neighbors to measure its density with
+          let distance = if size == 1 {
+            DistanceMetric::Custom(Box::new(|a: &Scores<2>, b: &Scores<2>| {
+              a.iter().zip(b).map(|(a, b)| f64::from(a - b).abs()).sum()
+            }))
+          } else {
+            DistanceMetric::Euclidean
+          };
+          let solutions = Spea2::builder()
+            .population(population)
+            .archive_size(size)
+            .tester(|x: &f32| [x.powf(2.0), (x - 2.0).powf(2.0)])
+            .selector(selector)
+            .recombinator(recombinator)
+            .mutator(|x: &mut f32| *x += 0.25)
+            .terminator(GenerationTerminator(10))
+            .empty_selection(EmptySelection::SelectAll)
+            .distance(distance)
+            .build()
+            .optimize();
+          assert!(
+            (1..=size).contains(&solutions.len()),
+            "population of {size}, selector #{selector_idx}, \
+             single parent: {single_parent}"
+          );
         }
       }
     }
+  }
+
+  #[test]
+  fn test_feedback() {
+    let feedback = |replacement| {
+      let records = Arc::new(Mutex::new(Vec::new()));
+      let recorded = Arc::clone(&records);
+      Spea2::builder()
+        .population(vec![0.0, 1.0])
+        .archive_size(2)
+        .tester(|x: &f32| [*x, *x])
+        .selector(AllSelector())
+        .recombinator(|x: &f32| x + 10.0)
+        .mutator(|_: &mut f32| {})
+        .terminator(GenerationTerminator(3))
+        .feedback(move |generation, offsprings, survived| {
+          recorded
+            .lock()
+            .unwrap()
+            .push((generation, offsprings, survived))
+        })
+        .replacement(replacement)
+        .build()
+        .optimize();
+      Arc::into_inner(records).unwrap().into_inner().unwrap()
+    };
+    // the initial population isn't made of offsprings
+    assert_eq!(feedback(Replacement::MuPlusLambda), [(1, 2, 0), (2, 2, 0)]);
+    assert_eq!(feedback(Replacement::MuCommaLambda), [(1, 2, 2), (2, 2, 2)]);
+  }
+
+  #[test]
+  fn test_feedback_on_copied_parents() {
+    let records = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&records);
+    // offsprings are copies of their parents flagged as offsprings, which the
+    // tester ignores, so they are duplicates of the archive
+    let solutions = Spea2::builder()
+      .population(vec![(0.0, false)])
+      .archive_size(1)
+      .tester(|&(x, _): &(f32, bool)| [x, x])
+      .selector(AllSelector())
+      .recombinator(|&(x, _): &(f32, bool)| (x, true))
+      .mutator(|_: &mut (f32, bool)| {})
+      .terminator(GenerationTerminator(2))
+      .feedback(move |generation, offsprings, survived| {
+        recorded
+          .lock()
+          .unwrap()
+          .push((generation, offsprings, survived))
+      })
+      .build()
+      .optimize();
+    assert_eq!(solutions, [(0.0, false)]);
+    assert_eq!(*records.lock().unwrap(), [(1, 1, 0)]);
+  }
+
+  #[test]
+  fn test_speciation() {
+    use crate::speciation::ThresholdSpeciation;
+
+    let crossed = Arc::new(AtomicUsize::new(0));
+    let counted = Arc::clone(&crossed);
+    // two clusters of solutions far from each other
+    let population: Vec<f32> = (0..10)
+      .flat_map(|i| [-10.0 - i as f32 * 0.1, 10.0 + i as f32 * 0.1])
+      .collect();
+    let solutions = Spea2::builder()
+      .population(population)
+      .archive_size(20)
+      .tester(|x: &f32| [(x + 10.0).powi(2), (x - 10.0).powi(2)])
+      .selector(AllSelector())
+      .recombinator(move |a: &f32, b: &f32| {
+        if (a - b).abs() > 5.0 {
+          counted.fetch_add(1, Ordering::Relaxed);
+        }
+        (a + b) / 2.0
+      })
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(5))
+      .speciation(ThresholdSpeciation::new(
+        |a: &f32, b: &f32| f64::from((a - b).abs()),
+        5.0,
+      ))
+      .build()
+      .optimize();
+    assert_eq!(crossed.load(Ordering::Relaxed), 0);
+    assert!(solutions.iter().any(|x| *x < 0.0));
+    assert!(solutions.iter().any(|x| *x > 0.0));
+  }
+
+  #[test]
+  fn test_acceptance() {
+    let optimize = |replacement| {
+      Spea2::builder()
+        .population(vec![0.0, 1.0])
+        .archive_size(2)
+        .tester(|x: &f32| [*x, *x])
+        .selector(AllSelector())
+        .recombinator(|x: &f32| x + 10.0)
+        .mutator(|_: &mut f32| {})
+        .terminator(GenerationTerminator(3))
+        .acceptance(|_: &Scores<2>, _: &Scores<2>, generation| generation == 1)
+        .replacement(replacement)
+        .build()
+        .optimize()
+    };
+    // offsprings of the first generation replace their parents, and only
+    // the best of them is nondominated
+    assert_eq!(optimize(Replacement::MuPlusLambda), [10.0]);
+    // acceptance isn't consulted when offsprings replace the archive anyway
+    assert_eq!(optimize(Replacement::MuCommaLambda), [20.0]);
+  }
+
+  #[test]
+  fn test_inject() {
+    let mut optimizer = Spea2::builder()
+      .population((5..15).map(|i| i as f32).collect())
+      .archive_size(10)
+      .tester(|x: &f32| [*x, x - 2.0])
+      .selector(FirstSelector(4))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(1))
+      .build();
+    optimizer.inject(vec![1.0; 3]).unwrap();
+    optimizer
+      .inject((20..50).map(|i| i as f32).collect())
+      .unwrap();
+    // the injected solution dominates all others, and its copies are
+    // exact duplicates
+    assert_eq!(optimizer.optimize(), [1.0]);
+  }
+
+  #[test]
+  fn test_dry_run_offspring_budget() {
+    let dry_run = |budget| {
+      Spea2::builder()
+        .population((0..10).map(|i| i as f32).collect())
+        .archive_size(10)
+        .tester(|x: &f32| [*x, x - 2.0])
+        .selector(FirstSelector(8))
+        .recombinator(|x: &f32, y: &f32, z: &f32| (x + y + z) / 3.0)
+        .mutator(|_: &mut f32| {})
+        .terminator(GenerationTerminator(1))
+        .max_offspring_per_generation(10)
+        .offspring_budget(budget)
+        .build()
+        .dry_run(8)
+    };
+    assert_eq!(
+      dry_run(OffspringBudget::Fail),
+      Err(OptimizationError::OffspringBudgetExceeded {
+        generation: 0,
+        projected: 56,
+        limit: 10,
+      })
+    );
+    let report = dry_run(OffspringBudget::Subsample { seed: 0 }).unwrap();
+    assert_eq!(report.offsprings, 10);
+  }
+
+  #[test]
+  fn test_dry_run() {
+    let optimizer = Spea2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .archive_size(10)
+      .tester(|x: &f32| [*x, x - 2.0])
+      .selector(FirstSelector(0))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(1))
+      .build();
+    assert_eq!(
+      optimizer.dry_run(5),
+      Err(OptimizationError::SelectionEmpty { generation: 0 })
+    );
+
+    let optimizer = Spea2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .archive_size(10)
+      .tester(|x: &f32| [*x, x - 2.0])
+      .selector(FirstSelector(4))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|x: &mut f32| *x = f32::NAN)
+      .terminator(GenerationTerminator(1))
+      .build();
+    let report = optimizer.dry_run(5).unwrap();
+    assert_eq!(
+      (report.sampled, report.selected, report.offsprings),
+      (5, 4, 6)
+    );
+    assert_eq!(report.anomalies, [DryRunAnomaly::OffspringNanScores {
+      offsprings: 6
+    }]);
+  }
+
+  /// Tests solutions, panicking on solutions greater than `8`.
+  fn panicking_test(x: &f32) -> [f32; 1] {
+    assert!(*x <= 8.0, "pathological solution {x}");
+    [1.0 / x]
+  }
 
-    archive
-      .into_iter()
-      .zip(selected_sols)
-      .filter_map(|(sol, is_selected)| is_selected.then_some(sol))
-      .collect()
+  #[test]
+  fn test_catch_operator_panics() {
+    let failure = Spea2::builder()
+      .population(vec![1.0, 2.0, 3.0])
+      .archive_size(3)
+      .tester(panicking_test)
+      .selector(AllSelector())
+      .recombinator(|x: &f32| x + 1.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(100))
+      .catch_operator_panics(true)
+      .build()
+      .try_optimize_best_effort()
+      .unwrap_err();
+    assert_eq!(failure.error, OptimizationError::OperatorPanicked {
+      operator: OperatorKind::Tester,
+      generation: 6,
+      payload_message: "pathological solution 9".to_string(),
+    });
+    let mut population = failure.population;
+    population.sort_by(f32::total_cmp);
+    assert_eq!(population, [6.0, 7.0, 8.0]);
   }
 }
@@ -0,0 +1,226 @@
+//! Pause points of interactive optimization.
+//!
+//! An optimizer built with `pause_every(generations, handler)` pauses after
+//! every `generations` generations and shows the current Pareto front to a
+//! [`PauseHandler`], such as a decision maker. The handler returns a
+//! [`Directive`] that the optimizer applies before resuming.
+//!
+//! # Examples
+//! ```
+//! use moga::{
+//!   optimizer::{nsga::Nsga2, Optimizer},
+//!   pause::Directive,
+//!   selection::RandomSelector,
+//!   termination::GenerationTerminator,
+//! };
+//!
+//! let solutions = Nsga2::builder()
+//!   .population(vec![10.0, 20.0, 30.0])
+//!   .tester(|x: &f32| [x.powf(2.0), (x - 2.0).powf(2.0)])
+//!   .selector(RandomSelector(3))
+//!   .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+//!   .mutator(|_: &mut f32| {})
+//!   .terminator(GenerationTerminator(100))
+//!   .pause_every(10, |_: usize, _: &[&f32], scores: &[[f32; 2]]| {
+//!     if scores.iter().any(|s| s[0] < 10.0) {
+//!       Directive::Stop
+//!     } else {
+//!       // suggest a solution the optimizer hasn't found yet
+//!       Directive::InjectSolutions(vec![1.0])
+//!     }
+//!   })
+//!   .build()
+//!   .optimize();
+//! assert!(solutions.contains(&1.0));
+//! ```
+
+use std::fmt::Debug;
+
+use crate::score::{pareto_front_indices, Scores};
+
+/// What an optimizer does after a pause.
+#[derive(Clone, PartialEq, Debug)]
+#[non_exhaustive]
+pub enum Directive<S, const N: usize> {
+  /// Resume optimization.
+  Continue,
+  /// Stop optimization and return the current solutions.
+  Stop,
+  /// Move the reference point of the optimizer's truncation, if it has one,
+  /// with [`Truncation::adjust_reference`], and resume optimization.
+  ///
+  /// [`Truncation::adjust_reference`]:
+  /// crate::truncation::Truncation::adjust_reference
+  AdjustReference(Scores<N>),
+  /// Test solutions with the optimizer's tester, merge them into the
+  /// population and resume optimization. Injected solutions may be selected
+  /// as parents of the next generation and compete with its offsprings for
+  /// survival, unless offsprings replace the population with
+  /// [`Replacement::MuCommaLambda`].
+  ///
+  /// [`Replacement::MuCommaLambda`]:
+  /// crate::optimizer::Replacement::MuCommaLambda
+  InjectSolutions(Vec<S>),
+  /// Keep the Pareto front of the population, replace the rest of it with
+  /// given solutions, tested with the optimizer's tester, and resume
+  /// optimization.
+  Restart(Vec<S>),
+}
+
+/// A handler of pause points of an optimizer. It receives the number of the
+/// generation after which the optimizer paused, solutions of the Pareto front
+/// of the population and their scores, and returns a [`Directive`].
+///
+/// # Examples
+/// Any closure that takes the generation number, solutions and their scores
+/// and returns a [`Directive`] is a `PauseHandler`.
+/// ```
+/// use moga::pause::Directive;
+///
+/// let h = |generation: usize, front: &[&f32], _: &[[f32; 2]]| {
+///   println!("generation {generation}: {} solutions", front.len());
+///   Directive::<f32, 2>::Continue
+/// };
+/// ```
+///
+/// **Note that you always can implement this trait instead of using closures.**
+pub trait PauseHandler<S, const N: usize> {
+  /// Handles a pause after the `generation` and returns what to do next.
+  fn pause(
+    &mut self,
+    generation: usize,
+    solutions: &[&S],
+    scores: &[Scores<N>],
+  ) -> Directive<S, N>;
+}
+
+impl<S, const N: usize, F> PauseHandler<S, N> for F
+where
+  F: FnMut(usize, &[&S], &[Scores<N>]) -> Directive<S, N>,
+{
+  fn pause(
+    &mut self,
+    generation: usize,
+    solutions: &[&S],
+    scores: &[Scores<N>],
+  ) -> Directive<S, N> {
+    self(generation, solutions, scores)
+  }
+}
+
+/// A [`PauseHandler`] of an optimizer with the number of generations between
+/// pauses, as set with `pause_every()` method of the optimizer's builder.
+pub struct PausePoints<S, const N: usize> {
+  every: usize,
+  handler: Box<dyn PauseHandler<S, N> + Send>,
+}
+
+impl<S, const N: usize> PausePoints<S, N> {
+  /// Creates pause points after every `every` generations.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `every` is `0`.
+  pub(crate) fn new(
+    every: usize,
+    handler: impl PauseHandler<S, N> + Send + 'static,
+  ) -> Self {
+    assert!(every > 0, "number of generations between pauses is zero");
+    Self {
+      every,
+      handler: Box::new(handler),
+    }
+  }
+
+  /// Shows the Pareto front of `solutions` to the handler if the optimizer
+  /// pauses after the `generation`, and returns the handler's directive.
+  pub(crate) fn directive(
+    &mut self,
+    generation: usize,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) -> Option<Directive<S, N>> {
+    if !(generation + 1).is_multiple_of(self.every) {
+      return None;
+    }
+    let front = pareto_front_indices(scores);
+    let front_solutions: Vec<_> =
+      front.iter().map(|&idx| &solutions[idx]).collect();
+    let front_scores: Vec<_> = front.iter().map(|&idx| scores[idx]).collect();
+    Some(
+      self
+        .handler
+        .pause(generation, &front_solutions, &front_scores),
+    )
+  }
+}
+
+/// Removes solutions that aren't in the Pareto front from `solutions` and
+/// their `scores`, keeping the order of the rest.
+pub(crate) fn retain_front<S, const N: usize>(
+  solutions: &mut Vec<S>,
+  scores: &mut Vec<Scores<N>>,
+) {
+  let front = pareto_front_indices(scores);
+  let mut in_front = vec![false; scores.len()];
+  front.iter().for_each(|&idx| in_front[idx] = true);
+  let mut flags = in_front.iter();
+  solutions.retain(|_| *flags.next().unwrap());
+  let mut flags = in_front.iter();
+  scores.retain(|_| *flags.next().unwrap());
+}
+
+impl<S, const N: usize> Debug for PausePoints<S, N> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("PausePoints")
+      .field("every", &self.every)
+      .finish_non_exhaustive()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_pause_points() {
+    let mut generations = Vec::new();
+    let mut points = PausePoints::new(
+      3,
+      |generation: usize, front: &[&u8], scores: &[Scores<2>]| {
+        assert_eq!(front, [&0, &2]);
+        assert_eq!(scores, [[0.0, 2.0], [2.0, 0.0]]);
+        Directive::InjectSolutions(vec![generation as u8])
+      },
+    );
+    let solutions = [0, 1, 2];
+    let scores = [[0.0, 2.0], [2.0, 2.0], [2.0, 0.0]];
+    for generation in 0..7 {
+      if let Some(directive) = points.directive(generation, &solutions, &scores)
+      {
+        generations.push(directive);
+      }
+    }
+    assert_eq!(generations, [
+      Directive::InjectSolutions(vec![2]),
+      Directive::InjectSolutions(vec![5])
+    ]);
+  }
+
+  #[test]
+  fn test_retain_front() {
+    let mut solutions = vec![0, 1, 2, 3];
+    let mut scores = vec![[2.0, 0.0], [2.0, 2.0], [0.0, 2.0], [-2.0, 0.0]];
+    retain_front(&mut solutions, &mut scores);
+    assert_eq!(solutions, [0, 2, 3]);
+    assert_eq!(scores, [[2.0, 0.0], [0.0, 2.0], [-2.0, 0.0]]);
+  }
+
+  #[test]
+  #[should_panic(expected = "number of generations between pauses is zero")]
+  fn test_pause_points_zero() {
+    PausePoints::new(0, |_: usize, _: &[&u8], _: &[Scores<1>]| {
+      Directive::Continue
+    });
+  }
+}
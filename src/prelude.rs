@@ -0,0 +1,142 @@
+//! Re-exports of operator traits, optimizers and [`Scores`].
+//!
+//! ```
+//! use moga::prelude::*;
+//! ```
+
+#[cfg(feature = "parallel")]
+pub use crate::operator::{ParBatch, ParEach};
+pub use crate::{
+  mutation::{Mutation, Mutator},
+  optimizer::{nsga::Nsga2, spea::Spea2, Optimizer},
+  recombination::{Recombination, Recombinator},
+  score::Scores,
+  selection::{Selection, Selector},
+  termination::{Termination, Terminator},
+  testing::{BatchTest, Test, Tester},
+};
+
+/// Returns the given [`Test`]. Wrap a closure into it to get an error where
+/// the closure is defined if it isn't a [`Test`].
+#[doc(hidden)]
+pub fn assert_test<S, const N: usize, T: Test<S, N>>(t: T) -> T {
+  t
+}
+
+/// Returns the given [`Tester`]. Wrap a closure into it to get an error where
+/// the closure is defined if it isn't a [`Tester`].
+#[doc(hidden)]
+pub fn assert_tester<S, const N: usize, T: Tester<S, N>>(t: T) -> T {
+  t
+}
+
+/// Returns the given [`Selection`]. Wrap a closure into it to get an error
+/// where the closure is defined if it isn't a [`Selection`].
+#[doc(hidden)]
+pub fn assert_selection<S, const N: usize, T: Selection<S, N>>(t: T) -> T {
+  t
+}
+
+/// Returns the given [`Selector`]. Wrap a closure into it to get an error
+/// where the closure is defined if it isn't a [`Selector`].
+#[doc(hidden)]
+pub fn assert_selector<S, const N: usize, T: Selector<S, N>>(t: T) -> T {
+  t
+}
+
+/// Returns the given [`Recombination`]. Wrap a closure into it to get an
+/// error where the closure is defined if it isn't a [`Recombination`].
+#[doc(hidden)]
+pub fn assert_recombination<
+  S,
+  const P: usize,
+  const O: usize,
+  T: Recombination<S, P, O>,
+>(
+  t: T,
+) -> T {
+  t
+}
+
+/// Returns the given [`Recombinator`]. Wrap a closure into it to get an error
+/// where the closure is defined if it isn't a [`Recombinator`].
+#[doc(hidden)]
+pub fn assert_recombinator<S, T: Recombinator<S>>(t: T) -> T {
+  t
+}
+
+/// Returns the given [`Mutation`]. Wrap a closure into it to get an error
+/// where the closure is defined if it isn't a [`Mutation`].
+#[doc(hidden)]
+pub fn assert_mutation<S, T: Mutation<S>>(t: T) -> T {
+  t
+}
+
+/// Returns the given [`Mutator`]. Wrap a closure into it to get an error
+/// where the closure is defined if it isn't a [`Mutator`].
+#[doc(hidden)]
+pub fn assert_mutator<S, T: Mutator<S>>(t: T) -> T {
+  t
+}
+
+/// Returns the given [`Termination`]. Wrap a closure into it to get an error
+/// where the closure is defined if it isn't a [`Termination`].
+#[doc(hidden)]
+pub fn assert_termination<S, const N: usize, T: Termination<S, N>>(t: T) -> T {
+  t
+}
+
+/// Returns the given [`Terminator`]. Wrap a closure into it to get an error
+/// where the closure is defined if it isn't a [`Terminator`].
+#[doc(hidden)]
+pub fn assert_terminator<S, const N: usize, T: Terminator<S, N>>(t: T) -> T {
+  t
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    optimizer::nsga::Nsga2For,
+    selection::RandomSelector,
+    termination::GenerationTerminator,
+  };
+
+  #[test]
+  fn test_assert_operators() {
+    let test = assert_test(|x: &f32| [*x, x - 2.0]);
+    let tester = assert_tester(|xs: &[f32]| {
+      xs.iter().map(|x| [*x, x - 2.0]).collect::<Vec<_>>()
+    });
+    let selection = assert_selection(|_: &f32, s: &Scores<2>| s[0] < 5.0);
+    let selector = assert_selector::<f32, 2, _>(RandomSelector(4));
+    let recombination = assert_recombination(|x: &f32, y: &f32| (x + y) / 2.0);
+    let recombinator = assert_recombinator(|xs: Vec<&f32>| {
+      xs.into_iter().map(|x| x * 2.0).collect::<Vec<_>>()
+    });
+    let mutation = assert_mutation(|x: &mut f32| *x += 0.1);
+    let mutator = assert_mutator(|xs: &mut [f32]| xs.reverse());
+    let termination = assert_termination(|_: &f32, s: &Scores<2>| s[0] == 0.0);
+    let terminator = assert_terminator::<f32, 2, _>(GenerationTerminator(3));
+
+    assert_eq!(test.test(&3.0), [3.0, 1.0]);
+    assert_eq!(Tester::test(&tester, &[3.0]), [[3.0, 1.0]]);
+    assert!(Selection::select(&selection, &1.0, &[1.0, 0.0]));
+    assert_eq!(recombination.recombine([&1.0, &3.0]), [2.0]);
+    assert_eq!(Recombinator::recombine(&recombinator, vec![&1.0]), [2.0]);
+    let mut xs = [1.0, 2.0];
+    mutator.mutate(&mut xs);
+    assert_eq!(xs, [2.0, 1.0]);
+    assert!(termination.terminate(&0.0, &[0.0, 1.0]));
+
+    let optimizer: Nsga2For<f32, 2, _, _, _, _, _> = Nsga2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .tester(test)
+      .selector(selector)
+      .recombinator(recombination)
+      .mutator(mutation)
+      .terminator(terminator)
+      .build();
+    assert_eq!(optimizer.optimize().len(), 10);
+  }
+}
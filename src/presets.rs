@@ -0,0 +1,386 @@
+//! Ready-made operator sets for quick starts.
+//!
+//! Each preset returns an [`OperatorSet`]: an initial population and
+//! selection, recombination, mutation and termination operators that work
+//! well together on a common representation of solutions. Pass its fields to
+//! the builder of any optimizer and add a tester of your problem:
+//!
+//! ```
+//! use moga::{
+//!   genomes::real::Bounds,
+//!   optimizer::{spea::Spea2, Optimizer},
+//!   presets,
+//!   rng::SplitRng,
+//! };
+//!
+//! let ops = presets::real_valued(
+//!   Bounds::uniform(2, -5.0..=5.0),
+//!   40,
+//!   50,
+//!   SplitRng::new(42),
+//! );
+//! let f1 = |x: &Vec<f32>| x[0].powi(2) + x[1].powi(2);
+//! let f2 = |x: &Vec<f32>| (x[0] - 2.0).powi(2);
+//! let solutions = Spea2::builder()
+//!   .population(ops.population)
+//!   .archive_size(40)
+//!   .tester(|x: &Vec<f32>| [f1(x), f2(x)])
+//!   .selector(ops.selector)
+//!   .recombinator(ops.recombinator)
+//!   .mutator(ops.mutator)
+//!   .terminator(ops.terminator)
+//!   .build()
+//!   .optimize();
+//! assert!(!solutions.is_empty());
+//! ```
+//!
+//! Presets are a starting point, not a substitute for operators tailored to
+//! the problem. All their random operators draw from generators derived from
+//! the given [`SplitRng`], so runs with the same seed repeat.
+//!
+//! Each generation, a preset selects parents by binary tournaments and
+//! recombines every pair of them into two offsprings, so it selects just
+//! enough parents for their pairs to create about as many offsprings as
+//! there are solutions in the population.
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng};
+
+use crate::{
+  genomes::{
+    binary::{BitFlipMutation, UniformCrossover},
+    permutation::{OrderCrossover, SwapMutation},
+    real::{Bounds, PolynomialMutation, SimulatedBinaryCrossover},
+  },
+  init::{from_generator, random_uniform},
+  mutation::{IndexedRandomMutation, RandomMutation},
+  recombination::{IndexedRandomRecombination, RandomRecombination},
+  rng::SplitRng,
+  selection::{SeededSelector, TournamentSelectorWithReplacement},
+  termination::GenerationTerminator,
+};
+
+/// A distribution index of [`SimulatedBinaryCrossover`] of
+/// [`real_valued`] presets.
+pub const CROSSOVER_DISTRIBUTION_INDEX: f32 = 15.0;
+
+/// A distribution index of [`PolynomialMutation`] of [`real_valued`]
+/// presets.
+pub const MUTATION_DISTRIBUTION_INDEX: f32 = 20.0;
+
+/// An initial population and operators to build an optimizer with. Fields
+/// are public, so that any of them can be replaced or passed to builders of
+/// different optimizers.
+#[derive(Debug)]
+pub struct OperatorSet<S, Sel, Rec, Mut, Ter> {
+  /// The initial population.
+  pub population: Vec<S>,
+  /// The selection operator.
+  pub selector: Sel,
+  /// The recombination operator.
+  pub recombinator: Rec,
+  /// The mutation operator.
+  pub mutator: Mut,
+  /// The termination operator.
+  pub terminator: Ter,
+}
+
+/// An [`OperatorSet`] of a preset with the recombination `R` and the
+/// mutation `M`.
+type Preset<S, R, M> = OperatorSet<
+  S,
+  SeededSelector<TournamentSelectorWithReplacement, StdRng>,
+  IndexedRandomRecombination<R, 2, 2>,
+  IndexedRandomMutation<M>,
+  GenerationTerminator,
+>;
+
+/// Returns the number of parents whose pairs create at least
+/// `population_size` offsprings, two per pair.
+fn parents_for(population_size: usize) -> usize {
+  (2..)
+    .find(|&k: &usize| k.saturating_mul(k - 1) >= population_size)
+    .unwrap()
+}
+
+/// Builds a preset of the `population` with the `recombination` and the
+/// `mutation` that runs for `generations`.
+fn preset<S, R, M>(
+  population: Vec<S>,
+  recombination: R,
+  mutation: M,
+  generations: usize,
+  rng: &SplitRng,
+) -> Preset<S, R, M>
+where
+  R: RandomRecombination<S, 2, 2>,
+  M: RandomMutation<S>,
+{
+  let parents = parents_for(population.len());
+  OperatorSet {
+    population,
+    selector: TournamentSelectorWithReplacement(parents, 2)
+      .with_rng(rng.stream("selection")),
+    recombinator: IndexedRandomRecombination::from_random(
+      rng.split("recombination"),
+      recombination,
+    ),
+    mutator: IndexedRandomMutation::from_random(
+      rng.split("mutation"),
+      mutation,
+    ),
+    terminator: GenerationTerminator(generations),
+  }
+}
+
+/// Returns operators for real vectors within `bounds`: a population of
+/// `population_size` vectors sampled uniformly within bounds, binary
+/// tournament selection, [`SimulatedBinaryCrossover`] and
+/// [`PolynomialMutation`] of one value per vector on average, which runs for
+/// `generations`. Random numbers are drawn from generators derived from the
+/// `rng`.
+///
+/// Distribution indices are [`CROSSOVER_DISTRIBUTION_INDEX`] and
+/// [`MUTATION_DISTRIBUTION_INDEX`], the common defaults of SBX and
+/// polynomial mutation. Compared with the [`bench`] harness against indices
+/// `5` and `30` of crossover and `5` and `100` of mutation, with NSGA-II on
+/// the DTLZ1, DTLZ2 and DTLZ7 [`problems`] of 2 objectives, they get median
+/// hypervolume and IGD close to the best ones on all three problems. Small
+/// mutation shifts stall on the multimodal DTLZ1, so the mutation index must
+/// not be too large. See the `nsga2_presets` example.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   genomes::real::Bounds,
+///   optimizer::{nsga::Nsga2, Optimizer},
+///   presets,
+///   rng::SplitRng,
+/// };
+///
+/// let bounds = Bounds::uniform(1, -10.0..=10.0);
+/// let ops = presets::real_valued(bounds.clone(), 20, 30, SplitRng::new(0));
+/// let solutions = Nsga2::builder()
+///   .population(ops.population)
+///   .tester(|x: &Vec<f32>| [x[0].powi(2), (x[0] - 2.0).powi(2)])
+///   .selector(ops.selector)
+///   .recombinator(ops.recombinator)
+///   .mutator(ops.mutator)
+///   .terminator(ops.terminator)
+///   .build()
+///   .optimize();
+/// assert!(solutions.iter().all(|x| bounds.contains(x)));
+/// ```
+///
+/// [`problems`]: crate::problems
+/// [`bench`]: crate::bench
+pub fn real_valued(
+  bounds: Bounds,
+  population_size: usize,
+  generations: usize,
+  rng: SplitRng,
+) -> Preset<Vec<f32>, SimulatedBinaryCrossover, PolynomialMutation> {
+  real_valued_with(
+    bounds,
+    population_size,
+    generations,
+    rng,
+    CROSSOVER_DISTRIBUTION_INDEX,
+    MUTATION_DISTRIBUTION_INDEX,
+  )
+}
+
+/// Returns the [`real_valued`] preset with the given distribution indices.
+fn real_valued_with(
+  bounds: Bounds,
+  population_size: usize,
+  generations: usize,
+  rng: SplitRng,
+  crossover_index: f32,
+  mutation_index: f32,
+) -> Preset<Vec<f32>, SimulatedBinaryCrossover, PolynomialMutation> {
+  let population =
+    random_uniform(&bounds, population_size, &mut rng.stream("population"));
+  preset(
+    population,
+    SimulatedBinaryCrossover::new(bounds.clone(), crossover_index),
+    PolynomialMutation::new(bounds, mutation_index),
+    generations,
+    &rng,
+  )
+}
+
+/// Returns operators for bit vectors of the `length`: a population of
+/// `population_size` random vectors, binary tournament selection,
+/// [`UniformCrossover`] and [`BitFlipMutation`] of one bit per vector on
+/// average, which runs for `generations`. Random numbers are drawn from
+/// generators derived from the `rng`.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   optimizer::{nsga::Nsga2, Optimizer},
+///   presets,
+///   rng::SplitRng,
+/// };
+///
+/// // count ones and zeros
+/// let ops = presets::binary(8, 20, 30, SplitRng::new(0));
+/// let solutions = Nsga2::builder()
+///   .population(ops.population)
+///   .tester(|x: &Vec<bool>| {
+///     let ones = x.iter().filter(|&&bit| bit).count() as f32;
+///     [ones, 8.0 - ones]
+///   })
+///   .selector(ops.selector)
+///   .recombinator(ops.recombinator)
+///   .mutator(ops.mutator)
+///   .terminator(ops.terminator)
+///   .build()
+///   .optimize();
+/// assert!(solutions.iter().all(|x| x.len() == 8));
+/// ```
+pub fn binary(
+  length: usize,
+  population_size: usize,
+  generations: usize,
+  rng: SplitRng,
+) -> Preset<Vec<bool>, UniformCrossover, BitFlipMutation> {
+  let mut population_rng = rng.stream("population");
+  let population = from_generator(population_size, |_| {
+    (0..length).map(|_| population_rng.gen()).collect()
+  });
+  preset(
+    population,
+    UniformCrossover,
+    BitFlipMutation(1.0 / length.max(1) as f64),
+    generations,
+    &rng,
+  )
+}
+
+/// Returns operators for permutations of indices `0..length`: a population
+/// of `population_size` random permutations, binary tournament selection,
+/// [`OrderCrossover`] and [`SwapMutation`] of one swap per permutation on
+/// average, which runs for `generations`. Random numbers are drawn from
+/// generators derived from the `rng`.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   optimizer::{nsga::Nsga2, Optimizer},
+///   presets,
+///   rng::SplitRng,
+/// };
+///
+/// // sort in ascending and in descending order
+/// let ops = presets::permutation(6, 20, 30, SplitRng::new(0));
+/// let solutions = Nsga2::builder()
+///   .population(ops.population)
+///   .tester(|x: &Vec<usize>| {
+///     let ascending = x.windows(2).filter(|w| w[0] < w[1]).count() as f32;
+///     [5.0 - ascending, ascending]
+///   })
+///   .selector(ops.selector)
+///   .recombinator(ops.recombinator)
+///   .mutator(ops.mutator)
+///   .terminator(ops.terminator)
+///   .build()
+///   .optimize();
+/// assert!(solutions.iter().all(|x| x.len() == 6));
+/// ```
+pub fn permutation(
+  length: usize,
+  population_size: usize,
+  generations: usize,
+  rng: SplitRng,
+) -> Preset<Vec<usize>, OrderCrossover, SwapMutation> {
+  let mut population_rng = rng.stream("population");
+  let population = from_generator(population_size, |_| {
+    let mut permutation: Vec<_> = (0..length).collect();
+    permutation.shuffle(&mut population_rng);
+    permutation
+  });
+  preset(
+    population,
+    OrderCrossover,
+    SwapMutation(1.0 / length.max(1) as f64),
+    generations,
+    &rng,
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    optimizer::{nsga::Nsga2, spea::Spea2, Optimizer},
+    problems::dtlz::Dtlz2,
+  };
+
+  #[test]
+  fn test_parents_for() {
+    assert_eq!(parents_for(0), 2);
+    assert_eq!(parents_for(2), 2);
+    assert_eq!(parents_for(3), 3);
+    assert_eq!(parents_for(100), 11);
+    assert_eq!(parents_for(110), 11);
+  }
+
+  fn nsga2_dtlz2(
+    ops: Preset<Vec<f32>, SimulatedBinaryCrossover, PolynomialMutation>,
+  ) -> Vec<Vec<f32>> {
+    Nsga2::builder()
+      .population(ops.population)
+      .tester(Dtlz2::<2>::default())
+      .selector(ops.selector)
+      .recombinator(ops.recombinator)
+      .mutator(ops.mutator)
+      .terminator(ops.terminator)
+      .build()
+      .optimize()
+  }
+
+  #[test]
+  fn test_real_valued_is_reproducible() {
+    let bounds = Dtlz2::<2>::default().bounds();
+    let ops = || real_valued(bounds.clone(), 20, 10, SplitRng::new(7));
+    let solutions = nsga2_dtlz2(ops());
+    assert_eq!(solutions.len(), 20);
+    assert!(solutions.iter().all(|x| bounds.contains(x)));
+    assert_eq!(solutions, nsga2_dtlz2(ops()));
+  }
+
+  #[test]
+  fn test_presets_with_spea2() {
+    let ops = permutation(5, 10, 5, SplitRng::new(0));
+    let solutions = Spea2::builder()
+      .population(ops.population)
+      .archive_size(10)
+      .tester(|x: &Vec<usize>| [x[0] as f32, x[4] as f32])
+      .selector(ops.selector)
+      .recombinator(ops.recombinator)
+      .mutator(ops.mutator)
+      .terminator(ops.terminator)
+      .build()
+      .optimize();
+    assert!(solutions.iter().all(|x| {
+      let mut sorted = x.clone();
+      sorted.sort();
+      sorted == [0, 1, 2, 3, 4]
+    }));
+
+    let ops = binary(4, 10, 5, SplitRng::new(0));
+    assert_eq!(ops.population.len(), 10);
+    let solutions = Spea2::builder()
+      .population(ops.population)
+      .archive_size(10)
+      .tester(|x: &Vec<bool>| [x[0] as u8 as f32, !x[1] as u8 as f32])
+      .selector(ops.selector)
+      .recombinator(ops.recombinator)
+      .mutator(ops.mutator)
+      .terminator(ops.terminator)
+      .build()
+      .optimize();
+    assert!(solutions.iter().all(|x| x.len() == 4));
+  }
+}
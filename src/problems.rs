@@ -0,0 +1,68 @@
+//! Benchmark problems with known Pareto fronts, for testing optimizers and
+//! comparing them with each other.
+//!
+//! Each problem is a [`Test`](crate::testing::Test) of solutions represented
+//! as vectors of decision variables, i.e. any `S: AsRef<[f32]>`, such as
+//! `Vec<f32>` or `[f32; D]`, or as pairs `(f32, f32)` for [`classic`]
+//! problems of two variables. Problems also provide bounds of their decision
+//! variables and generators of their reference fronts.
+
+pub mod classic;
+pub mod dtlz;
+
+/// Generates evenly spread points on the unit simplex, i.e. points with
+/// non-negative coordinates that sum up to `1`, by dividing each axis into
+/// `divisions` parts (Das and Dennis' method). The number of generated points
+/// is `C(divisions + M - 1, M - 1)`.
+///
+/// # Panics
+///
+/// Panics if `divisions` is `0` or `M` is `0`.
+pub(crate) fn simplex_lattice<const M: usize>(
+  divisions: usize,
+) -> Vec<[f64; M]> {
+  fn fill<const M: usize>(
+    point: &mut [usize; M],
+    idx: usize,
+    left: usize,
+    divisions: usize,
+    points: &mut Vec<[f64; M]>,
+  ) {
+    if idx == M - 1 {
+      point[idx] = left;
+      points.push(point.map(|p| p as f64 / divisions as f64));
+      return;
+    }
+    for p in 0..=left {
+      point[idx] = p;
+      fill(point, idx + 1, left - p, divisions, points);
+    }
+  }
+
+  assert!(divisions > 0, "the number of divisions must be positive");
+  assert!(M > 0, "the number of dimensions must be positive");
+  let mut points = Vec::new();
+  fill(&mut [0; M], 0, divisions, divisions, &mut points);
+  points
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_simplex_lattice() {
+    let points = simplex_lattice::<3>(2);
+    assert_eq!(points, [
+      [0.0, 0.0, 1.0],
+      [0.0, 0.5, 0.5],
+      [0.0, 1.0, 0.0],
+      [0.5, 0.0, 0.5],
+      [0.5, 0.5, 0.0],
+      [1.0, 0.0, 0.0],
+    ]);
+    // C(12 + 4, 4)
+    assert_eq!(simplex_lattice::<5>(12).len(), 1820);
+    assert_eq!(simplex_lattice::<1>(4), [[1.0]]);
+  }
+}
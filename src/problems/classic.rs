@@ -0,0 +1,353 @@
+//! Classic problems with two objectives and two decision variables: Binh and
+//! Korn, Chankong and Haimes, and Fonseca and Fleming problems.
+//!
+//! Solutions are pairs `(x, y)` of decision variables. Constrained problems
+//! are also [`Constraint`]s that evaluate violations of their constraints, so
+//! the same problem is the tester and the constraint of an optimizer.
+//!
+//! # Examples
+//! ```
+//! use moga::{
+//!   constraining::AdaptivePenalty,
+//!   optimizer::{nsga::Nsga2, Optimizer},
+//!   problems::classic::BinhKorn,
+//!   selection::RandomSelector,
+//!   termination::GenerationTerminator,
+//! };
+//! use rand::Rng;
+//!
+//! let penalty = AdaptivePenalty::new(BinhKorn, 0.8, 1.5);
+//! let bounds = BinhKorn.bounds();
+//! let mut rng = rand::thread_rng();
+//! let population = (0..100)
+//!   .map(|_| {
+//!     let (lower, upper) = (bounds.lower(), bounds.upper());
+//!     (
+//!       rng.gen_range(lower[0]..=upper[0]),
+//!       rng.gen_range(lower[1]..=upper[1]),
+//!     )
+//!   })
+//!   .collect();
+//! let solutions = Nsga2::builder()
+//!   .population(population)
+//!   .tester(penalty.penalize(BinhKorn))
+//!   .selector(RandomSelector(20))
+//!   .recombinator(|a: &(f32, f32), b: &(f32, f32)| {
+//!     ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0)
+//!   })
+//!   .mutator(|_: &mut (f32, f32)| {})
+//!   .constraint(penalty.clone())
+//!   .terminator(GenerationTerminator(10))
+//!   .build()
+//!   .optimize();
+//! assert_eq!(solutions.len(), 100);
+//! ```
+//!
+//! [`Constraint`]: crate::constraining::Constraint
+
+use std::{
+  cmp::Ordering,
+  f64::consts::{FRAC_1_SQRT_2, TAU},
+};
+
+use crate::{
+  constraining::Constraint,
+  genomes::real::Bounds,
+  score::{ParetoDominance, Scores},
+  testing::Test,
+};
+
+/// Returns `divisions + 1` evenly spread values from `start` to `end`.
+///
+/// # Panics
+///
+/// Panics if `divisions` is `0`.
+fn steps(start: f64, end: f64, divisions: usize) -> impl Iterator<Item = f64> {
+  assert!(divisions > 0, "the number of divisions must be positive");
+  (0..=divisions)
+    .map(move |i| start + (end - start) * i as f64 / divisions as f64)
+}
+
+/// Binh and Korn problem:
+/// - minimize `f1 = 4x^2 + 4y^2` and `f2 = (x - 5)^2 + (y - 5)^2`,
+/// - subject to `(x - 5)^2 + y^2 <= 25` and `(x - 8)^2 + (y + 3)^2 >= 7.7`,
+/// - where `0 <= x <= 5` and `0 <= y <= 3`.
+///
+/// Its Pareto optimal solutions are `x = y` for `0 <= x <= 3`, and `y = 3`
+/// for `3 <= x <= 5`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct BinhKorn;
+
+impl BinhKorn {
+  /// Returns bounds of decision variables: `0..=5` for `x` and `0..=3` for
+  /// `y`.
+  pub fn bounds(&self) -> Bounds {
+    Bounds::new(vec![0.0, 0.0], vec![5.0, 3.0])
+  }
+
+  /// Evaluates scores of the decision variables `(x, y)`.
+  pub fn evaluate(&self, (x, y): (f32, f32)) -> Scores<2> {
+    let (x, y) = (f64::from(x), f64::from(y));
+    [
+      4.0 * x.powi(2) + 4.0 * y.powi(2),
+      (x - 5.0).powi(2) + (y - 5.0).powi(2),
+    ]
+    .map(|f| f as f32)
+  }
+
+  /// Returns points of the Pareto front, evaluated at Pareto optimal
+  /// solutions evenly spread along `x`, which is divided into `divisions`
+  /// parts. There are `divisions + 1` points.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `divisions` is `0`.
+  pub fn reference_front(&self, divisions: usize) -> Vec<Scores<2>> {
+    steps(0.0, 5.0, divisions)
+      .map(|x| self.evaluate((x as f32, x.min(3.0) as f32)))
+      .collect()
+  }
+}
+
+impl Test<(f32, f32), 2> for BinhKorn {
+  fn test(&self, solution: &(f32, f32)) -> Scores<2> {
+    self.evaluate(*solution)
+  }
+}
+
+impl Constraint<(f32, f32), 2> for BinhKorn {
+  fn violations(&self, &(x, y): &(f32, f32)) -> Scores<2> {
+    let (x, y) = (f64::from(x), f64::from(y));
+    [
+      (x - 5.0).powi(2) + y.powi(2) - 25.0,
+      7.7 - (x - 8.0).powi(2) - (y + 3.0).powi(2),
+    ]
+    .map(|v| v as f32)
+  }
+}
+
+/// Chankong and Haimes problem:
+/// - minimize `f1 = 2 + (x - 2)^2 + (y - 1)^2` and `f2 = 9x - (y - 1)^2`,
+/// - subject to `x^2 + y^2 <= 225` and `x - 3y + 10 <= 0`,
+/// - where `-20 <= x <= 20` and `-20 <= y <= 20`.
+///
+/// Since optimizers bring scores closer to `0`, `f2` is shifted by
+/// [`F2_OFFSET`](Self::F2_OFFSET), so that it's non-negative within the
+/// bounds.
+///
+/// Both constraints are active on the Pareto front: its Pareto optimal
+/// solutions lie on the line `x = -2.5`, and on the boundaries of both
+/// constraints next to it.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct ChankongHaimes;
+
+impl ChankongHaimes {
+  /// The value added to `f2`, which is its negated minimum within the bounds.
+  pub const F2_OFFSET: f32 = 621.0;
+
+  /// Returns bounds of decision variables, which are `-20` and `20` for each
+  /// variable.
+  pub fn bounds(&self) -> Bounds {
+    Bounds::uniform(2, -20.0..=20.0)
+  }
+
+  /// Evaluates scores of the decision variables `(x, y)`.
+  pub fn evaluate(&self, (x, y): (f32, f32)) -> Scores<2> {
+    let (x, y) = (f64::from(x), f64::from(y));
+    [
+      2.0 + (x - 2.0).powi(2) + (y - 1.0).powi(2),
+      9.0 * x - (y - 1.0).powi(2) + f64::from(Self::F2_OFFSET),
+    ]
+    .map(|f| f as f32)
+  }
+
+  /// Returns points of the Pareto front, sampled from the line `x = -2.5`
+  /// and from the boundaries of both constraints, each of which is divided
+  /// into `divisions` parts. Infeasible and dominated samples are discarded,
+  /// so there are at most `3 * (divisions + 1)` points.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `divisions` is `0`.
+  pub fn reference_front(&self, divisions: usize) -> Vec<Scores<2>> {
+    // the second constraint's boundary `x = 3y - 10` crosses the circle of
+    // the first one at `y` roots of `10y^2 - 60y - 125 = 0`
+    let root = 8600f64.sqrt();
+    let (low, high) = ((60.0 - root) / 20.0, (60.0 + root) / 20.0);
+    // the feasible part of the line, between both boundaries
+    let line = steps(2.5, 218.75f64.sqrt(), divisions).map(|y| (-2.5, y));
+    let boundary = steps(low, high, divisions).map(|y| (3.0 * y - 10.0, y));
+    let arc = steps(
+      high.atan2(3.0 * high - 10.0),
+      low.atan2(3.0 * low - 10.0) + TAU,
+      divisions,
+    )
+    .map(|angle| (15.0 * angle.cos(), 15.0 * angle.sin()));
+    let samples: Vec<Scores<2>> = line
+      .chain(boundary)
+      .chain(arc)
+      .map(|(x, y)| (x as f32, y as f32))
+      .filter(|solution| {
+        // samples on a boundary may violate it by a rounding error
+        self.violations(solution).iter().all(|&v| v <= 1e-3)
+      })
+      .map(|solution| self.evaluate(solution))
+      .collect();
+    samples
+      .iter()
+      .filter(|p_sc| {
+        !samples
+          .iter()
+          .any(|q_sc| q_sc.dominance(*p_sc) == Ordering::Less)
+      })
+      .copied()
+      .collect()
+  }
+}
+
+impl Test<(f32, f32), 2> for ChankongHaimes {
+  fn test(&self, solution: &(f32, f32)) -> Scores<2> {
+    self.evaluate(*solution)
+  }
+}
+
+impl Constraint<(f32, f32), 2> for ChankongHaimes {
+  fn violations(&self, &(x, y): &(f32, f32)) -> Scores<2> {
+    let (x, y) = (f64::from(x), f64::from(y));
+    [x.powi(2) + y.powi(2) - 225.0, x - 3.0 * y + 10.0].map(|v| v as f32)
+  }
+}
+
+/// Fonseca and Fleming problem with two decision variables:
+/// - minimize `f1 = 1 - exp(-(x - 1/sqrt(2))^2 - (y - 1/sqrt(2))^2)` and
+///   `f2 = 1 - exp(-(x + 1/sqrt(2))^2 - (y + 1/sqrt(2))^2)`,
+/// - where `-4 <= x <= 4` and `-4 <= y <= 4`.
+///
+/// The problem is unconstrained and its Pareto front is concave. Its Pareto
+/// optimal solutions are `x = y` for `-1/sqrt(2) <= x <= 1/sqrt(2)`.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct FonsecaFleming;
+
+impl FonsecaFleming {
+  /// Returns bounds of decision variables, which are `-4` and `4` for each
+  /// variable.
+  pub fn bounds(&self) -> Bounds {
+    Bounds::uniform(2, -4.0..=4.0)
+  }
+
+  /// Evaluates scores of the decision variables `(x, y)`.
+  pub fn evaluate(&self, (x, y): (f32, f32)) -> Scores<2> {
+    let (x, y) = (f64::from(x), f64::from(y));
+    [FRAC_1_SQRT_2, -FRAC_1_SQRT_2]
+      .map(|c| 1.0 - (-(x - c).powi(2) - (y - c).powi(2)).exp())
+      .map(|f| f as f32)
+  }
+
+  /// Returns points of the Pareto front, evaluated at Pareto optimal
+  /// solutions evenly spread along `x`, which is divided into `divisions`
+  /// parts. There are `divisions + 1` points.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `divisions` is `0`.
+  pub fn reference_front(&self, divisions: usize) -> Vec<Scores<2>> {
+    steps(-FRAC_1_SQRT_2, FRAC_1_SQRT_2, divisions)
+      .map(|x| self.evaluate((x as f32, x as f32)))
+      .collect()
+  }
+}
+
+impl Test<(f32, f32), 2> for FonsecaFleming {
+  fn test(&self, solution: &(f32, f32)) -> Scores<2> {
+    self.evaluate(*solution)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::constraining::is_feasible;
+
+  /// Returns `true` if no feasible point of a grid over the `bounds`
+  /// dominates any point of the `front` by more than `tolerance`.
+  fn is_front(
+    front: &[Scores<2>],
+    bounds: &Bounds,
+    evaluate: impl Fn((f32, f32)) -> Option<Scores<2>>,
+    tolerance: f32,
+  ) -> bool {
+    let (lower, upper) = (bounds.lower(), bounds.upper());
+    let grid = |i: usize, j: usize| {
+      (
+        lower[0] + (upper[0] - lower[0]) * i as f32 / 200.0,
+        lower[1] + (upper[1] - lower[1]) * j as f32 / 200.0,
+      )
+    };
+    (0..=200)
+      .flat_map(|i| (0..=200).map(move |j| (i, j)))
+      .filter_map(|(i, j)| evaluate(grid(i, j)))
+      .all(|sc| {
+        front
+          .iter()
+          .all(|f| sc[0] + tolerance > f[0] || sc[1] + tolerance > f[1])
+      })
+  }
+
+  #[test]
+  fn test_binh_korn() {
+    let problem = BinhKorn;
+    assert_eq!(problem.evaluate((0.0, 0.0)), [0.0, 50.0]);
+    assert_eq!(problem.test(&(5.0, 3.0)), [136.0, 4.0]);
+    assert!(is_feasible(&problem.violations(&(1.0, 1.0))));
+    assert!(problem.violations(&(0.0, 3.0))[0] > 0.0);
+
+    let front = problem.reference_front(50);
+    assert_eq!(front.len(), 51);
+    assert_eq!(front[0], [0.0, 50.0]);
+    assert_eq!(front[50], [136.0, 4.0]);
+    let feasible =
+      |x| is_feasible(&problem.violations(&x)).then(|| problem.evaluate(x));
+    assert!(is_front(&front, &problem.bounds(), feasible, 1e-3));
+  }
+
+  #[test]
+  fn test_chankong_haimes() {
+    let problem = ChankongHaimes;
+    assert_eq!(problem.evaluate((-2.5, 2.5)), [24.5, 596.25]);
+    assert_eq!(problem.violations(&(-2.5, 2.5)), [-212.5, 0.0]);
+    assert!(problem.violations(&(2.0, 1.0))[1] > 0.0);
+
+    let front = problem.reference_front(1000);
+    assert!(!front.is_empty() && front.len() <= 3003);
+    // there are optimal solutions on the line and on both boundaries
+    // on the line, `f1 + f2` is constant
+    assert!(front
+      .iter()
+      .any(|sc| sc[0] > 50.0 && (sc[0] + sc[1] - 620.75).abs() < 1e-3));
+    assert!(front.iter().any(|sc| sc[0] < 24.0));
+    assert!(front.iter().any(|sc| sc[0] > 213.0));
+    let feasible =
+      |x| is_feasible(&problem.violations(&x)).then(|| problem.evaluate(x));
+    assert!(is_front(&front, &problem.bounds(), feasible, 0.1));
+  }
+
+  #[test]
+  fn test_fonseca_fleming() {
+    let problem = FonsecaFleming;
+    let [f1, f2] =
+      problem.evaluate((FRAC_1_SQRT_2 as f32, FRAC_1_SQRT_2 as f32));
+    assert!(f1.abs() < 1e-6);
+    assert!((f2 - (1.0 - (-4.0f32).exp())).abs() < 1e-6);
+
+    let front = problem.reference_front(20);
+    assert_eq!(front.len(), 21);
+    let all = |x| Some(problem.test(&x));
+    assert!(is_front(&front, &problem.bounds(), all, 1e-4));
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_zero_divisions() {
+    FonsecaFleming.reference_front(0);
+  }
+}
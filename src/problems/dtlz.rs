@@ -0,0 +1,387 @@
+//! Scalable DTLZ test problems of Deb, Thiele, Laumanns and Zitzler with any
+//! number of objectives `M`.
+//!
+//! A problem with `M` objectives has `n = M + k - 1` decision variables, each
+//! of them lies between `0` and `1`. The first `M - 1` variables define
+//! position of a solution on the Pareto front, while the last `k` variables
+//! define its distance to the front. Solutions are Pareto optimal when the
+//! last `k` variables are equal to `0.5` for [`Dtlz1`] and [`Dtlz2`], or to
+//! `0` for [`Dtlz7`].
+//!
+//! # Examples
+//! ```
+//! use moga::{
+//!   init::latin_hypercube,
+//!   optimizer::{nsga::Nsga2, Optimizer},
+//!   problems::dtlz::{recommended_population_size, Dtlz2},
+//!   selection::RandomSelector,
+//!   termination::GenerationTerminator,
+//! };
+//!
+//! let problem = Dtlz2::<5>::default();
+//! let population_size = recommended_population_size(5).unwrap();
+//! let population = latin_hypercube(
+//!   &problem.bounds(),
+//!   population_size,
+//!   &mut rand::thread_rng(),
+//! );
+//! let solutions = Nsga2::builder()
+//!   .population(population)
+//!   .tester(problem)
+//!   .selector(RandomSelector(10))
+//!   .recombinator(|a: &Vec<f32>, b: &Vec<f32>| {
+//!     a.iter()
+//!       .zip(b)
+//!       .map(|(a, b)| (a + b) / 2.0)
+//!       .collect::<Vec<_>>()
+//!   })
+//!   .mutator(|_: &mut Vec<f32>| {})
+//!   .terminator(GenerationTerminator(10))
+//!   .build()
+//!   .optimize();
+//! assert_eq!(solutions.len(), population_size);
+//! ```
+
+use std::{
+  cmp::Ordering,
+  f64::consts::{FRAC_PI_2, PI},
+};
+
+use super::simplex_lattice;
+use crate::{
+  genomes::real::Bounds,
+  score::{ParetoDominance, Scores},
+  testing::Test,
+};
+
+/// Returns the recommended population size for DTLZ problems with the given
+/// number of `objectives`, as used by the authors of NSGA-III: the smallest
+/// multiple of 4 that is not less than the number of evenly spread reference
+/// points. Returns `None` for numbers of objectives other than 3, 5, 8, 10
+/// and 15.
+pub const fn recommended_population_size(objectives: usize) -> Option<usize> {
+  match objectives {
+    3 => Some(92),
+    5 => Some(212),
+    8 => Some(156),
+    10 => Some(276),
+    15 => Some(136),
+    _ => None,
+  }
+}
+
+macro_rules! dtlz_problem {
+  (
+    $(#[$attr:meta])*
+    $name:ident, $k:literal
+  ) => {
+    $(#[$attr])*
+    #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+    pub struct $name<const M: usize> {
+      dimension: usize,
+    }
+
+    impl<const M: usize> $name<M> {
+      #[doc = concat!(
+        "The recommended number `k` of distance variables, which is `",
+        stringify!($k),
+        "`."
+      )]
+      pub const K: usize = $k;
+
+      /// Creates a problem with the given number of decision variables.
+      ///
+      /// # Panics
+      ///
+      /// Panics if `M` is less than 2 or `dimension` is less than `M`.
+      pub fn new(dimension: usize) -> Self {
+        assert!(M >= 2, "DTLZ problems have at least 2 objectives");
+        assert!(
+          dimension >= M,
+          "DTLZ problem with {M} objectives has at least {M} variables"
+        );
+        Self { dimension }
+      }
+
+      /// Returns the number of decision variables.
+      pub fn dimension(&self) -> usize {
+        self.dimension
+      }
+
+      /// Returns bounds of decision variables, which are `0` and `1` for
+      /// each variable.
+      pub fn bounds(&self) -> Bounds {
+        Bounds::uniform(self.dimension, 0.0..=1.0)
+      }
+    }
+
+    impl<const M: usize> Default for $name<M> {
+      /// Creates a problem with the recommended number of decision variables:
+      #[doc = concat!("`M + ", stringify!($k), " - 1`.")]
+      fn default() -> Self {
+        Self::new(M + Self::K - 1)
+      }
+    }
+
+    impl<S: AsRef<[f32]>, const M: usize> Test<S, M> for $name<M> {
+      fn test(&self, solution: &S) -> Scores<M> {
+        self.evaluate(solution.as_ref())
+      }
+    }
+  };
+}
+
+dtlz_problem!(
+  /// DTLZ1 problem. Its Pareto front is the linear hyperplane
+  /// `f1 + f2 + ... + fM = 0.5`, and the problem has `11^k - 1` local fronts.
+  Dtlz1,
+  5
+);
+
+dtlz_problem!(
+  /// DTLZ2 problem. Its Pareto front is the part of the unit hypersphere
+  /// `f1^2 + f2^2 + ... + fM^2 = 1` with non-negative scores.
+  Dtlz2,
+  10
+);
+
+dtlz_problem!(
+  /// DTLZ7 problem. Its Pareto front consists of `2^(M - 1)` disconnected
+  /// regions.
+  Dtlz7,
+  20
+);
+
+/// Splits decision variables `x` into position and distance variables.
+///
+/// # Panics
+///
+/// Panics if the number of variables doesn't match the problem's dimension.
+fn split_variables(x: &[f32], dimension: usize, m: usize) -> (&[f32], &[f32]) {
+  assert_eq!(
+    x.len(),
+    dimension,
+    "the number of decision variables doesn't match dimension of the problem"
+  );
+  x.split_at(m - 1)
+}
+
+impl<const M: usize> Dtlz1<M> {
+  /// Evaluates scores of the decision variables `x`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the number of variables doesn't match the problem's dimension.
+  pub fn evaluate(&self, x: &[f32]) -> Scores<M> {
+    let (position, distance) = split_variables(x, self.dimension, M);
+    let g = 100.0
+      * (distance.len() as f64
+        + distance
+          .iter()
+          .map(|&x| f64::from(x) - 0.5)
+          .map(|x| x.powi(2) - (20.0 * PI * x).cos())
+          .sum::<f64>());
+    std::array::from_fn(|o_idx| {
+      let mut f = 0.5 * (1.0 + g);
+      f *= position[..M - 1 - o_idx]
+        .iter()
+        .map(|&x| f64::from(x))
+        .product::<f64>();
+      if o_idx > 0 {
+        f *= 1.0 - f64::from(position[M - 1 - o_idx]);
+      }
+      f as f32
+    })
+  }
+
+  /// Returns evenly spread points of the Pareto front. Each objective's axis
+  /// is divided into `divisions` parts, so there are
+  /// `C(divisions + M - 1, M - 1)` points.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `divisions` is `0`.
+  pub fn reference_front(&self, divisions: usize) -> Vec<Scores<M>> {
+    simplex_lattice::<M>(divisions)
+      .into_iter()
+      .map(|point| point.map(|p| (0.5 * p) as f32))
+      .collect()
+  }
+}
+
+impl<const M: usize> Dtlz2<M> {
+  /// Evaluates scores of the decision variables `x`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the number of variables doesn't match the problem's dimension.
+  pub fn evaluate(&self, x: &[f32]) -> Scores<M> {
+    let (position, distance) = split_variables(x, self.dimension, M);
+    let g = distance
+      .iter()
+      .map(|&x| (f64::from(x) - 0.5).powi(2))
+      .sum::<f64>();
+    std::array::from_fn(|o_idx| {
+      let mut f = 1.0 + g;
+      f *= position[..M - 1 - o_idx]
+        .iter()
+        .map(|&x| (f64::from(x) * FRAC_PI_2).cos())
+        .product::<f64>();
+      if o_idx > 0 {
+        f *= (f64::from(position[M - 1 - o_idx]) * FRAC_PI_2).sin();
+      }
+      f as f32
+    })
+  }
+
+  /// Returns evenly spread points of the Pareto front. Each objective's axis
+  /// is divided into `divisions` parts, so there are
+  /// `C(divisions + M - 1, M - 1)` points.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `divisions` is `0`.
+  pub fn reference_front(&self, divisions: usize) -> Vec<Scores<M>> {
+    simplex_lattice::<M>(divisions)
+      .into_iter()
+      .map(|point| {
+        let norm = point.iter().map(|p| p * p).sum::<f64>().sqrt();
+        point.map(|p| (p / norm) as f32)
+      })
+      .collect()
+  }
+}
+
+impl<const M: usize> Dtlz7<M> {
+  /// Evaluates scores of the decision variables `x`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the number of variables doesn't match the problem's dimension.
+  pub fn evaluate(&self, x: &[f32]) -> Scores<M> {
+    let (position, distance) = split_variables(x, self.dimension, M);
+    let g = 1.0
+      + 9.0 / distance.len() as f64
+        * distance.iter().map(|&x| f64::from(x)).sum::<f64>();
+    let mut scores = [0.0; M];
+    scores[..M - 1].copy_from_slice(position);
+    scores[M - 1] = ((1.0 + g) * Self::h(position, g)) as f32;
+    scores
+  }
+
+  fn h(position: &[f32], g: f64) -> f64 {
+    M as f64
+      - position
+        .iter()
+        .map(|&f| f64::from(f))
+        .map(|f| f / (1.0 + g) * (1.0 + (3.0 * PI * f).sin()))
+        .sum::<f64>()
+  }
+
+  /// Returns points of the Pareto front, sampled from a grid where each of
+  /// the first `M - 1` objectives' axis is divided into `divisions` parts.
+  /// Dominated samples are discarded, so there are at most
+  /// `(divisions + 1)^(M - 1)` points.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `divisions` is `0`.
+  pub fn reference_front(&self, divisions: usize) -> Vec<Scores<M>> {
+    assert!(divisions > 0, "the number of divisions must be positive");
+    let samples_count = (divisions + 1).pow(M as u32 - 1);
+    let samples: Vec<Scores<M>> = (0..samples_count)
+      .map(|mut s_idx| {
+        let mut scores = [0.0; M];
+        for f in &mut scores[..M - 1] {
+          *f = (s_idx % (divisions + 1)) as f32 / divisions as f32;
+          s_idx /= divisions + 1;
+        }
+        scores[M - 1] = (2.0 * Self::h(&scores[..M - 1], 1.0)) as f32;
+        scores
+      })
+      .collect();
+    samples
+      .iter()
+      .filter(|p_sc| {
+        !samples
+          .iter()
+          .any(|q_sc| q_sc.dominance(*p_sc) == Ordering::Less)
+      })
+      .copied()
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Decision variables with the given position and Pareto optimal distance
+  /// variables.
+  fn optimal(position: &[f32], dimension: usize, distance: f32) -> Vec<f32> {
+    let mut x = position.to_vec();
+    x.resize(dimension, distance);
+    x
+  }
+
+  #[test]
+  fn test_dtlz1() {
+    let problem = Dtlz1::<5>::default();
+    assert_eq!(problem.dimension(), 9);
+    assert_eq!(problem.bounds(), Bounds::uniform(9, 0.0..=1.0));
+    let x = optimal(&[0.2, 0.4, 0.6, 0.8], problem.dimension(), 0.5);
+    let scores = problem.test(&x);
+    assert!((scores.iter().sum::<f32>() - 0.5).abs() < 1e-6);
+
+    let x = optimal(&[0.2, 0.4, 0.6, 0.8], problem.dimension(), 0.0);
+    assert!(problem.evaluate(&x).iter().sum::<f32>() > 0.5);
+
+    let front = problem.reference_front(4);
+    assert_eq!(front.len(), 70);
+    assert!(front
+      .iter()
+      .all(|sc| (sc.iter().sum::<f32>() - 0.5).abs() < 1e-6));
+  }
+
+  #[test]
+  fn test_dtlz2() {
+    let problem = Dtlz2::<4>::new(6);
+    let x = optimal(&[0.1, 0.5, 0.9], problem.dimension(), 0.5);
+    let scores = problem.test(&x);
+    assert!((scores.iter().map(|f| f * f).sum::<f32>() - 1.0).abs() < 1e-6);
+    assert_eq!(problem.test(&[1.0, 0.0, 0.0, 0.5, 0.5, 0.5])[3], 1.0);
+
+    let front = problem.reference_front(3);
+    assert_eq!(front.len(), 20);
+    assert!(front
+      .iter()
+      .all(|sc| (sc.iter().map(|f| f * f).sum::<f32>() - 1.0).abs() < 1e-6));
+  }
+
+  #[test]
+  fn test_dtlz7() {
+    let problem = Dtlz7::<3>::default();
+    assert_eq!(problem.dimension(), 22);
+    let x = optimal(&[0.0, 0.0], problem.dimension(), 0.0);
+    assert_eq!(problem.evaluate(&x), [0.0, 0.0, 6.0]);
+
+    let front = problem.reference_front(50);
+    assert!(!front.is_empty() && front.len() < 51 * 51);
+    assert!(front.contains(&[0.0, 0.0, 6.0]));
+    // the front is disconnected: there are no optimal solutions with
+    // positions between regions
+    assert!(front.iter().all(|sc| !(0.3..0.6).contains(&sc[0])));
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_dimension_mismatch() {
+    Dtlz2::<3>::default().evaluate(&[0.5; 3]);
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_too_small_dimension() {
+    Dtlz1::<3>::new(2);
+  }
+}
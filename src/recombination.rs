@@ -1,14 +1,23 @@
 //! Recombination operators and utilities.
 
+use std::{
+  collections::BTreeSet,
+  sync::{Arc, Mutex, MutexGuard},
+};
+
 use executor::RecombinationExecutor;
-use itertools::Itertools;
-use rayon::prelude::*;
+use rand::{rngs::SmallRng, seq::index, Rng};
 
 use crate::{
   execution::strategy::*,
-  operator::{tag::RecombinationOperatorTag, ParEach, ParEachOperator},
+  operator::OperatorFeedback,
+  rng::{IndexedStreams, SplitRng},
+  score::{Score, Scores},
 };
 
+#[cfg(feature = "parallel")]
+mod parallel;
+
 /// An operator that creates new solutions from all possible combinations (in
 /// the [mathematical sense](https://en.wikipedia.org/wiki/Combination))
 /// of parents' references of length `P`. Each permutation produces `O`
@@ -28,6 +37,7 @@ use crate::{
 /// Any closure that take from 1 to 4 references to solutions and returns from
 /// 1 to 4 solutions is a `Recombinator`.
 /// ```
+/// # #[cfg(feature = "parallel")] {
 /// # use moga::operator::*;
 /// let r = |a: &f32| a * -1.0; // 1 to 1
 /// let r = |a: &f32, b: &f32| (a + b) / 2.0; // 2 to 1
@@ -35,6 +45,7 @@ use crate::{
 /// let r = |a: &f32| (a + 1.0, a + 2.0, a + 3.0); // 1 to 3
 /// let r = |a: &f32, b: &f32, c: &f32, d: &f32| a + b - c - d; // 4 to 1 etc...
 /// let r = r.par_each();
+/// # }
 /// ```
 ///
 /// **Note that you always can implement this trait instead of using closures.**
@@ -84,14 +95,6 @@ recombination_fn_impl! {(S, S, S, S), (a, b, c, d), 4, (S, S), (m, n), 2}
 recombination_fn_impl! {(S, S, S, S), (a, b, c, d), 4, (S, S, S), (m, n, o), 3}
 recombination_fn_impl! {(S, S, S, S), (a, b, c, d), 4, (S, S, S, S), (m, n, o, p), 4}
 
-impl<S, R, const P: usize, const O: usize>
-  ParEach<RecombinationOperatorTag, S, P, O> for R
-where
-  S: Sync + Send,
-  R: Recombination<S, P, O> + Sync,
-{
-}
-
 /// An operator that receives references to previously selected parents and
 /// recombines them into a vector of offsprings. Created offsprings are passed
 /// into `Mutator`.
@@ -120,9 +123,56 @@ where
   }
 }
 
-/// This private module prevents exposing the `Executor` to a user.
+/// Executors are exposed to users only by [`crate::experimental::executor`].
+/// An operator that creates new solutions from selected parents, given
+/// their scores, like a [`Recombinator`] does. Scores let it pick which
+/// parents to breed, e.g. pair parents that excel at different objectives,
+/// like [`ComplementaryPairing`] does.
+///
+/// Optimizers give it the scores of parents. Applied without them, e.g.
+/// boxed into a [`BoxedRecombinator`], it gets NaN scores.
+///
+/// # Examples
+/// ```
+/// use moga::{recombination::ScoredRecombinator, score::Scores};
+///
+/// // breeds each parent with the one with the lowest first score
+/// let r = |parents: Vec<(&f32, &Scores<2>)>| {
+///   let best = parents
+///     .iter()
+///     .min_by(|(_, a), (_, b)| a[0].abs().total_cmp(&b[0].abs()));
+///   match best {
+///     Some(&(best, _)) => {
+///       parents.iter().map(|(p, _)| (best + *p) / 2.0).collect()
+///     }
+///     None => vec![],
+///   }
+/// };
+/// let offsprings = r.recombine(vec![(&1.0, &[0.0, 3.0]), (&3.0, &[2.0, 1.0])]);
+/// assert_eq!(offsprings, [1.0, 2.0]);
+/// ```
+///
+/// **Note that you always can implement this trait instead of using closures.**
+pub trait ScoredRecombinator<S, const N: usize> {
+  /// Recombines `parents` paired with their scores into offsprings.
+  fn recombine(&self, parents: Vec<(&S, &Scores<N>)>) -> Vec<S>;
+}
+
+impl<S, const N: usize, F> ScoredRecombinator<S, N> for F
+where
+  F: Fn(Vec<(&S, &Scores<N>)>) -> Vec<S>,
+{
+  fn recombine(&self, parents: Vec<(&S, &Scores<N>)>) -> Vec<S> {
+    self(parents)
+  }
+}
+
 pub(crate) mod executor {
-  /// An internal recombination executor.
+  use rand::rngs::SmallRng;
+
+  use crate::score::Scores;
+
+  /// An executor of recombination operators.
   pub trait RecombinationExecutor<
     S,
     const P: usize,
@@ -132,6 +182,79 @@ pub(crate) mod executor {
   {
     /// Executes recombinations optionally parallelizing operator's application.
     fn execute_recombination(&self, parents: Vec<&S>) -> Vec<S>;
+
+    /// Returns the number of offsprings that recombination of `parents`
+    /// parents creates, saturated at `usize::MAX`, or `None` if it isn't
+    /// known before recombination.
+    fn projected_offsprings(&self, parents: usize) -> Option<usize> {
+      let _ = parents;
+      None
+    }
+
+    /// Executes recombinations of combinations of `parents` sampled with
+    /// `rng`, so that at most `limit` offsprings are created. Recombines all
+    /// `parents` if the number of offsprings isn't known before
+    /// recombination.
+    fn execute_sampled_recombination(
+      &self,
+      parents: Vec<&S>,
+      limit: usize,
+      rng: &mut SmallRng,
+    ) -> Vec<S> {
+      let _ = (limit, rng);
+      self.execute_recombination(parents)
+    }
+
+    /// Tells the executor the `generation` that following recombinations
+    /// belong to. Optimizers call it before recombining parents of each
+    /// generation, so that executors whose recombinations depend on the
+    /// generation don't have to count generations themselves.
+    fn start_generation(&self, generation: usize) {
+      let _ = generation;
+    }
+
+    /// Executes recombinations of `solutions` at `selected` indices. Unless
+    /// overridden, recombines references to them.
+    fn execute_selected_recombination(
+      &self,
+      solutions: &[S],
+      selected: &[usize],
+    ) -> Vec<S> {
+      self.execute_recombination(
+        selected.iter().map(|&idx| &solutions[idx]).collect(),
+      )
+    }
+
+    /// Executes recombinations of `solutions` at `selected` indices, given
+    /// `scores` of all `solutions`. Unless overridden, ignores the scores.
+    fn execute_scored_selected_recombination<const N: usize>(
+      &self,
+      solutions: &[S],
+      scores: &[Scores<N>],
+      selected: &[usize],
+    ) -> Vec<S>
+    where
+      Self: Sized,
+    {
+      let _ = scores;
+      self.execute_selected_recombination(solutions, selected)
+    }
+
+    /// Executes recombinations of combinations of `solutions` at `selected`
+    /// indices sampled with `rng`, like `execute_sampled_recombination` does.
+    fn execute_sampled_selected_recombination(
+      &self,
+      solutions: &[S],
+      selected: &[usize],
+      limit: usize,
+      rng: &mut SmallRng,
+    ) -> Vec<S> {
+      self.execute_sampled_recombination(
+        selected.iter().map(|&idx| &solutions[idx]).collect(),
+        limit,
+        rng,
+      )
+    }
   }
 }
 
@@ -150,60 +273,1048 @@ where
   }
 }
 
+impl<S, const N: usize, R>
+  RecombinationExecutor<
+    S,
+    { usize::MAX },
+    { usize::MAX },
+    ScoredExecutionStrategy<N>,
+  > for R
+where
+  R: ScoredRecombinator<S, N>,
+{
+  fn execute_recombination(&self, parents: Vec<&S>) -> Vec<S> {
+    let nan_scores = [Score::NAN; N];
+    self.recombine(parents.into_iter().map(|p| (p, &nan_scores)).collect())
+  }
+
+  fn execute_scored_selected_recombination<const M: usize>(
+    &self,
+    solutions: &[S],
+    scores: &[Scores<M>],
+    selected: &[usize],
+  ) -> Vec<S> {
+    assert_eq!(
+      M, N,
+      "a scored recombinator of {N} objectives can't recombine solutions of \
+       {M} objectives"
+    );
+    let scores = scores.as_flattened();
+    self.recombine(
+      selected
+        .iter()
+        .map(|&idx| {
+          let scores = &scores[idx * N..(idx + 1) * N];
+          (
+            &solutions[idx],
+            scores.try_into().expect("scores must have N values"),
+          )
+        })
+        .collect(),
+    )
+  }
+}
+
 impl<S, const P: usize, const O: usize, R>
   RecombinationExecutor<S, P, O, SequentialExecutionStrategy> for R
 where
   R: Recombination<S, P, O>,
 {
   fn execute_recombination(&self, parents: Vec<&S>) -> Vec<S> {
-    parents
-      .iter()
-      .copied()
-      .combinations(P)
-      .flat_map(|c| {
-        self.recombine(c.try_into().unwrap_or_else(|c: Vec<&S>| {
-          panic!(
-            "combination size must be equal to {} but it is {}",
-            P,
-            c.len()
-          )
-        }))
+    let capacity = combinations_count(parents.len(), P)
+      .and_then(|cnt| cnt.checked_mul(O))
+      .unwrap_or(0);
+    let mut offsprings = Vec::with_capacity(capacity);
+    if P == 1 {
+      // each parent is its own combination, so there is nothing to combine
+      offsprings.extend(
+        parents
+          .iter()
+          .flat_map(|&p| self.recombine(std::array::from_fn(|_| p))),
+      );
+    } else {
+      offsprings
+        .extend(Combinations::new(&parents).flat_map(|c| self.recombine(c)));
+    }
+    offsprings
+  }
+
+  fn projected_offsprings(&self, parents: usize) -> Option<usize> {
+    Some(projected_offsprings(parents, P, O))
+  }
+
+  fn execute_sampled_recombination(
+    &self,
+    parents: Vec<&S>,
+    limit: usize,
+    rng: &mut SmallRng,
+  ) -> Vec<S> {
+    sample_combinations::<_, P>(&parents, limit / O, rng)
+      .into_iter()
+      .flat_map(|c| self.recombine(c))
+      .collect()
+  }
+}
+
+/// An iterator over all combinations of `P` parents in lexicographic order of
+/// their indices. Unlike `Itertools::combinations`, it doesn't allocate a
+/// vector for each combination. Parents are either references to solutions
+/// or, for [`ByValue`] recombinations, copies of them.
+struct Combinations<'a, T, const P: usize> {
+  parents: &'a [T],
+  indices: [usize; P],
+  is_exhausted: bool,
+}
+
+impl<'a, T: Copy, const P: usize> Combinations<'a, T, P> {
+  fn new(parents: &'a [T]) -> Self {
+    let mut indices = [0; P];
+    indices.iter_mut().enumerate().for_each(|(i, idx)| *idx = i);
+    Self {
+      parents,
+      indices,
+      is_exhausted: P > parents.len(),
+    }
+  }
+}
+
+impl<T: Copy, const P: usize> Iterator for Combinations<'_, T, P> {
+  type Item = [T; P];
+
+  fn next(&mut self) -> Option<Self::Item> {
+    if self.is_exhausted {
+      return None;
+    }
+    let combination = self.indices.map(|idx| self.parents[idx]);
+    // find the rightmost index that can be incremented...
+    let n = self.parents.len();
+    match (0..P).rev().find(|&i| self.indices[i] < n - P + i) {
+      // increment it and reset all indices to its right
+      Some(i) => {
+        self.indices[i] += 1;
+        for j in (i + 1)..P {
+          self.indices[j] = self.indices[j - 1] + 1;
+        }
+      }
+      // or stop if this was the last combination
+      None => self.is_exhausted = true,
+    }
+    Some(combination)
+  }
+}
+
+/// Returns the number of combinations of `p` elements out of `n`, or `None` on
+/// overflow.
+fn combinations_count(n: usize, p: usize) -> Option<usize> {
+  if p > n {
+    return Some(0);
+  }
+  (0..p.min(n - p)).try_fold(1usize, |cnt, k| {
+    cnt.checked_mul(n - k).map(|cnt| cnt / (k + 1))
+  })
+}
+
+/// Returns the number of offsprings that recombination of each combination of
+/// `p` out of `n` parents into `o` offsprings creates, saturated at
+/// `usize::MAX`.
+pub(crate) fn projected_offsprings(n: usize, p: usize, o: usize) -> usize {
+  combinations_count(n, p).map_or(usize::MAX, |cnt| cnt.saturating_mul(o))
+}
+
+/// Returns at most `amount` distinct combinations of `P` parents sampled
+/// uniformly with `rng`, in lexicographic order of their indices.
+pub(crate) fn sample_combinations<T: Copy, const P: usize>(
+  parents: &[T],
+  amount: usize,
+  rng: &mut SmallRng,
+) -> Vec<[T; P]> {
+  sample_indexed_combinations(parents, amount, rng)
+    .into_iter()
+    .map(|(_, c)| c)
+    .collect()
+}
+
+/// Samples combinations like [`sample_combinations`] does, pairing each of
+/// them with its index in the lexicographic order of all combinations. If
+/// there are too many combinations to index them, they are indexed in order
+/// of the sample instead.
+fn sample_indexed_combinations<T: Copy, const P: usize>(
+  parents: &[T],
+  amount: usize,
+  rng: &mut SmallRng,
+) -> Vec<(usize, [T; P])> {
+  if P > parents.len() {
+    return Vec::new();
+  }
+  match combinations_count(parents.len(), P) {
+    Some(cnt) => {
+      let mut picked = index::sample(rng, cnt, amount.min(cnt)).into_vec();
+      picked.sort_unstable();
+      let mut picked = picked.into_iter().peekable();
+      Combinations::new(parents)
+        .enumerate()
+        .filter(|(idx, _)| picked.next_if_eq(idx).is_some())
+        .take(amount)
+        .collect()
+    }
+    // too many combinations to index them, but also to ever pick the same
+    // one twice in practice, so they are drawn one at a time
+    None => {
+      let mut picked = BTreeSet::new();
+      while picked.len() < amount {
+        let mut indices: [usize; P] = index::sample(rng, parents.len(), P)
+          .into_vec()
+          .try_into()
+          .unwrap_or_else(|_| unreachable!());
+        indices.sort_unstable();
+        picked.insert(indices);
+      }
+      picked
+        .into_iter()
+        .map(|indices| indices.map(|idx| parents[idx]))
+        .enumerate()
+        .collect()
+    }
+  }
+}
+
+/// A recombination operator of solutions that are cheap to copy, such as
+/// numbers or small arrays of them. Unlike [`Recombination`], it takes each
+/// combination of `P` parents by value: selected parents are copied out of
+/// the population into a contiguous vector once per generation, and
+/// combinations are recombined from it, sparing an indirection per parent.
+///
+/// It goes through the same combinations in the same order as a
+/// `Recombination` does, so wrapping a recombination of references into
+/// `ByValue` only changes how fast it runs.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   optimizer::{nsga::Nsga2, Optimizer},
+///   recombination::ByValue,
+///   selection::RandomSelector,
+///   termination::GenerationTerminator,
+/// };
+///
+/// let solutions = Nsga2::builder()
+///   .population(vec![-2.0, 0.5, 3.0])
+///   .tester(|x: &f32| [x.powf(2.0), (x - 2.0).powf(2.0)])
+///   .selector(RandomSelector(3))
+///   .recombinator(ByValue(|[a, b]: [f32; 2]| [(a + b) / 2.0, a - b]))
+///   .mutator(|_: &mut f32| {})
+///   .terminator(GenerationTerminator(10))
+///   .build()
+///   .optimize();
+/// assert!(!solutions.is_empty());
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct ByValue<F>(pub F);
+
+impl<S, const P: usize, const O: usize, F>
+  RecombinationExecutor<S, P, O, ByValueExecutionStrategy> for ByValue<F>
+where
+  S: Copy,
+  F: Fn([S; P]) -> [S; O],
+{
+  fn execute_recombination(&self, parents: Vec<&S>) -> Vec<S> {
+    let parents: Vec<S> = parents.into_iter().copied().collect();
+    self.recombine_all(&parents)
+  }
+
+  fn projected_offsprings(&self, parents: usize) -> Option<usize> {
+    Some(projected_offsprings(parents, P, O))
+  }
+
+  fn execute_sampled_recombination(
+    &self,
+    parents: Vec<&S>,
+    limit: usize,
+    rng: &mut SmallRng,
+  ) -> Vec<S> {
+    let parents: Vec<S> = parents.into_iter().copied().collect();
+    self.recombine_sample(&parents, limit, rng)
+  }
+
+  fn execute_selected_recombination(
+    &self,
+    solutions: &[S],
+    selected: &[usize],
+  ) -> Vec<S> {
+    let parents: Vec<S> = selected.iter().map(|&idx| solutions[idx]).collect();
+    self.recombine_all(&parents)
+  }
+
+  fn execute_sampled_selected_recombination(
+    &self,
+    solutions: &[S],
+    selected: &[usize],
+    limit: usize,
+    rng: &mut SmallRng,
+  ) -> Vec<S> {
+    let parents: Vec<S> = selected.iter().map(|&idx| solutions[idx]).collect();
+    self.recombine_sample(&parents, limit, rng)
+  }
+}
+
+impl<F> ByValue<F> {
+  /// Recombines all combinations of copied `parents`.
+  fn recombine_all<S: Copy, const P: usize, const O: usize>(
+    &self,
+    parents: &[S],
+  ) -> Vec<S>
+  where
+    F: Fn([S; P]) -> [S; O],
+  {
+    let capacity = combinations_count(parents.len(), P)
+      .and_then(|cnt| cnt.checked_mul(O))
+      .unwrap_or(0);
+    let mut offsprings = Vec::with_capacity(capacity);
+    if P == 1 {
+      offsprings.extend(
+        parents
+          .iter()
+          .flat_map(|&p| (self.0)(std::array::from_fn(|_| p))),
+      );
+    } else {
+      offsprings.extend(Combinations::new(parents).flat_map(|c| (self.0)(c)));
+    }
+    offsprings
+  }
+
+  /// Recombines combinations of copied `parents` sampled with `rng`, so that
+  /// at most `limit` offsprings are created.
+  fn recombine_sample<S: Copy, const P: usize, const O: usize>(
+    &self,
+    parents: &[S],
+    limit: usize,
+    rng: &mut SmallRng,
+  ) -> Vec<S>
+  where
+    F: Fn([S; P]) -> [S; O],
+  {
+    sample_combinations::<_, P>(parents, limit / O, rng)
+      .into_iter()
+      .flat_map(|c| (self.0)(c))
+      .collect()
+  }
+}
+
+/// A recombination operator that clones each selected parent into an
+/// offspring, for evolutionary strategies that only mutate solutions.
+/// Recombining `n` parents creates exactly `n` offsprings, so the number of
+/// offsprings per generation is the number of parents the selector selects,
+/// or the offspring limit of the optimizer if it's smaller, in which case
+/// cloned parents are sampled.
+///
+/// Parents are cloned directly instead of going through combinations of
+/// parents, and they get the same offsprings that a recombination of a
+/// single parent `|x: &S| x.clone()` would give them.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   optimizer::{nsga::Nsga2, Optimizer},
+///   recombination::CloneParents,
+///   selection::RandomSelector,
+///   termination::GenerationTerminator,
+/// };
+/// use rand::Rng;
+///
+/// let solutions = Nsga2::builder()
+///   .population(vec![-2.0, 0.5, 3.0])
+///   .tester(|x: &f32| [x.powf(2.0), (x - 2.0).powf(2.0)])
+///   .selector(RandomSelector(3))
+///   .recombinator(CloneParents)
+///   .mutator(|x: &mut f32| *x += rand::thread_rng().gen_range(-0.5..0.5))
+///   .terminator(GenerationTerminator(10))
+///   .build()
+///   .optimize();
+/// assert!(!solutions.is_empty());
+/// ```
+#[derive(Clone, Copy, Default, Debug)]
+pub struct CloneParents;
+
+impl<S: Clone> RecombinationExecutor<S, 1, 1, CloningExecutionStrategy>
+  for CloneParents
+{
+  fn execute_recombination(&self, parents: Vec<&S>) -> Vec<S> {
+    parents.into_iter().cloned().collect()
+  }
+
+  fn projected_offsprings(&self, parents: usize) -> Option<usize> {
+    Some(parents)
+  }
+
+  fn execute_sampled_recombination(
+    &self,
+    parents: Vec<&S>,
+    limit: usize,
+    rng: &mut SmallRng,
+  ) -> Vec<S> {
+    sample_combinations::<_, 1>(&parents, limit, rng)
+      .into_iter()
+      .map(|[p]| p.clone())
+      .collect()
+  }
+
+  fn execute_selected_recombination(
+    &self,
+    solutions: &[S],
+    selected: &[usize],
+  ) -> Vec<S> {
+    selected.iter().map(|&idx| solutions[idx].clone()).collect()
+  }
+}
+
+/// A boxed [`Recombinator`] that any recombination operator, parallelized or
+/// not, can be converted into. Unlike operators, boxed recombinators have the
+/// same type regardless of the number of parents and offsprings, so the type
+/// of an optimizer with a recombinator chosen at runtime can be named.
+///
+/// # Examples
+/// ```
+/// # use moga::recombination::BoxedRecombinator;
+/// let crossover = true;
+/// let r: BoxedRecombinator<f32> = if crossover {
+///   BoxedRecombinator::new(|a: &f32, b: &f32| (a + b) / 2.0)
+/// } else {
+///   BoxedRecombinator::new(|a: &f32| -a)
+/// };
+/// ```
+pub struct BoxedRecombinator<S>(Box<dyn Recombinator<S> + Send>);
+
+impl<S: 'static> BoxedRecombinator<S> {
+  /// Boxes the `recombinator`.
+  pub fn new<ExecStrat: 'static, const P: usize, const O: usize>(
+    recombinator: impl RecombinationExecutor<S, P, O, ExecStrat> + Send + 'static,
+  ) -> Self {
+    Self(Box::new(move |parents: Vec<&S>| {
+      recombinator.execute_recombination(parents)
+    }))
+  }
+}
+
+impl<S> Recombinator<S> for BoxedRecombinator<S> {
+  fn recombine(&self, parents: Vec<&S>) -> Vec<S> {
+    self.0.recombine(parents)
+  }
+}
+
+impl<S> std::fmt::Debug for BoxedRecombinator<S> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("BoxedRecombinator")
+  }
+}
+
+/// A recombination of `P` parents into `O` offsprings that draws random
+/// numbers from a given generator. Apply it with an
+/// [`IndexedRandomRecombination`], which gives each combination of parents
+/// a generator of its own.
+///
+/// # Examples
+/// Any closure that takes an array of references to parents and a generator
+/// and returns an array of offsprings is a `RandomRecombination`.
+/// ```
+/// use moga::recombination::RandomRecombination;
+/// use rand::{rngs::SmallRng, Rng, SeedableRng};
+///
+/// let r = |[a, b]: [&f32; 2], rng: &mut SmallRng| {
+///   [a + rng.gen_range(0.0..=1.0) * (b - a)]
+/// };
+/// let [x] = r.recombine_with([&1.0, &2.0], &mut SmallRng::seed_from_u64(0));
+/// assert!((1.0..=2.0).contains(&x));
+/// ```
+///
+/// **Note that you always can implement this trait instead of using closures.**
+pub trait RandomRecombination<S, const P: usize, const O: usize> {
+  /// Recombines `parents` into offsprings, drawing random numbers from
+  /// `rng`.
+  fn recombine_with(&self, parents: [&S; P], rng: &mut SmallRng) -> [S; O];
+}
+
+impl<S, F, const P: usize, const O: usize> RandomRecombination<S, P, O> for F
+where
+  F: Fn([&S; P], &mut SmallRng) -> [S; O],
+{
+  fn recombine_with(&self, parents: [&S; P], rng: &mut SmallRng) -> [S; O] {
+    self(parents, rng)
+  }
+}
+
+/// A recombination that recombines each combination of `P` parents into `O`
+/// offsprings with a random number generator of its own, derived from the
+/// master seed, the generation and the index of the combination with
+/// [`SplitRng::indexed`]. Combinations are indexed in the same order as
+/// [`Recombination`] goes through them, including when optimizers sample
+/// them to stay within an offspring limit, so each combination is recombined
+/// the same way regardless of the order combinations are recombined in. The
+/// recombination stays reproducible when recombining in parallel with
+/// `par_each()`, and offsprings keep the order of their combinations.
+///
+/// Optimizers tell the recombination the generation they are in. Applied on
+/// its own, it counts generations by the number of times it was applied.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   optimizer::{nsga::Nsga2, Optimizer},
+///   recombination::IndexedRandomRecombination,
+///   rng::SplitRng,
+///   selection::RandomSelector,
+///   termination::GenerationTerminator,
+/// };
+/// use rand::Rng;
+///
+/// let r = IndexedRandomRecombination::new(
+///   SplitRng::new(42),
+///   |[a, b]: [&f32; 2], rng| [a + rng.gen_range(0.0..1.0) * (b - a)],
+/// );
+/// # #[cfg(feature = "parallel")]
+/// let r = r.par_each();
+/// let solutions = Nsga2::builder()
+///   .population(vec![-2.0, 0.5, 3.0])
+///   .tester(|x: &f32| [x.powf(2.0), (x - 2.0).powf(2.0)])
+///   .selector(RandomSelector(3))
+///   .recombinator(r)
+///   .mutator(|_: &mut f32| {})
+///   .terminator(GenerationTerminator(10))
+///   .build()
+///   .optimize();
+/// ```
+pub struct IndexedRandomRecombination<F, const P: usize, const O: usize> {
+  streams: IndexedStreams,
+  recombination: F,
+  parallel: bool,
+}
+
+impl<F, const P: usize, const O: usize> IndexedRandomRecombination<F, P, O> {
+  /// Creates a recombination that applies the `recombination` to each
+  /// combination of parents with a generator derived from the master seed
+  /// `rng`.
+  pub fn new<S>(rng: SplitRng, recombination: F) -> Self
+  where
+    F: Fn([&S; P], &mut SmallRng) -> [S; O],
+  {
+    Self::from_random(rng, recombination)
+  }
+
+  /// Creates a recombination that applies the [`RandomRecombination`] to
+  /// each combination of parents with a generator derived from the master
+  /// seed `rng`. Unlike [`new`](Self::new), it accepts recombinations that
+  /// aren't closures, such as [`SimulatedBinaryCrossover`].
+  ///
+  /// [`SimulatedBinaryCrossover`]: crate::genomes::real::SimulatedBinaryCrossover
+  pub fn from_random<S>(rng: SplitRng, recombination: F) -> Self
+  where
+    F: RandomRecombination<S, P, O>,
+  {
+    Self {
+      streams: IndexedStreams::new(rng),
+      recombination,
+      parallel: false,
+    }
+  }
+
+  /// Recombines parents in parallel. Offsprings are the same and in the same
+  /// order as they would be sequentially.
+  #[cfg(feature = "parallel")]
+  pub fn par_each(self) -> Self {
+    Self {
+      parallel: true,
+      ..self
+    }
+  }
+}
+
+#[cfg(not(feature = "parallel"))]
+impl<S, F, const P: usize, const O: usize>
+  RecombinationExecutor<S, P, O, IndexedExecutionStrategy>
+  for IndexedRandomRecombination<F, P, O>
+where
+  F: RandomRecombination<S, P, O>,
+{
+  fn execute_recombination(&self, parents: Vec<&S>) -> Vec<S> {
+    let generation = self.streams.next_generation();
+    Combinations::<_, P>::new(&parents)
+      .enumerate()
+      .flat_map(|(idx, c)| {
+        self
+          .recombination
+          .recombine_with(c, &mut self.streams.stream(generation, idx))
+      })
+      .collect()
+  }
+
+  fn projected_offsprings(&self, parents: usize) -> Option<usize> {
+    Some(projected_offsprings(parents, P, O))
+  }
+
+  fn execute_sampled_recombination(
+    &self,
+    parents: Vec<&S>,
+    limit: usize,
+    rng: &mut SmallRng,
+  ) -> Vec<S> {
+    let generation = self.streams.next_generation();
+    sample_indexed_combinations::<_, P>(&parents, limit / O, rng)
+      .into_iter()
+      .flat_map(|(idx, c)| {
+        self
+          .recombination
+          .recombine_with(c, &mut self.streams.stream(generation, idx))
       })
       .collect()
   }
+
+  fn start_generation(&self, generation: usize) {
+    self.streams.start_generation(generation as u64)
+  }
 }
 
-impl<S, const P: usize, const O: usize, R>
-  RecombinationExecutor<S, P, O, ParallelEachExecutionStrategy>
-  for ParEachOperator<RecombinationOperatorTag, S, R>
+#[cfg(feature = "parallel")]
+impl<S, F, const P: usize, const O: usize>
+  RecombinationExecutor<S, P, O, IndexedExecutionStrategy>
+  for IndexedRandomRecombination<F, P, O>
 where
   S: Sync + Send,
-  R: Recombination<S, P, O> + Sync,
+  F: RandomRecombination<S, P, O> + Sync,
 {
   fn execute_recombination(&self, parents: Vec<&S>) -> Vec<S> {
-    parents
-      .iter()
-      .copied()
-      .combinations(P)
-      .par_bridge()
-      .flat_map_iter(|c| {
+    let generation = self.streams.next_generation();
+    let combinations = Combinations::<_, P>::new(&parents).enumerate();
+    if self.parallel {
+      // combinations are collected to be recombined in their order
+      self.par_recombine(combinations.collect(), generation)
+    } else {
+      combinations
+        .flat_map(|(idx, c)| {
+          self
+            .recombination
+            .recombine_with(c, &mut self.streams.stream(generation, idx))
+        })
+        .collect()
+    }
+  }
+
+  fn projected_offsprings(&self, parents: usize) -> Option<usize> {
+    Some(projected_offsprings(parents, P, O))
+  }
+
+  fn execute_sampled_recombination(
+    &self,
+    parents: Vec<&S>,
+    limit: usize,
+    rng: &mut SmallRng,
+  ) -> Vec<S> {
+    let generation = self.streams.next_generation();
+    let combinations =
+      sample_indexed_combinations::<_, P>(&parents, limit / O, rng);
+    if self.parallel {
+      self.par_recombine(combinations, generation)
+    } else {
+      combinations
+        .into_iter()
+        .flat_map(|(idx, c)| {
+          self
+            .recombination
+            .recombine_with(c, &mut self.streams.stream(generation, idx))
+        })
+        .collect()
+    }
+  }
+
+  fn start_generation(&self, generation: usize) {
+    self.streams.start_generation(generation as u64)
+  }
+}
+
+#[cfg(feature = "parallel")]
+impl<F, const P: usize, const O: usize> IndexedRandomRecombination<F, P, O> {
+  /// Recombines indexed `combinations` in parallel, keeping their order.
+  fn par_recombine<S>(
+    &self,
+    combinations: Vec<(usize, [&S; P])>,
+    generation: u64,
+  ) -> Vec<S>
+  where
+    S: Sync + Send,
+    F: RandomRecombination<S, P, O> + Sync,
+  {
+    use rayon::prelude::*;
+
+    combinations
+      .into_par_iter()
+      .flat_map_iter(|(idx, c)| {
         self
-          .operator()
-          .recombine(c.try_into().unwrap_or_else(|c: Vec<&S>| {
-            panic!(
-              "combination size must be equal to {} but it is {}",
-              P,
-              c.len()
-            )
-          }))
+          .recombination
+          .recombine_with(c, &mut self.streams.stream(generation, idx))
       })
       .collect()
   }
 }
 
+impl<F, const P: usize, const O: usize> std::fmt::Debug
+  for IndexedRandomRecombination<F, P, O>
+{
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("IndexedRandomRecombination")
+      .field("streams", &self.streams)
+      .field("parallel", &self.parallel)
+      .finish_non_exhaustive()
+  }
+}
+
+/// A [`ScoredRecombinator`] that pairs parents that complement each other and
+/// recombines each pair with a recombination of two parents into `O`
+/// offsprings. The contrast of two parents is the weighted sum of
+/// differences of their absolute scores over objectives, each objective
+/// normalized by the range of scores of the parents, so parents that excel
+/// at different objectives contrast the most. Pairs are picked greedily, the
+/// most contrasting first, and each parent is paired at most once, so `n`
+/// parents create `n / 2 * O` offsprings.
+///
+/// Contrasts of all pairs are computed, so pairing `n` parents takes
+/// `O(n^2 log n)` time.
+///
+/// # Examples
+/// ```
+/// use moga::recombination::{ComplementaryPairing, ScoredRecombinator};
+///
+/// let r =
+///   ComplementaryPairing::new([1.0, 1.0], |a: &f32, b: &f32| (a + b) / 2.0);
+/// // the first and the last parents are the opposite specialists
+/// let offsprings = r.recombine(vec![
+///   (&0.0, &[0.0, 9.0]),
+///   (&4.0, &[4.0, 5.0]),
+///   (&6.0, &[6.0, 4.0]),
+///   (&9.0, &[9.0, 0.0]),
+/// ]);
+/// assert_eq!(offsprings, [4.5, 5.0]);
+/// ```
+pub struct ComplementaryPairing<R, const N: usize, const O: usize> {
+  weights: Scores<N>,
+  recombination: R,
+}
+
+impl<R, const N: usize, const O: usize> ComplementaryPairing<R, N, O> {
+  /// Creates a new `ComplementaryPairing` that weighs contrasts of objectives
+  /// with `weights` and recombines pairs of parents with the
+  /// `recombination`. Weights of `[1.0; N]` weigh objectives equally.
+  ///
+  /// # Panics
+  ///
+  /// Panics if any weight is negative, infinite or NaN.
+  pub fn new<S>(weights: Scores<N>, recombination: R) -> Self
+  where
+    R: Recombination<S, 2, O>,
+  {
+    if weights.iter().any(|w| !(w.is_finite() && *w >= 0.0)) {
+      panic!("weights must be finite and not negative")
+    }
+    Self {
+      weights,
+      recombination,
+    }
+  }
+}
+
+impl<S, R, const N: usize, const O: usize> ScoredRecombinator<S, N>
+  for ComplementaryPairing<R, N, O>
+where
+  R: Recombination<S, 2, O>,
+{
+  fn recombine(&self, parents: Vec<(&S, &Scores<N>)>) -> Vec<S> {
+    let len = parents.len();
+    // range of absolute scores of each objective among parents
+    let mut min = [Score::INFINITY; N];
+    let mut max = [Score::NEG_INFINITY; N];
+    for (_, scores) in &parents {
+      for o in 0..N {
+        min[o] = min[o].min(scores[o].abs());
+        max[o] = max[o].max(scores[o].abs());
+      }
+    }
+    let contrast = |a: &Scores<N>, b: &Scores<N>| {
+      (0..N)
+        .filter(|&o| max[o] > min[o])
+        .map(|o| {
+          let diff = (a[o].abs() - b[o].abs()).abs();
+          self.weights[o] * diff / (max[o] - min[o])
+        })
+        .filter(|c| !c.is_nan())
+        .sum::<Score>()
+    };
+    let mut pairs: Vec<_> = (0..len)
+      .flat_map(|i| (i + 1..len).map(move |j| (i, j)))
+      .map(|(i, j)| (contrast(parents[i].1, parents[j].1), i, j))
+      .collect();
+    // the most contrasting pairs first, ties broken by order of parents
+    pairs.sort_by(|a, b| b.0.total_cmp(&a.0).then((a.1, a.2).cmp(&(b.1, b.2))));
+    let mut is_paired = vec![false; len];
+    let mut offsprings = Vec::with_capacity(len / 2 * O);
+    for (_, i, j) in pairs {
+      if is_paired[i] || is_paired[j] {
+        continue;
+      }
+      is_paired[i] = true;
+      is_paired[j] = true;
+      let pair = [parents[i].0, parents[j].0];
+      offsprings.extend(self.recombination.recombine(pair));
+    }
+    offsprings
+  }
+}
+
+impl<R, const N: usize, const O: usize> std::fmt::Debug
+  for ComplementaryPairing<R, N, O>
+{
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("ComplementaryPairing")
+      .field("weights", &self.weights)
+      .finish_non_exhaustive()
+  }
+}
+
+/// A policy that [`AdaptiveRecombinator`] picks its recombinators with.
+/// Rewards of recombinators are fractions of their offsprings that survived
+/// truncation.
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AdaptivePolicy {
+  /// Upper confidence bound policy UCB1. Each recombinator is picked once,
+  /// then the one with the greatest `mean_reward + exploration *
+  /// sqrt(ln(picks) / recombinator_picks)` is picked, where `picks` is the
+  /// total number of picks. Ties go to the first recombinator.
+  Ucb {
+    /// Weight of the exploration term. `2f64.sqrt()` is the classic choice.
+    exploration: f64,
+  },
+  /// Probability matching policy. Each recombinator has a quality, an
+  /// exponential moving average of its rewards with rate `adaptation_rate`,
+  /// and is picked at random with probability `min_probability + (1 -
+  /// recombinators * min_probability) * quality / total_quality`.
+  ///
+  /// Random numbers are drawn from streams of [`SplitRng::indexed`] seeded
+  /// with `seed`, one for each pick of each generation.
+  ProbabilityMatching {
+    /// Weight of the latest reward in the quality of a recombinator, between
+    /// `0` and `1`.
+    adaptation_rate: f64,
+    /// Probability of picking a recombinator regardless of its quality, at
+    /// most `1 / recombinators`.
+    min_probability: f64,
+    /// The master seed of random numbers.
+    seed: u64,
+  },
+}
+
+/// A [`Recombinator`] that holds several recombinators and learns online
+/// which of them works best. At each generation it picks one recombinator
+/// with its [`AdaptivePolicy`] and recombines all parents with it. The
+/// picked recombinator is rewarded when the optimizer reports how many of its
+/// offsprings survived through [`OperatorFeedback`], so the recombinator
+/// should be given to the optimizer's `feedback` option as well. Clones share
+/// recombinators and their statistics.
+///
+/// With speciation, a recombinator is picked for each species, and each pick
+/// of a generation is rewarded with the fraction of all offsprings of the
+/// generation that survived.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   optimizer::{nsga::Nsga2, Optimizer},
+///   recombination::{AdaptivePolicy, AdaptiveRecombinator, BoxedRecombinator},
+///   selection::RandomSelector,
+///   termination::GenerationTerminator,
+/// };
+///
+/// let recombinator =
+///   AdaptiveRecombinator::new(AdaptivePolicy::Ucb { exploration: 0.5 }, vec![
+///     BoxedRecombinator::new(|a: &f32, b: &f32| (a + b) / 2.0),
+///     BoxedRecombinator::new(|a: &f32| a + 100.0),
+///   ]);
+/// Nsga2::builder()
+///   .population(vec![0.0, 1.0, 2.0])
+///   .tester(|x: &f32| [x.powf(2.0), (x - 2.0).powf(2.0)])
+///   .selector(RandomSelector(3))
+///   .recombinator(recombinator.clone())
+///   .mutator(|_: &mut f32| {})
+///   .terminator(GenerationTerminator(50))
+///   .feedback(recombinator.clone())
+///   .build()
+///   .optimize();
+/// let picks = recombinator.picks();
+/// assert!(picks[0] > picks[1]);
+/// ```
+///
+/// [`OperatorFeedback`]: crate::operator::OperatorFeedback
+pub struct AdaptiveRecombinator<S>(Arc<Mutex<AdaptiveState<S>>>);
+
+struct AdaptiveState<S> {
+  policy: AdaptivePolicy,
+  recombinators: Vec<BoxedRecombinator<S>>,
+  picks: Vec<usize>,
+  rewards: Vec<f64>,
+  qualities: Vec<f64>,
+  picked: Vec<usize>,
+  generations: usize,
+}
+
+impl<S> AdaptiveRecombinator<S> {
+  /// Creates a recombinator that picks one of `recombinators` at each
+  /// generation with the `policy`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if there are no `recombinators`.
+  pub fn new(
+    policy: AdaptivePolicy,
+    recombinators: Vec<BoxedRecombinator<S>>,
+  ) -> Self {
+    assert!(!recombinators.is_empty(), "no recombinators to pick from");
+    let k = recombinators.len();
+    Self(Arc::new(Mutex::new(AdaptiveState {
+      policy,
+      recombinators,
+      picks: vec![0; k],
+      rewards: vec![0.0; k],
+      qualities: vec![1.0 / k as f64; k],
+      picked: Vec::new(),
+      generations: 0,
+    })))
+  }
+
+  /// Returns the number of times each recombinator was picked.
+  pub fn picks(&self) -> Vec<usize> {
+    self.state().picks.clone()
+  }
+
+  /// Returns the mean reward of each recombinator, `0` for recombinators
+  /// that weren't rewarded yet.
+  pub fn mean_rewards(&self) -> Vec<f64> {
+    let state = self.state();
+    state
+      .rewards
+      .iter()
+      .zip(&state.picks)
+      .map(|(&reward, &picks)| match picks {
+        0 => 0.0,
+        picks => reward / picks as f64,
+      })
+      .collect()
+  }
+
+  fn state(&self) -> MutexGuard<'_, AdaptiveState<S>> {
+    self.0.lock().unwrap_or_else(|e| e.into_inner())
+  }
+}
+
+impl<S> AdaptiveState<S> {
+  fn pick(&self) -> usize {
+    match self.policy {
+      AdaptivePolicy::Ucb { exploration } => {
+        if let Some(idx) = self.picks.iter().position(|&picks| picks == 0) {
+          return idx;
+        }
+        let total = (self.picks.iter().sum::<usize>() as f64).ln();
+        let bound = |idx: usize| {
+          let picks = self.picks[idx] as f64;
+          self.rewards[idx] / picks + exploration * (total / picks).sqrt()
+        };
+        (1..self.picks.len()).fold(0, |best, idx| {
+          if bound(idx) > bound(best) {
+            idx
+          } else {
+            best
+          }
+        })
+      }
+      AdaptivePolicy::ProbabilityMatching {
+        min_probability,
+        seed,
+        ..
+      } => {
+        let k = self.qualities.len() as f64;
+        let total = self.qualities.iter().sum::<f64>();
+        // generations are counted by feedbacks, as recombinators aren't told
+        // the generation
+        let mut point = SplitRng::new(seed)
+          .indexed(self.generations as u64, self.picked.len() as u64)
+          .gen::<f64>();
+        for (idx, &quality) in self.qualities.iter().enumerate() {
+          let share = if total > 0.0 {
+            quality / total
+          } else {
+            1.0 / k
+          };
+          point -= min_probability + (1.0 - k * min_probability) * share;
+          if point < 0.0 {
+            return idx;
+          }
+        }
+        self.qualities.len() - 1
+      }
+    }
+  }
+}
+
+impl<S> Recombinator<S> for AdaptiveRecombinator<S> {
+  fn recombine(&self, parents: Vec<&S>) -> Vec<S> {
+    let mut state = self.state();
+    let idx = state.pick();
+    state.picks[idx] += 1;
+    state.picked.push(idx);
+    state.recombinators[idx].recombine(parents)
+  }
+}
+
+impl<S> OperatorFeedback for AdaptiveRecombinator<S> {
+  fn feedback(&mut self, _: usize, offsprings: usize, survived: usize) {
+    let mut state = self.state();
+    let reward = match offsprings {
+      0 => 0.0,
+      offsprings => survived as f64 / offsprings as f64,
+    };
+    state.generations += 1;
+    // offsprings of species are counted together, so each pick is rewarded
+    // the same
+    for idx in std::mem::take(&mut state.picked) {
+      state.rewards[idx] += reward;
+      if let AdaptivePolicy::ProbabilityMatching {
+        adaptation_rate, ..
+      } = state.policy
+      {
+        state.qualities[idx] +=
+          adaptation_rate * (reward - state.qualities[idx]);
+      }
+    }
+  }
+}
+
+impl<S> Clone for AdaptiveRecombinator<S> {
+  fn clone(&self) -> Self {
+    Self(Arc::clone(&self.0))
+  }
+}
+
+impl<S> std::fmt::Debug for AdaptiveRecombinator<S> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let state = self.state();
+    f.debug_struct("AdaptiveRecombinator")
+      .field("policy", &state.policy)
+      .field("recombinators", &state.recombinators.len())
+      .field("picks", &state.picks)
+      .finish_non_exhaustive()
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
+  #[cfg(feature = "parallel")]
+  use crate::operator::ParEach;
 
   type Solution = f32;
 
@@ -222,6 +1333,7 @@ mod tests {
   fn test_recombination_from_closure_1_to_1() {
     let r = |_: &Solution| 0.0;
     takes_recombinator(&r);
+    #[cfg(feature = "parallel")]
     takes_recombinator(&r.par_each());
   }
 
@@ -229,6 +1341,7 @@ mod tests {
   fn test_recombination_from_closure_1_to_2() {
     let r = |_: &Solution| (0.0, 0.0);
     takes_recombinator(&r);
+    #[cfg(feature = "parallel")]
     takes_recombinator(&r.par_each());
   }
 
@@ -236,6 +1349,7 @@ mod tests {
   fn test_recombination_from_closure_1_to_3() {
     let r = |_: &Solution| (0.0, 0.0, 0.0);
     takes_recombinator(&r);
+    #[cfg(feature = "parallel")]
     takes_recombinator(&r.par_each());
   }
 
@@ -243,6 +1357,7 @@ mod tests {
   fn test_recombination_from_closure_1_to_4() {
     let r = |_: &Solution| (0.0, 0.0, 0.0, 0.0);
     takes_recombinator(&r);
+    #[cfg(feature = "parallel")]
     takes_recombinator(&r.par_each());
   }
 
@@ -250,6 +1365,7 @@ mod tests {
   fn test_recombination_from_closure_2_to_1() {
     let r = |_: &Solution, _: &Solution| 0.0;
     takes_recombinator(&r);
+    #[cfg(feature = "parallel")]
     takes_recombinator(&r.par_each());
   }
 
@@ -257,6 +1373,7 @@ mod tests {
   fn test_recombination_from_closure_2_to_2() {
     let r = |_: &Solution, _: &Solution| (0.0, 0.0);
     takes_recombinator(&r);
+    #[cfg(feature = "parallel")]
     takes_recombinator(&r.par_each());
   }
 
@@ -264,6 +1381,7 @@ mod tests {
   fn test_recombination_from_closure_2_to_3() {
     let r = |_: &Solution, _: &Solution| (0.0, 0.0, 0.0);
     takes_recombinator(&r);
+    #[cfg(feature = "parallel")]
     takes_recombinator(&r.par_each());
   }
 
@@ -271,6 +1389,7 @@ mod tests {
   fn test_recombination_from_closure_2_to_4() {
     let r = |_: &Solution, _: &Solution| (0.0, 0.0, 0.0, 0.0);
     takes_recombinator(&r);
+    #[cfg(feature = "parallel")]
     takes_recombinator(&r.par_each());
   }
 
@@ -278,6 +1397,7 @@ mod tests {
   fn test_recombination_from_closure_3_to_1() {
     let r = |_: &Solution, _: &Solution, _: &Solution| 0.0;
     takes_recombinator(&r);
+    #[cfg(feature = "parallel")]
     takes_recombinator(&r.par_each());
   }
 
@@ -285,6 +1405,7 @@ mod tests {
   fn test_recombination_from_closure_3_to_2() {
     let r = |_: &Solution, _: &Solution, _: &Solution| (0.0, 0.0);
     takes_recombinator(&r);
+    #[cfg(feature = "parallel")]
     takes_recombinator(&r.par_each());
   }
 
@@ -292,6 +1413,7 @@ mod tests {
   fn test_recombination_from_closure_3_to_3() {
     let r = |_: &Solution, _: &Solution, _: &Solution| (0.0, 0.0, 0.0);
     takes_recombinator(&r);
+    #[cfg(feature = "parallel")]
     takes_recombinator(&r.par_each());
   }
 
@@ -299,6 +1421,7 @@ mod tests {
   fn test_recombination_from_closure_3_to_4() {
     let r = |_: &Solution, _: &Solution, _: &Solution| (0.0, 0.0, 0.0, 0.0);
     takes_recombinator(&r);
+    #[cfg(feature = "parallel")]
     takes_recombinator(&r.par_each());
   }
 
@@ -306,6 +1429,7 @@ mod tests {
   fn test_recombination_from_closure_4_to_1() {
     let r = |_: &Solution, _: &Solution, _: &Solution, _: &Solution| 0.0;
     takes_recombinator(&r);
+    #[cfg(feature = "parallel")]
     takes_recombinator(&r.par_each());
   }
 
@@ -313,6 +1437,7 @@ mod tests {
   fn test_recombination_from_closure_4_to_2() {
     let r = |_: &Solution, _: &Solution, _: &Solution, _: &Solution| (0.0, 0.0);
     takes_recombinator(&r);
+    #[cfg(feature = "parallel")]
     takes_recombinator(&r.par_each());
   }
 
@@ -321,6 +1446,7 @@ mod tests {
     let r =
       |_: &Solution, _: &Solution, _: &Solution, _: &Solution| (0.0, 0.0, 0.0);
     takes_recombinator(&r);
+    #[cfg(feature = "parallel")]
     takes_recombinator(&r.par_each());
   }
 
@@ -330,6 +1456,7 @@ mod tests {
       (0.0, 0.0, 0.0, 0.0)
     };
     takes_recombinator(&r);
+    #[cfg(feature = "parallel")]
     takes_recombinator(&r.par_each());
   }
 
@@ -350,6 +1477,7 @@ mod tests {
 
     let recombination = CustomRecombination {};
     takes_recombinator(&recombination);
+    #[cfg(feature = "parallel")]
     takes_recombinator(&recombination.par_each());
   }
 
@@ -365,4 +1493,343 @@ mod tests {
     let recombination = CustomRecombinator {};
     takes_recombinator(&recombination);
   }
+
+  #[test]
+  fn test_combinations() {
+    let (a, b, c, d) = (0.0, 1.0, 2.0, 3.0);
+    let parents = vec![&a, &b, &c, &d];
+    let pairs: Vec<[&Solution; 2]> = Combinations::new(&parents).collect();
+    assert_eq!(pairs, [[&a, &b], [&a, &c], [&a, &d], [&b, &c], [&b, &d], [
+      &c, &d
+    ]]);
+    assert_eq!(Combinations::<_, 4>::new(&parents).count(), 1);
+    assert_eq!(Combinations::<_, 1>::new(&parents).count(), 4);
+    assert_eq!(Combinations::<_, 3>::new(&parents[..2]).count(), 0);
+    assert_eq!(Combinations::<&Solution, 2>::new(&[]).count(), 0);
+  }
+
+  #[test]
+  fn test_combinations_count() {
+    for n in 0..10 {
+      let parents = vec![&0.0; n];
+      assert_eq!(
+        combinations_count(n, 3),
+        Some(Combinations::<&Solution, 3>::new(&parents).count())
+      );
+    }
+    assert_eq!(combinations_count(4, 2), Some(6));
+    assert_eq!(combinations_count(2, 4), Some(0));
+    assert_eq!(combinations_count(usize::MAX, 2), None);
+  }
+
+  #[test]
+  fn test_sampled_recombination() {
+    use rand::SeedableRng;
+
+    let parents: Vec<f32> = (0..30).map(|i| i as f32).collect();
+    let parents: Vec<_> = parents.iter().collect();
+    let r = |a: &f32, b: &f32, c: &f32| (a + b + c, a * b * c);
+    let r: &dyn RecombinationExecutor<_, 3, 2, SequentialExecutionStrategy> =
+      &r;
+    assert_eq!(r.projected_offsprings(parents.len()), Some(4060 * 2));
+    let custom: &dyn RecombinationExecutor<
+      _,
+      { usize::MAX },
+      { usize::MAX },
+      _,
+    > = &|_: Vec<&f32>| vec![];
+    assert_eq!(custom.projected_offsprings(parents.len()), None);
+
+    let mut rng = SmallRng::seed_from_u64(0);
+    let offsprings =
+      r.execute_sampled_recombination(parents.clone(), 101, &mut rng);
+    assert_eq!(offsprings.len(), 100);
+    let mut rng = SmallRng::seed_from_u64(0);
+    assert_eq!(
+      r.execute_sampled_recombination(parents.clone(), 101, &mut rng),
+      offsprings
+    );
+
+    let combinations = sample_combinations::<_, 3>(&parents, 50, &mut rng);
+    assert_eq!(combinations.len(), 50);
+    assert!(combinations.windows(2).all(|w| w[0] < w[1]));
+    let combinations = sample_combinations::<_, 3>(&parents[..4], 50, &mut rng);
+    assert_eq!(combinations.len(), 4);
+  }
+
+  #[test]
+  fn test_single_parent_recombination() {
+    let parents: Vec<Solution> = (0..100).map(|i| i as Solution).collect();
+    let parents: Vec<_> = parents.iter().collect();
+    let r = |a: &Solution| (*a, -a);
+    let generic: Vec<Solution> = Combinations::new(&parents)
+      .flat_map(|c| Recombination::recombine(&r, c))
+      .collect();
+    assert_eq!(r.execute_recombination(parents.clone()), generic);
+    #[cfg(feature = "parallel")]
+    assert_eq!(r.par_each().execute_recombination(parents), generic);
+  }
+
+  #[test]
+  fn test_by_value_recombination() {
+    use rand::SeedableRng;
+
+    let solutions: Vec<Solution> = (0..30).map(|i| i as Solution).collect();
+    let selected: Vec<usize> = (0..30).rev().step_by(2).collect();
+    let by_reference = |a: &Solution, b: &Solution| (a + b, a - b);
+    let by_value = ByValue(|[a, b]: [Solution; 2]| [a + b, a - b]);
+    takes_recombinator(&by_value);
+    assert_eq!(
+      by_value.execute_selected_recombination(&solutions, &selected),
+      by_reference.execute_selected_recombination(&solutions, &selected)
+    );
+    let parents: Vec<_> = selected.iter().map(|&idx| &solutions[idx]).collect();
+    assert_eq!(
+      by_value.execute_recombination(parents.clone()),
+      by_reference.execute_recombination(parents)
+    );
+    let single = ByValue(|[a]: [Solution; 1]| [-a]);
+    assert_eq!(
+      single.execute_selected_recombination(&solutions, &[3, 1]),
+      [-3.0, -1.0]
+    );
+
+    fn sample<ES>(
+      r: &impl RecombinationExecutor<Solution, 2, 2, ES>,
+      solutions: &[Solution],
+      selected: &[usize],
+    ) -> Vec<Solution> {
+      let mut rng = SmallRng::seed_from_u64(0);
+      r.execute_sampled_selected_recombination(
+        solutions, selected, 20, &mut rng,
+      )
+    }
+    let sampled = sample(&by_value, &solutions, &selected);
+    assert_eq!(sampled.len(), 20);
+    assert_eq!(sampled, sample(&by_reference, &solutions, &selected));
+  }
+
+  #[test]
+  fn test_clone_parents() {
+    use rand::SeedableRng;
+
+    let solutions: Vec<Solution> = (0..30).map(|i| i as Solution).collect();
+    let selected: Vec<usize> = (0..30).rev().step_by(2).collect();
+    let cloning = |a: &Solution| *a;
+    takes_recombinator(&CloneParents);
+    assert_eq!(
+      CloneParents.execute_selected_recombination(&solutions, &selected),
+      selected
+        .iter()
+        .map(|&idx| solutions[idx])
+        .collect::<Vec<_>>()
+    );
+    let parents: Vec<_> = selected.iter().map(|&idx| &solutions[idx]).collect();
+    assert_eq!(
+      CloneParents.execute_recombination(parents.clone()),
+      cloning.execute_recombination(parents)
+    );
+    assert_eq!(
+      RecombinationExecutor::<Solution, 1, 1, _>::projected_offsprings(
+        &CloneParents,
+        15
+      ),
+      Some(15)
+    );
+
+    fn sample<ES>(
+      r: &impl RecombinationExecutor<Solution, 1, 1, ES>,
+      solutions: &[Solution],
+      selected: &[usize],
+    ) -> Vec<Solution> {
+      let mut rng = SmallRng::seed_from_u64(0);
+      r.execute_sampled_selected_recombination(
+        solutions, selected, 10, &mut rng,
+      )
+    }
+    let sampled = sample(&CloneParents, &solutions, &selected);
+    assert_eq!(sampled.len(), 10);
+    assert_eq!(sampled, sample(&cloning, &solutions, &selected));
+  }
+
+  #[test]
+  fn test_scored_recombinator() {
+    let r = |parents: Vec<(&Solution, &Scores<2>)>| {
+      parents
+        .into_iter()
+        .map(|(p, sc)| p + sc[0] + sc[1])
+        .collect()
+    };
+    takes_recombinator(&r);
+    let solutions = [1.0, 2.0, 3.0];
+    let scores = [[10.0, 20.0], [30.0, 40.0], [50.0, 60.0]];
+    let offsprings =
+      r.execute_scored_selected_recombination(&solutions, &scores, &[2, 0]);
+    assert_eq!(offsprings, [113.0, 31.0]);
+    // parents get NaN scores without them
+    assert!(r.execute_recombination(vec![&1.0])[0].is_nan());
+  }
+
+  #[test]
+  #[should_panic(expected = "scored recombinator of 2 objectives")]
+  fn test_scored_recombinator_of_other_objectives() {
+    let r = |parents: Vec<(&Solution, &Scores<2>)>| {
+      parents.into_iter().map(|(p, _)| *p).collect()
+    };
+    r.execute_scored_selected_recombination(&[1.0], &[[1.0]], &[0]);
+  }
+
+  #[test]
+  fn test_complementary_pairing() {
+    let r =
+      ComplementaryPairing::new([1.0, 1.0], |a: &Solution, b: &Solution| {
+        (*a, *b)
+      });
+    takes_recombinator(&r);
+    let recombine = |parents: &[(Solution, Scores<2>)]| {
+      r.recombine(parents.iter().map(|(p, sc)| (p, sc)).collect())
+    };
+    assert!(recombine(&[]).is_empty());
+    assert!(recombine(&[(0.0, [1.0, 1.0])]).is_empty());
+    // signs of scores don't matter, and the odd parent is left unpaired
+    let offsprings =
+      recombine(&[(0.0, [-1.0, 0.0]), (1.0, [0.5, 0.5]), (2.0, [0.0, 1.0])]);
+    assert_eq!(offsprings, [0.0, 2.0]);
+    // objectives with equal or NaN scores don't contrast parents
+    let offsprings = recombine(&[
+      (0.0, [1.0, f32::NAN]),
+      (1.0, [1.0, 0.0]),
+      (2.0, [1.0, 5.0]),
+      (3.0, [1.0, 1.0]),
+    ]);
+    assert_eq!(offsprings, [1.0, 2.0, 0.0, 3.0]);
+    // contrasts of objectives of zero weight don't count
+    let r =
+      ComplementaryPairing::new([0.0, 1.0], |a: &Solution, b: &Solution| {
+        (*a, *b)
+      });
+    let offsprings = r.recombine(vec![
+      (&0.0, &[0.0, 1.0]),
+      (&1.0, &[9.0, 1.0]),
+      (&2.0, &[0.0, 0.0]),
+    ]);
+    assert_eq!(offsprings, [0.0, 2.0]);
+  }
+
+  #[test]
+  fn test_boxed_recombinator() {
+    let recombinator =
+      BoxedRecombinator::new(|a: &Solution, b: &Solution| a + b);
+    takes_recombinator(&recombinator);
+    let offsprings = recombinator.execute_recombination(vec![&1.0, &2.0, &3.0]);
+    assert_eq!(offsprings, [3.0, 4.0, 5.0]);
+    #[cfg(feature = "parallel")]
+    {
+      let recombinator =
+        BoxedRecombinator::new((|a: &Solution| (*a, -a)).par_each());
+      let offsprings = recombinator.execute_recombination(vec![&1.0]);
+      assert_eq!(offsprings, [1.0, -1.0]);
+    }
+  }
+
+  #[test]
+  fn test_indexed_random_recombination() {
+    use rand::{Rng, SeedableRng};
+
+    let recombination = |[a, b]: [&Solution; 2], rng: &mut SmallRng| {
+      [a + b, rng.gen_range(0.0..1.0)]
+    };
+    let parents: Vec<Solution> = (0..20).map(|i| i as Solution).collect();
+    let parents: Vec<_> = parents.iter().collect();
+    let r = IndexedRandomRecombination::new(SplitRng::new(0), recombination);
+    let offsprings = r.execute_recombination(parents.clone());
+    assert_eq!(offsprings.len(), 2 * 190);
+    assert_eq!(offsprings[0], 1.0);
+    assert_eq!(offsprings[2], 2.0);
+    assert_ne!(offsprings[1], offsprings[3]);
+    assert_ne!(offsprings, r.execute_recombination(parents.clone()));
+    // the generation is counted from the one the optimizer starts
+    r.start_generation(0);
+    assert_eq!(r.execute_recombination(parents.clone()), offsprings);
+    // sampled combinations are recombined as they are in full
+    r.start_generation(0);
+    let mut rng = SmallRng::seed_from_u64(0);
+    let sampled =
+      r.execute_sampled_recombination(parents.clone(), 20, &mut rng);
+    assert_eq!(sampled.len(), 20);
+    assert!(sampled
+      .chunks(2)
+      .all(|o| offsprings.chunks(2).any(|expected| expected == o)));
+    #[cfg(feature = "parallel")]
+    {
+      let r = IndexedRandomRecombination::new(SplitRng::new(0), recombination)
+        .par_each();
+      assert_eq!(r.execute_recombination(parents.clone()), offsprings);
+      r.start_generation(0);
+      let mut rng = SmallRng::seed_from_u64(0);
+      assert_eq!(
+        r.execute_sampled_recombination(parents, 20, &mut rng),
+        sampled
+      );
+    }
+    takes_recombinator(&r);
+  }
+
+  #[test]
+  #[cfg(feature = "parallel")]
+  fn test_par_each_recombination_keeps_order() {
+    let parents: Vec<Solution> = (0..100).map(|i| i as Solution).collect();
+    let parents: Vec<_> = parents.iter().collect();
+    let r = |a: &Solution, b: &Solution| a * 1000.0 + b;
+    let expected = r.execute_recombination(parents.clone());
+    assert_eq!(r.par_each().execute_recombination(parents), expected);
+  }
+
+  #[test]
+  fn test_adaptive_recombinator_ucb() {
+    let mut r = AdaptiveRecombinator::new(
+      AdaptivePolicy::Ucb { exploration: 0.5 },
+      vec![
+        BoxedRecombinator::new(|a: &f32| -a),
+        BoxedRecombinator::new(|a: &f32| a * 2.0),
+      ],
+    );
+    // the first recombinator's offsprings never survive
+    for generation in 0..20 {
+      let offsprings = r.recombine(vec![&1.0, &2.0]);
+      let survived = if offsprings[0] > 0.0 { 2 } else { 0 };
+      r.feedback(generation, 2, survived);
+    }
+    let picks = r.picks();
+    assert_eq!(picks.iter().sum::<usize>(), 20);
+    assert!(picks[1] > 15, "{picks:?}");
+    assert_eq!(r.mean_rewards(), vec![0.0, 1.0]);
+  }
+
+  #[test]
+  fn test_adaptive_recombinator_probability_matching() {
+    let picks = |seed| {
+      let mut r = AdaptiveRecombinator::new(
+        AdaptivePolicy::ProbabilityMatching {
+          adaptation_rate: 0.5,
+          min_probability: 0.05,
+          seed,
+        },
+        vec![
+          BoxedRecombinator::new(|a: &f32| -a),
+          BoxedRecombinator::new(|a: &f32| a * 2.0),
+        ],
+      );
+      for generation in 0..200 {
+        let offsprings = r.recombine(vec![&1.0]);
+        let survived = usize::from(offsprings[0] > 0.0);
+        r.feedback(generation, 1, survived);
+      }
+      r.picks()
+    };
+    let first = picks(0);
+    assert!(first[1] > first[0] * 3, "{first:?}");
+    // the same seed picks the same recombinators
+    assert_eq!(picks(0), first);
+  }
 }
@@ -0,0 +1,66 @@
+//! Parallel execution of recombination operators.
+
+use rand::rngs::SmallRng;
+use rayon::prelude::*;
+
+use super::{
+  executor::RecombinationExecutor,
+  projected_offsprings,
+  sample_combinations,
+  Combinations,
+  Recombination,
+};
+use crate::{
+  execution::strategy::*,
+  operator::{tag::RecombinationOperatorTag, ParEach, ParEachOperator},
+};
+
+impl<S, R, const P: usize, const O: usize>
+  ParEach<RecombinationOperatorTag, S, P, O> for R
+where
+  S: Sync + Send,
+  R: Recombination<S, P, O> + Sync,
+{
+}
+
+impl<S, const P: usize, const O: usize, R>
+  RecombinationExecutor<S, P, O, ParallelEachExecutionStrategy>
+  for ParEachOperator<RecombinationOperatorTag, S, R>
+where
+  S: Sync + Send,
+  R: Recombination<S, P, O> + Sync,
+{
+  fn execute_recombination(&self, parents: Vec<&S>) -> Vec<S> {
+    if P == 1 {
+      // each parent is its own combination, so there is nothing to collect
+      return parents
+        .par_iter()
+        .flat_map_iter(|&p| {
+          self.operator().recombine(std::array::from_fn(|_| p))
+        })
+        .collect();
+    }
+    // combinations are collected to keep offsprings in their order
+    let combinations: Vec<_> = Combinations::new(&parents).collect();
+    combinations
+      .into_par_iter()
+      .flat_map_iter(|c| self.operator().recombine(c))
+      .collect()
+  }
+
+  fn projected_offsprings(&self, parents: usize) -> Option<usize> {
+    Some(projected_offsprings(parents, P, O))
+  }
+
+  fn execute_sampled_recombination(
+    &self,
+    parents: Vec<&S>,
+    limit: usize,
+    rng: &mut SmallRng,
+  ) -> Vec<S> {
+    sample_combinations::<_, P>(&parents, limit / O, rng)
+      .into_par_iter()
+      .flat_map_iter(|c| self.operator().recombine(c))
+      .collect()
+  }
+}
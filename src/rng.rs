@@ -0,0 +1,217 @@
+//! Seeding of random number generators of operators for reproducible runs.
+//!
+//! An optimization is reproducible if each random operator draws from its own
+//! seeded generator. [`SplitRng`] derives such generators from one master
+//! seed, and built-in random selectors accept them with their `with_rng()`
+//! method.
+//!
+//! Operators that are applied to solutions in parallel can't share a
+//! generator without making results depend on the order threads run in.
+//! Indexed adapters, such as [`IndexedRandomMutation`], give each solution a
+//! generator of its own instead, derived with [`SplitRng::indexed`].
+//!
+//! [`IndexedRandomMutation`]: crate::mutation::IndexedRandomMutation
+//!
+//! # Examples
+//! ```
+//! use std::sync::Mutex;
+//!
+//! use moga::{
+//!   optimizer::{nsga::Nsga2, Optimizer},
+//!   rng::SplitRng,
+//!   selection::TournamentSelectorWithReplacement,
+//!   termination::GenerationTerminator,
+//! };
+//! use rand::Rng;
+//!
+//! let optimize = |seed| {
+//!   let master = SplitRng::new(seed);
+//!   let recombination_rng = Mutex::new(master.stream("recombination"));
+//!   Nsga2::builder()
+//!     .population((0..10).map(|i| i as f32).collect())
+//!     .tester(|x: &f32| [x.powf(2.0), (x - 2.0).powf(2.0)])
+//!     .selector(
+//!       TournamentSelectorWithReplacement(10, 2)
+//!         .with_rng(master.stream("selection")),
+//!     )
+//!     .recombinator(move |a: &f32, b: &f32| {
+//!       a + recombination_rng.lock().unwrap().gen_range(-1.0..2.0) * (b - a)
+//!     })
+//!     .mutator(|_: &mut f32| {})
+//!     .terminator(GenerationTerminator(10))
+//!     .build()
+//!     .optimize()
+//! };
+//! assert_eq!(optimize(42), optimize(42));
+//! ```
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use rand::{
+  rngs::{SmallRng, StdRng},
+  SeedableRng,
+};
+
+/// A master seed that independent streams of random numbers are derived
+/// from by their names. The same seed and name always give the same stream,
+/// and different names give unrelated streams.
+///
+/// Streams are [`StdRng`]s, which are deterministic for a given version of
+/// `rand` crate.
+///
+/// # Examples
+/// ```
+/// use moga::rng::SplitRng;
+/// use rand::Rng;
+///
+/// let master = SplitRng::new(7);
+/// let a: u64 = master.stream("selection").gen();
+/// let b: u64 = master.stream("mutation").gen();
+/// assert_ne!(a, b);
+/// assert_eq!(a, SplitRng::new(7).stream("selection").gen::<u64>());
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SplitRng {
+  seed: u64,
+}
+
+impl SplitRng {
+  /// Creates a master seed.
+  pub fn new(seed: u64) -> Self {
+    Self { seed }
+  }
+
+  /// Returns the master seed.
+  pub fn seed(&self) -> u64 {
+    self.seed
+  }
+
+  /// Returns a stream of random numbers named `name`.
+  pub fn stream(&self, name: &str) -> StdRng {
+    StdRng::seed_from_u64(self.derive(name))
+  }
+
+  /// Returns a master seed named `name` to derive streams of a group of
+  /// operators from, such as operators of one of several optimizers.
+  pub fn split(&self, name: &str) -> Self {
+    Self::new(self.derive(name))
+  }
+
+  /// Returns a stream of random numbers of the solution at `index` in the
+  /// `generation`. Streams of different solutions and generations are
+  /// unrelated.
+  ///
+  /// Unlike named streams, indexed streams are [`SmallRng`]s, which are cheap
+  /// to create for each solution, but may differ between platforms.
+  ///
+  /// # Examples
+  /// ```
+  /// use moga::rng::SplitRng;
+  /// use rand::Rng;
+  ///
+  /// let master = SplitRng::new(7);
+  /// let a: u64 = master.indexed(0, 1).gen();
+  /// assert_ne!(a, master.indexed(1, 0).gen::<u64>());
+  /// assert_eq!(a, master.indexed(0, 1).gen::<u64>());
+  /// ```
+  pub fn indexed(&self, generation: u64, index: u64) -> SmallRng {
+    let generation_seed = splitmix64(self.seed ^ splitmix64(generation));
+    SmallRng::seed_from_u64(splitmix64(generation_seed ^ splitmix64(index)))
+  }
+
+  /// Derives a seed from the master seed and the `name`.
+  fn derive(&self, name: &str) -> u64 {
+    // FNV-1a, unlike `std` hashers, is guaranteed to stay the same
+    let name_hash = name.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+      (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    });
+    splitmix64(self.seed ^ splitmix64(name_hash))
+  }
+}
+
+/// Indexed streams of an operator, which counts generations by the number of
+/// times the operator was applied to the population.
+#[derive(Debug)]
+pub(crate) struct IndexedStreams {
+  rng: SplitRng,
+  generations: AtomicU64,
+}
+
+impl IndexedStreams {
+  /// Creates indexed streams starting at generation `0`.
+  pub(crate) fn new(rng: SplitRng) -> Self {
+    Self {
+      rng,
+      generations: AtomicU64::new(0),
+    }
+  }
+
+  /// Starts the next generation, returning its number.
+  pub(crate) fn next_generation(&self) -> u64 {
+    self.generations.fetch_add(1, Ordering::Relaxed)
+  }
+
+  /// Makes the `generation` the next one to start, so that generations are
+  /// counted from it.
+  pub(crate) fn start_generation(&self, generation: u64) {
+    self.generations.store(generation, Ordering::Relaxed)
+  }
+
+  /// Returns the stream of the solution at `index` in the `generation`.
+  pub(crate) fn stream(&self, generation: u64, index: usize) -> SmallRng {
+    self.rng.indexed(generation, index as u64)
+  }
+}
+
+/// A step of SplitMix64 generator, which scrambles bits of `x` well.
+fn splitmix64(x: u64) -> u64 {
+  let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+  z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+  z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+  z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::Rng;
+
+  use super::*;
+
+  #[test]
+  fn test_streams_are_stable() {
+    let master = SplitRng::new(0);
+    // derived seeds must not change between versions of the crate
+    assert_eq!(master.derive("selection"), 0xcf08_526a_9e70_ac29);
+    let draw =
+      |rng: &mut StdRng| (0..4).map(|_| rng.gen()).collect::<Vec<u32>>();
+    assert_eq!(
+      draw(&mut master.stream("selection")),
+      draw(&mut master.stream("selection"))
+    );
+    assert_ne!(
+      draw(&mut master.stream("selection")),
+      draw(&mut master.stream("mutation"))
+    );
+    assert_ne!(
+      draw(&mut master.stream("selection")),
+      draw(&mut SplitRng::new(1).stream("selection"))
+    );
+    assert_ne!(
+      master.split("a").stream("b").gen::<u64>(),
+      master.stream("b").gen::<u64>()
+    );
+  }
+
+  #[test]
+  fn test_indexed_streams() {
+    let streams = IndexedStreams::new(SplitRng::new(0));
+    assert_eq!(streams.next_generation(), 0);
+    assert_eq!(streams.next_generation(), 1);
+    let draw = |g, i| streams.stream(g, i).gen::<u64>();
+    assert_eq!(draw(0, 0), draw(0, 0));
+    assert_ne!(draw(0, 0), draw(0, 1));
+    assert_ne!(draw(0, 1), draw(1, 0));
+    assert_ne!(draw(1, 0), SplitRng::new(1).indexed(1, 0).gen::<u64>());
+  }
+}
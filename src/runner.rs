@@ -0,0 +1,217 @@
+//! Running optimizers on background threads.
+//!
+//! [`spawn`] starts an optimizer on a new thread and returns a [`RunHandle`]
+//! that observes its progress, cancels it and waits for its result without
+//! blocking the spawning thread in the meantime.
+//!
+//! # Examples
+//! ```
+//! use moga::{
+//!   optimizer::nsga::Nsga2,
+//!   runner,
+//!   selection::RandomSelector,
+//!   termination::GenerationTerminator,
+//! };
+//!
+//! let optimizer = Nsga2::builder()
+//!   .population(vec![1.0, 2.0, 3.0])
+//!   .tester(|x: &f32| [x.powf(2.0), (x - 2.0).powf(2.0)])
+//!   .selector(RandomSelector(3))
+//!   .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+//!   .mutator(|_: &mut f32| {})
+//!   .terminator(GenerationTerminator(usize::MAX))
+//!   .build();
+//! let handle = runner::spawn(optimizer);
+//! while handle.progress().is_none() {
+//!   std::thread::yield_now();
+//! }
+//! handle.cancel();
+//! let solutions = handle.join().unwrap();
+//! assert!(!solutions.is_empty());
+//! ```
+
+use std::{
+  panic,
+  sync::{Arc, Mutex},
+  thread::{self, JoinHandle},
+};
+
+use crate::{
+  observer::{GenerationRecord, Observer},
+  optimizer::{OptimizationError, Optimizer},
+  score::Scores,
+  termination::FlagTerminator,
+};
+
+/// An [`Optimizer`] that can be run with [`spawn`]. Implemented by
+/// [`Nsga2`] and [`Spea2`].
+///
+/// [`Nsga2`]: crate::optimizer::nsga::Nsga2
+/// [`Spea2`]: crate::optimizer::spea::Spea2
+pub trait Spawnable<S, const N: usize>: Optimizer<S, N> {
+  /// Returns a clone of the optimizer's cancel [`FlagTerminator`], setting a
+  /// new one if the optimizer has none.
+  fn cancel_flag(&mut self) -> FlagTerminator;
+
+  /// Adds an `observer` that is notified after the optimizer's own observer,
+  /// if it has one.
+  fn add_observer(&mut self, observer: Box<dyn Observer<S, N> + Send>);
+}
+
+/// A handle of an optimizer running on a background thread, returned by
+/// [`spawn`].
+#[derive(Debug)]
+pub struct RunHandle<S, const N: usize> {
+  flag: FlagTerminator,
+  progress: Arc<Mutex<Option<GenerationRecord<N>>>>,
+  thread: JoinHandle<Result<Vec<S>, OptimizationError>>,
+}
+
+impl<S, const N: usize> RunHandle<S, N> {
+  /// Cancels optimization. The optimizer stops at the start of its next
+  /// generation, so a generation in progress is completed first.
+  pub fn cancel(&self) {
+    self.flag.raise();
+  }
+
+  /// Returns the [`GenerationRecord`] of the last completed generation, or
+  /// `None` if no generation has completed yet.
+  pub fn progress(&self) -> Option<GenerationRecord<N>> {
+    *self.progress.lock().unwrap_or_else(|e| e.into_inner())
+  }
+
+  /// Returns `true` if the optimizer has finished, successfully or not.
+  pub fn is_finished(&self) -> bool {
+    self.thread.is_finished()
+  }
+
+  /// Waits for the optimizer to finish and returns the result of
+  /// [`Optimizer::try_optimize`]. A cancelled optimizer returns the last
+  /// found population.
+  ///
+  /// # Panics
+  ///
+  /// Resumes the panic of the background thread if the optimizer panicked.
+  pub fn join(self) -> Result<Vec<S>, OptimizationError> {
+    self
+      .thread
+      .join()
+      .unwrap_or_else(|payload| panic::resume_unwind(payload))
+  }
+}
+
+/// Runs the `optimizer` on a new thread and returns a [`RunHandle`] of it.
+pub fn spawn<S, const N: usize>(
+  mut optimizer: impl Spawnable<S, N> + Send + 'static,
+) -> RunHandle<S, N>
+where
+  S: Send + 'static,
+{
+  let flag = optimizer.cancel_flag();
+  let progress = Arc::new(Mutex::new(None));
+  let shared = Arc::clone(&progress);
+  optimizer.add_observer(Box::new(
+    move |generation: usize, _: &[S], scores: &[Scores<N>]| {
+      let record = GenerationRecord::new(generation, scores, None);
+      *shared.lock().unwrap_or_else(|e| e.into_inner()) = Some(record);
+    },
+  ));
+  let thread = thread::spawn(move || optimizer.try_optimize());
+  RunHandle {
+    flag,
+    progress,
+    thread,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::{Duration, Instant};
+
+  use super::*;
+  use crate::{
+    optimizer::{nsga::Nsga2, spea::Spea2},
+    selection::RandomSelector,
+    termination::GenerationTerminator,
+  };
+
+  fn wait_for_progress<S, const N: usize>(handle: &RunHandle<S, N>) {
+    while handle.progress().is_none() {
+      thread::yield_now();
+    }
+  }
+
+  #[test]
+  fn test_cancel_then_join() {
+    let optimizer = Nsga2::builder()
+      .population(vec![1.0, 2.0, 3.0, 4.0])
+      .tester(|x: &f32| [x.powf(2.0), (x - 2.0).powf(2.0)])
+      .selector(RandomSelector(4))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(usize::MAX))
+      .build();
+    let handle = spawn(optimizer);
+    wait_for_progress(&handle);
+    let start = Instant::now();
+    handle.cancel();
+    let solutions = handle.join().unwrap();
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert_eq!(solutions.len(), 4);
+  }
+
+  #[test]
+  fn test_spawn_keeps_own_observer() {
+    let generations = Arc::new(Mutex::new(0));
+    let observed = Arc::clone(&generations);
+    let optimizer = Spea2::builder()
+      .population(vec![1.0, 2.0, 3.0, 4.0])
+      .archive_size(4)
+      .tester(|x: &f32| [x.powf(2.0), (x - 2.0).powf(2.0)])
+      .selector(RandomSelector(4))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(3))
+      .observer(move |_: usize, _: &[f32], _: &[Scores<2>]| {
+        *observed.lock().unwrap() += 1;
+      })
+      .build();
+    let handle = spawn(optimizer);
+    handle.join().unwrap();
+    assert_eq!(*generations.lock().unwrap(), 3);
+  }
+
+  #[test]
+  fn test_progress() {
+    let optimizer = Nsga2::builder()
+      .population(vec![1.0, 2.0, 3.0])
+      .tester(|x: &f32| [x.powf(2.0), (x - 2.0).powf(2.0)])
+      .selector(RandomSelector(3))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(2))
+      .build();
+    let handle = spawn(optimizer);
+    while !handle.is_finished() {
+      thread::yield_now();
+    }
+    let record = handle.progress().unwrap();
+    assert_eq!(record.generation, 1);
+    assert_eq!(record.population_size, 3);
+    assert_eq!(handle.join().unwrap().len(), 3);
+  }
+
+  #[test]
+  #[should_panic(expected = "tester panicked")]
+  fn test_join_propagates_panic() {
+    let optimizer = Nsga2::builder()
+      .population(vec![1.0, 2.0, 3.0])
+      .tester(|_: &f32| -> [f32; 2] { panic!("tester panicked") })
+      .selector(RandomSelector(3))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(2))
+      .build();
+    let _ = spawn(optimizer).join();
+  }
+}
@@ -3,6 +3,46 @@
 
 use std::cmp::Ordering;
 
+mod archive;
+mod asf;
+mod attainment;
+mod front_index;
+mod hypervolume;
+mod igd;
+mod kdtree;
+mod knee;
+mod labels;
+#[cfg(feature = "score-matrix")]
+pub(crate) mod matrix;
+mod quantizer;
+mod reduction;
+#[cfg(feature = "serde")]
+pub(crate) mod serde_scores;
+pub(crate) mod sorting;
+mod stats;
+mod transform;
+
+pub use archive::{EpsilonSchedule, InsertOutcome, ParetoArchive};
+pub use asf::asf;
+pub use attainment::{
+  attainment_difference,
+  attainment_surface,
+  AttainmentDifference,
+};
+pub use front_index::FrontIndex;
+pub use hypervolume::hypervolume;
+pub(crate) use hypervolume::hypervolume_contributions;
+pub use igd::igd;
+pub(crate) use kdtree::KdTree;
+pub use knee::knee_points;
+pub use labels::ObjectiveLabels;
+pub use quantizer::ScoreQuantizer;
+pub use reduction::{objective_correlation, suggest_redundant};
+pub use sorting::pareto_front_indices;
+pub use stats::ScoreStats;
+pub use transform::ScoreTransform;
+pub(crate) use transform::{select_by, shadow_view};
+
 /// An alias for a fitness score.
 ///
 /// The target value of a score, which it converges at, is considered to be `0`.
@@ -14,6 +54,45 @@ pub type Score = f32;
 /// An alias for an array of `N` values of `Score` type.
 pub type Scores<const N: usize> = [Score; N];
 
+/// A solution paired with its scores. Pairs of solutions and their scores
+/// convert into it and back.
+///
+/// With `serde` feature enabled, it implements `Serialize` and `Deserialize`
+/// as a struct with `solution` and `scores` fields.
+///
+/// # Examples
+/// ```
+/// use moga::score::ScoredSolution;
+///
+/// let scored: Vec<ScoredSolution<_, 2>> = vec![("a", [1.0, 2.0])]
+///   .into_iter()
+///   .map(ScoredSolution::from)
+///   .collect();
+/// assert_eq!(scored[0].solution, "a");
+/// assert_eq!(scored[0].scores, [1.0, 2.0]);
+/// ```
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScoredSolution<S, const N: usize> {
+  /// The solution.
+  pub solution: S,
+  /// Scores of the solution.
+  #[cfg_attr(feature = "serde", serde(with = "serde_scores"))]
+  pub scores: Scores<N>,
+}
+
+impl<S, const N: usize> From<(S, Scores<N>)> for ScoredSolution<S, N> {
+  fn from((solution, scores): (S, Scores<N>)) -> Self {
+    Self { solution, scores }
+  }
+}
+
+impl<S, const N: usize> From<ScoredSolution<S, N>> for (S, Scores<N>) {
+  fn from(scored: ScoredSolution<S, N>) -> Self {
+    (scored.solution, scored.scores)
+  }
+}
+
 /// Describes pareto dominance for arrays of `Score`s.
 pub(crate) trait ParetoDominance {
   /// Returns `Less` if `self` dominates `other`, `Greater` if `other`
@@ -37,6 +116,14 @@ impl ParetoDominance for [Score] {
   }
 }
 
+/// Returns squared euclidean distance between scores `a` and `b`.
+pub(crate) fn squared_distance<const N: usize>(
+  a: &Scores<N>,
+  b: &Scores<N>,
+) -> f64 {
+  a.iter().zip(b).map(|(a, b)| (a - b).powf(2.0) as f64).sum()
+}
+
 #[cfg(test)]
 mod tests {
   use std::cmp::Ordering;
@@ -88,4 +175,22 @@ mod tests {
 
     assert_eq!([1.0; 0].dominance(&[0.0; 0]), Ordering::Equal);
   }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn test_scored_solution_serde_round_trip() {
+    let scored = ScoredSolution {
+      solution: (1, "a".to_string()),
+      scores: [0.5; 40],
+    };
+    let json = serde_json::to_string(&scored).unwrap();
+    assert_eq!(
+      serde_json::from_str::<ScoredSolution<_, 40>>(&json).unwrap(),
+      scored
+    );
+
+    let json = r#"{"solution":1,"scores":[1.0,2.0]}"#;
+    assert!(serde_json::from_str::<ScoredSolution<i32, 3>>(json).is_err());
+    assert!(serde_json::from_str::<ScoredSolution<i32, 1>>(json).is_err());
+  }
 }
@@ -0,0 +1,701 @@
+//! Online archive of nondominated solutions.
+
+use std::cmp::Ordering;
+
+use super::{pareto_front_indices, ParetoDominance, Score, Scores};
+use crate::optimizer::{apply_permutation, nsga::crowding_distances, to_index};
+
+/// An outcome of inserting a solution into a [`ParetoArchive`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum InsertOutcome<S> {
+  /// The solution entered the archive and no entries left it.
+  Accepted,
+  /// The solution didn't enter the archive: it's dominated by an entry, or
+  /// the archive is full and the solution is the most crowded one.
+  Rejected,
+  /// The solution entered the archive and evicted these entries, which it
+  /// dominates or which are the most crowded ones, or which were dropped
+  /// when an [`EpsilonSchedule::TargetSize`] changed epsilon of the archive.
+  AcceptedEvicting(Vec<S>),
+  /// The solution entered the archive, but an
+  /// [`EpsilonSchedule::TargetSize`] has changed epsilon of the archive, and
+  /// the solution was dropped along with these entries.
+  RejectedEvicting(Vec<S>),
+}
+
+impl<S> InsertOutcome<S> {
+  /// Returns `true` if the solution entered the archive.
+  pub fn is_accepted(&self) -> bool {
+    matches!(self, Self::Accepted | Self::AcceptedEvicting(_))
+  }
+}
+
+/// A side of boxes of the grid of an epsilon archive, see
+/// [`ParetoArchive::with_epsilon_schedule`]. A good epsilon is hard to choose
+/// before the range of scores is known, so it may change as the archive
+/// fills.
+///
+/// # Examples
+/// ```
+/// use moga::score::{EpsilonSchedule, ParetoArchive};
+///
+/// let mut archive =
+///   ParetoArchive::new().with_epsilon_schedule(EpsilonSchedule::Geometric {
+///     initial: 1.0,
+///     factor: 0.5,
+///     min: 0.2,
+///   });
+/// archive.insert("a", [0.2, 0.9]);
+/// // "a" and "b" are in the same box with a side of 1.0
+/// assert!(!archive.insert("b", [0.9, 0.2]).is_accepted());
+/// archive.next_generation();
+/// assert_eq!(archive.epsilon(), Some(0.5));
+/// assert!(archive.insert("b", [0.9, 0.2]).is_accepted());
+/// archive.next_generation();
+/// archive.next_generation();
+/// assert_eq!(archive.epsilon(), Some(0.2));
+/// ```
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[non_exhaustive]
+pub enum EpsilonSchedule {
+  /// Epsilon doesn't change.
+  Constant(Score),
+  /// Epsilon is multiplied by `factor` each generation, but doesn't fall
+  /// below `min`. Generations are advanced with
+  /// [`ParetoArchive::next_generation`], so the archive starts coarse and
+  /// gets finer as the population converges.
+  Geometric {
+    /// Epsilon of the first generation.
+    initial: Score,
+    /// Factor in `(0, 1]` that epsilon is multiplied by each generation.
+    factor: Score,
+    /// The least epsilon.
+    min: Score,
+  },
+  /// Epsilon grows when the archive exceeds the `target` number of entries
+  /// and shrinks when the archive stays below it, so that the archive keeps
+  /// about `target` entries whatever the range of scores is. Unlike a bounded
+  /// archive, it keeps entries spread over the grid rather than evicting the
+  /// most crowded ones.
+  TargetSize {
+    /// Epsilon before the first insertion.
+    initial: Score,
+    /// The number of entries the archive should keep.
+    target: usize,
+  },
+}
+
+impl EpsilonSchedule {
+  /// Returns the initial epsilon.
+  fn initial(&self) -> Score {
+    match *self {
+      Self::Constant(epsilon) => epsilon,
+      Self::Geometric { initial, .. } | Self::TargetSize { initial, .. } => {
+        initial
+      }
+    }
+  }
+
+  /// Panics if epsilons of the schedule aren't positive and finite or if its
+  /// factor or target are out of range.
+  fn validate(&self) {
+    let initial = self.initial();
+    assert!(
+      initial > 0.0 && initial.is_finite(),
+      "epsilon must be positive"
+    );
+    match *self {
+      Self::Constant(_) => {}
+      Self::Geometric { factor, min, .. } => {
+        assert!(factor > 0.0 && factor <= 1.0, "factor must be in (0, 1]");
+        assert!(min > 0.0 && min.is_finite(), "epsilon must be positive");
+      }
+      Self::TargetSize { target, .. } => {
+        assert!(target > 0, "target must be positive")
+      }
+    }
+  }
+}
+
+/// An archive of mutually nondominated solutions, updated one solution at a
+/// time. It can collect the best solutions of several optimizations, or of
+/// solutions that weren't found by optimizers at all.
+///
+/// An inserted solution enters the archive unless an entry dominates it, and
+/// evicts entries that it dominates. Solutions with equal scores don't
+/// dominate each other, so all of them are kept. Entries keep the order they
+/// were inserted in.
+///
+/// The archive is unbounded unless created with [`ParetoArchive::bounded`].
+/// A full archive evicts the entry with the least crowding distance, as
+/// [`Nsga2`] does. With [`ParetoArchive::with_epsilon`], the archive keeps at
+/// most one solution in each box of a grid in objective space, which bounds
+/// its size and spreads its entries evenly. An [`EpsilonSchedule`] changes
+/// the side of boxes over generations or with the size of the archive.
+///
+/// # Examples
+/// ```
+/// use moga::score::{InsertOutcome, ParetoArchive};
+///
+/// let mut archive = ParetoArchive::new();
+/// assert_eq!(archive.insert("a", [2.0, 2.0]), InsertOutcome::Accepted);
+/// assert_eq!(archive.insert("b", [3.0, 3.0]), InsertOutcome::Rejected);
+/// assert_eq!(archive.insert("c", [0.0, 3.0]), InsertOutcome::Accepted);
+/// assert_eq!(
+///   archive.insert("d", [1.0, 1.0]),
+///   InsertOutcome::AcceptedEvicting(vec!["a"])
+/// );
+/// assert_eq!(archive.solutions(), ["c", "d"]);
+/// ```
+///
+/// [`Nsga2`]: crate::optimizer::nsga::Nsga2
+#[derive(Clone, Debug)]
+pub struct ParetoArchive<S, const N: usize> {
+  solutions: Vec<S>,
+  scores: Vec<Scores<N>>,
+  capacity: Option<usize>,
+  epsilon: Option<Score>,
+  schedule: Option<EpsilonSchedule>,
+  /// Number of insertions since the last change of epsilon.
+  insertions: usize,
+}
+
+impl<S, const N: usize> ParetoArchive<S, N> {
+  /// Creates an empty unbounded archive.
+  pub fn new() -> Self {
+    Self {
+      solutions: Vec::new(),
+      scores: Vec::new(),
+      capacity: None,
+      epsilon: None,
+      schedule: None,
+      insertions: 0,
+    }
+  }
+
+  /// Creates an empty archive of at most `capacity` entries. When a
+  /// nondominated solution is inserted into a full archive, the entry with
+  /// the least crowding distance is evicted, or the solution is rejected if
+  /// it's the most crowded one. Ties are broken in favor of entries that are
+  /// already in the archive.
+  pub fn bounded(capacity: usize) -> Self {
+    Self {
+      capacity: Some(capacity),
+      ..Self::new()
+    }
+  }
+
+  /// Makes the archive compare solutions by boxes of a grid in objective
+  /// space with a side of `epsilon`, rather than by their scores. A solution
+  /// is rejected if the box of an entry dominates its box, and evicts entries
+  /// whose boxes its box dominates. Of two solutions in the same box, the
+  /// archive keeps the one that dominates the other, or the one closer to
+  /// the corner of the box.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `epsilon` isn't positive or if the archive isn't empty.
+  pub fn with_epsilon(self, epsilon: Score) -> Self {
+    self.with_epsilon_schedule(EpsilonSchedule::Constant(epsilon))
+  }
+
+  /// Makes the archive compare solutions by boxes of a grid, like
+  /// [`ParetoArchive::with_epsilon`] does, with the side of boxes changed by
+  /// the `schedule`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the `schedule` is invalid or if the archive isn't empty.
+  pub fn with_epsilon_schedule(mut self, schedule: EpsilonSchedule) -> Self {
+    schedule.validate();
+    assert!(self.is_empty(), "archive must be empty");
+    self.epsilon = Some(schedule.initial());
+    self.schedule = Some(schedule);
+    self
+  }
+
+  /// Returns the current side of boxes of the grid, if the archive has one.
+  pub fn epsilon(&self) -> Option<Score> {
+    self.epsilon
+  }
+
+  /// Advances the [`EpsilonSchedule::Geometric`] schedule of the archive by
+  /// a generation, shrinking epsilon. Returns entries that were dropped
+  /// because the finer grid has separated their boxes from a dominating
+  /// box. Does nothing for other schedules.
+  pub fn next_generation(&mut self) -> Vec<S> {
+    let Some(EpsilonSchedule::Geometric { factor, min, .. }) = self.schedule
+    else {
+      return Vec::new();
+    };
+    let epsilon = self.epsilon.expect("schedule sets epsilon");
+    let shrunk = (epsilon * factor).max(min);
+    if shrunk == epsilon {
+      return Vec::new();
+    }
+    self.epsilon = Some(shrunk);
+    let kept = self.regrid((0..self.len()).collect());
+    self.remove_ascending(&complement(&kept, self.len()))
+  }
+
+  /// Returns the maximum number of entries, if the archive is bounded.
+  pub fn capacity(&self) -> Option<usize> {
+    self.capacity
+  }
+
+  /// Returns the number of entries.
+  pub fn len(&self) -> usize {
+    self.solutions.len()
+  }
+
+  /// Returns `true` if the archive has no entries.
+  pub fn is_empty(&self) -> bool {
+    self.solutions.is_empty()
+  }
+
+  /// Returns solutions of the archive.
+  pub fn solutions(&self) -> &[S] {
+    &self.solutions
+  }
+
+  /// Returns scores of solutions of the archive.
+  pub fn scores(&self) -> &[Scores<N>] {
+    &self.scores
+  }
+
+  /// Returns an iterator over solutions of the archive and their scores.
+  pub fn iter(&self) -> impl Iterator<Item = (&S, &Scores<N>)> {
+    self.solutions.iter().zip(&self.scores)
+  }
+
+  /// Returns solutions of the archive and their scores.
+  pub fn into_parts(self) -> (Vec<S>, Vec<Scores<N>>) {
+    (self.solutions, self.scores)
+  }
+
+  /// Inserts a `solution` with its `scores` into the archive and returns
+  /// whether it entered the archive and which entries it evicted.
+  ///
+  /// # Panics
+  ///
+  /// Panics if a score is NaN.
+  pub fn insert(&mut self, solution: S, scores: Scores<N>) -> InsertOutcome<S> {
+    let mut evicted = Vec::new();
+    let mut accepted = self.insert_entry(solution, scores, &mut evicted);
+    let dropped = self.adapt_epsilon();
+    if !dropped.is_empty() {
+      let inserted = self.len() - 1;
+      let mut dropped_solutions = self.remove_ascending(&dropped);
+      // the inserted solution is the last entry, so it's dropped last
+      if accepted && dropped.last() == Some(&inserted) {
+        dropped_solutions.pop();
+        accepted = false;
+      }
+      evicted.extend(dropped_solutions);
+    }
+    match (accepted, evicted.is_empty()) {
+      (true, true) => InsertOutcome::Accepted,
+      (true, false) => InsertOutcome::AcceptedEvicting(evicted),
+      (false, true) => InsertOutcome::Rejected,
+      (false, false) => InsertOutcome::RejectedEvicting(evicted),
+    }
+  }
+
+  /// Inserts a `solution` as the last entry, unless an entry is preferred to
+  /// it, and moves entries it evicts to `evicted`. Returns `true` if the
+  /// solution entered the archive.
+  fn insert_entry(
+    &mut self,
+    solution: S,
+    scores: Scores<N>,
+    evicted: &mut Vec<S>,
+  ) -> bool {
+    let mut dominated = Vec::new();
+    for (idx, entry) in self.scores.iter().enumerate() {
+      // if an entry dominates the solution, no entry is dominated by it
+      match compare(self.epsilon, entry, &scores) {
+        Ordering::Less => return false,
+        Ordering::Greater => dominated.push(idx),
+        Ordering::Equal => {}
+      }
+    }
+    evicted.extend(self.remove_ascending(&dominated));
+    self.solutions.push(solution);
+    self.scores.push(scores);
+    match self.capacity {
+      Some(capacity) if self.len() > capacity => {
+        let most_crowded = self.most_crowded();
+        self.scores.remove(most_crowded);
+        let solution = self.solutions.remove(most_crowded);
+        if most_crowded == self.len() {
+          return false;
+        }
+        evicted.push(solution);
+        true
+      }
+      _ => true,
+    }
+  }
+
+  /// Changes epsilon of an [`EpsilonSchedule::TargetSize`] schedule after an
+  /// insertion, if the archive has grown too large or has stayed too small
+  /// for a while. Returns ascending indices of entries that don't survive
+  /// the new grid.
+  ///
+  /// The archive grows epsilon in small steps as soon as it exceeds the
+  /// target by more than a tenth, merging boxes until the archive fits
+  /// again. It shrinks epsilon in proportion to the shortfall when it has
+  /// stayed below the target by more than a tenth for as many insertions as
+  /// the target, since a finer grid makes room for solutions only as they
+  /// come. Changes are rare, so the archive doesn't spend much more time
+  /// regridding than inserting.
+  fn adapt_epsilon(&mut self) -> Vec<usize> {
+    const GROWTH: Score = 1.02;
+    let Some(EpsilonSchedule::TargetSize { target, .. }) = self.schedule else {
+      return Vec::new();
+    };
+    let (lower, upper) = (target - target / 10, target + target / 10);
+    let (len, epsilon) =
+      (self.len(), self.epsilon.expect("schedule sets epsilon"));
+    self.insertions += 1;
+    if len > upper {
+      let mut kept: Vec<_> = (0..len).collect();
+      while kept.len() > upper {
+        self.epsilon = self.epsilon.map(|epsilon| epsilon * GROWTH);
+        kept = self.regrid(kept);
+      }
+      self.insertions = 0;
+      complement(&kept, len)
+    } else if len < lower && self.insertions >= target {
+      // a front of N objectives spans about epsilon^(1 - N) boxes
+      let dimensions = N.saturating_sub(1).max(1) as Score;
+      let shortfall = len.max(1) as Score / target as Score;
+      self.epsilon =
+        Some(epsilon * shortfall.powf(dimensions.recip()).max(0.5));
+      self.insertions = 0;
+      complement(&self.regrid((0..len).collect()), len)
+    } else {
+      Vec::new()
+    }
+  }
+
+  /// Returns ascending indices of entries among the ascending `candidates`
+  /// that are preferred in the current grid, as if they were inserted into
+  /// an empty archive one by one.
+  fn regrid(&self, candidates: Vec<usize>) -> Vec<usize> {
+    let mut kept: Vec<usize> = Vec::with_capacity(candidates.len());
+    for idx in candidates {
+      let scores = &self.scores[idx];
+      let mut is_preferred = true;
+      kept.retain(|&entry| {
+        match compare(self.epsilon, &self.scores[entry], scores) {
+          Ordering::Less => {
+            is_preferred = false;
+            true
+          }
+          Ordering::Greater => false,
+          Ordering::Equal => true,
+        }
+      });
+      if is_preferred {
+        kept.push(idx);
+      }
+    }
+    kept
+  }
+
+  /// Removes entries at ascending `indices`, keeping the order of the rest,
+  /// and returns their solutions.
+  fn remove_ascending(&mut self, indices: &[usize]) -> Vec<S> {
+    if indices.is_empty() {
+      return Vec::new();
+    }
+    // kept entries come first in the permutation, removed entries follow
+    let mut is_removed = vec![false; self.len()];
+    indices.iter().for_each(|&idx| is_removed[idx] = true);
+    let mut permutation: Vec<_> = (0..self.len())
+      .filter(|&idx| !is_removed[idx])
+      .chain(indices.iter().copied())
+      .map(to_index)
+      .collect();
+    let kept = self.len() - indices.len();
+    apply_permutation(&mut permutation, &mut self.solutions, &mut self.scores);
+    self.scores.truncate(kept);
+    self.solutions.split_off(kept)
+  }
+
+  /// Returns the index of the entry with the least crowding distance. The
+  /// last entry is the inserted solution, which is chosen on ties.
+  fn most_crowded(&self) -> usize {
+    let mut front: Vec<_> = (0..self.len()).map(to_index).collect();
+    let mut distances = Vec::with_capacity(self.len());
+    crowding_distances(&mut front, &self.scores, None, &mut distances);
+    (0..self.len())
+      .rev()
+      .min_by(|&a, &b| distances[a].total_cmp(&distances[b]))
+      .expect("archive is not empty")
+  }
+}
+
+impl<S, const N: usize> Default for ParetoArchive<S, N> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl<S, const N: usize> From<(Vec<S>, Vec<Scores<N>>)> for ParetoArchive<S, N> {
+  /// Creates an unbounded archive of nondominated `solutions`, found with
+  /// [`pareto_front_indices`] at once instead of inserting solutions one by
+  /// one.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the number of scores differs from the number of solutions or
+  /// if a score is NaN.
+  fn from((solutions, scores): (Vec<S>, Vec<Scores<N>>)) -> Self {
+    assert_eq!(
+      solutions.len(),
+      scores.len(),
+      "number of scores differs from the number of solutions"
+    );
+    let mut archive = Self::new();
+    // indices are ascending, so nondominated solutions keep their order
+    let mut nondominated = pareto_front_indices(&scores).into_iter().peekable();
+    for (idx, (sol, sc)) in solutions.into_iter().zip(scores).enumerate() {
+      if nondominated.next_if_eq(&idx).is_some() {
+        archive.solutions.push(sol);
+        archive.scores.push(sc);
+      }
+    }
+    archive
+  }
+}
+
+impl<S, const N: usize> Extend<(S, Scores<N>)> for ParetoArchive<S, N> {
+  fn extend<I: IntoIterator<Item = (S, Scores<N>)>>(&mut self, iter: I) {
+    for (solution, scores) in iter {
+      self.insert(solution, scores);
+    }
+  }
+}
+
+impl<S, const N: usize> IntoIterator for ParetoArchive<S, N> {
+  type Item = (S, Scores<N>);
+  type IntoIter =
+    std::iter::Zip<std::vec::IntoIter<S>, std::vec::IntoIter<Scores<N>>>;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.solutions.into_iter().zip(self.scores)
+  }
+}
+
+/// Compares an `entry` with scores of an inserted `solution` in the grid
+/// with a side of `epsilon`, if any. Returns `Less` if the entry is preferred
+/// to the solution and `Greater` if the solution is preferred to the entry.
+fn compare<const N: usize>(
+  epsilon: Option<Score>,
+  entry: &Scores<N>,
+  solution: &Scores<N>,
+) -> Ordering {
+  let Some(epsilon) = epsilon else {
+    return entry.dominance(solution);
+  };
+  let (entry_box, solution_box) =
+    (grid_box(entry, epsilon), grid_box(solution, epsilon));
+  if entry_box != solution_box {
+    return entry_box.dominance(&solution_box);
+  }
+  match entry.dominance(solution) {
+    Ordering::Equal => {
+      let corner_distance = |sc: &Scores<N>| -> f64 {
+        sc.iter()
+          .zip(&entry_box)
+          .map(|(s, b)| f64::from(s.abs() - b * epsilon).powi(2))
+          .sum()
+      };
+      // the solution replaces the entry only if it's strictly closer
+      if corner_distance(solution) < corner_distance(entry) {
+        Ordering::Greater
+      } else {
+        Ordering::Less
+      }
+    }
+    ord => ord,
+  }
+}
+
+/// Returns indices below `len` that aren't among ascending `kept` ones.
+fn complement(kept: &[usize], len: usize) -> Vec<usize> {
+  let mut kept = kept.iter().peekable();
+  (0..len)
+    .filter(|&idx| kept.next_if_eq(&&idx).is_none())
+    .collect()
+}
+
+/// Returns coordinates of the box of a grid with a side of `epsilon` that
+/// `scores` fall into. As scores converge at `0`, their absolute values are
+/// used.
+fn grid_box<const N: usize>(scores: &Scores<N>, epsilon: Score) -> Scores<N> {
+  scores.map(|s| (s.abs() / epsilon).floor())
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::prelude::*;
+
+  use super::*;
+
+  #[test]
+  fn test_unbounded_archive() {
+    let mut archive = ParetoArchive::new();
+    assert_eq!(archive.insert(0, [2.0, 2.0]), InsertOutcome::Accepted);
+    assert_eq!(archive.insert(1, [2.0, 2.0]), InsertOutcome::Accepted);
+    assert_eq!(archive.insert(2, [-3.0, 2.0]), InsertOutcome::Rejected);
+    assert_eq!(archive.insert(3, [4.0, 0.0]), InsertOutcome::Accepted);
+    assert_eq!(archive.insert(4, [0.0, 4.0]), InsertOutcome::Accepted);
+    assert_eq!(
+      archive.insert(5, [1.0, -2.0]),
+      InsertOutcome::AcceptedEvicting(vec![0, 1])
+    );
+    assert_eq!(archive.solutions(), [3, 4, 5]);
+    assert_eq!(archive.scores(), [[4.0, 0.0], [0.0, 4.0], [1.0, -2.0]]);
+    assert_eq!(archive.iter().map(|(&s, _)| s).collect::<Vec<_>>(), [
+      3, 4, 5
+    ]);
+    assert_eq!(archive.capacity(), None);
+  }
+
+  #[test]
+  fn test_bounded_archive() {
+    let mut archive = ParetoArchive::bounded(3);
+    archive.extend([(0, [0.0, 4.0]), (1, [1.0, 3.0]), (2, [4.0, 0.0])]);
+    assert_eq!(archive.len(), 3);
+    // evicts the entry in the crowded part of the front
+    assert_eq!(
+      archive.insert(3, [2.5, 1.5]),
+      InsertOutcome::AcceptedEvicting(vec![1])
+    );
+    // rejects a solution that would be the most crowded one
+    assert_eq!(archive.insert(4, [3.5, 0.5]), InsertOutcome::Rejected);
+    assert_eq!(archive.solutions(), [0, 2, 3]);
+
+    let mut archive = ParetoArchive::bounded(1);
+    assert_eq!(archive.insert(0, [0.0, 1.0]), InsertOutcome::Accepted);
+    assert_eq!(archive.insert(1, [1.0, 0.0]), InsertOutcome::Rejected);
+    assert_eq!(
+      archive.insert(2, [0.0, 0.5]),
+      InsertOutcome::AcceptedEvicting(vec![0])
+    );
+
+    let mut archive = ParetoArchive::bounded(0);
+    assert_eq!(archive.insert(0, [0.0]), InsertOutcome::Rejected);
+    assert!(archive.is_empty());
+  }
+
+  #[test]
+  fn test_epsilon_archive() {
+    let mut archive = ParetoArchive::new().with_epsilon(1.0);
+    assert_eq!(archive.insert(0, [0.5, 2.5]), InsertOutcome::Accepted);
+    // the same box, farther from its corner
+    assert_eq!(archive.insert(1, [0.9, 2.1]), InsertOutcome::Rejected);
+    // the same box, closer to its corner
+    assert_eq!(
+      archive.insert(2, [0.1, 2.3]),
+      InsertOutcome::AcceptedEvicting(vec![0])
+    );
+    // a box dominated by the box of an entry
+    assert_eq!(archive.insert(3, [1.0, 2.0]), InsertOutcome::Rejected);
+    assert_eq!(archive.insert(4, [2.5, 0.5]), InsertOutcome::Accepted);
+    // a box that dominates boxes of both entries
+    assert_eq!(
+      archive.insert(5, [0.5, 0.5]),
+      InsertOutcome::AcceptedEvicting(vec![2, 4])
+    );
+    assert!(archive.insert(6, [0.2, 0.2]).is_accepted());
+    assert_eq!(archive.into_iter().collect::<Vec<_>>(), [(6, [0.2, 0.2])]);
+  }
+
+  #[test]
+  fn test_target_size_epsilon_archive() {
+    // solutions approach a front with scores of a range unknown in advance
+    let stream = |scale: f32| {
+      let mut rng = StdRng::seed_from_u64(0);
+      (0..20_000).map(move |i| {
+        let x = rng.gen::<f32>();
+        let noise = rng.gen::<f32>() / (1.0 + i as f32 / 100.0);
+        (i, [x * scale, (1.0 - x + noise) * scale])
+      })
+    };
+    for (scale, initial) in [(1.0, 1e-4), (1000.0, 1e-4), (1.0, 10.0)] {
+      let mut archive = ParetoArchive::new().with_epsilon_schedule(
+        EpsilonSchedule::TargetSize {
+          initial,
+          target: 50,
+        },
+      );
+      for (i, (solution, scores)) in stream(scale).enumerate() {
+        let outcome = archive.insert(solution, scores);
+        if let InsertOutcome::RejectedEvicting(evicted) = outcome {
+          assert!(!evicted.is_empty());
+        }
+        if i >= 2_000 {
+          assert!(
+            (40..=60).contains(&archive.len()),
+            "archive has {} entries after {i} insertions at scale {scale}",
+            archive.len()
+          );
+        }
+      }
+      let scores = archive.scores();
+      assert_eq!(pareto_front_indices(scores).len(), scores.len());
+    }
+  }
+
+  #[test]
+  fn test_geometric_epsilon_archive() {
+    let mut archive =
+      ParetoArchive::new().with_epsilon_schedule(EpsilonSchedule::Geometric {
+        initial: 1.0,
+        factor: 0.5,
+        min: 0.25,
+      });
+    assert!(archive.insert(0, [0.2, 0.9]).is_accepted());
+    assert_eq!(archive.insert(1, [0.9, 0.2]), InsertOutcome::Rejected);
+    archive.next_generation();
+    assert!(archive.insert(2, [0.9, 0.2]).is_accepted());
+    assert_eq!(archive.solutions(), [0, 2]);
+    assert!(archive.next_generation().is_empty());
+    archive.next_generation();
+    assert_eq!(archive.epsilon(), Some(0.25));
+
+    let mut archive = ParetoArchive::new().with_epsilon(0.5);
+    archive.insert(0, [0.2, 0.9]);
+    assert!(archive.next_generation().is_empty());
+    assert_eq!(archive.epsilon(), Some(0.5));
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_invalid_epsilon_schedule() {
+    ParetoArchive::<(), 2>::new().with_epsilon_schedule(
+      EpsilonSchedule::Geometric {
+        initial: 1.0,
+        factor: 0.0,
+        min: 0.1,
+      },
+    );
+  }
+
+  #[test]
+  fn test_archive_from_population() {
+    let solutions = vec![0, 1, 2, 3, 4];
+    let scores =
+      vec![[2.0, 2.0], [3.0, 3.0], [0.0, 4.0], [2.0, 2.0], [4.0, 4.0]];
+    let archive = ParetoArchive::from((solutions.clone(), scores.clone()));
+    assert_eq!(archive.solutions(), [0, 2, 3]);
+
+    let mut inserted = ParetoArchive::new();
+    inserted.extend(solutions.into_iter().zip(scores));
+    assert_eq!(inserted.into_parts(), archive.into_parts());
+  }
+}
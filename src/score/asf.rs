@@ -0,0 +1,111 @@
+//! Achievement scalarizing function.
+
+use super::Scores;
+
+/// Augmentation coefficient of [`asf`], which makes weakly dominated scores
+/// worse than the scores that dominate them.
+const AUGMENTATION: f32 = 1e-4;
+
+/// Calculates augmented Chebyshev achievement scalarizing function of
+/// `scores` towards the `reference` point. The lower the value, the better
+/// the scores achieve the reference point. As scores converge at `0`,
+/// absolute values of scores and of the reference point are used.
+///
+/// The value is the maximum of weighted differences between scores and the
+/// reference point, augmented by a small fraction of their sum. It's negative
+/// if the scores dominate the reference point, and positive if they don't.
+/// `weights` are normalized to sum up to `1`, so only their ratios matter. If
+/// all weights are zero, objectives are weighted equally.
+///
+/// # Examples
+/// ```
+/// use moga::score::asf;
+///
+/// let reference = [1.0, 1.0];
+/// let weights = [1.0, 1.0];
+/// let close = asf(&[1.0, 1.5], &reference, &weights);
+/// let far = asf(&[0.5, 3.0], &reference, &weights);
+/// assert!(close < far);
+/// ```
+///
+/// # Panics
+///
+/// Panics if any weight is negative.
+pub fn asf<const N: usize>(
+  scores: &Scores<N>,
+  reference: &Scores<N>,
+  weights: &Scores<N>,
+) -> f32 {
+  assert!(
+    weights.iter().all(|&w| w >= 0.0),
+    "weights must not be negative"
+  );
+  let total = weights.iter().sum::<f32>();
+  let weights = if total > 0.0 {
+    weights.map(|w| w / total)
+  } else {
+    [1.0 / N as f32; N]
+  };
+  let (max, sum) = scores
+    .iter()
+    .zip(reference)
+    .zip(weights)
+    .map(|((s, r), w)| w * (s.abs() - r.abs()))
+    .fold((f32::NEG_INFINITY, 0.0), |(max, sum), d| {
+      (max.max(d), sum + d)
+    });
+  max + AUGMENTATION * sum
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_asf() {
+    let reference = [1.0, 2.0];
+    assert_eq!(asf(&[1.0, 2.0], &reference, &[1.0, 1.0]), 0.0);
+    // weights are normalized
+    assert_eq!(
+      asf(&[3.0, 2.0], &reference, &[1.0, 1.0]),
+      asf(&[3.0, 2.0], &reference, &[5.0, 5.0]),
+    );
+    assert_eq!(
+      asf(&[3.0, 2.0], &reference, &[0.0, 0.0]),
+      asf(&[3.0, 2.0], &reference, &[1.0, 1.0]),
+    );
+    // only the worst weighted difference matters most
+    assert!((asf(&[3.0, 2.0], &reference, &[1.0, 1.0]) - 1.0001).abs() < 1e-6);
+    assert!((asf(&[3.0, 2.0], &reference, &[1.0, 3.0]) - 0.50005).abs() < 1e-6);
+    // negative scores are treated as their absolute values
+    assert_eq!(
+      asf(&[-3.0, 2.0], &[1.0, -2.0], &[1.0, 1.0]),
+      asf(&[3.0, 2.0], &reference, &[1.0, 1.0]),
+    );
+  }
+
+  #[test]
+  fn test_asf_augmentation() {
+    let reference = [0.0, 0.0];
+    let weights = [1.0, 1.0];
+    // weakly dominated scores are worse
+    let dominating = asf(&[1.0, 0.5], &reference, &weights);
+    let dominated = asf(&[1.0, 1.0], &reference, &weights);
+    assert!(dominating < dominated);
+  }
+
+  #[test]
+  fn test_asf_reference_points() {
+    let weights = [1.0, 1.0];
+    // dominated reference point
+    assert!(asf(&[1.0, 1.0], &[2.0, 2.0], &weights) < 0.0);
+    // unreachable reference point
+    assert!(asf(&[1.0, 1.0], &[0.0, 0.0], &weights) > 0.0);
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_asf_negative_weights() {
+    asf(&[1.0], &[1.0], &[-1.0]);
+  }
+}
@@ -0,0 +1,142 @@
+//! Nearest neighbor queries over solutions in objective space.
+
+use super::{kdtree::KdTree, ScoredSolution, Scores};
+
+/// Solutions indexed by their scores for nearest neighbor queries in
+/// objective space, such as finding the solution closest to an aspiration
+/// point. Distances are euclidean distances between raw scores.
+///
+/// The index is built once in `O(n log n)`, and then answers queries without
+/// going through all solutions.
+///
+/// # Examples
+/// ```
+/// use moga::score::FrontIndex;
+///
+/// let solutions = vec!["a", "b", "c"];
+/// let scores = vec![[0.0, 4.0], [2.0, 2.0], [4.0, 0.0]];
+/// let index = FrontIndex::from((solutions, scores));
+/// assert_eq!(index.nearest(&[1.5, 2.5], 2), [&"b", &"a"]);
+/// assert_eq!(index.within(&[1.5, 2.5], 1.0), [&"b"]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct FrontIndex<S, const N: usize> {
+  solutions: Vec<S>,
+  scores: Vec<Scores<N>>,
+  tree: KdTree,
+}
+
+impl<S, const N: usize> FrontIndex<S, N> {
+  /// Indexes `solutions` by their `scores`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the number of scores differs from the number of solutions or
+  /// if a score is NaN.
+  pub fn new(solutions: Vec<S>, scores: Vec<Scores<N>>) -> Self {
+    assert_eq!(
+      solutions.len(),
+      scores.len(),
+      "number of scores differs from the number of solutions"
+    );
+    let mut tree = KdTree::default();
+    tree.build(&scores);
+    Self {
+      solutions,
+      scores,
+      tree,
+    }
+  }
+
+  /// Returns the number of indexed solutions.
+  pub fn len(&self) -> usize {
+    self.solutions.len()
+  }
+
+  /// Returns `true` if no solutions are indexed.
+  pub fn is_empty(&self) -> bool {
+    self.solutions.is_empty()
+  }
+
+  /// Returns indexed solutions.
+  pub fn solutions(&self) -> &[S] {
+    &self.solutions
+  }
+
+  /// Returns scores of indexed solutions.
+  pub fn scores(&self) -> &[Scores<N>] {
+    &self.scores
+  }
+
+  /// Returns at most `k` solutions nearest to the `query`, from the nearest
+  /// to the farthest. Equally distant solutions are ordered by their
+  /// position in the index.
+  pub fn nearest(&self, query: &Scores<N>, k: usize) -> Vec<&S> {
+    let mut nearest = Vec::with_capacity(k.min(self.len()) + 1);
+    self.tree.nearest(&self.scores, query, k, &mut nearest);
+    nearest
+      .into_iter()
+      .map(|(idx, _)| &self.solutions[idx])
+      .collect()
+  }
+
+  /// Returns solutions within `radius` from the `query`, from the nearest to
+  /// the farthest. Equally distant solutions are ordered by their position
+  /// in the index.
+  pub fn within(&self, query: &Scores<N>, radius: f32) -> Vec<&S> {
+    let mut within = Vec::new();
+    self
+      .tree
+      .within(&self.scores, query, radius as f64, &mut within);
+    within
+      .into_iter()
+      .map(|(idx, _)| &self.solutions[idx])
+      .collect()
+  }
+}
+
+impl<S, const N: usize> From<(Vec<S>, Vec<Scores<N>>)> for FrontIndex<S, N> {
+  fn from((solutions, scores): (Vec<S>, Vec<Scores<N>>)) -> Self {
+    Self::new(solutions, scores)
+  }
+}
+
+impl<S, const N: usize> FromIterator<ScoredSolution<S, N>>
+  for FrontIndex<S, N>
+{
+  fn from_iter<I: IntoIterator<Item = ScoredSolution<S, N>>>(iter: I) -> Self {
+    let (solutions, scores) =
+      iter.into_iter().map(<(S, Scores<N>)>::from).unzip();
+    Self::new(solutions, scores)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_front_index() {
+    let index: FrontIndex<_, 2> = (0..10)
+      .map(|i| ScoredSolution {
+        solution: i,
+        scores: [i as f32, 9.0 - i as f32],
+      })
+      .collect();
+    assert_eq!(index.len(), 10);
+    assert_eq!(index.nearest(&[3.0, 6.0], 3), [&3, &2, &4]);
+    assert_eq!(index.nearest(&[0.0, 0.0], 2), [&4, &5]);
+    assert_eq!(index.within(&[9.0, 0.0], 1.5), [&9, &8]);
+    assert!(index.within(&[20.0, 20.0], 1.0).is_empty());
+
+    let empty = FrontIndex::<u8, 2>::new(Vec::new(), Vec::new());
+    assert!(empty.is_empty());
+    assert!(empty.nearest(&[0.0, 0.0], 1).is_empty());
+  }
+
+  #[test]
+  #[should_panic(expected = "number of scores differs")]
+  fn test_front_index_count_mismatch() {
+    FrontIndex::new(vec![1, 2], vec![[0.0]]);
+  }
+}
@@ -0,0 +1,206 @@
+//! Hypervolume indicator.
+
+use super::Scores;
+
+/// Calculates hypervolume of the region dominated by `scores` and bounded by
+/// the `reference` point. As scores converge at `0`, their absolute values are
+/// used, and the region lies between the scores and the reference point.
+/// Scores that don't dominate the reference point don't contribute to the
+/// hypervolume.
+///
+/// The bigger the hypervolume, the closer the scores are to the ideal point
+/// and the better they are spread. Two objectives are handled in
+/// `O(n * log(n))`, more objectives are handled by slicing the region along
+/// the last objective, which takes `O(n^(N - 1) * log(n))`.
+///
+/// # Examples
+/// ```
+/// use moga::score::hypervolume;
+///
+/// let scores = [[1.0, 3.0], [2.0, 2.0], [3.0, 1.0]];
+/// assert_eq!(hypervolume(&scores, &[4.0, 4.0]), 6.0);
+/// ```
+pub fn hypervolume<const N: usize>(
+  scores: &[Scores<N>],
+  reference: &Scores<N>,
+) -> f64 {
+  let reference = reference.map(|r| f64::from(r.abs()));
+  let points: Vec<[f64; N]> = scores
+    .iter()
+    .map(|sc| sc.map(|s| f64::from(s.abs())))
+    .filter(|p| p.iter().zip(&reference).all(|(p, r)| p < r))
+    .collect();
+  let mut points: Vec<&[f64]> = points.iter().map(|p| p.as_slice()).collect();
+  sliced_hypervolume(&mut points, &reference)
+}
+
+/// Calculates exclusive hypervolume contribution of each of mutually
+/// nondominated `scores`: the hypervolume that is lost if the solution is
+/// removed. Absolute values of scores are used, just like in [`hypervolume`].
+/// Scores that don't dominate the `reference` point contribute nothing.
+///
+/// Contributions of two objectives are calculated in `O(n * log(n))` by
+/// sorting the scores. For more objectives, the contribution of each solution
+/// is the volume of its box without the hypervolume of other scores limited to
+/// the box, as in WFG algorithm.
+pub(crate) fn hypervolume_contributions<const N: usize>(
+  scores: &[Scores<N>],
+  reference: &Scores<N>,
+) -> Vec<f64> {
+  let reference = reference.map(|r| f64::from(r.abs()));
+  let points: Vec<[f64; N]> = scores
+    .iter()
+    .map(|sc| sc.map(|s| f64::from(s.abs())))
+    .collect();
+  let dominates_reference =
+    |p: &[f64; N]| p.iter().zip(&reference).all(|(p, r)| p < r);
+  let mut contributions = vec![0.0; scores.len()];
+  let mut indices = (0..points.len())
+    .filter(|&i| dominates_reference(&points[i]))
+    .collect::<Vec<_>>();
+
+  if N == 2 {
+    // along a front sorted by the first objective, the second objective
+    // decreases, and each solution exclusively dominates the box between its
+    // neighbors. duplicates are adjacent and contribute nothing
+    indices.sort_unstable_by(|&a, &b| {
+      points[a][0]
+        .total_cmp(&points[b][0])
+        .then(points[a][1].total_cmp(&points[b][1]))
+    });
+    for (k, &i) in indices.iter().enumerate() {
+      let right = indices.get(k + 1).map_or(reference[0], |&n| points[n][0]);
+      let top = k
+        .checked_sub(1)
+        .map_or(reference[1], |p| points[indices[p]][1]);
+      contributions[i] = (right - points[i][0]) * (top - points[i][1]);
+    }
+    return contributions;
+  }
+
+  for &i in &indices {
+    let p = &points[i];
+    let volume = p
+      .iter()
+      .zip(&reference)
+      .map(|(p, r)| r - p)
+      .product::<f64>();
+    let limited: Vec<[f64; N]> = indices
+      .iter()
+      .filter(|&&j| j != i)
+      .map(|&j| std::array::from_fn(|o| points[j][o].max(p[o])))
+      .collect();
+    let mut limited: Vec<&[f64]> =
+      limited.iter().map(|p| p.as_slice()).collect();
+    contributions[i] = volume - sliced_hypervolume(&mut limited, &reference);
+  }
+  contributions
+}
+
+/// Recursively calculates hypervolume of the region dominated by `points` in
+/// the first `reference.len()` dimensions. All points must dominate the
+/// reference point.
+fn sliced_hypervolume(points: &mut [&[f64]], reference: &[f64]) -> f64 {
+  let dims = reference.len();
+  match dims {
+    _ if points.is_empty() => 0.0,
+    0 => 1.0,
+    1 => {
+      let min = points.iter().map(|p| p[0]).fold(f64::INFINITY, f64::min);
+      reference[0] - min
+    }
+    2 => {
+      // sweep along the first objective, keeping the best second objective
+      points.sort_unstable_by(|a, b| a[0].total_cmp(&b[0]));
+      let mut volume = 0.0;
+      let mut best = reference[1];
+      for (i, p) in points.iter().enumerate() {
+        best = best.min(p[1]);
+        let next = points.get(i + 1).map_or(reference[0], |n| n[0]);
+        volume += (next - p[0]) * (reference[1] - best);
+      }
+      volume
+    }
+    _ => {
+      // slice the region along the last objective. each slice is bounded by
+      // two consecutive values of the last objective and is dominated by the
+      // points that precede its upper bound
+      let last = dims - 1;
+      points.sort_unstable_by(|a, b| a[last].total_cmp(&b[last]));
+      let mut volume = 0.0;
+      for i in 0..points.len() {
+        let next = points.get(i + 1).map_or(reference[last], |n| n[last]);
+        let depth = next - points[i][last];
+        if depth > 0.0 {
+          let mut slice = points[..=i].to_vec();
+          volume += depth * sliced_hypervolume(&mut slice, &reference[..last]);
+        }
+      }
+      volume
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_hypervolume_single_point() {
+    assert_eq!(hypervolume(&[[1.0]], &[3.0]), 2.0);
+    assert_eq!(hypervolume(&[[1.0, -2.0]], &[3.0, 3.0]), 2.0);
+    assert_eq!(hypervolume(&[[1.0, 1.0, 1.0]], &[2.0, 3.0, 4.0]), 6.0);
+  }
+
+  #[test]
+  fn test_hypervolume_ignores_points_outside_reference() {
+    assert_eq!(hypervolume::<2>(&[], &[1.0, 1.0]), 0.0);
+    assert_eq!(hypervolume(&[[5.0, 0.0], [0.0, 1.0]], &[1.0, 1.0]), 0.0);
+  }
+
+  #[test]
+  fn test_hypervolume_ignores_dominated_points() {
+    let front = [[1.0, 3.0, 2.0], [2.0, 2.0, 2.0], [3.0, 1.0, 1.0]];
+    let mut scores = front.to_vec();
+    scores.extend([[3.0, 3.0, 3.0], [2.0, 2.0, 2.0], [3.5, 1.0, 2.0]]);
+    assert_eq!(
+      hypervolume(&scores, &[4.0; 3]),
+      hypervolume(&front, &[4.0; 3])
+    );
+  }
+
+  #[test]
+  fn test_hypervolume_contributions() {
+    let reference = [4.0, 4.0];
+    let front = [[2.0, 2.0], [1.0, 3.0], [3.0, 1.0], [2.0, 2.0]];
+    assert_eq!(hypervolume_contributions(&front, &reference), [
+      0.0, 1.0, 1.0, 0.0
+    ]);
+    let front = [[2.0, 2.0], [1.0, 3.0], [3.0, 1.0], [5.0, 0.0]];
+    assert_eq!(hypervolume_contributions(&front, &reference), [
+      1.0, 1.0, 1.0, 0.0
+    ]);
+    // contributions are the volume lost when a solution is removed
+    let front = [[0.0, 1.0, 2.0], [1.0, 0.0, 2.0], [2.0, 2.0, 0.0]];
+    let reference = [3.0; 3];
+    let total = hypervolume(&front, &reference);
+    for (i, contribution) in hypervolume_contributions(&front, &reference)
+      .into_iter()
+      .enumerate()
+    {
+      let mut rest = front.to_vec();
+      rest.remove(i);
+      assert_eq!(contribution, total - hypervolume(&rest, &reference));
+    }
+  }
+
+  #[test]
+  fn test_hypervolume_3d() {
+    // two boxes of volume 8 and 6 intersecting in a box of volume 4
+    let scores = [[0.0, 0.0, 2.0], [0.0, 1.0, 1.0]];
+    assert_eq!(hypervolume(&scores, &[2.0, 2.0, 4.0]), 10.0);
+    // a 2D front extruded along the third objective
+    let scores = [[1.0, 3.0, 0.0], [2.0, 2.0, 0.0], [3.0, 1.0, 0.0]];
+    assert_eq!(hypervolume(&scores, &[4.0, 4.0, 2.0]), 12.0);
+  }
+}
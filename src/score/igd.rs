@@ -0,0 +1,61 @@
+//! Inverted generational distance indicator.
+
+use super::{KdTree, Scores};
+
+/// Calculates inverted generational distance (IGD) of `scores` to the
+/// `reference` front: the mean euclidean distance from each point of the
+/// reference front to the closest of `scores`. As scores converge at `0`,
+/// their absolute values are used.
+///
+/// The smaller the distance, the closer the scores are to the reference front
+/// and the better they cover it. Returns infinity if there are no `scores`,
+/// and `0` if the reference front is empty.
+///
+/// # Examples
+/// ```
+/// use moga::score::igd;
+///
+/// let reference = [[0.0, 2.0], [1.0, 1.0], [2.0, 0.0]];
+/// assert_eq!(igd(&reference, &reference), 0.0);
+/// assert_eq!(igd(&[[1.0, 2.0]], &[[1.0, 1.0], [1.0, -1.0]]), 1.0);
+/// ```
+pub fn igd<const N: usize>(
+  scores: &[Scores<N>],
+  reference: &[Scores<N>],
+) -> f64 {
+  if reference.is_empty() {
+    return 0.0;
+  }
+  if scores.is_empty() {
+    return f64::INFINITY;
+  }
+  let points: Vec<_> = scores.iter().map(|sc| sc.map(f32::abs)).collect();
+  let mut tree = KdTree::default();
+  tree.build(&points);
+  let mut nearest = Vec::with_capacity(2);
+  let total: f64 = reference
+    .iter()
+    .map(|r| {
+      tree.nearest(&points, &r.map(f32::abs), 1, &mut nearest);
+      nearest[0].1.sqrt()
+    })
+    .sum();
+  total / reference.len() as f64
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_igd() {
+    let reference = [[0.0, 4.0], [2.0, 2.0], [4.0, 0.0]];
+    // one solution in the middle of the front
+    let expected = (2.0 * 8.0f64.sqrt()) / 3.0;
+    assert!((igd(&[[2.0, 2.0]], &reference) - expected).abs() < 1e-9);
+    // adding solutions never increases the distance
+    assert_eq!(igd(&[[2.0, 2.0], [0.0, -4.0], [4.0, 0.0]], &reference), 0.0);
+    assert_eq!(igd(&[], &reference), f64::INFINITY);
+    assert_eq!(igd(&[[1.0, 1.0]], &[]), 0.0);
+  }
+}
@@ -0,0 +1,209 @@
+//! A k-d tree over fitness scores for nearest neighbor queries.
+
+use std::cmp::Ordering;
+
+use super::{squared_distance, Scores};
+
+/// A balanced k-d tree over a slice of points in objective space. Distances
+/// are squared euclidean distances between raw scores.
+///
+/// The tree is implicit: `order` holds indices of points, and the middle of
+/// each range of `order` is the root of the subtree of that range, which
+/// splits it by the axis of its depth. The tree doesn't own the points, so
+/// queries must be given the same points that the tree was built over. The
+/// buffer is reused between builds.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct KdTree {
+  order: Vec<usize>,
+}
+
+impl KdTree {
+  /// Builds a tree over `points`. Queries return indices of `points`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if a point has a NaN score.
+  pub(crate) fn build<const N: usize>(&mut self, points: &[Scores<N>]) {
+    self.order.clear();
+    self.order.extend(0..points.len());
+    build(points, &mut self.order, 0);
+  }
+
+  /// Stores indices of at most `k` `points` nearest to the `query` in
+  /// `nearest`, paired with squared distances to them, from the nearest to
+  /// the farthest. Equally distant points are ordered by their indices.
+  pub(crate) fn nearest<const N: usize>(
+    &self,
+    points: &[Scores<N>],
+    query: &Scores<N>,
+    k: usize,
+    nearest: &mut Vec<(usize, f64)>,
+  ) {
+    debug_assert_eq!(points.len(), self.order.len());
+    nearest.clear();
+    if k > 0 {
+      search_nearest(points, query, k, &self.order, 0, nearest);
+    }
+  }
+
+  /// Stores indices of `points` within `radius` from the `query` in
+  /// `within`, paired with squared distances to them, from the nearest to
+  /// the farthest. Equally distant points are ordered by their indices.
+  pub(crate) fn within<const N: usize>(
+    &self,
+    points: &[Scores<N>],
+    query: &Scores<N>,
+    radius: f64,
+    within: &mut Vec<(usize, f64)>,
+  ) {
+    debug_assert_eq!(points.len(), self.order.len());
+    within.clear();
+    search_within(points, query, radius * radius, &self.order, 0, within);
+    within.sort_unstable_by(cmp_neighbors);
+  }
+}
+
+fn search_nearest<const N: usize>(
+  points: &[Scores<N>],
+  query: &Scores<N>,
+  k: usize,
+  range: &[usize],
+  depth: usize,
+  nearest: &mut Vec<(usize, f64)>,
+) {
+  let Some((mid, left, right)) = split(range) else {
+    return;
+  };
+  let neighbor = (mid, squared_distance(query, &points[mid]));
+  // `nearest` is kept sorted, so the farthest neighbor is the last one
+  let pos = nearest
+    .binary_search_by(|other| cmp_neighbors(other, &neighbor))
+    .unwrap_or_else(|pos| pos);
+  if pos < k {
+    if nearest.len() == k {
+      nearest.pop();
+    }
+    nearest.insert(pos, neighbor);
+  }
+
+  let axis = depth % N;
+  let diff = (query[axis] - points[mid][axis]) as f64;
+  let (near, far) = if diff < 0.0 {
+    (left, right)
+  } else {
+    (right, left)
+  };
+  search_nearest(points, query, k, near, depth + 1, nearest);
+  let farthest = nearest.last().map_or(f64::INFINITY, |(_, d)| *d);
+  if nearest.len() < k || diff * diff <= farthest {
+    search_nearest(points, query, k, far, depth + 1, nearest);
+  }
+}
+
+fn search_within<const N: usize>(
+  points: &[Scores<N>],
+  query: &Scores<N>,
+  radius_sq: f64,
+  range: &[usize],
+  depth: usize,
+  within: &mut Vec<(usize, f64)>,
+) {
+  let Some((mid, left, right)) = split(range) else {
+    return;
+  };
+  let d = squared_distance(query, &points[mid]);
+  if d <= radius_sq {
+    within.push((mid, d));
+  }
+
+  let axis = depth % N;
+  let diff = (query[axis] - points[mid][axis]) as f64;
+  if diff <= 0.0 || diff * diff <= radius_sq {
+    search_within(points, query, radius_sq, left, depth + 1, within);
+  }
+  if diff >= 0.0 || diff * diff <= radius_sq {
+    search_within(points, query, radius_sq, right, depth + 1, within);
+  }
+}
+
+/// Arranges `order` into an implicit tree, splitting it by the `depth` axis.
+fn build<const N: usize>(
+  points: &[Scores<N>],
+  order: &mut [usize],
+  depth: usize,
+) {
+  if order.len() <= 1 {
+    return;
+  }
+  let axis = depth % N;
+  let mid = order.len() / 2;
+  order.select_nth_unstable_by(mid, |&a, &b| {
+    points[a][axis]
+      .partial_cmp(&points[b][axis])
+      .expect("NaN encountered")
+  });
+  let (left, right) = order.split_at_mut(mid);
+  build(points, left, depth + 1);
+  build(points, &mut right[1..], depth + 1);
+}
+
+/// Splits a `range` of the tree into its root and ranges of its subtrees.
+fn split(range: &[usize]) -> Option<(usize, &[usize], &[usize])> {
+  let (left, rest) = range.split_at(range.len() / 2);
+  let (&mid, right) = rest.split_first()?;
+  Some((mid, left, right))
+}
+
+/// Orders neighbors by distance, then by index.
+fn cmp_neighbors(a: &(usize, f64), b: &(usize, f64)) -> Ordering {
+  a.1.total_cmp(&b.1).then(a.0.cmp(&b.0))
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::prelude::*;
+
+  use super::*;
+
+  /// Returns all neighbors of the `query`, from the nearest to the farthest.
+  fn brute_force<const N: usize>(
+    points: &[Scores<N>],
+    query: &Scores<N>,
+  ) -> Vec<(usize, f64)> {
+    let mut neighbors: Vec<_> = points
+      .iter()
+      .enumerate()
+      .map(|(i, p)| (i, squared_distance(query, p)))
+      .collect();
+    neighbors.sort_unstable_by(cmp_neighbors);
+    neighbors
+  }
+
+  #[test]
+  fn test_queries_match_brute_force() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut tree = KdTree::default();
+    let mut neighbors = Vec::new();
+    for len in [0, 1, 2, 7, 300] {
+      // a small grid produces plenty of ties and duplicates
+      let points: Vec<Scores<3>> = (0..len)
+        .map(|_| [(); 3].map(|_| rng.gen_range(-5..=5) as f32))
+        .collect();
+      // the tree is reused between builds
+      tree.build(&points);
+      for _ in 0..20 {
+        let query = [(); 3].map(|_| rng.gen_range(-6.0..6.0));
+        let expected = brute_force(&points, &query);
+        for k in [0, 1, 5, len + 1] {
+          tree.nearest(&points, &query, k, &mut neighbors);
+          assert_eq!(neighbors, expected[..k.min(len)]);
+        }
+        tree.within(&points, &query, 3.0, &mut neighbors);
+        let within = &neighbors;
+        let expected: Vec<_> =
+          expected.into_iter().filter(|(_, d)| *d <= 9.0).collect();
+        assert_eq!(*within, expected);
+      }
+    }
+  }
+}
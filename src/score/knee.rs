@@ -0,0 +1,188 @@
+//! Knee points detection.
+
+use super::Scores;
+
+/// Returns indices of at most `count` solutions of the `front` that are the
+/// most knee-like, from the most to the least. A knee point is a solution
+/// where improving any objective a bit costs a lot in other objectives, which
+/// makes it a good compromise to deploy. As scores converge at `0`, their
+/// absolute values are used, and each objective is normalized by its range in
+/// the front.
+///
+/// For 2 objectives, solutions are rated by their distance from the line
+/// through both boundary solutions of the front towards the ideal point. For
+/// more objectives, solutions are rated by mean cosine of angles between the
+/// diagonal of the objective space and directions from the solution to the
+/// other solutions, which is the highest for solutions that bulge towards the
+/// ideal point.
+///
+/// Solutions of linear and degenerate fronts are rated equally, in which case
+/// the first solutions of the front are returned. Solutions with NaN scores
+/// are rated the lowest.
+///
+/// # Examples
+/// ```
+/// use moga::score::knee_points;
+///
+/// let front = [[0.0, 4.0], [0.5, 1.0], [1.0, 0.5], [4.0, 0.0]];
+/// assert_eq!(knee_points(&front, 2), [1, 2]);
+/// ```
+pub fn knee_points<const N: usize>(
+  front: &[Scores<N>],
+  count: usize,
+) -> Vec<usize> {
+  let points = normalize(front);
+  let ratings = if N == 2 {
+    boundary_line_distances(&points)
+  } else {
+    mean_diagonal_cosines(&points)
+  };
+  let mut indices = (0..front.len()).collect::<Vec<_>>();
+  // stable sort keeps equally rated solutions in order of the front
+  indices.sort_by(|&a, &b| {
+    let rating = |i: usize| match ratings[i] {
+      r if r.is_nan() => f64::NEG_INFINITY,
+      // rounding errors must not break ties of equally rated solutions
+      r if r.abs() < 1e-9 => 0.0,
+      r => r,
+    };
+    rating(b).total_cmp(&rating(a))
+  });
+  indices.truncate(count);
+  indices
+}
+
+/// Normalizes absolute values of scores of each objective to `0.0..=1.0`.
+/// Objectives with equal scores are normalized to `0.0`.
+fn normalize<const N: usize>(front: &[Scores<N>]) -> Vec<[f64; N]> {
+  let mut min = [f64::INFINITY; N];
+  let mut max = [f64::NEG_INFINITY; N];
+  for sc in front {
+    for (o, s) in sc.iter().enumerate() {
+      let s = f64::from(s.abs());
+      min[o] = min[o].min(s);
+      max[o] = max[o].max(s);
+    }
+  }
+  front
+    .iter()
+    .map(|sc| {
+      std::array::from_fn(|o| {
+        let range = max[o] - min[o];
+        let range = if range > 0.0 { range } else { 1.0 };
+        (f64::from(sc[o].abs()) - min[o]) / range
+      })
+    })
+    .collect()
+}
+
+/// Rates normalized 2-objective points by their distance from the line
+/// through boundary points towards the ideal point. If boundary points
+/// coincide, points are rated by their negative distance to them.
+fn boundary_line_distances<const N: usize>(points: &[[f64; N]]) -> Vec<f64> {
+  let boundary = |o: usize| {
+    points
+      .iter()
+      .filter(|p| !p[0].is_nan() && !p[1].is_nan())
+      .min_by(|p, q| p[o].total_cmp(&q[o]).then(p[1 - o].total_cmp(&q[1 - o])))
+  };
+  let (Some(a), Some(b)) = (boundary(0), boundary(1)) else {
+    return vec![f64::NAN; points.len()];
+  };
+  let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+  let length = dx.hypot(dy);
+  points
+    .iter()
+    .map(|p| {
+      let (px, py) = (p[0] - a[0], p[1] - a[1]);
+      if length > 0.0 {
+        // `a` is the best in the first objective and `b` - in the second, so
+        // the ideal point lies to the right of the line from `a` to `b`
+        (px * dy - py * dx) / length
+      } else {
+        -px.hypot(py)
+      }
+    })
+    .collect()
+}
+
+/// Rates normalized points by mean cosine of angles between the diagonal and
+/// directions from each point to other points. Coinciding points are skipped.
+fn mean_diagonal_cosines<const N: usize>(points: &[[f64; N]]) -> Vec<f64> {
+  let diagonal = 1.0 / (N as f64).sqrt();
+  points
+    .iter()
+    .map(|p| {
+      if p.iter().any(|v| v.is_nan()) {
+        return f64::NAN;
+      }
+      let (sum, count) = points
+        .iter()
+        .filter_map(|q| {
+          let length =
+            p.iter().zip(q).map(|(p, q)| (q - p).powi(2)).sum::<f64>();
+          let length = length.sqrt();
+          let projection = p.iter().zip(q).map(|(p, q)| q - p).sum::<f64>();
+          (length > 0.0).then(|| projection * diagonal / length)
+        })
+        .fold((0.0, 0), |(sum, count), cos| (sum + cos, count + 1));
+      if count > 0 {
+        sum / count as f64
+      } else {
+        0.0
+      }
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_knee_points_2d() {
+    let front = [[0.0, 1.0], [0.3, 0.1], [0.1, 0.5], [0.6, 0.05], [1.0, 0.0]];
+    assert_eq!(knee_points(&front, 1), [1]);
+    assert_eq!(knee_points(&front, 10).len(), 5);
+    assert_eq!(knee_points(&front, 0), Vec::<usize>::new());
+  }
+
+  #[test]
+  fn test_knee_points_3d() {
+    let front = [
+      [1.0, 0.0, 0.0],
+      [0.0, 1.0, 0.0],
+      [0.0, 0.0, 1.0],
+      [0.2, 0.2, 0.2],
+      [0.5, 0.5, 0.0],
+    ];
+    assert_eq!(knee_points(&front, 1), [3]);
+  }
+
+  #[test]
+  fn test_knee_points_negative_scores() {
+    let front = [[0.0, -1.0], [-0.3, 0.1], [-1.0, 0.0]];
+    assert_eq!(knee_points(&front, 1), [1]);
+  }
+
+  #[test]
+  fn test_knee_points_degenerate_fronts() {
+    assert_eq!(knee_points::<2>(&[], 3), Vec::<usize>::new());
+    assert_eq!(knee_points(&[[1.0, 2.0]], 3), [0]);
+    assert_eq!(knee_points(&[[1.0, 2.0, 3.0]], 3), [0]);
+    // collinear points are rated equally
+    let line = [[0.0, 1.0], [0.25, 0.75], [0.5, 0.5], [1.0, 0.0]];
+    assert_eq!(knee_points(&line, 4), [0, 1, 2, 3]);
+    // all points coincide
+    assert_eq!(knee_points(&[[1.0, 1.0]; 3], 2), [0, 1]);
+    assert_eq!(knee_points(&[[1.0; 3]; 3], 2), [0, 1]);
+  }
+
+  #[test]
+  fn test_knee_points_nan() {
+    let front = [[f32::NAN, 0.5], [0.0, 1.0], [0.2, 0.2], [1.0, 0.0]];
+    assert_eq!(knee_points(&front, 4), [2, 1, 3, 0]);
+    let front = [[f32::NAN, 0.5, 0.0], [0.2, 0.2, 0.2], [1.0, 0.0, 0.0]];
+    assert_eq!(knee_points(&front, 3)[2], 0);
+  }
+}
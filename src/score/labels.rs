@@ -0,0 +1,79 @@
+//! Labels of objectives.
+
+use std::fmt::{self, Display};
+
+/// Labels of objectives, such as `["cost", "weight"]`, that make exported
+/// scores, statistics and reports self-describing. Labels are metadata only:
+/// they don't affect optimization.
+///
+/// With `serde` feature enabled, labels are serialized as a sequence of
+/// strings. They can't be deserialized, since they are static strings.
+///
+/// # Examples
+/// ```
+/// use moga::score::ObjectiveLabels;
+///
+/// let labels = ObjectiveLabels(["cost", "weight"]);
+/// assert_eq!(labels.label(1), "weight");
+/// assert_eq!(labels.to_string(), "cost, weight");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ObjectiveLabels<const N: usize>(pub [&'static str; N]);
+
+impl<const N: usize> ObjectiveLabels<N> {
+  /// Returns the label of the objective at `o_idx`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `o_idx` isn't less than `N`.
+  pub fn label(&self, o_idx: usize) -> &'static str {
+    self.0[o_idx]
+  }
+
+  /// Returns an iterator over labels in order of objectives.
+  pub fn iter(&self) -> impl Iterator<Item = &'static str> + '_ {
+    self.0.iter().copied()
+  }
+}
+
+impl<const N: usize> Display for ObjectiveLabels<N> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.0.join(", "))
+  }
+}
+
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for ObjectiveLabels<N> {
+  fn serialize<S: serde::Serializer>(
+    &self,
+    serializer: S,
+  ) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(self.iter())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_objective_labels() {
+    let labels = ObjectiveLabels(["cost", "weight", "risk"]);
+    assert_eq!(labels.iter().collect::<Vec<_>>(), [
+      "cost", "weight", "risk"
+    ]);
+    assert_eq!(labels.label(2), "risk");
+    assert_eq!(labels.to_string(), "cost, weight, risk");
+    assert_eq!(ObjectiveLabels([]).to_string(), "");
+  }
+
+  #[cfg(feature = "serde_json")]
+  #[test]
+  fn test_serialize_objective_labels() {
+    let labels = ObjectiveLabels(["cost", "weight"]);
+    assert_eq!(
+      serde_json::to_string(&labels).unwrap(),
+      r#"["cost","weight"]"#
+    );
+  }
+}
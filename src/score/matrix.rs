@@ -0,0 +1,254 @@
+//! Column-major storage of fitness scores.
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use super::{sorting::DominancePairs, Score, Scores};
+#[cfg(feature = "parallel")]
+use crate::execution::PARALLEL_THRESHOLD;
+
+/// Number of solutions whose scores of an objective are stored contiguously.
+/// Dominance is checked a chunk at a time, in loops over chunks of columns
+/// that the autovectorizer turns into SIMD comparisons.
+pub(crate) const CHUNK: usize = 16;
+
+/// Scores of solutions stored column-major. Unlike in `[Scores<N>]`, scores of
+/// an objective aren't interleaved with scores of other objectives: the
+/// matrix is split into chunks of [`CHUNK`] solutions, and each chunk keeps
+/// its column of each objective contiguous. Solutions can be appended without
+/// moving scores of others. Unused rows of the last chunk are padded with
+/// infinities.
+///
+/// The matrix is built from a buffer, which it gives back with
+/// [`ScoreMatrix::into_buffer`], so buffers can be reused between sorts.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ScoreMatrix<const N: usize> {
+  values: Vec<Score>,
+  len: usize,
+  has_nan: bool,
+}
+
+impl<const N: usize> ScoreMatrix<N> {
+  /// Creates an empty matrix that stores scores in the `buffer`.
+  pub(crate) fn from_buffer(mut buffer: Vec<Score>) -> Self {
+    buffer.clear();
+    Self {
+      values: buffer,
+      len: 0,
+      has_nan: false,
+    }
+  }
+
+  /// Creates a matrix of `scores` that stores them in the `buffer`.
+  pub(crate) fn from_scores(buffer: Vec<Score>, scores: &[Scores<N>]) -> Self {
+    let mut matrix = Self::from_buffer(buffer);
+    matrix
+      .values
+      .reserve(scores.len().div_ceil(CHUNK) * CHUNK * N);
+    scores.iter().for_each(|sc| matrix.push(sc));
+    matrix
+  }
+
+  /// Returns the buffer the matrix stores its scores in.
+  pub(crate) fn into_buffer(self) -> Vec<Score> {
+    self.values
+  }
+
+  /// Appends `scores` of a solution to the matrix.
+  pub(crate) fn push(&mut self, scores: &Scores<N>) {
+    let row = self.len % CHUNK;
+    if row == 0 {
+      self
+        .values
+        .resize(self.values.len() + CHUNK * N, Score::INFINITY);
+    }
+    let chunk_start = self.values.len() - CHUNK * N;
+    for (o_idx, &score) in scores.iter().enumerate() {
+      self.values[chunk_start + o_idx * CHUNK + row] = score;
+    }
+    self.has_nan |= scores.iter().any(|sc| sc.is_nan());
+    self.len += 1;
+  }
+
+  /// Returns the score of objective `o_idx` of solution `idx`.
+  pub(crate) fn get(&self, idx: usize, o_idx: usize) -> Score {
+    self.values[idx / CHUNK * CHUNK * N + o_idx * CHUNK + idx % CHUNK]
+  }
+
+  /// Returns scores of solution `idx`.
+  pub(crate) fn scores(&self, idx: usize) -> Scores<N> {
+    std::array::from_fn(|o_idx| self.get(idx, o_idx))
+  }
+
+  /// Copies scores of objective `o_idx` of all solutions into `column`.
+  pub(crate) fn copy_column(&self, o_idx: usize, column: &mut Vec<Score>) {
+    column.clear();
+    for chunk in self.values.chunks_exact(CHUNK * N) {
+      column.extend_from_slice(&chunk[o_idx * CHUNK..(o_idx + 1) * CHUNK]);
+    }
+    column.truncate(self.len);
+  }
+
+  /// Compares scores `p_sc` with scores of each solution of chunk `c_idx`.
+  /// Returns flags of solutions `p` is better than by some objective and
+  /// flags of solutions `p` is worse than by some objective, so `p`
+  /// dominates a solution that has only the first flag set and is dominated
+  /// by a solution that has only the second one.
+  fn compare_chunk(
+    &self,
+    c_idx: usize,
+    p_sc: &Scores<N>,
+  ) -> ([bool; CHUNK], [bool; CHUNK]) {
+    let mut better = [false; CHUNK];
+    let mut worse = [false; CHUNK];
+    let chunk = &self.values[c_idx * CHUNK * N..(c_idx + 1) * CHUNK * N];
+    for (p_sc, column) in p_sc.iter().zip(chunk.chunks_exact(CHUNK)) {
+      let p_sc = p_sc.abs();
+      for ((better, worse), q_sc) in
+        better.iter_mut().zip(worse.iter_mut()).zip(column)
+      {
+        let q_sc = q_sc.abs();
+        *better |= p_sc < q_sc;
+        *worse |= p_sc > q_sc;
+      }
+    }
+    (better, worse)
+  }
+
+  /// Panics if comparing solutions of the matrix would compare a NaN score.
+  fn assert_comparable(&self) {
+    assert!(!self.has_nan || self.len < 2, "NaN encountered");
+  }
+
+  /// Returns `true` if any solution of the matrix dominates a solution with
+  /// scores `p_sc`. Padding never dominates anything, as it's infinite.
+  ///
+  /// With `parallel` feature enabled, matrices of [`PARALLEL_THRESHOLD`] or
+  /// more solutions are checked in parallel.
+  pub(crate) fn dominates(&self, p_sc: &Scores<N>) -> bool {
+    if p_sc.iter().any(|sc| sc.is_nan()) && self.len > 0 {
+      panic!("NaN encountered");
+    }
+    self.assert_comparable();
+    let is_dominated_by_chunk = |c_idx: usize| {
+      let (better, worse) = self.compare_chunk(c_idx, p_sc);
+      better
+        .iter()
+        .zip(worse)
+        .any(|(&better, worse)| worse && !better)
+    };
+    let chunks = self.len.div_ceil(CHUNK);
+    #[cfg(feature = "parallel")]
+    if self.len >= PARALLEL_THRESHOLD {
+      return (0..chunks).into_par_iter().any(is_dominated_by_chunk);
+    }
+    // the last solutions are the most similar to `p`
+    (0..chunks).rev().any(is_dominated_by_chunk)
+  }
+}
+
+impl<const N: usize> DominancePairs for ScoreMatrix<N> {
+  /// Calls `f` for the same pairs in the same order as the pass over rows of
+  /// `[Scores<N>]` does.
+  fn for_each_dominance(&self, mut f: impl FnMut(usize, usize)) {
+    self.assert_comparable();
+    for p_idx in 0..self.len {
+      let p_sc = self.scores(p_idx);
+      let start = p_idx + 1;
+      for c_idx in start / CHUNK..self.len.div_ceil(CHUNK) {
+        let (better, worse) = self.compare_chunk(c_idx, &p_sc);
+        let chunk_start = c_idx * CHUNK;
+        let end = self.len.min(chunk_start + CHUNK);
+        for q_idx in start.max(chunk_start)..end {
+          match (better[q_idx - chunk_start], worse[q_idx - chunk_start]) {
+            // solution `p` dominates solution `q`
+            (true, false) => f(p_idx, q_idx),
+            // solution `q` dominates solution `p`
+            (false, true) => f(q_idx, p_idx),
+            _ => {}
+          }
+        }
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::cmp::Ordering;
+
+  use rand::prelude::*;
+
+  use super::*;
+  use crate::score::ParetoDominance;
+
+  fn random_scores<const N: usize>(len: usize, seed: u64) -> Vec<Scores<N>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..len)
+      .map(|_| std::array::from_fn(|_| rng.gen_range(-4..4) as Score))
+      .collect()
+  }
+
+  fn row_major_dominances<const N: usize>(
+    scores: &[Scores<N>],
+  ) -> Vec<(usize, usize)> {
+    let mut pairs = Vec::new();
+    for (p_idx, p_sc) in scores.iter().enumerate() {
+      for (q_idx, q_sc) in scores.iter().enumerate().skip(p_idx + 1) {
+        match p_sc.dominance(q_sc) {
+          Ordering::Less => pairs.push((p_idx, q_idx)),
+          Ordering::Greater => pairs.push((q_idx, p_idx)),
+          Ordering::Equal => {}
+        }
+      }
+    }
+    pairs
+  }
+
+  #[test]
+  fn test_score_matrix_layout() {
+    let scores = random_scores::<3>(CHUNK + 3, 0);
+    let matrix = ScoreMatrix::from_scores(Vec::new(), &scores);
+    assert_eq!(matrix.len, scores.len());
+    for (idx, sc) in scores.iter().enumerate() {
+      assert_eq!(matrix.scores(idx), *sc);
+    }
+    let mut column = Vec::new();
+    matrix.copy_column(1, &mut column);
+    assert!(column.iter().zip(&scores).all(|(a, sc)| *a == sc[1]));
+    assert_eq!(column.len(), scores.len());
+
+    let buffer = matrix.into_buffer();
+    let capacity = buffer.capacity();
+    let matrix = ScoreMatrix::<3>::from_scores(buffer, &scores[..4]);
+    assert_eq!(matrix.len, 4);
+    assert_eq!(matrix.into_buffer().capacity(), capacity);
+  }
+
+  #[test]
+  fn test_score_matrix_dominances() {
+    for len in [0, 1, CHUNK - 1, CHUNK, 3 * CHUNK + 5] {
+      let scores = random_scores::<3>(len, len as u64);
+      let matrix = ScoreMatrix::from_scores(Vec::new(), &scores);
+      let mut pairs = Vec::new();
+      matrix.for_each_dominance(|p_idx, q_idx| pairs.push((p_idx, q_idx)));
+      assert_eq!(pairs, row_major_dominances(&scores));
+
+      let extra = random_scores::<3>(20, 100 + len as u64);
+      for p_sc in &extra {
+        let expected = scores
+          .iter()
+          .any(|q_sc| q_sc.dominance(p_sc) == Ordering::Less);
+        assert_eq!(matrix.dominates(p_sc), expected);
+      }
+    }
+  }
+
+  #[test]
+  #[should_panic(expected = "NaN encountered")]
+  fn test_score_matrix_nan() {
+    let matrix =
+      ScoreMatrix::from_scores(Vec::new(), &[[1.0, 2.0], [Score::NAN, 0.0]]);
+    matrix.for_each_dominance(|_, _| {});
+  }
+}
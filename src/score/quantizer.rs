@@ -0,0 +1,82 @@
+//! Snapping of scores to a grid.
+
+use super::{Score, Scores};
+
+/// Snaps scores to a grid of the given per-objective `resolution` before
+/// optimizers compare them, so that scores differing only by floating-point
+/// noise are considered equal. Optimizers still keep and report the original
+/// scores, see the `quantizer` setters of [`Nsga2`] and [`Spea2`].
+///
+/// Each score is rounded to the closest multiple of its objective's
+/// resolution. An objective with resolution of `0.0` is left as is. It's a
+/// much cheaper way than epsilon-dominance to keep a front from exploding
+/// into thousands of virtually identical solutions.
+///
+/// # Examples
+/// ```
+/// use moga::score::ScoreQuantizer;
+///
+/// let quantizer = ScoreQuantizer::new([0.5, 0.0]);
+/// assert_eq!(quantizer.quantize(&[1.2, 1.2]), [1.0, 1.2]);
+/// assert_eq!(quantizer.quantize(&[-0.8, 3.0]), [-1.0, 3.0]);
+/// ```
+///
+/// [`Nsga2`]: crate::optimizer::nsga::Nsga2
+/// [`Spea2`]: crate::optimizer::spea::Spea2
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ScoreQuantizer<const N: usize> {
+  /// Grid step of each objective.
+  pub resolution: Scores<N>,
+}
+
+impl<const N: usize> ScoreQuantizer<N> {
+  /// Creates a new `ScoreQuantizer` with the grid step of each objective.
+  ///
+  /// # Panics
+  ///
+  /// Panics if any resolution is negative, infinite or NaN.
+  pub fn new(resolution: Scores<N>) -> Self {
+    if resolution.iter().any(|r| !(r.is_finite() && *r >= 0.0)) {
+      panic!("resolution must be finite and not negative")
+    }
+    Self { resolution }
+  }
+
+  /// Returns `scores` snapped to the grid.
+  pub fn quantize(&self, scores: &Scores<N>) -> Scores<N> {
+    std::array::from_fn(|o| snap(scores[o], self.resolution[o]))
+  }
+}
+
+/// Rounds the `score` to the closest multiple of the `resolution`, unless
+/// the resolution is `0.0`.
+fn snap(score: Score, resolution: Score) -> Score {
+  if resolution > 0.0 {
+    (score / resolution).round() * resolution
+  } else {
+    score
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_quantize() {
+    let quantizer = ScoreQuantizer::new([1e-3, 0.0, 10.0]);
+    let a = quantizer.quantize(&[0.1234567, 0.1234567, 14.0]);
+    let b = quantizer.quantize(&[0.1234561, 0.1234561, 6.0]);
+    assert_eq!(a[0], b[0]);
+    assert_ne!(a[1], b[1]);
+    assert_eq!(a[2], 10.0);
+    assert_eq!(b[2], 10.0);
+    assert!(quantizer.quantize(&[f32::NAN, 0.0, 0.0])[0].is_nan());
+  }
+
+  #[test]
+  #[should_panic]
+  fn test_negative_resolution() {
+    ScoreQuantizer::new([1.0, -1.0]);
+  }
+}
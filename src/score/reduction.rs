@@ -0,0 +1,231 @@
+//! Objective reduction analysis.
+
+use super::Scores;
+
+/// Returns the matrix of Spearman rank correlations between objectives over
+/// the `front`, where the element `[a][b]` is the correlation between
+/// objectives `a` and `b`. As scores converge at `0`, their absolute values
+/// are ranked, and ties share their average rank. Rank correlation doesn't
+/// depend on scales of objectives and catches any monotonic relationship
+/// between them, not just a linear one.
+///
+/// Correlation ranges from `-1.0`, for objectives in perfect conflict, to
+/// `1.0`, for objectives that always improve together, so that one of them
+/// is redundant. Correlation of an objective that is constant over the front
+/// with any other objective is `0.0`, while the diagonal is always `1.0`.
+/// Solutions with NaN scores are ignored.
+///
+/// # Examples
+/// ```
+/// use moga::score::objective_correlation;
+///
+/// // the second objective is a monotonic function of the first one, the
+/// // third one conflicts with both
+/// let front = [[1.0, 1.0, 9.0], [2.0, 8.0, 4.0], [3.0, 27.0, 1.0]];
+/// let correlation = objective_correlation(&front);
+/// assert_eq!(correlation[0][1], 1.0);
+/// assert_eq!(correlation[0][2], -1.0);
+/// ```
+pub fn objective_correlation<const N: usize>(
+  front: &[Scores<N>],
+) -> [[f32; N]; N] {
+  let ranks = objective_ranks(front);
+  let mut correlation = [[0.0; N]; N];
+  for a in 0..N {
+    correlation[a][a] = 1.0;
+    for b in a + 1..N {
+      let r = pearson(&ranks[a], &ranks[b]) as f32;
+      correlation[a][b] = r;
+      correlation[b][a] = r;
+    }
+  }
+  correlation
+}
+
+/// Returns indices of objectives that are redundant over the `front`, in
+/// ascending order. Dropping them from optimization barely changes which
+/// solutions are nondominated, while it makes the search faster and the
+/// front easier to explore.
+///
+/// Objectives are analyzed in order: an objective is redundant if its
+/// Spearman rank correlation with an objective that was kept is at least the
+/// `threshold`, see [`objective_correlation`], or if it's constant over the
+/// front. Thus, of a group of correlated objectives, the first one is kept.
+/// If all objectives are constant, the first one is kept, too. Sensible
+/// thresholds lie between `0.8` and `0.95`.
+///
+/// # Examples
+/// ```
+/// use moga::score::suggest_redundant;
+///
+/// // cost and weight go hand in hand, durability conflicts with them, and
+/// // color is the same for all solutions
+/// let front = [
+///   [10.0, 2.0, 0.9, 1.0],
+///   [20.0, 5.0, 0.5, 1.0],
+///   [30.0, 6.0, 0.4, 1.0],
+///   [40.0, 9.0, 0.1, 1.0],
+/// ];
+/// assert_eq!(suggest_redundant(&front, 0.9), [1, 3]);
+/// ```
+pub fn suggest_redundant<const N: usize>(
+  front: &[Scores<N>],
+  threshold: f32,
+) -> Vec<usize> {
+  let ranks = objective_ranks(front);
+  if ranks.iter().all(|r| is_constant(r)) {
+    return (1..N).collect();
+  }
+  let mut kept: Vec<usize> = Vec::with_capacity(N);
+  let mut redundant = Vec::new();
+  for o in 0..N {
+    let is_redundant = is_constant(&ranks[o])
+      || kept
+        .iter()
+        .any(|&k| pearson(&ranks[k], &ranks[o]) as f32 >= threshold);
+    if is_redundant {
+      redundant.push(o);
+    } else {
+      kept.push(o);
+    }
+  }
+  redundant
+}
+
+/// Returns ranks of absolute scores of each objective among solutions of the
+/// `front` without NaN scores. Ties share their average rank.
+fn objective_ranks<const N: usize>(front: &[Scores<N>]) -> [Vec<f64>; N] {
+  let valid: Vec<_> = front
+    .iter()
+    .filter(|sc| sc.iter().all(|s| !s.is_nan()))
+    .collect();
+  std::array::from_fn(|o| {
+    let mut order: Vec<_> = (0..valid.len()).collect();
+    order.sort_by(|&a, &b| valid[a][o].abs().total_cmp(&valid[b][o].abs()));
+    let mut ranks = vec![0.0; valid.len()];
+    let mut start = 0;
+    while start < order.len() {
+      let score = valid[order[start]][o].abs();
+      let end = start
+        + order[start..]
+          .iter()
+          .take_while(|&&i| valid[i][o].abs() == score)
+          .count();
+      // ranks `start..end` are tied, so each gets their average
+      let rank = (start + end - 1) as f64 / 2.0;
+      for &i in &order[start..end] {
+        ranks[i] = rank;
+      }
+      start = end;
+    }
+    ranks
+  })
+}
+
+/// Returns `true` if all `ranks` are equal, i.e. the objective is constant.
+fn is_constant(ranks: &[f64]) -> bool {
+  ranks.windows(2).all(|w| w[0] == w[1])
+}
+
+/// Returns Pearson correlation of `x` and `y`, or `0.0` if either of them is
+/// constant.
+fn pearson(x: &[f64], y: &[f64]) -> f64 {
+  let n = x.len() as f64;
+  let mean_x = x.iter().sum::<f64>() / n;
+  let mean_y = y.iter().sum::<f64>() / n;
+  let (mut cov, mut var_x, mut var_y) = (0.0, 0.0, 0.0);
+  for (a, b) in x.iter().zip(y) {
+    let (dx, dy) = (a - mean_x, b - mean_y);
+    cov += dx * dy;
+    var_x += dx * dx;
+    var_y += dy * dy;
+  }
+  if var_x > 0.0 && var_y > 0.0 {
+    (cov / (var_x * var_y).sqrt()).clamp(-1.0, 1.0)
+  } else {
+    0.0
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_objective_correlation() {
+    // the second objective is a scaled and shifted copy of the first one, the
+    // third one is its reverse, the fourth one is noise
+    let front: Vec<[f32; 4]> = (0..20)
+      .map(|i| {
+        let x = i as f32;
+        [x, 1000.0 * x + 5.0, 20.0 - x, ((i * 7) % 20) as f32]
+      })
+      .collect();
+    let correlation = objective_correlation(&front);
+    assert_eq!(correlation[0][1], 1.0);
+    assert_eq!(correlation[0][2], -1.0);
+    assert!(correlation[0][3].abs() < 0.5);
+    for (a, row) in correlation.iter().enumerate() {
+      assert_eq!(row[a], 1.0);
+      for (b, &r) in row.iter().enumerate() {
+        assert_eq!(r, correlation[b][a]);
+      }
+    }
+  }
+
+  #[test]
+  fn test_objective_correlation_ties_and_constants() {
+    let front = [[1.0, 3.0, 2.0], [1.0, 3.0, 4.0], [2.0, 3.0, 5.0]];
+    let correlation = objective_correlation(&front);
+    // ties share the average rank, so the correlation is not perfect
+    assert!(correlation[0][2] > 0.8 && correlation[0][2] < 1.0);
+    assert_eq!(correlation[0][1], 0.0);
+    assert_eq!(correlation[1][1], 1.0);
+  }
+
+  #[test]
+  fn test_objective_correlation_absolute_scores() {
+    // scores converge at `0`, so `-3.0` is as bad as `3.0`
+    let front = [[-1.0, 1.0], [-2.0, 2.0], [-3.0, 3.0]];
+    assert_eq!(objective_correlation(&front)[0][1], 1.0);
+  }
+
+  #[test]
+  fn test_objective_correlation_degenerate_fronts() {
+    let identity = [[1.0, 0.0], [0.0, 1.0]];
+    assert_eq!(objective_correlation::<2>(&[]), identity);
+    assert_eq!(objective_correlation(&[[1.0, 2.0]]), identity);
+    // solutions with NaN scores are ignored
+    let front = [[1.0, 1.0], [f32::NAN, 0.0], [2.0, 2.0]];
+    assert_eq!(objective_correlation(&front)[0][1], 1.0);
+  }
+
+  #[test]
+  fn test_suggest_redundant() {
+    // objectives 0, 2 and 4 form a group of the same ranking, 1 and 3
+    // conflict with them and with each other, 5 is constant
+    let front: Vec<[f32; 6]> = (0..30)
+      .map(|i| {
+        let x = i as f32 / 29.0;
+        [
+          x,
+          1.0 - x * x,
+          x.powi(3) * 10.0,
+          ((i * 11) % 30) as f32,
+          x.sqrt() + 3.0,
+          7.0,
+        ]
+      })
+      .collect();
+    assert_eq!(suggest_redundant(&front, 0.9), [2, 4, 5]);
+    // with a threshold above `1.0`, only constant objectives are redundant
+    assert_eq!(suggest_redundant(&front, 1.1), [5]);
+  }
+
+  #[test]
+  fn test_suggest_redundant_all_constant() {
+    let front = [[1.0, 2.0, 3.0]; 4];
+    assert_eq!(suggest_redundant(&front, 0.9), [1, 2]);
+    assert_eq!(suggest_redundant::<2>(&[], 0.9), [1]);
+  }
+}
@@ -0,0 +1,57 @@
+//! (De)serialization of [`Scores`] of any length for `#[serde(with)]`
+//! attribute. `serde` implements its traits only for arrays of up to 32
+//! elements, so scores are (de)serialized as tuples by hand.
+
+use std::{fmt, marker::PhantomData};
+
+use serde::{
+  de::{self, SeqAccess, Visitor},
+  ser::SerializeTuple,
+  Deserializer,
+  Serializer,
+};
+
+use super::{Score, Scores};
+
+pub(crate) fn serialize<S: Serializer, const N: usize>(
+  scores: &Scores<N>,
+  serializer: S,
+) -> Result<S::Ok, S::Error> {
+  let mut tuple = serializer.serialize_tuple(N)?;
+  for score in scores {
+    tuple.serialize_element(score)?;
+  }
+  tuple.end()
+}
+
+pub(crate) fn deserialize<'de, D: Deserializer<'de>, const N: usize>(
+  deserializer: D,
+) -> Result<Scores<N>, D::Error> {
+  struct ScoresVisitor<const N: usize>(PhantomData<Scores<N>>);
+
+  impl<'de, const N: usize> Visitor<'de> for ScoresVisitor<N> {
+    type Value = Scores<N>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+      write!(f, "an array of {N} scores")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(
+      self,
+      mut seq: A,
+    ) -> Result<Self::Value, A::Error> {
+      let mut scores = [0.0; N];
+      for (i, score) in scores.iter_mut().enumerate() {
+        *score = seq
+          .next_element::<Score>()?
+          .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+      }
+      if seq.next_element::<de::IgnoredAny>()?.is_some() {
+        return Err(de::Error::invalid_length(N + 1, &self));
+      }
+      Ok(scores)
+    }
+  }
+
+  deserializer.deserialize_tuple(N, ScoresVisitor(PhantomData))
+}
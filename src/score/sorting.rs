@@ -0,0 +1,709 @@
+//! Nondominated sorting of fitness scores.
+
+use std::cmp::Ordering;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+#[cfg(feature = "score-matrix")]
+use super::{matrix::ScoreMatrix, Score};
+use super::{ParetoDominance, Scores};
+#[cfg(feature = "parallel")]
+use crate::execution::PARALLEL_THRESHOLD;
+pub(crate) use crate::optimizer::SolutionIndex;
+use crate::optimizer::{from_index, to_index};
+
+/// Number of solution's dominators.
+type DominanceCounter = u32;
+/// Indices of solutions of a front.
+pub(crate) type Front = Vec<SolutionIndex>;
+
+/// Number of solutions starting from which [`nondominated_sort_with`] switches from
+/// the naive algorithm to the efficient nondominated sort.
+pub(crate) const ENS_THRESHOLD: usize = 256;
+
+/// Reusable buffers of nondominated sorting. Keeping them between sorts saves
+/// a lot of allocations.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct SortingBuffers {
+  /// Indices of solutions dominated by each solution, list after list.
+  dominated: Vec<SolutionIndex>,
+  /// Start of each solution's list in `dominated`, followed by its length.
+  dominated_offsets: Vec<usize>,
+  dominance_counters: Vec<DominanceCounter>,
+  ens_fronts: Vec<Front>,
+  /// Indices of solutions of all fronts, front after front.
+  sorted_indices: Vec<SolutionIndex>,
+  /// End of each front in `sorted_indices`.
+  front_ends: Vec<usize>,
+  /// Buffer of the column-major matrix of all scores.
+  #[cfg(feature = "score-matrix")]
+  score_columns: Vec<Score>,
+  /// Buffers of column-major matrices of scores of each front.
+  #[cfg(feature = "score-matrix")]
+  ens_front_columns: Vec<Vec<Score>>,
+}
+
+impl SortingBuffers {
+  /// Returns fronts found by the last sort.
+  pub(crate) fn fronts(&self) -> impl Iterator<Item = &[SolutionIndex]> {
+    std::iter::once(0)
+      .chain(self.front_ends.iter().copied())
+      .zip(self.front_ends.iter())
+      .map(|(start, &end)| &self.sorted_indices[start..end])
+  }
+}
+
+/// Sorts solutions into fronts by their scores. The first front contains
+/// indices of nondominated solutions, the second front contains indices of
+/// solutions dominated only by the solutions of the first front and so on.
+/// Each index of `scores` is present in exactly one front. Found fronts are
+/// stored in given `buffers` and can be accessed with
+/// [`SortingBuffers::fronts`].
+///
+/// For small slices of scores, the naive algorithm from the original NSGA-II
+/// paper is used. Starting from [`ENS_THRESHOLD`] solutions, the efficient
+/// nondominated sort with sequential search strategy (ENS-SS) is used instead.
+/// Both algorithms produce identical fronts, but the order of indices inside
+/// each front may differ.
+///
+/// With `score-matrix` feature enabled, both algorithms compare scores stored
+/// column-major in a [`ScoreMatrix`], which is filled from `scores` on each
+/// sort. Fronts are the same either way.
+pub(crate) fn nondominated_sort_with<const N: usize>(
+  scores: &[Scores<N>],
+  buffers: &mut SortingBuffers,
+) {
+  if scores.len() < ENS_THRESHOLD {
+    naive_nondominated_sort(scores, buffers)
+  } else {
+    ens_nondominated_sort(scores, buffers)
+  }
+}
+
+/// Returns the number of the front each solution belongs to by its `scores`,
+/// starting from `0` for nondominated solutions. See
+/// [`nondominated_sort_with`].
+pub(crate) fn front_ranks<const N: usize>(scores: &[Scores<N>]) -> Vec<usize> {
+  let mut sorting = SortingBuffers::default();
+  nondominated_sort_with(scores, &mut sorting);
+  let mut ranks = vec![0; scores.len()];
+  for (rank, front) in sorting.fronts().enumerate() {
+    front.iter().for_each(|&idx| ranks[from_index(idx)] = rank);
+  }
+  ranks
+}
+
+/// The fast nondominated sort from the NSGA-II paper. Builds dominance lists
+/// and counters for each pair of solutions, then peels off fronts one by one.
+fn naive_nondominated_sort<const N: usize>(
+  scores: &[Scores<N>],
+  buffers: &mut SortingBuffers,
+) {
+  let SortingBuffers {
+    dominated,
+    dominated_offsets,
+    dominance_counters,
+    #[cfg(feature = "score-matrix")]
+    score_columns,
+    ..
+  } = buffers;
+  #[cfg(feature = "score-matrix")]
+  let matrix = ScoreMatrix::from_scores(std::mem::take(score_columns), scores);
+  #[cfg(feature = "score-matrix")]
+  let pairs = &matrix;
+  #[cfg(not(feature = "score-matrix"))]
+  let pairs = scores;
+  dominance_counters.clear();
+  dominance_counters.resize(scores.len(), 0);
+  dominated_offsets.clear();
+  dominated_offsets.resize(scores.len() + 1, 0);
+
+  // count solutions dominated by each solution and solutions dominating it
+  pairs.for_each_dominance(|dominating_idx, dominated_idx| {
+    dominated_offsets[dominating_idx] += 1;
+    dominance_counters[dominated_idx] += 1;
+  });
+  // turn counts into starts of dominance lists
+  let mut offset = 0;
+  for entry in dominated_offsets.iter_mut() {
+    offset += std::mem::replace(entry, offset);
+  }
+  // compare solutions once again to fill the lists, moving each start to the
+  // end of its list...
+  dominated.clear();
+  dominated.resize(offset, to_index(0));
+  pairs.for_each_dominance(|dominating_idx, dominated_idx| {
+    dominated[dominated_offsets[dominating_idx]] = to_index(dominated_idx);
+    dominated_offsets[dominating_idx] += 1;
+  });
+  // so a list ends where the next one starts
+  dominated_offsets.copy_within(..scores.len(), 1);
+  dominated_offsets[0] = 0;
+  #[cfg(feature = "score-matrix")]
+  {
+    *score_columns = matrix.into_buffer();
+  }
+
+  peel_fronts(scores.len(), buffers);
+}
+
+/// Peels off fronts of `len` solutions one by one, given dominance lists and
+/// counters in `buffers`. Counters are used up, lists are left as they are.
+fn peel_fronts(len: usize, buffers: &mut SortingBuffers) {
+  let SortingBuffers {
+    dominated,
+    dominated_offsets,
+    dominance_counters,
+    sorted_indices,
+    front_ends,
+    ..
+  } = buffers;
+  sorted_indices.clear();
+  front_ends.clear();
+
+  // solutions that aren't dominated by any other solution form the first front
+  sorted_indices.extend(
+    (0..len)
+      .filter(|&idx| dominance_counters[idx] == 0)
+      .map(to_index),
+  );
+  let mut front_start = 0;
+  while front_start < sorted_indices.len() {
+    let front_end = sorted_indices.len();
+    // for each solution `p` in last front...
+    for i in front_start..front_end {
+      let p_idx = from_index(sorted_indices[i]);
+      // for each solution `q` dominated by `p`...
+      let p_dominated =
+        &dominated[dominated_offsets[p_idx]..dominated_offsets[p_idx + 1]];
+      for &q_idx in p_dominated {
+        // decrement counter of solutions dominating `q`
+        let counter = &mut dominance_counters[from_index(q_idx)];
+        *counter -= 1;
+        // if no more solutions dominate `q`, push its index into next front
+        if *counter == 0 {
+          sorted_indices.push(q_idx);
+        }
+      }
+    }
+    front_ends.push(front_end);
+    front_start = front_end;
+  }
+}
+
+/// Returns indices of nondominated `scores` in ascending order. Solutions
+/// with equal scores are either all nondominated or all dominated.
+///
+/// With `parallel` feature enabled, `PARALLEL_THRESHOLD` or more scores are
+/// checked for dominance in parallel. The result doesn't depend on it.
+///
+/// # Examples
+/// ```
+/// use moga::score::pareto_front_indices;
+///
+/// let scores = [[1.0, 4.0], [2.0, 5.0], [3.0, 1.0], [1.0, 4.0]];
+/// assert_eq!(pareto_front_indices(&scores), [0, 2, 3]);
+/// ```
+pub fn pareto_front_indices<const N: usize>(
+  scores: &[Scores<N>],
+) -> Vec<usize> {
+  #[cfg(feature = "parallel")]
+  if scores.len() >= PARALLEL_THRESHOLD {
+    return (0..scores.len())
+      .into_par_iter()
+      .filter(|&i| {
+        !scores
+          .iter()
+          .any(|other| other.dominance(&scores[i]) == Ordering::Less)
+      })
+      .collect();
+  }
+
+  // if a flag is not set, the corresponding solution is dominated
+  let mut nondominated = vec![true; scores.len()];
+  for (i, p_sc) in scores.iter().enumerate() {
+    if !nondominated[i] {
+      // solutions dominated by this one are dominated by its dominator, too
+      continue;
+    }
+    for (j, q_sc) in scores.iter().enumerate().skip(i + 1) {
+      match p_sc.dominance(q_sc) {
+        Ordering::Less => nondominated[j] = false,
+        Ordering::Greater => nondominated[i] = false,
+        Ordering::Equal => {}
+      }
+    }
+  }
+  (0..scores.len()).filter(|&i| nondominated[i]).collect()
+}
+
+/// Scores of solutions that can be compared pairwise for dominance.
+pub(crate) trait DominancePairs {
+  /// Calls `f` with indices of dominating and dominated solutions for each
+  /// unique pair of solutions where one dominates the other.
+  fn for_each_dominance(&self, f: impl FnMut(usize, usize));
+}
+
+impl<const N: usize> DominancePairs for [Scores<N>] {
+  fn for_each_dominance(&self, mut f: impl FnMut(usize, usize)) {
+    for (p_idx, p_sc) in self.iter().enumerate() {
+      for (q_idx, q_sc) in self.iter().enumerate().skip(p_idx + 1) {
+        match p_sc.dominance(q_sc) {
+          // solution `p` dominates solution `q`
+          Ordering::Less => f(p_idx, q_idx),
+          // solution `q` dominates solution `p`
+          Ordering::Greater => f(q_idx, p_idx),
+          Ordering::Equal => {}
+        }
+      }
+    }
+  }
+}
+
+/// The efficient nondominated sort with sequential search strategy (ENS-SS).
+///
+/// Solutions are presorted lexicographically by their absolute scores, so a
+/// solution can only be dominated by solutions that precede it. Then each
+/// solution is put into the first front that has no solution dominating it.
+/// Members of a front are checked starting from the last one, as it's the
+/// most similar to the solution being placed. With `parallel` feature enabled,
+/// fronts of `PARALLEL_THRESHOLD` or more solutions are checked in parallel.
+/// With `score-matrix` feature enabled, scores of members of each front are
+/// kept in a [`ScoreMatrix`] and checked a chunk at a time.
+fn ens_nondominated_sort<const N: usize>(
+  scores: &[Scores<N>],
+  buffers: &mut SortingBuffers,
+) {
+  let SortingBuffers {
+    ens_fronts,
+    sorted_indices,
+    front_ends,
+    #[cfg(feature = "score-matrix")]
+    ens_front_columns,
+    ..
+  } = buffers;
+  sorted_indices.clear();
+  sorted_indices.extend((0..scores.len()).map(to_index));
+  sorted_indices.sort_unstable_by(|&a_idx, &b_idx| {
+    scores[from_index(a_idx)]
+      .iter()
+      .zip(scores[from_index(b_idx)].iter())
+      .map(|(a, b)| a.abs().total_cmp(&b.abs()))
+      .find(|ord| ord.is_ne())
+      .unwrap_or(Ordering::Equal)
+  });
+
+  ens_fronts.iter_mut().for_each(Vec::clear);
+  // scores of members of each front, in the same order as the members
+  #[cfg(feature = "score-matrix")]
+  let mut front_matrices: Vec<ScoreMatrix<N>> =
+    std::mem::take(ens_front_columns)
+      .into_iter()
+      .map(ScoreMatrix::from_buffer)
+      .collect();
+  let mut front_cnt = 0;
+  for &p_idx in sorted_indices.iter() {
+    let p_sc = &scores[from_index(p_idx)];
+    // find the first front without solutions that dominate solution `p`
+    #[cfg(feature = "score-matrix")]
+    let front_idx = front_matrices[..front_cnt]
+      .iter()
+      .position(|matrix| !matrix.dominates(p_sc))
+      .unwrap_or(front_cnt);
+    #[cfg(not(feature = "score-matrix"))]
+    let front_idx = {
+      let dominates_p = |&q_idx: &SolutionIndex| {
+        scores[from_index(q_idx)].dominance(p_sc) == Ordering::Less
+      };
+      ens_fronts[..front_cnt]
+        .iter()
+        .position(|front| {
+          #[cfg(feature = "parallel")]
+          if front.len() >= PARALLEL_THRESHOLD {
+            return !front.par_iter().any(dominates_p);
+          }
+          !front.iter().rev().any(dominates_p)
+        })
+        .unwrap_or(front_cnt)
+    };
+    // or start a new front
+    if front_idx == front_cnt {
+      front_cnt += 1;
+      if ens_fronts.len() < front_cnt {
+        ens_fronts.push(Vec::new());
+      }
+      #[cfg(feature = "score-matrix")]
+      if front_matrices.len() < front_cnt {
+        front_matrices.push(ScoreMatrix::default());
+      }
+    }
+    ens_fronts[front_idx].push(p_idx);
+    #[cfg(feature = "score-matrix")]
+    front_matrices[front_idx].push(p_sc);
+  }
+  #[cfg(feature = "score-matrix")]
+  {
+    *ens_front_columns = front_matrices
+      .into_iter()
+      .map(ScoreMatrix::into_buffer)
+      .collect();
+  }
+
+  sorted_indices.clear();
+  front_ends.clear();
+  for front in ens_fronts[..front_cnt].iter() {
+    sorted_indices.extend_from_slice(front);
+    front_ends.push(sorted_indices.len());
+  }
+}
+
+/// Nondominated sorting that keeps dominance relations of survivors between
+/// sorts. Dominance of two solutions depends only on their scores, so
+/// relations of solutions whose scores haven't changed since the last sort
+/// stay the same. Only relations of the other solutions, such as new
+/// offsprings, are computed, and the rest are taken from the last sort.
+///
+/// Solutions are matched with survivors of the last sort by position: the
+/// longest prefix of scores that equals scores of survivors is reused, so if
+/// survivors were mutated, reordered or removed, relations of fewer of them
+/// are reused. Every `full_sort_interval` sorts, all relations are computed
+/// anew.
+///
+/// It finds the same fronts in the same order as the naive algorithm of
+/// [`nondominated_sort_with`].
+#[derive(Clone, Debug)]
+pub(crate) struct IncrementalSorting<const N: usize> {
+  full_sort_interval: usize,
+  sorts_until_full: usize,
+  /// Scores of survivors of the last sort.
+  scores: Vec<Scores<N>>,
+  /// Indices of survivors dominated by each survivor, list after list, each
+  /// list in ascending order.
+  dominated: Vec<SolutionIndex>,
+  /// Start of each survivor's list in `dominated`, followed by its end.
+  dominated_offsets: Vec<usize>,
+  /// Pairs of dominating and dominated solutions found by the last sort.
+  relations: Vec<(SolutionIndex, SolutionIndex)>,
+  /// Position of each solution of the last sort among survivors.
+  positions: Vec<usize>,
+}
+
+impl<const N: usize> IncrementalSorting<N> {
+  /// Creates a sorting that computes all relations every
+  /// `full_sort_interval` sorts, starting from the first one.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `full_sort_interval` is `0`.
+  pub(crate) fn new(full_sort_interval: usize) -> Self {
+    assert!(full_sort_interval > 0, "full sort interval must not be 0");
+    Self {
+      full_sort_interval,
+      sorts_until_full: 0,
+      scores: Vec::new(),
+      dominated: Vec::new(),
+      dominated_offsets: Vec::new(),
+      relations: Vec::new(),
+      positions: Vec::new(),
+    }
+  }
+
+  /// Sorts solutions into fronts by their `scores`, like
+  /// [`nondominated_sort_with`], reusing relations of survivors of the last
+  /// sort.
+  pub(crate) fn sort(
+    &mut self,
+    scores: &[Scores<N>],
+    buffers: &mut SortingBuffers,
+  ) {
+    let kept = if self.sorts_until_full == 0 {
+      self.sorts_until_full = self.full_sort_interval;
+      0
+    } else {
+      self
+        .scores
+        .iter()
+        .zip(scores)
+        .take_while(|(a, b)| {
+          a.iter()
+            .zip(b.iter())
+            .all(|(a, b)| a.to_bits() == b.to_bits())
+        })
+        .count()
+    };
+    self.sorts_until_full -= 1;
+
+    // compare each solution after the kept ones with each solution before it
+    self.relations.clear();
+    for q_idx in kept..scores.len() {
+      for p_idx in 0..q_idx {
+        match scores[p_idx].dominance(&scores[q_idx]) {
+          Ordering::Less => {
+            self.relations.push((to_index(p_idx), to_index(q_idx)))
+          }
+          Ordering::Greater => {
+            self.relations.push((to_index(q_idx), to_index(p_idx)))
+          }
+          Ordering::Equal => {}
+        }
+      }
+    }
+
+    let SortingBuffers {
+      dominated,
+      dominated_offsets,
+      dominance_counters,
+      ..
+    } = buffers;
+    dominance_counters.clear();
+    dominance_counters.resize(scores.len(), 0);
+    dominated_offsets.clear();
+    dominated_offsets.resize(scores.len() + 1, 0);
+    // count kept relations and new ones, as the naive sort does...
+    for (p_idx, offset) in dominated_offsets[..kept].iter_mut().enumerate() {
+      let kept_dominated = self.kept_dominated(p_idx, kept);
+      *offset += kept_dominated.len();
+      for &q_idx in kept_dominated {
+        dominance_counters[from_index(q_idx)] += 1;
+      }
+    }
+    for &(p_idx, q_idx) in &self.relations {
+      dominated_offsets[from_index(p_idx)] += 1;
+      dominance_counters[from_index(q_idx)] += 1;
+    }
+    let mut offset = 0;
+    for entry in dominated_offsets.iter_mut() {
+      offset += std::mem::replace(entry, offset);
+    }
+    // and fill the lists, kept relations first, so that each list is in
+    // ascending order, as lists of the naive sort are
+    dominated.clear();
+    dominated.resize(offset, to_index(0));
+    for (p_idx, offset) in dominated_offsets[..kept].iter_mut().enumerate() {
+      let kept_dominated = self.kept_dominated(p_idx, kept);
+      dominated[*offset..*offset + kept_dominated.len()]
+        .copy_from_slice(kept_dominated);
+      *offset += kept_dominated.len();
+    }
+    for &(p_idx, q_idx) in &self.relations {
+      dominated[dominated_offsets[from_index(p_idx)]] = q_idx;
+      dominated_offsets[from_index(p_idx)] += 1;
+    }
+    dominated_offsets.copy_within(..scores.len(), 1);
+    dominated_offsets[0] = 0;
+
+    peel_fronts(scores.len(), buffers);
+  }
+
+  /// Returns indices of the first `kept` survivors dominated by survivor
+  /// `p_idx`.
+  fn kept_dominated(&self, p_idx: usize, kept: usize) -> &[SolutionIndex] {
+    let list = &self.dominated
+      [self.dominated_offsets[p_idx]..self.dominated_offsets[p_idx + 1]];
+    &list[..list.partition_point(|&q_idx| from_index(q_idx) < kept)]
+  }
+
+  /// Keeps relations between `survivors` of the last sort of `scores` with
+  /// `buffers`, in their order, for the next sort.
+  pub(crate) fn retain(
+    &mut self,
+    scores: &[Scores<N>],
+    survivors: &[SolutionIndex],
+    buffers: &SortingBuffers,
+  ) {
+    self.positions.clear();
+    self.positions.resize(scores.len(), usize::MAX);
+    for (position, &idx) in survivors.iter().enumerate() {
+      self.positions[from_index(idx)] = position;
+    }
+    self.scores.clear();
+    self
+      .scores
+      .extend(survivors.iter().map(|&idx| scores[from_index(idx)]));
+    self.dominated.clear();
+    self.dominated_offsets.clear();
+    self.dominated_offsets.push(0);
+    for &p_idx in survivors {
+      let p_idx = from_index(p_idx);
+      let start = self.dominated.len();
+      let p_dominated = &buffers.dominated[buffers.dominated_offsets[p_idx]
+        ..buffers.dominated_offsets[p_idx + 1]];
+      self.dominated.extend(
+        p_dominated
+          .iter()
+          .map(|&q_idx| self.positions[from_index(q_idx)])
+          .filter(|&position| position != usize::MAX)
+          .map(to_index),
+      );
+      self.dominated[start..].sort_unstable();
+      self.dominated_offsets.push(self.dominated.len());
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use rand::prelude::*;
+
+  use super::*;
+
+  /// Returns front number of each solution.
+  fn front_numbers(fronts: &[Front], len: usize) -> Vec<usize> {
+    let mut numbers = vec![usize::MAX; len];
+    for (front_idx, front) in fronts.iter().enumerate() {
+      for &idx in front {
+        let idx = from_index(idx);
+        assert_eq!(numbers[idx], usize::MAX, "index {idx} is in two fronts");
+        numbers[idx] = front_idx;
+      }
+    }
+    assert!(numbers.iter().all(|&n| n != usize::MAX), "index is missing");
+    numbers
+  }
+
+  fn random_scores<const N: usize>(
+    rng: &mut impl Rng,
+    len: usize,
+    grid: i32,
+  ) -> Vec<Scores<N>> {
+    (0..len)
+      .map(|_| [(); N].map(|_| rng.gen_range(-grid..=grid) as f32))
+      .collect()
+  }
+
+  fn sort_with<const N: usize>(
+    sort: fn(&[Scores<N>], &mut SortingBuffers),
+    scores: &[Scores<N>],
+  ) -> Vec<Front> {
+    let mut buffers = SortingBuffers::default();
+    sort(scores, &mut buffers);
+    buffers.fronts().map(<[_]>::to_vec).collect()
+  }
+
+  fn assert_sorts_match<const N: usize>(scores: &[Scores<N>]) {
+    let naive = sort_with(naive_nondominated_sort, scores);
+    let ens = sort_with(ens_nondominated_sort, scores);
+    assert_eq!(
+      front_numbers(&naive, scores.len()),
+      front_numbers(&ens, scores.len())
+    );
+  }
+
+  #[test]
+  fn test_nondominated_sort_fronts() {
+    let scores = [[1.0, 4.0], [2.0, 2.0], [4.0, 1.0], [3.0, 3.0], [4.0, 4.0]];
+    let fronts = sort_with(nondominated_sort_with, &scores);
+    assert_eq!(front_numbers(&fronts, scores.len()), [0, 0, 0, 1, 2]);
+  }
+
+  #[test]
+  fn test_nondominated_sort_empty() {
+    assert!(sort_with::<2>(naive_nondominated_sort, &[]).is_empty());
+    assert!(sort_with::<2>(ens_nondominated_sort, &[]).is_empty());
+  }
+
+  #[test]
+  fn test_nondominated_sort_duplicates() {
+    let scores = [[1.0, 1.0]; 10];
+    assert_eq!(sort_with(naive_nondominated_sort, &scores).len(), 1);
+    assert_eq!(sort_with(ens_nondominated_sort, &scores).len(), 1);
+  }
+
+  #[test]
+  fn test_ens_matches_naive_sort_large_front() {
+    // a single front big enough to be checked in parallel, with duplicates
+    let len = crate::execution::PARALLEL_THRESHOLD;
+    let front = (0..len).map(|i| [i as f32, (len - i) as f32]);
+    let scores: Vec<Scores<2>> = front
+      .clone()
+      .chain(front.clone())
+      .chain(front.map(|[a, b]| [a + 1.0, b + 1.0]))
+      .collect();
+    assert_sorts_match(&scores);
+    assert_eq!(sort_with(ens_nondominated_sort, &scores).len(), 2);
+  }
+
+  #[test]
+  fn test_sorting_buffers_reuse() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut buffers = SortingBuffers::default();
+    for len in [300, 10, 500, 0, 50] {
+      let scores = random_scores::<2>(&mut rng, len, 5);
+      nondominated_sort_with(&scores, &mut buffers);
+      let fronts: Vec<Front> = buffers.fronts().map(<[_]>::to_vec).collect();
+      assert_eq!(
+        front_numbers(&fronts, len),
+        front_numbers(&sort_with(naive_nondominated_sort, &scores), len)
+      );
+    }
+  }
+
+  #[test]
+  fn test_ens_matches_naive_sort_randomized() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for _ in 0..50 {
+      // a small grid produces plenty of ties and duplicates
+      let len = rng.gen_range(0..300);
+      assert_sorts_match(&random_scores::<2>(&mut rng, len, 5));
+      assert_sorts_match(&random_scores::<3>(&mut rng, len, 3));
+      assert_sorts_match(&random_scores::<5>(&mut rng, len, 100));
+    }
+  }
+
+  #[test]
+  fn test_pareto_front_indices() {
+    let mut rng = StdRng::seed_from_u64(0);
+    for len in [0, 1, 2, 100, 1500] {
+      let scores = random_scores::<3>(&mut rng, len, 10);
+      let mut first_front: Vec<_> = sort_with(naive_nondominated_sort, &scores)
+        .first()
+        .map(|front| front.iter().copied().map(from_index).collect())
+        .unwrap_or_default();
+      first_front.sort_unstable();
+      assert_eq!(pareto_front_indices(&scores), first_front);
+    }
+    // duplicates survive together
+    assert_eq!(pareto_front_indices(&[[1.0, 1.0], [-1.0, 1.0]]), [0, 1]);
+  }
+
+  #[test]
+  fn test_incremental_sorting_matches_naive_sort() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut incremental = IncrementalSorting::<3>::new(7);
+    let mut buffers = SortingBuffers::default();
+    let mut population = random_scores::<3>(&mut rng, 50, 4);
+    for generation in 0..500 {
+      // survivors may be mutated, reordered or removed between generations
+      match rng.gen_range(0..10) {
+        0 => {
+          let idx = rng.gen_range(0..population.len());
+          population[idx] = random_scores::<3>(&mut rng, 1, 4)[0];
+        }
+        1 => population.shuffle(&mut rng),
+        2 => population.truncate(rng.gen_range(0..=population.len())),
+        _ => {}
+      }
+      let offsprings = rng.gen_range(0..60);
+      population.extend(random_scores::<3>(&mut rng, offsprings, 4));
+
+      incremental.sort(&population, &mut buffers);
+      let fronts: Vec<Front> = buffers.fronts().map(<[_]>::to_vec).collect();
+      assert_eq!(
+        fronts,
+        sort_with(naive_nondominated_sort, &population),
+        "generation {generation}"
+      );
+
+      // random survivors in random order
+      let mut survivors: Vec<_> = (0..population.len()).map(to_index).collect();
+      survivors.shuffle(&mut rng);
+      survivors.truncate(rng.gen_range(0..=50.min(survivors.len())));
+      incremental.retain(&population, &survivors, &buffers);
+      population = survivors
+        .iter()
+        .map(|&idx| population[from_index(idx)])
+        .collect();
+    }
+  }
+}
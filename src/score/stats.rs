@@ -0,0 +1,202 @@
+//! Statistics of scores.
+
+use std::fmt::{self, Display};
+
+use super::{Score, Scores};
+
+/// Statistics of each objective's scores: minimum, maximum, mean, standard
+/// deviation and the best score, i.e. the one closest to `0`.
+///
+/// Statistics are computed in a single pass, variance is computed with
+/// Welford's algorithm. NaN scores are ignored, and statistics of an objective
+/// without any other scores are NaNs. Statistics are displayed with a line per
+/// objective.
+///
+/// # Examples
+/// ```
+/// use moga::score::ScoreStats;
+///
+/// let stats = ScoreStats::compute(&[[1.0, -2.0], [3.0, 0.5]]);
+/// assert_eq!(stats.min(), [1.0, -2.0]);
+/// assert_eq!(stats.max(), [3.0, 0.5]);
+/// assert_eq!(stats.mean(), [2.0, -0.75]);
+/// assert_eq!(stats.std(), [1.0, 1.25]);
+/// assert_eq!(stats.best(), [1.0, 0.5]);
+/// ```
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScoreStats<const N: usize> {
+  len: usize,
+  #[cfg_attr(feature = "serde", serde(with = "super::serde_scores"))]
+  min: Scores<N>,
+  #[cfg_attr(feature = "serde", serde(with = "super::serde_scores"))]
+  max: Scores<N>,
+  #[cfg_attr(feature = "serde", serde(with = "super::serde_scores"))]
+  mean: Scores<N>,
+  #[cfg_attr(feature = "serde", serde(with = "super::serde_scores"))]
+  std: Scores<N>,
+  #[cfg_attr(feature = "serde", serde(with = "super::serde_scores"))]
+  best: Scores<N>,
+}
+
+impl<const N: usize> ScoreStats<N> {
+  /// Computes statistics of `scores`.
+  pub fn compute(scores: &[Scores<N>]) -> Self {
+    let mut counts = [0_usize; N];
+    let mut min = [Score::NAN; N];
+    let mut max = [Score::NAN; N];
+    let mut best = [Score::NAN; N];
+    let mut mean = [0.0_f64; N];
+    let mut m2 = [0.0_f64; N];
+    for sc in scores {
+      for (o_idx, &s) in sc.iter().enumerate() {
+        if s.is_nan() {
+          continue;
+        }
+        counts[o_idx] += 1;
+        // NaN is replaced by the first score
+        min[o_idx] = min[o_idx].min(s);
+        max[o_idx] = max[o_idx].max(s);
+        if best[o_idx].is_nan() || s.abs() < best[o_idx].abs() {
+          best[o_idx] = s;
+        }
+        let s = f64::from(s);
+        let delta = s - mean[o_idx];
+        mean[o_idx] += delta / counts[o_idx] as f64;
+        m2[o_idx] += delta * (s - mean[o_idx]);
+      }
+    }
+    let stat = |values: [f64; N]| {
+      std::array::from_fn(|o_idx| match counts[o_idx] {
+        0 => Score::NAN,
+        _ => values[o_idx] as Score,
+      })
+    };
+    Self {
+      len: scores.len(),
+      min,
+      max,
+      mean: stat(mean),
+      std: stat(std::array::from_fn(|o_idx| {
+        (m2[o_idx] / counts[o_idx].max(1) as f64).sqrt()
+      })),
+      best,
+    }
+  }
+
+  /// Returns the number of scores the statistics were computed of, including
+  /// NaNs.
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  /// Returns `true` if the statistics were computed of no scores.
+  pub fn is_empty(&self) -> bool {
+    self.len == 0
+  }
+
+  /// Returns the minimum of each objective's scores.
+  pub fn min(&self) -> Scores<N> {
+    self.min
+  }
+
+  /// Returns the maximum of each objective's scores.
+  pub fn max(&self) -> Scores<N> {
+    self.max
+  }
+
+  /// Returns the mean of each objective's scores.
+  pub fn mean(&self) -> Scores<N> {
+    self.mean
+  }
+
+  /// Returns the population standard deviation of each objective's scores.
+  pub fn std(&self) -> Scores<N> {
+    self.std
+  }
+
+  /// Returns the best score of each objective, i.e. the score closest to `0`.
+  pub fn best(&self) -> Scores<N> {
+    self.best
+  }
+}
+
+impl<const N: usize> Display for ScoreStats<N> {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for o_idx in 0..N {
+      if o_idx > 0 {
+        writeln!(f)?;
+      }
+      write!(
+        f,
+        "objective {o_idx}: min {}, mean {}, max {}, std {}, best {}",
+        self.min[o_idx],
+        self.mean[o_idx],
+        self.max[o_idx],
+        self.std[o_idx],
+        self.best[o_idx],
+      )?;
+    }
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_score_stats() {
+    let scores = [[2.0], [4.0], [4.0], [4.0], [5.0], [5.0], [7.0], [9.0]];
+    let stats = ScoreStats::compute(&scores);
+    assert_eq!(stats.len(), 8);
+    assert_eq!(stats.min(), [2.0]);
+    assert_eq!(stats.max(), [9.0]);
+    assert_eq!(stats.mean(), [5.0]);
+    assert_eq!(stats.std(), [2.0]);
+    assert_eq!(stats.best(), [2.0]);
+  }
+
+  #[test]
+  fn test_score_stats_empty() {
+    let stats = ScoreStats::<2>::compute(&[]);
+    assert!(stats.is_empty());
+    let all = [stats.min(), stats.max(), stats.mean(), stats.std()];
+    for stat in all.into_iter().chain([stats.best()]) {
+      assert!(stat.iter().all(|s| s.is_nan()));
+    }
+  }
+
+  #[test]
+  fn test_score_stats_single() {
+    let stats = ScoreStats::compute(&[[-1.0, 2.0]]);
+    assert_eq!(stats.len(), 1);
+    assert_eq!(stats.min(), [-1.0, 2.0]);
+    assert_eq!(stats.max(), [-1.0, 2.0]);
+    assert_eq!(stats.mean(), [-1.0, 2.0]);
+    assert_eq!(stats.std(), [0.0, 0.0]);
+    assert_eq!(stats.best(), [-1.0, 2.0]);
+  }
+
+  #[test]
+  fn test_score_stats_nan() {
+    let nan = f32::NAN;
+    let stats = ScoreStats::compute(&[[nan, nan], [1.0, nan], [3.0, nan]]);
+    assert_eq!(stats.len(), 3);
+    assert_eq!(stats.min()[0], 1.0);
+    assert_eq!(stats.mean()[0], 2.0);
+    assert_eq!(stats.best()[0], 1.0);
+    assert!(stats.mean()[1].is_nan());
+    assert!(stats.std()[1].is_nan());
+  }
+
+  #[test]
+  fn test_score_stats_display() {
+    let stats = ScoreStats::compute(&[[1.0, -2.0], [3.0, 0.5]]);
+    assert_eq!(
+      stats.to_string(),
+      "objective 0: min 1, mean 2, max 3, std 1, best 1\n\
+       objective 1: min -2, mean -0.75, max 0.5, std 1.25, best 0.5"
+    );
+  }
+}
@@ -0,0 +1,140 @@
+//! Transformations of scores.
+
+use super::{Score, ScoreQuantizer, Scores};
+
+/// A transformation of scores of an objective that optimizers apply before
+/// comparing them, see the `score_transform` setters of [`Nsga2`] and
+/// [`Spea2`]. Optimizers still keep and report the original scores.
+///
+/// Scores of an objective that spans many orders of magnitude, say from
+/// `1e-9` to `1e3`, make crowding distances and densities meaningless in
+/// their own units: the few largest scores stretch the extent of the
+/// objective, so that all the others look crowded. Log-scaling spreads them
+/// evenly instead.
+///
+/// Transformations that keep the order of absolute values of scores, like
+/// [`ScoreTransform::Log1p`], don't change which scores dominate which, so
+/// they change only the diversity of survivors. Since scores converge at
+/// `0`, a transformed score should too.
+///
+/// # Examples
+/// ```
+/// use moga::score::ScoreTransform;
+///
+/// let transforms = [ScoreTransform::Identity, ScoreTransform::Log1p];
+/// let scores = ScoreTransform::apply_each(&transforms, &[1e3, -1e3]);
+/// assert_eq!(scores[0], 1e3);
+/// assert!((scores[1] + 1001_f32.ln()).abs() < 1e-5);
+/// let halved = ScoreTransform::Custom(|s| s / 2.0);
+/// assert_eq!(halved.apply(3.0), 1.5);
+/// ```
+///
+/// [`Nsga2`]: crate::optimizer::nsga::Nsga2
+/// [`Spea2`]: crate::optimizer::spea::Spea2
+#[derive(Clone, Copy, Debug, Default)]
+pub enum ScoreTransform {
+  /// Leaves scores as they are.
+  #[default]
+  Identity,
+  /// Replaces a score `s` with `ln(1 + |s|)`, keeping its sign.
+  Log1p,
+  /// Applies the function to each score.
+  Custom(fn(Score) -> Score),
+}
+
+impl ScoreTransform {
+  /// Returns the transformed `score`.
+  pub fn apply(&self, score: Score) -> Score {
+    match self {
+      Self::Identity => score,
+      Self::Log1p => score.abs().ln_1p().copysign(score),
+      Self::Custom(f) => f(score),
+    }
+  }
+
+  /// Returns `scores` with the transformation of each objective applied.
+  pub fn apply_each<const N: usize>(
+    transforms: &[Self; N],
+    scores: &Scores<N>,
+  ) -> Scores<N> {
+    std::array::from_fn(|o| transforms[o].apply(scores[o]))
+  }
+}
+
+/// Returns the view of scores that optimizers compare instead of the original
+/// scores: scores snapped to the grid of the `quantizer`, then transformed
+/// with `transforms`. Returns `None` if there's neither, so the original
+/// scores are compared.
+pub(crate) fn shadow_view<'a, const N: usize>(
+  quantizer: Option<&'a ScoreQuantizer<N>>,
+  transforms: Option<&'a [ScoreTransform; N]>,
+) -> Option<impl Fn(&Scores<N>) -> Scores<N> + 'a> {
+  if quantizer.is_none() && transforms.is_none() {
+    return None;
+  }
+  Some(move |scores: &Scores<N>| {
+    let scores = quantizer.map_or(*scores, |q| q.quantize(scores));
+    transforms.map_or(scores, |t| ScoreTransform::apply_each(t, &scores))
+  })
+}
+
+/// Applies `select` to `solutions` paired with their original scores and to
+/// their scores seen through the `view`. `select` reorders and truncates both
+/// vectors alike, then the survivors are unpaired back into `solutions` and
+/// `scores`, so that they keep the original scores.
+pub(crate) fn select_by<S, const N: usize>(
+  solutions: &mut Vec<S>,
+  scores: &mut Vec<Scores<N>>,
+  view: impl Fn(&Scores<N>) -> Scores<N>,
+  select: impl FnOnce(&mut Vec<(S, Scores<N>)>, &mut Vec<Scores<N>>),
+) {
+  let mut viewed: Vec<_> = scores.iter().map(view).collect();
+  let mut paired: Vec<_> = solutions.drain(..).zip(scores.drain(..)).collect();
+  select(&mut paired, &mut viewed);
+  debug_assert_eq!(paired.len(), viewed.len());
+  (*solutions, *scores) = paired.into_iter().unzip();
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_log1p() {
+    let log1p = ScoreTransform::Log1p;
+    assert_eq!(log1p.apply(0.0), 0.0);
+    assert_eq!(log1p.apply(-1e-9), -1e-9);
+    assert!((log1p.apply(1e3) - 1001_f32.ln()).abs() < 1e-5);
+    assert!(log1p.apply(f32::NAN).is_nan());
+    // the order of absolute values is kept
+    assert!(log1p.apply(-2.0).abs() > log1p.apply(1.0).abs());
+  }
+
+  #[test]
+  fn test_shadow_view() {
+    assert!(shadow_view::<2>(None, None).is_none());
+    let quantizer = ScoreQuantizer::new([1.0, 0.0]);
+    let transforms = [ScoreTransform::Log1p, ScoreTransform::Identity];
+    let view = shadow_view(Some(&quantizer), Some(&transforms)).unwrap();
+    // scores are quantized before they are transformed
+    assert_eq!(view(&[0.9, 0.9]), [2_f32.ln(), 0.9]);
+  }
+
+  #[test]
+  fn test_select_keeps_original_scores() {
+    let quantizer = ScoreQuantizer::new([1.0]);
+    let mut solutions = vec!['a', 'b', 'c'];
+    let mut scores = vec![[0.9], [2.2], [0.2]];
+    let view = |sc: &Scores<1>| quantizer.quantize(sc);
+    select_by(&mut solutions, &mut scores, view, |paired, viewed| {
+      assert_eq!(*viewed, [[1.0], [2.0], [0.0]]);
+      // move the last solution to the front and drop the first one
+      paired.swap(0, 2);
+      viewed.swap(0, 2);
+      paired.truncate(2);
+      viewed.truncate(2);
+    });
+    assert_eq!(solutions, ['c', 'b']);
+    assert_eq!(scores, [[0.2], [2.2]]);
+  }
+}
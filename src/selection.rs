@@ -1,21 +1,22 @@
 //! Selection operators and utilities.
 
+use std::{
+  cmp::Ordering,
+  marker::PhantomData,
+  sync::{Mutex, PoisonError},
+};
+
 use executor::SelectionExecutor;
 use rand::prelude::*;
-use rayon::prelude::*;
 
 use crate::{
   execution::strategy::*,
-  operator::{
-    tag::SelectionOperatorTag,
-    ParBatch,
-    ParBatchOperator,
-    ParEach,
-    ParEachOperator,
-  },
-  score::{ParetoDominance, Scores},
+  score::{asf, sorting::front_ranks, ParetoDominance, Scores},
 };
 
+#[cfg(feature = "parallel")]
+mod parallel;
+
 /// An operator that decides whether a solution will be selected as a parent
 /// for the next generation of solutions or not. Selected solutions' references
 /// are passed into `Recombinator`.
@@ -46,19 +47,6 @@ where
   }
 }
 
-impl<S, const N: usize, L> ParEach<SelectionOperatorTag, S, N, 0> for L
-where
-  S: Sync,
-  L: Selection<S, N> + Sync,
-{
-}
-impl<S, const N: usize, L> ParBatch<SelectionOperatorTag, S, N> for L
-where
-  S: Sync,
-  L: Selection<S, N> + Sync,
-{
-}
-
 /// An operator that selects solutions suitable for recombination into a new
 /// generation of solutions. Selected solutions' references are passed into
 /// `Recombinator`.
@@ -87,6 +75,64 @@ pub trait Selector<S, const N: usize> {
   /// Takes slices of solutions and their respective scores.
   /// Returns a vector of references to selected solutions.
   fn select<'a>(&self, solutions: &'a [S], scores: &[Scores<N>]) -> Vec<&'a S>;
+
+  /// Takes slices of solutions and their respective scores. Returns indices
+  /// of selected solutions. Optimizers select solutions with this method.
+  ///
+  /// By default, indices are recovered from references that [`select`]
+  /// returns. Override this method if your selector knows indices of
+  /// selected solutions anyway, which all selectors of this crate do.
+  ///
+  /// # Panics
+  ///
+  /// The default implementation panics if [`select`] returns a reference to
+  /// a solution outside of `solutions`. Zero-sized solutions are all
+  /// considered to be the first solution.
+  ///
+  /// [`select`]: Selector::select
+  fn select_indices(
+    &self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) -> Vec<usize> {
+    self
+      .select(solutions, scores)
+      .into_iter()
+      .map(|solution| index_of(solutions, solution))
+      .collect()
+  }
+
+  /// Returns the number of solutions the selector promises to select, if it
+  /// promises anything. Optimizers check each selection against it. Returns
+  /// `None` by default, see [`Expect`] to make a promise.
+  fn selection_contract(&self) -> Option<SelectionContract> {
+    None
+  }
+}
+
+/// Returns the index of the `solution` in `solutions` by its address.
+///
+/// # Panics
+///
+/// Panics if the `solution` is not an element of `solutions`.
+fn index_of<S>(solutions: &[S], solution: &S) -> usize {
+  let size = std::mem::size_of::<S>();
+  if size == 0 {
+    return 0;
+  }
+  let offset = (solution as *const S as usize)
+    .checked_sub(solutions.as_ptr() as usize)
+    .filter(|offset| offset % size == 0)
+    .map(|offset| offset / size);
+  match offset {
+    Some(idx) if idx < solutions.len() => idx,
+    _ => panic!("selector selected a solution outside of given solutions"),
+  }
+}
+
+/// Returns references to solutions with given `indices`.
+fn by_indices<S>(solutions: &[S], indices: Vec<usize>) -> Vec<&S> {
+  indices.into_iter().map(|idx| &solutions[idx]).collect()
 }
 
 impl<S, const N: usize, F> Selector<S, N> for F
@@ -98,18 +144,42 @@ where
   }
 }
 
-/// This private module prevents exposing the `Executor` to a user.
+/// Executors are exposed to users only by [`crate::experimental::executor`].
 pub(crate) mod executor {
+  use super::SelectionContract;
   use crate::score::Scores;
 
-  /// An internal selecion executor.
+  /// An executor of selection operators.
   pub trait SelectionExecutor<S, const N: usize, ExecutionStrategy> {
     /// Executes selection optionally parallelizing operator's application.
-    fn execute_selection<'a>(
+    /// Returns indices of selected solutions.
+    fn execute_selection(
+      &self,
+      solutions: &[S],
+      scores: &[Scores<N>],
+    ) -> Vec<usize>;
+
+    /// Executes selection like [`execute_selection`], but returns references
+    /// to selected solutions.
+    ///
+    /// [`execute_selection`]: SelectionExecutor::execute_selection
+    fn execute_selection_refs<'a>(
       &self,
       solutions: &'a [S],
       scores: &[Scores<N>],
-    ) -> Vec<&'a S>;
+    ) -> Vec<&'a S> {
+      self
+        .execute_selection(solutions, scores)
+        .into_iter()
+        .map(|idx| &solutions[idx])
+        .collect()
+    }
+
+    /// Returns the number of solutions the operator promises to select, if
+    /// it promises anything. Optimizers check each selection against it.
+    fn selection_contract(&self) -> Option<SelectionContract> {
+      None
+    }
   }
 }
 
@@ -118,12 +188,16 @@ impl<S, const N: usize, L> SelectionExecutor<S, N, CustomExecutionStrategy>
 where
   L: Selector<S, N>,
 {
-  fn execute_selection<'a>(
+  fn execute_selection(
     &self,
-    solutions: &'a [S],
+    solutions: &[S],
     scores: &[Scores<N>],
-  ) -> Vec<&'a S> {
-    self.select(solutions, scores)
+  ) -> Vec<usize> {
+    self.select_indices(solutions, scores)
+  }
+
+  fn selection_contract(&self) -> Option<SelectionContract> {
+    Selector::selection_contract(self)
   }
 }
 
@@ -132,101 +206,87 @@ impl<S, const N: usize, L> SelectionExecutor<S, N, SequentialExecutionStrategy>
 where
   L: Selection<S, N>,
 {
-  fn execute_selection<'a>(
+  fn execute_selection(
     &self,
-    solutions: &'a [S],
+    solutions: &[S],
     scores: &[Scores<N>],
-  ) -> Vec<&'a S> {
+  ) -> Vec<usize> {
     solutions
       .iter()
       .zip(scores)
-      .filter_map(|(sol, sc)| self.select(sol, sc).then_some(sol))
-      .collect()
-  }
-}
-
-impl<S, const N: usize, L>
-  SelectionExecutor<S, N, ParallelEachExecutionStrategy>
-  for ParEachOperator<SelectionOperatorTag, S, L>
-where
-  S: Sync,
-  L: Selection<S, N> + Sync,
-{
-  fn execute_selection<'a>(
-    &self,
-    solutions: &'a [S],
-    scores: &[Scores<N>],
-  ) -> Vec<&'a S> {
-    solutions
-      .par_iter()
-      .zip(scores)
-      .filter_map(|(sol, sc)| self.operator().select(sol, sc).then_some(sol))
-      .collect()
-  }
-}
-
-impl<S, const N: usize, L>
-  SelectionExecutor<S, N, ParallelBatchExecutionStrategy>
-  for ParBatchOperator<SelectionOperatorTag, S, L>
-where
-  S: Sync,
-  L: Selection<S, N> + Sync,
-{
-  fn execute_selection<'a>(
-    &self,
-    solutions: &'a [S],
-    scores: &[Scores<N>],
-  ) -> Vec<&'a S> {
-    let chunk_size = (solutions.len() / rayon::current_num_threads()).max(1);
-    solutions
-      .chunks(chunk_size)
-      .zip(scores.chunks(chunk_size))
-      .par_bridge()
-      .flat_map_iter(|chunk| {
-        chunk.0.iter().zip(chunk.1).filter_map(|(sol, sc)| {
-          self.operator().select(sol, sc).then_some(sol)
-        })
-      })
+      .enumerate()
+      .filter_map(|(idx, (sol, sc))| self.select(sol, sc).then_some(idx))
       .collect()
   }
 }
 
 /// Selects all solutions. No discrimination whatsoever.
+///
+/// Selects exactly `solutions.len()` distinct solutions.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct AllSelector();
 
 impl<const N: usize, S> Selector<S, N> for AllSelector {
   fn select<'a>(&self, solutions: &'a [S], _: &[Scores<N>]) -> Vec<&'a S> {
     solutions.iter().collect()
   }
+
+  fn select_indices(&self, solutions: &[S], _: &[Scores<N>]) -> Vec<usize> {
+    (0..solutions.len()).collect()
+  }
 }
 
 /// Selects at most `n` first solutions. 'First' doesn't mean the best, this
 /// selector just returns `n` solutions it meets first.
 ///
-/// If `n` is bigger than the number of solutions, this selector selects all
-/// solutions.
+/// Selects exactly `min(n, solutions.len())` distinct solutions, so if `n` is
+/// bigger than the number of solutions, this selector selects all solutions.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct FirstSelector(pub usize);
 
 impl<const N: usize, S> Selector<S, N> for FirstSelector {
   fn select<'a>(&self, solutions: &'a [S], _: &[Scores<N>]) -> Vec<&'a S> {
     solutions.iter().take(self.0).collect()
   }
+
+  fn select_indices(&self, solutions: &[S], _: &[Scores<N>]) -> Vec<usize> {
+    (0..solutions.len().min(self.0)).collect()
+  }
 }
 
 /// Selects at most `n` random solutions.
 ///
-/// If `n` is bigger than the number of solutions, this selector selects all
-/// solutions.
+/// Selects exactly `min(n, solutions.len())` distinct solutions, so if `n` is
+/// bigger than the number of solutions, this selector selects all solutions.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RandomSelector(pub usize);
 
 impl<const N: usize, S> Selector<S, N> for RandomSelector {
-  fn select<'a>(&self, solutions: &'a [S], _: &[Scores<N>]) -> Vec<&'a S> {
-    solutions
-      .choose_multiple(&mut rand::thread_rng(), self.0)
-      .collect()
+  fn select<'a>(&self, solutions: &'a [S], scores: &[Scores<N>]) -> Vec<&'a S> {
+    by_indices(solutions, self.select_indices(solutions, scores))
+  }
+
+  fn select_indices(
+    &self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) -> Vec<usize> {
+    self.select_indices_with(solutions, scores, &mut rand::thread_rng())
+  }
+}
+
+impl<const N: usize, S> SeedableSelector<S, N> for RandomSelector {
+  fn select_indices_with<R: Rng + ?Sized>(
+    &self,
+    solutions: &[S],
+    _: &[Scores<N>],
+    rng: &mut R,
+  ) -> Vec<usize> {
+    rand::seq::index::sample(rng, solutions.len(), self.0.min(solutions.len()))
+      .into_vec()
   }
 }
 
@@ -234,22 +294,38 @@ impl<const N: usize, S> Selector<S, N> for RandomSelector {
 /// dominations. The chance of choosing a solution is directly proportional to
 /// the number of solutions it dominates.
 ///
-/// If `n` is bigger than the number of solutions, this selector selects all
-/// solutions.
+/// Selects exactly `min(n, solutions.len())` distinct solutions, so if `n` is
+/// bigger than the number of solutions, this selector selects all solutions.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct RouletteSelector(pub usize);
 
 impl<const N: usize, S> Selector<S, N> for RouletteSelector {
   fn select<'a>(&self, solutions: &'a [S], scores: &[Scores<N>]) -> Vec<&'a S> {
-    let mut sol_dominations = solutions
-      .iter()
-      .map(|sol| (sol, 0_usize))
+    by_indices(solutions, self.select_indices(solutions, scores))
+  }
+
+  fn select_indices(
+    &self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) -> Vec<usize> {
+    self.select_indices_with(solutions, scores, &mut rand::thread_rng())
+  }
+}
+
+impl<const N: usize, S> SeedableSelector<S, N> for RouletteSelector {
+  fn select_indices_with<R: Rng + ?Sized>(
+    &self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+    rng: &mut R,
+  ) -> Vec<usize> {
+    let mut sol_dominations = (0..solutions.len())
+      .map(|idx| (idx, 0_usize))
       .collect::<Vec<_>>();
-    for p_idx in 0..scores.len() {
-      let (p_sc, rest_scs) =
-        scores[p_idx..].split_first().expect("no scores remain");
-      for (i, q_sc) in rest_scs.iter().enumerate() {
-        let q_idx = p_idx + i + 1;
+    for (p_idx, p_sc) in scores.iter().enumerate() {
+      for (q_idx, q_sc) in scores.iter().enumerate().skip(p_idx + 1) {
         match p_sc.dominance(q_sc) {
           std::cmp::Ordering::Less => sol_dominations[p_idx].1 += 1,
           std::cmp::Ordering::Greater => sol_dominations[q_idx].1 += 1,
@@ -258,50 +334,111 @@ impl<const N: usize, S> Selector<S, N> for RouletteSelector {
       }
     }
     sol_dominations
-      .choose_multiple_weighted(&mut rand::thread_rng(), self.0, |sol_dom| {
-        sol_dom.1 as f64
-      })
+      .choose_multiple_weighted(rng, self.0, |sol_dom| sol_dom.1 as f64)
       .expect("bad weight was encountered during roulette selection")
       .map(|sol_dom| sol_dom.0)
       .collect::<Vec<_>>()
   }
 }
 
+/// Selects at most `n` random solutions with chances decaying across Pareto
+/// fronts. Solutions are sorted into fronts first, and each solution of the
+/// front with rank `r`, starting from `0`, is weighted by `1 / (r + 1)`. So
+/// the chance is the same for all solutions of a front, unlike
+/// [`RouletteSelector`], which favors solutions that dominate many others and
+/// may starve the rest of the first front. If all solutions are
+/// nondominated, the selection is uniform.
+///
+/// Selects exactly `min(n, solutions.len())` distinct solutions, so if `n` is
+/// bigger than the number of solutions, this selector selects all solutions.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RankRouletteSelector(pub usize);
+
+impl<const N: usize, S> Selector<S, N> for RankRouletteSelector {
+  fn select<'a>(&self, solutions: &'a [S], scores: &[Scores<N>]) -> Vec<&'a S> {
+    by_indices(solutions, self.select_indices(solutions, scores))
+  }
+
+  fn select_indices(
+    &self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) -> Vec<usize> {
+    self.select_indices_with(solutions, scores, &mut rand::thread_rng())
+  }
+}
+
+impl<const N: usize, S> SeedableSelector<S, N> for RankRouletteSelector {
+  fn select_indices_with<R: Rng + ?Sized>(
+    &self,
+    _: &[S],
+    scores: &[Scores<N>],
+    rng: &mut R,
+  ) -> Vec<usize> {
+    let ranks = front_ranks(scores);
+    let indices: Vec<usize> = (0..scores.len()).collect();
+    indices
+      .choose_multiple_weighted(rng, self.0, |&idx| {
+        1.0 / (ranks[idx] + 1) as f64
+      })
+      .expect("bad weight was encountered during roulette selection")
+      .copied()
+      .collect()
+  }
+}
+
 /// Selects at most `n` solutions from random chunks of *unique* solutions of
 /// size `k`. Each solution can be selected only once.
 ///
 /// From each chunk, the least dominated solution is selected. If there are
-/// multiple equally dominated solutions, a random one is selected.
-/// This selector selects one solution per a chunk, so it may select less than
-/// `n` solutions. If you want to select all solutions this selector can proide,
+/// multiple equally dominated solutions, a random one is selected, see
+/// [`TournamentSelector`] for ties.
+/// This selector selects one solution per a chunk, so it selects exactly
+/// `min(n, solutions.len().div_ceil(k))` distinct solutions, which may be less
+/// than `n`. If you want to select all solutions this selector can provide,
 /// set this value to `usize::MAX`.
 ///
 /// # Panics
 ///
 /// Will cause panic in runtime if `k` is 0.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TournamentSelectorWithoutReplacement(pub usize, pub usize);
 
 impl<const N: usize, S> Selector<S, N>
   for TournamentSelectorWithoutReplacement
 {
   fn select<'a>(&self, solutions: &'a [S], scores: &[Scores<N>]) -> Vec<&'a S> {
-    rand::seq::index::sample(
-      &mut rand::thread_rng(),
-      solutions.len(),
+    by_indices(solutions, self.select_indices(solutions, scores))
+  }
+
+  fn select_indices(
+    &self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) -> Vec<usize> {
+    self.select_indices_with(solutions, scores, &mut rand::thread_rng())
+  }
+}
+
+impl<const N: usize, S> SeedableSelector<S, N>
+  for TournamentSelectorWithoutReplacement
+{
+  fn select_indices_with<R: Rng + ?Sized>(
+    &self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+    rng: &mut R,
+  ) -> Vec<usize> {
+    tournaments_without_replacement(
+      self.0,
+      self.1,
       solutions.len(),
+      |a, b| a.dominance(b),
+      scores,
+      rng,
     )
-    .into_vec()
-    .chunks(self.1)
-    .take(self.0)
-    .map(|chunk| {
-      chunk
-        .iter()
-        .min_by(|i, j| scores[**i].dominance(&scores[**j]))
-        .map(|idx| &solutions[*idx])
-        .expect("chunk must not be empty")
-    })
-    .collect()
   }
 }
 
@@ -309,37 +446,620 @@ impl<const N: usize, S> Selector<S, N>
 /// Each solution can be selected multiple times.
 ///
 /// From each chunk, the least dominated solution is selected. If there are
-/// multiple equally dominated solutions, a random one is selected. If `k` is
+/// multiple equally dominated solutions, a random one is selected, see
+/// [`TournamentSelector`] for ties. If `k` is
 /// bigger than the number of solutions, all solutions will form a single chunk
 /// from which `n` solutions will be selected.
 ///
+/// Selects exactly `n` solutions, which may repeat.
+///
 /// # Panics
 ///
 /// Will cause panic in runtime if `k` is 0.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct TournamentSelectorWithReplacement(pub usize, pub usize);
 
 impl<const N: usize, S> Selector<S, N> for TournamentSelectorWithReplacement {
   fn select<'a>(&self, solutions: &'a [S], scores: &[Scores<N>]) -> Vec<&'a S> {
-    (0..self.0)
-      .map(|_| {
-        rand::seq::index::sample(
-          &mut rand::thread_rng(),
-          solutions.len(),
-          self.1.min(solutions.len()),
-        )
-        .iter()
-        .min_by(|i, j| scores[*i].dominance(&scores[*j]))
-        .map(|idx| &solutions[idx])
+    by_indices(solutions, self.select_indices(solutions, scores))
+  }
+
+  fn select_indices(
+    &self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) -> Vec<usize> {
+    self.select_indices_with(solutions, scores, &mut rand::thread_rng())
+  }
+}
+
+impl<const N: usize, S> SeedableSelector<S, N>
+  for TournamentSelectorWithReplacement
+{
+  fn select_indices_with<R: Rng + ?Sized>(
+    &self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+    rng: &mut R,
+  ) -> Vec<usize> {
+    tournaments_with_replacement(
+      self.0,
+      self.1,
+      solutions.len(),
+      |a, b| a.dominance(b),
+      scores,
+      rng,
+    )
+  }
+}
+
+/// Selects solutions by tournaments, like [`TournamentSelectorWithReplacement`]
+/// and [`TournamentSelectorWithoutReplacement`] do, but compares scores of
+/// candidates with a custom comparator instead of Pareto dominance, such as
+/// a comparison of scalarized scores, constrained dominance or epsilon
+/// dominance. The comparator returns `Less` if the first scores win, and
+/// `Equal` if neither scores win.
+///
+/// Each tournament is won by a candidate that no other candidate beats. Ties
+/// are broken randomly: all such candidates win equally often, even if the
+/// comparator isn't transitive, as pareto dominance isn't.
+///
+/// Selects as many solutions as the tournament selector with the same
+/// replacement does.
+///
+/// # Panics
+///
+/// Will cause panic in runtime if `k` is 0, or if there are no solutions to
+/// select with replacement from.
+///
+/// # Examples
+/// ```
+/// use moga::selection::TournamentSelector;
+///
+/// // tournaments of 3 solutions on the sum of scores
+/// let selector =
+///   TournamentSelector::with_comparator(10, 3, |a: &[f32; 2], b: &[f32; 2]| {
+///     (a[0].abs() + a[1].abs()).total_cmp(&(b[0].abs() + b[1].abs()))
+///   });
+/// let selector = selector.without_replacement();
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct TournamentSelector<C> {
+  n: usize,
+  k: usize,
+  cmp: C,
+  replacement: bool,
+}
+
+impl<C> TournamentSelector<C> {
+  /// Creates a selector that selects `n` solutions from tournaments of `k`
+  /// random solutions each, comparing them with `cmp`. Each solution can be
+  /// selected multiple times, as with [`TournamentSelectorWithReplacement`].
+  pub fn with_comparator(n: usize, k: usize, cmp: C) -> Self {
+    Self {
+      n,
+      k,
+      cmp,
+      replacement: true,
+    }
+  }
+
+  /// Makes the selector select at most `n` solutions from random chunks of
+  /// unique solutions, as [`TournamentSelectorWithoutReplacement`] does, so
+  /// each solution can be selected only once.
+  pub fn without_replacement(self) -> Self {
+    Self {
+      replacement: false,
+      ..self
+    }
+  }
+
+  /// Wraps the selector into a [`Selector`] that draws random numbers from
+  /// `rng`. See [`SeededSelector`].
+  pub fn with_rng<R: RngCore>(self, rng: R) -> SeededSelector<Self, R> {
+    SeededSelector::new(self, rng)
+  }
+}
+
+impl<const N: usize, S, C> Selector<S, N> for TournamentSelector<C>
+where
+  C: Fn(&Scores<N>, &Scores<N>) -> Ordering + Sync,
+{
+  fn select<'a>(&self, solutions: &'a [S], scores: &[Scores<N>]) -> Vec<&'a S> {
+    by_indices(solutions, self.select_indices(solutions, scores))
+  }
+
+  fn select_indices(
+    &self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) -> Vec<usize> {
+    self.select_indices_with(solutions, scores, &mut rand::thread_rng())
+  }
+}
+
+impl<const N: usize, S, C> SeedableSelector<S, N> for TournamentSelector<C>
+where
+  C: Fn(&Scores<N>, &Scores<N>) -> Ordering + Sync,
+{
+  fn select_indices_with<R: Rng + ?Sized>(
+    &self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+    rng: &mut R,
+  ) -> Vec<usize> {
+    if self.replacement {
+      tournaments_with_replacement(
+        self.n,
+        self.k,
+        solutions.len(),
+        &self.cmp,
+        scores,
+        rng,
+      )
+    } else {
+      tournaments_without_replacement(
+        self.n,
+        self.k,
+        solutions.len(),
+        &self.cmp,
+        scores,
+        rng,
+      )
+    }
+  }
+}
+
+/// Returns indices of winners of `n` tournaments of `k` random solutions out
+/// of `len` solutions each.
+fn tournaments_with_replacement<const N: usize, R: Rng + ?Sized>(
+  n: usize,
+  k: usize,
+  len: usize,
+  cmp: impl Fn(&Scores<N>, &Scores<N>) -> Ordering,
+  scores: &[Scores<N>],
+  rng: &mut R,
+) -> Vec<usize> {
+  (0..n)
+    .map(|_| {
+      let candidates = rand::seq::index::sample(rng, len, k.min(len));
+      tournament_winner(&candidates.into_vec(), &cmp, scores, rng)
         .expect("chunk must not be empty")
-      })
-      .collect()
+    })
+    .collect()
+}
+
+/// Returns indices of winners of at most `n` tournaments of random chunks of
+/// `k` unique solutions out of `len` solutions.
+fn tournaments_without_replacement<const N: usize, R: Rng + ?Sized>(
+  n: usize,
+  k: usize,
+  len: usize,
+  cmp: impl Fn(&Scores<N>, &Scores<N>) -> Ordering,
+  scores: &[Scores<N>],
+  rng: &mut R,
+) -> Vec<usize> {
+  let order = rand::seq::index::sample(rng, len, len).into_vec();
+  order
+    .chunks(k)
+    .take(n)
+    .map(|chunk| {
+      tournament_winner(chunk, &cmp, scores, rng)
+        .expect("chunk must not be empty")
+    })
+    .collect()
+}
+
+/// Returns the index of the candidate that wins a tournament by `cmp` of
+/// their scores: one of the candidates that no other candidate beats, picked
+/// uniformly at random. Unlike the best candidate by a running comparison,
+/// it's never beaten, even if `cmp` isn't transitive, as pareto dominance
+/// isn't. If every candidate is beaten, as `cmp` may be cyclic, any of them
+/// wins. Returns `None` only if there are no candidates.
+fn tournament_winner<const N: usize, R: Rng + ?Sized>(
+  candidates: &[usize],
+  cmp: impl Fn(&Scores<N>, &Scores<N>) -> Ordering,
+  scores: &[Scores<N>],
+  rng: &mut R,
+) -> Option<usize> {
+  // tournaments are small, so candidates are compared pairwise twice
+  // instead of collecting unbeaten ones
+  let is_unbeaten = |&idx: &usize| {
+    !candidates
+      .iter()
+      .any(|&other| cmp(&scores[other], &scores[idx]) == Ordering::Less)
+  };
+  let unbeaten = candidates.iter().filter(|idx| is_unbeaten(idx)).count();
+  if unbeaten == 0 {
+    return candidates.choose(rng).copied();
+  }
+  let pick = rng.gen_range(0..unbeaten);
+  candidates.iter().copied().filter(is_unbeaten).nth(pick)
+}
+
+/// Selects at most `n` solutions that achieve the `reference` point the best,
+/// ranking them by [`asf`] with given `weights`. Use it to steer the search
+/// towards the region of the front you are interested in.
+///
+/// Selects exactly `min(n, solutions.len())` distinct solutions, so if `n` is
+/// bigger than the number of solutions, this selector selects all solutions.
+///
+/// # Panics
+///
+/// Will cause panic in runtime if any weight is negative.
+///
+/// # Examples
+/// ```
+/// use moga::selection::ReferencePointSelector;
+///
+/// // selects 10 solutions closest to scores of `[1.0, 2.0]`, where the first
+/// // objective is twice as important as the second one
+/// let selector = ReferencePointSelector(10, [1.0, 2.0], [2.0, 1.0]);
+/// ```
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReferencePointSelector<const N: usize>(
+  pub usize,
+  #[cfg_attr(feature = "serde", serde(with = "crate::score::serde_scores"))]
+  pub Scores<N>,
+  #[cfg_attr(feature = "serde", serde(with = "crate::score::serde_scores"))]
+  pub Scores<N>,
+);
+
+impl<const N: usize, S> Selector<S, N> for ReferencePointSelector<N> {
+  fn select<'a>(&self, solutions: &'a [S], scores: &[Scores<N>]) -> Vec<&'a S> {
+    by_indices(solutions, self.select_indices(solutions, scores))
+  }
+
+  fn select_indices(
+    &self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) -> Vec<usize> {
+    let values = scores
+      .iter()
+      .map(|sc| asf(sc, &self.1, &self.2))
+      .collect::<Vec<_>>();
+    let mut indices = (0..solutions.len()).collect::<Vec<_>>();
+    // NaN values are sorted away
+    indices.sort_by(|&a, &b| {
+      values[a]
+        .partial_cmp(&values[b])
+        .unwrap_or_else(|| values[a].is_nan().cmp(&values[b].is_nan()))
+    });
+    indices.truncate(self.0);
+    indices
+  }
+}
+
+/// A [`Selector`] that draws random numbers from a given generator. Built-in
+/// random selectors implement it, drawing from `thread_rng()` as
+/// [`Selector`]s. Wrap them with their `with_rng()` method to draw from a
+/// seeded generator instead, such as a stream of [`SplitRng`].
+///
+/// [`SplitRng`]: crate::rng::SplitRng
+pub trait SeedableSelector<S, const N: usize> {
+  /// Returns indices of selected solutions, drawing random numbers from
+  /// `rng`.
+  fn select_indices_with<R: Rng + ?Sized>(
+    &self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+    rng: &mut R,
+  ) -> Vec<usize>;
+}
+
+/// A [`Selector`] out of a [`SeedableSelector`] that draws random numbers
+/// from its own generator. Created with `with_rng()` method of built-in
+/// random selectors or with [`SeededSelector::new`].
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   rng::SplitRng,
+///   selection::{RandomSelector, Selector},
+/// };
+///
+/// let master = SplitRng::new(0);
+/// let a = RandomSelector(2).with_rng(master.stream("selection"));
+/// let b = RandomSelector(2).with_rng(master.stream("selection"));
+/// let scores = [[0.0], [1.0], [2.0], [3.0]];
+/// assert_eq!(
+///   a.select_indices(&[0, 1, 2, 3], &scores),
+///   b.select_indices(&[0, 1, 2, 3], &scores)
+/// );
+/// ```
+#[derive(Debug)]
+pub struct SeededSelector<T, R> {
+  selector: T,
+  rng: Mutex<R>,
+}
+
+impl<T, R> SeededSelector<T, R> {
+  /// Wraps the `selector` into a [`Selector`] that draws random numbers from
+  /// `rng`.
+  pub fn new(selector: T, rng: R) -> Self {
+    Self {
+      selector,
+      rng: Mutex::new(rng),
+    }
+  }
+
+  /// Returns the wrapped selector.
+  pub fn selector(&self) -> &T {
+    &self.selector
+  }
+}
+
+impl<S, const N: usize, T, R> Selector<S, N> for SeededSelector<T, R>
+where
+  T: SeedableSelector<S, N>,
+  R: RngCore,
+{
+  fn select<'a>(&self, solutions: &'a [S], scores: &[Scores<N>]) -> Vec<&'a S> {
+    by_indices(solutions, self.select_indices(solutions, scores))
+  }
+
+  fn select_indices(
+    &self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) -> Vec<usize> {
+    let mut rng = self.rng.lock().unwrap_or_else(PoisonError::into_inner);
+    self
+      .selector
+      .select_indices_with(solutions, scores, &mut *rng)
+  }
+}
+
+/// Implements `with_rng()` for [`SeedableSelector`]s.
+macro_rules! impl_with_rng {
+  ($($selector:ty),* $(,)?) => {$(
+    impl $selector {
+      /// Wraps the selector into a [`Selector`] that draws random numbers
+      /// from `rng`. See [`SeededSelector`].
+      pub fn with_rng<R: RngCore>(self, rng: R) -> SeededSelector<Self, R> {
+        SeededSelector::new(self, rng)
+      }
+    }
+  )*};
+}
+
+impl_with_rng!(
+  RandomSelector,
+  RouletteSelector,
+  RankRouletteSelector,
+  TournamentSelectorWithoutReplacement,
+  TournamentSelectorWithReplacement,
+);
+
+/// A number of solutions a selection operator is expected to select. See
+/// [`Expect`].
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SelectionContract {
+  /// Exactly `n` solutions.
+  Exactly(usize),
+  /// At most `n` solutions.
+  AtMost(usize),
+  /// At least `n` solutions.
+  AtLeast(usize),
+}
+
+impl SelectionContract {
+  /// Returns `true` if selecting `selected` solutions fulfills the contract.
+  pub fn is_fulfilled_by(&self, selected: usize) -> bool {
+    match *self {
+      Self::Exactly(n) => selected == n,
+      Self::AtMost(n) => selected <= n,
+      Self::AtLeast(n) => selected >= n,
+    }
+  }
+}
+
+impl std::fmt::Display for SelectionContract {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      Self::Exactly(n) => write!(f, "exactly {n}"),
+      Self::AtMost(n) => write!(f, "at most {n}"),
+      Self::AtLeast(n) => write!(f, "at least {n}"),
+    }
+  }
+}
+
+/// A selection operator, parallelized or not, with a [`SelectionContract`]
+/// on the number of solutions it selects. If the operator breaks the
+/// contract, optimization fails with
+/// [`OptimizationError::SelectionContractViolated`] instead of going on
+/// with an unexpected number of parents.
+///
+/// The contract is checked before an empty selection is handled, so
+/// [`EmptySelection::SelectAll`] doesn't save an operator that must select
+/// at least one solution.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   optimizer::{nsga::Nsga2, OptimizationError, Optimizer},
+///   selection::{Expect, SelectionContract},
+///   termination::GenerationTerminator,
+/// };
+///
+/// // a closure that selects solutions better than some threshold
+/// let selector = Expect::at_least(|_: &f32, sc: &[f32; 2]| sc[0] < 1.0, 2);
+/// let result = Nsga2::builder()
+///   .population(vec![3.0, 4.0, 5.0])
+///   .tester(|x: &f32| [x.powf(2.0), (x - 2.0).powf(2.0)])
+///   .selector(selector)
+///   .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+///   .mutator(|_: &mut f32| {})
+///   .terminator(GenerationTerminator(10))
+///   .build()
+///   .try_optimize();
+/// assert_eq!(
+///   result,
+///   Err(OptimizationError::SelectionContractViolated {
+///     generation: 0,
+///     expected: SelectionContract::AtLeast(2),
+///     actual: 0,
+///   })
+/// );
+/// ```
+///
+/// [`OptimizationError::SelectionContractViolated`]:
+///   crate::optimizer::OptimizationError::SelectionContractViolated
+/// [`EmptySelection::SelectAll`]: crate::optimizer::EmptySelection::SelectAll
+pub struct Expect<L, ES> {
+  selector: L,
+  contract: SelectionContract,
+  _strategy: PhantomData<fn() -> ES>,
+}
+
+impl<L, ES> Expect<L, ES> {
+  /// Expects the `selector` to select exactly `n` solutions.
+  pub fn exactly(selector: L, n: usize) -> Self {
+    Self::new(selector, SelectionContract::Exactly(n))
+  }
+
+  /// Expects the `selector` to select at most `n` solutions.
+  pub fn at_most(selector: L, n: usize) -> Self {
+    Self::new(selector, SelectionContract::AtMost(n))
+  }
+
+  /// Expects the `selector` to select at least `n` solutions.
+  pub fn at_least(selector: L, n: usize) -> Self {
+    Self::new(selector, SelectionContract::AtLeast(n))
+  }
+
+  /// Expects the `selector` to fulfill the `contract`.
+  pub fn new(selector: L, contract: SelectionContract) -> Self {
+    Self {
+      selector,
+      contract,
+      _strategy: PhantomData,
+    }
+  }
+
+  /// Returns the wrapped selector.
+  pub fn selector(&self) -> &L {
+    &self.selector
+  }
+
+  /// Returns the contract of the selector.
+  pub fn contract(&self) -> SelectionContract {
+    self.contract
+  }
+}
+
+impl<S, const N: usize, L, ES> Selector<S, N> for Expect<L, ES>
+where
+  L: SelectionExecutor<S, N, ES>,
+{
+  fn select<'a>(&self, solutions: &'a [S], scores: &[Scores<N>]) -> Vec<&'a S> {
+    by_indices(solutions, self.select_indices(solutions, scores))
+  }
+
+  fn select_indices(
+    &self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) -> Vec<usize> {
+    self.selector.execute_selection(solutions, scores)
+  }
+
+  fn selection_contract(&self) -> Option<SelectionContract> {
+    Some(self.contract)
+  }
+}
+
+impl<L: Clone, ES> Clone for Expect<L, ES> {
+  fn clone(&self) -> Self {
+    Self {
+      selector: self.selector.clone(),
+      contract: self.contract,
+      _strategy: PhantomData,
+    }
+  }
+}
+
+impl<L: std::fmt::Debug, ES> std::fmt::Debug for Expect<L, ES> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Expect")
+      .field("selector", &self.selector)
+      .field("contract", &self.contract)
+      .finish()
+  }
+}
+
+/// A boxed [`Selector`] that any selection operator, parallelized or not,
+/// can be converted into. Unlike operators, boxed selectors have the same
+/// type, so the type of an optimizer with a selector chosen at runtime can be
+/// named.
+///
+/// # Examples
+/// ```
+/// # use moga::selection::{BoxedSelector, FirstSelector, RandomSelector};
+/// let random = true;
+/// let s: BoxedSelector<f32, 2> = if random {
+///   BoxedSelector::new(RandomSelector(10))
+/// } else {
+///   BoxedSelector::new(FirstSelector(10))
+/// };
+/// ```
+pub struct BoxedSelector<S, const N: usize>(
+  Box<SelectorFn<S, N>>,
+  Option<SelectionContract>,
+);
+
+/// A selector closure that returns indices of selected solutions.
+type SelectorFn<S, const N: usize> =
+  dyn Fn(&[S], &[Scores<N>]) -> Vec<usize> + Send;
+
+impl<S: 'static, const N: usize> BoxedSelector<S, N> {
+  /// Boxes the `selector`.
+  pub fn new<ExecStrat: 'static>(
+    selector: impl SelectionExecutor<S, N, ExecStrat> + Send + 'static,
+  ) -> Self {
+    let contract = selector.selection_contract();
+    Self(
+      Box::new(move |solutions: &[S], scores: &[Scores<N>]| {
+        selector.execute_selection(solutions, scores)
+      }),
+      contract,
+    )
+  }
+}
+
+impl<S, const N: usize> Selector<S, N> for BoxedSelector<S, N> {
+  fn select<'a>(&self, solutions: &'a [S], scores: &[Scores<N>]) -> Vec<&'a S> {
+    by_indices(solutions, self.select_indices(solutions, scores))
+  }
+
+  fn select_indices(
+    &self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) -> Vec<usize> {
+    (self.0)(solutions, scores)
+  }
+
+  fn selection_contract(&self) -> Option<SelectionContract> {
+    self.1
+  }
+}
+
+impl<S, const N: usize> std::fmt::Debug for BoxedSelector<S, N> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("BoxedSelector")
   }
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
+  #[cfg(feature = "parallel")]
+  use crate::operator::{ParAuto, ParBatch, ParEach};
 
   type Solution = f32;
 
@@ -358,11 +1078,40 @@ mod tests {
   fn test_selection_from_closure() {
     let selection = |_: &Solution, _: &Scores<2>| true;
     takes_selector(&selection);
+    #[cfg(feature = "parallel")]
     takes_selector(&selection.par_each());
+    #[cfg(feature = "parallel")]
     takes_selector(&selection.par_batch());
+    #[cfg(feature = "parallel")]
+    takes_selector(&selection.par_auto());
     takes_selector_empty(&selection);
   }
 
+  #[test]
+  fn test_seeded_selectors() {
+    use crate::rng::SplitRng;
+
+    fn assert_repeats<T: SeedableSelector<Solution, 2> + Copy>(selector: T) {
+      let solutions: Vec<_> = (0..50).map(|i| i as Solution).collect();
+      let scores: Vec<_> = solutions.iter().map(|&x| [x, x + 1.0]).collect();
+      let select = |seed| {
+        let selector =
+          SeededSelector::new(selector, SplitRng::new(seed).stream("s"));
+        (0..5)
+          .map(|_| selector.select_indices(&solutions, &scores))
+          .collect::<Vec<_>>()
+      };
+      assert_eq!(select(0), select(0));
+      assert_ne!(select(0), select(1));
+    }
+
+    assert_repeats(RandomSelector(10));
+    assert_repeats(RouletteSelector(10));
+    assert_repeats(RankRouletteSelector(10));
+    assert_repeats(TournamentSelectorWithoutReplacement(10, 3));
+    assert_repeats(TournamentSelectorWithReplacement(10, 3));
+  }
+
   #[test]
   fn test_selector_from_fn() {
     fn selector<'a>(
@@ -399,7 +1148,9 @@ mod tests {
 
     let selection = CustomSelection {};
     takes_selector(&selection);
+    #[cfg(feature = "parallel")]
     takes_selector(&selection.par_each());
+    #[cfg(feature = "parallel")]
     takes_selector(&selection.par_batch());
     takes_selector_empty(&selection);
   }
@@ -447,6 +1198,58 @@ mod tests {
     takes_selector_empty(&selector);
   }
 
+  #[test]
+  fn test_rank_roulette_selector() {
+    let selector = RankRouletteSelector(10);
+    takes_selector(&selector);
+    takes_selector_empty(&selector);
+
+    // three fronts of two solutions each
+    let solutions: Vec<Solution> = (0..6).map(|i| i as Solution).collect();
+    let scores = [
+      [0.0, 2.0],
+      [2.0, 0.0],
+      [1.0, 3.0],
+      [3.0, 1.0],
+      [2.0, 4.0],
+      [4.0, 2.0],
+    ];
+    let mut rng = SmallRng::seed_from_u64(0);
+    let trials = 30_000;
+    let mut counts = [0; 6];
+    for _ in 0..trials {
+      let selected = RankRouletteSelector(1)
+        .select_indices_with(&solutions, &scores, &mut rng);
+      counts[selected[0]] += 1;
+    }
+    let frequency = |count: usize| count as f64 / trials as f64;
+    // weights are 1, 1/2 and 1/3, so each front is chosen in 6, 3 and 2
+    // cases out of 11, and its solutions are chosen equally often
+    for (front, expected) in
+      [6.0 / 11.0, 3.0 / 11.0, 2.0 / 11.0].into_iter().enumerate()
+    {
+      let (a, b) = (counts[2 * front], counts[2 * front + 1]);
+      assert!((frequency(a + b) - expected).abs() < 0.015, "{counts:?}");
+      assert!((frequency(a) - frequency(b)).abs() < 0.02, "{counts:?}");
+    }
+
+    // a single front is selected uniformly
+    let scores = [[0.0, 3.0], [1.0, 2.0], [2.0, 1.0], [3.0, 0.0]];
+    let mut counts = [0; 4];
+    for _ in 0..trials {
+      for idx in RankRouletteSelector(2).select_indices_with(
+        &solutions[..4],
+        &scores,
+        &mut rng,
+      ) {
+        counts[idx] += 1;
+      }
+    }
+    for count in counts {
+      assert!((frequency(count) - 0.5).abs() < 0.02, "{counts:?}");
+    }
+  }
+
   #[test]
   fn test_tournament_selector_with_replacement() {
     let selector = TournamentSelectorWithReplacement(10, 10);
@@ -466,4 +1269,258 @@ mod tests {
     takes_selector(&selector);
     takes_selector_empty(&selector);
   }
+
+  #[test]
+  fn test_tournament_selector_with_comparator() {
+    let by_first = |a: &Scores<2>, b: &Scores<2>| a[0].total_cmp(&b[0]);
+    let selector = TournamentSelector::with_comparator(10, 10, by_first);
+    takes_selector(&selector);
+    let selector = selector.without_replacement();
+    takes_selector(&selector);
+    takes_selector_empty(&selector);
+
+    let solutions = [0, 1, 2, 3];
+    let scores = [[3.0, 0.0], [1.0, 5.0], [2.0, 1.0], [4.0, 4.0]];
+    // the whole population takes part in a tournament
+    let selector = TournamentSelector::with_comparator(3, 4, by_first);
+    assert_eq!(selector.select_indices(&solutions, &scores), [1, 1, 1]);
+    let selector = selector.without_replacement();
+    assert_eq!(selector.select_indices(&solutions, &scores), [1]);
+  }
+
+  #[test]
+  fn test_tournament_with_cyclic_comparator() {
+    // rock, paper and scissors each beat one another
+    let beats =
+      |a: &Scores<1>, b: &Scores<1>| match (a[0] - b[0]).rem_euclid(3.0) {
+        1.0 => Ordering::Less,
+        2.0 => Ordering::Greater,
+        _ => Ordering::Equal,
+      };
+    let selector = TournamentSelector::with_comparator(4, 3, beats);
+    let selected = selector.select_indices(&[0, 1, 2], &[[0.0], [1.0], [2.0]]);
+    assert_eq!(selected.len(), 4);
+  }
+
+  #[test]
+  fn test_tournament_ties_are_random() {
+    let solutions = [0, 1, 2, 3];
+    let scores = [[1.0, 1.0]; 4];
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut wins = [0; 4];
+    for _ in 0..2000 {
+      for idx in TournamentSelectorWithoutReplacement(1, 4)
+        .select_indices_with(&solutions, &scores, &mut rng)
+      {
+        wins[idx] += 1;
+      }
+    }
+    assert!(wins.iter().all(|&w| w > 400), "{wins:?}");
+  }
+
+  #[test]
+  fn test_tournament_winner_is_unbeaten() {
+    // `[1, 3]` and `[2, 1]` are incomparable, as are `[1, 3]` and `[3, 2]`,
+    // but `[2, 1]` dominates `[3, 2]`
+    let solutions = [0, 1, 2];
+    let scores = [[1.0, 3.0], [2.0, 1.0], [3.0, 2.0]];
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut wins = [0; 3];
+    for _ in 0..600 {
+      for idx in TournamentSelectorWithoutReplacement(1, 3)
+        .select_indices_with(&solutions, &scores, &mut rng)
+      {
+        wins[idx] += 1;
+      }
+    }
+    assert_eq!(wins[2], 0, "{wins:?}");
+    assert!(wins[0] > 200 && wins[1] > 200, "{wins:?}");
+  }
+
+  #[test]
+  fn test_reference_point_selector() {
+    let selector = ReferencePointSelector(10, [1.0, 1.0], [1.0, 1.0]);
+    takes_selector(&selector);
+    takes_selector_empty(&selector);
+
+    let solutions = [0, 1, 2, 3, 4];
+    let scores = [[0.0, 4.0], [1.0, 2.0], [2.0, 1.0], [4.0, 0.0], [2.0, 2.0]];
+    // solutions closest to the reference point come first
+    let selector = ReferencePointSelector(2, [2.0, 1.0], [1.0, 1.0]);
+    assert_eq!(selector.select(&solutions, &scores), [&2, &1]);
+    // weights steer selection towards the more important objective
+    let selector = ReferencePointSelector(1, [1.0, 1.0], [10.0, 1.0]);
+    assert_eq!(selector.select(&solutions, &scores), [&1]);
+    // dominated and unreachable reference points still rank solutions
+    let selector = ReferencePointSelector(1, [5.0, 4.0], [1.0, 1.0]);
+    assert_eq!(selector.select(&solutions, &scores), [&2]);
+    let selector = ReferencePointSelector(1, [-1.0, 0.0], [1.0, 1.0]);
+    assert_eq!(selector.select(&solutions, &scores), [&2]);
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn test_selectors_serde_round_trip() {
+    fn round_trip<T>(value: T)
+    where
+      T: serde::Serialize
+        + serde::de::DeserializeOwned
+        + PartialEq
+        + std::fmt::Debug,
+    {
+      let json = serde_json::to_string(&value).unwrap();
+      assert_eq!(serde_json::from_str::<T>(&json).unwrap(), value);
+    }
+
+    round_trip(AllSelector());
+    round_trip(FirstSelector(1));
+    round_trip(RandomSelector(2));
+    round_trip(RouletteSelector(3));
+    round_trip(TournamentSelectorWithoutReplacement(4, 5));
+    round_trip(TournamentSelectorWithReplacement(6, 7));
+    round_trip(ReferencePointSelector(8, [1.0, 2.0], [3.0, 4.0]));
+    assert_eq!(serde_json::to_string(&RouletteSelector(3)).unwrap(), "3");
+  }
+
+  #[test]
+  fn test_select_indices() {
+    let solutions = [1.0, 2.0, 3.0, 4.0];
+    let scores = [[4.0, 0.0], [3.0, 1.0], [2.0, 2.0], [1.0, 3.0]];
+    fn assert_indices<L: Selector<Solution, 2>>(
+      selector: L,
+      solutions: &[Solution],
+      scores: &[Scores<2>],
+    ) {
+      for idx in selector.select_indices(solutions, scores) {
+        assert!(idx < solutions.len());
+      }
+      for selected in selector.select(solutions, scores) {
+        assert!(solutions.contains(selected));
+      }
+    }
+    assert_indices(AllSelector(), &solutions, &scores);
+    assert_indices(FirstSelector(2), &solutions, &scores);
+    assert_indices(RandomSelector(5), &solutions, &scores);
+    assert_indices(RouletteSelector(2), &solutions, &scores);
+    assert_indices(
+      TournamentSelectorWithReplacement(5, 2),
+      &solutions,
+      &scores,
+    );
+    assert_indices(
+      TournamentSelectorWithoutReplacement(5, 2),
+      &solutions,
+      &scores,
+    );
+    assert_indices(
+      ReferencePointSelector(2, [2.0, 2.0], [1.0, 1.0]),
+      &solutions,
+      &scores,
+    );
+    assert_eq!(FirstSelector(2).select_indices(&solutions, &scores), [0, 1]);
+    assert_eq!(
+      ReferencePointSelector(1, [2.0, 2.0], [1.0, 1.0])
+        .select_indices(&solutions, &scores),
+      [2]
+    );
+    // indices of parallelized selections
+    let selection = |x: &Solution, _: &Scores<2>| *x > 2.0;
+    assert_eq!(selection.execute_selection(&solutions, &scores), [2, 3]);
+    #[cfg(feature = "parallel")]
+    {
+      let par_each = selection.par_each();
+      assert_eq!(par_each.execute_selection(&solutions, &scores), [2, 3]);
+      let par_batch = selection.par_batch();
+      assert_eq!(par_batch.execute_selection(&solutions, &scores), [2, 3]);
+    }
+  }
+
+  #[test]
+  fn test_default_select_indices() {
+    // selects every second solution, twice
+    struct EverySecond;
+    impl<S> Selector<S, 2> for EverySecond {
+      fn select<'a>(&self, solutions: &'a [S], _: &[Scores<2>]) -> Vec<&'a S> {
+        solutions
+          .iter()
+          .step_by(2)
+          .chain(solutions.iter().step_by(2))
+          .collect()
+      }
+    }
+    let scores = [[0.0, 0.0]; 5];
+    assert_eq!(EverySecond.select_indices(&[1, 2, 3, 4, 5], &scores), [
+      0, 2, 4, 0, 2, 4
+    ]);
+    assert_eq!(EverySecond.select_indices(&[(); 5], &scores), [0; 6]);
+    assert_eq!(
+      EverySecond.execute_selection_refs(&[1, 2, 3], &scores[..3]),
+      [&1, &3, &1, &3]
+    );
+  }
+
+  #[test]
+  #[should_panic(expected = "outside of given solutions")]
+  fn test_default_select_indices_outside() {
+    struct Outsider;
+    impl Selector<u8, 2> for Outsider {
+      fn select<'a>(&self, _: &'a [u8], _: &[Scores<2>]) -> Vec<&'a u8> {
+        vec![&0]
+      }
+    }
+    Outsider.select_indices(&[1, 2], &[[0.0, 0.0]; 2]);
+  }
+
+  #[test]
+  fn test_boxed_selector() {
+    let selector = BoxedSelector::new(FirstSelector(2));
+    takes_selector(&selector);
+    let selected = selector.execute_selection(
+      &[1.0, 2.0, 3.0], //
+      &[[1.0, 2.0], [3.0, 4.0], [5.0, 6.0]],
+    );
+    assert_eq!(selected, [0, 1]);
+    let selector = BoxedSelector::new(|_: &Solution, s: &Scores<2>| s[0] > 2.0);
+    takes_selector(&selector);
+  }
+
+  #[test]
+  fn test_expect() {
+    let solutions = [1.0, 2.0, 3.0];
+    let scores = [[1.0, 2.0], [3.0, 4.0], [5.0, 6.0]];
+    let selector = Expect::at_most(FirstSelector(2), 2);
+    takes_selector(&selector);
+    assert_eq!(selector.execute_selection(&solutions, &scores), [0, 1]);
+    assert_eq!(
+      Selector::<Solution, 2>::selection_contract(&selector),
+      Some(SelectionContract::AtMost(2))
+    );
+    let selector = Expect::exactly(|_: &Solution, s: &Scores<2>| s[0] > 2.0, 1);
+    takes_selector(&selector);
+    let contract = Selector::selection_contract(&selector).unwrap();
+    let selected = selector.execute_selection(&solutions, &scores);
+    assert!(!contract.is_fulfilled_by(selected.len()));
+    assert!(SelectionContract::AtLeast(1).is_fulfilled_by(selected.len()));
+    // boxed selectors keep contracts of boxed operators
+    let selector = BoxedSelector::new(selector);
+    assert_eq!(Selector::selection_contract(&selector), Some(contract));
+    let selector = BoxedSelector::<Solution, 2>::new(FirstSelector(2));
+    assert_eq!(Selector::selection_contract(&selector), None);
+  }
+
+  #[test]
+  #[cfg(feature = "parallel")]
+  fn test_par_batch_selects_in_order() {
+    let selection =
+      (|x: &Solution, _: &Scores<1>| (*x as usize).is_multiple_of(3))
+        .par_batch();
+    crate::execution::for_uneven_batches(|len| {
+      let solutions: Vec<_> = (0..len).map(|i| i as Solution).collect();
+      let scores = vec![[0.0]; len];
+      assert_eq!(
+        selection.execute_selection(&solutions, &scores),
+        (0..len).step_by(3).collect::<Vec<_>>()
+      );
+    });
+  }
 }
@@ -0,0 +1,145 @@
+//! Parallel execution of selection operators.
+
+use rayon::prelude::*;
+
+use super::{executor::SelectionExecutor, Selection};
+use crate::{
+  execution::{batch_size, strategy::*},
+  operator::{
+    tag::SelectionOperatorTag,
+    AutoDecision,
+    ParAuto,
+    ParAutoOperator,
+    ParBatch,
+    ParBatchOperator,
+    ParEach,
+    ParEachOperator,
+  },
+  score::Scores,
+};
+
+impl<S, const N: usize, L> ParEach<SelectionOperatorTag, S, N, 0> for L
+where
+  S: Sync,
+  L: Selection<S, N> + Sync,
+{
+}
+
+impl<S, const N: usize, L> ParBatch<SelectionOperatorTag, S, N> for L
+where
+  S: Sync,
+  L: Selection<S, N> + Sync,
+{
+}
+
+impl<S, const N: usize, L> ParAuto<SelectionOperatorTag, S, N> for L
+where
+  S: Sync,
+  L: Selection<S, N> + Sync,
+{
+}
+
+impl<S, const N: usize, L>
+  SelectionExecutor<S, N, ParallelEachExecutionStrategy>
+  for ParEachOperator<SelectionOperatorTag, S, L>
+where
+  S: Sync,
+  L: Selection<S, N> + Sync,
+{
+  fn execute_selection(
+    &self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) -> Vec<usize> {
+    solutions
+      .par_iter()
+      .zip(scores)
+      .enumerate()
+      .filter_map(|(idx, (sol, sc))| {
+        self.operator().select(sol, sc).then_some(idx)
+      })
+      .collect()
+  }
+}
+
+impl<S, const N: usize, L>
+  SelectionExecutor<S, N, ParallelBatchExecutionStrategy>
+  for ParBatchOperator<SelectionOperatorTag, S, L>
+where
+  S: Sync,
+  L: Selection<S, N> + Sync,
+{
+  fn execute_selection(
+    &self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) -> Vec<usize> {
+    let chunk_size = batch_size(solutions.len());
+    solutions
+      .par_chunks(chunk_size)
+      .zip(scores.par_chunks(chunk_size))
+      .enumerate()
+      .flat_map_iter(|(chunk_idx, chunk)| {
+        let offset = chunk_idx * chunk_size;
+        chunk.0.iter().zip(chunk.1).enumerate().filter_map(
+          move |(idx, (sol, sc))| {
+            self.operator().select(sol, sc).then_some(offset + idx)
+          },
+        )
+      })
+      .collect()
+  }
+}
+
+impl<S, const N: usize, L> SelectionExecutor<S, N, AutoExecutionStrategy>
+  for ParAutoOperator<SelectionOperatorTag, S, L>
+where
+  S: Sync,
+  L: Selection<S, N> + Sync,
+{
+  fn execute_selection(
+    &self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) -> Vec<usize> {
+    let select = |offset, solutions, scores| {
+      select_from(self.operator(), offset, solutions, scores)
+    };
+    let mut selected = Vec::new();
+    let (checked, decision) = self.plan(solutions.len(), |sample| {
+      selected.extend(select(0, &solutions[..sample], &scores[..sample]))
+    });
+    let (rest, rest_scores) = (&solutions[checked..], &scores[checked..]);
+    match decision {
+      AutoDecision::Sequential => {
+        selected.extend(select(checked, rest, rest_scores))
+      }
+      AutoDecision::Batch => {
+        let chunk_size = batch_size(rest.len());
+        selected.par_extend(
+          rest
+            .par_chunks(chunk_size)
+            .zip(rest_scores.par_chunks(chunk_size))
+            .enumerate()
+            .flat_map_iter(|(chunk_idx, chunk)| {
+              select(checked + chunk_idx * chunk_size, chunk.0, chunk.1)
+            }),
+        )
+      }
+    }
+    selected
+  }
+}
+
+/// Returns indices of solutions that the `selection` selects, offset by the
+/// `offset`.
+fn select_from<'a, S, const N: usize, L: Selection<S, N>>(
+  selection: &'a L,
+  offset: usize,
+  solutions: &'a [S],
+  scores: &'a [Scores<N>],
+) -> impl Iterator<Item = usize> + 'a {
+  solutions.iter().zip(scores).enumerate().filter_map(
+    move |(idx, (sol, sc))| selection.select(sol, sc).then_some(offset + idx),
+  )
+}
@@ -0,0 +1,290 @@
+//! Partitioning of the population into species for multi-modal problems.
+//!
+//! With a global selection, the best solutions of a single region of the
+//! decision space take over the population, even if the problem has several
+//! equally good regions. Optimizers built with a [`SpeciesAssigner`], see the
+//! `speciation` setters of [`Nsga2`] and [`Spea2`], partition the population
+//! into species of similar solutions each generation instead, and select and
+//! recombine parents within each species, the way NEAT maintains diversity.
+//!
+//! Each species gets a share of offsprings of the generation, its quota,
+//! inversely proportional to the mean rank of its solutions, where the rank
+//! of a solution is the number of its front, starting from `0`. So a species
+//! of nondominated solutions gets more offsprings than a dominated one, but
+//! no species is left without offsprings only because it's small.
+//!
+//! [`Nsga2`]: crate::optimizer::nsga::Nsga2
+//! [`Spea2`]: crate::optimizer::spea::Spea2
+
+use std::ops::Range;
+
+use crate::{
+  optimizer::{apply_permutation, to_index, OffspringBudget},
+  score::{sorting::front_ranks, Scores},
+  truncation::DiversityMeasure,
+};
+
+/// An operator that partitions solutions into species.
+///
+/// # Examples
+/// ```
+/// use moga::speciation::SpeciesAssigner;
+///
+/// // negative and non-negative numbers
+/// let by_sign = |solutions: &[f32]| {
+///   let (negative, rest) =
+///     (0..solutions.len()).partition(|&i| solutions[i] < 0.0);
+///   vec![negative, rest]
+/// };
+/// assert_eq!(by_sign.assign(&[1.0, -2.0, 3.0]), [vec![1], vec![0, 2]]);
+/// ```
+///
+/// **Note that you always can implement this trait instead of using closures.**
+pub trait SpeciesAssigner<S> {
+  /// Returns indices of `solutions` of each species. Each solution must
+  /// belong to exactly one species. Empty species are ignored.
+  fn assign(&self, solutions: &[S]) -> Vec<Vec<usize>>;
+}
+
+impl<S, F> SpeciesAssigner<S> for F
+where
+  F: Fn(&[S]) -> Vec<Vec<usize>>,
+{
+  fn assign(&self, solutions: &[S]) -> Vec<Vec<usize>> {
+    self(solutions)
+  }
+}
+
+impl<S> std::fmt::Debug for dyn SpeciesAssigner<S> + Send {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("SpeciesAssigner")
+  }
+}
+
+/// A [`SpeciesAssigner`] that clusters solutions by a distance threshold in
+/// decision space, as measured by the `measure`. Solutions are assigned in
+/// order: a solution joins the first species whose first solution, its
+/// representative, is closer to it than the `threshold`, or founds a new
+/// species otherwise. Species are formed anew each generation.
+///
+/// Assigning `n` solutions to `k` species takes `O(n * k)` distances.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   speciation::{SpeciesAssigner, ThresholdSpeciation},
+///   truncation::Euclidean,
+/// };
+///
+/// let s = ThresholdSpeciation::new(Euclidean, 1.0);
+/// let solutions = vec![vec![0.0], vec![5.0], vec![0.5], vec![6.5]];
+/// assert_eq!(s.assign(&solutions), [vec![0, 2], vec![1], vec![3]]);
+/// ```
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ThresholdSpeciation<D> {
+  /// The measure of distance between solutions.
+  pub measure: D,
+  /// The distance to a representative below which a solution joins its
+  /// species.
+  pub threshold: f64,
+}
+
+impl<D> ThresholdSpeciation<D> {
+  /// Creates a new `ThresholdSpeciation` with the distance `measure` and the
+  /// `threshold`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if the `threshold` is negative or NaN.
+  pub fn new(measure: D, threshold: f64) -> Self {
+    if threshold.is_nan() || threshold < 0.0 {
+      panic!("threshold must not be negative")
+    }
+    Self { measure, threshold }
+  }
+}
+
+impl<S, D> SpeciesAssigner<S> for ThresholdSpeciation<D>
+where
+  D: DiversityMeasure<S>,
+{
+  fn assign(&self, solutions: &[S]) -> Vec<Vec<usize>> {
+    let mut species: Vec<Vec<usize>> = Vec::new();
+    for (idx, solution) in solutions.iter().enumerate() {
+      let joined = species.iter_mut().find(|members| {
+        let representative = &solutions[members[0]];
+        self.measure.distance(representative, solution) < self.threshold
+      });
+      match joined {
+        Some(members) => members.push(idx),
+        None => species.push(vec![idx]),
+      }
+    }
+    species
+  }
+}
+
+/// A species of the population of a generation: its solutions occupy the
+/// `range` of the population, and it may create up to `quota` offsprings.
+#[derive(Clone, PartialEq, Debug)]
+pub(crate) struct Species {
+  pub(crate) range: Range<usize>,
+  pub(crate) quota: usize,
+}
+
+/// Returns the budget that recombination of parents of a species is subject
+/// to. Its quota is met by sampling combinations of parents with the seed of
+/// the optimizer's `budget`, or with seed `0` if the budget is
+/// [`OffspringBudget::Fail`], since a quota isn't an error to exceed.
+pub(crate) fn quota_budget(budget: OffspringBudget) -> OffspringBudget {
+  match budget {
+    OffspringBudget::Fail => OffspringBudget::Subsample { seed: 0 },
+    subsample @ OffspringBudget::Subsample { .. } => subsample,
+  }
+}
+
+/// Partitions `solutions` into species with the `assigner` and reorders them
+/// along with their `scores`, so that solutions of each species are
+/// contiguous. Returns the species in order, with quotas of `offsprings`
+/// split between them.
+///
+/// # Panics
+///
+/// Panics if the species don't partition the solutions.
+pub(crate) fn speciate<S, const N: usize>(
+  assigner: &(impl SpeciesAssigner<S> + ?Sized),
+  solutions: &mut [S],
+  scores: &mut [Scores<N>],
+  offsprings: usize,
+) -> Vec<Species> {
+  let mut assigned = assigner.assign(solutions);
+  assigned.retain(|members| !members.is_empty());
+  let mut is_assigned = vec![false; solutions.len()];
+  for &idx in assigned.iter().flatten() {
+    assert!(
+      idx < solutions.len() && !is_assigned[idx],
+      "species must partition the population"
+    );
+    is_assigned[idx] = true;
+  }
+  assert!(
+    is_assigned.into_iter().all(|is_assigned| is_assigned),
+    "species must partition the population"
+  );
+
+  let ranks = front_ranks(scores);
+  let weights: Vec<_> = assigned
+    .iter()
+    .map(|members| {
+      let rank_sum: usize = members.iter().map(|&idx| ranks[idx]).sum();
+      let mean_rank = rank_sum as f64 / members.len() as f64;
+      1.0 / (mean_rank + 1.0)
+    })
+    .collect();
+  let quotas = apportion(&weights, offsprings);
+
+  let mut permutation: Vec<_> = assigned
+    .iter()
+    .flatten()
+    .map(|&idx| to_index(idx))
+    .collect();
+  apply_permutation(&mut permutation, solutions, scores);
+  let mut start = 0;
+  assigned
+    .iter()
+    .zip(quotas)
+    .map(|(members, quota)| {
+      let range = start..start + members.len();
+      start = range.end;
+      Species { range, quota }
+    })
+    .collect()
+}
+
+/// Splits `total` into whole shares proportional to `weights` by the largest
+/// remainder method, so that shares add up to `total`.
+fn apportion(weights: &[f64], total: usize) -> Vec<usize> {
+  let weight_sum: f64 = weights.iter().sum();
+  if weights.is_empty() || weight_sum <= 0.0 {
+    return vec![0; weights.len()];
+  }
+  let exact: Vec<_> = weights
+    .iter()
+    .map(|w| w / weight_sum * total as f64)
+    .collect();
+  let mut shares: Vec<_> = exact.iter().map(|e| e.floor() as usize).collect();
+  let left = total.saturating_sub(shares.iter().sum());
+  let mut by_remainder: Vec<_> = (0..weights.len()).collect();
+  by_remainder.sort_by(|&a, &b| {
+    (exact[b] - exact[b].floor()).total_cmp(&(exact[a] - exact[a].floor()))
+  });
+  by_remainder
+    .into_iter()
+    .cycle()
+    .take(left)
+    .for_each(|idx| shares[idx] += 1);
+  shares
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::truncation::Euclidean;
+
+  #[test]
+  fn test_threshold_speciation() {
+    let s = ThresholdSpeciation::new(Euclidean, 1.0);
+    assert!(s.assign(&[] as &[Vec<f32>]).is_empty());
+    // the second solution is close to the third one, but not to the first
+    // one, which is the representative of its species
+    let solutions = vec![vec![0.0], vec![0.9], vec![1.5]];
+    assert_eq!(s.assign(&solutions), [vec![0, 1], vec![2]]);
+    let s = ThresholdSpeciation::new(Euclidean, 0.0);
+    assert_eq!(s.assign(&solutions), [vec![0], vec![1], vec![2]]);
+  }
+
+  #[test]
+  fn test_apportion() {
+    assert_eq!(apportion(&[1.0, 1.0, 2.0], 8), [2, 2, 4]);
+    assert_eq!(apportion(&[1.0, 1.0, 1.0], 10), [4, 3, 3]);
+    assert_eq!(apportion(&[1.0, 0.5], 0), [0, 0]);
+    assert!(apportion(&[], 10).is_empty());
+    assert_eq!(
+      apportion(&[1.0, 0.5, 1.0 / 3.0], 100).iter().sum::<usize>(),
+      100
+    );
+  }
+
+  #[test]
+  fn test_speciate() {
+    let mut solutions = vec![0.0, 10.0, 0.5, 10.5];
+    // the species around `0` is nondominated, the other one is dominated
+    let mut scores = vec![[0.0, 1.0], [2.0, 2.0], [1.0, 0.0], [3.0, 3.0]];
+    let by_magnitude = |solutions: &[f64]| {
+      let (small, large) =
+        (0..solutions.len()).partition(|&i| solutions[i] < 5.0);
+      vec![large, vec![], small]
+    };
+    let species = speciate(&by_magnitude, &mut solutions, &mut scores, 9);
+    assert_eq!(solutions, [10.0, 10.5, 0.0, 0.5]);
+    assert_eq!(scores, [[2.0, 2.0], [3.0, 3.0], [0.0, 1.0], [1.0, 0.0]]);
+    // mean ranks are `1.5` and `0`
+    assert_eq!(species, [
+      Species {
+        range: 0..2,
+        quota: 3
+      },
+      Species {
+        range: 2..4,
+        quota: 6
+      },
+    ]);
+  }
+
+  #[test]
+  #[should_panic(expected = "species must partition the population")]
+  fn test_speciate_overlapping_species() {
+    let overlapping = |_: &[f32]| vec![vec![0, 1], vec![1]];
+    speciate(&overlapping, &mut [0.0, 1.0], &mut [[0.0], [1.0]], 2);
+  }
+}
@@ -1,20 +1,77 @@
 //! Termination operators and utilities.
 
+use std::{
+  borrow::Cow,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+  },
+};
+
 use executor::TerminationExecutor;
-use rayon::prelude::*;
 
 use crate::{
   execution::strategy::*,
-  operator::{
-    tag::TerminationOperatorTag,
-    ParBatch,
-    ParBatchOperator,
-    ParEach,
-    ParEachOperator,
-  },
-  score::Scores,
+  score::{Score, ScoreStats, Scores},
 };
 
+#[cfg(feature = "parallel")]
+mod parallel;
+
+/// A decision of a termination operator on whether the algorithm should be
+/// terminated, and why. Operators that return `bool` are adapted to it, see
+/// [`Terminator::decide`] and [`Termination::decide`].
+///
+/// # Examples
+/// ```
+/// # use moga::termination::TerminationDecision;
+/// let decision = TerminationDecision::stop("the front has converged");
+/// assert!(decision.is_stop());
+/// assert_eq!(decision.reason(), Some("the front has converged"));
+/// assert_eq!(
+///   TerminationDecision::from(false),
+///   TerminationDecision::Continue
+/// );
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub enum TerminationDecision {
+  /// The algorithm continues.
+  Continue,
+  /// The algorithm is terminated for the given reason.
+  Stop(Cow<'static, str>),
+}
+
+impl TerminationDecision {
+  /// Creates a decision to terminate the algorithm for the `reason`.
+  pub fn stop(reason: impl Into<Cow<'static, str>>) -> Self {
+    Self::Stop(reason.into())
+  }
+
+  /// Returns `true` if the algorithm is terminated.
+  pub fn is_stop(&self) -> bool {
+    matches!(self, Self::Stop(_))
+  }
+
+  /// Returns the reason to terminate the algorithm, if it's terminated.
+  pub fn reason(&self) -> Option<&str> {
+    match self {
+      Self::Continue => None,
+      Self::Stop(reason) => Some(reason),
+    }
+  }
+}
+
+impl From<bool> for TerminationDecision {
+  /// Converts `true` into a decision to terminate the algorithm for a generic
+  /// reason.
+  fn from(terminate: bool) -> Self {
+    match terminate {
+      true => Self::stop("the terminator has met its condition"),
+      false => Self::Continue,
+    }
+  }
+}
+
 /// An operator that for each given solution decides whether the algorithm
 /// should be terminated or not.
 ///
@@ -33,6 +90,18 @@ use crate::{
 pub trait Termination<S, const N: usize> {
   /// If returns `true`, the algorithm is terminated.
   fn terminate(&self, solution: &S, scores: &Scores<N>) -> bool;
+
+  /// Decides whether the algorithm is terminated, and why. Defaults to
+  /// [`terminate`](Termination::terminate) with a generic reason. Override it
+  /// to report a specific one.
+  fn decide(&self, solution: &S, scores: &Scores<N>) -> TerminationDecision {
+    match self.terminate(solution, scores) {
+      true => TerminationDecision::stop(
+        "a solution has met the termination condition",
+      ),
+      false => TerminationDecision::Continue,
+    }
+  }
 }
 
 impl<S, const N: usize, F> Termination<S, N> for F
@@ -44,20 +113,6 @@ where
   }
 }
 
-impl<S, const N: usize, T> ParEach<TerminationOperatorTag, S, N, 0> for T
-where
-  S: Sync,
-  T: Termination<S, N> + Sync,
-{
-}
-
-impl<S, const N: usize, T> ParBatch<TerminationOperatorTag, S, N> for T
-where
-  S: Sync,
-  T: Termination<S, N> + Sync,
-{
-}
-
 /// An operator that terminates the algorithm based on some termination
 /// condition.
 ///
@@ -70,6 +125,20 @@ where
 pub trait Terminator<S, const N: usize> {
   /// If returns `true`, the algorithm is terminated.
   fn terminate(&mut self, solutions: &[S], scores: &[Scores<N>]) -> bool;
+
+  /// Decides whether the algorithm is terminated, and why. This is what
+  /// optimizers call. Defaults to [`terminate`](Terminator::terminate) with a
+  /// generic reason. Override it to report a specific one, the reason ends up
+  /// in [`OptimizationOutcome::reason`].
+  ///
+  /// [`OptimizationOutcome::reason`]: crate::optimizer::OptimizationOutcome::reason
+  fn decide(
+    &mut self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) -> TerminationDecision {
+    self.terminate(solutions, scores).into()
+  }
 }
 
 impl<S, const N: usize, F> Terminator<S, N> for F
@@ -81,11 +150,12 @@ where
   }
 }
 
-/// This private module prevents exposing the `Executor` to a user.
+/// Executors are exposed to users only by [`crate::experimental::executor`].
 pub(crate) mod executor {
+  use super::TerminationDecision;
   use crate::score::Scores;
 
-  /// An internal termination executor.
+  /// An executor of termination operators.
   pub trait TerminationExecutor<S, const N: usize, ExecutionStrategy> {
     /// Executes termination evaluation optionally parallelizing operator's
     /// application.
@@ -93,7 +163,7 @@ pub(crate) mod executor {
       &mut self,
       solutions: &[S],
       scores: &[Scores<N>],
-    ) -> bool;
+    ) -> TerminationDecision;
   }
 }
 
@@ -106,8 +176,8 @@ where
     &mut self,
     solutions: &[S],
     scores: &[Scores<N>],
-  ) -> bool {
-    self.terminate(solutions, scores)
+  ) -> TerminationDecision {
+    self.decide(solutions, scores)
   }
 }
 
@@ -120,80 +190,230 @@ where
     &mut self,
     solutions: &[S],
     scores: &[Scores<N>],
-  ) -> bool {
+  ) -> TerminationDecision {
     solutions
       .iter()
       .zip(scores)
-      .any(|(sol, sc)| self.terminate(sol, sc))
-  }
-}
-
-impl<S, const N: usize, T>
-  TerminationExecutor<S, N, ParallelEachExecutionStrategy>
-  for ParEachOperator<TerminationOperatorTag, S, T>
-where
-  S: Sync,
-  T: Termination<S, N> + Sync,
-{
-  fn execute_termination(
-    &mut self,
-    solutions: &[S],
-    scores: &[Scores<N>],
-  ) -> bool {
-    solutions
-      .par_iter()
-      .zip(scores)
-      .any(|(sol, sc)| self.operator().terminate(sol, sc))
-  }
-}
-
-impl<S, const N: usize, T>
-  TerminationExecutor<S, N, ParallelBatchExecutionStrategy>
-  for ParBatchOperator<TerminationOperatorTag, S, T>
-where
-  S: Sync,
-  T: Termination<S, N> + Sync,
-{
-  fn execute_termination(
-    &mut self,
-    solutions: &[S],
-    scores: &[Scores<N>],
-  ) -> bool {
-    let chunk_size = (solutions.len() / rayon::current_num_threads()).max(1);
-    solutions
-      .chunks(chunk_size)
-      .zip(scores.chunks(chunk_size))
-      .par_bridge()
-      .any(|chunk| {
-        chunk
-          .0
-          .iter()
-          .zip(chunk.1)
-          .any(|(sol, sc)| self.operator().terminate(sol, sc))
-      })
+      .map(|(sol, sc)| self.decide(sol, sc))
+      .find(TerminationDecision::is_stop)
+      .unwrap_or(TerminationDecision::Continue)
   }
 }
 
 /// A `Terminator` that terminates the algorithm as soon as a certain number of
 /// generations have passed.
 #[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GenerationTerminator(pub usize);
 
 impl<S, const N: usize> Terminator<S, N> for GenerationTerminator {
-  fn terminate(&mut self, _: &[S], _: &[Scores<N>]) -> bool {
+  fn terminate(&mut self, solutions: &[S], scores: &[Scores<N>]) -> bool {
+    self.decide(solutions, scores).is_stop()
+  }
+
+  fn decide(&mut self, _: &[S], _: &[Scores<N>]) -> TerminationDecision {
     match self.0 {
-      0 => true,
+      0 => TerminationDecision::stop("all generations have passed"),
       _ => {
         self.0 -= 1;
-        false
+        TerminationDecision::Continue
       }
     }
   }
 }
 
+/// A `Terminator` that terminates the algorithm once its flag is raised. Clones
+/// share the flag, so a clone kept by another thread can stop optimization
+/// running on this one.
+///
+/// # Examples
+/// ```
+/// # use moga::termination::FlagTerminator;
+/// let terminator = FlagTerminator::new();
+/// let flag = terminator.clone();
+/// std::thread::spawn(move || flag.raise()).join().unwrap();
+/// assert!(terminator.is_raised());
+/// ```
+#[derive(Clone, Default, Debug)]
+pub struct FlagTerminator(Arc<AtomicBool>);
+
+impl FlagTerminator {
+  /// Creates a `FlagTerminator` with a lowered flag.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Raises the flag, so that the algorithm is terminated at the start of its
+  /// next generation.
+  pub fn raise(&self) {
+    self.0.store(true, Ordering::Relaxed);
+  }
+
+  /// Returns `true` if the flag is raised.
+  pub fn is_raised(&self) -> bool {
+    self.0.load(Ordering::Relaxed)
+  }
+}
+
+impl<S, const N: usize> Terminator<S, N> for FlagTerminator {
+  fn terminate(&mut self, _: &[S], _: &[Scores<N>]) -> bool {
+    self.is_raised()
+  }
+
+  fn decide(&mut self, _: &[S], _: &[Scores<N>]) -> TerminationDecision {
+    match self.is_raised() {
+      true => TerminationDecision::stop("the flag has been raised"),
+      false => TerminationDecision::Continue,
+    }
+  }
+}
+
+/// A `Terminator` that terminates the algorithm once the bounding box of
+/// scores, i.e. the minimum and the maximum score of each objective, hasn't
+/// moved by more than `epsilon` of the objective for `patience` generations
+/// in a row. Any bigger move resets the count, so a population that
+/// oscillates isn't considered converged.
+///
+/// It's a cheap proxy of convergence: unlike a change of hypervolume, the box
+/// is computed in a single pass over scores with [`ScoreStats`]. Empty scores,
+/// like those of an empty archive of [`Spea2`], are skipped. Objectives
+/// without scores other than NaNs are considered stable.
+///
+/// # Examples
+/// ```
+/// use moga::termination::{BoundingBoxTerminator, Terminator};
+///
+/// let mut terminator = BoundingBoxTerminator::new([0.1, 0.1], 2);
+/// let mut terminate = |scores: &[[f32; 2]]| {
+///   let solutions = vec![(); scores.len()];
+///   terminator.terminate(&solutions, scores)
+/// };
+/// assert!(!terminate(&[[1.0, 4.0], [3.0, 2.0]]));
+/// assert!(!terminate(&[[1.0, 4.0], [3.05, 2.0]]));
+/// assert!(terminate(&[[1.0, 3.95], [3.0, 2.0]]));
+/// ```
+///
+/// [`Spea2`]: crate::optimizer::spea::Spea2
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BoundingBoxTerminator<const N: usize> {
+  /// The largest move of each objective's bounds that is considered
+  /// insignificant.
+  #[cfg_attr(feature = "serde", serde(with = "crate::score::serde_scores"))]
+  pub epsilon: Scores<N>,
+  /// The number of generations in a row the box has to stay put.
+  pub patience: usize,
+  bounds: Option<ScoreStats<N>>,
+  stable: usize,
+}
+
+impl<const N: usize> BoundingBoxTerminator<N> {
+  /// Creates a `BoundingBoxTerminator` with the largest insignificant move
+  /// `epsilon` of each objective's bounds and the number of generations
+  /// `patience` the box has to stay put for.
+  pub fn new(epsilon: Scores<N>, patience: usize) -> Self {
+    Self {
+      epsilon,
+      patience,
+      bounds: None,
+      stable: 0,
+    }
+  }
+
+  /// Returns the number of generations in a row the box has stayed put for
+  /// so far.
+  pub fn stable_generations(&self) -> usize {
+    self.stable
+  }
+}
+
+impl<S, const N: usize> Terminator<S, N> for BoundingBoxTerminator<N> {
+  fn terminate(&mut self, solutions: &[S], scores: &[Scores<N>]) -> bool {
+    self.decide(solutions, scores).is_stop()
+  }
+
+  fn decide(&mut self, _: &[S], scores: &[Scores<N>]) -> TerminationDecision {
+    if scores.is_empty() {
+      return TerminationDecision::Continue;
+    }
+    let bounds = ScoreStats::compute(scores);
+    let is_stable = self.bounds.is_some_and(|last| {
+      let is_close = |a: Score, b: Score, epsilon: Score| {
+        (a.is_nan() && b.is_nan()) || (a - b).abs() <= epsilon
+      };
+      (0..N).all(|o| {
+        is_close(last.min()[o], bounds.min()[o], self.epsilon[o])
+          && is_close(last.max()[o], bounds.max()[o], self.epsilon[o])
+      })
+    });
+    self.stable = if is_stable { self.stable + 1 } else { 0 };
+    self.bounds = Some(bounds);
+    if self.stable >= self.patience {
+      TerminationDecision::stop(format!(
+        "the bounding box of scores has been stable for {} generations",
+        self.stable
+      ))
+    } else {
+      TerminationDecision::Continue
+    }
+  }
+}
+
+/// A boxed [`Terminator`] that any termination operator, parallelized or not,
+/// can be converted into. Unlike operators, boxed terminators have the same
+/// type, so the type of an optimizer with a terminator chosen at runtime can
+/// be named.
+///
+/// # Examples
+/// ```
+/// # use moga::termination::{BoxedTerminator, GenerationTerminator};
+/// let generations = Some(100);
+/// let t: BoxedTerminator<f32, 2> = match generations {
+///   Some(n) => BoxedTerminator::new(GenerationTerminator(n)),
+///   None => BoxedTerminator::new(|_: &f32, s: &[f32; 2]| s[0] == 0.0),
+/// };
+/// ```
+pub struct BoxedTerminator<S, const N: usize>(Box<TerminatorFn<S, N>>);
+
+type TerminatorFn<S, const N: usize> =
+  dyn FnMut(&[S], &[Scores<N>]) -> TerminationDecision + Send;
+
+impl<S: 'static, const N: usize> BoxedTerminator<S, N> {
+  /// Boxes the `terminator`.
+  pub fn new<ExecStrat: 'static>(
+    mut terminator: impl TerminationExecutor<S, N, ExecStrat> + Send + 'static,
+  ) -> Self {
+    Self(Box::new(move |solutions: &[S], scores: &[Scores<N>]| {
+      terminator.execute_termination(solutions, scores)
+    }))
+  }
+}
+
+impl<S, const N: usize> Terminator<S, N> for BoxedTerminator<S, N> {
+  fn terminate(&mut self, solutions: &[S], scores: &[Scores<N>]) -> bool {
+    (self.0)(solutions, scores).is_stop()
+  }
+
+  fn decide(
+    &mut self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) -> TerminationDecision {
+    (self.0)(solutions, scores)
+  }
+}
+
+impl<S, const N: usize> std::fmt::Debug for BoxedTerminator<S, N> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("BoxedTerminator")
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
+  #[cfg(feature = "parallel")]
+  use crate::operator::{ParAuto, ParBatch, ParEach};
 
   type Solution = f32;
 
@@ -213,8 +433,12 @@ mod tests {
       *solution > 0.0 && scores.iter().sum::<f32>() == 0.0
     };
     takes_terminator(&mut termination);
+    #[cfg(feature = "parallel")]
     takes_terminator(&mut termination.par_each());
+    #[cfg(feature = "parallel")]
     takes_terminator(&mut termination.par_batch());
+    #[cfg(feature = "parallel")]
+    takes_terminator(&mut termination.par_auto());
   }
 
   #[test]
@@ -236,7 +460,9 @@ mod tests {
 
     let mut termination = CustomTermination {};
     takes_terminator(&mut termination);
+    #[cfg(feature = "parallel")]
     takes_terminator(&mut termination.par_each());
+    #[cfg(feature = "parallel")]
     takes_terminator(&mut termination.par_batch());
   }
 
@@ -252,4 +478,144 @@ mod tests {
     let mut terminator = CustomTerminator {};
     takes_terminator(&mut terminator);
   }
+
+  #[test]
+  fn test_bounding_box_terminator() {
+    let mut terminator = BoundingBoxTerminator::new([0.1, 0.5], 2);
+    takes_terminator(&mut terminator);
+    let mut decide = |scores: &[Scores<2>]| {
+      Terminator::<(), 2>::decide(
+        &mut terminator,
+        &vec![(); scores.len()],
+        scores,
+      )
+    };
+    // the first call of `Spea2` sees an empty archive
+    assert!(!decide(&[]).is_stop());
+    assert!(!decide(&[[1.0, 4.0], [3.0, 2.0]]).is_stop());
+    assert!(!decide(&[[1.05, 4.0], [3.0, 2.4]]).is_stop());
+    // the box oscillates, so the count restarts
+    assert!(!decide(&[[1.0, 4.0], [3.0, 3.0]]).is_stop());
+    assert!(!decide(&[[1.0, 4.0], [3.0, 2.0]]).is_stop());
+    assert!(!decide(&[[1.0, 4.0], [3.0, 2.0], [2.0, 3.0]]).is_stop());
+    assert!(!decide(&[]).is_stop());
+    assert_eq!(
+      decide(&[[1.0, 4.0], [3.0, 2.0]]).reason(),
+      Some("the bounding box of scores has been stable for 2 generations")
+    );
+
+    // objectives with NaNs only don't move
+    let mut terminator = BoundingBoxTerminator::new([0.1], 1);
+    assert!(!terminator.terminate(&[0.0], &[[f32::NAN]]));
+    assert!(terminator.terminate(&[0.0], &[[f32::NAN]]));
+  }
+
+  #[test]
+  fn test_termination_decision() {
+    struct Converged {}
+    impl<S> Terminator<S, 1> for Converged {
+      fn terminate(&mut self, _: &[S], scores: &[Scores<1>]) -> bool {
+        scores.iter().all(|sc| sc[0] == 0.0)
+      }
+
+      fn decide(
+        &mut self,
+        solutions: &[S],
+        scores: &[Scores<1>],
+      ) -> TerminationDecision {
+        match self.terminate(solutions, scores) {
+          true => TerminationDecision::stop("all scores are zero"),
+          false => TerminationDecision::Continue,
+        }
+      }
+    }
+
+    let mut converged = Converged {};
+    assert_eq!(
+      converged.execute_termination(&[1.0], &[[0.0]]).reason(),
+      Some("all scores are zero")
+    );
+    // `bool`-returning operators are adapted with a generic reason
+    let mut terminator = |_: &[f32], _: &[Scores<1>]| true;
+    assert!(terminator.execute_termination(&[], &[]).is_stop());
+    let mut termination = |x: &f32, _: &Scores<1>| *x > 1.0;
+    assert_eq!(
+      termination.execute_termination(&[0.0, 1.0], &[[0.0], [0.0]]),
+      TerminationDecision::Continue
+    );
+    assert!(termination
+      .execute_termination(&[0.0, 2.0], &[[0.0], [0.0]])
+      .is_stop());
+    let mut terminator = GenerationTerminator(1);
+    assert_eq!(
+      Terminator::<f32, 1>::decide(&mut terminator, &[], &[]),
+      TerminationDecision::Continue
+    );
+    assert!(Terminator::<f32, 1>::terminate(&mut terminator, &[], &[]));
+  }
+
+  #[cfg(feature = "serde")]
+  #[test]
+  fn test_experiment_configuration_serde_round_trip() {
+    use crate::selection::TournamentSelectorWithReplacement;
+
+    #[derive(serde::Serialize, serde::Deserialize, PartialEq, Debug)]
+    struct Experiment {
+      selector: TournamentSelectorWithReplacement,
+      terminator: GenerationTerminator,
+    }
+
+    let experiment = Experiment {
+      selector: TournamentSelectorWithReplacement(10, 2),
+      terminator: GenerationTerminator(100),
+    };
+    let json = serde_json::to_string(&experiment).unwrap();
+    assert_eq!(json, r#"{"selector":[10,2],"terminator":100}"#);
+    assert_eq!(
+      serde_json::from_str::<Experiment>(&json).unwrap(),
+      experiment
+    );
+  }
+
+  #[test]
+  fn test_flag_terminator() {
+    let mut terminator = FlagTerminator::new();
+    let flag = terminator.clone();
+    assert!(!Terminator::<f32, 2>::terminate(&mut terminator, &[], &[]));
+    flag.raise();
+    assert!(Terminator::<f32, 2>::terminate(&mut terminator, &[], &[]));
+    assert_eq!(
+      Terminator::<f32, 2>::decide(&mut terminator, &[], &[]).reason(),
+      Some("the flag has been raised")
+    );
+  }
+
+  #[test]
+  fn test_boxed_terminator() {
+    let mut terminator = BoxedTerminator::new(GenerationTerminator(1));
+    takes_terminator::<_, 2, _>(&mut terminator);
+    assert_eq!(
+      terminator.execute_termination(&[], &[]),
+      TerminationDecision::stop("all generations have passed")
+    );
+  }
+
+  #[test]
+  #[cfg(feature = "parallel")]
+  fn test_par_batch_terminates_on_any_solution() {
+    crate::execution::for_uneven_batches(|len| {
+      let solutions: Vec<_> = (0..len).map(|i| i as Solution).collect();
+      let scores = vec![[0.0]; len];
+      // only the last solution, which ends up in the last batch, terminates
+      let last = len as Solution - 1.0;
+      let mut termination =
+        (move |x: &Solution, _: &Scores<1>| *x == last).par_batch();
+      assert_eq!(
+        termination
+          .execute_termination(&solutions, &scores)
+          .is_stop(),
+        len > 0
+      );
+    });
+  }
 }
@@ -0,0 +1,131 @@
+//! Parallel execution of termination operators.
+
+use rayon::prelude::*;
+
+use super::{executor::TerminationExecutor, Termination, TerminationDecision};
+use crate::{
+  execution::{batch_size, strategy::*},
+  operator::{
+    tag::TerminationOperatorTag,
+    AutoDecision,
+    ParAuto,
+    ParAutoOperator,
+    ParBatch,
+    ParBatchOperator,
+    ParEach,
+    ParEachOperator,
+  },
+  score::Scores,
+};
+
+impl<S, const N: usize, T> ParEach<TerminationOperatorTag, S, N, 0> for T
+where
+  S: Sync,
+  T: Termination<S, N> + Sync,
+{
+}
+
+impl<S, const N: usize, T> ParBatch<TerminationOperatorTag, S, N> for T
+where
+  S: Sync,
+  T: Termination<S, N> + Sync,
+{
+}
+
+impl<S, const N: usize, T> ParAuto<TerminationOperatorTag, S, N> for T
+where
+  S: Sync,
+  T: Termination<S, N> + Sync,
+{
+}
+
+impl<S, const N: usize, T>
+  TerminationExecutor<S, N, ParallelEachExecutionStrategy>
+  for ParEachOperator<TerminationOperatorTag, S, T>
+where
+  S: Sync,
+  T: Termination<S, N> + Sync,
+{
+  fn execute_termination(
+    &mut self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) -> TerminationDecision {
+    solutions
+      .par_iter()
+      .zip(scores)
+      .find_map_any(|(sol, sc)| stop(self.operator().decide(sol, sc)))
+      .unwrap_or(TerminationDecision::Continue)
+  }
+}
+
+impl<S, const N: usize, T>
+  TerminationExecutor<S, N, ParallelBatchExecutionStrategy>
+  for ParBatchOperator<TerminationOperatorTag, S, T>
+where
+  S: Sync,
+  T: Termination<S, N> + Sync,
+{
+  fn execute_termination(
+    &mut self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) -> TerminationDecision {
+    let chunk_size = batch_size(solutions.len());
+    solutions
+      .par_chunks(chunk_size)
+      .zip(scores.par_chunks(chunk_size))
+      .find_map_any(|chunk| {
+        chunk
+          .0
+          .iter()
+          .zip(chunk.1)
+          .find_map(|(sol, sc)| stop(self.operator().decide(sol, sc)))
+      })
+      .unwrap_or(TerminationDecision::Continue)
+  }
+}
+
+impl<S, const N: usize, T> TerminationExecutor<S, N, AutoExecutionStrategy>
+  for ParAutoOperator<TerminationOperatorTag, S, T>
+where
+  S: Sync,
+  T: Termination<S, N> + Sync,
+{
+  fn execute_termination(
+    &mut self,
+    solutions: &[S],
+    scores: &[Scores<N>],
+  ) -> TerminationDecision {
+    let decide = |solutions: &[S], scores: &[Scores<N>]| {
+      solutions
+        .iter()
+        .zip(scores)
+        .find_map(|(sol, sc)| stop(self.operator().decide(sol, sc)))
+    };
+    let mut decided = None;
+    let (checked, strategy) = self.plan(solutions.len(), |sample| {
+      decided = decide(&solutions[..sample], &scores[..sample])
+    });
+    if let Some(decision) = decided {
+      return decision;
+    }
+    let (rest, rest_scores) = (&solutions[checked..], &scores[checked..]);
+    match strategy {
+      AutoDecision::Sequential => decide(rest, rest_scores),
+      AutoDecision::Batch => {
+        let chunk_size = batch_size(rest.len());
+        rest
+          .par_chunks(chunk_size)
+          .zip(rest_scores.par_chunks(chunk_size))
+          .find_map_any(|chunk| decide(chunk.0, chunk.1))
+      }
+    }
+    .unwrap_or(TerminationDecision::Continue)
+  }
+}
+
+/// Returns the `decision` if it terminates the algorithm.
+fn stop(decision: TerminationDecision) -> Option<TerminationDecision> {
+  Some(decision).filter(TerminationDecision::is_stop)
+}
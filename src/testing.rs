@@ -1,20 +1,30 @@
 //! Fitness scores evaluation operators and utilities.
 
+#[cfg(feature = "serde_json")]
+use std::io::{self, Read, Write};
+use std::{
+  collections::{HashMap, HashSet},
+  marker::PhantomData,
+  sync::{Arc, Mutex, MutexGuard},
+};
+
 use executor::TestExecutor;
-use rayon::prelude::*;
+use rand::rngs::SmallRng;
 
 use crate::{
   execution::strategy::*,
-  operator::{
-    tag::TestOperatorTag,
-    ParBatch,
-    ParBatchOperator,
-    ParEach,
-    ParEachOperator,
-  },
-  score::Scores,
+  rng::{IndexedStreams, SplitRng},
+  score::{squared_distance, Score, Scores},
 };
 
+#[cfg(feature = "parallel")]
+mod parallel;
+#[cfg(feature = "serde_json")]
+mod subprocess;
+
+#[cfg(feature = "serde_json")]
+pub use subprocess::SubprocessTester;
+
 /// An operator that tests a solution's fitness, evaluating an array of its
 /// fitness scores.
 ///
@@ -33,6 +43,7 @@ use crate::{
 ///
 /// # Examples
 /// ```
+/// # #[cfg(feature = "parallel")] {
 /// # use moga::operator::*;
 /// let t = |f: &f32| [f * 2.0]; // only one objective
 /// let t = |f: &f32| [f + 1.0, f + 2.0, f + 3.0]; // 3 objectives
@@ -43,6 +54,7 @@ use crate::{
 ///   |f: &f32| f * f * f + 3.0,
 /// ];
 /// t.par_batch();
+/// # }
 /// ```
 ///
 /// **Note that you always can implement this trait instead of using closures.**
@@ -70,20 +82,6 @@ where
   }
 }
 
-impl<S, const N: usize, T> ParEach<TestOperatorTag, S, N, 0> for T
-where
-  S: Sync,
-  T: Test<S, N> + Sync,
-{
-}
-
-impl<S, const N: usize, T> ParBatch<TestOperatorTag, S, N> for T
-where
-  S: Sync,
-  T: Test<S, N> + Sync,
-{
-}
-
 /// An operator that tests solutions' fitness, evaluating an array of fitness
 /// scores for each solution.
 ///
@@ -108,10 +106,12 @@ pub trait Tester<S, const N: usize> {
   /// Returns a vector of arrays of fitness scores for given solutions.
   /// The closer a score is to 0 - the better.
   ///
-  /// # Panics
+  /// Optimization fails with [`OptimizationError::ScoreCountMismatch`] if
+  /// this function returns a different number of scores than the number of
+  /// solutions.
   ///
-  /// Doesn't panic itself but will cause panic during optimization if this
-  /// function returns a different number of scores than the number of solutions.
+  /// [`OptimizationError::ScoreCountMismatch`]:
+  /// crate::optimizer::OptimizationError::ScoreCountMismatch
   fn test(&self, solutions: &[S]) -> Vec<Scores<N>>;
 }
 
@@ -124,14 +124,134 @@ where
   }
 }
 
-/// This private module prevents exposing the `Executor` to a user.
+/// A failure of a test that can't evaluate scores, such as a crashed or timed
+/// out simulation. A test operator raises it with [`TestFailure::raise`]
+/// instead of returning scores, and optimization fails with
+/// [`OptimizationError::TestFailed`], whether or not the optimizer catches
+/// other panics of operators.
+///
+/// The failure unwinds the stack like a panic does, so it doesn't stop
+/// optimization if panics abort.
+///
+/// # Examples
+/// ```
+/// use moga::{
+///   optimizer::{nsga::Nsga2, OptimizationError, Optimizer},
+///   selection::AllSelector,
+///   termination::GenerationTerminator,
+///   testing::TestFailure,
+/// };
+///
+/// let result = Nsga2::builder()
+///   .population(vec![1.0, 2.0])
+///   .tester(|x: &f32| {
+///     if *x > 1.0 {
+///       TestFailure::raise("the simulation has diverged");
+///     }
+///     [*x]
+///   })
+///   .selector(AllSelector())
+///   .recombinator(|x: &f32| *x)
+///   .mutator(|_: &mut f32| {})
+///   .terminator(GenerationTerminator(1))
+///   .build()
+///   .try_optimize();
+/// assert_eq!(
+///   result,
+///   Err(OptimizationError::TestFailed {
+///     generation: 0,
+///     message: "the simulation has diverged".to_string(),
+///   })
+/// );
+/// ```
+///
+/// [`OptimizationError::TestFailed`]:
+/// crate::optimizer::OptimizationError::TestFailed
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TestFailure {
+  /// What has gone wrong.
+  pub message: String,
+}
+
+impl TestFailure {
+  /// Stops the test, failing optimization with the `message`.
+  pub fn raise(message: impl Into<String>) -> ! {
+    std::panic::panic_any(Self {
+      message: message.into(),
+    })
+  }
+}
+
+impl std::fmt::Display for TestFailure {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str(&self.message)
+  }
+}
+
+/// An operator that tests a batch of solutions at once, evaluating an array
+/// of fitness scores for each solution of the batch. Useful when the fitness
+/// function is vectorized and much faster on many solutions than on one.
+///
+/// Unlike a [`Tester`], it can be parallelized: with `par_batch()`, the
+/// population is split into a chunk per thread, and each chunk is tested in
+/// parallel. If the test returns a different number of scores than the
+/// number of solutions in a chunk, scores of the chunk are discarded, so
+/// optimization fails with [`OptimizationError::ScoreCountMismatch`].
+///
+/// # Examples
+/// ```
+/// # #[cfg(feature = "parallel")] {
+/// # use moga::operator::*;
+/// let t = |fs: &[f32]| fs.iter().map(|f| [f * 2.0, f - 1.0]).collect();
+/// let t = t.par_batch();
+/// # }
+/// ```
+///
+/// **Note that you always can implement this trait instead of using closures.**
+///
+/// [`OptimizationError::ScoreCountMismatch`]:
+/// crate::optimizer::OptimizationError::ScoreCountMismatch
+pub trait BatchTest<S, const N: usize> {
+  /// Returns a vector of arrays of fitness scores for given batch of
+  /// solutions, one array per solution.
+  fn test_batch(&self, solutions: &[S]) -> Vec<Scores<N>>;
+}
+
+impl<S, const N: usize, F> BatchTest<S, N> for F
+where
+  F: Fn(&[S]) -> Vec<Scores<N>>,
+{
+  fn test_batch(&self, solutions: &[S]) -> Vec<Scores<N>> {
+    self(solutions)
+  }
+}
+
+/// Executors are exposed to users only by [`crate::experimental::executor`].
 pub(crate) mod executor {
+  use std::time::Instant;
+
   use crate::score::Scores;
 
-  /// An internal test executor.
+  /// An executor of test operators.
   pub trait TestExecutor<S, const N: usize, ExecutionStrategy> {
     /// Executes tests optionally parallelizing operator's application.
     fn execute_tests(&self, solutions: &[S]) -> Vec<Scores<N>>;
+
+    /// Executes tests of `solutions` until the `deadline`, returning scores of
+    /// solutions tested before it and `None` for solutions left untested.
+    /// Unless overridden, tests all solutions regardless of the deadline.
+    fn execute_tests_until(
+      &self,
+      solutions: &[S],
+      deadline: Instant,
+    ) -> Vec<Option<Scores<N>>> {
+      let _ = deadline;
+      self
+        .execute_tests(solutions)
+        .into_iter()
+        .map(Some)
+        .collect()
+    }
   }
 }
 
@@ -153,38 +273,810 @@ where
   }
 }
 
-impl<const N: usize, S, T> TestExecutor<S, N, ParallelEachExecutionStrategy>
-  for ParEachOperator<TestOperatorTag, S, T>
+/// A boxed [`Tester`] that any test operator, parallelized or not, can be
+/// converted into. Unlike operators, boxed testers have the same type, so the
+/// type of an optimizer with a tester chosen at runtime can be named.
+///
+/// Boxed operators are dispatched dynamically once per generation, which
+/// costs next to nothing compared to the work of the operators themselves.
+/// See `nsga2_boxed` example.
+///
+/// # Examples
+/// ```
+/// # use moga::testing::BoxedTester;
+/// let objectives = 2;
+/// let t: BoxedTester<f32, 2> = if objectives == 2 {
+///   BoxedTester::new(|x: &f32| [*x, x - 2.0])
+/// } else {
+///   BoxedTester::new(|x: &f32| [*x, -x])
+/// };
+/// ```
+pub struct BoxedTester<S, const N: usize>(Box<dyn Tester<S, N> + Send>);
+
+impl<S: 'static, const N: usize> BoxedTester<S, N> {
+  /// Boxes the `tester`.
+  pub fn new<ExecStrat: 'static>(
+    tester: impl TestExecutor<S, N, ExecStrat> + Send + 'static,
+  ) -> Self {
+    Self(Box::new(move |solutions: &[S]| {
+      tester.execute_tests(solutions)
+    }))
+  }
+}
+
+impl<S, const N: usize> Tester<S, N> for BoxedTester<S, N> {
+  fn test(&self, solutions: &[S]) -> Vec<Scores<N>> {
+    self.0.test(solutions)
+  }
+}
+
+impl<S, const N: usize> std::fmt::Debug for BoxedTester<S, N> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("BoxedTester")
+  }
+}
+
+/// A [`Tester`] that tests each solution with a random number generator of
+/// its own, derived from the master seed, the generation and the index of the
+/// solution with [`SplitRng::indexed`], such as a noisy or a sampled fitness
+/// function. Each solution is tested the same way regardless of the order
+/// solutions are tested in, so the tester stays reproducible when testing
+/// solutions in parallel with `par_each()`.
+///
+/// Generations are counted by the number of times the tester was applied.
+/// Optimizers apply it once to the initial population and once per
+/// generation to offsprings, as well as to injected solutions.
+///
+/// # Examples
+/// ```
+/// use moga::{rng::SplitRng, testing::IndexedRandomTest};
+/// use rand::Rng;
+///
+/// let t = IndexedRandomTest::new(SplitRng::new(42), |x: &f32, rng| {
+///   [x + rng.gen_range(-0.1..0.1), x - 2.0]
+/// });
+/// # #[cfg(feature = "parallel")]
+/// let t = t.par_each();
+/// ```
+pub struct IndexedRandomTest<F> {
+  streams: IndexedStreams,
+  test: F,
+  parallel: bool,
+}
+
+impl<F> IndexedRandomTest<F> {
+  /// Creates a tester that applies the `test` to each solution with
+  /// a generator derived from the master seed `rng`.
+  pub fn new<S, const N: usize>(rng: SplitRng, test: F) -> Self
+  where
+    F: Fn(&S, &mut SmallRng) -> Scores<N>,
+  {
+    Self {
+      streams: IndexedStreams::new(rng),
+      test,
+      parallel: false,
+    }
+  }
+
+  /// Tests solutions in parallel. Solutions get the same scores as they
+  /// would sequentially.
+  #[cfg(feature = "parallel")]
+  pub fn par_each(self) -> Self {
+    Self {
+      parallel: true,
+      ..self
+    }
+  }
+}
+
+#[cfg(not(feature = "parallel"))]
+impl<S, const N: usize, F> Tester<S, N> for IndexedRandomTest<F>
 where
-  S: Sync,
-  T: Test<S, N> + Sync,
+  F: Fn(&S, &mut SmallRng) -> Scores<N>,
 {
-  fn execute_tests(&self, solutions: &[S]) -> Vec<Scores<N>> {
+  fn test(&self, solutions: &[S]) -> Vec<Scores<N>> {
+    let generation = self.streams.next_generation();
     solutions
-      .par_iter()
-      .map(|s| self.operator().test(s))
+      .iter()
+      .enumerate()
+      .map(|(idx, s)| (self.test)(s, &mut self.streams.stream(generation, idx)))
       .collect()
   }
 }
 
-impl<const N: usize, S, T> TestExecutor<S, N, ParallelBatchExecutionStrategy>
-  for ParBatchOperator<TestOperatorTag, S, T>
+#[cfg(feature = "parallel")]
+impl<S, const N: usize, F> Tester<S, N> for IndexedRandomTest<F>
 where
   S: Sync,
-  T: Test<S, N> + Sync,
+  F: Fn(&S, &mut SmallRng) -> Scores<N> + Sync,
 {
-  fn execute_tests(&self, solutions: &[S]) -> Vec<Scores<N>> {
-    let chunk_size = (solutions.len() / rayon::current_num_threads()).max(1);
-    solutions
-      .par_chunks(chunk_size)
-      .flat_map_iter(|chunk| chunk.iter().map(|s| self.operator().test(s)))
+  fn test(&self, solutions: &[S]) -> Vec<Scores<N>> {
+    use rayon::prelude::*;
+
+    let generation = self.streams.next_generation();
+    let test = |(idx, s): (usize, &S)| {
+      (self.test)(s, &mut self.streams.stream(generation, idx))
+    };
+    if self.parallel {
+      solutions.par_iter().enumerate().map(test).collect()
+    } else {
+      solutions.iter().enumerate().map(test).collect()
+    }
+  }
+}
+
+impl<F> std::fmt::Debug for IndexedRandomTest<F> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("IndexedRandomTest")
+      .field("streams", &self.streams)
+      .field("parallel", &self.parallel)
+      .finish_non_exhaustive()
+  }
+}
+
+/// A way of aggregating scores of several objectives into a single score,
+/// used by [`Scalarized`]. As scores converge at `0`, absolute values of
+/// scores are aggregated. If any weighted score is NaN, so is the aggregated
+/// score.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Scalarization {
+  /// The sum of weighted scores. It's minimized only by solutions on convex
+  /// parts of the pareto front.
+  WeightedSum,
+  /// The maximum of weighted scores. It's minimized by any point of the
+  /// pareto front with suitable weights, including its non-convex parts.
+  Chebyshev,
+}
+
+impl Scalarization {
+  /// Aggregates `scores` weighted by `weights` into a single score.
+  pub fn apply<const N: usize>(
+    self,
+    scores: &Scores<N>,
+    weights: &Scores<N>,
+  ) -> f32 {
+    let weighted = scores.iter().zip(weights).map(|(s, w)| s.abs() * w);
+    match self {
+      Self::WeightedSum => weighted.sum(),
+      // `f32::max` ignores NaN, so it's propagated explicitly
+      Self::Chebyshev => weighted.fold(0.0, |max, w| {
+        if max.is_nan() || w.is_nan() {
+          f32::NAN
+        } else {
+          max.max(w)
+        }
+      }),
+    }
+  }
+}
+
+/// A [`Test`] of a single objective that aggregates scores of a test of `N`
+/// objectives with user weights, so a multi-objective problem can be solved
+/// as a single-objective one and compared against the pareto approach.
+///
+/// Only the aggregated score reaches the optimizer. Scores of all objectives
+/// of selected solutions can be evaluated afterwards with
+/// [`Scalarized::expand`], which tests them with the wrapped test again.
+///
+/// # Examples
+/// ```
+/// use moga::testing::{Scalarized, Test};
+///
+/// let t = Scalarized::chebyshev(|x: &f32| [*x, 1.0 - x], [1.0, 2.0]);
+/// assert_eq!(t.test(&0.25), [0.75 * 2.0]);
+/// assert_eq!(t.expand(&[0.25]), [[0.25, 0.75]]);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Scalarized<T, const N: usize> {
+  test: T,
+  weights: Scores<N>,
+  scalarization: Scalarization,
+}
+
+impl<T, const N: usize> Scalarized<T, N> {
+  /// Wraps the `test`, aggregating its scores with the `scalarization`
+  /// weighted by `weights`.
+  ///
+  /// # Panics
+  ///
+  /// Panics if any weight is negative.
+  pub fn new(
+    test: T,
+    weights: Scores<N>,
+    scalarization: Scalarization,
+  ) -> Self {
+    assert!(
+      weights.iter().all(|&w| w >= 0.0),
+      "weights must not be negative"
+    );
+    Self {
+      test,
+      weights,
+      scalarization,
+    }
+  }
+
+  /// Wraps the `test`, aggregating its scores with [weighted sum].
+  ///
+  /// [weighted sum]: Scalarization::WeightedSum
+  pub fn weighted_sum(test: T, weights: Scores<N>) -> Self {
+    Self::new(test, weights, Scalarization::WeightedSum)
+  }
+
+  /// Wraps the `test`, aggregating its scores with [weighted Chebyshev]
+  /// function.
+  ///
+  /// [weighted Chebyshev]: Scalarization::Chebyshev
+  pub fn chebyshev(test: T, weights: Scores<N>) -> Self {
+    Self::new(test, weights, Scalarization::Chebyshev)
+  }
+
+  /// Returns the wrapped test.
+  pub fn inner(&self) -> &T {
+    &self.test
+  }
+
+  /// Returns weights of objectives.
+  pub fn weights(&self) -> Scores<N> {
+    self.weights
+  }
+
+  /// Returns the way scores are aggregated.
+  pub fn scalarization(&self) -> Scalarization {
+    self.scalarization
+  }
+
+  /// Tests `solutions` with the wrapped test, returning scores of all
+  /// objectives in order of solutions.
+  pub fn expand<S>(&self, solutions: &[S]) -> Vec<Scores<N>>
+  where
+    T: Test<S, N>,
+  {
+    solutions.iter().map(|s| self.test.test(s)).collect()
+  }
+}
+
+impl<S, T, const N: usize> Test<S, 1> for Scalarized<T, N>
+where
+  T: Test<S, N>,
+{
+  fn test(&self, solution: &S) -> Scores<1> {
+    [self
+      .scalarization
+      .apply(&self.test.test(solution), &self.weights)]
+  }
+}
+
+/// A [`Tester`] that penalizes scores of a wrapped tester by niche counts of
+/// solutions, which is known as fitness sharing. Solutions crowded in one
+/// niche of objective space share their fitness, so the population spreads
+/// over several optima of multimodal problems instead of converging to one.
+///
+/// The niche count of a solution is the sum of sharing function values
+/// `1 - (d / sigma)^alpha` over solutions within distance `d < sigma` from
+/// it, itself included, where `d` is euclidean distance between raw scores.
+/// Each score of a solution is multiplied by its niche count, moving it away
+/// from `0`. Solutions are compared with each other, so testing takes
+/// quadratic time of the number of solutions.
+///
+/// **Penalized scores are what the optimizer sees, so they change the
+/// meaning of scores reported to observers, events and exports.** Evaluate
+/// raw scores of solutions with [`FitnessSharing::inner`] if you need them.
+/// Niche counts are counted among solutions tested together: the initial
+/// population and offsprings of each generation, but not the population they
+/// are merged with.
+///
+/// # Examples
+/// ```
+/// use moga::testing::{FitnessSharing, Tester};
+///
+/// let t = FitnessSharing::new(|x: &f32| [*x, 1.0 - x], 0.5, 1.0);
+/// let scores = t.test(&[0.0, 0.0, 1.0]);
+/// // two equal solutions share their niche, the third one is alone
+/// assert_eq!(scores, [[0.0, 2.0], [0.0, 2.0], [1.0, 0.0]]);
+/// ```
+pub struct FitnessSharing<T, ES> {
+  tester: T,
+  sigma: f32,
+  alpha: f32,
+  _strategy: PhantomData<fn() -> ES>,
+}
+
+impl<T, ES> FitnessSharing<T, ES> {
+  /// Wraps the `tester`, penalizing its scores by niche counts with niche
+  /// radius `sigma` and sharing function shape `alpha`. With `alpha` of
+  /// `1.0` the sharing function decreases linearly with distance.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `sigma` or `alpha` is not positive.
+  pub fn new<S, const N: usize>(tester: T, sigma: f32, alpha: f32) -> Self
+  where
+    T: TestExecutor<S, N, ES>,
+  {
+    assert!(sigma > 0.0, "niche radius must be positive");
+    assert!(alpha > 0.0, "sharing function shape must be positive");
+    Self {
+      tester,
+      sigma,
+      alpha,
+      _strategy: PhantomData,
+    }
+  }
+
+  /// Returns the wrapped tester.
+  pub fn inner(&self) -> &T {
+    &self.tester
+  }
+
+  /// Returns the niche radius.
+  pub fn sigma(&self) -> f32 {
+    self.sigma
+  }
+
+  /// Returns the shape of the sharing function.
+  pub fn alpha(&self) -> f32 {
+    self.alpha
+  }
+
+  /// Returns niche counts of solutions with given `scores`, in their order.
+  pub fn niche_counts<const N: usize>(&self, scores: &[Scores<N>]) -> Vec<f32> {
+    let sigma = f64::from(self.sigma);
+    let alpha = f64::from(self.alpha);
+    let mut counts = vec![1.0; scores.len()];
+    for (i, a) in scores.iter().enumerate() {
+      for (j, b) in scores.iter().enumerate().skip(i + 1) {
+        let distance = squared_distance(a, b).sqrt();
+        if distance < sigma {
+          let sharing = 1.0 - (distance / sigma).powf(alpha);
+          counts[i] += sharing;
+          counts[j] += sharing;
+        }
+      }
+    }
+    counts.into_iter().map(|c| c as f32).collect()
+  }
+}
+
+impl<S, T, ES, const N: usize> Tester<S, N> for FitnessSharing<T, ES>
+where
+  T: TestExecutor<S, N, ES>,
+{
+  fn test(&self, solutions: &[S]) -> Vec<Scores<N>> {
+    let mut scores = self.tester.execute_tests(solutions);
+    let counts = self.niche_counts(&scores);
+    for (sc, count) in scores.iter_mut().zip(counts) {
+      sc.iter_mut().for_each(|s| *s *= count);
+    }
+    scores
+  }
+}
+
+impl<T: Clone, ES> Clone for FitnessSharing<T, ES> {
+  fn clone(&self) -> Self {
+    Self {
+      tester: self.tester.clone(),
+      sigma: self.sigma,
+      alpha: self.alpha,
+      _strategy: PhantomData,
+    }
+  }
+}
+
+impl<T, ES> std::fmt::Debug for FitnessSharing<T, ES> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("FitnessSharing")
+      .field("sigma", &self.sigma)
+      .field("alpha", &self.alpha)
+      .finish_non_exhaustive()
+  }
+}
+
+/// A [`Tester`] that memoizes scores of the wrapped tester, so that each
+/// solution is tested once no matter how many times it reappears. Solutions
+/// are identified by keys that the `key` function computes, since `Hash` of
+/// floating point numbers is unreliable. Keys should be stable, i.e. the same
+/// between runs, for saved scores to be found, and collisions of keys make
+/// different solutions share scores.
+///
+/// Clones share memoized scores and the evaluation counter, so a clone kept
+/// aside reads them after an optimizer has consumed the tester. With
+/// `serde_json` feature enabled, memoized scores can be saved with
+/// [`CachedTest::save`] and loaded with [`CachedTest::load`] to persist them
+/// between runs.
+///
+/// If the wrapped tester returns a different number of scores than the
+/// number of solutions it was given, nothing is memoized, and no scores are
+/// returned.
+///
+/// # Examples
+/// ```
+/// use moga::testing::{CachedTest, Tester};
+///
+/// let t =
+///   CachedTest::new(|x: &f32| [*x, 1.0 - x], |x: &f32| x.to_bits().into());
+/// assert_eq!(t.test(&[0.0, 1.0, 0.0]), [[0.0, 1.0], [1.0, 0.0], [
+///   0.0, 1.0
+/// ]]);
+/// t.test(&[1.0, 2.0]);
+/// assert_eq!(t.evaluations(), 3);
+/// ```
+pub struct CachedTest<T, K, ES, const N: usize> {
+  tester: T,
+  key: K,
+  cache: Arc<Mutex<Cache<N>>>,
+  _strategy: PhantomData<fn() -> ES>,
+}
+
+/// Memoized scores of [`CachedTest`] and the number of evaluated solutions.
+#[derive(Debug, Default)]
+struct Cache<const N: usize> {
+  scores: HashMap<u64, Scores<N>>,
+  evaluations: usize,
+}
+
+impl<T, K, ES, const N: usize> CachedTest<T, K, ES, N> {
+  /// Wraps the `tester`, memoizing its scores by keys of solutions computed
+  /// with the `key` function.
+  pub fn new<S>(tester: T, key: K) -> Self
+  where
+    T: TestExecutor<S, N, ES>,
+    K: Fn(&S) -> u64,
+  {
+    Self {
+      tester,
+      key,
+      cache: Default::default(),
+      _strategy: PhantomData,
+    }
+  }
+
+  /// Returns the wrapped tester.
+  pub fn inner(&self) -> &T {
+    &self.tester
+  }
+
+  /// Returns the number of solutions the wrapped tester has tested.
+  pub fn evaluations(&self) -> usize {
+    self.cache().evaluations
+  }
+
+  /// Returns the number of memoized scores.
+  pub fn len(&self) -> usize {
+    self.cache().scores.len()
+  }
+
+  /// Returns `true` if no scores are memoized.
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+
+  /// Forgets memoized scores. The evaluation counter is kept.
+  pub fn clear(&self) {
+    self.cache().scores.clear();
+  }
+
+  /// Writes memoized scores as JSON along with the version of the format.
+  /// Scores that aren't finite can't be written as JSON and are skipped.
+  #[cfg(feature = "serde_json")]
+  pub fn save<W: Write>(&self, mut writer: W) -> io::Result<()> {
+    let cache = self.cache();
+    let mut entries: Vec<_> = cache
+      .scores
+      .iter()
+      .filter(|(_, scores)| scores.iter().all(|s| s.is_finite()))
+      .map(|(&key, &scores)| CacheEntry { key, scores })
+      .collect();
+    // sorted for the same scores to be written the same way
+    entries.sort_unstable_by_key(|entry| entry.key);
+    let file = CacheFile {
+      version: CACHE_FORMAT_VERSION,
+      objectives: N,
+      entries,
+    };
+    serde_json::to_writer(&mut writer, &file)?;
+    writer.flush()
+  }
+
+  /// Reads scores written with [`CachedTest::save`] and memoizes them, then
+  /// returns the number of read scores.
+  ///
+  /// Returns an error of [`io::ErrorKind::InvalidData`] kind if the scores
+  /// were written in another version of the format or for another number of
+  /// objectives.
+  #[cfg(feature = "serde_json")]
+  pub fn load<R: Read>(&self, reader: R) -> io::Result<usize> {
+    let invalid = |message| io::Error::new(io::ErrorKind::InvalidData, message);
+    let file: serde_json::Value = serde_json::from_reader(reader)?;
+    match file.get("version").and_then(serde_json::Value::as_u64) {
+      Some(CACHE_FORMAT_VERSION) => {}
+      Some(version) => {
+        return Err(invalid(format!(
+          "unsupported cache format version {version}, \
+           expected {CACHE_FORMAT_VERSION}"
+        )))
+      }
+      None => return Err(invalid("cache format version is missing".into())),
+    }
+    let file: CacheFile<N> = serde_json::from_value(file)?;
+    if file.objectives != N {
+      return Err(invalid(format!(
+        "cached scores have {} objectives, expected {N}",
+        file.objectives
+      )));
+    }
+    let loaded = file.entries.len();
+    self
+      .cache()
+      .scores
+      .extend(file.entries.into_iter().map(|e| (e.key, e.scores)));
+    Ok(loaded)
+  }
+
+  fn cache(&self) -> MutexGuard<'_, Cache<N>> {
+    self.cache.lock().unwrap_or_else(|e| e.into_inner())
+  }
+}
+
+impl<S, T, K, ES, const N: usize> Tester<S, N> for CachedTest<T, K, ES, N>
+where
+  S: Clone,
+  T: TestExecutor<S, N, ES>,
+  K: Fn(&S) -> u64,
+{
+  fn test(&self, solutions: &[S]) -> Vec<Scores<N>> {
+    let keys: Vec<_> = solutions.iter().map(&self.key).collect();
+    // hits are copied while the cache is locked, as another clone may clear
+    // it before it's locked again
+    let (hits, (missed, missed_keys)): (Vec<_>, (Vec<_>, Vec<_>)) = {
+      let cache = self.cache();
+      let hits: Vec<_> = keys
+        .iter()
+        .map(|key| cache.scores.get(key).copied())
+        .collect();
+      let mut seen = HashSet::new();
+      let missed = solutions
+        .iter()
+        .zip(&keys)
+        .zip(&hits)
+        .filter(|((_, key), hit)| hit.is_none() && seen.insert(**key))
+        .map(|((solution, &key), _)| (solution.clone(), key))
+        .unzip();
+      (hits, missed)
+    };
+    // the cache isn't locked while testing, which may take a while
+    let missed_scores = self.tester.execute_tests(&missed);
+    if missed_scores.len() != missed.len() {
+      return Vec::new();
+    }
+    let tested: HashMap<_, _> =
+      missed_keys.into_iter().zip(missed_scores).collect();
+    let mut cache = self.cache();
+    cache.evaluations += missed.len();
+    cache.scores.extend(&tested);
+    hits
+      .into_iter()
+      .zip(&keys)
+      .map(|(hit, key)| hit.unwrap_or_else(|| tested[key]))
+      .collect()
+  }
+}
+
+impl<T: Clone, K: Clone, ES, const N: usize> Clone for CachedTest<T, K, ES, N> {
+  fn clone(&self) -> Self {
+    Self {
+      tester: self.tester.clone(),
+      key: self.key.clone(),
+      cache: Arc::clone(&self.cache),
+      _strategy: PhantomData,
+    }
+  }
+}
+
+impl<T, K, ES, const N: usize> std::fmt::Debug for CachedTest<T, K, ES, N> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("CachedTest")
+      .field("cache", &self.cache())
+      .finish_non_exhaustive()
+  }
+}
+
+/// Version of the format [`CachedTest::save`] writes scores in.
+#[cfg(feature = "serde_json")]
+const CACHE_FORMAT_VERSION: u64 = 1;
+
+/// Scores saved by [`CachedTest::save`].
+#[cfg(feature = "serde_json")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CacheFile<const N: usize> {
+  version: u64,
+  objectives: usize,
+  entries: Vec<CacheEntry<N>>,
+}
+
+/// Scores of a solution with the `key`, saved by [`CachedTest::save`].
+#[cfg(feature = "serde_json")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct CacheEntry<const N: usize> {
+  key: u64,
+  #[serde(with = "crate::score::serde_scores")]
+  scores: Scores<N>,
+}
+
+/// A [`Tester`] that evaluates solutions in two stages, so that an expensive
+/// test runs only for solutions that pass a cheap one, such as a simulation
+/// gated by an analytic bound. The first stage tests all solutions by the
+/// first `K` of `N` objectives, and the `keep` predicate decides by these
+/// scores which solutions are kept. The second stage tests only the kept
+/// solutions by all `N` objectives, which includes the cheap ones. The rest
+/// get sentinel scores, except for the first `K` objectives, which keep
+/// their scores of the first stage. Scores are returned in order of
+/// solutions, whichever of them are kept.
+///
+/// Sentinel scores default to [`f32::MAX`] and are meant to be large finite
+/// values: never NaN, which can't be sorted into fronts, nor infinities, which
+/// break metrics such as hypervolume. With them, a killed solution is
+/// dominated by any kept solution that is as good by the first `K`
+/// objectives.
+///
+/// If either stage returns a different number of scores than the number of
+/// solutions it was given, no scores are returned.
+///
+/// # Examples
+/// ```
+/// use moga::testing::{Staged, Tester};
+///
+/// let t = Staged::new(
+///   |x: &f32| [x.abs()],
+///   |&[bound]: &[f32; 1]| bound < 1.0,
+///   |x: &f32| [x.abs(), x * x], // expensive
+/// )
+/// .with_sentinel([0.0, 1e9]);
+/// assert_eq!(t.test(&[0.5, 2.0]), [[0.5, 0.25], [2.0, 1e9]]);
+/// ```
+pub struct Staged<T1, T2, P, ES1, ES2, const K: usize, const N: usize> {
+  first: T1,
+  keep: P,
+  second: T2,
+  sentinel: Scores<N>,
+  _strategy: PhantomData<fn() -> (ES1, ES2)>,
+}
+
+impl<T1, T2, P, ES1, ES2, const K: usize, const N: usize>
+  Staged<T1, T2, P, ES1, ES2, K, N>
+{
+  /// Tests solutions with the `first` tester, then tests solutions whose
+  /// scores the `keep` predicate accepts with the `second` tester.
+  pub fn new<S>(first: T1, keep: P, second: T2) -> Self
+  where
+    T1: TestExecutor<S, K, ES1>,
+    P: Fn(&Scores<K>) -> bool,
+    T2: TestExecutor<S, N, ES2>,
+  {
+    const {
+      assert!(K <= N, "the first stage must test a subset of objectives");
+    }
+    Self {
+      first,
+      keep,
+      second,
+      sentinel: [Score::MAX; N],
+      _strategy: PhantomData,
+    }
+  }
+
+  /// Sets scores that solutions not kept by the first stage get. Their
+  /// first `K` scores are replaced by scores of the first stage.
+  ///
+  /// # Panics
+  ///
+  /// Panics if any of the `sentinel` scores is not finite.
+  pub fn with_sentinel(self, sentinel: Scores<N>) -> Self {
+    assert!(
+      sentinel.iter().all(|s| s.is_finite()),
+      "sentinel scores must be finite"
+    );
+    Self { sentinel, ..self }
+  }
+
+  /// Returns the tester of the first stage.
+  pub fn first(&self) -> &T1 {
+    &self.first
+  }
+
+  /// Returns the tester of the second stage.
+  pub fn second(&self) -> &T2 {
+    &self.second
+  }
+
+  /// Returns scores that solutions not kept by the first stage get.
+  pub fn sentinel(&self) -> Scores<N> {
+    self.sentinel
+  }
+}
+
+impl<S, T1, T2, P, ES1, ES2, const K: usize, const N: usize> Tester<S, N>
+  for Staged<T1, T2, P, ES1, ES2, K, N>
+where
+  S: Clone,
+  T1: TestExecutor<S, K, ES1>,
+  P: Fn(&Scores<K>) -> bool,
+  T2: TestExecutor<S, N, ES2>,
+{
+  fn test(&self, solutions: &[S]) -> Vec<Scores<N>> {
+    let first_scores = self.first.execute_tests(solutions);
+    if first_scores.len() != solutions.len() {
+      return Vec::new();
+    }
+    let kept: Vec<_> = first_scores.iter().map(&self.keep).collect();
+    let kept_num = kept.iter().filter(|&&keep| keep).count();
+    // solutions are tested as they are if all of them are kept
+    let second_scores = if kept_num == solutions.len() {
+      self.second.execute_tests(solutions)
+    } else {
+      let kept_solutions: Vec<_> = solutions
+        .iter()
+        .zip(&kept)
+        .filter(|(_, &keep)| keep)
+        .map(|(solution, _)| solution.clone())
+        .collect();
+      self.second.execute_tests(&kept_solutions)
+    };
+    if second_scores.len() != kept_num {
+      return Vec::new();
+    }
+    let mut second_scores = second_scores.into_iter();
+    first_scores
+      .into_iter()
+      .zip(kept)
+      .map(|(first, keep)| {
+        if keep {
+          second_scores.next().expect("a kept solution has scores")
+        } else {
+          let mut scores = self.sentinel;
+          scores[..K].copy_from_slice(&first);
+          scores
+        }
+      })
       .collect()
   }
 }
 
+impl<T1, T2, P, ES1, ES2, const K: usize, const N: usize> Clone
+  for Staged<T1, T2, P, ES1, ES2, K, N>
+where
+  T1: Clone,
+  T2: Clone,
+  P: Clone,
+{
+  fn clone(&self) -> Self {
+    Self {
+      first: self.first.clone(),
+      keep: self.keep.clone(),
+      second: self.second.clone(),
+      sentinel: self.sentinel,
+      _strategy: PhantomData,
+    }
+  }
+}
+
+impl<T1, T2, P, ES1, ES2, const K: usize, const N: usize> std::fmt::Debug
+  for Staged<T1, T2, P, ES1, ES2, K, N>
+{
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("Staged")
+      .field("sentinel", &self.sentinel)
+      .finish_non_exhaustive()
+  }
+}
+
 #[cfg(test)]
 mod tests {
+  use std::sync::OnceLock;
+
   use super::*;
+  #[cfg(feature = "parallel")]
+  use crate::operator::{ParAuto, ParBatch, ParEach};
 
   type Solution = f32;
 
@@ -196,8 +1088,12 @@ mod tests {
   fn test_test_from_closure() {
     let test = |v: &Solution| [v * 1.0, v * 2.0, v * 3.0];
     takes_tester(&test);
+    #[cfg(feature = "parallel")]
     takes_tester(&test.par_each());
+    #[cfg(feature = "parallel")]
     takes_tester(&test.par_batch());
+    #[cfg(feature = "parallel")]
+    takes_tester(&test.par_auto());
   }
 
   #[test]
@@ -207,7 +1103,9 @@ mod tests {
     let f3 = |v: &Solution| v * 3.0;
     let test = [f1, f2, f3];
     takes_tester(&test);
+    #[cfg(feature = "parallel")]
     takes_tester(&test.par_each());
+    #[cfg(feature = "parallel")]
     takes_tester(&test.par_batch());
   }
 
@@ -219,6 +1117,44 @@ mod tests {
     takes_tester(&tester);
   }
 
+  #[cfg(feature = "parallel")]
+  #[test]
+  fn test_batch_test() {
+    use std::sync::Mutex;
+
+    let batch_sizes = Mutex::new(Vec::new());
+    let test = |solutions: &[Solution]| {
+      batch_sizes.lock().unwrap().push(solutions.len());
+      solutions.iter().map(|v| [v * 2.0]).collect()
+    };
+    let tester = test.par_batch();
+    takes_tester(&tester);
+
+    let solutions: Vec<_> = (0..100).map(|i| i as Solution).collect();
+    let scores = tester.execute_tests(&solutions);
+    assert_eq!(
+      scores,
+      solutions.iter().map(|v| [v * 2.0]).collect::<Vec<_>>()
+    );
+    // solutions are split into chunks per thread
+    let batch_sizes = batch_sizes.into_inner().unwrap();
+    assert_eq!(batch_sizes.iter().sum::<usize>(), 100);
+    let chunk_size = crate::execution::batch_size(100);
+    assert!(batch_sizes.iter().all(|&len| len <= chunk_size));
+
+    // a chunk with `1.0` gets one score too many, so they are discarded
+    let test = |solutions: &[Solution]| {
+      let mut scores: Vec<_> = solutions.iter().map(|v| [*v]).collect();
+      if solutions.contains(&1.0) {
+        scores.push([0.0]);
+      }
+      scores
+    };
+    let scores = test.par_batch().execute_tests(&[1.0, 2.0, 3.0]);
+    assert!(scores.len() < 3);
+    assert!(!scores.contains(&[1.0]));
+  }
+
   #[test]
   fn test_custom_test() {
     #[derive(Clone, Copy)]
@@ -231,7 +1167,9 @@ mod tests {
 
     let test = CustomTest {};
     takes_tester(&test);
+    #[cfg(feature = "parallel")]
     takes_tester(&test.par_each());
+    #[cfg(feature = "parallel")]
     takes_tester(&test.par_batch());
   }
 
@@ -247,4 +1185,318 @@ mod tests {
     let tester = CustomTester {};
     takes_tester(&tester);
   }
+
+  #[test]
+  fn test_boxed_tester() {
+    let tester = BoxedTester::new(|v: &Solution| [v * 1.0, v * 2.0]);
+    takes_tester(&tester);
+    assert_eq!(tester.execute_tests(&[1.0, 2.0]), [[1.0, 2.0], [2.0, 4.0]]);
+    #[cfg(feature = "parallel")]
+    {
+      let tester = BoxedTester::new((|v: &Solution| [v * 1.0]).par_batch());
+      assert_eq!(tester.execute_tests(&[1.0, 2.0]), [[1.0], [2.0]]);
+    }
+  }
+
+  #[test]
+  fn test_indexed_random_test() {
+    use rand::Rng;
+
+    let test = |v: &Solution, rng: &mut SmallRng| [v + rng.gen::<f32>()];
+    let t = IndexedRandomTest::new(SplitRng::new(0), test);
+    let solutions = [0.0; 10];
+    let scores = t.test(&solutions);
+    assert_ne!(scores[0], scores[1]);
+    assert_ne!(scores, t.test(&solutions));
+    // a tester with the same seed repeats the generations
+    let t = IndexedRandomTest::new(SplitRng::new(0), test);
+    assert_eq!(t.test(&solutions), scores);
+    #[cfg(feature = "parallel")]
+    {
+      let t = IndexedRandomTest::new(SplitRng::new(0), test).par_each();
+      assert_eq!(t.test(&solutions), scores);
+    }
+    takes_tester(&t);
+  }
+
+  #[test]
+  fn test_scalarized() {
+    let test = |v: &Solution| [*v, v - 2.0];
+    let t = Scalarized::weighted_sum(test, [1.0, 0.5]);
+    takes_tester(&t);
+    #[cfg(feature = "parallel")]
+    takes_tester(&t.par_each());
+    assert_eq!(t.test(&1.0), [1.5]);
+    let t = Scalarized::chebyshev(test, [1.0, 3.0]);
+    assert_eq!(t.test(&1.0), [3.0]);
+    assert_eq!(t.expand(&[1.0, -1.0]), [[1.0, -1.0], [-1.0, -3.0]]);
+  }
+
+  #[test]
+  fn test_scalarization_nan() {
+    for scalarization in [Scalarization::WeightedSum, Scalarization::Chebyshev]
+    {
+      assert!(scalarization.apply(&[1.0, f32::NAN], &[1.0, 1.0]).is_nan());
+      assert!(scalarization.apply(&[f32::NAN, 1.0], &[1.0, 1.0]).is_nan());
+      assert!(scalarization.apply(&[1.0, 2.0], &[f32::NAN, 1.0]).is_nan());
+    }
+  }
+
+  #[test]
+  fn test_fitness_sharing() {
+    let test = |v: &Solution| [*v, 4.0 - v];
+    let t = FitnessSharing::new(test, 2.0, 1.0);
+    takes_tester(&t);
+    // `1.0` and `2.0` are `sqrt(2)` apart, `4.0` is alone
+    let counts = t.niche_counts(&[[1.0, 3.0], [2.0, 2.0], [4.0, 0.0]]);
+    let shared = 1.0 + (1.0 - 2.0f32.sqrt() / 2.0);
+    assert_eq!(counts, [shared, shared, 1.0]);
+    let scores = t.test(&[1.0, 2.0, 4.0]);
+    assert_eq!(scores, [
+      [shared, 3.0 * shared],
+      [2.0 * shared, 2.0 * shared],
+      [4.0, 0.0]
+    ]);
+    // NaN scores are neither shared nor hidden
+    let counts = t.niche_counts(&[[f32::NAN, 0.0], [0.0, 0.0]]);
+    assert_eq!(counts, [1.0, 1.0]);
+    #[cfg(feature = "parallel")]
+    {
+      let t = FitnessSharing::new(test.par_batch(), 2.0, 1.0);
+      assert_eq!(t.test(&[1.0, 2.0, 4.0]), scores);
+    }
+  }
+
+  #[test]
+  fn test_chebyshev_finds_non_convex_front() {
+    // the front `f2 = 1 - f1^2` is concave, so weighted sum is minimized
+    // only at its ends
+    let test = |x: &Solution| [*x, 1.0 - x * x];
+    let solutions: Vec<_> = (0..=100).map(|i| i as Solution / 100.0).collect();
+    let best = |t: &Scalarized<_, 2>| {
+      *solutions
+        .iter()
+        .min_by(|a, b| t.test(*a)[0].total_cmp(&t.test(*b)[0]))
+        .unwrap()
+    };
+    for weights in [[0.5, 0.5], [0.4, 0.6], [0.6, 0.4]] {
+      let x = best(&Scalarized::weighted_sum(test, weights));
+      assert!(x == 0.0 || x == 1.0);
+    }
+    // the point where both weighted objectives are equal
+    let x = best(&Scalarized::chebyshev(test, [0.5, 0.5]));
+    assert!((x - 0.62).abs() < 0.011);
+  }
+
+  #[test]
+  #[cfg(feature = "parallel")]
+  fn test_par_batch_tests_each_solution_once() {
+    let test = |v: &Solution| [v * 2.0];
+    let batch_test =
+      |vs: &[Solution]| vs.iter().map(|v| [v * 2.0]).collect::<Vec<_>>();
+    crate::execution::for_uneven_batches(|len| {
+      let solutions: Vec<_> = (0..len).map(|i| i as Solution).collect();
+      let expected: Vec<_> = solutions.iter().map(test).collect();
+      assert_eq!(test.par_batch().execute_tests(&solutions), expected);
+      assert_eq!(batch_test.par_batch().execute_tests(&solutions), expected);
+    });
+  }
+
+  #[test]
+  #[cfg(feature = "parallel")]
+  fn test_par_batch_tests_until_deadline() {
+    use std::time::{Duration, Instant};
+
+    // the second solution is pathological, so the deadline expires while it
+    // is tested
+    let test = |v: &Solution| {
+      if *v == 1.0 {
+        std::thread::sleep(Duration::from_millis(50));
+      }
+      [v * 2.0]
+    };
+    let batch_test =
+      |vs: &[Solution]| vs.iter().map(|v| [v * 2.0]).collect::<Vec<_>>();
+    let solutions = [0.0, 1.0, 2.0, 3.0];
+    let pool = rayon::ThreadPoolBuilder::new()
+      .num_threads(1)
+      .build()
+      .unwrap();
+    pool.install(|| {
+      let deadline = Instant::now() + Duration::from_millis(10);
+      let scores = test.par_batch().execute_tests_until(&solutions, deadline);
+      assert_eq!(scores, [Some([0.0]), Some([2.0]), None, None]);
+
+      let deadline = Instant::now() + Duration::from_secs(60);
+      let scores = batch_test
+        .par_batch()
+        .execute_tests_until(&solutions, deadline);
+      assert_eq!(scores, [Some([0.0]), Some([2.0]), Some([4.0]), Some([6.0])]);
+      let scores = batch_test
+        .par_batch()
+        .execute_tests_until(&solutions, Instant::now());
+      assert_eq!(scores, [None; 4]);
+    });
+    // testers that don't check the deadline test everything
+    let scores = (|v: &Solution| [v * 2.0])
+      .execute_tests_until(&solutions[..1], Instant::now());
+    assert_eq!(scores, [Some([0.0])]);
+  }
+
+  #[test]
+  fn test_cached_test() {
+    let t = CachedTest::new(|x: &f32| [*x, -x], |x: &f32| x.to_bits().into());
+    let copy = t.clone();
+    assert_eq!(t.test(&[1.0, 2.0, 1.0]), [[1.0, -1.0], [2.0, -2.0], [
+      1.0, -1.0
+    ]]);
+    assert_eq!(t.test(&[2.0, 3.0]), [[2.0, -2.0], [3.0, -3.0]]);
+    assert_eq!(copy.evaluations(), 3);
+    assert_eq!(copy.len(), 3);
+    copy.clear();
+    t.test(&[2.0]);
+    assert_eq!(t.evaluations(), 4);
+
+    let mismatched = CachedTest::new(
+      |xs: &[f32]| vec![[0.0]; xs.len() + 1],
+      |x: &f32| x.to_bits().into(),
+    );
+    assert!(mismatched.test(&[1.0]).is_empty());
+    assert!(mismatched.is_empty());
+  }
+
+  #[test]
+  fn test_cached_test_cleared_while_testing() {
+    let shared = Arc::new(OnceLock::<Arc<Mutex<Cache<1>>>>::new());
+    let clearing = Arc::clone(&shared);
+    let t = CachedTest::new(
+      move |x: &f32| {
+        // another clone clears the cache while a solution is tested
+        if let Some(cache) = clearing.get() {
+          cache.lock().unwrap().scores.clear();
+        }
+        [*x]
+      },
+      |x: &f32| x.to_bits().into(),
+    );
+    t.test(&[1.0]);
+    shared.set(Arc::clone(&t.cache)).unwrap();
+    assert_eq!(t.test(&[1.0, 2.0, 2.0]), [[1.0], [2.0], [2.0]]);
+    assert_eq!(t.evaluations(), 2);
+  }
+
+  #[cfg(feature = "serde_json")]
+  #[test]
+  fn test_cached_test_persistence() {
+    use crate::{
+      optimizer::{nsga::Nsga2, Optimizer},
+      selection::FirstSelector,
+      termination::GenerationTerminator,
+    };
+
+    let optimize = |tester| {
+      Nsga2::builder()
+        .population(vec![0.0, 1.0, 4.0, 9.0])
+        .tester(tester)
+        .selector(FirstSelector(3))
+        .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+        .mutator(|_: &mut f32| {})
+        .terminator(GenerationTerminator(5))
+        .build()
+        .optimize()
+    };
+    let test = |x: &f32| [x.powf(2.0), (x - 2.0).powf(2.0)];
+    let key = |x: &f32| u64::from(x.to_bits());
+
+    let first = CachedTest::new(test, key);
+    let solutions = optimize(first.clone());
+    assert!(first.evaluations() > 4);
+    let mut saved = Vec::new();
+    first.save(&mut saved).unwrap();
+
+    let second = CachedTest::new(test, key);
+    assert_eq!(second.load(saved.as_slice()).unwrap(), first.len());
+    assert_eq!(optimize(second.clone()), solutions);
+    assert_eq!(second.evaluations(), 0);
+
+    let json = String::from_utf8(saved).unwrap();
+    let outdated = json.replace(r#""version":1"#, r#""version":0"#);
+    let err = second.load(outdated.as_bytes()).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    let err = CachedTest::new(|x: &f32| [*x], key)
+      .load(json.as_bytes())
+      .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+  }
+
+  #[test]
+  fn test_staged() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let simulations = AtomicUsize::new(0);
+    let t = Staged::new(
+      |x: &f32| [x.abs()],
+      |&[bound]: &[f32; 1]| bound < 2.0,
+      |xs: &[f32]| {
+        simulations.fetch_add(xs.len(), Ordering::Relaxed);
+        xs.iter().map(|x| [x.abs(), x * 2.0, x - 1.0]).collect()
+      },
+    );
+    assert_eq!(t.sentinel(), [f32::MAX; 3]);
+    let t = t.with_sentinel([0.0, 100.0, 200.0]);
+    assert_eq!(t.test(&[1.0, -3.0, 0.5, 4.0]), [
+      [1.0, 2.0, 0.0],
+      [3.0, 100.0, 200.0],
+      [0.5, 1.0, -0.5],
+      [4.0, 100.0, 200.0]
+    ]);
+    assert_eq!(simulations.load(Ordering::Relaxed), 2);
+
+    // nothing is tested by the second stage if nothing is kept
+    assert_eq!(t.test(&[5.0]), [[5.0, 100.0, 200.0]]);
+    assert_eq!(simulations.load(Ordering::Relaxed), 2);
+    assert!(t.test(&[]).is_empty());
+  }
+
+  #[test]
+  fn test_staged_score_count_mismatch() {
+    let t = Staged::new(
+      |x: &f32| [*x],
+      |_: &[f32; 1]| true,
+      |xs: &[f32]| vec![[0.0, 0.0]; xs.len() + 1],
+    );
+    assert!(t.test(&[1.0, 2.0]).is_empty());
+  }
+
+  #[test]
+  #[should_panic(expected = "sentinel scores must be finite")]
+  fn test_staged_nan_sentinel() {
+    Staged::new(|x: &f32| [*x], |_: &[f32; 1]| true, |x: &f32| [*x])
+      .with_sentinel([f32::NAN]);
+  }
+
+  #[test]
+  fn test_staged_optimization() {
+    use crate::{
+      optimizer::{nsga::Nsga2, Optimizer},
+      selection::RandomSelector,
+      termination::GenerationTerminator,
+    };
+
+    let solutions = Nsga2::builder()
+      .population((0..20).map(|i| i as f32 - 10.0).collect())
+      .tester(Staged::new(
+        |x: &f32| [x.abs()],
+        |&[bound]: &[f32; 1]| bound < 5.0,
+        |x: &f32| [x.abs(), (x - 2.0).abs()],
+      ))
+      .selector(RandomSelector(10))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .terminator(GenerationTerminator(10))
+      .build()
+      .optimize();
+    // killed solutions are dominated by solutions between 0 and 2
+    assert!(solutions.iter().all(|x| (0.0..=2.0).contains(x)));
+  }
 }
@@ -0,0 +1,172 @@
+//! Parallel execution of test operators.
+
+use std::time::Instant;
+
+use rayon::prelude::*;
+
+use super::{executor::TestExecutor, BatchTest, Test};
+use crate::{
+  execution::{batch_size, strategy::*},
+  operator::{
+    tag::{BatchTestOperatorTag, TestOperatorTag},
+    AutoDecision,
+    ParAuto,
+    ParAutoOperator,
+    ParBatch,
+    ParBatchOperator,
+    ParEach,
+    ParEachOperator,
+  },
+  score::Scores,
+};
+
+impl<S, const N: usize, T> ParEach<TestOperatorTag, S, N, 0> for T
+where
+  S: Sync,
+  T: Test<S, N> + Sync,
+{
+}
+
+impl<S, const N: usize, T> ParBatch<TestOperatorTag, S, N> for T
+where
+  S: Sync,
+  T: Test<S, N> + Sync,
+{
+}
+
+impl<S, const N: usize, T> ParAuto<TestOperatorTag, S, N> for T
+where
+  S: Sync,
+  T: Test<S, N> + Sync,
+{
+}
+
+impl<S, const N: usize, T> ParBatch<BatchTestOperatorTag, S, N> for T
+where
+  S: Sync,
+  T: BatchTest<S, N> + Sync,
+{
+}
+
+impl<const N: usize, S, T> TestExecutor<S, N, ParallelEachExecutionStrategy>
+  for ParEachOperator<TestOperatorTag, S, T>
+where
+  S: Sync,
+  T: Test<S, N> + Sync,
+{
+  fn execute_tests(&self, solutions: &[S]) -> Vec<Scores<N>> {
+    solutions
+      .par_iter()
+      .map(|s| self.operator().test(s))
+      .collect()
+  }
+}
+
+impl<const N: usize, S, T> TestExecutor<S, N, ParallelBatchExecutionStrategy>
+  for ParBatchOperator<TestOperatorTag, S, T>
+where
+  S: Sync,
+  T: Test<S, N> + Sync,
+{
+  fn execute_tests(&self, solutions: &[S]) -> Vec<Scores<N>> {
+    let chunk_size = batch_size(solutions.len());
+    solutions
+      .par_chunks(chunk_size)
+      .flat_map_iter(|chunk| chunk.iter().map(|s| self.operator().test(s)))
+      .collect()
+  }
+
+  fn execute_tests_until(
+    &self,
+    solutions: &[S],
+    deadline: Instant,
+  ) -> Vec<Option<Scores<N>>> {
+    let chunk_size = batch_size(solutions.len());
+    // the deadline is checked between solutions of each chunk, so a chunk
+    // overruns it by at most one test
+    solutions
+      .par_chunks(chunk_size)
+      .flat_map_iter(|chunk| {
+        chunk
+          .iter()
+          .map(|s| (Instant::now() < deadline).then(|| self.operator().test(s)))
+      })
+      .collect()
+  }
+}
+
+impl<const N: usize, S, T> TestExecutor<S, N, ParallelBatchExecutionStrategy>
+  for ParBatchOperator<BatchTestOperatorTag, S, T>
+where
+  S: Sync,
+  T: BatchTest<S, N> + Sync,
+{
+  fn execute_tests(&self, solutions: &[S]) -> Vec<Scores<N>> {
+    let chunk_size = batch_size(solutions.len());
+    let chunk_scores: Vec<_> = solutions
+      .par_chunks(chunk_size)
+      .map(|chunk| (chunk.len(), self.operator().test_batch(chunk)))
+      .collect();
+    // scores of a mismatched chunk can't be paired with its solutions, so
+    // they are discarded and the total number of scores mismatches, too
+    chunk_scores
+      .into_iter()
+      .filter(|(len, scores)| scores.len() == *len)
+      .flat_map(|(_, scores)| scores)
+      .collect()
+  }
+
+  fn execute_tests_until(
+    &self,
+    solutions: &[S],
+    deadline: Instant,
+  ) -> Vec<Option<Scores<N>>> {
+    let chunk_size = batch_size(solutions.len());
+    // a chunk is tested at once, so only chunks that start after the
+    // deadline are left untested
+    let chunk_scores: Vec<_> = solutions
+      .par_chunks(chunk_size)
+      .map(|chunk| {
+        let scores = (Instant::now() < deadline)
+          .then(|| self.operator().test_batch(chunk));
+        (chunk.len(), scores)
+      })
+      .collect();
+    chunk_scores
+      .into_iter()
+      .flat_map(|(len, scores)| match scores {
+        Some(scores) if scores.len() == len => {
+          scores.into_iter().map(Some).collect()
+        }
+        Some(_) => Vec::new(),
+        None => vec![None; len],
+      })
+      .collect()
+  }
+}
+
+impl<const N: usize, S, T> TestExecutor<S, N, AutoExecutionStrategy>
+  for ParAutoOperator<TestOperatorTag, S, T>
+where
+  S: Sync,
+  T: Test<S, N> + Sync,
+{
+  fn execute_tests(&self, solutions: &[S]) -> Vec<Scores<N>> {
+    let mut scores = Vec::with_capacity(solutions.len());
+    let (tested, decision) = self.plan(solutions.len(), |sample| {
+      scores.extend(solutions[..sample].iter().map(|s| self.operator().test(s)))
+    });
+    let rest = &solutions[tested..];
+    match decision {
+      AutoDecision::Sequential => {
+        scores.extend(rest.iter().map(|s| self.operator().test(s)))
+      }
+      AutoDecision::Batch => scores.par_extend(
+        rest
+          .par_chunks(batch_size(rest.len()))
+          .flat_map_iter(|chunk| chunk.iter().map(|s| self.operator().test(s))),
+      ),
+    }
+    scores
+  }
+}
@@ -0,0 +1,421 @@
+//! Evaluation of solutions in worker processes.
+
+use std::{
+  ffi::OsString,
+  io::{Read, Write},
+  marker::PhantomData,
+  process::{Child, Command, ExitStatus, Stdio},
+  sync::{
+    atomic::{AtomicUsize, Ordering},
+    Mutex,
+  },
+  thread::{self, JoinHandle},
+  time::{Duration, Instant},
+};
+
+use serde::Serialize;
+
+use super::{TestFailure, Tester};
+use crate::score::{Score, Scores};
+
+/// How often a worker process is checked for completion while it's timed.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+/// A [`Tester`] that evaluates solutions in worker processes, such as a
+/// Python simulator. Solutions are split into batches, and each batch is
+/// written as a JSON array to the standard input of a new process of the
+/// worker command, which must print a JSON array of scores arrays, one per
+/// solution in the same order, to its standard output. `null` scores are
+/// read as NaNs. Up to [`workers`](Self::workers) processes run at once.
+///
+/// If a worker can't be started, exits unsuccessfully, prints malformed
+/// scores or outlives the [`timeout`](Self::timeout), the test raises a
+/// [`TestFailure`], and optimization fails with
+/// [`OptimizationError::TestFailed`] telling what has happened, including
+/// what the worker has written to its standard error. The worker is killed
+/// on timeout, but processes it has spawned itself are not.
+///
+/// Requires `serde_json` feature.
+///
+/// # Examples
+/// ```no_run
+/// use std::time::Duration;
+///
+/// use moga::{
+///   optimizer::{nsga::Nsga2, Optimizer},
+///   selection::RandomSelector,
+///   termination::GenerationTerminator,
+///   testing::SubprocessTester,
+/// };
+///
+/// // `simulate.py` reads solutions, e.g. `[[0.5, 1.0], [2.0, 0.1]]`, and
+/// // prints their scores, e.g. `[[1.2, 3.4], [0.7, 8.1]]`
+/// let tester = SubprocessTester::<[f32; 2], 2>::new("python3")
+///   .arg("simulate.py")
+///   .workers(8)
+///   .timeout(Duration::from_secs(60));
+/// let solutions = Nsga2::builder()
+///   .population(vec![[0.5, 1.0]; 100])
+///   .tester(tester)
+///   .selector(RandomSelector(50))
+///   .recombinator(|a: &[f32; 2], b: &[f32; 2]| [a[0], b[1]])
+///   .mutator(|s: &mut [f32; 2]| s[0] += 0.1)
+///   .terminator(GenerationTerminator(100))
+///   .build()
+///   .optimize();
+/// ```
+///
+/// [`OptimizationError::TestFailed`]:
+/// crate::optimizer::OptimizationError::TestFailed
+pub struct SubprocessTester<S, const N: usize> {
+  program: OsString,
+  args: Vec<OsString>,
+  workers: usize,
+  batch_size: Option<usize>,
+  timeout: Option<Duration>,
+  _solution: PhantomData<fn(&S)>,
+}
+
+impl<S, const N: usize> SubprocessTester<S, N> {
+  /// Creates a new `SubprocessTester` that runs the `program` as a worker,
+  /// one process at a time, without a timeout.
+  pub fn new(program: impl Into<OsString>) -> Self {
+    Self {
+      program: program.into(),
+      args: Vec::new(),
+      workers: 1,
+      batch_size: None,
+      timeout: None,
+      _solution: PhantomData,
+    }
+  }
+
+  /// Adds an argument the worker program is run with.
+  pub fn arg(mut self, arg: impl Into<OsString>) -> Self {
+    self.args.push(arg.into());
+    self
+  }
+
+  /// Adds arguments the worker program is run with.
+  pub fn args(
+    mut self,
+    args: impl IntoIterator<Item = impl Into<OsString>>,
+  ) -> Self {
+    self.args.extend(args.into_iter().map(Into::into));
+    self
+  }
+
+  /// Sets the maximum number of worker processes that run at once.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `workers` is `0`.
+  pub fn workers(mut self, workers: usize) -> Self {
+    if workers == 0 {
+      panic!("number of workers cannot be 0")
+    }
+    self.workers = workers;
+    self
+  }
+
+  /// Sets the maximum number of solutions a worker process is given. By
+  /// default, solutions are split evenly between the workers, so that each
+  /// worker runs once per generation.
+  ///
+  /// # Panics
+  ///
+  /// Panics if `batch_size` is `0`.
+  pub fn batch_size(mut self, batch_size: usize) -> Self {
+    if batch_size == 0 {
+      panic!("batch size cannot be 0")
+    }
+    self.batch_size = Some(batch_size);
+    self
+  }
+
+  /// Sets how long a worker process may run before it's killed and the test
+  /// fails.
+  pub fn timeout(mut self, timeout: Duration) -> Self {
+    self.timeout = Some(timeout);
+    self
+  }
+
+  /// Evaluates scores of the `batch` in a new worker process.
+  fn evaluate(&self, batch: &[S]) -> Result<Vec<Scores<N>>, String>
+  where
+    S: Serialize,
+  {
+    let input = serde_json::to_vec(batch)
+      .map_err(|e| format!("failed to serialize solutions: {e}"))?;
+    let program = self.program.to_string_lossy();
+    let mut child = Command::new(&self.program)
+      .args(&self.args)
+      .stdin(Stdio::piped())
+      .stdout(Stdio::piped())
+      .stderr(Stdio::piped())
+      .spawn()
+      .map_err(|e| format!("failed to start worker `{program}`: {e}"))?;
+    // pipes are served by their own threads, so that neither the worker nor
+    // the tester blocks on a full pipe, and they are abandoned if the worker
+    // times out
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let writer = thread::spawn(move || stdin.write_all(&input));
+    let stdout = read_to_end(child.stdout.take().expect("stdout is piped"));
+    let stderr = read_to_end(child.stderr.take().expect("stderr is piped"));
+
+    let status = self.wait(&mut child).map_err(|e| match e {
+      Some(e) => format!("failed to wait for worker `{program}`: {e}"),
+      None => format!(
+        "worker `{program}` has timed out after {:?}",
+        self.timeout.unwrap_or_default()
+      ),
+    })?;
+    let stderr = String::from_utf8_lossy(&stderr.join().unwrap_or_default())
+      .trim()
+      .to_string();
+    if !status.success() {
+      return Err(format!(
+        "worker `{program}` has exited with {status}: {stderr}"
+      ));
+    }
+    // a worker may exit without reading all of its input, which is fine as
+    // long as it prints all scores
+    let _ = writer.join();
+    let output = stdout.join().unwrap_or_default();
+    parse_scores(&output, batch.len())
+      .map_err(|e| format!("worker `{program}` has printed {e}"))
+  }
+
+  /// Waits for the `child` to exit. Kills it and returns `Err(None)` if it
+  /// doesn't exit in time.
+  fn wait(
+    &self,
+    child: &mut Child,
+  ) -> Result<ExitStatus, Option<std::io::Error>> {
+    let Some(timeout) = self.timeout else {
+      return child.wait().map_err(Some);
+    };
+    let deadline = Instant::now() + timeout;
+    loop {
+      if let Some(status) = child.try_wait().map_err(Some)? {
+        return Ok(status);
+      }
+      let now = Instant::now();
+      if now >= deadline {
+        let _ = child.kill();
+        let _ = child.wait();
+        return Err(None);
+      }
+      thread::sleep(POLL_INTERVAL.min(deadline - now));
+    }
+  }
+}
+
+// not derived, so that solutions don't have to implement these traits
+impl<S, const N: usize> Clone for SubprocessTester<S, N> {
+  fn clone(&self) -> Self {
+    Self {
+      program: self.program.clone(),
+      args: self.args.clone(),
+      _solution: PhantomData,
+      ..*self
+    }
+  }
+}
+
+impl<S, const N: usize> std::fmt::Debug for SubprocessTester<S, N> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("SubprocessTester")
+      .field("program", &self.program)
+      .field("args", &self.args)
+      .field("workers", &self.workers)
+      .field("batch_size", &self.batch_size)
+      .field("timeout", &self.timeout)
+      .finish()
+  }
+}
+
+impl<S: Serialize + Sync, const N: usize> Tester<S, N>
+  for SubprocessTester<S, N>
+{
+  fn test(&self, solutions: &[S]) -> Vec<Scores<N>> {
+    if solutions.is_empty() {
+      return Vec::new();
+    }
+    let batch_size = self
+      .batch_size
+      .unwrap_or_else(|| solutions.len().div_ceil(self.workers));
+    let batches: Vec<_> = solutions.chunks(batch_size).collect();
+    let results: Vec<_> = batches.iter().map(|_| Mutex::new(None)).collect();
+    let next = AtomicUsize::new(0);
+    // each worker thread takes the next batch until none are left
+    thread::scope(|scope| {
+      for _ in 0..self.workers.min(batches.len()) {
+        scope.spawn(|| loop {
+          let idx = next.fetch_add(1, Ordering::Relaxed);
+          let Some(batch) = batches.get(idx) else {
+            break;
+          };
+          let result = self.evaluate(batch);
+          let is_err = result.is_err();
+          *results[idx].lock().unwrap() = Some(result);
+          // the test fails anyway, so the rest of batches are skipped
+          if is_err {
+            next.store(batches.len(), Ordering::Relaxed);
+          }
+        });
+      }
+    });
+
+    let mut scores = Vec::with_capacity(solutions.len());
+    for result in results {
+      match result.into_inner().unwrap() {
+        Some(Ok(batch_scores)) => scores.extend(batch_scores),
+        Some(Err(message)) => TestFailure::raise(message),
+        // skipped after another batch has failed
+        None => {}
+      }
+    }
+    scores
+  }
+}
+
+/// Reads the `pipe` to its end in a new thread.
+fn read_to_end(mut pipe: impl Read + Send + 'static) -> JoinHandle<Vec<u8>> {
+  thread::spawn(move || {
+    let mut buffer = Vec::new();
+    let _ = pipe.read_to_end(&mut buffer);
+    buffer
+  })
+}
+
+/// Parses scores of `expected` solutions from JSON `output`. Returns what's
+/// wrong with the output otherwise.
+fn parse_scores<const N: usize>(
+  output: &[u8],
+  expected: usize,
+) -> Result<Vec<Scores<N>>, String> {
+  let parsed: Vec<Vec<Option<Score>>> = serde_json::from_slice(output)
+    .map_err(|e| format!("malformed scores: {e}"))?;
+  if parsed.len() != expected {
+    return Err(format!(
+      "scores of {} solutions instead of {expected}",
+      parsed.len()
+    ));
+  }
+  parsed
+    .into_iter()
+    .enumerate()
+    .map(|(idx, scores)| {
+      let scores: Vec<_> = scores
+        .into_iter()
+        .map(|s| s.unwrap_or(Score::NAN))
+        .collect();
+      Scores::<N>::try_from(scores).map_err(|scores| {
+        format!("{} scores instead of {N} for solution {idx}", scores.len())
+      })
+    })
+    .collect()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+  use super::*;
+  use crate::{
+    optimizer::{nsga::Nsga2, OptimizationError, Optimizer},
+    selection::AllSelector,
+    termination::GenerationTerminator,
+  };
+
+  /// A worker that prints its input back, so solutions of scores arrays are
+  /// evaluated as themselves.
+  fn echo() -> SubprocessTester<[f32; 2], 2> {
+    SubprocessTester::new("sh").args(["-c", "cat"])
+  }
+
+  /// Returns the message of the `TestFailure` the `test` raises.
+  fn failure(test: impl FnOnce() + std::panic::UnwindSafe) -> String {
+    let payload = std::panic::catch_unwind(test).unwrap_err();
+    payload.downcast::<TestFailure>().unwrap().message
+  }
+
+  #[test]
+  fn test_evaluates_batches_in_order() {
+    let solutions: Vec<_> = (0..10).map(|i| [i as f32, -i as f32]).collect();
+    assert_eq!(echo().test(&solutions), solutions);
+    assert_eq!(echo().workers(3).test(&solutions), solutions);
+    assert_eq!(echo().workers(2).batch_size(3).test(&solutions), solutions);
+    assert!(echo().test(&[]).is_empty());
+  }
+
+  #[test]
+  fn test_null_scores_are_nans() {
+    let tester = SubprocessTester::<u8, 2>::new("sh")
+      .args(["-c", "cat > /dev/null; echo '[[1.0, null]]'"]);
+    let scores = tester.test(&[0]);
+    assert_eq!(scores[0][0], 1.0);
+    assert!(scores[0][1].is_nan());
+  }
+
+  #[test]
+  fn test_failures() {
+    let message = failure(|| {
+      SubprocessTester::<u8, 1>::new("/nonexistent/worker").test(&[0]);
+    });
+    assert!(message.starts_with("failed to start worker"), "{message}");
+
+    let message = failure(|| {
+      SubprocessTester::<u8, 1>::new("sh")
+        .args(["-c", "echo 'out of memory' >&2; exit 3"])
+        .test(&[0]);
+    });
+    assert!(message.contains("exit status: 3"), "{message}");
+    assert!(message.ends_with("out of memory"), "{message}");
+
+    let message = failure(|| {
+      SubprocessTester::<u8, 1>::new("sh")
+        .args(["-c", "echo '[[1.0]]'"])
+        .test(&[0, 1]);
+    });
+    assert!(message.ends_with("scores of 1 solutions instead of 2"));
+
+    let message = failure(|| {
+      SubprocessTester::<u8, 2>::new("sh")
+        .args(["-c", "echo '[[1.0]]'"])
+        .test(&[0]);
+    });
+    assert!(message.ends_with("1 scores instead of 2 for solution 0"));
+
+    let message = failure(|| {
+      SubprocessTester::<u8, 1>::new("sh")
+        .args(["-c", "echo 'nope'"])
+        .test(&[0]);
+    });
+    assert!(message.contains("malformed scores"), "{message}");
+  }
+
+  #[test]
+  fn test_timeout() {
+    let tester = SubprocessTester::<[f32; 1], 1>::new("sh")
+      .args(["-c", "exec sleep 10"])
+      .timeout(Duration::from_millis(100));
+    let start = Instant::now();
+    let result = Nsga2::builder()
+      .population(vec![[1.0]])
+      .tester(tester)
+      .selector(AllSelector())
+      .recombinator(|x: &[f32; 1]| *x)
+      .mutator(|_: &mut [f32; 1]| {})
+      .terminator(GenerationTerminator(1))
+      .build()
+      .try_optimize();
+    assert!(start.elapsed() < Duration::from_secs(5));
+    assert_eq!(
+      result,
+      Err(OptimizationError::TestFailed {
+        generation: 0,
+        message: "worker `sh` has timed out after 100ms".to_string(),
+      })
+    );
+  }
+}
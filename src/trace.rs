@@ -0,0 +1,107 @@
+//! Instrumentation of optimizers with `tracing` crate. Without `tracing`
+//! feature enabled, spans compile to nothing. Timers of phases for
+//! profiling are here, too.
+
+use std::time::Instant;
+
+use crate::observer::{OperatorTimings, PhaseTiming};
+
+/// Creates a [`Phase`] of the generation loop with the given name and fields.
+/// Fields without values are recorded later with [`Phase::record`]. Each
+/// phase also records its duration in microseconds to `elapsed_us` field.
+#[cfg(feature = "tracing")]
+macro_rules! phase {
+  (@value $value:expr) => {
+    $value
+  };
+  (@value) => {
+    tracing::field::Empty
+  };
+  ($name:literal $(, $field:ident $(= $value:expr)?)* $(,)?) => {
+    $crate::trace::Phase::new(tracing::debug_span!(
+      target: "moga",
+      $name,
+      $($field = $crate::trace::phase!(@value $($value)?),)*
+      elapsed_us = tracing::field::Empty,
+    ))
+  };
+}
+
+/// Creates a [`Phase`] of the generation loop with the given name and fields.
+/// Fields without values are recorded later with [`Phase::record`]. Each
+/// phase also records its duration in microseconds to `elapsed_us` field.
+#[cfg(not(feature = "tracing"))]
+macro_rules! phase {
+  ($name:literal $(, $field:ident $(= $value:expr)?)* $(,)?) => {
+    $crate::trace::Phase
+  };
+}
+
+pub(crate) use phase;
+
+/// An entered span of a phase of the generation loop. The span is exited
+/// when the phase is dropped.
+#[cfg(feature = "tracing")]
+pub(crate) struct Phase {
+  span: tracing::span::EnteredSpan,
+  start: std::time::Instant,
+}
+
+/// An entered span of a phase of the generation loop. The span is exited
+/// when the phase is dropped.
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct Phase;
+
+#[cfg(feature = "tracing")]
+impl Phase {
+  pub(crate) fn new(span: tracing::Span) -> Self {
+    Self {
+      span: span.entered(),
+      start: std::time::Instant::now(),
+    }
+  }
+
+  /// Records a value of the phase's field.
+  pub(crate) fn record(&self, field: &str, value: impl tracing::Value) {
+    self.span.record(field, value);
+  }
+}
+
+#[cfg(not(feature = "tracing"))]
+impl Phase {
+  /// Records a value of the phase's field.
+  #[inline(always)]
+  pub(crate) fn record<T>(&self, _field: &str, _value: T) {}
+}
+
+#[cfg(feature = "tracing")]
+impl Drop for Phase {
+  fn drop(&mut self) {
+    let elapsed_us = self.start.elapsed().as_micros() as u64;
+    self.span.record("elapsed_us", elapsed_us);
+  }
+}
+
+/// A timer of a phase of the generation loop. Adds the time until it's
+/// dropped to the [`PhaseTiming`] of the phase, if timings are collected.
+/// Otherwise, it takes no time measurements.
+pub(crate) struct Timer<'a>(Option<(&'a mut PhaseTiming, Instant)>);
+
+impl<'a> Timer<'a> {
+  /// Starts timing the `phase` of `timings`.
+  pub(crate) fn start(
+    timings: &'a mut Option<OperatorTimings>,
+    phase: fn(&mut OperatorTimings) -> &mut PhaseTiming,
+  ) -> Self {
+    Self(timings.as_mut().map(|t| (phase(t), Instant::now())))
+  }
+}
+
+impl Drop for Timer<'_> {
+  fn drop(&mut self) {
+    if let Some((timing, start)) = self.0.take() {
+      timing.total += start.elapsed();
+      timing.calls += 1;
+    }
+  }
+}
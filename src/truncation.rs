@@ -0,0 +1,893 @@
+//! Truncation operators and utilities.
+
+use std::{cmp::Ordering, fmt::Debug};
+
+use rand::Rng;
+
+use crate::{
+  optimizer::{from_index, nsga::crowding_distances, to_index},
+  rng::SplitRng,
+  score::{
+    hypervolume_contributions,
+    sorting::{nondominated_sort_with, SortingBuffers},
+    ParetoDominance,
+    Scores,
+  },
+};
+
+/// An operator that truncates a population of parents and offsprings down to
+/// the size of the next generation, deciding which solutions survive.
+///
+/// [`Nsga2`] truncates populations with [`CrowdingTruncation`] unless given
+/// another truncation. Truncations may reuse given vectors and don't have to
+/// preserve the order of solutions, but each solution must stay paired with
+/// its scores.
+///
+/// # Examples
+/// ```
+/// // keeps solutions with the least sum of scores
+/// let t =
+///   |mut solutions: Vec<f32>, mut scores: Vec<[f32; 2]>, target: usize| {
+///     let mut pairs = solutions.into_iter().zip(scores).collect::<Vec<_>>();
+///     pairs.sort_by(|(_, a), (_, b)| {
+///       let (a, b) = (a[0].abs() + a[1].abs(), b[0].abs() + b[1].abs());
+///       a.total_cmp(&b)
+///     });
+///     pairs.truncate(target);
+///     pairs.into_iter().unzip()
+///   };
+/// # let _: (Vec<_>, Vec<_>) = t(vec![], vec![], 0);
+/// ```
+///
+/// **Note that you always can implement this trait instead of using closures.**
+///
+/// [`Nsga2`]: crate::optimizer::nsga::Nsga2
+/// [`CrowdingTruncation`]: crate::optimizer::nsga::CrowdingTruncation
+pub trait Truncation<S, const N: usize> {
+  /// Takes solutions and their respective scores. Returns at most `target`
+  /// surviving solutions and their scores.
+  ///
+  /// Optimization fails with [`OptimizationError::ScoreCountMismatch`] if
+  /// this function returns a different number of scores than the number of
+  /// solutions.
+  ///
+  /// [`OptimizationError::ScoreCountMismatch`]:
+  /// crate::optimizer::OptimizationError::ScoreCountMismatch
+  fn truncate(
+    &self,
+    solutions: Vec<S>,
+    scores: Vec<Scores<N>>,
+    target: usize,
+  ) -> (Vec<S>, Vec<Scores<N>>);
+
+  /// Moves the point of objective space that the truncation prefers
+  /// solutions close to, if it has one, to the `reference`. Called when a
+  /// pause handler returns [`Directive::AdjustReference`]. Does nothing by
+  /// default.
+  ///
+  /// [`Directive::AdjustReference`]: crate::pause::Directive::AdjustReference
+  fn adjust_reference(&mut self, reference: Scores<N>) {
+    let _ = reference;
+  }
+}
+
+impl<S, const N: usize, F> Truncation<S, N> for F
+where
+  F: Fn(Vec<S>, Vec<Scores<N>>, usize) -> (Vec<S>, Vec<Scores<N>>),
+{
+  fn truncate(
+    &self,
+    solutions: Vec<S>,
+    scores: Vec<Scores<N>>,
+    target: usize,
+  ) -> (Vec<S>, Vec<Scores<N>>) {
+    self(solutions, scores, target)
+  }
+}
+
+impl<S, const N: usize> Debug for dyn Truncation<S, N> + Send {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("Truncation")
+  }
+}
+
+/// A [`Truncation`] that keeps the solutions of the best fronts and removes
+/// solutions of the last front that fits one by one, each time removing the
+/// solution with the least exclusive hypervolume contribution, calculated
+/// against the `reference` point. Gives [`Nsga2`] the selection pressure of
+/// SMS-EMOA towards both convergence and spread of the front.
+///
+/// As contributions are recalculated after each removal, truncating a front
+/// of two objectives takes `O(n * log(n))` per removed solution. For more
+/// objectives, contributions are calculated as in WFG algorithm, which gets
+/// expensive quickly with the number of objectives and the size of the front.
+///
+/// Solutions whose scores don't dominate the `reference` point contribute
+/// nothing and are removed first, so the reference point should be worse than
+/// any solution you want to keep in every objective. See [`hypervolume`].
+///
+/// # Examples
+/// ```
+/// use moga::truncation::{HypervolumeTruncation, Truncation};
+///
+/// let t = HypervolumeTruncation {
+///   reference: [4.0, 4.0],
+/// };
+/// let solutions = vec!["a", "b", "c", "d"];
+/// let scores = vec![[0.0, 3.0], [1.0, 2.9], [2.0, 1.0], [3.0, 0.0]];
+/// let (solutions, _) = t.truncate(solutions, scores, 3);
+/// assert_eq!(solutions, ["a", "c", "d"]);
+/// ```
+///
+/// [`Nsga2`]: crate::optimizer::nsga::Nsga2
+/// [`hypervolume`]: crate::score::hypervolume
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct HypervolumeTruncation<const N: usize> {
+  /// The reference point hypervolume contributions are bounded by.
+  pub reference: Scores<N>,
+}
+
+impl<S, const N: usize> Truncation<S, N> for HypervolumeTruncation<N> {
+  fn truncate(
+    &self,
+    solutions: Vec<S>,
+    scores: Vec<Scores<N>>,
+    target: usize,
+  ) -> (Vec<S>, Vec<Scores<N>>) {
+    truncate_by_fronts(
+      solutions,
+      scores,
+      target,
+      |mut front, _, scores, count| {
+        while front.len() > count {
+          let front_scores =
+            front.iter().map(|&idx| scores[idx]).collect::<Vec<_>>();
+          let contributions =
+            hypervolume_contributions(&front_scores, &self.reference);
+          let least = (0..front.len())
+            .min_by(|&a, &b| contributions[a].total_cmp(&contributions[b]))
+            .expect("front must not be empty");
+          front.swap_remove(least);
+        }
+        front
+      },
+    )
+  }
+}
+
+/// A [`Truncation`] that steers the search towards trade-offs a decision
+/// maker is interested in, in the manner of R-NSGA-II. Keeps the solutions of
+/// the best fronts, and of the last front that fits it keeps:
+///
+/// 1. for each of reference `points`, the solution closest to its reference
+///    direction, if there is room for it. Points earlier in the list take
+///    precedence;
+/// 2. solutions that lie within `radius` of any reference direction, the
+///    least crowded first;
+/// 3. the least crowded of other solutions.
+///
+/// A reference direction is the ray from the ideal point, where all scores
+/// are `0`, through a reference point, and distances are perpendicular
+/// distances to it. As scores converge at `0`, absolute values of scores and
+/// of reference points are used, and each objective is scaled by the largest
+/// score of the population, so `radius` is relative to the extent of the
+/// population. Since only directions matter, reference points don't have to
+/// be feasible or nondominated: a point better than any solution steers the
+/// search just like a point dominated by the whole population does.
+///
+/// # Examples
+/// ```
+/// use moga::truncation::{ReferencePointTruncation, Truncation};
+///
+/// let t = ReferencePointTruncation {
+///   points: vec![[1.0, 4.0]],
+///   radius: 0.1,
+/// };
+/// let solutions = vec!["a", "b", "c", "d", "e"];
+/// let scores = vec![[0.0, 4.0], [1.0, 3.0], [2.0, 2.0], [3.0, 1.0], [4.0, 0.0]];
+/// let (solutions, _) = t.truncate(solutions, scores, 1);
+/// assert_eq!(solutions, ["b"]);
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct ReferencePointTruncation<const N: usize> {
+  /// Reference points that define preferred trade-offs.
+  pub points: Vec<Scores<N>>,
+  /// Perpendicular distance from reference directions within which solutions
+  /// are preferred, relative to the largest scores of the population.
+  pub radius: f32,
+}
+
+impl<S, const N: usize> Truncation<S, N> for ReferencePointTruncation<N> {
+  /// Replaces reference points with the `reference`.
+  fn adjust_reference(&mut self, reference: Scores<N>) {
+    self.points = vec![reference];
+  }
+
+  fn truncate(
+    &self,
+    solutions: Vec<S>,
+    scores: Vec<Scores<N>>,
+    target: usize,
+  ) -> (Vec<S>, Vec<Scores<N>>) {
+    truncate_by_fronts(solutions, scores, target, |front, _, scores, count| {
+      let mut scale = [0.0f64; N];
+      for sc in scores {
+        for (max, s) in scale.iter_mut().zip(sc) {
+          *max = max.max(f64::from(s.abs()));
+        }
+      }
+      let scale = scale.map(|s| if s > 0.0 { s } else { 1.0 });
+      let normalize = |sc: &Scores<N>| -> [f64; N] {
+        std::array::from_fn(|o| f64::from(sc[o].abs()) / scale[o])
+      };
+      let directions = self
+        .points
+        .iter()
+        .map(|p| unit(normalize(p)))
+        .collect::<Vec<_>>();
+      // distances of solutions of the front to each direction
+      let distances = front
+        .iter()
+        .map(|&idx| {
+          let p = normalize(&scores[idx]);
+          directions
+            .iter()
+            .map(|w| perpendicular_distance(&p, w.as_ref()))
+            .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+      let mut survivors = Vec::with_capacity(count);
+      let closest = (0..directions.len()).filter_map(|r| {
+        (0..front.len())
+          .min_by(|&a, &b| distances[a][r].total_cmp(&distances[b][r]))
+      });
+      for closest in closest {
+        if survivors.len() < count && !survivors.contains(&closest) {
+          survivors.push(closest);
+        }
+      }
+
+      let mut front_indices: Vec<_> =
+        front.iter().map(|&idx| to_index(idx)).collect();
+      let mut cds = Vec::new();
+      crowding_distances(&mut front_indices, scores, None, &mut cds);
+      let radius = f64::from(self.radius);
+      let is_outside = |i: usize| !distances[i].iter().any(|&d| d <= radius);
+      let mut rest = (0..front.len())
+        .filter(|i| !survivors.contains(i))
+        .collect::<Vec<_>>();
+      rest.sort_by(|&a, &b| {
+        is_outside(a)
+          .cmp(&is_outside(b))
+          .then(cds[front[b]].total_cmp(&cds[front[a]]))
+      });
+      survivors.extend(rest);
+      survivors.truncate(count);
+      survivors.into_iter().map(|i| front[i]).collect()
+    })
+  }
+}
+
+/// A measure of distance between solutions in decision space, as opposed to
+/// distance between their scores in objective space. Two very different
+/// solutions with the same scores are duplicates for objective space
+/// diversity mechanisms, but not for truncations that use a diversity measure,
+/// such as [`GenotypicCrowdingTruncation`] and [`GenotypicTieBreak`].
+///
+/// The distance must be symmetric and non-negative.
+///
+/// # Examples
+/// ```
+/// use moga::truncation::{DiversityMeasure, Hamming};
+///
+/// // number of differing characters
+/// let d = |a: &String, b: &String| {
+///   a.chars().zip(b.chars()).filter(|(a, b)| a != b).count() as f64
+/// };
+/// assert_eq!(d.distance(&"abc".to_string(), &"abd".to_string()), 1.0);
+/// assert_eq!(
+///   Hamming.distance(&vec![true, false], &vec![false, false]),
+///   1.0
+/// );
+/// ```
+///
+/// **Note that you always can implement this trait instead of using closures.**
+pub trait DiversityMeasure<S> {
+  /// Returns the distance between solutions `a` and `b`.
+  fn distance(&self, a: &S, b: &S) -> f64;
+}
+
+impl<S, F> DiversityMeasure<S> for F
+where
+  F: Fn(&S, &S) -> f64,
+{
+  fn distance(&self, a: &S, b: &S) -> f64 {
+    self(a, b)
+  }
+}
+
+/// A [`DiversityMeasure`] of real vectors: euclidean distance between them.
+/// Values of the longer vector beyond the length of the shorter one are
+/// ignored.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Euclidean;
+
+impl DiversityMeasure<Vec<f32>> for Euclidean {
+  fn distance(&self, a: &Vec<f32>, b: &Vec<f32>) -> f64 {
+    a.iter()
+      .zip(b)
+      .map(|(a, b)| f64::from(a - b).powi(2))
+      .sum::<f64>()
+      .sqrt()
+  }
+}
+
+/// A [`DiversityMeasure`] of binary vectors: the number of positions at which
+/// they differ. Values of the longer vector beyond the length of the shorter
+/// one are counted as differing.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Hamming;
+
+impl DiversityMeasure<Vec<bool>> for Hamming {
+  fn distance(&self, a: &Vec<bool>, b: &Vec<bool>) -> f64 {
+    let differing = a.iter().zip(b).filter(|(a, b)| a != b).count();
+    (differing + a.len().abs_diff(b.len())) as f64
+  }
+}
+
+/// A [`Truncation`] that keeps the solutions of the best fronts and removes
+/// solutions of the last front that fits one by one, each time removing the
+/// solution closest to another remaining solution of the front in decision
+/// space, as measured by the `measure`. Unlike [`CrowdingTruncation`], it
+/// preserves diverse solutions rather than diverse trade-offs.
+///
+/// Distances between all solutions of the last front are calculated, so
+/// truncating a front of `n` solutions takes `O(n^2)` distances.
+///
+/// # Examples
+/// ```
+/// use moga::truncation::{Euclidean, GenotypicCrowdingTruncation, Truncation};
+///
+/// let t = GenotypicCrowdingTruncation { measure: Euclidean };
+/// // all solutions have the same scores, but "a" and "b" are close designs
+/// let solutions = vec![vec![0.0, 0.0], vec![0.0, 0.1], vec![5.0, 5.0]];
+/// let scores = vec![[1.0, 1.0]; 3];
+/// let (solutions, _) = t.truncate(solutions, scores, 2);
+/// assert_eq!(solutions, [vec![0.0, 0.1], vec![5.0, 5.0]]);
+/// ```
+///
+/// [`CrowdingTruncation`]: crate::optimizer::nsga::CrowdingTruncation
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct GenotypicCrowdingTruncation<D> {
+  /// The measure of distance between solutions.
+  pub measure: D,
+}
+
+impl<S, D, const N: usize> Truncation<S, N> for GenotypicCrowdingTruncation<D>
+where
+  D: DiversityMeasure<S>,
+{
+  fn truncate(
+    &self,
+    solutions: Vec<S>,
+    scores: Vec<Scores<N>>,
+    target: usize,
+  ) -> (Vec<S>, Vec<Scores<N>>) {
+    truncate_by_fronts(
+      solutions,
+      scores,
+      target,
+      |front, solutions, _, count| {
+        let len = front.len();
+        let distances = pairwise_distances(&front, solutions, &self.measure);
+        let mut is_kept = vec![true; len];
+        // distance from each solution to its nearest kept neighbor
+        let nearest = |i: usize, is_kept: &[bool]| {
+          (0..len)
+            .filter(|&j| j != i && is_kept[j])
+            .map(|j| distances[i * len + j])
+            .min_by(f64::total_cmp)
+            .unwrap_or(f64::MAX)
+        };
+        let mut nearest_distances =
+          (0..len).map(|i| nearest(i, &is_kept)).collect::<Vec<_>>();
+        for _ in count..len {
+          let removed = (0..len)
+            .filter(|&i| is_kept[i])
+            .min_by(|&a, &b| {
+              nearest_distances[a].total_cmp(&nearest_distances[b])
+            })
+            .expect("front must not be empty");
+          is_kept[removed] = false;
+          // only solutions that were nearest to the removed one are affected
+          for i in 0..len {
+            if is_kept[i]
+              && distances[i * len + removed] == nearest_distances[i]
+            {
+              nearest_distances[i] = nearest(i, &is_kept);
+            }
+          }
+        }
+        front
+          .into_iter()
+          .zip(is_kept)
+          .filter_map(|(idx, is_kept)| is_kept.then_some(idx))
+          .collect()
+      },
+    )
+  }
+}
+
+/// A [`Truncation`] that truncates the population like [`CrowdingTruncation`]
+/// does, but breaks ties between solutions of the last front that fits with
+/// equal crowding distances by their diversity: solutions farther from their
+/// nearest neighbor of the front in decision space, as measured by the
+/// `measure`, are kept first. Ties are common among boundary solutions and
+/// solutions with equal scores, which crowding distance alone can't tell
+/// apart.
+///
+/// # Examples
+/// ```
+/// use moga::truncation::{GenotypicTieBreak, Hamming, Truncation};
+///
+/// let t = GenotypicTieBreak { measure: Hamming };
+/// let solutions = vec![vec![true, true, true], vec![true, true, false], vec![
+///   false, false, false,
+/// ]];
+/// // solutions are boundary solutions of both objectives
+/// let scores = vec![[1.0, 1.0]; 3];
+/// let (solutions, _) = t.truncate(solutions, scores, 1);
+/// assert_eq!(solutions, [vec![false, false, false]]);
+/// ```
+///
+/// [`CrowdingTruncation`]: crate::optimizer::nsga::CrowdingTruncation
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct GenotypicTieBreak<D> {
+  /// The measure of distance between solutions.
+  pub measure: D,
+}
+
+impl<S, D, const N: usize> Truncation<S, N> for GenotypicTieBreak<D>
+where
+  D: DiversityMeasure<S>,
+{
+  fn truncate(
+    &self,
+    solutions: Vec<S>,
+    scores: Vec<Scores<N>>,
+    target: usize,
+  ) -> (Vec<S>, Vec<Scores<N>>) {
+    truncate_by_fronts(
+      solutions,
+      scores,
+      target,
+      |front, solutions, scores, count| {
+        let len = front.len();
+        let distances = pairwise_distances(&front, solutions, &self.measure);
+        let nearest = (0..len)
+          .map(|i| {
+            (0..len)
+              .filter(|&j| j != i)
+              .map(|j| distances[i * len + j])
+              .min_by(f64::total_cmp)
+              .unwrap_or(f64::MAX)
+          })
+          .collect::<Vec<_>>();
+        let mut front_indices: Vec<_> =
+          front.iter().map(|&idx| to_index(idx)).collect();
+        let mut cds = Vec::new();
+        crowding_distances(&mut front_indices, scores, None, &mut cds);
+        let mut order = (0..len).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| {
+          cds[front[b]]
+            .total_cmp(&cds[front[a]])
+            .then(nearest[b].total_cmp(&nearest[a]))
+        });
+        order.truncate(count);
+        order.into_iter().map(|i| front[i]).collect()
+      },
+    )
+  }
+}
+
+/// Returns distances between solutions of the `front`, stored row after row,
+/// so that the distance between `i`-th and `j`-th solutions of the front is
+/// at `i * front.len() + j`.
+fn pairwise_distances<S>(
+  front: &[usize],
+  solutions: &[S],
+  measure: &impl DiversityMeasure<S>,
+) -> Vec<f64> {
+  let len = front.len();
+  let mut distances = vec![0.0; len * len];
+  for i in 0..len {
+    for j in i + 1..len {
+      let d = measure.distance(&solutions[front[i]], &solutions[front[j]]);
+      distances[i * len + j] = d;
+      distances[j * len + i] = d;
+    }
+  }
+  distances
+}
+
+/// A rule that decides whether a dominated offspring, a `candidate`, replaces
+/// a solution of the population that dominates it, an `incumbent`. Accepting
+/// worse solutions from time to time lets the population escape local
+/// fronts, in the manner of simulated annealing.
+///
+/// Optimizers built with an `acceptance` consult it before truncation, for
+/// each offspring dominated by a solution of the population, the first such
+/// solution being the incumbent. An accepted offspring replaces its
+/// incumbent and survives the truncation of the generation, and each
+/// incumbent is replaced at most once. Without an acceptance, optimizers
+/// behave as with [`ParetoAcceptance`].
+///
+/// # Examples
+/// ```
+/// use moga::truncation::Acceptance;
+///
+/// // accepts offsprings within `0.1` of incumbents for 10 generations
+/// let a = |c: &[f32; 2], i: &[f32; 2], generation: usize| {
+///   generation < 10 && c.iter().zip(i).all(|(c, i)| c.abs() - i.abs() < 0.1)
+/// };
+/// assert!(a.accept(&[1.05, 1.0], &[1.0, 1.0], 0));
+/// assert!(!a.accept(&[1.05, 1.0], &[1.0, 1.0], 10));
+/// ```
+///
+/// **Note that you always can implement this trait instead of using closures.**
+pub trait Acceptance<const N: usize> {
+  /// Returns `true` if the `candidate` replaces the `incumbent` in the
+  /// `generation`, starting from `0`.
+  fn accept(
+    &self,
+    candidate: &Scores<N>,
+    incumbent: &Scores<N>,
+    generation: usize,
+  ) -> bool;
+}
+
+impl<const N: usize, F> Acceptance<N> for F
+where
+  F: Fn(&Scores<N>, &Scores<N>, usize) -> bool,
+{
+  fn accept(
+    &self,
+    candidate: &Scores<N>,
+    incumbent: &Scores<N>,
+    generation: usize,
+  ) -> bool {
+    self(candidate, incumbent, generation)
+  }
+}
+
+impl<const N: usize> Debug for dyn Acceptance<N> + Send {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("Acceptance")
+  }
+}
+
+/// An [`Acceptance`] that never accepts dominated candidates, so only the
+/// truncation decides which solutions survive.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct ParetoAcceptance;
+
+impl<const N: usize> Acceptance<N> for ParetoAcceptance {
+  fn accept(
+    &self,
+    candidate: &Scores<N>,
+    incumbent: &Scores<N>,
+    _: usize,
+  ) -> bool {
+    candidate.dominance(incumbent) != Ordering::Greater
+  }
+}
+
+/// An [`Acceptance`] that accepts dominated candidates with probability
+/// `exp(-delta / t)`, like simulated annealing does. `delta` is the sum of
+/// differences between absolute scores of the candidate and the incumbent
+/// over objectives where the candidate is worse, and `t` is the temperature
+/// of the generation, which starts at `t0` and is multiplied by `cooling`
+/// each generation. Worse candidates are accepted less often, and all of
+/// them are accepted less and less often over generations.
+///
+/// Random numbers are drawn from streams of [`SplitRng::indexed`] seeded with
+/// `seed`, one for each generation and pair of scores, so optimizations with
+/// the same seed accept the same candidates.
+///
+/// # Examples
+/// ```
+/// use moga::truncation::AnnealedAcceptance;
+///
+/// let a = AnnealedAcceptance {
+///   t0: 1.0,
+///   cooling: 0.5,
+///   seed: 0,
+/// };
+/// assert_eq!(a.temperature(2), 0.25);
+/// assert_eq!(a.probability(&[1.5, 1.0], &[1.0, 1.0], 0), (-0.5f64).exp());
+/// assert_eq!(a.probability(&[0.5, 1.0], &[1.0, 1.0], 0), 1.0);
+/// ```
+#[derive(Clone, Copy, PartialEq, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AnnealedAcceptance {
+  /// The temperature of the first generation.
+  pub t0: f64,
+  /// The factor the temperature is multiplied by each generation, usually
+  /// slightly less than `1.0`.
+  pub cooling: f64,
+  /// The master seed of random numbers.
+  pub seed: u64,
+}
+
+impl AnnealedAcceptance {
+  /// Returns the temperature of the `generation`.
+  pub fn temperature(&self, generation: usize) -> f64 {
+    self.t0 * self.cooling.powf(generation as f64)
+  }
+
+  /// Returns the probability of the `candidate` replacing the `incumbent` in
+  /// the `generation`. Candidates that aren't dominated by the incumbent are
+  /// always accepted, and dominated ones are never accepted once the
+  /// temperature drops to `0`.
+  pub fn probability<const N: usize>(
+    &self,
+    candidate: &Scores<N>,
+    incumbent: &Scores<N>,
+    generation: usize,
+  ) -> f64 {
+    if candidate.dominance(incumbent) != Ordering::Greater {
+      return 1.0;
+    }
+    let temperature = self.temperature(generation);
+    if temperature <= 0.0 {
+      return 0.0;
+    }
+    let delta = candidate
+      .iter()
+      .zip(incumbent)
+      .map(|(c, i)| f64::from(c.abs() - i.abs()).max(0.0))
+      .sum::<f64>();
+    (-delta / temperature).exp()
+  }
+}
+
+impl<const N: usize> Acceptance<N> for AnnealedAcceptance {
+  fn accept(
+    &self,
+    candidate: &Scores<N>,
+    incumbent: &Scores<N>,
+    generation: usize,
+  ) -> bool {
+    let probability = self.probability(candidate, incumbent, generation);
+    // FNV-1a of bits of both scores tells pairs of scores apart
+    let index = candidate.iter().chain(incumbent).fold(
+      0xcbf2_9ce4_8422_2325,
+      |hash, score| {
+        (hash ^ u64::from(score.to_bits())).wrapping_mul(0x0100_0000_01b3)
+      },
+    );
+    let mut rng = SplitRng::new(self.seed).indexed(generation as u64, index);
+    rng.gen::<f64>() < probability
+  }
+}
+
+/// Returns the unit vector of the direction of `p`, or `None` if `p` is
+/// zero or has NaN values.
+fn unit<const N: usize>(p: [f64; N]) -> Option<[f64; N]> {
+  let length = p.iter().map(|v| v * v).sum::<f64>().sqrt();
+  (length > 0.0).then(|| p.map(|v| v / length))
+}
+
+/// Returns the distance from `p` to the ray of unit `direction`, or to the
+/// origin if there is no direction.
+fn perpendicular_distance<const N: usize>(
+  p: &[f64; N],
+  direction: Option<&[f64; N]>,
+) -> f64 {
+  let projection =
+    direction.map_or(0.0, |w| p.iter().zip(w).map(|(p, w)| p * w).sum::<f64>());
+  p.iter()
+    .enumerate()
+    .map(|(o, v)| {
+      let on_ray = direction.map_or(0.0, |w| projection * w[o]);
+      (v - on_ray).powi(2)
+    })
+    .sum::<f64>()
+    .sqrt()
+}
+
+/// Keeps solutions of the best fronts that fit into `target` and lets
+/// `truncate_front` choose survivors of the first front that doesn't fit. It
+/// takes indices of solutions of the front, all `solutions` and `scores` and
+/// the number of survivors to choose, and returns indices of the survivors.
+/// Survivors keep their order.
+fn truncate_by_fronts<S, const N: usize>(
+  solutions: Vec<S>,
+  scores: Vec<Scores<N>>,
+  target: usize,
+  mut truncate_front: impl FnMut(
+    Vec<usize>,
+    &[S],
+    &[Scores<N>],
+    usize,
+  ) -> Vec<usize>,
+) -> (Vec<S>, Vec<Scores<N>>) {
+  if solutions.len() <= target {
+    return (solutions, scores);
+  }
+  let mut sorting = SortingBuffers::default();
+  nondominated_sort_with(&scores, &mut sorting);
+  let mut is_survivor = vec![false; solutions.len()];
+  let mut survived = 0;
+  for front in sorting.fronts() {
+    let front = front.iter().map(|&idx| from_index(idx)).collect::<Vec<_>>();
+    let front = if survived + front.len() <= target {
+      front
+    } else {
+      truncate_front(front, &solutions, &scores, target - survived)
+    };
+    survived += front.len();
+    front.into_iter().for_each(|idx| is_survivor[idx] = true);
+    if survived >= target {
+      break;
+    }
+  }
+  solutions
+    .into_iter()
+    .zip(scores)
+    .zip(is_survivor)
+    .filter_map(|(pair, is_survivor)| is_survivor.then_some(pair))
+    .unzip()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn test_hypervolume_truncation() {
+    let t = HypervolumeTruncation {
+      reference: [10.0, 10.0],
+    };
+    // the second front is dropped, and solutions of the first front that
+    // contribute the least are removed one by one
+    let scores = vec![
+      [0.0, 4.0],
+      [5.0, 5.0],
+      [1.0, 3.8],
+      [2.0, 2.0],
+      [3.8, 1.0],
+      [4.0, 0.0],
+      [6.0, 6.0],
+    ];
+    let solutions = (0..scores.len()).collect::<Vec<_>>();
+    let (survivors, survivor_scores) = t.truncate(solutions, scores.clone(), 3);
+    assert_eq!(survivors, [0, 3, 5]);
+    assert_eq!(survivor_scores, [scores[0], scores[3], scores[5]]);
+
+    // the first front fits, and the second is truncated
+    let (survivors, _) = t.truncate(survivors, survivor_scores, 3);
+    assert_eq!(survivors, [0, 3, 5]);
+    let solutions = (0..scores.len()).collect::<Vec<_>>();
+    let (survivors, _) = t.truncate(solutions, scores.clone(), 6);
+    assert_eq!(survivors, [0, 1, 2, 3, 4, 5]);
+    let (survivors, _) = t.truncate(vec![0, 1], scores[..2].to_vec(), 0);
+    assert!(survivors.is_empty());
+  }
+
+  #[test]
+  fn test_hypervolume_truncation_3d() {
+    let t = HypervolumeTruncation {
+      reference: [2.0; 3],
+    };
+    let scores = vec![
+      [0.0, 0.0, 1.0],
+      [0.0, 1.0, 0.0],
+      [1.0, 0.0, 0.0],
+      [0.5, 0.5, 0.5],
+      [0.1, 0.1, 0.9],
+      [1.0, 1.0, 1.0],
+    ];
+    let solutions = (0..scores.len()).collect::<Vec<_>>();
+    let (survivors, _) = t.truncate(solutions, scores, 4);
+    assert_eq!(survivors, [0, 1, 2, 3]);
+  }
+
+  /// Returns a linear 2-objective front of 11 solutions that lies between
+  /// `[0.0, 1.0]` and `[1.0, 0.0]`, followed by a dominated solution.
+  fn line_front() -> Vec<Scores<2>> {
+    let mut scores = (0..=10)
+      .map(|i| [i as f32 / 10.0, 1.0 - i as f32 / 10.0])
+      .collect::<Vec<_>>();
+    scores.push([5.0, 5.0]);
+    scores
+  }
+
+  #[test]
+  fn test_reference_point_truncation() {
+    let scores = line_front();
+    let solutions = (0..scores.len()).collect::<Vec<_>>();
+    // dominated and infeasible reference points of the same direction
+    // prefer the same solutions
+    for point in [[2.0, 8.0], [0.02, 0.08]] {
+      let t = ReferencePointTruncation {
+        points: vec![point],
+        radius: 0.03,
+      };
+      let (survivors, _) = t.truncate(solutions.clone(), scores.clone(), 3);
+      assert_eq!(survivors, [1, 2, 3]);
+    }
+    // without a radius, only the closest solution is preferred, and the rest
+    // is chosen by crowding distance
+    let t = ReferencePointTruncation {
+      points: vec![[2.0, 8.0]],
+      radius: 0.0,
+    };
+    let (survivors, _) = t.truncate(solutions.clone(), scores.clone(), 3);
+    assert_eq!(survivors, [0, 2, 10]);
+    // each reference point keeps a solution
+    let t = ReferencePointTruncation {
+      points: vec![[9.0, 1.0], [1.0, 9.0]],
+      radius: 0.0,
+    };
+    let (survivors, _) = t.truncate(solutions.clone(), scores.clone(), 2);
+    assert_eq!(survivors, [1, 9]);
+    let (survivors, _) = t.truncate(solutions.clone(), scores.clone(), 1);
+    assert_eq!(survivors, [9]);
+    // the ideal point as a reference point prefers solutions closest to it
+    let t = ReferencePointTruncation {
+      points: vec![[0.0, 0.0]],
+      radius: 0.0,
+    };
+    let (survivors, _) = t.truncate(solutions, scores, 1);
+    assert_eq!(survivors, [5]);
+  }
+
+  #[test]
+  fn test_diversity_measures() {
+    assert_eq!(Euclidean.distance(&vec![0.0, 1.0], &vec![3.0, 5.0]), 5.0);
+    assert_eq!(Euclidean.distance(&vec![1.0], &vec![1.0, 2.0]), 0.0);
+    let a = vec![true, false, true];
+    assert_eq!(Hamming.distance(&a, &a), 0.0);
+    assert_eq!(Hamming.distance(&a, &vec![false, false, true]), 1.0);
+    assert_eq!(Hamming.distance(&a, &vec![true]), 2.0);
+  }
+
+  #[test]
+  fn test_genotypic_crowding_truncation() {
+    let t = GenotypicCrowdingTruncation {
+      measure: |a: &f32, b: &f32| f64::from((a - b).abs()),
+    };
+    // designs with identical scores are kept by their spread, and the
+    // dominated one is dropped
+    let solutions = vec![0.0, 10.0, 11.0, 13.0, 50.0, 90.0];
+    let mut scores = vec![[1.0, 1.0]; 5];
+    scores.push([2.0, 2.0]);
+    let (survivors, survivor_scores) =
+      t.truncate(solutions.clone(), scores.clone(), 3);
+    assert_eq!(survivors, [0.0, 13.0, 50.0]);
+    assert_eq!(survivor_scores, [[1.0, 1.0]; 3]);
+    let (survivors, _) = t.truncate(solutions.clone(), scores.clone(), 6);
+    assert_eq!(survivors, solutions);
+    let (survivors, _) = t.truncate(solutions, scores, 0);
+    assert!(survivors.is_empty());
+  }
+
+  #[test]
+  fn test_genotypic_tie_break() {
+    let t = GenotypicTieBreak {
+      measure: |a: &f32, b: &f32| f64::from((a - b).abs()),
+    };
+    let scores = line_front();
+    let solutions = (0..scores.len()).map(|i| i as f32).collect::<Vec<_>>();
+    // without ties, solutions are kept by crowding distance
+    let (survivors, _) = t.truncate(solutions, scores, 2);
+    assert_eq!(survivors, [0.0, 10.0]);
+    // boundary solutions of the same scores are told apart by diversity
+    let solutions = vec![0.0, 0.5, 3.0, 7.0];
+    let scores = vec![[0.0, 1.0], [0.0, 1.0], [1.0, 0.0], [0.5, 0.5]];
+    let (survivors, _) = t.truncate(solutions, scores, 2);
+    assert_eq!(survivors, [0.0, 3.0]);
+  }
+}
@@ -0,0 +1,160 @@
+//! Counts heap allocations and memory used by optimizers.
+
+use std::{
+  alloc::{GlobalAlloc, Layout, System},
+  sync::{
+    atomic::{AtomicUsize, Ordering},
+    Mutex,
+  },
+};
+
+use moga::{
+  optimizer::{nsga::Nsga2, spea::Spea2, Optimizer},
+  selection::{FirstSelector, RandomSelector},
+  termination::GenerationTerminator,
+};
+use rand::prelude::*;
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+static PEAK_ALLOCATED_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// Counters are global, so tests that read them must not run concurrently.
+static COUNTERS_LOCK: Mutex<()> = Mutex::new(());
+
+fn add_allocated_bytes(bytes: usize) {
+  let allocated = ALLOCATED_BYTES.fetch_add(bytes, Ordering::Relaxed) + bytes;
+  PEAK_ALLOCATED_BYTES.fetch_max(allocated, Ordering::Relaxed);
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+  unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    add_allocated_bytes(layout.size());
+    System.alloc(layout)
+  }
+
+  unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+    ALLOCATED_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+    System.dealloc(ptr, layout)
+  }
+
+  unsafe fn realloc(
+    &self,
+    ptr: *mut u8,
+    layout: Layout,
+    new_size: usize,
+  ) -> *mut u8 {
+    ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+    ALLOCATED_BYTES.fetch_sub(layout.size(), Ordering::Relaxed);
+    add_allocated_bytes(new_size);
+    System.realloc(ptr, layout, new_size)
+  }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+fn schaffer_n1(x: &f32) -> [f32; 2] {
+  [x.powf(2.0), (x - 2.0).powf(2.0)]
+}
+
+/// Returns the number of allocations made by `optimize`.
+fn count_allocations<O: Optimizer<f32, 2>>(optimizer: O) -> usize {
+  let before = ALLOCATIONS.load(Ordering::Relaxed);
+  optimizer.optimize();
+  ALLOCATIONS.load(Ordering::Relaxed) - before
+}
+
+fn nsga2_allocations(generations: usize) -> usize {
+  count_allocations(
+    Nsga2::builder()
+      .population((0..100).map(|i| i as f32).collect())
+      .tester(schaffer_n1)
+      .selector(FirstSelector(10))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|x: &mut f32| *x += 0.1)
+      .terminator(GenerationTerminator(generations))
+      .build(),
+  )
+}
+
+fn spea2_allocations(generations: usize) -> usize {
+  count_allocations(
+    Spea2::builder()
+      .population((0..100).map(|i| i as f32).collect())
+      .archive_size(100)
+      .tester(schaffer_n1)
+      .selector(RandomSelector(10))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|x: &mut f32| *x += 0.1)
+      .terminator(GenerationTerminator(generations))
+      .build(),
+  )
+}
+
+#[test]
+fn test_generation_loop_allocations() {
+  let _lock = COUNTERS_LOCK.lock().unwrap();
+  let nsga2_per_generation =
+    (nsga2_allocations(200) - nsga2_allocations(100)) / 100;
+  let spea2_per_generation =
+    (spea2_allocations(200) - spea2_allocations(100)) / 100;
+  // selected parents, offsprings and their scores are the only vectors that
+  // are expected to be allocated each generation. reusable buffers still grow
+  // now and then, as the number of nondominated solutions varies
+  assert!(
+    nsga2_per_generation <= 16,
+    "Nsga2 makes {nsga2_per_generation} allocations per generation"
+  );
+  assert!(
+    spea2_per_generation <= 16,
+    "Spea2 makes {spea2_per_generation} allocations per generation"
+  );
+}
+
+/// Returns peak number of bytes allocated by `optimize` on top of the memory
+/// allocated before it was called.
+fn peak_memory_usage<S, O: Optimizer<S, 2>>(optimizer: O) -> usize {
+  let before = ALLOCATED_BYTES.load(Ordering::Relaxed);
+  PEAK_ALLOCATED_BYTES.store(before, Ordering::Relaxed);
+  optimizer.optimize();
+  PEAK_ALLOCATED_BYTES.load(Ordering::Relaxed) - before
+}
+
+#[test]
+#[ignore = "takes a while, run with `--release -- --ignored`"]
+fn test_large_population_memory_usage() {
+  let _lock = COUNTERS_LOCK.lock().unwrap();
+  let population_size = 1_000_000;
+  let mut rng = StdRng::seed_from_u64(0);
+  let population: Vec<[f32; 2]> = (0..population_size)
+    .map(|_| [rng.gen_range(0.0..1.0), rng.gen_range(0.0..1.0)])
+    .collect();
+  let population_bytes = std::mem::size_of_val(population.as_slice());
+
+  let peak = peak_memory_usage(
+    Nsga2::builder()
+      .population(population)
+      .tester(|x: &[f32; 2]| *x)
+      .selector(FirstSelector(2))
+      .recombinator(|x: &[f32; 2], y: &[f32; 2]| [x[0] + y[0], x[1] + y[1]])
+      .mutator(|_: &mut [f32; 2]| {})
+      .terminator(GenerationTerminator(1))
+      .build(),
+  );
+  // the population is moved into the optimizer, while scores and buffers of
+  // the optimizer are allocated on top of it
+  let bytes_per_solution = (peak + population_bytes) / population_size;
+  let max_bytes_per_solution = if cfg!(feature = "u32-indices") {
+    80
+  } else {
+    112
+  };
+  assert!(
+    bytes_per_solution <= max_bytes_per_solution,
+    "Nsga2 uses {bytes_per_solution} bytes per solution"
+  );
+}
@@ -0,0 +1,192 @@
+//! Runs optimizers on classic problems with two variables, constrained ones
+//! with an adaptive penalty, and checks that they find the known fronts.
+
+use moga::{
+  constraining::{AdaptivePenalty, Constraint},
+  genomes::real::Bounds,
+  optimizer::{nsga::Nsga2, spea::Spea2, Optimizer},
+  problems::classic::{BinhKorn, ChankongHaimes, FonsecaFleming},
+  score::Scores,
+  selection::RandomSelector,
+  termination::GenerationTerminator,
+  testing::Test,
+};
+use rand::prelude::*;
+
+type Solution = (f32, f32);
+
+/// Returns a population of random solutions within the `bounds` that pass
+/// the `filter`.
+fn population(
+  bounds: &Bounds,
+  size: usize,
+  filter: impl Fn(&Solution) -> bool,
+) -> Vec<Solution> {
+  let mut rng = StdRng::seed_from_u64(0);
+  let (lower, upper) = (bounds.lower(), bounds.upper());
+  std::iter::repeat_with(|| {
+    (
+      rng.gen_range(lower[0]..=upper[0]),
+      rng.gen_range(lower[1]..=upper[1]),
+    )
+  })
+  .filter(filter)
+  .take(size)
+  .collect()
+}
+
+/// Returns blend crossover and gaussian-like mutation within the `bounds`.
+fn operators(
+  bounds: &Bounds,
+) -> (
+  impl Fn(&Solution, &Solution) -> Solution + Clone,
+  impl Fn(&mut Solution) + Clone,
+) {
+  let (lower, upper) = (bounds.lower(), bounds.upper());
+  let (lower, upper) = ((lower[0], lower[1]), (upper[0], upper[1]));
+  let recombination = move |a: &Solution, b: &Solution| {
+    let mut rng = rand::thread_rng();
+    let mut blend = |a: f32, b: f32, lower: f32, upper: f32| {
+      let r = rng.gen_range(-0.25..1.25);
+      (a + r * (b - a)).clamp(lower, upper)
+    };
+    (
+      blend(a.0, b.0, lower.0, upper.0),
+      blend(a.1, b.1, lower.1, upper.1),
+    )
+  };
+  let mutation = move |x: &mut Solution| {
+    let mut rng = rand::thread_rng();
+    let step = |lower: f32, upper: f32| (upper - lower) / 100.0;
+    x.0 = (x.0 + rng.gen_range(-1.0..1.0) * step(lower.0, upper.0))
+      .clamp(lower.0, upper.0);
+    x.1 = (x.1 + rng.gen_range(-1.0..1.0) * step(lower.1, upper.1))
+      .clamp(lower.1, upper.1);
+  };
+  (recombination, mutation)
+}
+
+/// Mean distance of scores of `solutions` to the closest point of the
+/// `reference` front, relative to the extent of the front.
+fn mean_distance(
+  problem: &impl Test<Solution, 2>,
+  solutions: &[Solution],
+  reference: &[Scores<2>],
+) -> f32 {
+  let extent = |o: usize| {
+    let (min, max) = reference
+      .iter()
+      .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), sc| {
+        (min.min(sc[o]), max.max(sc[o]))
+      });
+    max - min
+  };
+  let extent = [extent(0), extent(1)];
+  solutions
+    .iter()
+    .map(|s| {
+      let sc = problem.test(s);
+      reference
+        .iter()
+        .map(|r| {
+          (0..2)
+            .map(|o| ((sc[o] - r[o]) / extent[o]).powi(2))
+            .sum::<f32>()
+            .sqrt()
+        })
+        .fold(f32::INFINITY, f32::min)
+    })
+    .sum::<f32>()
+    / solutions.len() as f32
+}
+
+/// Returns feasible solutions of the `problem`, asserting that they make up
+/// most of the `solutions`. Scores of a solution are penalized with weights
+/// of the generation it was tested in, so the population may keep a few
+/// infeasible solutions tested before the weights have grown.
+fn feasible(
+  problem: &impl Constraint<Solution, 2>,
+  solutions: &[Solution],
+) -> Vec<Solution> {
+  let feasible: Vec<_> = solutions
+    .iter()
+    .filter(|s| problem.violations(s).iter().all(|&v| v <= 1e-3))
+    .copied()
+    .collect();
+  assert!(
+    feasible.len() * 10 >= solutions.len() * 7,
+    "only {} of {} solutions are feasible",
+    feasible.len(),
+    solutions.len()
+  );
+  feasible
+}
+
+#[test]
+fn test_nsga2_binh_korn() {
+  let problem = BinhKorn;
+  let penalty = AdaptivePenalty::new(problem, 1.0, 2.0);
+  let (recombination, mutation) = operators(&problem.bounds());
+  // start from feasible solutions, so that none of them survives with a
+  // penalty of the initial weights
+  let solutions = Nsga2::builder()
+    .population(population(&problem.bounds(), 100, |s| {
+      problem.violations(s).iter().all(|&v| v <= 0.0)
+    }))
+    .tester(penalty.penalize(problem))
+    .selector(RandomSelector(50))
+    .recombinator(recombination)
+    .mutator(mutation)
+    .constraint(penalty.clone())
+    .terminator(GenerationTerminator(100))
+    .build()
+    .optimize();
+  let solutions = feasible(&problem, &solutions);
+  let distance =
+    mean_distance(&problem, &solutions, &problem.reference_front(500));
+  assert!(distance < 0.01, "mean distance to the front is {distance}");
+}
+
+#[test]
+fn test_nsga2_chankong_haimes() {
+  let problem = ChankongHaimes;
+  let penalty = AdaptivePenalty::new(problem, 1.0, 2.0);
+  let (recombination, mutation) = operators(&problem.bounds());
+  // start from feasible solutions, so that none of them survives with a
+  // penalty of the initial weights
+  let solutions = Nsga2::builder()
+    .population(population(&problem.bounds(), 100, |s| {
+      problem.violations(s).iter().all(|&v| v <= 0.0)
+    }))
+    .tester(penalty.penalize(problem))
+    .selector(RandomSelector(50))
+    .recombinator(recombination)
+    .mutator(mutation)
+    .constraint(penalty.clone())
+    .terminator(GenerationTerminator(100))
+    .build()
+    .optimize();
+  let solutions = feasible(&problem, &solutions);
+  let distance =
+    mean_distance(&problem, &solutions, &problem.reference_front(500));
+  assert!(distance < 0.01, "mean distance to the front is {distance}");
+}
+
+#[test]
+fn test_spea2_fonseca_fleming() {
+  let problem = FonsecaFleming;
+  let (recombination, mutation) = operators(&problem.bounds());
+  let solutions = Spea2::builder()
+    .population(population(&problem.bounds(), 50, |_| true))
+    .archive_size(50)
+    .tester(problem)
+    .selector(RandomSelector(25))
+    .recombinator(recombination)
+    .mutator(mutation)
+    .terminator(GenerationTerminator(50))
+    .build()
+    .optimize();
+  let distance =
+    mean_distance(&problem, &solutions, &problem.reference_front(500));
+  assert!(distance < 0.01, "mean distance to the front is {distance}");
+}
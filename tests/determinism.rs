@@ -0,0 +1,100 @@
+//! Runs optimizers with seeded operators and checks that runs repeat.
+
+use std::sync::Mutex;
+
+use moga::{
+  mutation::IndexedRandomMutation,
+  optimizer::{nsga::Nsga2, OffspringBudget, Optimizer},
+  recombination::IndexedRandomRecombination,
+  rng::SplitRng,
+  selection::TournamentSelectorWithReplacement,
+  termination::GenerationTerminator,
+};
+use rand::prelude::*;
+
+fn schaffer_n1(x: &f32) -> [f32; 2] {
+  [x.powf(2.0), (x - 2.0).powf(2.0)]
+}
+
+/// Returns bits of solutions found by NSGA-II with operators seeded from
+/// the `seed`.
+fn nsga2_schaffer_n1(seed: u64) -> Vec<u32> {
+  let master = SplitRng::new(seed);
+  let mut init_rng = master.stream("init");
+  let recombination_rng = Mutex::new(master.stream("recombination"));
+  let mutation_rng = Mutex::new(master.stream("mutation"));
+
+  let solutions = Nsga2::builder()
+    .population((0..100).map(|_| init_rng.gen_range(-1e3..1e3)).collect())
+    .tester(schaffer_n1)
+    .selector(
+      TournamentSelectorWithReplacement(30, 2)
+        .with_rng(master.stream("selection")),
+    )
+    .recombinator(move |a: &f32, b: &f32| {
+      a + recombination_rng.lock().unwrap().gen_range(-1.0..2.0) * (b - a)
+    })
+    .mutator(move |x: &mut f32| {
+      *x += mutation_rng.lock().unwrap().gen_range(-0.1..0.1)
+    })
+    .terminator(GenerationTerminator(100))
+    .build()
+    .optimize();
+  solutions.iter().map(|x| x.to_bits()).collect()
+}
+
+#[test]
+fn test_nsga2_is_deterministic() {
+  let solutions = nsga2_schaffer_n1(42);
+  assert_eq!(solutions.len(), 100);
+  assert_eq!(solutions, nsga2_schaffer_n1(42));
+  assert_ne!(solutions, nsga2_schaffer_n1(43));
+}
+
+/// Returns bits of solutions found by NSGA-II with indexed operators seeded
+/// from the `seed`, which recombine and mutate in parallel if `parallel`.
+fn indexed_nsga2_schaffer_n1(seed: u64, parallel: bool) -> Vec<u32> {
+  let master = SplitRng::new(seed);
+  let mut init_rng = master.stream("init");
+  let recombination = IndexedRandomRecombination::new(
+    master.split("recombination"),
+    |[a, b]: [&f32; 2], rng| [a + rng.gen_range(-1.0..2.0) * (b - a)],
+  );
+  let mutation =
+    IndexedRandomMutation::new(master.split("mutation"), |x: &mut f32, rng| {
+      *x += rng.gen_range(-0.1..0.1)
+    });
+  #[cfg(feature = "parallel")]
+  let (recombination, mutation) = if parallel {
+    (recombination.par_each(), mutation.par_each())
+  } else {
+    (recombination, mutation)
+  };
+  #[cfg(not(feature = "parallel"))]
+  let _ = parallel;
+
+  let solutions = Nsga2::builder()
+    .population((0..100).map(|_| init_rng.gen_range(-1e3..1e3)).collect())
+    .tester(schaffer_n1)
+    .selector(
+      TournamentSelectorWithReplacement(30, 2)
+        .with_rng(master.stream("selection")),
+    )
+    .recombinator(recombination)
+    .mutator(mutation)
+    .terminator(GenerationTerminator(100))
+    .max_offspring_per_generation(200)
+    .offspring_budget(OffspringBudget::Subsample { seed })
+    .build()
+    .optimize();
+  solutions.iter().map(|x| x.to_bits()).collect()
+}
+
+#[test]
+fn test_parallel_nsga2_is_deterministic() {
+  let solutions = indexed_nsga2_schaffer_n1(42, false);
+  assert_eq!(solutions.len(), 100);
+  assert_eq!(solutions, indexed_nsga2_schaffer_n1(42, true));
+  assert_eq!(solutions, indexed_nsga2_schaffer_n1(42, true));
+  assert_ne!(solutions, indexed_nsga2_schaffer_n1(43, true));
+}
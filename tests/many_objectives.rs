@@ -0,0 +1,103 @@
+//! Runs optimizers on problems with more than 3 objectives.
+
+use moga::{
+  init::random_uniform,
+  optimizer::{nsga::Nsga2, spea::Spea2, Optimizer},
+  problems::dtlz::Dtlz2,
+  score::Scores,
+  selection::RandomSelector,
+  termination::GenerationTerminator,
+  testing::Test,
+};
+use rand::prelude::*;
+
+const OBJECTIVES: usize = 5;
+
+fn population(problem: &Dtlz2<OBJECTIVES>, size: usize) -> Vec<Vec<f32>> {
+  random_uniform(&problem.bounds(), size, &mut StdRng::seed_from_u64(0))
+}
+
+// operators take references to solutions, which are vectors here
+#[allow(clippy::ptr_arg)]
+fn recombination(a: &Vec<f32>, b: &Vec<f32>) -> Vec<f32> {
+  let mut rng = rand::thread_rng();
+  a.iter()
+    .zip(b)
+    .map(|(a, b)| {
+      let r = rng.gen_range(-0.25..1.25);
+      (a + r * (b - a)).clamp(0.0, 1.0)
+    })
+    .collect()
+}
+
+#[allow(clippy::ptr_arg)]
+fn mutation(x: &mut Vec<f32>) {
+  let mut rng = rand::thread_rng();
+  let idx = rng.gen_range(0..x.len());
+  x[idx] = (x[idx] + rng.gen_range(-0.1..0.1)).clamp(0.0, 1.0);
+}
+
+/// Mean distance of scores to the Pareto front, i.e. to the unit
+/// hypersphere.
+fn mean_distance(scores: &[Scores<OBJECTIVES>]) -> f32 {
+  scores
+    .iter()
+    .map(|sc| sc.iter().map(|f| f * f).sum::<f32>().sqrt() - 1.0)
+    .sum::<f32>()
+    / scores.len() as f32
+}
+
+fn assert_converges(
+  problem: Dtlz2<OBJECTIVES>,
+  initial: &[Vec<f32>],
+  solutions: &[Vec<f32>],
+) {
+  let scores = |sols: &[Vec<f32>]| -> Vec<_> {
+    sols.iter().map(|s| problem.test(s)).collect()
+  };
+  let initial_distance = mean_distance(&scores(initial));
+  let distance = mean_distance(&scores(solutions));
+  // with many objectives, most solutions are nondominated and selection
+  // pressure is weak, so convergence is slow and noisy. only check that
+  // solutions are closer to the front than the random initial population
+  assert!(
+    distance < initial_distance,
+    "mean distance to the front {distance} isn't less than the initial \
+     {initial_distance}"
+  );
+}
+
+#[test]
+fn test_nsga2_many_objectives() {
+  let problem = Dtlz2::<OBJECTIVES>::default();
+  let population = population(&problem, 100);
+  let solutions = Nsga2::builder()
+    .population(population.clone())
+    .tester(problem)
+    .selector(RandomSelector(20))
+    .recombinator(recombination)
+    .mutator(mutation)
+    .terminator(GenerationTerminator(50))
+    .build()
+    .optimize();
+  assert_eq!(solutions.len(), 100);
+  assert_converges(problem, &population, &solutions);
+}
+
+#[test]
+fn test_spea2_many_objectives() {
+  let problem = Dtlz2::<OBJECTIVES>::default();
+  let population = population(&problem, 100);
+  let solutions = Spea2::builder()
+    .population(population.clone())
+    .archive_size(100)
+    .tester(problem)
+    .selector(RandomSelector(20))
+    .recombinator(recombination)
+    .mutator(mutation)
+    .terminator(GenerationTerminator(50))
+    .build()
+    .optimize();
+  assert!(!solutions.is_empty() && solutions.len() <= 100);
+  assert_converges(problem, &population, &solutions);
+}
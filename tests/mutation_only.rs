@@ -0,0 +1,68 @@
+//! Runs optimizers as evolutionary strategies that only mutate solutions,
+//! cloning selected parents instead of recombining them.
+
+use moga::{
+  mutation::IndexedRandomMutation,
+  optimizer::{nsga::Nsga2, spea::Spea2, Optimizer},
+  recombination::CloneParents,
+  rng::SplitRng,
+  selection::RandomSelector,
+  termination::GenerationTerminator,
+};
+use rand::prelude::*;
+
+fn schaffer_n1(x: &f32) -> [f32; 2] {
+  [x.powf(2.0), (x - 2.0).powf(2.0)]
+}
+
+fn population(size: usize) -> Vec<f32> {
+  let mut rng = StdRng::seed_from_u64(0);
+  (0..size).map(|_| rng.gen_range(-10.0..10.0)).collect()
+}
+
+fn mutation(seed: u64) -> IndexedRandomMutation<fn(&mut f32, &mut SmallRng)> {
+  IndexedRandomMutation::new(SplitRng::new(seed), |x: &mut f32, rng| {
+    *x += rng.gen_range(-0.5..0.5)
+  })
+}
+
+/// Asserts that all solutions lie on the pareto front of Schaffer's Problem
+/// No.1, between `0` and `2`. A solution just outside of it is nondominated
+/// if no other solution is closer to the edge.
+fn assert_on_front(solutions: &[f32]) {
+  assert!(
+    solutions.iter().all(|x| (-0.01..=2.01).contains(x)),
+    "solutions aren't on the front: {solutions:?}"
+  );
+}
+
+#[test]
+fn test_nsga2_mutation_only() {
+  let solutions = Nsga2::builder()
+    .population(population(50))
+    .tester(schaffer_n1)
+    .selector(RandomSelector(20))
+    .recombinator(CloneParents)
+    .mutator(mutation(0))
+    .terminator(GenerationTerminator(100))
+    .build()
+    .optimize();
+  assert_eq!(solutions.len(), 50);
+  assert_on_front(&solutions);
+}
+
+#[test]
+fn test_spea2_mutation_only() {
+  let solutions = Spea2::builder()
+    .population(population(50))
+    .archive_size(50)
+    .tester(schaffer_n1)
+    .selector(RandomSelector(20))
+    .recombinator(CloneParents)
+    .mutator(mutation(0))
+    .terminator(GenerationTerminator(100))
+    .build()
+    .optimize();
+  assert!(!solutions.is_empty() && solutions.len() <= 50);
+  assert_on_front(&solutions);
+}
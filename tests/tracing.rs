@@ -0,0 +1,168 @@
+//! Checks spans and events emitted by optimizers with `tracing` feature.
+
+#![cfg(feature = "tracing")]
+
+use std::{
+  collections::BTreeSet,
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+    Mutex,
+  },
+};
+
+use moga::{
+  dedup::Dedup,
+  optimizer::{nsga::Nsga2, spea::Spea2, Optimizer},
+  selection::RandomSelector,
+  termination::GenerationTerminator,
+};
+use tracing::{
+  field::{Field, Visit},
+  span::{Attributes, Id, Record},
+  Event,
+  Metadata,
+  Subscriber,
+};
+
+/// Collects names of spans, their fields and messages of events.
+#[derive(Default)]
+struct Collector {
+  next_id: AtomicU64,
+  spans: Mutex<Vec<&'static str>>,
+  fields: Mutex<BTreeSet<String>>,
+  messages: Mutex<Vec<String>>,
+}
+
+struct FieldNames<'a>(&'a mut BTreeSet<String>, &'static str);
+
+impl Visit for FieldNames<'_> {
+  fn record_debug(&mut self, field: &Field, _: &dyn std::fmt::Debug) {
+    self.0.insert(format!("{}.{}", self.1, field.name()));
+  }
+}
+
+struct Message<'a>(&'a mut Vec<String>);
+
+impl Visit for Message<'_> {
+  fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+    if field.name() == "message" {
+      self.0.push(format!("{value:?}"));
+    }
+  }
+}
+
+impl Subscriber for Collector {
+  fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+    metadata.target() == "moga"
+  }
+
+  fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+    let mut spans = self.spans.lock().unwrap();
+    spans.push(attrs.metadata().name());
+    let mut fields = self.fields.lock().unwrap();
+    attrs.record(&mut FieldNames(&mut fields, attrs.metadata().name()));
+    Id::from_u64(self.next_id.fetch_add(1, Ordering::Relaxed) + 1)
+  }
+
+  fn record(&self, span: &Id, values: &Record<'_>) {
+    let name = self.spans.lock().unwrap()[span.into_u64() as usize - 1];
+    values.record(&mut FieldNames(&mut self.fields.lock().unwrap(), name));
+  }
+
+  fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+  fn event(&self, event: &Event<'_>) {
+    event.record(&mut Message(&mut self.messages.lock().unwrap()));
+  }
+
+  fn enter(&self, _: &Id) {}
+
+  fn exit(&self, _: &Id) {}
+}
+
+fn collect(optimize: impl FnOnce()) -> Arc<Collector> {
+  let collector = Arc::new(Collector::default());
+  tracing::subscriber::with_default(collector.clone(), optimize);
+  collector
+}
+
+fn assert_phases(collector: &Collector, generations: usize) {
+  let spans = collector.spans.lock().unwrap();
+  let count = |name| spans.iter().filter(|&&s| s == name).count();
+  assert_eq!(count("generation"), generations + 1);
+  assert_eq!(count("terminate"), generations + 1);
+  assert_eq!(count("test"), generations + 1);
+  for phase in [
+    "select",
+    "recombine",
+    "mutate",
+    "repair",
+    "dedup",
+    "truncate",
+    "constrain",
+  ] {
+    assert_eq!(count(phase), generations, "{phase} spans");
+  }
+
+  let fields = collector.fields.lock().unwrap();
+  for field in [
+    "generation.generation",
+    "terminate.terminated",
+    "select.selected",
+    "recombine.parents",
+    "recombine.offspring",
+    "mutate.offspring",
+    "repair.offspring",
+    "dedup.removed",
+    "test.population_size",
+    "truncate.survived",
+    "truncate.elapsed_us",
+    "constrain.population_size",
+  ] {
+    assert!(fields.contains(field), "{field} is not recorded");
+  }
+
+  let messages = collector.messages.lock().unwrap();
+  assert_eq!(messages.len(), generations);
+  assert!(messages.iter().all(|m| m == "generation completed"));
+}
+
+#[test]
+fn test_nsga2_tracing() {
+  let collector = collect(|| {
+    Nsga2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .tester(|x: &f32| [x.powf(2.0), (x - 2.0).powf(2.0)])
+      .selector(RandomSelector(4))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .repairer(|x: &mut f32| *x = x.clamp(0.0, 9.0))
+      .dedup(Dedup::By(|x: &f32| x.to_bits()))
+      .constraint(|x: &f32| [x - 5.0])
+      .terminator(GenerationTerminator(3))
+      .build()
+      .optimize();
+  });
+  assert_phases(&collector, 3);
+}
+
+#[test]
+fn test_spea2_tracing() {
+  let collector = collect(|| {
+    Spea2::builder()
+      .population((0..10).map(|i| i as f32).collect())
+      .archive_size(10)
+      .tester(|x: &f32| [x.powf(2.0), (x - 2.0).powf(2.0)])
+      .selector(RandomSelector(4))
+      .recombinator(|x: &f32, y: &f32| (x + y) / 2.0)
+      .mutator(|_: &mut f32| {})
+      .repairer(|x: &mut f32| *x = x.clamp(0.0, 9.0))
+      .dedup(Dedup::By(|x: &f32| x.to_bits()))
+      .constraint(|x: &f32| [x - 5.0])
+      .terminator(GenerationTerminator(3))
+      .build()
+      .optimize();
+  });
+  assert_phases(&collector, 3);
+}